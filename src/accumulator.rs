@@ -0,0 +1,352 @@
+//! Bilinear accumulator (Nguyen) over the scalar field with membership and non-membership
+//! witnesses. The accumulator of a set `Y` is `prod_(y in Y) (y + alpha) * P` where `alpha` is the
+//! manager's secret key and `P` the G1 generator of the group parameters. Witnesses are updated
+//! by their holders after a batch of additions or removals using a short update published by the
+//! manager (Vitto, Biryukov), without learning the other members.
+
+use crate::curve_order_elem::CurveOrderElement;
+use crate::errors::ValueError;
+use crate::extension_field_gt::GT;
+use crate::group_elem::{GroupElement, GroupElementVector};
+use crate::group_elem_g1::{G1Vector, G1};
+use crate::group_elem_g2::G2;
+use crate::group_params::GroupParams;
+use crate::univar_poly::UnivarPolynomial;
+
+use rand::{CryptoRng, RngCore};
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct AccumulatorSecretKey(CurveOrderElement);
+
+/// `alpha * P~` where `P~` is the G2 generator of the group parameters
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct AccumulatorPublicKey(G2);
+
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Accumulator(G1);
+
+/// `C = V / (y + alpha)` for a member `y` of the accumulator `V`
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct MembershipWitness(G1);
+
+/// `(C, d)` with `(y + alpha) * C + d * P = V` and `d != 0` for a non-member `y` of the
+/// accumulator `V`
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct NonMembershipWitness {
+    c: G1,
+    d: CurveOrderElement,
+}
+
+/// Published by the manager after a batch change, `[V, alpha*V, ... alpha^(m-1)*V]` for a batch
+/// of `m` elements where `V` is the accumulator before additions or after removals
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct WitnessUpdate(G1Vector);
+
+impl AccumulatorSecretKey {
+    pub fn random() -> Self {
+        Self(CurveOrderElement::random())
+    }
+
+    pub fn random_using_rng<R: RngCore + CryptoRng>(rng: &mut R) -> Self {
+        Self(CurveOrderElement::random_using_rng(rng))
+    }
+}
+
+impl AccumulatorPublicKey {
+    pub fn new(sk: &AccumulatorSecretKey, params: &GroupParams) -> Self {
+        Self(params.g2() * &sk.0)
+    }
+}
+
+impl Accumulator {
+    /// Accumulator of the empty set
+    pub fn new(params: &GroupParams) -> Self {
+        Self(params.g1().clone())
+    }
+
+    pub fn value(&self) -> &G1 {
+        &self.0
+    }
+
+    pub fn add(&mut self, y: &CurveOrderElement, sk: &AccumulatorSecretKey) {
+        self.0 = &self.0 * &(y + &sk.0);
+    }
+
+    pub fn remove(&mut self, y: &CurveOrderElement, sk: &AccumulatorSecretKey) {
+        self.0 = &self.0 * &(y + &sk.0).inverse_const_time();
+    }
+
+    /// Add all of `ys` and return the update for holders of existing witnesses
+    pub fn add_batch(&mut self, ys: &[CurveOrderElement], sk: &AccumulatorSecretKey) -> WitnessUpdate {
+        let update = WitnessUpdate::new(&self.0, ys.len(), sk);
+        let f = ys
+            .iter()
+            .fold(CurveOrderElement::one(), |acc, y| acc * (y + &sk.0));
+        self.0 = &self.0 * &f;
+        update
+    }
+
+    /// Remove all of `ys` and return the update for holders of remaining witnesses
+    pub fn remove_batch(&mut self, ys: &[CurveOrderElement], sk: &AccumulatorSecretKey) -> WitnessUpdate {
+        let f = ys
+            .iter()
+            .fold(CurveOrderElement::one(), |acc, y| acc * (y + &sk.0));
+        self.0 = &self.0 * &f.inverse_const_time();
+        WitnessUpdate::new(&self.0, ys.len(), sk)
+    }
+
+    /// Witness for `y`, which the caller must have added
+    pub fn membership_witness(&self, y: &CurveOrderElement, sk: &AccumulatorSecretKey) -> MembershipWitness {
+        MembershipWitness(&self.0 * &(y + &sk.0).inverse_const_time())
+    }
+
+    /// Witness for `y` not being in `members`, which must be all current members. Fails if `y` is
+    /// a member.
+    pub fn non_membership_witness(
+        &self,
+        y: &CurveOrderElement,
+        members: &[CurveOrderElement],
+        sk: &AccumulatorSecretKey,
+        params: &GroupParams,
+    ) -> Result<NonMembershipWitness, ValueError> {
+        // d = f(-y) where f(x) = prod (y_i + x), the remainder of dividing f by (x + y)
+        let d = members
+            .iter()
+            .fold(CurveOrderElement::one(), |acc, m| acc * (m - y));
+        if d.is_zero() {
+            return Err(ValueError::ZeroElement);
+        }
+        let c = (&self.0 - &(params.g1() * &d)) * (y + &sk.0).inverse_const_time();
+        Ok(NonMembershipWitness { c, d })
+    }
+
+    /// Check `e(C, y*P~ + alpha*P~) == e(V, P~)`
+    pub fn verify_membership(
+        &self,
+        y: &CurveOrderElement,
+        witness: &MembershipWitness,
+        pk: &AccumulatorPublicKey,
+        params: &GroupParams,
+    ) -> bool {
+        let g2 = params.g2().binary_scalar_mul(&pk.0, y, &CurveOrderElement::one());
+        GT::ate_2_pairing(&witness.0, &g2, &self.0.negation(), params.g2()).is_one()
+    }
+
+    /// Check `e(C, y*P~ + alpha*P~) * e(d*P, P~) == e(V, P~)` and `d != 0`
+    pub fn verify_non_membership(
+        &self,
+        y: &CurveOrderElement,
+        witness: &NonMembershipWitness,
+        pk: &AccumulatorPublicKey,
+        params: &GroupParams,
+    ) -> bool {
+        if witness.d.is_zero() {
+            return false;
+        }
+        let g2 = params.g2().binary_scalar_mul(&pk.0, y, &CurveOrderElement::one());
+        let g1 = params.g1() * &witness.d - &self.0;
+        GT::ate_2_pairing(&witness.c, &g2, &g1, params.g2()).is_one()
+    }
+}
+
+impl WitnessUpdate {
+    fn new(v: &G1, m: usize, sk: &AccumulatorSecretKey) -> Self {
+        let mut omega = G1Vector::with_capacity(m);
+        let mut p = v.clone();
+        for _ in 0..m {
+            omega.push(p.clone());
+            p = &p * &sk.0;
+        }
+        Self(omega)
+    }
+
+    /// For `g(x) = prod (b_i + x)` over the batch `bs`, returns `g(-y)` and
+    /// `q(alpha) * V` where `q = (g(x) - g(-y)) / (x + y)`
+    fn apply(&self, y: &CurveOrderElement, bs: &[CurveOrderElement]) -> Result<(CurveOrderElement, G1), ValueError> {
+        check_vector_size_for_equality!(self.0, bs)?;
+        let roots: Vec<_> = bs.iter().map(|b| b.negation()).collect();
+        let g = UnivarPolynomial::new_with_roots(&roots);
+        let (q, g_y) = g.divide_by_linear(&y.negation());
+        let q_v = self.0.multi_scalar_mul_var_time(q.coefficients().iter())?;
+        Ok((g_y, q_v))
+    }
+}
+
+impl MembershipWitness {
+    pub fn as_point(&self) -> &G1 {
+        &self.0
+    }
+
+    /// Witness of `y` after the manager added `additions` in one batch with `update`,
+    /// `C' = g(-y) * C + q(alpha) * V`
+    pub fn update_on_additions(
+        &self,
+        y: &CurveOrderElement,
+        additions: &[CurveOrderElement],
+        update: &WitnessUpdate,
+    ) -> Result<Self, ValueError> {
+        let (g_y, q_v) = update.apply(y, additions)?;
+        Ok(Self(&self.0 * &g_y + q_v))
+    }
+
+    /// Witness of `y` after the manager removed `removals` in one batch with `update`,
+    /// `C' = (C - q(alpha) * V') / g(-y)`. Fails if `y` was removed.
+    pub fn update_on_removals(
+        &self,
+        y: &CurveOrderElement,
+        removals: &[CurveOrderElement],
+        update: &WitnessUpdate,
+    ) -> Result<Self, ValueError> {
+        let (g_y, q_v) = update.apply(y, removals)?;
+        if g_y.is_zero() {
+            return Err(ValueError::ZeroElement);
+        }
+        Ok(Self((&self.0 - &q_v) * g_y.inverse_const_time()))
+    }
+}
+
+impl NonMembershipWitness {
+    pub fn c(&self) -> &G1 {
+        &self.c
+    }
+
+    pub fn d(&self) -> &CurveOrderElement {
+        &self.d
+    }
+
+    /// Witness of `y` after the manager added `additions` in one batch with `update`,
+    /// `C' = g(-y) * C + q(alpha) * V` and `d' = g(-y) * d`. Fails if `y` was added.
+    pub fn update_on_additions(
+        &self,
+        y: &CurveOrderElement,
+        additions: &[CurveOrderElement],
+        update: &WitnessUpdate,
+    ) -> Result<Self, ValueError> {
+        let (g_y, q_v) = update.apply(y, additions)?;
+        if g_y.is_zero() {
+            return Err(ValueError::ZeroElement);
+        }
+        Ok(Self {
+            c: &self.c * &g_y + q_v,
+            d: &self.d * &g_y,
+        })
+    }
+
+    /// Witness of `y` after the manager removed `removals` in one batch with `update`,
+    /// `C' = (C - q(alpha) * V') / g(-y)` and `d' = d / g(-y)`
+    pub fn update_on_removals(
+        &self,
+        y: &CurveOrderElement,
+        removals: &[CurveOrderElement],
+        update: &WitnessUpdate,
+    ) -> Result<Self, ValueError> {
+        let (g_y, q_v) = update.apply(y, removals)?;
+        if g_y.is_zero() {
+            return Err(ValueError::ZeroElement);
+        }
+        let g_y_inv = g_y.inverse_const_time();
+        Ok(Self {
+            c: (&self.c - &q_v) * &g_y_inv,
+            d: &self.d * &g_y_inv,
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_membership() {
+        let params = GroupParams::default();
+        let sk = AccumulatorSecretKey::random();
+        let pk = AccumulatorPublicKey::new(&sk, &params);
+        let mut acc = Accumulator::new(&params);
+        let members: Vec<_> = (0..5).map(|_| CurveOrderElement::random()).collect();
+        for m in &members {
+            acc.add(m, &sk);
+        }
+        for m in &members {
+            let w = acc.membership_witness(m, &sk);
+            assert!(acc.verify_membership(m, &w, &pk, &params));
+        }
+        let w = acc.membership_witness(&members[0], &sk);
+        assert!(!acc.verify_membership(&members[1], &w, &pk, &params));
+
+        acc.remove(&members[0], &sk);
+        assert!(!acc.verify_membership(&members[0], &w, &pk, &params));
+        let w = acc.membership_witness(&members[1], &sk);
+        assert!(acc.verify_membership(&members[1], &w, &pk, &params));
+    }
+
+    #[test]
+    fn test_non_membership() {
+        let params = GroupParams::default();
+        let sk = AccumulatorSecretKey::random();
+        let pk = AccumulatorPublicKey::new(&sk, &params);
+        let mut acc = Accumulator::new(&params);
+        let members: Vec<_> = (0..5).map(|_| CurveOrderElement::random()).collect();
+        acc.add_batch(&members, &sk);
+
+        let y = CurveOrderElement::random();
+        let w = acc.non_membership_witness(&y, &members, &sk, &params).unwrap();
+        assert!(acc.verify_non_membership(&y, &w, &pk, &params));
+        assert!(!acc.verify_non_membership(&members[0], &w, &pk, &params));
+        assert!(acc
+            .non_membership_witness(&members[2], &members, &sk, &params)
+            .is_err());
+    }
+
+    #[test]
+    fn test_batch_witness_update() {
+        let params = GroupParams::default();
+        let sk = AccumulatorSecretKey::random();
+        let pk = AccumulatorPublicKey::new(&sk, &params);
+        let mut acc = Accumulator::new(&params);
+        let members: Vec<_> = (0..4).map(|_| CurveOrderElement::random()).collect();
+        acc.add_batch(&members, &sk);
+
+        let y = members[0].clone();
+        let non_member = CurveOrderElement::random();
+        let w = acc.membership_witness(&y, &sk);
+        let nw = acc
+            .non_membership_witness(&non_member, &members, &sk, &params)
+            .unwrap();
+
+        let additions: Vec<_> = (0..6).map(|_| CurveOrderElement::random()).collect();
+        let update = acc.add_batch(&additions, &sk);
+        assert!(!acc.verify_membership(&y, &w, &pk, &params));
+        let w = w.update_on_additions(&y, &additions, &update).unwrap();
+        assert!(acc.verify_membership(&y, &w, &pk, &params));
+        assert_eq!(w, acc.membership_witness(&y, &sk));
+        let nw = nw
+            .update_on_additions(&non_member, &additions, &update)
+            .unwrap();
+        assert!(acc.verify_non_membership(&non_member, &nw, &pk, &params));
+
+        let removals = vec![members[1].clone(), additions[2].clone(), additions[4].clone()];
+        let update = acc.remove_batch(&removals, &sk);
+        assert!(!acc.verify_membership(&y, &w, &pk, &params));
+        let new_w = w.update_on_removals(&y, &removals, &update).unwrap();
+        assert!(acc.verify_membership(&y, &new_w, &pk, &params));
+        let nw = nw
+            .update_on_removals(&non_member, &removals, &update)
+            .unwrap();
+        assert!(acc.verify_non_membership(&non_member, &nw, &pk, &params));
+
+        // A removed member can't update its witness and the update must match the batch
+        let w1 = acc.membership_witness(&additions[0], &sk);
+        let removals = vec![additions[0].clone()];
+        let update = acc.remove_batch(&removals, &sk);
+        assert!(w1.update_on_removals(&additions[0], &removals, &update).is_err());
+        assert!(new_w.update_on_removals(&y, &additions, &update).is_err());
+    }
+}
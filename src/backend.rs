@@ -0,0 +1,188 @@
+//! The only place where the library doing the actual arithmetic is referenced. The rest of the crate
+//! uses the re-exports and the `Backend`, `Group` and `MapToPoint` traits from here so that the
+//! wrapper types (`G1`, `G2`, `GT`, `CurveOrderElement`) do not depend on a particular library. Supporting another library like
+//! miracl_core means providing the same re-exports and implementations of these traits and
+//! pointing `CurrentBackend` at it; the public API does not change.
+
+pub use crate::ECCurve::big::{BASEBITS, BIG, MODBYTES, NLEN};
+pub use crate::ECCurve::dbig::DBIG;
+pub use crate::ECCurve::ecp::ECP;
+pub use crate::ECCurve::fp::FP;
+pub use crate::ECCurve::rom;
+pub use amcl::arch::Chunk;
+pub use amcl::rand::RAND;
+
+#[cfg(any(feature = "bls381", feature = "bn254"))]
+pub use crate::ECCurve::ecp2::ECP2;
+#[cfg(any(feature = "bls381", feature = "bn254"))]
+pub use crate::ECCurve::fp12::{DENSE, FP12};
+#[cfg(any(feature = "bls381", feature = "bn254"))]
+pub use crate::ECCurve::fp2::FP2;
+#[cfg(any(feature = "bls381", feature = "bn254"))]
+pub use crate::ECCurve::fp4::FP4;
+
+#[cfg(any(feature = "bls381", feature = "bn254"))]
+use crate::ECCurve::pair;
+
+/// Operations that are not methods on the library's types. Pairing arguments are never the identity,
+/// callers handle that case.
+pub trait Backend {
+    /// RNG seeded with the given entropy
+    fn seeded_rng(entropy: &[u8]) -> RAND;
+
+    /// Reduced pairing `e(p, q)`
+    #[cfg(any(feature = "bls381", feature = "bn254"))]
+    fn pairing(p: &ECP, q: &ECP2) -> FP12;
+
+    /// Reduced pairing product `e(p1, q1) * e(p2, q2)`
+    #[cfg(any(feature = "bls381", feature = "bn254"))]
+    fn double_pairing(p1: &ECP, q1: &ECP2, p2: &ECP, q2: &ECP2) -> FP12;
+
+    /// Reduced pairing product `e(p1, q1) * e(p2, q2) * ...`. Shares the Miller loop and the final
+    /// exponentiation between all pairs.
+    #[cfg(any(feature = "bls381", feature = "bn254"))]
+    fn multi_pairing<I: Iterator<Item = (ECP, ECP2)>>(pairs: I) -> FP12;
+
+    /// Shared Miller loop of `multi_pairing` without the final exponentiation. Products of its
+    /// outputs give the reduced pairing product of all their pairs after one `final_exp`.
+    #[cfg(any(feature = "bls381", feature = "bn254"))]
+    fn miller_loop<I: Iterator<Item = (ECP, ECP2)>>(pairs: I) -> FP12;
+
+    /// Final exponentiation of the output of `miller_loop`
+    #[cfg(any(feature = "bls381", feature = "bn254"))]
+    fn final_exp(f: &FP12) -> FP12;
+
+    /// Compressed encoding of a G1 point, a byte with the parity of `y` followed by `x`
+    fn g1_to_compressed_bytes(p: &ECP, out: &mut [u8]);
+
+    /// `k*p + l*q`, faster than the two scalar multiplications
+    fn g1_mul2(p: &ECP, k: &BIG, q: &ECP, l: &BIG) -> ECP;
+}
+
+pub struct Amcl;
+
+impl Backend for Amcl {
+    fn seeded_rng(entropy: &[u8]) -> RAND {
+        let mut r = RAND::new();
+        r.clean();
+        r.seed(entropy.len(), entropy);
+        r
+    }
+
+    #[cfg(any(feature = "bls381", feature = "bn254"))]
+    fn pairing(p: &ECP, q: &ECP2) -> FP12 {
+        pair::fexp(&pair::ate(q, p))
+    }
+
+    #[cfg(any(feature = "bls381", feature = "bn254"))]
+    fn double_pairing(p1: &ECP, q1: &ECP2, p2: &ECP, q2: &ECP2) -> FP12 {
+        pair::fexp(&pair::ate2(q1, p1, q2, p2))
+    }
+
+    #[cfg(any(feature = "bls381", feature = "bn254"))]
+    fn multi_pairing<I: Iterator<Item = (ECP, ECP2)>>(pairs: I) -> FP12 {
+        Self::final_exp(&Self::miller_loop(pairs))
+    }
+
+    #[cfg(any(feature = "bls381", feature = "bn254"))]
+    fn miller_loop<I: Iterator<Item = (ECP, ECP2)>>(pairs: I) -> FP12 {
+        let mut accum = pair::initmp();
+        for (p, q) in pairs {
+            pair::another(&mut accum, &q, &p);
+        }
+        pair::miller(&accum)
+    }
+
+    #[cfg(any(feature = "bls381", feature = "bn254"))]
+    fn final_exp(f: &FP12) -> FP12 {
+        pair::fexp(f)
+    }
+
+    fn g1_to_compressed_bytes(p: &ECP, out: &mut [u8]) {
+        p.tobytes(out, true)
+    }
+
+    fn g1_mul2(p: &ECP, k: &BIG, q: &ECP, l: &BIG) -> ECP {
+        p.mul2(k, q, l)
+    }
+}
+
+/// Encoding, scalar multiplication and hashing of the library's point and `GT` types, the
+/// operations on them whose names or signatures differ between libraries
+pub trait Group: Sized {
+    /// Decode the uncompressed encoding written by `to_bytes`
+    fn from_bytes(bytes: &[u8]) -> Self;
+
+    /// Uncompressed encoding
+    fn to_bytes(&self, out: &mut [u8]);
+
+    /// `k*self`, or `self^k` in `GT`. Constant time.
+    fn scalar_mul(&self, k: &BIG) -> Self;
+}
+
+/// Point types with the library's (non standard) map of a hash to a point
+pub trait MapToPoint {
+    /// Point of the hash `h` of a message
+    fn map_to_point(h: &[u8]) -> Self;
+}
+
+impl Group for ECP {
+    fn from_bytes(bytes: &[u8]) -> Self {
+        ECP::frombytes(bytes)
+    }
+
+    fn to_bytes(&self, out: &mut [u8]) {
+        self.tobytes(out, false)
+    }
+
+    fn scalar_mul(&self, k: &BIG) -> Self {
+        self.mul(k)
+    }
+}
+
+impl MapToPoint for ECP {
+    fn map_to_point(h: &[u8]) -> Self {
+        ECP::mapit(h)
+    }
+}
+
+#[cfg(any(feature = "bls381", feature = "bn254"))]
+impl Group for ECP2 {
+    fn from_bytes(bytes: &[u8]) -> Self {
+        ECP2::frombytes(bytes)
+    }
+
+    fn to_bytes(&self, out: &mut [u8]) {
+        self.tobytes(out)
+    }
+
+    fn scalar_mul(&self, k: &BIG) -> Self {
+        self.mul(k)
+    }
+}
+
+#[cfg(any(feature = "bls381", feature = "bn254"))]
+impl MapToPoint for ECP2 {
+    fn map_to_point(h: &[u8]) -> Self {
+        ECP2::mapit(h)
+    }
+}
+
+#[cfg(any(feature = "bls381", feature = "bn254"))]
+impl Group for FP12 {
+    fn from_bytes(bytes: &[u8]) -> Self {
+        FP12::frombytes(bytes)
+    }
+
+    fn to_bytes(&self, out: &mut [u8]) {
+        let mut temp = *self;
+        temp.tobytes(out)
+    }
+
+    fn scalar_mul(&self, k: &BIG) -> Self {
+        self.pow(k)
+    }
+}
+
+/// Backend the crate is built against
+pub type CurrentBackend = Amcl;
@@ -0,0 +1,298 @@
+//! Threshold randomness beacon in the style of drand. A group of `total` members holds Shamir
+//! shares of a BLS secret key and any `threshold` of them produce the beacon of a round: each
+//! member signs the round message with its share and the partial signatures are combined by
+//! Lagrange interpolation in the exponent into the unique BLS signature under the group key.
+//! The randomness of the round is `SHA256(signature)`.
+//!
+//! In chained mode the message of a round is `SHA256(previous signature || round)` with the round
+//! as 8 big endian bytes, so every beacon commits to the whole chain before it. Without a previous
+//! signature (the first round or unchained mode) the message is `SHA256(round)`. Messages are
+//! signed in the basic scheme of the `bls` module, so beacons are not interoperable with drand,
+//! which uses its own domain separation tags.
+
+use crate::bls::{PublicKey, Scheme, SecretKey, Signature, SIGNATURE_SIZE};
+use crate::curve_order_elem::CurveOrderElement;
+use crate::errors::{CryptoError, ValueError};
+use crate::group_elem_g1::G1;
+use crate::secret_sharing::{reconstruct_g1, share_secret, Share};
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::HashSet;
+
+/// Public information of the group: the threshold, the group public key and the public key of
+/// each member's share, member `i` having id `i + 1`
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Group {
+    threshold: usize,
+    public_key: PublicKey,
+    member_keys: Vec<PublicKey>,
+}
+
+/// A member's share of the group secret key
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct KeyShare {
+    id: usize,
+    sk: SecretKey,
+}
+
+/// Signature of a member on the message of a round
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct PartialBeacon {
+    pub round: u64,
+    pub share: Share<G1>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Beacon {
+    pub round: u64,
+    pub previous_signature: Option<Signature>,
+    pub signature: Signature,
+}
+
+/// Message signed for `round`, chained to `previous_signature` if given
+pub fn round_message(round: u64, previous_signature: Option<&Signature>) -> Vec<u8> {
+    let mut hasher = Sha256::new();
+    if let Some(prev) = previous_signature {
+        let bytes: [u8; SIGNATURE_SIZE] = prev.clone().into();
+        hasher.input(&bytes[..]);
+    }
+    hasher.input(round.to_be_bytes());
+    hasher.result().to_vec()
+}
+
+/// Trusted dealer: generate a group key and split it into `total` shares, any `threshold` of which
+/// produce beacons. Groups without a trusted dealer run a distributed key generation instead and
+/// build the `Group` with `Group::new`.
+pub fn deal(threshold: usize, total: usize) -> Result<(Group, Vec<KeyShare>), ValueError> {
+    let secret = CurveOrderElement::random();
+    let (shares, _) = share_secret(&secret, threshold, total)?;
+    let key_shares: Vec<_> = shares
+        .into_iter()
+        .map(|s| KeyShare {
+            id: s.id,
+            sk: SecretKey::from(s.value),
+        })
+        .collect();
+    let group = Group {
+        threshold,
+        public_key: PublicKey::new(&SecretKey::from(secret)),
+        member_keys: key_shares.iter().map(|s| PublicKey::new(&s.sk)).collect(),
+    };
+    Ok((group, key_shares))
+}
+
+impl Group {
+    pub fn new(
+        threshold: usize,
+        public_key: PublicKey,
+        member_keys: Vec<PublicKey>,
+    ) -> Result<Self, ValueError> {
+        if threshold == 0 {
+            return Err(ValueError::OutOfRange(threshold));
+        }
+        if threshold > member_keys.len() {
+            return Err(ValueError::UnequalSizeVectors(threshold, member_keys.len()));
+        }
+        Ok(Self {
+            threshold,
+            public_key,
+            member_keys,
+        })
+    }
+
+    pub fn threshold(&self) -> usize {
+        self.threshold
+    }
+
+    pub fn public_key(&self) -> &PublicKey {
+        &self.public_key
+    }
+
+    /// Public key of the share of the member with id `id`
+    pub fn member_key(&self, id: usize) -> Option<&PublicKey> {
+        if id == 0 {
+            return None;
+        }
+        self.member_keys.get(id - 1)
+    }
+
+    /// Check a member's signature on the message of its round
+    pub fn verify_partial(
+        &self,
+        partial: &PartialBeacon,
+        previous_signature: Option<&Signature>,
+    ) -> bool {
+        let pk = match self.member_key(partial.share.id) {
+            Some(pk) => pk,
+            None => return false,
+        };
+        let msg = round_message(partial.round, previous_signature);
+        let sig = Signature::from_point(partial.share.value.clone());
+        pk.verify_with_scheme(&msg, &sig, Scheme::Basic)
+    }
+
+    /// Combine partial signatures for `round` into its beacon. Invalid partials, partials of other
+    /// rounds and repeated members are skipped; fails with `CryptoError::ThresholdNotMet` when
+    /// fewer than threshold many remain.
+    pub fn aggregate(
+        &self,
+        round: u64,
+        previous_signature: Option<&Signature>,
+        partials: &[PartialBeacon],
+    ) -> Result<Beacon, CryptoError> {
+        let mut seen = HashSet::new();
+        let shares: Vec<Share<G1>> = partials
+            .iter()
+            .filter(|p| p.round == round && self.verify_partial(p, previous_signature))
+            .filter(|p| seen.insert(p.share.id))
+            .take(self.threshold)
+            .map(|p| p.share.clone())
+            .collect();
+        if shares.len() < self.threshold {
+            return Err(CryptoError::ThresholdNotMet(self.threshold, shares.len()));
+        }
+        Ok(Beacon {
+            round,
+            previous_signature: previous_signature.cloned(),
+            signature: Signature::from_point(reconstruct_g1(&shares)?),
+        })
+    }
+
+    /// Check the group's signature on the message of the beacon's round
+    pub fn verify(&self, beacon: &Beacon) -> bool {
+        let msg = round_message(beacon.round, beacon.previous_signature.as_ref());
+        self.public_key
+            .verify_with_scheme(&msg, &beacon.signature, Scheme::Basic)
+    }
+
+    /// Check successive beacons of a chain: each is valid, rounds increase by one and each
+    /// beacon is chained to the signature of the one before it. The first beacon is only checked
+    /// on its own.
+    pub fn verify_chain(&self, beacons: &[Beacon]) -> bool {
+        if !beacons.iter().all(|b| self.verify(b)) {
+            return false;
+        }
+        beacons.windows(2).all(|w| {
+            w[0].round.checked_add(1) == Some(w[1].round)
+                && w[1].previous_signature.as_ref() == Some(&w[0].signature)
+        })
+    }
+}
+
+impl KeyShare {
+    pub fn id(&self) -> usize {
+        self.id
+    }
+
+    /// Sign the message of `round`, chained to `previous_signature` if given
+    pub fn partial_sign(
+        &self,
+        round: u64,
+        previous_signature: Option<&Signature>,
+    ) -> PartialBeacon {
+        let msg = round_message(round, previous_signature);
+        PartialBeacon {
+            round,
+            share: Share {
+                id: self.id,
+                value: self
+                    .sk
+                    .sign_with_scheme(&msg, Scheme::Basic)
+                    .as_point()
+                    .clone(),
+            },
+        }
+    }
+}
+
+impl Beacon {
+    /// `SHA256(signature)`
+    pub fn randomness(&self) -> [u8; 32] {
+        let bytes: [u8; SIGNATURE_SIZE] = self.signature.clone().into();
+        let mut out = [0u8; 32];
+        out.copy_from_slice(Sha256::digest(&bytes[..]).as_slice());
+        out
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::group_elem::GroupElement;
+
+    #[test]
+    fn test_beacon_chain() {
+        let (group, shares) = deal(3, 5).unwrap();
+        let mut chain: Vec<Beacon> = vec![];
+        for round in 1..=4u64 {
+            let prev = chain.last().map(|b| b.signature.clone());
+            // Different subsets of members produce the same beacon
+            let partials: Vec<_> = shares
+                .iter()
+                .map(|s| s.partial_sign(round, prev.as_ref()))
+                .collect();
+            for p in &partials {
+                assert!(group.verify_partial(p, prev.as_ref()));
+            }
+            let beacon = group
+                .aggregate(round, prev.as_ref(), &partials[..3])
+                .unwrap();
+            let other = group
+                .aggregate(round, prev.as_ref(), &partials[2..])
+                .unwrap();
+            assert_eq!(beacon, other);
+            assert_eq!(beacon.randomness(), other.randomness());
+            assert!(group.verify(&beacon));
+            chain.push(beacon);
+        }
+        assert!(group.verify_chain(&chain));
+        assert_ne!(chain[0].randomness(), chain[1].randomness());
+
+        // Reordered or skipped rounds
+        let mut reordered = chain.clone();
+        reordered.swap(1, 2);
+        assert!(!group.verify_chain(&reordered));
+        assert!(!group.verify_chain(&[chain[0].clone(), chain[2].clone()]));
+
+        // Beacon signed unchained does not verify as chained
+        let mut unchained = chain[1].clone();
+        unchained.previous_signature = None;
+        assert!(!group.verify(&unchained));
+    }
+
+    #[test]
+    fn test_aggregate_rejects_bad_partials() {
+        let (group, shares) = deal(2, 4).unwrap();
+        let round = 7;
+        let mut partials: Vec<_> = shares.iter().map(|s| s.partial_sign(round, None)).collect();
+
+        // A bad partial, a repeated member and a partial of another round are skipped
+        partials[0].share.value = G1::random();
+        partials[2] = partials[1].clone();
+        partials[3] = shares[3].partial_sign(round + 1, None);
+        assert!(!group.verify_partial(&partials[0], None));
+        assert!(matches!(
+            group.aggregate(round, None, &partials),
+            Err(CryptoError::ThresholdNotMet(2, 1))
+        ));
+
+        partials.push(shares[3].partial_sign(round, None));
+        let beacon = group.aggregate(round, None, &partials).unwrap();
+        assert!(group.verify(&beacon));
+        assert!(group.verify_chain(&[beacon]));
+
+        // Partial from an unknown member
+        let outsider = KeyShare {
+            id: 9,
+            sk: SecretKey::random(),
+        };
+        assert!(!group.verify_partial(&outsider.partial_sign(round, None), None));
+        assert!(deal(5, 4).is_err());
+    }
+}
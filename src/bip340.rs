@@ -0,0 +1,445 @@
+//! BIP340 Schnorr signatures over secp256k1. Public keys are the 32 byte x coordinate of a point with
+//! even y, signatures are `x(R) || s` and all hashes are the BIP340 tagged SHA-256 hashes, so keys and
+//! signatures are interchangeable with Bitcoin's.
+
+use crate::constants::FIELD_ORDER_ELEMENT_SIZE;
+use crate::curve_order_elem::CurveOrderElement;
+use crate::group_elem::GroupElement;
+use crate::group_elem_g1::G1;
+use crate::types::GroupG1;
+
+use rand::{CryptoRng, RngCore};
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use signature::{Error as SignatureError, SignatureEncoding, Signer, Verifier};
+use std::convert::TryFrom;
+
+/// Byte size of a signature, x coordinate of the nonce point and a curve order element
+pub const SIGNATURE_SIZE: usize = 2 * FIELD_ORDER_ELEMENT_SIZE;
+/// Byte size of an x-only public key
+pub const PUBLIC_KEY_SIZE: usize = FIELD_ORDER_ELEMENT_SIZE;
+
+const TAG_AUX: &str = "BIP0340/aux";
+const TAG_NONCE: &str = "BIP0340/nonce";
+const TAG_CHALLENGE: &str = "BIP0340/challenge";
+const TAG_TAP_TWEAK: &str = "TapTweak";
+
+/// `SHA256(SHA256(tag) || SHA256(tag) || msg)`
+pub fn tagged_hash(tag: &str, msg: &[u8]) -> [u8; 32] {
+    let tag_hash = Sha256::digest(tag.as_bytes());
+    let mut hasher = Sha256::new();
+    hasher.input(tag_hash);
+    hasher.input(tag_hash);
+    hasher.input(msg);
+    let mut out = [0u8; 32];
+    out.copy_from_slice(&hasher.result());
+    out
+}
+
+fn has_even_y(p: &G1) -> bool {
+    p.to_bytes()[2 * FIELD_ORDER_ELEMENT_SIZE - 1] & 1 == 0
+}
+
+fn x_bytes(p: &G1) -> [u8; FIELD_ORDER_ELEMENT_SIZE] {
+    let bytes = p.to_bytes();
+    *array_ref![bytes, 0, FIELD_ORDER_ELEMENT_SIZE]
+}
+
+/// Point with the given x coordinate and even y. None if `x` is not less than the field modulus or
+/// is not the x coordinate of a point.
+fn lift_x(x: &[u8; FIELD_ORDER_ELEMENT_SIZE]) -> Option<G1> {
+    let mut bytes = [0u8; FIELD_ORDER_ELEMENT_SIZE + 1];
+    bytes[0] = 2;
+    bytes[1..].copy_from_slice(x);
+    let p: G1 = GroupG1::frombytes(&bytes).into();
+    if p.is_identity() {
+        None
+    } else {
+        Some(p)
+    }
+}
+
+fn challenge(r: &[u8], pk: &[u8], msg: &[u8]) -> CurveOrderElement {
+    let e = tagged_hash(TAG_CHALLENGE, &[r, pk, msg].concat());
+    CurveOrderElement::from(&e)
+}
+
+/// Parse a curve order element, rejecting encodings of values not less than the curve order
+fn scalar_from_canonical_bytes(bytes: &[u8]) -> Result<CurveOrderElement, SignatureError> {
+    let s = CurveOrderElement::try_from(bytes).map_err(|_| SignatureError::new())?;
+    if s.to_compressed_bytes()[..] != bytes[..] {
+        return Err(SignatureError::new());
+    }
+    Ok(s)
+}
+
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct SecretKey(CurveOrderElement);
+
+impl SecretKey {
+    pub fn random() -> Self {
+        Self(CurveOrderElement::random())
+    }
+
+    pub fn random_using_rng<R: RngCore + CryptoRng>(rng: &mut R) -> Self {
+        Self(CurveOrderElement::random_using_rng(rng))
+    }
+
+    /// Sign with fresh auxiliary randomness
+    pub fn sign(&self, msg: &[u8]) -> Signature {
+        let mut aux_rand = [0u8; 32];
+        rand::thread_rng().fill_bytes(&mut aux_rand);
+        self.sign_with_aux_rand(msg, &aux_rand)
+    }
+
+    /// The BIP340 signing algorithm. Signing is deterministic for a given `aux_rand`, which should
+    /// still be fresh randomness outside of tests.
+    pub fn sign_with_aux_rand(&self, msg: &[u8], aux_rand: &[u8; 32]) -> Signature {
+        let p = G1::generator() * &self.0;
+        let d = if has_even_y(&p) {
+            self.0.clone()
+        } else {
+            self.0.negation()
+        };
+        let pk = x_bytes(&p);
+
+        let mut t = d.to_bytes();
+        for (t_i, a_i) in t.iter_mut().zip(tagged_hash(TAG_AUX, aux_rand).iter()) {
+            *t_i ^= a_i;
+        }
+        let rand = tagged_hash(TAG_NONCE, &[&t[..], &pk, msg].concat());
+        let mut k = CurveOrderElement::from(&rand);
+        // Only happens with negligible probability
+        assert!(!k.is_zero(), "BIP340 nonce is zero");
+        let r = G1::generator() * &k;
+        if !has_even_y(&r) {
+            k.negate();
+        }
+        let r = x_bytes(&r);
+        let e = challenge(&r, &pk, msg);
+        Signature { r, s: k + &e * &d }
+    }
+
+    pub fn as_scalar(&self) -> &CurveOrderElement {
+        &self.0
+    }
+
+    pub fn to_bytes(&self) -> [u8; FIELD_ORDER_ELEMENT_SIZE] {
+        self.0.to_bytes()
+    }
+
+    /// Secret key of `XOnlyPublicKey::tweak_add` with the same tweak. The key is negated first when
+    /// its public key has odd y since the x-only public key is the point with even y.
+    pub fn tweak_add(&self, tweak: &CurveOrderElement) -> Result<Self, SignatureError> {
+        let p = G1::generator() * &self.0;
+        let d = if has_even_y(&p) {
+            self.0.clone()
+        } else {
+            self.0.negation()
+        };
+        let d = d + tweak;
+        if d.is_zero() {
+            return Err(SignatureError::new());
+        }
+        Ok(Self(d))
+    }
+
+    /// Tweak with the BIP341 Taproot tweak of the public key, see `XOnlyPublicKey::tap_tweak`
+    pub fn tap_tweak(&self, merkle_root: Option<&[u8; 32]>) -> Result<Self, SignatureError> {
+        self.tweak_add(&XOnlyPublicKey::new(self).tap_tweak_scalar(merkle_root)?)
+    }
+}
+
+impl From<CurveOrderElement> for SecretKey {
+    fn from(x: CurveOrderElement) -> Self {
+        Self(x)
+    }
+}
+
+/// Rejects zero and values not less than the curve order
+impl TryFrom<&[u8]> for SecretKey {
+    type Error = SignatureError;
+
+    fn try_from(bytes: &[u8]) -> Result<Self, SignatureError> {
+        let d = scalar_from_canonical_bytes(bytes)?;
+        if d.is_zero() {
+            return Err(SignatureError::new());
+        }
+        Ok(Self(d))
+    }
+}
+
+impl Signer<Signature> for SecretKey {
+    fn try_sign(&self, msg: &[u8]) -> Result<Signature, SignatureError> {
+        Ok(self.sign(msg))
+    }
+}
+
+/// The point with the given x coordinate and even y
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct XOnlyPublicKey(G1);
+
+impl XOnlyPublicKey {
+    pub fn new(sk: &SecretKey) -> Self {
+        Self::from_point(&(G1::generator() * &sk.0))
+    }
+
+    /// Drops the parity of y, so `p` and `-p` give the same key
+    pub fn from_point(p: &G1) -> Self {
+        if has_even_y(p) {
+            Self(p.clone())
+        } else {
+            Self(p.negation())
+        }
+    }
+
+    pub fn to_bytes(&self) -> [u8; PUBLIC_KEY_SIZE] {
+        x_bytes(&self.0)
+    }
+
+    pub fn as_point(&self) -> &G1 {
+        &self.0
+    }
+
+    /// `P + g*tweak` as an x-only key along with whether its y was odd, which a Taproot script path
+    /// spend needs to reveal. Fails if the result is the identity.
+    pub fn tweak_add(&self, tweak: &CurveOrderElement) -> Result<(Self, bool), SignatureError> {
+        let q = G1::generator().binary_scalar_mul(&self.0, tweak, &CurveOrderElement::one());
+        if q.is_identity() {
+            return Err(SignatureError::new());
+        }
+        let odd = !has_even_y(&q);
+        Ok((Self::from_point(&q), odd))
+    }
+
+    /// Check that `tweaked` with parity `odd` is the result of `tweak_add` with `tweak`
+    pub fn tweak_add_check(&self, tweaked: &Self, odd: bool, tweak: &CurveOrderElement) -> bool {
+        match self.tweak_add(tweak) {
+            Ok((q, q_odd)) => q == *tweaked && q_odd == odd,
+            Err(_) => false,
+        }
+    }
+
+    /// `int(tagged_hash("TapTweak", P || merkle_root))`, the Merkle root is omitted for keys
+    /// without a script tree. Fails if the hash is not less than the curve order.
+    pub fn tap_tweak_scalar(
+        &self,
+        merkle_root: Option<&[u8; 32]>,
+    ) -> Result<CurveOrderElement, SignatureError> {
+        let mut data = self.to_bytes().to_vec();
+        if let Some(root) = merkle_root {
+            data.extend_from_slice(root);
+        }
+        scalar_from_canonical_bytes(&tagged_hash(TAG_TAP_TWEAK, &data))
+    }
+
+    /// The BIP341 Taproot output key and its parity
+    pub fn tap_tweak(&self, merkle_root: Option<&[u8; 32]>) -> Result<(Self, bool), SignatureError> {
+        self.tweak_add(&self.tap_tweak_scalar(merkle_root)?)
+    }
+}
+
+impl From<&SecretKey> for XOnlyPublicKey {
+    fn from(sk: &SecretKey) -> Self {
+        Self::new(sk)
+    }
+}
+
+impl TryFrom<&[u8]> for XOnlyPublicKey {
+    type Error = SignatureError;
+
+    fn try_from(bytes: &[u8]) -> Result<Self, SignatureError> {
+        if bytes.len() != PUBLIC_KEY_SIZE {
+            return Err(SignatureError::new());
+        }
+        lift_x(array_ref![bytes, 0, PUBLIC_KEY_SIZE])
+            .map(Self)
+            .ok_or_else(SignatureError::new)
+    }
+}
+
+impl Verifier<Signature> for XOnlyPublicKey {
+    fn verify(&self, msg: &[u8], sig: &Signature) -> Result<(), SignatureError> {
+        let pk = self.to_bytes();
+        let e = challenge(&sig.r, &pk, msg);
+        let r = G1::generator().binary_scalar_mul(&self.0, &sig.s, &e.negation());
+        if r.is_identity() || !has_even_y(&r) || x_bytes(&r) != sig.r {
+            return Err(SignatureError::new());
+        }
+        Ok(())
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Signature {
+    r: [u8; FIELD_ORDER_ELEMENT_SIZE],
+    s: CurveOrderElement,
+}
+
+/// `r` is only checked to be a valid x coordinate during verification, as in BIP340
+impl TryFrom<&[u8]> for Signature {
+    type Error = SignatureError;
+
+    fn try_from(bytes: &[u8]) -> Result<Self, SignatureError> {
+        if bytes.len() != SIGNATURE_SIZE {
+            return Err(SignatureError::new());
+        }
+        Ok(Self {
+            r: *array_ref![bytes, 0, FIELD_ORDER_ELEMENT_SIZE],
+            s: scalar_from_canonical_bytes(&bytes[FIELD_ORDER_ELEMENT_SIZE..])?,
+        })
+    }
+}
+
+impl TryFrom<[u8; SIGNATURE_SIZE]> for Signature {
+    type Error = SignatureError;
+
+    fn try_from(bytes: [u8; SIGNATURE_SIZE]) -> Result<Self, SignatureError> {
+        Self::try_from(&bytes[..])
+    }
+}
+
+impl From<Signature> for [u8; SIGNATURE_SIZE] {
+    fn from(sig: Signature) -> Self {
+        let mut bytes = [0u8; SIGNATURE_SIZE];
+        bytes[..FIELD_ORDER_ELEMENT_SIZE].copy_from_slice(&sig.r);
+        bytes[FIELD_ORDER_ELEMENT_SIZE..].copy_from_slice(&sig.s.to_bytes());
+        bytes
+    }
+}
+
+impl SignatureEncoding for Signature {
+    type Repr = [u8; SIGNATURE_SIZE];
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use subtle_encoding::hex;
+
+    fn decode(s: &str) -> Vec<u8> {
+        hex::decode_upper(s).unwrap()
+    }
+
+    #[test]
+    fn test_vectors() {
+        // Vectors 0 and 1 from the BIP340 test vectors
+        let vectors = [
+            (
+                "0000000000000000000000000000000000000000000000000000000000000003",
+                "F9308A019258C31049344F85F89D5229B531C845836F99B08601F113BCE036F9",
+                "0000000000000000000000000000000000000000000000000000000000000000",
+                "0000000000000000000000000000000000000000000000000000000000000000",
+                "E907831F80848D1069A5371B402410364BDF1C5F8307B0084C55F1CE2DCA821525F66A4A85EA8B71E482A74F382D2CE5EBEEE8FDB2172F477DF4900D310536C0",
+            ),
+            (
+                "B7E151628AED2A6ABF7158809CF4F3C762E7160F38B4DA56A784D9045190CFEF",
+                "DFF1D77F2A671C5F36183726DB2341BE58FEAE1DA2DECED843240F7B502BA659",
+                "0000000000000000000000000000000000000000000000000000000000000001",
+                "243F6A8885A308D313198A2E03707344A4093822299F31D0082EFA98EC4E6C89",
+                "6896BD60EEAE296DB48A229FF71DFE071BDE413E6D43F917DC8DCF8C78DE33418906D11AC976ABCCB20B091292BFF4EA897EFCB639EA871CFA95F6DE339E4B0A",
+            ),
+        ];
+        for (sk, pk, aux_rand, msg, sig) in vectors.iter() {
+            let sk = SecretKey::try_from(&decode(sk)[..]).unwrap();
+            let pk = XOnlyPublicKey::try_from(&decode(pk)[..]).unwrap();
+            assert_eq!(XOnlyPublicKey::from(&sk), pk);
+            let msg = decode(msg);
+            let aux_rand = decode(aux_rand);
+            let s = sk.sign_with_aux_rand(&msg, array_ref![aux_rand, 0, 32]);
+            assert_eq!(s.to_bytes().to_vec(), decode(sig));
+            let sig = Signature::try_from(&decode(sig)[..]).unwrap();
+            assert!(pk.verify(&msg, &sig).is_ok());
+        }
+    }
+
+    #[test]
+    fn test_sign_verify() {
+        let sk = SecretKey::random();
+        let pk = XOnlyPublicKey::from(&sk);
+        let msg = b"test message";
+        let sig = sk.try_sign(msg).unwrap();
+        assert!(pk.verify(msg, &sig).is_ok());
+        assert!(pk.verify(b"another message", &sig).is_err());
+        assert!(XOnlyPublicKey::from(&SecretKey::random()).verify(msg, &sig).is_err());
+
+        // Negated secret key has the same x-only public key and its signatures verify
+        let neg_sk = SecretKey::from(sk.as_scalar().negation());
+        assert_eq!(XOnlyPublicKey::from(&neg_sk), pk);
+        assert!(pk.verify(msg, &neg_sk.sign(msg)).is_ok());
+    }
+
+    #[test]
+    fn test_tweak_add() {
+        let tweak = CurveOrderElement::random();
+        for _ in 0..10 {
+            let sk = SecretKey::random();
+            let pk = XOnlyPublicKey::from(&sk);
+            let (tweaked_pk, odd) = pk.tweak_add(&tweak).unwrap();
+            let tweaked_sk = sk.tweak_add(&tweak).unwrap();
+            assert_eq!(XOnlyPublicKey::from(&tweaked_sk), tweaked_pk);
+            assert_eq!(
+                !has_even_y(&(G1::generator() * tweaked_sk.as_scalar())),
+                odd
+            );
+            assert!(pk.tweak_add_check(&tweaked_pk, odd, &tweak));
+            assert!(!pk.tweak_add_check(&tweaked_pk, !odd, &tweak));
+            assert!(!pk.tweak_add_check(&pk, odd, &tweak));
+
+            let msg = b"test message";
+            assert!(tweaked_pk.verify(msg, &tweaked_sk.sign(msg)).is_ok());
+        }
+
+        // Tweaking by the negated secret key gives the identity. Tweaking by zero gives the secret
+        // key of the point with even y.
+        let sk = SecretKey::random();
+        let pk = XOnlyPublicKey::from(&sk);
+        let d = sk.tweak_add(&CurveOrderElement::zero()).unwrap();
+        assert!(pk.tweak_add(&d.as_scalar().negation()).is_err());
+        assert!(sk.tweak_add(&d.as_scalar().negation()).is_err());
+    }
+
+    #[test]
+    fn test_tap_tweak() {
+        // Key path only output from the BIP341 wallet test vectors
+        let pk = XOnlyPublicKey::try_from(
+            &decode("D6889CB081036E0FAEFA3A35157AD71086B123B2B144B649798B494C300A961D")[..],
+        )
+        .unwrap();
+        assert_eq!(
+            pk.tap_tweak_scalar(None).unwrap().to_bytes().to_vec(),
+            decode("B86E7BE8F39BAB32A6F2C0443ABBC210F0EDAC0E2C53D501B36B64437D9C6C70")
+        );
+        let (q, _) = pk.tap_tweak(None).unwrap();
+        assert_eq!(
+            q.to_bytes().to_vec(),
+            decode("53A1F6E454DF1AA2776A2814A721372D6258050DE330B3C6D10EE8F4E0DDA343")
+        );
+
+        let sk = SecretKey::random();
+        let root = [7u8; 32];
+        let (q, _) = XOnlyPublicKey::from(&sk).tap_tweak(Some(&root)).unwrap();
+        assert_eq!(XOnlyPublicKey::from(&sk.tap_tweak(Some(&root)).unwrap()), q);
+    }
+
+    #[test]
+    fn test_encoding() {
+        let sk = SecretKey::random();
+        let pk = XOnlyPublicKey::from(&sk);
+        let sig = sk.sign(b"test message");
+
+        let sig_bytes = sig.to_bytes();
+        assert_eq!(Signature::try_from(&sig_bytes[..]).unwrap(), sig);
+        assert!(Signature::try_from([0xffu8; SIGNATURE_SIZE]).is_err());
+
+        let pk_bytes = pk.to_bytes();
+        assert_eq!(XOnlyPublicKey::try_from(&pk_bytes[..]).unwrap(), pk);
+        // Not less than the field modulus
+        assert!(XOnlyPublicKey::try_from(&[0xffu8; PUBLIC_KEY_SIZE][..]).is_err());
+        assert!(SecretKey::try_from(&[0u8; 32][..]).is_err());
+    }
+}
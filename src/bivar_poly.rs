@@ -0,0 +1,188 @@
+use crate::curve_order_elem::{CurveOrderElement, CurveOrderElementVector};
+use crate::group_elem_g1::{G1Vector, G1};
+use crate::univar_poly::UnivarPolynomial;
+
+#[cfg(any(feature = "bls381", feature = "bn254"))]
+use crate::errors::ValueError;
+#[cfg(any(feature = "bls381", feature = "bn254"))]
+use crate::kzg::KzgParams;
+
+use rayon::prelude::*;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// Bivariate polynomial `f(x, y) = sum_i x^i * f_i(y)` represented by its rows `f_i`, univariate
+/// polynomials in `y` of the same degree. The coefficient of `x^i * y^j` is the jth coefficient of
+/// the ith row.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct BivarPolynomial(pub Vec<UnivarPolynomial>);
+
+impl BivarPolynomial {
+    /// Return a zero polynomial of degree `degree_x` in x and `degree_y` in y
+    pub fn new(degree_x: usize, degree_y: usize) -> Self {
+        Self((0..=degree_x).map(|_| UnivarPolynomial::new(degree_y)).collect())
+    }
+
+    /// Return a polynomial with randomly chosen coefficients
+    pub fn random(degree_x: usize, degree_y: usize) -> Self {
+        Self((0..=degree_x).map(|_| UnivarPolynomial::random(degree_y)).collect())
+    }
+
+    /// Return a random polynomial with `f(x, y) = f(y, x)`, as used by some DKGs so that the
+    /// shares `f(i, j)` and `f(j, i)` exchanged by parties `i` and `j` can be checked against each
+    /// other
+    pub fn random_symmetric(degree: usize) -> Self {
+        let mut poly = Self::new(degree, degree);
+        for i in 0..=degree {
+            for j in i..=degree {
+                let c = CurveOrderElement::random();
+                poly.0[j][i] = c.clone();
+                poly.0[i][j] = c;
+            }
+        }
+        poly
+    }
+
+    pub fn degree_x(&self) -> usize {
+        self.0.len() - 1
+    }
+
+    pub fn degree_y(&self) -> usize {
+        self.0[0].degree()
+    }
+
+    pub fn rows(&self) -> &[UnivarPolynomial] {
+        &self.0
+    }
+
+    /// Coefficient of `x^i * y^j`
+    pub fn coefficient(&self, i: usize, j: usize) -> &CurveOrderElement {
+        &self.0[i][j]
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.0.iter().all(|r| r.is_zero())
+    }
+
+    /// Evaluate polynomial at given `x` and `y`
+    pub fn eval(&self, x: &CurveOrderElement, y: &CurveOrderElement) -> CurveOrderElement {
+        self.eval_y(y).eval(x)
+    }
+
+    /// Partial evaluation with `x` fixed, the univariate polynomial `f(x, .)` in y
+    pub fn eval_x(&self, x: &CurveOrderElement) -> UnivarPolynomial {
+        let powers = CurveOrderElementVector::new_vandermonde_vector(x, self.0.len());
+        let coeffs: Vec<_> = (0..=self.degree_y())
+            .into_par_iter()
+            .map(|j| {
+                self.0
+                    .iter()
+                    .zip(powers.iter())
+                    .fold(CurveOrderElement::zero(), |acc, (row, p)| acc + &row[j] * p)
+            })
+            .collect();
+        UnivarPolynomial(coeffs.into())
+    }
+
+    /// Partial evaluation with `y` fixed, the univariate polynomial `f(., y)` in x
+    pub fn eval_y(&self, y: &CurveOrderElement) -> UnivarPolynomial {
+        let coeffs: Vec<_> = self.0.par_iter().map(|row| row.eval(y)).collect();
+        UnivarPolynomial(coeffs.into())
+    }
+
+    /// Feldman commitments to each coefficient, `g * f_i[j]` at position `[i][j]`
+    pub fn commit_coefficients(&self, g: &G1) -> Vec<G1Vector> {
+        self.0
+            .par_iter()
+            .map(|row| row.coefficients().iter().map(|c| g * c).collect())
+            .collect()
+    }
+
+    /// KZG commitment to each row `f_i`
+    #[cfg(any(feature = "bls381", feature = "bn254"))]
+    pub fn commit_rows(&self, params: &KzgParams) -> Result<G1Vector, ValueError> {
+        let commitments: Result<Vec<G1>, ValueError> =
+            self.0.par_iter().map(|row| params.commit(row)).collect();
+        commitments.map(G1Vector::from)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::group_elem::{GroupElement, GroupElementVector};
+
+    #[test]
+    fn test_eval() {
+        let poly = BivarPolynomial::random(3, 5);
+        assert_eq!(poly.degree_x(), 3);
+        assert_eq!(poly.degree_y(), 5);
+        assert!(!poly.is_zero());
+        assert!(BivarPolynomial::new(3, 5).is_zero());
+
+        let x = CurveOrderElement::random();
+        let y = CurveOrderElement::random();
+        let mut expected = CurveOrderElement::zero();
+        for i in 0..=3 {
+            for j in 0..=5 {
+                expected += poly.coefficient(i, j)
+                    * &x.pow(&CurveOrderElement::from(i as u64))
+                    * &y.pow(&CurveOrderElement::from(j as u64));
+            }
+        }
+        assert_eq!(poly.eval(&x, &y), expected);
+
+        let fx = poly.eval_x(&x);
+        let fy = poly.eval_y(&y);
+        assert_eq!(fx.degree(), 5);
+        assert_eq!(fy.degree(), 3);
+        assert_eq!(fx.eval(&y), expected);
+        assert_eq!(fy.eval(&x), expected);
+
+        // Constant in x
+        let poly = BivarPolynomial::random(0, 2);
+        assert_eq!(poly.eval(&x, &y), poly.rows()[0].eval(&y));
+    }
+
+    #[test]
+    fn test_symmetric() {
+        let poly = BivarPolynomial::random_symmetric(4);
+        let a = CurveOrderElement::random();
+        let b = CurveOrderElement::random();
+        assert_eq!(poly.eval(&a, &b), poly.eval(&b, &a));
+        assert_eq!(poly.eval_x(&a), poly.eval_y(&a));
+    }
+
+    #[test]
+    fn test_commit_coefficients() {
+        let poly = BivarPolynomial::random(2, 3);
+        let g = G1::generator();
+        let comms = poly.commit_coefficients(&g);
+        assert_eq!(comms.len(), 3);
+        assert_eq!(comms[1].len(), 4);
+        assert_eq!(comms[1][2], &g * poly.coefficient(1, 2));
+
+        // Commitment to f(x, .) from the coefficient commitments
+        let x = CurveOrderElement::random();
+        let fx = poly.eval_x(&x);
+        let powers = CurveOrderElementVector::new_vandermonde_vector(&x, 3);
+        for j in 0..=3 {
+            let col: G1Vector = comms.iter().map(|c| c[j].clone()).collect();
+            assert_eq!(col.multi_scalar_mul_var_time(powers.iter()).unwrap(), &g * &fx[j]);
+        }
+    }
+
+    #[cfg(any(feature = "bls381", feature = "bn254"))]
+    #[test]
+    fn test_commit_rows() {
+        use crate::group_params::GroupParams;
+
+        let params = KzgParams::new(4, &GroupParams::default());
+        let poly = BivarPolynomial::random(2, 4);
+        let comms = poly.commit_rows(&params).unwrap();
+        assert_eq!(comms.len(), 3);
+        assert_eq!(comms[2], params.commit(&poly.rows()[2]).unwrap());
+        assert!(BivarPolynomial::random(2, 5).commit_rows(&params).is_err());
+    }
+}
@@ -0,0 +1,224 @@
+//! Blind Schnorr signatures over group G1, issued in three moves and verifying as ordinary
+//! signatures of the `schnorr` module.
+//! 1. The signer sends the commitment `R' = k*g`.
+//! 2. The user picks `alpha, beta`, computes `R = R' + alpha*g + beta*pk`, `e = H(R || pk || msg)`
+//!    and sends the blinded challenge `e' = e - beta`.
+//! 3. The signer sends `s' = k - e'*sk` and the user outputs the signature `(e, s' + alpha)`.
+//!
+//! The signer learns neither the message nor the signature. Blind Schnorr signatures are only
+//! one-more unforgeable when the signer runs sessions one after another: with many concurrent
+//! sessions the ROS attack forges signatures in polynomial time. Hence a `BlindSigner` owns the
+//! secret key and lends it mutably to its one open `SequentialSignerSession`, so the next session
+//! can only start once the previous one responded or was dropped. Creating several signers from
+//! clones of the same secret key defeats this.
+
+use crate::curve_order_elem::CurveOrderElement;
+use crate::errors::ValueError;
+use crate::group_elem::GroupElement;
+use crate::group_elem_g1::G1;
+use crate::group_params::GroupParams;
+use crate::schnorr::{challenge, PublicKey, SecretKey, Signature};
+
+use signature::Error as SignatureError;
+use std::fmt;
+
+/// Issuer of blind signatures running one session at a time
+pub struct BlindSigner {
+    sk: SecretKey,
+    g: G1,
+}
+
+/// State of the signer between sending the commitment and the response, borrowing its signer
+/// mutably. Not `Clone` so the nonce can't be used twice, which would reveal the secret key.
+pub struct SequentialSignerSession<'a> {
+    k: CurveOrderElement,
+    commitment: G1,
+    signer: &'a mut BlindSigner,
+}
+
+/// State of the user between sending the blinded challenge and receiving the response
+pub struct UserSession {
+    alpha: CurveOrderElement,
+    blinded_e: CurveOrderElement,
+    e: CurveOrderElement,
+    commitment: G1,
+    pk: PublicKey,
+    g: G1,
+}
+
+impl BlindSigner {
+    /// Signer with respect to the standard generator of group G1
+    pub fn new(sk: SecretKey) -> Self {
+        Self::new_with_params(sk, &GroupParams::default())
+    }
+
+    pub fn new_with_params(sk: SecretKey, params: &GroupParams) -> Self {
+        Self {
+            sk,
+            g: params.g1().clone(),
+        }
+    }
+
+    /// Start a session, returning the commitment for the user. The signer stays borrowed until
+    /// the session responded or was dropped.
+    pub fn start(&mut self) -> (SequentialSignerSession<'_>, G1) {
+        let k = CurveOrderElement::random();
+        let commitment = &self.g * &k;
+        (
+            SequentialSignerSession {
+                k,
+                commitment: commitment.clone(),
+                signer: self,
+            },
+            commitment,
+        )
+    }
+}
+
+impl<'a> SequentialSignerSession<'a> {
+    /// Response `s' = k - e'*sk` to the user's blinded challenge `e'`
+    pub fn respond(self, blinded_challenge: &CurveOrderElement) -> CurveOrderElement {
+        &self.k - &(blinded_challenge * self.signer.sk.as_scalar())
+    }
+
+    pub fn commitment(&self) -> &G1 {
+        &self.commitment
+    }
+}
+
+impl fmt::Debug for BlindSigner {
+    /// Does not show the secret key
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("BlindSigner").field("g", &self.g).finish()
+    }
+}
+
+impl<'a> fmt::Debug for SequentialSignerSession<'a> {
+    /// Does not show the nonce or secret key
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("SequentialSignerSession")
+            .field("commitment", &self.commitment)
+            .finish()
+    }
+}
+
+impl fmt::Debug for UserSession {
+    /// Does not show the blinding factor, which links the signature to the session
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("UserSession")
+            .field("commitment", &self.commitment)
+            .field("pk", &self.pk)
+            .finish()
+    }
+}
+
+impl UserSession {
+    /// Blind the signer's commitment for signing `msg` with respect to the standard generator of
+    /// group G1. Returns the blinded challenge for the signer.
+    pub fn new(
+        commitment: &G1,
+        pk: &PublicKey,
+        msg: &[u8],
+    ) -> Result<(Self, CurveOrderElement), ValueError> {
+        Self::new_with_params(commitment, pk, msg, &GroupParams::default())
+    }
+
+    pub fn new_with_params(
+        commitment: &G1,
+        pk: &PublicKey,
+        msg: &[u8],
+        params: &GroupParams,
+    ) -> Result<(Self, CurveOrderElement), ValueError> {
+        if commitment.is_identity() || pk.as_point().is_identity() {
+            return Err(ValueError::IdentityElement);
+        }
+        let alpha = CurveOrderElement::random();
+        let beta = CurveOrderElement::random();
+        let r = commitment + &params.g1().binary_scalar_mul(pk.as_point(), &alpha, &beta);
+        let e = challenge(&r, pk.as_point(), msg);
+        let blinded_e = &e - &beta;
+        Ok((
+            Self {
+                alpha,
+                blinded_e: blinded_e.clone(),
+                e,
+                commitment: commitment.clone(),
+                pk: pk.clone(),
+                g: params.g1().clone(),
+            },
+            blinded_e,
+        ))
+    }
+
+    /// Check the signer's response against its commitment and unblind it into a signature on the
+    /// message
+    pub fn finish(self, response: &CurveOrderElement) -> Result<Signature, SignatureError> {
+        let r = self
+            .g
+            .binary_scalar_mul(self.pk.as_point(), response, &self.blinded_e);
+        if r != self.commitment {
+            return Err(SignatureError::new());
+        }
+        Ok(Signature::from_parts(self.e, response + &self.alpha))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use signature::Verifier;
+
+    #[test]
+    fn test_blind_sign() {
+        let sk = SecretKey::random();
+        let pk = PublicKey::from(&sk);
+        let msg = b"test message";
+
+        let mut signer = BlindSigner::new(sk.clone());
+        let (session, commitment) = signer.start();
+        assert_eq!(session.commitment(), &commitment);
+        let (user, blinded_e) = UserSession::new(&commitment, &pk, msg).unwrap();
+        let response = session.respond(&blinded_e);
+        let sig = user.finish(&response).unwrap();
+        assert!(pk.verify(msg, &sig).is_ok());
+        assert!(pk.verify(b"another message", &sig).is_err());
+
+        // Signatures from two sessions on the same message differ
+        let (session, commitment) = signer.start();
+        let (user, blinded_e) = UserSession::new(&commitment, &pk, msg).unwrap();
+        let other = user.finish(&session.respond(&blinded_e)).unwrap();
+        assert!(pk.verify(msg, &other).is_ok());
+        assert_ne!(sig, other);
+
+        // A response not matching the commitment is rejected
+        let (session, commitment) = signer.start();
+        let (user, blinded_e) = UserSession::new(&commitment, &pk, msg).unwrap();
+        let _ = session.respond(&blinded_e);
+        assert!(user.finish(&CurveOrderElement::random()).is_err());
+
+        // Debug output shows neither the secret key, the nonce nor the blinding factor
+        let (session, commitment) = signer.start();
+        let (user, _) = UserSession::new(&commitment, &pk, msg).unwrap();
+        assert!(!format!("{:?}", session).contains(&session.k.fmt_short()));
+        assert!(!format!("{:?}", user).contains(&user.alpha.fmt_short()));
+        drop(session);
+        assert!(!format!("{:?}", signer).contains(&sk.as_scalar().fmt_short()));
+
+        assert!(UserSession::new(&G1::identity(), &pk, msg).is_err());
+    }
+
+    #[test]
+    fn test_blind_sign_with_params() {
+        let params = GroupParams::from_label(b"test");
+        let sk = SecretKey::random();
+        let pk = PublicKey::new_with_params(&sk, &params);
+        let msg = b"test message";
+        let mut signer = BlindSigner::new_with_params(sk, &params);
+        let (session, commitment) = signer.start();
+        let (user, blinded_e) =
+            UserSession::new_with_params(&commitment, &pk, msg, &params).unwrap();
+        let sig = user.finish(&session.respond(&blinded_e)).unwrap();
+        assert!(pk.verify_with_params(msg, &sig, &params));
+        assert!(pk.verify(msg, &sig).is_err());
+    }
+}
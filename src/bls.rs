@@ -0,0 +1,981 @@
+//! BLS signatures with public keys in group G2 and signatures in group G1. Signatures (and public keys)
+//! can be aggregated by adding them. Aggregating public keys is only safe when each key comes with a
+//! verified proof of possession.
+//!
+//! The `Scheme` selects how rogue key attacks on aggregates are prevented, following the variants of
+//! the IETF BLS signature draft: requiring distinct messages (basic), prepending the public key to
+//! the message before hashing (message augmentation) or proofs of possession of the keys.
+//!
+//! `bls_min_pk` has the opposite orientation, public keys in group G1 and signatures in group G2.
+
+use crate::constants::FIELD_ORDER_ELEMENT_SIZE;
+use crate::ct;
+use crate::curve_order_elem::CurveOrderElement;
+use crate::encoding::IdentityPolicy;
+use crate::extension_field_gt::GT;
+use crate::group_elem::GroupElement;
+use crate::group_elem_g1::G1;
+use crate::group_elem_g2::G2;
+use crate::group_params::GroupParams;
+use crate::utils::hash_msg_to_scalar;
+
+use rand::{CryptoRng, RngCore};
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+use signature::{Error as SignatureError, SignatureEncoding, Signer, Verifier};
+use std::collections::HashSet;
+use std::convert::TryFrom;
+use std::fmt;
+
+/// Domain separation tag for hashing messages to group G1
+pub const DST: &[u8] = b"BLS_SIG_BLS12381G1_XMD:SHA-256_SSWU_RO_NUL_";
+/// Domain separation tag for hashing messages to group G1 in the message augmentation scheme
+pub const DST_AUG: &[u8] = b"BLS_SIG_BLS12381G1_XMD:SHA-256_SSWU_RO_AUG_";
+/// Domain separation tag for hashing messages to group G1 in the proof of possession scheme
+pub const DST_POP: &[u8] = b"BLS_SIG_BLS12381G1_XMD:SHA-256_SSWU_RO_POP_";
+/// Domain separation tag for hashing public keys to group G1 when proving possession
+pub const DST_POP_PROVE: &[u8] = b"BLS_POP_BLS12381G1_XMD:SHA-256_SSWU_RO_POP_";
+/// Domain separation tag for hashing a public key and index to the tweak of a child key
+pub const DST_CHILD: &[u8] = b"BLS_CHILD_KEY_G2_TWEAK_";
+
+/// Byte size of a signature, a compressed element of group G1
+pub const SIGNATURE_SIZE: usize = FIELD_ORDER_ELEMENT_SIZE;
+/// Byte size of a public key, a compressed element of group G2
+pub const PUBLIC_KEY_SIZE: usize = 2 * FIELD_ORDER_ELEMENT_SIZE;
+
+/// Variants of the signature scheme, differing in how aggregation is protected against rogue keys
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum Scheme {
+    /// Messages are hashed as is. Aggregate signatures must be over distinct messages.
+    Basic,
+    /// The signer's public key is prepended to the message before hashing, so aggregate
+    /// signatures can be over the same message without further checks.
+    MessageAugmentation,
+    /// Messages are hashed as is and every public key must come with a verified proof of
+    /// possession, after which signatures on the same message can be verified against the
+    /// aggregated public key.
+    ProofOfPossession,
+}
+
+impl Scheme {
+    /// Domain separation tag used to hash messages
+    pub fn dst(&self) -> &'static [u8] {
+        scheme_dst::<MinSig>(*self)
+    }
+}
+
+/// An orientation of BLS signatures: the groups of public keys and signatures, the tags and the
+/// hash of messages to the signature group. `bls` and `bls_min_pk` share the schemes, aggregation
+/// and proofs of possession below through it.
+pub(crate) trait Orientation {
+    type Key: GroupElement;
+    type Sig: GroupElement;
+
+    const DST: &'static [u8];
+    const DST_AUG: &'static [u8];
+    const DST_POP: &'static [u8];
+    const DST_POP_PROVE: &'static [u8];
+    const DST_CHILD: &'static [u8];
+
+    fn hash(msg: &[u8], dst: &[u8]) -> Self::Sig;
+
+    fn key_generator(params: &GroupParams) -> Self::Key;
+
+    /// Compressed public key, prepended to messages in the message augmentation scheme
+    fn key_to_bytes(pk: &Self::Key) -> Vec<u8>;
+
+    /// Whether the product of the pairings of `pairs` is one
+    fn pairing_product_is_one(pairs: Vec<(&Self::Key, &Self::Sig)>) -> bool;
+}
+
+/// Public keys in group G2, signatures in group G1
+pub(crate) struct MinSig;
+
+impl Orientation for MinSig {
+    type Key = G2;
+    type Sig = G1;
+
+    const DST: &'static [u8] = DST;
+    const DST_AUG: &'static [u8] = DST_AUG;
+    const DST_POP: &'static [u8] = DST_POP;
+    const DST_POP_PROVE: &'static [u8] = DST_POP_PROVE;
+    const DST_CHILD: &'static [u8] = DST_CHILD;
+
+    fn hash(msg: &[u8], dst: &[u8]) -> G1 {
+        hash_to_g1(msg, dst)
+    }
+
+    fn key_generator(params: &GroupParams) -> G2 {
+        params.g2().clone()
+    }
+
+    fn key_to_bytes(pk: &G2) -> Vec<u8> {
+        pk.to_compressed_bytes().to_vec()
+    }
+
+    fn pairing_product_is_one(pairs: Vec<(&G2, &G1)>) -> bool {
+        GT::ate_multi_pairing(pairs.into_iter().map(|(k, s)| (s, k)).collect()).is_one()
+    }
+}
+
+/// Domain separation tag used to hash messages in `scheme`
+pub(crate) fn scheme_dst<O: Orientation>(scheme: Scheme) -> &'static [u8] {
+    match scheme {
+        Scheme::Basic => O::DST,
+        Scheme::MessageAugmentation => O::DST_AUG,
+        Scheme::ProofOfPossession => O::DST_POP,
+    }
+}
+
+/// Hash `msg` signed by the owner of `pk` in `scheme`
+pub(crate) fn scheme_hash<O: Orientation>(scheme: Scheme, msg: &[u8], pk: &O::Key) -> O::Sig {
+    match scheme {
+        Scheme::MessageAugmentation => {
+            O::hash(&[&O::key_to_bytes(pk)[..], msg].concat(), O::DST_AUG)
+        }
+        _ => O::hash(msg, scheme_dst::<O>(scheme)),
+    }
+}
+
+/// Tweak of the child key `index` of `pk`, public so child public keys can be derived without
+/// the secret key
+pub(crate) fn child_tweak<O: Orientation>(pk: &O::Key, index: u32) -> CurveOrderElement {
+    hash_msg_to_scalar(
+        &[&O::key_to_bytes(pk)[..], &index.to_be_bytes()].concat(),
+        O::DST_CHILD,
+    )
+}
+
+/// Signature of `msg` in `scheme`. The public key is only computed when it is hashed with the
+/// message.
+pub(crate) fn sign<O: Orientation>(
+    sk: &CurveOrderElement,
+    msg: &[u8],
+    scheme: Scheme,
+    params: &GroupParams,
+) -> O::Sig {
+    let h = match scheme {
+        Scheme::MessageAugmentation => {
+            let pk = O::key_generator(params).scalar_mul_const_time(sk);
+            scheme_hash::<O>(scheme, msg, &pk)
+        }
+        _ => O::hash(msg, scheme_dst::<O>(scheme)),
+    };
+    h.scalar_mul_const_time(sk)
+}
+
+/// Signature on the public key
+pub(crate) fn prove_possession<O: Orientation>(
+    sk: &CurveOrderElement,
+    params: &GroupParams,
+) -> O::Sig {
+    let pk = O::key_generator(params).scalar_mul_const_time(sk);
+    O::hash(&O::key_to_bytes(&pk), O::DST_POP_PROVE).scalar_mul_const_time(sk)
+}
+
+/// Check `e(g, sig) == e(pk, H(msg))`, with the orientation's order of the arguments
+pub(crate) fn verify<O: Orientation>(
+    pk: &O::Key,
+    msg: &[u8],
+    sig: &O::Sig,
+    scheme: Scheme,
+    params: &GroupParams,
+    policy: IdentityPolicy,
+) -> bool {
+    if policy == IdentityPolicy::Reject && (pk.is_identity() || sig.is_identity()) {
+        return false;
+    }
+    let h = scheme_hash::<O>(scheme, msg, pk);
+    let neg_g = O::key_generator(params).negation();
+    O::pairing_product_is_one(vec![(&neg_g, sig), (pk, &h)])
+}
+
+pub(crate) fn verify_possession<O: Orientation>(
+    pk: &O::Key,
+    proof: &O::Sig,
+    params: &GroupParams,
+) -> bool {
+    if pk.is_identity() || proof.is_identity() {
+        return false;
+    }
+    let h = O::hash(&O::key_to_bytes(pk), O::DST_POP_PROVE);
+    let neg_g = O::key_generator(params).negation();
+    O::pairing_product_is_one(vec![(&neg_g, proof), (pk, &h)])
+}
+
+/// `pk + g*tweak`, fails if the result is the identity
+pub(crate) fn tweak_add<O: Orientation>(
+    pk: &O::Key,
+    tweak: &CurveOrderElement,
+    params: &GroupParams,
+) -> Result<O::Key, SignatureError> {
+    let q = O::key_generator(params)
+        .scalar_mul_const_time(tweak)
+        .plus(pk);
+    if q.is_identity() {
+        return Err(SignatureError::new());
+    }
+    Ok(q)
+}
+
+/// Sum of the points
+pub(crate) fn aggregate<'a, G: GroupElement + 'a>(points: impl Iterator<Item = &'a G>) -> G {
+    let mut agg = G::identity();
+    for p in points {
+        agg.add_assign_(p);
+    }
+    agg
+}
+
+/// Check `e(g, sig) == prod e(pk_i, H_i)` with `H_i` the hash of the `i`th message in the
+/// scheme. The basic scheme rejects repeated messages.
+pub(crate) fn verify_aggregate<O: Orientation>(
+    sig: &O::Sig,
+    signed: &[(&O::Key, &[u8])],
+    scheme: Scheme,
+    params: &GroupParams,
+    policy: IdentityPolicy,
+) -> bool {
+    if signed.is_empty() {
+        return false;
+    }
+    if policy == IdentityPolicy::Reject
+        && (sig.is_identity() || signed.iter().any(|(pk, _)| pk.is_identity()))
+    {
+        return false;
+    }
+    if scheme == Scheme::Basic {
+        let mut seen = HashSet::new();
+        if !signed.iter().all(|(_, msg)| seen.insert(*msg)) {
+            return false;
+        }
+    }
+    let hashes: Vec<O::Sig> = signed
+        .iter()
+        .map(|(pk, msg)| scheme_hash::<O>(scheme, msg, pk))
+        .collect();
+    let neg_g = O::key_generator(params).negation();
+    let mut pairs: Vec<(&O::Key, &O::Sig)> = signed
+        .iter()
+        .zip(hashes.iter())
+        .map(|((pk, _), h)| (*pk, h))
+        .collect();
+    pairs.push((&neg_g, sig));
+    O::pairing_product_is_one(pairs)
+}
+
+/// Hash message to group G1
+pub fn hash_msg_to_g1(msg: &[u8]) -> G1 {
+    hash_to_g1(msg, DST)
+}
+
+#[cfg(feature = "bls381")]
+fn hash_to_g1(msg: &[u8], dst: &[u8]) -> G1 {
+    crate::hash_to_curve::hash_to_g1(msg, dst).unwrap()
+}
+
+// `hash_to_curve` is only implemented for BLS12-381
+#[cfg(not(feature = "bls381"))]
+#[allow(deprecated)]
+fn hash_to_g1(msg: &[u8], dst: &[u8]) -> G1 {
+    G1::from_msg_hash(&[dst, msg].concat())
+}
+
+/// Compressed element of group G1, the identity only accepted if `policy` does. The bytes must be
+/// the canonical encoding of a point in the subgroup, as decoding maps invalid `x` to the identity
+/// and reduces non-canonical `x`.
+pub(crate) fn g1_from_compressed(
+    bytes: &[u8],
+    policy: IdentityPolicy,
+) -> Result<G1, SignatureError> {
+    if bytes.len() != G1::COMPRESSED_SIZE {
+        return Err(SignatureError::new());
+    }
+    // The compressed encoding of the identity does not decode to it
+    let identity = G1::identity();
+    let p = if bytes == identity.to_compressed_bytes() {
+        identity
+    } else {
+        G1::from(array_ref![bytes, 0, G1::COMPRESSED_SIZE])
+    };
+    if p.to_compressed_bytes()[..] != *bytes
+        || (policy == IdentityPolicy::Reject && p.is_identity())
+        || !p.has_correct_order()
+    {
+        return Err(SignatureError::new());
+    }
+    Ok(p)
+}
+
+/// Compressed element of group G2, see `g1_from_compressed`
+pub(crate) fn g2_from_compressed(
+    bytes: &[u8],
+    policy: IdentityPolicy,
+) -> Result<G2, SignatureError> {
+    if bytes.len() != G2::COMPRESSED_SIZE {
+        return Err(SignatureError::new());
+    }
+    let p = G2::from(array_ref![bytes, 0, G2::COMPRESSED_SIZE]);
+    if p.to_compressed_bytes()[..] != *bytes
+        || (policy == IdentityPolicy::Reject && p.is_identity())
+        || !p.has_correct_order()
+    {
+        return Err(SignatureError::new());
+    }
+    Ok(p)
+}
+
+#[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct SecretKey(CurveOrderElement);
+
+impl fmt::Debug for SecretKey {
+    /// Does not show the key
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("SecretKey(..)")
+    }
+}
+
+impl PartialEq for SecretKey {
+    /// Constant time in the keys
+    fn eq(&self, other: &Self) -> bool {
+        ct::scalar_eq(&self.0, &other.0)
+    }
+}
+
+impl SecretKey {
+    pub fn random() -> Self {
+        Self(CurveOrderElement::random())
+    }
+
+    pub fn random_using_rng<R: RngCore + CryptoRng>(rng: &mut R) -> Self {
+        Self(CurveOrderElement::random_using_rng(rng))
+    }
+
+    pub fn sign(&self, msg: &[u8]) -> Signature {
+        Signature(sign::<MinSig>(&self.0, msg, Scheme::Basic, &GroupParams::default()))
+    }
+
+    /// Sign in the given scheme, for a public key with respect to the standard generator of group G2
+    pub fn sign_with_scheme(&self, msg: &[u8], scheme: Scheme) -> Signature {
+        self.sign_with_scheme_and_params(msg, scheme, &GroupParams::default())
+    }
+
+    /// Sign in the given scheme. The parameters determine the public key prepended to the message
+    /// in the message augmentation scheme.
+    pub fn sign_with_scheme_and_params(
+        &self,
+        msg: &[u8],
+        scheme: Scheme,
+        params: &GroupParams,
+    ) -> Signature {
+        Signature(sign::<MinSig>(&self.0, msg, scheme, params))
+    }
+
+    /// Proof of possession of the secret key, a signature on the public key
+    pub fn prove_possession(&self) -> Signature {
+        self.prove_possession_with_params(&GroupParams::default())
+    }
+
+    pub fn prove_possession_with_params(&self, params: &GroupParams) -> Signature {
+        Signature(prove_possession::<MinSig>(&self.0, params))
+    }
+
+    /// Secret key of `PublicKey::tweak_add` with the same tweak
+    pub fn tweak_add(&self, tweak: &CurveOrderElement) -> Result<Self, SignatureError> {
+        let x = &self.0 + tweak;
+        if x.is_zero() {
+            return Err(SignatureError::new());
+        }
+        Ok(Self(x))
+    }
+
+    /// Secret key of `PublicKey::derive_child`, for a public key with respect to the standard
+    /// generator of group G2
+    pub fn derive_child(&self, index: u32) -> Result<Self, SignatureError> {
+        self.derive_child_with_params(index, &GroupParams::default())
+    }
+
+    pub fn derive_child_with_params(
+        &self,
+        index: u32,
+        params: &GroupParams,
+    ) -> Result<Self, SignatureError> {
+        let pk = PublicKey::new_with_params(self, params);
+        self.tweak_add(&child_tweak::<MinSig>(&pk.0, index))
+    }
+
+    pub fn as_scalar(&self) -> &CurveOrderElement {
+        &self.0
+    }
+}
+
+impl From<CurveOrderElement> for SecretKey {
+    fn from(x: CurveOrderElement) -> Self {
+        Self(x)
+    }
+}
+
+impl Signer<Signature> for SecretKey {
+    fn try_sign(&self, msg: &[u8]) -> Result<Signature, SignatureError> {
+        Ok(self.sign(msg))
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct PublicKey(G2);
+
+impl PublicKey {
+    /// Public key for the standard generator of group G2
+    pub fn new(sk: &SecretKey) -> Self {
+        Self::new_with_params(sk, &GroupParams::default())
+    }
+
+    pub fn new_with_params(sk: &SecretKey, params: &GroupParams) -> Self {
+        Self(params.g2() * &sk.0)
+    }
+
+    /// Sum of the public keys. Only secure when the keys come with a proof of possession.
+    pub fn aggregate(keys: &[PublicKey]) -> Self {
+        Self(aggregate(keys.iter().map(|k| &k.0)))
+    }
+
+    /// Check `e(sig, g2) == e(H(msg), pk)`
+    pub fn verify_with_params(&self, msg: &[u8], sig: &Signature, params: &GroupParams) -> bool {
+        self.verify_with_scheme_and_params(msg, sig, Scheme::Basic, params)
+    }
+
+    /// Verify a signature made in the given scheme, with respect to the standard generator of
+    /// group G2
+    pub fn verify_with_scheme(&self, msg: &[u8], sig: &Signature, scheme: Scheme) -> bool {
+        self.verify_with_scheme_and_params(msg, sig, scheme, &GroupParams::default())
+    }
+
+    pub fn verify_with_scheme_and_params(
+        &self,
+        msg: &[u8],
+        sig: &Signature,
+        scheme: Scheme,
+        params: &GroupParams,
+    ) -> bool {
+        self.verify_with_policy(msg, sig, scheme, params, IdentityPolicy::Reject)
+    }
+
+    /// `verify_with_scheme_and_params` accepting an identity public key or signature if `policy`
+    /// does. The other entry points reject them, as an identity key verifies the identity
+    /// signature on every message.
+    pub fn verify_with_policy(
+        &self,
+        msg: &[u8],
+        sig: &Signature,
+        scheme: Scheme,
+        params: &GroupParams,
+        policy: IdentityPolicy,
+    ) -> bool {
+        verify::<MinSig>(&self.0, msg, &sig.0, scheme, params, policy)
+    }
+
+    /// Check the proof of possession of the secret key, with respect to the standard generator
+    /// of group G2
+    pub fn verify_possession(&self, proof: &Signature) -> bool {
+        self.verify_possession_with_params(proof, &GroupParams::default())
+    }
+
+    pub fn verify_possession_with_params(&self, proof: &Signature, params: &GroupParams) -> bool {
+        verify_possession::<MinSig>(&self.0, &proof.0, params)
+    }
+
+    pub fn to_bytes(&self) -> [u8; PUBLIC_KEY_SIZE] {
+        self.0.to_compressed_bytes()
+    }
+
+    pub fn as_point(&self) -> &G2 {
+        &self.0
+    }
+
+    /// `pk + g2*tweak` for the standard generator of group G2, fails if the result is the identity
+    pub fn tweak_add(&self, tweak: &CurveOrderElement) -> Result<Self, SignatureError> {
+        self.tweak_add_with_params(tweak, &GroupParams::default())
+    }
+
+    pub fn tweak_add_with_params(
+        &self,
+        tweak: &CurveOrderElement,
+        params: &GroupParams,
+    ) -> Result<Self, SignatureError> {
+        tweak_add::<MinSig>(&self.0, tweak, params).map(Self)
+    }
+
+    /// Child key `index`, tweaked by the hash of the public key and index, so it can be derived
+    /// without the secret key. Child keys need their own proofs of possession.
+    pub fn derive_child(&self, index: u32) -> Result<Self, SignatureError> {
+        self.derive_child_with_params(index, &GroupParams::default())
+    }
+
+    pub fn derive_child_with_params(
+        &self,
+        index: u32,
+        params: &GroupParams,
+    ) -> Result<Self, SignatureError> {
+        self.tweak_add_with_params(&child_tweak::<MinSig>(&self.0, index), params)
+    }
+
+    /// Compressed public key, the identity only accepted if `policy` does. Non-canonical
+    /// encodings and points not in the subgroup are always rejected.
+    pub fn from_bytes_with_policy(
+        bytes: &[u8],
+        policy: IdentityPolicy,
+    ) -> Result<Self, SignatureError> {
+        g2_from_compressed(bytes, policy).map(Self)
+    }
+}
+
+#[cfg(feature = "jwk")]
+impl PublicKey {
+    pub fn to_jwk(&self) -> crate::jwk::Jwk {
+        self.0.to_jwk()
+    }
+
+    pub fn from_jwk(jwk: &crate::jwk::Jwk) -> Result<Self, crate::errors::SerzDeserzError> {
+        G2::from_jwk(jwk).map(Self)
+    }
+}
+
+#[cfg(all(feature = "cose", feature = "bls381"))]
+impl PublicKey {
+    pub fn to_cose_key(&self) -> crate::cose::CoseKey {
+        self.0.to_cose_key()
+    }
+
+    pub fn from_cose_key(
+        key: &crate::cose::CoseKey,
+    ) -> Result<Self, crate::errors::SerzDeserzError> {
+        G2::from_cose_key(key).map(Self)
+    }
+}
+
+impl From<&SecretKey> for PublicKey {
+    fn from(sk: &SecretKey) -> Self {
+        Self::new(sk)
+    }
+}
+
+impl TryFrom<&[u8]> for PublicKey {
+    type Error = SignatureError;
+
+    fn try_from(bytes: &[u8]) -> Result<Self, SignatureError> {
+        Self::from_bytes_with_policy(bytes, IdentityPolicy::Reject)
+    }
+}
+
+impl Verifier<Signature> for PublicKey {
+    fn verify(&self, msg: &[u8], sig: &Signature) -> Result<(), SignatureError> {
+        if self.verify_with_params(msg, sig, &GroupParams::default()) {
+            Ok(())
+        } else {
+            Err(SignatureError::new())
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Signature(G1);
+
+impl Signature {
+    /// Sum of the signatures. The aggregate verifies against the aggregated public key when all
+    /// signatures are over the same message.
+    pub fn aggregate(sigs: &[Signature]) -> Self {
+        Self(aggregate(sigs.iter().map(|s| &s.0)))
+    }
+
+    /// Verify an aggregate of signatures made in the given scheme, each by a public key on its
+    /// message, with respect to the standard generator of group G2
+    pub fn verify_aggregate(&self, signed: &[(PublicKey, &[u8])], scheme: Scheme) -> bool {
+        self.verify_aggregate_with_params(signed, scheme, &GroupParams::default())
+    }
+
+    /// Check `e(sig, g2) == prod e(H_i, pk_i)` with `H_i` the hash of the `i`th message in the
+    /// scheme. The basic scheme rejects repeated messages. With proofs of possession, signatures
+    /// on the same message are cheaper to verify against the aggregated public key.
+    pub fn verify_aggregate_with_params(
+        &self,
+        signed: &[(PublicKey, &[u8])],
+        scheme: Scheme,
+        params: &GroupParams,
+    ) -> bool {
+        self.verify_aggregate_with_policy(signed, scheme, params, IdentityPolicy::Reject)
+    }
+
+    /// `verify_aggregate_with_params` accepting identity public keys or an identity aggregate if
+    /// `policy` does
+    pub fn verify_aggregate_with_policy(
+        &self,
+        signed: &[(PublicKey, &[u8])],
+        scheme: Scheme,
+        params: &GroupParams,
+        policy: IdentityPolicy,
+    ) -> bool {
+        let signed: Vec<_> = signed.iter().map(|(pk, msg)| (&pk.0, *msg)).collect();
+        verify_aggregate::<MinSig>(&self.0, &signed, scheme, params, policy)
+    }
+
+    pub fn as_point(&self) -> &G1 {
+        &self.0
+    }
+
+    pub(crate) fn from_point(sig: G1) -> Self {
+        Self(sig)
+    }
+
+    /// Compressed signature, the identity only accepted if `policy` does. Non-canonical
+    /// encodings and points not in the subgroup are always rejected.
+    pub fn from_bytes_with_policy(
+        bytes: &[u8],
+        policy: IdentityPolicy,
+    ) -> Result<Self, SignatureError> {
+        g1_from_compressed(bytes, policy).map(Self)
+    }
+}
+
+impl TryFrom<&[u8]> for Signature {
+    type Error = SignatureError;
+
+    fn try_from(bytes: &[u8]) -> Result<Self, SignatureError> {
+        if bytes.len() != SIGNATURE_SIZE {
+            return Err(SignatureError::new());
+        }
+        Self::try_from(*array_ref![bytes, 0, SIGNATURE_SIZE])
+    }
+}
+
+impl TryFrom<[u8; SIGNATURE_SIZE]> for Signature {
+    type Error = SignatureError;
+
+    fn try_from(bytes: [u8; SIGNATURE_SIZE]) -> Result<Self, SignatureError> {
+        Self::from_bytes_with_policy(&bytes, IdentityPolicy::Reject)
+    }
+}
+
+impl From<Signature> for [u8; SIGNATURE_SIZE] {
+    fn from(sig: Signature) -> Self {
+        sig.0.to_compressed_bytes()
+    }
+}
+
+impl SignatureEncoding for Signature {
+    type Repr = [u8; SIGNATURE_SIZE];
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_secret_key_eq_and_debug() {
+        let sk = SecretKey::random();
+        assert_eq!(sk, sk.clone());
+        assert_ne!(sk, SecretKey::random());
+        assert_eq!(format!("{:?}", sk), "SecretKey(..)");
+    }
+
+    #[test]
+    fn test_sign_verify() {
+        let sk = SecretKey::random();
+        let pk = PublicKey::from(&sk);
+        let msg = b"test message";
+        let sig = sk.sign(msg);
+        assert!(pk.verify(msg, &sig).is_ok());
+        assert!(pk.verify(b"another message", &sig).is_err());
+
+        let other_pk = PublicKey::from(&SecretKey::random());
+        assert!(other_pk.verify(msg, &sig).is_err());
+
+        let params = GroupParams::from_label(b"test");
+        let pk = PublicKey::new_with_params(&sk, &params);
+        assert!(pk.verify_with_params(msg, &sig, &params));
+        assert!(pk.verify(msg, &sig).is_err());
+    }
+
+    #[test]
+    fn test_aggregate() {
+        let msg = b"test message";
+        let sks: Vec<SecretKey> = (0..5).map(|_| SecretKey::random()).collect();
+        let pks: Vec<PublicKey> = sks.iter().map(PublicKey::from).collect();
+        let sigs: Vec<Signature> = sks.iter().map(|sk| sk.try_sign(msg).unwrap()).collect();
+        let agg_pk = PublicKey::aggregate(&pks);
+        let agg_sig = Signature::aggregate(&sigs);
+        assert!(agg_pk.verify(msg, &agg_sig).is_ok());
+        assert!(PublicKey::aggregate(&pks[1..]).verify(msg, &agg_sig).is_err());
+    }
+
+    #[test]
+    fn test_tweak_and_derive_child() {
+        let sk = SecretKey::random();
+        let pk = PublicKey::from(&sk);
+        let tweak = CurveOrderElement::random();
+        assert_eq!(PublicKey::from(&sk.tweak_add(&tweak).unwrap()), pk.tweak_add(&tweak).unwrap());
+        assert_eq!(pk.tweak_add(&tweak).unwrap().as_point(), &pk.as_point().tweak_add(&tweak));
+        assert!(sk.tweak_add(&-sk.as_scalar()).is_err());
+        assert!(pk.tweak_add(&-sk.as_scalar()).is_err());
+
+        // Child public keys derived without the secret key match the child secret keys
+        let msg = b"test message";
+        let child_sk = sk.derive_child(7).unwrap();
+        let child_pk = pk.derive_child(7).unwrap();
+        assert_eq!(PublicKey::from(&child_sk), child_pk);
+        assert_eq!(pk.derive_child(7).unwrap(), child_pk);
+        assert_ne!(pk.derive_child(8).unwrap(), child_pk);
+        assert!(child_pk.verify(msg, &child_sk.sign(msg)).is_ok());
+        assert!(pk.verify(msg, &child_sk.sign(msg)).is_err());
+
+        let params = GroupParams::from_label(b"test");
+        let pk = PublicKey::new_with_params(&sk, &params);
+        let child_sk = sk.derive_child_with_params(7, &params).unwrap();
+        assert_eq!(
+            PublicKey::new_with_params(&child_sk, &params),
+            pk.derive_child_with_params(7, &params).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_encoding() {
+        let sk = SecretKey::random();
+        let pk = PublicKey::from(&sk);
+        let sig = sk.sign(b"test message");
+
+        let sig_bytes = sig.to_bytes();
+        assert_eq!(sig_bytes.len(), SIGNATURE_SIZE);
+        assert_eq!(Signature::try_from(&sig_bytes[..]).unwrap(), sig);
+        assert!(Signature::try_from(&sig_bytes[1..]).is_err());
+
+        let pk_bytes = pk.to_bytes();
+        assert_eq!(PublicKey::try_from(&pk_bytes[..]).unwrap(), pk);
+        assert!(PublicKey::try_from(&[0u8; PUBLIC_KEY_SIZE][..]).is_err());
+    }
+
+    // Hashing follows RFC 9380, test vector of appendix J.9.1
+    #[cfg(feature = "bls381")]
+    #[test]
+    fn test_hash_to_g1_rfc9380() {
+        let p = hash_to_g1(b"abc", b"QUUX-V01-CS02-with-BLS12381G1_XMD:SHA-256_SSWU_RO_").to_ecp();
+        assert_eq!(
+            p.getx().tostring().to_lowercase(),
+            "03567bc5ef9c690c2ab2ecdf6a96ef1c139cc0b2f284dca0a9a7943388a49a3aee664ba5379a7655d3c68900be2f6903"
+        );
+        assert_eq!(
+            p.gety().tostring().to_lowercase(),
+            "0b9c15f3fe6e5cf4211f346271d7b01c8f3b28be689c8429c85b67af215533311f0b8dfaaa154fa6b88176c229f2885d"
+        );
+
+        let sk = SecretKey(CurveOrderElement::one());
+        let pk = PublicKey::from(&sk);
+        let msg = b"abc";
+        assert_eq!(
+            sk.sign_with_scheme(msg, Scheme::Basic).0,
+            crate::hash_to_curve::hash_to_g1(msg, b"BLS_SIG_BLS12381G1_XMD:SHA-256_SSWU_RO_NUL_")
+                .unwrap()
+        );
+        assert_eq!(
+            sk.sign_with_scheme(msg, Scheme::ProofOfPossession).0,
+            crate::hash_to_curve::hash_to_g1(msg, b"BLS_SIG_BLS12381G1_XMD:SHA-256_SSWU_RO_POP_")
+                .unwrap()
+        );
+        assert_eq!(
+            sk.sign_with_scheme(msg, Scheme::MessageAugmentation).0,
+            crate::hash_to_curve::hash_to_g1(
+                &[&pk.to_bytes()[..], msg].concat(),
+                b"BLS_SIG_BLS12381G1_XMD:SHA-256_SSWU_RO_AUG_"
+            )
+            .unwrap()
+        );
+        assert_eq!(
+            sk.prove_possession().0,
+            crate::hash_to_curve::hash_to_g1(
+                &pk.to_bytes(),
+                b"BLS_POP_BLS12381G1_XMD:SHA-256_SSWU_RO_POP_"
+            )
+            .unwrap()
+        );
+    }
+
+    #[test]
+    fn test_decode_garbage_and_non_canonical() {
+        use crate::constants::MODULUS;
+        use crate::types::BigNum;
+        use rand::RngCore;
+
+        // Random bytes are only accepted when they are the exact encoding of a point, and never
+        // taken for the identity
+        let mut rng = rand::thread_rng();
+        for _ in 0..50 {
+            let mut bytes = [0u8; PUBLIC_KEY_SIZE];
+            rng.fill_bytes(&mut bytes);
+            if let Ok(p) = PublicKey::from_bytes_with_policy(&bytes, IdentityPolicy::Accept) {
+                assert!(!p.0.is_identity());
+                assert_eq!(p.to_bytes()[..], bytes[..]);
+            }
+            let bytes = &bytes[..SIGNATURE_SIZE];
+            if let Ok(p) = Signature::from_bytes_with_policy(bytes, IdentityPolicy::Accept) {
+                assert!(!p.0.is_identity());
+                assert_eq!(p.to_bytes()[..], *bytes);
+            }
+        }
+
+        // `x + p` in place of the coordinate `x` decodes to the same point but is rejected
+        let add_modulus = |bytes: &mut [u8], flags: u8| {
+            let top = bytes[0] & flags;
+            bytes[0] &= !flags;
+            let mut x = BigNum::frombytes(bytes);
+            x.add(&MODULUS);
+            x.norm();
+            x.tobytes(bytes);
+            bytes[0] |= top;
+        };
+        let sk = SecretKey::random();
+        let pk = PublicKey::from(&sk);
+        let sig = sk.sign(b"test message");
+        let mut bytes = sig.to_bytes();
+        add_modulus(&mut bytes, 0x80);
+        assert_eq!(G1::from(&bytes), sig.0);
+        assert!(Signature::from_bytes_with_policy(&bytes, IdentityPolicy::Accept).is_err());
+        let mut bytes = pk.to_bytes();
+        add_modulus(&mut bytes[FIELD_ORDER_ELEMENT_SIZE..], 0);
+        assert_eq!(G2::from(&bytes), pk.0);
+        assert!(PublicKey::from_bytes_with_policy(&bytes, IdentityPolicy::Accept).is_err());
+    }
+
+    #[test]
+    fn test_identity_policy() {
+        let identity_pk = PublicKey(G2::identity());
+        let identity_sig = Signature(G1::identity());
+        let pk_bytes = identity_pk.to_bytes();
+        let sig_bytes = identity_sig.to_bytes();
+        assert!(PublicKey::try_from(&pk_bytes[..]).is_err());
+        assert!(Signature::try_from(&sig_bytes[..]).is_err());
+        assert!(PublicKey::from_bytes_with_policy(&pk_bytes, IdentityPolicy::Reject).is_err());
+        assert_eq!(
+            PublicKey::from_bytes_with_policy(&pk_bytes, IdentityPolicy::Accept).unwrap(),
+            identity_pk
+        );
+        assert_eq!(
+            Signature::from_bytes_with_policy(&sig_bytes, IdentityPolicy::Accept).unwrap(),
+            identity_sig
+        );
+
+        // The identity key verifies the identity signature on any message, if accepted
+        let params = GroupParams::default();
+        let msg = b"test message";
+        for scheme in [Scheme::Basic, Scheme::ProofOfPossession].iter() {
+            assert!(!identity_pk.verify_with_scheme(msg, &identity_sig, *scheme));
+            assert!(identity_pk.verify_with_policy(
+                msg,
+                &identity_sig,
+                *scheme,
+                &params,
+                IdentityPolicy::Accept
+            ));
+        }
+
+        // Valid signatures verify with both policies
+        let sk = SecretKey::random();
+        let pk = PublicKey::from(&sk);
+        let sig = sk.sign(msg);
+        for policy in [IdentityPolicy::Accept, IdentityPolicy::Reject].iter() {
+            assert!(pk.verify_with_policy(msg, &sig, Scheme::Basic, &params, *policy));
+            assert!(sig.verify_aggregate_with_policy(
+                &[(pk.clone(), &msg[..])],
+                Scheme::Basic,
+                &params,
+                *policy
+            ));
+        }
+
+        let signed = [(pk, &msg[..]), (identity_pk, &b"other"[..])];
+        assert!(!sig.verify_aggregate(&signed, Scheme::Basic));
+        assert!(sig.verify_aggregate_with_policy(
+            &signed,
+            Scheme::Basic,
+            &params,
+            IdentityPolicy::Accept
+        ));
+    }
+
+    #[test]
+    fn test_schemes() {
+        let msg = b"test message";
+        let sk = SecretKey::random();
+        let pk = PublicKey::from(&sk);
+        let schemes = [
+            Scheme::Basic,
+            Scheme::MessageAugmentation,
+            Scheme::ProofOfPossession,
+        ];
+        for scheme in schemes.iter() {
+            let sig = sk.sign_with_scheme(msg, *scheme);
+            assert!(pk.verify_with_scheme(msg, &sig, *scheme));
+            assert!(!pk.verify_with_scheme(b"another message", &sig, *scheme));
+            for other in schemes.iter().filter(|s| *s != scheme) {
+                assert!(!pk.verify_with_scheme(msg, &sig, *other));
+            }
+        }
+        assert_eq!(sk.sign_with_scheme(msg, Scheme::Basic), sk.sign(msg));
+
+        // Augmented messages depend on the public key of the parameters
+        let params = GroupParams::from_label(b"test");
+        let pk = PublicKey::new_with_params(&sk, &params);
+        let sig = sk.sign_with_scheme_and_params(msg, Scheme::MessageAugmentation, &params);
+        assert!(pk.verify_with_scheme_and_params(msg, &sig, Scheme::MessageAugmentation, &params));
+        assert_ne!(sig, sk.sign_with_scheme(msg, Scheme::MessageAugmentation));
+    }
+
+    #[test]
+    fn test_aggregate_with_schemes() {
+        let sks: Vec<SecretKey> = (0..4).map(|_| SecretKey::random()).collect();
+        let pks: Vec<PublicKey> = sks.iter().map(PublicKey::from).collect();
+        let same: Vec<&[u8]> = vec![b"test message"; 4];
+        let distinct: Vec<&[u8]> = vec![b"message 1", b"message 2", b"message 3", b"message 4"];
+
+        for scheme in [
+            Scheme::Basic,
+            Scheme::MessageAugmentation,
+            Scheme::ProofOfPossession,
+        ]
+        .iter()
+        {
+            for msgs in [&same, &distinct].iter() {
+                let sigs: Vec<Signature> = sks
+                    .iter()
+                    .zip(msgs.iter())
+                    .map(|(sk, m)| sk.sign_with_scheme(m, *scheme))
+                    .collect();
+                let agg = Signature::aggregate(&sigs);
+                let signed: Vec<(PublicKey, &[u8])> =
+                    pks.iter().cloned().zip(msgs.iter().cloned()).collect();
+                // Basic scheme rejects repeated messages
+                let expected = *scheme != Scheme::Basic || msgs[0] != msgs[1];
+                assert_eq!(agg.verify_aggregate(&signed, *scheme), expected);
+                assert!(!agg.verify_aggregate(&signed[1..], *scheme));
+            }
+        }
+
+        // Proofs of possession allow verifying against the aggregated public key
+        let sigs: Vec<Signature> = sks
+            .iter()
+            .map(|sk| sk.sign_with_scheme(same[0], Scheme::ProofOfPossession))
+            .collect();
+        for (sk, pk) in sks.iter().zip(pks.iter()) {
+            let proof = sk.prove_possession();
+            assert!(pk.verify_possession(&proof));
+            assert!(!pks[0].verify_possession(&sks[1].prove_possession()));
+            assert!(!pk.verify_with_scheme(same[0], &proof, Scheme::ProofOfPossession));
+        }
+        assert!(PublicKey::aggregate(&pks).verify_with_scheme(
+            same[0],
+            &Signature::aggregate(&sigs),
+            Scheme::ProofOfPossession
+        ));
+    }
+}
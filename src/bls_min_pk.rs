@@ -0,0 +1,596 @@
+//! BLS signatures with public keys in group G1 and signatures in group G2, the orientation of
+//! `bls` swapped for deployments preferring small public keys over small signatures. The API
+//! mirrors `bls` and shares its `Scheme`.
+//!
+//! Messages are hashed to group G2 with `GroupElement::from_msg_hash` of the domain separated
+//! message, as `hash_to_curve` of RFC 9380 is not implemented for group G2 yet. The tags are
+//! specific to this crate and signatures are not interoperable with implementations of the G2
+//! ciphersuites of the IETF BLS signature draft.
+//!
+//! The schemes, aggregation and proofs of possession are those of `bls` with the groups swapped.
+
+use crate::bls::{
+    aggregate, child_tweak, g1_from_compressed, g2_from_compressed, prove_possession, sign,
+    tweak_add, verify, verify_aggregate, verify_possession, Orientation,
+};
+use crate::constants::FIELD_ORDER_ELEMENT_SIZE;
+use crate::ct;
+use crate::curve_order_elem::CurveOrderElement;
+use crate::encoding::IdentityPolicy;
+use crate::extension_field_gt::GT;
+use crate::group_elem::GroupElement;
+use crate::group_elem_g1::G1;
+use crate::group_elem_g2::G2;
+use crate::group_params::GroupParams;
+use crate::utils::domain_separated;
+
+pub use crate::bls::Scheme;
+
+use rand::{CryptoRng, RngCore};
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+use signature::{Error as SignatureError, SignatureEncoding, Signer, Verifier};
+use std::convert::TryFrom;
+use std::fmt;
+
+/// Domain separation tag for hashing messages to group G2. Not a tag of the IETF BLS signature
+/// draft, as the hash is not its `hash_to_curve`.
+pub const DST: &[u8] = b"AMCL_WRAPPER_ML_BLS_SIG_MIN_PK_G2_NUL_";
+/// Domain separation tag for hashing messages to group G2 in the message augmentation scheme
+pub const DST_AUG: &[u8] = b"AMCL_WRAPPER_ML_BLS_SIG_MIN_PK_G2_AUG_";
+/// Domain separation tag for hashing messages to group G2 in the proof of possession scheme
+pub const DST_POP: &[u8] = b"AMCL_WRAPPER_ML_BLS_SIG_MIN_PK_G2_POP_";
+/// Domain separation tag for hashing public keys to group G2 when proving possession
+pub const DST_POP_PROVE: &[u8] = b"AMCL_WRAPPER_ML_BLS_POP_MIN_PK_G2_POP_";
+/// Domain separation tag for hashing a public key and index to the tweak of a child key
+pub const DST_CHILD: &[u8] = b"BLS_CHILD_KEY_G1_TWEAK_";
+
+/// Byte size of a signature, a compressed element of group G2
+pub const SIGNATURE_SIZE: usize = 2 * FIELD_ORDER_ELEMENT_SIZE;
+/// Byte size of a public key, a compressed element of group G1
+pub const PUBLIC_KEY_SIZE: usize = FIELD_ORDER_ELEMENT_SIZE;
+
+/// Public keys in group G1, signatures in group G2
+struct MinPk;
+
+impl Orientation for MinPk {
+    type Key = G1;
+    type Sig = G2;
+
+    const DST: &'static [u8] = DST;
+    const DST_AUG: &'static [u8] = DST_AUG;
+    const DST_POP: &'static [u8] = DST_POP;
+    const DST_POP_PROVE: &'static [u8] = DST_POP_PROVE;
+    const DST_CHILD: &'static [u8] = DST_CHILD;
+
+    fn hash(msg: &[u8], dst: &[u8]) -> G2 {
+        hash_to_g2(msg, dst)
+    }
+
+    fn key_generator(params: &GroupParams) -> G1 {
+        params.g1().clone()
+    }
+
+    fn key_to_bytes(pk: &G1) -> Vec<u8> {
+        pk.to_compressed_bytes().to_vec()
+    }
+
+    fn pairing_product_is_one(pairs: Vec<(&G1, &G2)>) -> bool {
+        GT::ate_multi_pairing(pairs).is_one()
+    }
+}
+
+/// Domain separation tag used to hash messages in `scheme`
+pub fn scheme_dst(scheme: Scheme) -> &'static [u8] {
+    crate::bls::scheme_dst::<MinPk>(scheme)
+}
+
+/// Hash message to group G2
+pub fn hash_msg_to_g2(msg: &[u8]) -> G2 {
+    hash_to_g2(msg, DST)
+}
+
+// `hash_to_curve` is not implemented for group G2
+#[allow(deprecated)]
+fn hash_to_g2(msg: &[u8], dst: &[u8]) -> G2 {
+    G2::from_msg_hash(&domain_separated(msg, dst))
+}
+
+#[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct SecretKey(CurveOrderElement);
+
+impl fmt::Debug for SecretKey {
+    /// Does not show the key
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("SecretKey(..)")
+    }
+}
+
+impl PartialEq for SecretKey {
+    /// Constant time in the keys
+    fn eq(&self, other: &Self) -> bool {
+        ct::scalar_eq(&self.0, &other.0)
+    }
+}
+
+impl SecretKey {
+    pub fn random() -> Self {
+        Self(CurveOrderElement::random())
+    }
+
+    pub fn random_using_rng<R: RngCore + CryptoRng>(rng: &mut R) -> Self {
+        Self(CurveOrderElement::random_using_rng(rng))
+    }
+
+    pub fn sign(&self, msg: &[u8]) -> Signature {
+        Signature(sign::<MinPk>(&self.0, msg, Scheme::Basic, &GroupParams::default()))
+    }
+
+    /// Sign in the given scheme, for a public key with respect to the standard generator of group G1
+    pub fn sign_with_scheme(&self, msg: &[u8], scheme: Scheme) -> Signature {
+        self.sign_with_scheme_and_params(msg, scheme, &GroupParams::default())
+    }
+
+    /// Sign in the given scheme. The parameters determine the public key prepended to the message
+    /// in the message augmentation scheme.
+    pub fn sign_with_scheme_and_params(
+        &self,
+        msg: &[u8],
+        scheme: Scheme,
+        params: &GroupParams,
+    ) -> Signature {
+        Signature(sign::<MinPk>(&self.0, msg, scheme, params))
+    }
+
+    /// Proof of possession of the secret key, a signature on the public key
+    pub fn prove_possession(&self) -> Signature {
+        self.prove_possession_with_params(&GroupParams::default())
+    }
+
+    pub fn prove_possession_with_params(&self, params: &GroupParams) -> Signature {
+        Signature(prove_possession::<MinPk>(&self.0, params))
+    }
+
+    /// Secret key of `PublicKey::tweak_add` with the same tweak
+    pub fn tweak_add(&self, tweak: &CurveOrderElement) -> Result<Self, SignatureError> {
+        let x = &self.0 + tweak;
+        if x.is_zero() {
+            return Err(SignatureError::new());
+        }
+        Ok(Self(x))
+    }
+
+    /// Secret key of `PublicKey::derive_child`, for a public key with respect to the standard
+    /// generator of group G1
+    pub fn derive_child(&self, index: u32) -> Result<Self, SignatureError> {
+        self.derive_child_with_params(index, &GroupParams::default())
+    }
+
+    pub fn derive_child_with_params(
+        &self,
+        index: u32,
+        params: &GroupParams,
+    ) -> Result<Self, SignatureError> {
+        let pk = PublicKey::new_with_params(self, params);
+        self.tweak_add(&child_tweak::<MinPk>(&pk.0, index))
+    }
+
+    pub fn as_scalar(&self) -> &CurveOrderElement {
+        &self.0
+    }
+}
+
+impl From<CurveOrderElement> for SecretKey {
+    fn from(x: CurveOrderElement) -> Self {
+        Self(x)
+    }
+}
+
+impl Signer<Signature> for SecretKey {
+    fn try_sign(&self, msg: &[u8]) -> Result<Signature, SignatureError> {
+        Ok(self.sign(msg))
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct PublicKey(G1);
+
+impl PublicKey {
+    /// Public key for the standard generator of group G1
+    pub fn new(sk: &SecretKey) -> Self {
+        Self::new_with_params(sk, &GroupParams::default())
+    }
+
+    pub fn new_with_params(sk: &SecretKey, params: &GroupParams) -> Self {
+        Self(params.g1() * &sk.0)
+    }
+
+    /// Sum of the public keys. Only secure when the keys come with a proof of possession.
+    pub fn aggregate(keys: &[PublicKey]) -> Self {
+        Self(aggregate(keys.iter().map(|k| &k.0)))
+    }
+
+    /// Check `e(g1, sig) == e(pk, H(msg))`
+    pub fn verify_with_params(&self, msg: &[u8], sig: &Signature, params: &GroupParams) -> bool {
+        self.verify_with_scheme_and_params(msg, sig, Scheme::Basic, params)
+    }
+
+    /// Verify a signature made in the given scheme, with respect to the standard generator of
+    /// group G1
+    pub fn verify_with_scheme(&self, msg: &[u8], sig: &Signature, scheme: Scheme) -> bool {
+        self.verify_with_scheme_and_params(msg, sig, scheme, &GroupParams::default())
+    }
+
+    pub fn verify_with_scheme_and_params(
+        &self,
+        msg: &[u8],
+        sig: &Signature,
+        scheme: Scheme,
+        params: &GroupParams,
+    ) -> bool {
+        self.verify_with_policy(msg, sig, scheme, params, IdentityPolicy::Reject)
+    }
+
+    /// `verify_with_scheme_and_params` accepting an identity public key or signature if `policy`
+    /// does. The other entry points reject them, as an identity key verifies the identity
+    /// signature on every message.
+    pub fn verify_with_policy(
+        &self,
+        msg: &[u8],
+        sig: &Signature,
+        scheme: Scheme,
+        params: &GroupParams,
+        policy: IdentityPolicy,
+    ) -> bool {
+        verify::<MinPk>(&self.0, msg, &sig.0, scheme, params, policy)
+    }
+
+    /// Check the proof of possession of the secret key, with respect to the standard generator
+    /// of group G1
+    pub fn verify_possession(&self, proof: &Signature) -> bool {
+        self.verify_possession_with_params(proof, &GroupParams::default())
+    }
+
+    pub fn verify_possession_with_params(&self, proof: &Signature, params: &GroupParams) -> bool {
+        verify_possession::<MinPk>(&self.0, &proof.0, params)
+    }
+
+    pub fn to_bytes(&self) -> [u8; PUBLIC_KEY_SIZE] {
+        self.0.to_compressed_bytes()
+    }
+
+    pub fn as_point(&self) -> &G1 {
+        &self.0
+    }
+
+    /// `pk + g1*tweak` for the standard generator of group G1, fails if the result is the identity
+    pub fn tweak_add(&self, tweak: &CurveOrderElement) -> Result<Self, SignatureError> {
+        self.tweak_add_with_params(tweak, &GroupParams::default())
+    }
+
+    pub fn tweak_add_with_params(
+        &self,
+        tweak: &CurveOrderElement,
+        params: &GroupParams,
+    ) -> Result<Self, SignatureError> {
+        tweak_add::<MinPk>(&self.0, tweak, params).map(Self)
+    }
+
+    /// Child key `index`, tweaked by the hash of the public key and index, so it can be derived
+    /// without the secret key. Child keys need their own proofs of possession.
+    pub fn derive_child(&self, index: u32) -> Result<Self, SignatureError> {
+        self.derive_child_with_params(index, &GroupParams::default())
+    }
+
+    pub fn derive_child_with_params(
+        &self,
+        index: u32,
+        params: &GroupParams,
+    ) -> Result<Self, SignatureError> {
+        self.tweak_add_with_params(&child_tweak::<MinPk>(&self.0, index), params)
+    }
+
+    /// Compressed public key, the identity only accepted if `policy` does. Non-canonical
+    /// encodings and points not in the subgroup are always rejected.
+    pub fn from_bytes_with_policy(
+        bytes: &[u8],
+        policy: IdentityPolicy,
+    ) -> Result<Self, SignatureError> {
+        g1_from_compressed(bytes, policy).map(Self)
+    }
+}
+
+impl From<&SecretKey> for PublicKey {
+    fn from(sk: &SecretKey) -> Self {
+        Self::new(sk)
+    }
+}
+
+impl TryFrom<&[u8]> for PublicKey {
+    type Error = SignatureError;
+
+    fn try_from(bytes: &[u8]) -> Result<Self, SignatureError> {
+        Self::from_bytes_with_policy(bytes, IdentityPolicy::Reject)
+    }
+}
+
+impl Verifier<Signature> for PublicKey {
+    fn verify(&self, msg: &[u8], sig: &Signature) -> Result<(), SignatureError> {
+        if self.verify_with_params(msg, sig, &GroupParams::default()) {
+            Ok(())
+        } else {
+            Err(SignatureError::new())
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Signature(G2);
+
+impl Signature {
+    /// Sum of the signatures. The aggregate verifies against the aggregated public key when all
+    /// signatures are over the same message.
+    pub fn aggregate(sigs: &[Signature]) -> Self {
+        Self(aggregate(sigs.iter().map(|s| &s.0)))
+    }
+
+    /// Verify an aggregate of signatures made in the given scheme, each by a public key on its
+    /// message, with respect to the standard generator of group G1
+    pub fn verify_aggregate(&self, signed: &[(PublicKey, &[u8])], scheme: Scheme) -> bool {
+        self.verify_aggregate_with_params(signed, scheme, &GroupParams::default())
+    }
+
+    /// Check `e(g1, sig) == prod e(pk_i, H_i)` with `H_i` the hash of the `i`th message in the
+    /// scheme. The basic scheme rejects repeated messages.
+    pub fn verify_aggregate_with_params(
+        &self,
+        signed: &[(PublicKey, &[u8])],
+        scheme: Scheme,
+        params: &GroupParams,
+    ) -> bool {
+        self.verify_aggregate_with_policy(signed, scheme, params, IdentityPolicy::Reject)
+    }
+
+    /// `verify_aggregate_with_params` accepting identity public keys or an identity aggregate if
+    /// `policy` does
+    pub fn verify_aggregate_with_policy(
+        &self,
+        signed: &[(PublicKey, &[u8])],
+        scheme: Scheme,
+        params: &GroupParams,
+        policy: IdentityPolicy,
+    ) -> bool {
+        let signed: Vec<_> = signed.iter().map(|(pk, msg)| (&pk.0, *msg)).collect();
+        verify_aggregate::<MinPk>(&self.0, &signed, scheme, params, policy)
+    }
+
+    pub fn as_point(&self) -> &G2 {
+        &self.0
+    }
+
+    /// Compressed signature, the identity only accepted if `policy` does. Non-canonical
+    /// encodings and points not in the subgroup are always rejected.
+    pub fn from_bytes_with_policy(
+        bytes: &[u8],
+        policy: IdentityPolicy,
+    ) -> Result<Self, SignatureError> {
+        g2_from_compressed(bytes, policy).map(Self)
+    }
+}
+
+impl TryFrom<&[u8]> for Signature {
+    type Error = SignatureError;
+
+    fn try_from(bytes: &[u8]) -> Result<Self, SignatureError> {
+        Self::from_bytes_with_policy(bytes, IdentityPolicy::Reject)
+    }
+}
+
+impl TryFrom<[u8; SIGNATURE_SIZE]> for Signature {
+    type Error = SignatureError;
+
+    fn try_from(bytes: [u8; SIGNATURE_SIZE]) -> Result<Self, SignatureError> {
+        Self::from_bytes_with_policy(&bytes, IdentityPolicy::Reject)
+    }
+}
+
+impl From<Signature> for [u8; SIGNATURE_SIZE] {
+    fn from(sig: Signature) -> Self {
+        sig.0.to_compressed_bytes()
+    }
+}
+
+impl SignatureEncoding for Signature {
+    type Repr = [u8; SIGNATURE_SIZE];
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_secret_key_eq_and_debug() {
+        let sk = SecretKey::random();
+        assert_eq!(sk, sk.clone());
+        assert_ne!(sk, SecretKey::random());
+        assert_eq!(format!("{:?}", sk), "SecretKey(..)");
+    }
+
+    #[test]
+    fn test_sign_verify() {
+        let sk = SecretKey::random();
+        let pk = PublicKey::from(&sk);
+        let msg = b"test message";
+        let sig = sk.sign(msg);
+        assert!(pk.verify(msg, &sig).is_ok());
+        assert!(pk.verify(b"another message", &sig).is_err());
+        assert!(PublicKey::from(&SecretKey::random()).verify(msg, &sig).is_err());
+
+        let params = GroupParams::from_label(b"test");
+        let pk = PublicKey::new_with_params(&sk, &params);
+        assert!(pk.verify_with_params(msg, &sig, &params));
+        assert!(pk.verify(msg, &sig).is_err());
+
+        // Same secret key, opposite orientation of `bls`
+        let g2_sig = crate::bls::SecretKey::from(sk.as_scalar().clone()).sign(msg);
+        assert_eq!(pk.to_bytes().len(), g2_sig.to_bytes().len());
+        assert_eq!(sig.to_bytes().len(), crate::bls::PUBLIC_KEY_SIZE);
+    }
+
+    #[test]
+    fn test_tweak_and_derive_child() {
+        let sk = SecretKey::random();
+        let pk = PublicKey::from(&sk);
+        let tweak = CurveOrderElement::random();
+        assert_eq!(PublicKey::from(&sk.tweak_add(&tweak).unwrap()), pk.tweak_add(&tweak).unwrap());
+        assert!(pk.tweak_add(&-sk.as_scalar()).is_err());
+
+        let msg = b"test message";
+        let child_sk = sk.derive_child(7).unwrap();
+        let child_pk = pk.derive_child(7).unwrap();
+        assert_eq!(PublicKey::from(&child_sk), child_pk);
+        assert_ne!(pk.derive_child(8).unwrap(), child_pk);
+        assert!(child_pk.verify(msg, &child_sk.sign(msg)).is_ok());
+        assert!(pk.verify(msg, &child_sk.sign(msg)).is_err());
+    }
+
+    #[test]
+    fn test_encoding_and_identity_policy() {
+        let sk = SecretKey::random();
+        let pk = PublicKey::from(&sk);
+        let sig = sk.sign(b"test message");
+        let sig_bytes = sig.to_bytes();
+        assert_eq!(sig_bytes.len(), SIGNATURE_SIZE);
+        assert_eq!(Signature::try_from(&sig_bytes[..]).unwrap(), sig);
+        assert!(Signature::try_from(&sig_bytes[1..]).is_err());
+        assert_eq!(PublicKey::try_from(&pk.to_bytes()[..]).unwrap(), pk);
+
+        let identity_pk = PublicKey(G1::identity());
+        let identity_sig = Signature(G2::identity());
+        let pk_bytes = identity_pk.to_bytes();
+        let sig_bytes = identity_sig.to_bytes();
+        assert!(PublicKey::try_from(&pk_bytes[..]).is_err());
+        assert!(Signature::try_from(&sig_bytes[..]).is_err());
+        assert_eq!(
+            PublicKey::from_bytes_with_policy(&pk_bytes, IdentityPolicy::Accept).unwrap(),
+            identity_pk
+        );
+        assert_eq!(
+            Signature::from_bytes_with_policy(&sig_bytes, IdentityPolicy::Accept).unwrap(),
+            identity_sig
+        );
+        let msg = b"test message";
+        assert!(!identity_pk.verify_with_scheme(msg, &identity_sig, Scheme::Basic));
+        assert!(identity_pk.verify_with_policy(
+            msg,
+            &identity_sig,
+            Scheme::Basic,
+            &GroupParams::default(),
+            IdentityPolicy::Accept
+        ));
+    }
+
+    #[test]
+    fn test_decode_garbage_and_non_canonical() {
+        use crate::constants::MODULUS;
+        use crate::types::BigNum;
+
+        // Random bytes are only accepted when they are the exact encoding of a point, and never
+        // taken for the identity
+        let mut rng = rand::thread_rng();
+        for _ in 0..50 {
+            let mut bytes = [0u8; SIGNATURE_SIZE];
+            rng.fill_bytes(&mut bytes);
+            if let Ok(p) = Signature::from_bytes_with_policy(&bytes, IdentityPolicy::Accept) {
+                assert!(!p.0.is_identity());
+                assert_eq!(p.to_bytes()[..], bytes[..]);
+            }
+            let bytes = &bytes[..PUBLIC_KEY_SIZE];
+            if let Ok(p) = PublicKey::from_bytes_with_policy(bytes, IdentityPolicy::Accept) {
+                assert!(!p.0.is_identity());
+                assert_eq!(p.to_bytes()[..], *bytes);
+            }
+        }
+
+        // `x + p` in place of the coordinate `x` decodes to the same point but is rejected
+        let add_modulus = |bytes: &mut [u8], flags: u8| {
+            let top = bytes[0] & flags;
+            bytes[0] &= !flags;
+            let mut x = BigNum::frombytes(bytes);
+            x.add(&MODULUS);
+            x.norm();
+            x.tobytes(bytes);
+            bytes[0] |= top;
+        };
+        let sk = SecretKey::random();
+        let pk = PublicKey::from(&sk);
+        let sig = sk.sign(b"test message");
+        let mut bytes = pk.to_bytes();
+        add_modulus(&mut bytes, 0x80);
+        assert_eq!(G1::from(&bytes), pk.0);
+        assert!(PublicKey::from_bytes_with_policy(&bytes, IdentityPolicy::Accept).is_err());
+        let mut bytes = sig.to_bytes();
+        add_modulus(&mut bytes[FIELD_ORDER_ELEMENT_SIZE..], 0);
+        assert_eq!(G2::from(&bytes), sig.0);
+        assert!(Signature::from_bytes_with_policy(&bytes, IdentityPolicy::Accept).is_err());
+    }
+
+    #[test]
+    fn test_schemes_and_aggregate() {
+        let msg = b"test message";
+        let sks: Vec<SecretKey> = (0..3).map(|_| SecretKey::random()).collect();
+        let pks: Vec<PublicKey> = sks.iter().map(PublicKey::from).collect();
+        let schemes = [
+            Scheme::Basic,
+            Scheme::MessageAugmentation,
+            Scheme::ProofOfPossession,
+        ];
+        for scheme in schemes.iter() {
+            let sig = sks[0].sign_with_scheme(msg, *scheme);
+            assert!(pks[0].verify_with_scheme(msg, &sig, *scheme));
+            if *scheme != Scheme::MessageAugmentation {
+                #[allow(deprecated)]
+                let h = G2::from_msg_hash(&domain_separated(msg, scheme_dst(*scheme)));
+                assert_eq!(sig.as_point(), &(h * sks[0].as_scalar()));
+            }
+            for other in schemes.iter().filter(|s| *s != scheme) {
+                assert!(!pks[0].verify_with_scheme(msg, &sig, *other));
+            }
+        }
+
+        let distinct: Vec<&[u8]> = vec![b"message 1", b"message 2", b"message 3"];
+        for scheme in schemes.iter() {
+            for msgs in [vec![&msg[..]; 3], distinct.clone()].iter() {
+                let sigs: Vec<Signature> = sks
+                    .iter()
+                    .zip(msgs.iter())
+                    .map(|(sk, m)| sk.sign_with_scheme(m, *scheme))
+                    .collect();
+                let agg = Signature::aggregate(&sigs);
+                let signed: Vec<(PublicKey, &[u8])> =
+                    pks.iter().cloned().zip(msgs.iter().cloned()).collect();
+                let expected = *scheme != Scheme::Basic || msgs[0] != msgs[1];
+                assert_eq!(agg.verify_aggregate(&signed, *scheme), expected);
+                assert!(!agg.verify_aggregate(&signed[1..], *scheme));
+            }
+        }
+
+        // Proofs of possession allow verifying against the aggregated public key
+        let sigs: Vec<Signature> = sks
+            .iter()
+            .map(|sk| sk.sign_with_scheme(msg, Scheme::ProofOfPossession))
+            .collect();
+        for (sk, pk) in sks.iter().zip(pks.iter()) {
+            assert!(pk.verify_possession(&sk.prove_possession()));
+        }
+        assert!(!pks[0].verify_possession(&sks[1].prove_possession()));
+        assert!(PublicKey::aggregate(&pks).verify_with_scheme(
+            msg,
+            &Signature::aggregate(&sigs),
+            Scheme::ProofOfPossession
+        ));
+    }
+}
@@ -0,0 +1,212 @@
+//! Group elements known to be in the subgroup of the curve order, so APIs needing prime order
+//! inputs, like pairings and verification, can demand them in their types instead of checking at
+//! runtime or forgetting to. A `CheckedG1` or `CheckedG2` is only made by checking an element, by
+//! strict decoding, from the generator or by arithmetic on checked elements, as the subgroup is
+//! closed under addition and scalar multiplication. Deserialization with serde checks as well.
+
+use crate::curve_order_elem::CurveOrderElement;
+use crate::encoding::{ElementEncoding, IdentityPolicy};
+use crate::errors::{CryptoError, SerzDeserzError};
+use crate::group_elem::GroupElement;
+use crate::group_elem_g1::G1;
+#[cfg(any(feature = "bls381", feature = "bn254"))]
+use crate::{extension_field_gt::GT, group_elem_g2::G2};
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+use std::convert::TryFrom;
+use std::ops::{Add, Mul, Neg, Sub};
+
+macro_rules! impl_checked_group_elem {
+    ( $checked:ident, $group_element:ident, $group_element_str:literal ) => {
+        /// Element of the subgroup of the curve order
+        #[derive(Clone, Debug, PartialEq, Eq, Hash)]
+        #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+        #[cfg_attr(
+            feature = "serde",
+            serde(try_from = $group_element_str, into = $group_element_str)
+        )]
+        pub struct $checked($group_element);
+
+        impl $checked {
+            pub fn generator() -> Self {
+                Self($group_element::generator())
+            }
+
+            pub fn identity() -> Self {
+                Self($group_element::identity())
+            }
+
+            pub fn random() -> Self {
+                Self($group_element::random())
+            }
+
+            /// `ElementEncoding::decode`, which rejects elements outside the subgroup
+            pub fn decode(bytes: &[u8]) -> Result<Self, SerzDeserzError> {
+                <$group_element as ElementEncoding>::decode(bytes).map(Self)
+            }
+
+            /// `ElementEncoding::decode_with_policy`, which rejects elements outside the subgroup
+            pub fn decode_with_policy(
+                bytes: &[u8],
+                policy: IdentityPolicy,
+            ) -> Result<Self, SerzDeserzError> {
+                <$group_element as ElementEncoding>::decode_with_policy(bytes, policy).map(Self)
+            }
+
+            pub fn as_inner(&self) -> &$group_element {
+                &self.0
+            }
+
+            pub fn into_inner(self) -> $group_element {
+                self.0
+            }
+
+            /// `self * s` in constant time
+            pub fn scalar_mul_const_time(&self, s: &CurveOrderElement) -> Self {
+                Self(self.0.scalar_mul_const_time(s))
+            }
+        }
+
+        impl TryFrom<$group_element> for $checked {
+            type Error = CryptoError;
+
+            /// Fails with `CryptoError::InvalidPoint` outside the subgroup
+            fn try_from(elem: $group_element) -> Result<Self, CryptoError> {
+                if !elem.has_correct_order() {
+                    return Err(CryptoError::InvalidPoint);
+                }
+                Ok(Self(elem))
+            }
+        }
+
+        impl From<$checked> for $group_element {
+            fn from(elem: $checked) -> Self {
+                elem.0
+            }
+        }
+
+        impl AsRef<$group_element> for $checked {
+            fn as_ref(&self) -> &$group_element {
+                &self.0
+            }
+        }
+
+        impl<'a> Add<&'a $checked> for &'a $checked {
+            type Output = $checked;
+
+            fn add(self, other: &$checked) -> $checked {
+                $checked(&self.0 + &other.0)
+            }
+        }
+
+        impl<'a> Sub<&'a $checked> for &'a $checked {
+            type Output = $checked;
+
+            fn sub(self, other: &$checked) -> $checked {
+                $checked(&self.0 - &other.0)
+            }
+        }
+
+        impl Neg for &$checked {
+            type Output = $checked;
+
+            fn neg(self) -> $checked {
+                $checked(-&self.0)
+            }
+        }
+
+        impl<'a> Mul<&'a CurveOrderElement> for &'a $checked {
+            type Output = $checked;
+
+            fn mul(self, s: &CurveOrderElement) -> $checked {
+                $checked(&self.0 * s)
+            }
+        }
+    };
+}
+
+impl_checked_group_elem!(CheckedG1, G1, "G1");
+#[cfg(any(feature = "bls381", feature = "bn254"))]
+impl_checked_group_elem!(CheckedG2, G2, "G2");
+
+/// `GT::ate_pairing` of checked elements
+#[cfg(any(feature = "bls381", feature = "bn254"))]
+pub fn pairing(g1: &CheckedG1, g2: &CheckedG2) -> GT {
+    GT::ate_pairing(&g1.0, &g2.0)
+}
+
+/// `GT::ate_multi_pairing` of checked elements
+#[cfg(any(feature = "bls381", feature = "bn254"))]
+pub fn multi_pairing(elems: &[(&CheckedG1, &CheckedG2)]) -> GT {
+    GT::ate_multi_pairing(elems.iter().map(|(a, b)| (&a.0, &b.0)).collect())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_checked_g1() {
+        let g = CheckedG1::generator();
+        let s = CurveOrderElement::random();
+        let p = &g * &s;
+        assert_eq!(p.as_inner(), &(G1::generator() * &s));
+        assert_eq!(&(&p + &g) - &g, p);
+        assert!((&p + &(-&p)).as_inner().is_identity());
+        assert_eq!(p.scalar_mul_const_time(&s), &p * &s);
+
+        let q = G1::random();
+        assert_eq!(CheckedG1::try_from(q.clone()).unwrap().into_inner(), q);
+        let bytes = q.encode();
+        assert_eq!(CheckedG1::decode(&bytes).unwrap().as_inner(), &q);
+        assert!(CheckedG1::decode_with_policy(
+            &CheckedG1::identity().as_inner().encode(),
+            IdentityPolicy::Reject
+        )
+        .is_err());
+
+        #[cfg(feature = "serde")]
+        {
+            let s = serde_json::to_string(&p).unwrap();
+            assert_eq!(s, serde_json::to_string(p.as_inner()).unwrap());
+            let back: CheckedG1 = serde_json::from_str(&s).unwrap();
+            assert_eq!(back, p);
+        }
+    }
+
+    #[cfg(any(feature = "bls381", feature = "bn254"))]
+    #[test]
+    fn test_checked_g2_and_pairing() {
+        let a = CurveOrderElement::random();
+        let p = &CheckedG1::generator() * &a;
+        let q = CheckedG2::random();
+        assert_eq!(
+            pairing(&p, &q),
+            GT::ate_pairing(&G1::generator(), &(q.as_inner() * &a))
+        );
+        assert!(multi_pairing(&[(&p, &q), (&(-&p), &q)]).is_one());
+
+        // Points of the curve with small x are outside the subgroup but for negligible probability
+        let outside = (1u8..)
+            .map(|x| {
+                let mut bytes = [0u8; G2::COMPRESSED_SIZE];
+                bytes[G2::COMPRESSED_SIZE - 1] = x;
+                G2::from(&bytes)
+            })
+            .find(|p| !p.is_identity())
+            .unwrap();
+        #[cfg(feature = "serde")]
+        {
+            let s = serde_json::to_string(&q).unwrap();
+            let back: CheckedG2 = serde_json::from_str(&s).unwrap();
+            assert_eq!(back, q);
+            let s = serde_json::to_string(&outside).unwrap();
+            assert!(serde_json::from_str::<CheckedG2>(&s).is_err());
+        }
+        assert!(matches!(
+            CheckedG2::try_from(outside),
+            Err(CryptoError::InvalidPoint)
+        ));
+    }
+}
@@ -0,0 +1,247 @@
+//! Byte layout for protocol structs made of scalars and group elements, like proofs and statements,
+//! so they don't each need an ad-hoc layout. An `Encoder` writes a header and then the fields in
+//! order, a `Decoder` reads them back in the same order:
+//!
+//! - header: codec version (1 byte), curve tag (1 byte), label length (1 byte) and the label
+//!   naming the struct, e.g. `b"my-protocol/proof"`
+//! - element: the canonical encoding of `ElementEncoding`
+//! - vector of elements: big-endian `u32` length and the encoding of each element, the layout of
+//!   the `to_bytes` of vectors
+//! - byte string: big-endian `u32` length and the bytes
+//! - integer: 8 big-endian bytes
+//!
+//! Decoding checks the header, decodes elements strictly and rejects trailing bytes, so bytes of
+//! another struct, another curve or another version are not accepted.
+
+use crate::encoding::ElementEncoding;
+use crate::errors::SerzDeserzError;
+
+pub const CODEC_VERSION: u8 = 1;
+
+/// Identifier of the curve the crate is built for
+#[cfg(feature = "bls381")]
+pub const CURVE_TAG: u8 = 1;
+#[cfg(feature = "bn254")]
+pub const CURVE_TAG: u8 = 2;
+#[cfg(feature = "secp256k1")]
+pub const CURVE_TAG: u8 = 3;
+#[cfg(feature = "ed25519")]
+pub const CURVE_TAG: u8 = 4;
+#[cfg(feature = "nist256")]
+pub const CURVE_TAG: u8 = 5;
+
+/// Struct with a codec layout. Implementations write and read their fields in the same order.
+pub trait Codec: Sized {
+    /// Names the struct in the header, at most 255 bytes
+    const LABEL: &'static [u8];
+
+    fn encode_fields(&self, encoder: &mut Encoder);
+
+    fn decode_fields(decoder: &mut Decoder) -> Result<Self, SerzDeserzError>;
+
+    fn to_codec_bytes(&self) -> Vec<u8> {
+        let mut encoder = Encoder::new(Self::LABEL);
+        self.encode_fields(&mut encoder);
+        encoder.finish()
+    }
+
+    fn from_codec_bytes(bytes: &[u8]) -> Result<Self, SerzDeserzError> {
+        let mut decoder = Decoder::new(bytes, Self::LABEL)?;
+        let s = Self::decode_fields(&mut decoder)?;
+        decoder.finish()?;
+        Ok(s)
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Encoder {
+    bytes: Vec<u8>,
+}
+
+impl Encoder {
+    /// Start with the header for `label`. Panics if the label is longer than 255 bytes.
+    pub fn new(label: &[u8]) -> Self {
+        assert!(
+            label.len() <= u8::MAX as usize,
+            "label longer than 255 bytes"
+        );
+        let mut bytes = vec![CODEC_VERSION, CURVE_TAG, label.len() as u8];
+        bytes.extend_from_slice(label);
+        Self { bytes }
+    }
+
+    pub fn append<E: ElementEncoding>(&mut self, elem: &E) -> &mut Self {
+        self.bytes.extend_from_slice(&elem.encode());
+        self
+    }
+
+    pub fn append_vec<E: ElementEncoding>(&mut self, elems: &[E]) -> &mut Self {
+        self.bytes.extend_from_slice(&E::encode_vec(elems));
+        self
+    }
+
+    pub fn append_bytes(&mut self, bytes: &[u8]) -> &mut Self {
+        self.bytes
+            .extend_from_slice(&(bytes.len() as u32).to_be_bytes());
+        self.bytes.extend_from_slice(bytes);
+        self
+    }
+
+    pub fn append_u64(&mut self, n: u64) -> &mut Self {
+        self.bytes.extend_from_slice(&n.to_be_bytes());
+        self
+    }
+
+    pub fn finish(self) -> Vec<u8> {
+        self.bytes
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Decoder<'a> {
+    bytes: &'a [u8],
+}
+
+impl<'a> Decoder<'a> {
+    /// Check the header of `bytes` for the version, the curve and `label`
+    pub fn new(bytes: &'a [u8], label: &[u8]) -> Result<Self, SerzDeserzError> {
+        let mut decoder = Self { bytes };
+        let header = decoder.take(3)?;
+        if header[0] != CODEC_VERSION {
+            return Err(SerzDeserzError::UnsupportedCodecVersion(header[0]));
+        }
+        if header[1] != CURVE_TAG {
+            return Err(SerzDeserzError::CurveMismatch(header[1], CURVE_TAG));
+        }
+        if decoder.take(header[2] as usize)? != label {
+            return Err(SerzDeserzError::LabelMismatch);
+        }
+        Ok(decoder)
+    }
+
+    pub fn read<E: ElementEncoding>(&mut self) -> Result<E, SerzDeserzError> {
+        E::decode(self.take(E::ENCODED_SIZE)?)
+    }
+
+    pub fn read_vec<E: ElementEncoding>(&mut self) -> Result<Vec<E>, SerzDeserzError> {
+        let len = self.read_len()?;
+        let size = len
+            .checked_mul(E::ENCODED_SIZE)
+            .ok_or(SerzDeserzError::UnexpectedEnd)?;
+        self.take(size)?
+            .chunks(E::ENCODED_SIZE)
+            .map(E::decode)
+            .collect()
+    }
+
+    pub fn read_bytes(&mut self) -> Result<&'a [u8], SerzDeserzError> {
+        let len = self.read_len()?;
+        self.take(len)
+    }
+
+    pub fn read_u64(&mut self) -> Result<u64, SerzDeserzError> {
+        Ok(u64::from_be_bytes(*array_ref![self.take(8)?, 0, 8]))
+    }
+
+    /// Fails if there are bytes left
+    pub fn finish(self) -> Result<(), SerzDeserzError> {
+        if !self.bytes.is_empty() {
+            return Err(SerzDeserzError::TrailingBytes(self.bytes.len()));
+        }
+        Ok(())
+    }
+
+    fn read_len(&mut self) -> Result<usize, SerzDeserzError> {
+        Ok(u32::from_be_bytes(*array_ref![self.take(4)?, 0, 4]) as usize)
+    }
+
+    fn take(&mut self, n: usize) -> Result<&'a [u8], SerzDeserzError> {
+        if n > self.bytes.len() {
+            return Err(SerzDeserzError::UnexpectedEnd);
+        }
+        let (head, tail) = self.bytes.split_at(n);
+        self.bytes = tail;
+        Ok(head)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::curve_order_elem::CurveOrderElement;
+    use crate::group_elem::GroupElement;
+    use crate::group_elem_g1::G1;
+
+    #[derive(Debug, PartialEq)]
+    struct TestProof {
+        round: u64,
+        commitment: G1,
+        responses: Vec<CurveOrderElement>,
+        context: Vec<u8>,
+    }
+
+    impl Codec for TestProof {
+        const LABEL: &'static [u8] = b"test/proof";
+
+        fn encode_fields(&self, encoder: &mut Encoder) {
+            encoder
+                .append_u64(self.round)
+                .append(&self.commitment)
+                .append_vec(&self.responses)
+                .append_bytes(&self.context);
+        }
+
+        fn decode_fields(decoder: &mut Decoder) -> Result<Self, SerzDeserzError> {
+            Ok(Self {
+                round: decoder.read_u64()?,
+                commitment: decoder.read()?,
+                responses: decoder.read_vec()?,
+                context: decoder.read_bytes()?.to_vec(),
+            })
+        }
+    }
+
+    struct OtherProof;
+
+    impl Codec for OtherProof {
+        const LABEL: &'static [u8] = b"test/other";
+
+        fn encode_fields(&self, _: &mut Encoder) {}
+
+        fn decode_fields(_: &mut Decoder) -> Result<Self, SerzDeserzError> {
+            Ok(Self)
+        }
+    }
+
+    #[test]
+    fn test_codec() {
+        let proof = TestProof {
+            round: 3,
+            commitment: G1::random(),
+            responses: (0..4).map(|_| CurveOrderElement::random()).collect(),
+            context: b"context".to_vec(),
+        };
+        let bytes = proof.to_codec_bytes();
+        assert_eq!(&bytes[..3], &[CODEC_VERSION, CURVE_TAG, 10]);
+        assert_eq!(TestProof::from_codec_bytes(&bytes).unwrap(), proof);
+
+        // Bytes of another struct, version or curve
+        assert!(OtherProof::from_codec_bytes(&bytes).is_err());
+        let mut other = bytes.clone();
+        other[0] += 1;
+        assert!(TestProof::from_codec_bytes(&other).is_err());
+        let mut other = bytes.clone();
+        other[1] += 1;
+        assert!(TestProof::from_codec_bytes(&other).is_err());
+
+        // Truncated, extended or corrupted bytes
+        assert!(TestProof::from_codec_bytes(&bytes[..bytes.len() - 1]).is_err());
+        let mut longer = bytes.clone();
+        longer.push(0);
+        assert!(TestProof::from_codec_bytes(&longer).is_err());
+        let mut corrupted = bytes.clone();
+        corrupted[3 + 10 + 8 + 5] ^= 1;
+        assert!(TestProof::from_codec_bytes(&corrupted).is_err());
+        assert!(TestProof::from_codec_bytes(&[]).is_err());
+    }
+}
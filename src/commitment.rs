@@ -1,13 +1,25 @@
 use crate::errors::ValueError;
 use crate::curve_order_elem::{CurveOrderElement, CurveOrderElementVector};
-use crate::group_elem::GroupElementVector;
+use crate::group_elem::{GroupElement, GroupElementVector};
 use crate::group_elem_g1::{G1Vector, G1};
+use crate::group_params::{Generators, GroupParams};
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+use std::iter::once;
+
+const SUBSET_OPENING_DST: &[u8] = b"AMCL_WRAPPER_PEDERSEN_SUBSET_OPENING";
 
 /// Commit to field element `elem` with randomness `r` given groups elements `g` and `h`, i.e. compute g^elem.h^r
 pub fn commit_to_field_element(g: &G1, h: &G1, elem: &CurveOrderElement, r: &CurveOrderElement) -> G1 {
     g.binary_scalar_mul(h, elem, r)
 }
 
+/// Commit to field element `elem` with randomness `r` using the G1 generator `g` of `params` and group element `h`, i.e. compute g^elem.h^r
+pub fn commit_to_field_element_with_params(params: &GroupParams, h: &G1, elem: &CurveOrderElement, r: &CurveOrderElement) -> G1 {
+    commit_to_field_element(params.g1(), h, elem, r)
+}
+
 /// Commit to field element vectors `a` and `b` with random field element `c`
 /// Given group element vectors `g` and `h` and group element `u`, compute
 /// (a1*g1 + a2*g2 + a3*g3) + (b1*h1 + b2*h2 + b3*h3) + c*u
@@ -35,3 +47,295 @@ pub fn commit_to_field_element_vectors(
 
     G1Vector::from(combined_g).inner_product_const_time(&combined_f)
 }
+
+/// Pedersen vector commitment `a1*g1 + a2*g2 + ... + an*gn + r*u` to `values` with the first `n`
+/// generators of `gens.g_vec()` and the generator `gens.u()`
+pub fn commit_to_vector(gens: &Generators, values: &CurveOrderElementVector, r: &CurveOrderElement) -> Result<G1, ValueError> {
+    check_capacity(gens, values.len())?;
+    let mut points = gens.g_vec()[..values.len()].to_vec();
+    points.push(gens.u().clone());
+    let scalars: Vec<CurveOrderElement> = values.iter().chain(once(r)).cloned().collect();
+    G1::msm(&points, &scalars)
+}
+
+fn check_capacity(gens: &Generators, len: usize) -> Result<(), ValueError> {
+    if len > gens.capacity() {
+        return Err(ValueError::OutOfRange(len));
+    }
+    Ok(())
+}
+
+/// Opening of a `commit_to_vector` commitment at a subset of positions, with a single proof for all
+/// of them rather than one per position. The proof is a Schnorr proof of knowledge of the values
+/// at the other positions and the randomness, so those stay hidden, and is verified with one
+/// multi-scalar multiplication. It has a response for every hidden position, so it is smallest
+/// when most positions are opened. Many openings, of the same or different commitments, are
+/// verified together with `batch_verify`.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct SubsetOpening {
+    len: usize,
+    positions: Vec<usize>,
+    values: CurveOrderElementVector,
+    t: G1,
+    responses: CurveOrderElementVector,
+}
+
+impl SubsetOpening {
+    /// Open the commitment to `values` with randomness `r` at `positions`, which must be
+    /// increasing. `context` is bound into the challenge, e.g. the id of the audit.
+    pub fn new(
+        gens: &Generators,
+        values: &CurveOrderElementVector,
+        r: &CurveOrderElement,
+        positions: &[usize],
+        context: &[u8],
+    ) -> Result<Self, ValueError> {
+        let len = values.len();
+        check_capacity(gens, len)?;
+        check_positions(positions, len)?;
+        let hidden = hidden_positions(positions, len);
+
+        let blindings: Vec<CurveOrderElement> = (0..=hidden.len()).map(|_| CurveOrderElement::random()).collect();
+        let points: Vec<G1> = hidden.iter().map(|&i| gens.g_vec()[i].clone()).chain(once(gens.u().clone())).collect();
+        let t = G1::msm(&points, &blindings)?;
+
+        let commitment = commit_to_vector(gens, values, r)?;
+        let revealed: CurveOrderElementVector = positions.iter().map(|&i| values[i].clone()).collect();
+        let c = subset_challenge(&commitment, len, positions, &revealed, &t, context);
+        let responses = hidden
+            .iter()
+            .map(|&i| &values[i])
+            .chain(once(r))
+            .zip(blindings)
+            .map(|(a, k)| k + &c * a)
+            .collect();
+        Ok(Self {
+            len,
+            positions: positions.to_vec(),
+            values: revealed,
+            t,
+            responses,
+        })
+    }
+
+    /// Length of the committed vector
+    pub fn vector_len(&self) -> usize {
+        self.len
+    }
+
+    /// The opened positions, increasing
+    pub fn positions(&self) -> &[usize] {
+        &self.positions
+    }
+
+    /// The values at the opened positions
+    pub fn values(&self) -> &CurveOrderElementVector {
+        &self.values
+    }
+
+    /// Check the opening against `commitment`
+    pub fn verify(&self, gens: &Generators, commitment: &G1, context: &[u8]) -> bool {
+        verify_openings(gens, &[(commitment, self, context)], CurveOrderElement::one)
+    }
+
+    /// Verify all openings, each of a commitment with its own context, with one multi-scalar
+    /// multiplication over random linear combinations of their checks. Fails if any opening is
+    /// invalid, without telling which; verify individually to find it. An empty batch is valid.
+    pub fn batch_verify(gens: &Generators, batch: &[(&G1, &SubsetOpening, &[u8])]) -> bool {
+        verify_openings(gens, batch, CurveOrderElement::random)
+    }
+
+    /// Whether positions, values and responses are consistent, as deserialized openings might not be
+    fn is_well_formed(&self, gens: &Generators) -> bool {
+        self.len <= gens.capacity()
+            && check_positions(&self.positions, self.len).is_ok()
+            && self.values.len() == self.positions.len()
+            && self.responses.len() == self.len - self.positions.len() + 1
+    }
+}
+
+/// Positions must be increasing and less than `len`
+fn check_positions(positions: &[usize], len: usize) -> Result<(), ValueError> {
+    for (j, &i) in positions.iter().enumerate() {
+        if i >= len || (j > 0 && i <= positions[j - 1]) {
+            return Err(ValueError::OutOfRange(i));
+        }
+    }
+    Ok(())
+}
+
+/// Positions less than `len` not in the increasing `positions`
+fn hidden_positions(positions: &[usize], len: usize) -> Vec<usize> {
+    let mut opened = positions.iter().peekable();
+    (0..len)
+        .filter(|i| {
+            if opened.peek() == Some(&i) {
+                opened.next();
+                false
+            } else {
+                true
+            }
+        })
+        .collect()
+}
+
+fn subset_challenge(
+    commitment: &G1,
+    len: usize,
+    positions: &[usize],
+    values: &CurveOrderElementVector,
+    t: &G1,
+    context: &[u8],
+) -> CurveOrderElement {
+    let mut bytes = SUBSET_OPENING_DST.to_vec();
+    bytes.extend_from_slice(&commitment.to_vec());
+    bytes.extend_from_slice(&(len as u64).to_be_bytes());
+    bytes.extend_from_slice(&(positions.len() as u64).to_be_bytes());
+    for (i, v) in positions.iter().zip(values.iter()) {
+        bytes.extend_from_slice(&(*i as u64).to_be_bytes());
+        bytes.extend_from_slice(&v.to_bytes());
+    }
+    bytes.extend_from_slice(&t.to_vec());
+    bytes.extend_from_slice(context);
+    CurveOrderElement::from_msg_hash(&bytes)
+}
+
+/// Check `sum w*(s_i*g_i + s_r*u + c*a_j*g_j - c*C - t) = 0`, for hidden positions `i` and opened
+/// positions `j`, with weights `w` from `weight`. Terms on the same generator are merged.
+fn verify_openings(
+    gens: &Generators,
+    batch: &[(&G1, &SubsetOpening, &[u8])],
+    weight: impl Fn() -> CurveOrderElement,
+) -> bool {
+    if batch.iter().any(|(_, o, _)| !o.is_well_formed(gens)) {
+        return false;
+    }
+    let max_len = batch.iter().map(|(_, o, _)| o.len).max().unwrap_or(0);
+    let mut g_scalars = vec![CurveOrderElement::zero(); max_len];
+    let mut u_scalar = CurveOrderElement::zero();
+    let mut points = Vec::with_capacity(max_len + 1 + 2 * batch.len());
+    let mut scalars = Vec::with_capacity(max_len + 1 + 2 * batch.len());
+    for (commitment, opening, context) in batch {
+        let c = subset_challenge(
+            commitment,
+            opening.len,
+            &opening.positions,
+            &opening.values,
+            &opening.t,
+            context,
+        );
+        let w = weight();
+        let wc = &w * &c;
+        for (&i, a) in opening.positions.iter().zip(opening.values.iter()) {
+            g_scalars[i] += &wc * a;
+        }
+        let hidden = hidden_positions(&opening.positions, opening.len);
+        for (&i, s) in hidden.iter().zip(opening.responses.iter()) {
+            g_scalars[i] += &w * s;
+        }
+        u_scalar += &w * &opening.responses[hidden.len()];
+        points.push((*commitment).clone());
+        scalars.push(-&wc);
+        points.push(opening.t.clone());
+        scalars.push(-&w);
+    }
+    points.extend_from_slice(&gens.g_vec()[..max_len]);
+    scalars.extend(g_scalars);
+    points.push(gens.u().clone());
+    scalars.push(u_scalar);
+    match G1::msm_var_time(&points, &scalars) {
+        Ok(p) => p.is_identity(),
+        Err(_) => false,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::group_elem::GroupElement;
+
+    #[test]
+    fn test_commit_with_params() {
+        let params = GroupParams::from_label(b"test commitment");
+        let h = G1::random();
+        let elem = CurveOrderElement::random();
+        let r = CurveOrderElement::random();
+        let comm = commit_to_field_element_with_params(&params, &h, &elem, &r);
+        assert_eq!(comm, params.g1() * &elem + &h * &r);
+        assert_eq!(comm, commit_to_field_element(params.g1(), &h, &elem, &r));
+        assert_ne!(comm, commit_to_field_element_with_params(&GroupParams::default(), &h, &elem, &r));
+    }
+
+    #[test]
+    fn test_subset_opening() {
+        let gens = Generators::new(b"test subset opening", 20);
+        let values: CurveOrderElementVector = (0..20).map(|_| CurveOrderElement::random()).collect();
+        let r = CurveOrderElement::random();
+        let comm = commit_to_vector(&gens, &values, &r).unwrap();
+        assert_eq!(comm, gens.g_vec().iter().zip(values.iter()).fold(gens.u() * &r, |acc, (g, a)| acc + g * a));
+
+        let positions = [0, 3, 4, 11, 19];
+        let opening = SubsetOpening::new(&gens, &values, &r, &positions, b"audit 1").unwrap();
+        assert_eq!(opening.positions(), &positions);
+        assert_eq!(opening.values()[2], values[4]);
+        assert!(opening.verify(&gens, &comm, b"audit 1"));
+        assert!(!opening.verify(&gens, &comm, b"audit 2"));
+        assert!(!opening.verify(&gens, &G1::random(), b"audit 1"));
+
+        // A wrong value at an opened position
+        let mut bad = opening.clone();
+        bad.values[1] = CurveOrderElement::random();
+        assert!(!bad.verify(&gens, &comm, b"audit 1"));
+        // Malformed, e.g. deserialized, openings are rejected
+        let mut bad = opening.clone();
+        bad.positions[1] = 0;
+        assert!(!bad.verify(&gens, &comm, b"audit 1"));
+        let mut bad = opening.clone();
+        bad.responses.0.pop();
+        assert!(!bad.verify(&gens, &comm, b"audit 1"));
+
+        // Opening nothing or everything
+        assert!(SubsetOpening::new(&gens, &values, &r, &[], b"").unwrap().verify(&gens, &comm, b""));
+        let all: Vec<usize> = (0..20).collect();
+        assert!(SubsetOpening::new(&gens, &values, &r, &all, b"").unwrap().verify(&gens, &comm, b""));
+
+        assert!(SubsetOpening::new(&gens, &values, &r, &[4, 3], b"").is_err());
+        assert!(SubsetOpening::new(&gens, &values, &r, &[3, 3], b"").is_err());
+        assert!(SubsetOpening::new(&gens, &values, &r, &[20], b"").is_err());
+        let small = Generators::new(b"test subset opening", 10);
+        assert!(commit_to_vector(&small, &values, &r).is_err());
+        assert!(!opening.verify(&small, &comm, b"audit 1"));
+
+        #[cfg(feature = "serde")]
+        {
+            let s = serde_json::to_string(&opening).unwrap();
+            let d: SubsetOpening = serde_json::from_str(&s).unwrap();
+            assert_eq!(d, opening);
+        }
+    }
+
+    #[test]
+    fn test_subset_opening_batch_verify() {
+        let gens = Generators::new(b"test subset opening", 16);
+        let mut comms = vec![];
+        let mut openings = vec![];
+        for len in [16, 5, 9].iter() {
+            let values: CurveOrderElementVector = (0..*len).map(|_| CurveOrderElement::random()).collect();
+            let r = CurveOrderElement::random();
+            comms.push(commit_to_vector(&gens, &values, &r).unwrap());
+            let positions: Vec<usize> = (0..*len).filter(|i| i % 2 == 0).collect();
+            openings.push(SubsetOpening::new(&gens, &values, &r, &positions, b"audit").unwrap());
+        }
+        let batch: Vec<(&G1, &SubsetOpening, &[u8])> = comms.iter().zip(openings.iter()).map(|(c, o)| (c, o, &b"audit"[..])).collect();
+        assert!(SubsetOpening::batch_verify(&gens, &batch));
+        assert!(SubsetOpening::batch_verify(&gens, &[]));
+
+        let mut bad = batch.clone();
+        bad[1].0 = &comms[2];
+        assert!(!SubsetOpening::batch_verify(&gens, &bad));
+        let mut bad = batch.clone();
+        bad[2].2 = b"other";
+        assert!(!SubsetOpening::batch_verify(&gens, &bad));
+    }
+}
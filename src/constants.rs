@@ -1,7 +1,6 @@
 use super::types::{BigNum, DoubleBigNum, GroupG1};
 
-use super::ECCurve::big::{BASEBITS, MODBYTES as curve_MODBYTES, NLEN as curve_NLEN};
-use super::ECCurve::rom;
+use crate::backend::{rom, BASEBITS, MODBYTES as curve_MODBYTES, NLEN as curve_NLEN};
 
 pub const MODBYTES: usize = curve_MODBYTES;
 pub const NLEN: usize = curve_NLEN;
@@ -16,8 +15,61 @@ pub const CURVE_ORDER_ELEMENT_SIZE: usize = 32;
 pub const CURVE_ORDER_ELEMENT_SIZE: usize = 32;
 #[cfg(feature = "ed25519")]
 pub const CURVE_ORDER_ELEMENT_SIZE: usize = 32;
+#[cfg(feature = "nist256")]
+pub const CURVE_ORDER_ELEMENT_SIZE: usize = 32;
+
+// Parameters of scalar multiplication and multi-scalar multiplication per curve, from the ignored
+// benchmark `group_elem::test::timing_msm_parameters` (best of 5 runs, release build, 1 x86_64
+// core). The benchmark times the compiled-in `WNAF_WIDTH` and `MSM_CONST_TIME_WINDOW`, so the
+// pairs (4, 3), (5, 4) and (6, 5) were compared by building with each; the differences were often
+// within noise. Pippenger windows 4 to 10 were timed against Strauss' method for 64 to 4096
+// terms. Pippenger's method processes its windows in parallel, which a single core runs one
+// after the other; it was faster from 512 terms on, and from 256 terms on ed25519. With more
+// cores it gains more and the threshold is conservative.
+/// Width of the wNAF recoding of scalars in variable time scalar multiplication and multi-scalar
+/// multiplication
+#[cfg(any(feature = "bls381", feature = "nist256"))]
+pub const WNAF_WIDTH: usize = 5;
+#[cfg(any(feature = "bn254", feature = "secp256k1", feature = "ed25519"))]
+pub const WNAF_WIDTH: usize = 6;
+/// Number of odd multiples `A, 3A, .., (2^(WNAF_WIDTH-1) - 1)A` in the wNAF lookup tables
+pub const WNAF_LOOKUP_TABLE_SIZE: usize = 1 << (WNAF_WIDTH - 2);
+/// Window of constant time multi-scalar multiplication, the `2^w - 1` first multiples of each
+/// point are precomputed. At most 7.
+#[cfg(not(feature = "bn254"))]
+pub const MSM_CONST_TIME_WINDOW: usize = 4;
+#[cfg(feature = "bn254")]
+pub const MSM_CONST_TIME_WINDOW: usize = 5;
+/// Number of non-trivial terms from which variable time multi-scalar multiplication switches
+/// from Strauss' method to Pippenger's method
+#[cfg(not(feature = "ed25519"))]
+pub const PIPPENGER_THRESHOLD: usize = 512;
+#[cfg(feature = "ed25519")]
+pub const PIPPENGER_THRESHOLD: usize = 256;
+/// Largest window of Pippenger's method, `2^w - 1` buckets are kept per window
+pub const MAX_PIPPENGER_WINDOW: usize = 16;
+/// Window of Pippenger's method for `n` terms, `(least n, window)` in increasing order. The last
+/// window is used beyond the measured 4096 terms.
+#[cfg(feature = "bls381")]
+pub const PIPPENGER_WINDOWS: &[(usize, usize)] = &[(0, 6), (1024, 7), (4096, 8)];
+#[cfg(feature = "bn254")]
+pub const PIPPENGER_WINDOWS: &[(usize, usize)] = &[(0, 6), (1024, 7), (2048, 8), (4096, 9)];
+#[cfg(any(feature = "secp256k1", feature = "nist256"))]
+pub const PIPPENGER_WINDOWS: &[(usize, usize)] = &[(0, 6), (1024, 7), (2048, 8)];
+#[cfg(feature = "ed25519")]
+pub const PIPPENGER_WINDOWS: &[(usize, usize)] = &[(0, 5), (512, 6), (1024, 7), (4096, 9)];
+
+/// Window of Pippenger's method for `n` terms from `PIPPENGER_WINDOWS`
+pub fn pippenger_window(n: usize) -> usize {
+    PIPPENGER_WINDOWS
+        .iter()
+        .rev()
+        .find(|(min, _)| n >= *min)
+        .map(|(_, w)| *w)
+        .unwrap_or(PIPPENGER_WINDOWS[0].1)
+}
 
-// Byte size of element in group G1, 1 extra byte for compression flag
+// Byte size of element in group G1, 1 extra byte for compression flag. Also `G1::SERIALIZED_SIZE`.
 pub const GROUP_G1_SIZE: usize = (2 * MODBYTES + 1) as usize;
 
 pub const MODULUS: BigNum = BigNum { w: rom::MODULUS };
@@ -42,11 +94,11 @@ lazy_static! {
     pub static ref BARRETT_REDC_V: BigNum = {
         let k = CURVE_ORDER.nbits();
         let mut v = BigNum::new_int(1isize);
-        v.shl(k+1);
+        v.shl(k+2);
         v
     };
 }
 
 #[cfg(any(feature = "bls381", feature = "bn254"))]
-pub use crate::types_g2::{GENERATOR_G2, GROUP_G2_SIZE, GROUP_GT_SIZE};
+pub use crate::types_g2::{GENERATOR_G2, GROUP_G2_SIZE, GROUP_GT_COMPRESSED_SIZE, GROUP_GT_SIZE};
 
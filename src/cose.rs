@@ -0,0 +1,254 @@
+//! COSE_Key (RFC 9052) encoding of group elements and public keys as CBOR. On secp256k1 and P-256,
+//! G1 elements are `EC2` keys with `x` and `y` coordinates (RFC 9053, RFC 8812). On BLS12-381,
+//! elements of G1 and G2 are `OKP` keys whose `x` is the compressed point, following the BLS key
+//! representation draft. BN254 has no registered COSE curve so it is not supported.
+//!
+//! COSE signatures are the raw signature bytes, which is what `SignatureEncoding::to_bytes` gives,
+//! eg. `r || s` for ES256K. `signature_to_cbor` and `signature_from_cbor` wrap them in a CBOR byte string.
+
+#[cfg(feature = "bls381")]
+use crate::constants::FIELD_ORDER_ELEMENT_SIZE;
+use crate::errors::SerzDeserzError;
+use crate::group_elem::GroupElement;
+use crate::group_elem_g1::G1;
+#[cfg(feature = "bls381")]
+use crate::group_elem_g2::G2;
+
+use ciborium::value::Value;
+use signature::SignatureEncoding;
+use std::convert::TryFrom;
+
+/// Key type `OKP`
+pub const KTY_OKP: i64 = 1;
+/// Key type `EC2`
+pub const KTY_EC2: i64 = 2;
+
+#[cfg(feature = "nist256")]
+pub const CRV_G1: i64 = 1;
+#[cfg(feature = "secp256k1")]
+pub const CRV_G1: i64 = 8;
+#[cfg(feature = "bls381")]
+pub const CRV_G1: i64 = 13;
+#[cfg(feature = "bls381")]
+pub const CRV_G2: i64 = 14;
+
+/// Algorithm ECDSA with SHA-256 over secp256k1, as implemented in the `ecdsa` module
+pub const ALG_ES256K: i64 = -47;
+
+const LABEL_KTY: i64 = 1;
+const LABEL_ALG: i64 = 3;
+const LABEL_CRV: i64 = -1;
+const LABEL_X: i64 = -2;
+const LABEL_Y: i64 = -3;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CoseKey {
+    pub kty: i64,
+    pub alg: Option<i64>,
+    pub crv: i64,
+    pub x: Vec<u8>,
+    pub y: Option<Vec<u8>>,
+}
+
+impl CoseKey {
+    /// Deterministic CBOR encoding, the map keys are in canonical order.
+    pub fn to_cbor(&self) -> Vec<u8> {
+        let mut map = vec![(Value::from(LABEL_KTY), Value::from(self.kty))];
+        if let Some(alg) = self.alg {
+            map.push((Value::from(LABEL_ALG), Value::from(alg)));
+        }
+        map.push((Value::from(LABEL_CRV), Value::from(self.crv)));
+        map.push((Value::from(LABEL_X), Value::Bytes(self.x.clone())));
+        if let Some(y) = &self.y {
+            map.push((Value::from(LABEL_Y), Value::Bytes(y.clone())));
+        }
+        let mut bytes = vec![];
+        ciborium::ser::into_writer(&Value::Map(map), &mut bytes).unwrap();
+        bytes
+    }
+
+    /// Labels other than `kty`, `alg`, `crv`, `x` and `y` are ignored.
+    pub fn from_cbor(bytes: &[u8]) -> Result<Self, SerzDeserzError> {
+        let value: Value =
+            ciborium::de::from_reader(bytes).map_err(|_| SerzDeserzError::InvalidCoseKey)?;
+        let map = value.as_map().ok_or(SerzDeserzError::InvalidCoseKey)?;
+        let (mut kty, mut alg, mut crv, mut x, mut y) = (None, None, None, None, None);
+        for (label, v) in map {
+            let label = label.as_integer().and_then(|l| i64::try_from(l).ok());
+            match label {
+                Some(LABEL_KTY) => kty = Some(to_int(v)?),
+                Some(LABEL_ALG) => alg = Some(to_int(v)?),
+                Some(LABEL_CRV) => crv = Some(to_int(v)?),
+                Some(LABEL_X) => x = Some(to_bytes(v)?),
+                Some(LABEL_Y) => y = Some(to_bytes(v)?),
+                _ => (),
+            }
+        }
+        Ok(Self {
+            kty: kty.ok_or(SerzDeserzError::InvalidCoseKey)?,
+            alg,
+            crv: crv.ok_or(SerzDeserzError::InvalidCoseKey)?,
+            x: x.ok_or(SerzDeserzError::InvalidCoseKey)?,
+            y,
+        })
+    }
+
+    fn check(&self, kty: i64, crv: i64) -> Result<(), SerzDeserzError> {
+        if self.kty != kty || self.crv != crv {
+            return Err(SerzDeserzError::InvalidCoseKey);
+        }
+        Ok(())
+    }
+}
+
+fn to_int(v: &Value) -> Result<i64, SerzDeserzError> {
+    v.as_integer()
+        .and_then(|i| i64::try_from(i).ok())
+        .ok_or(SerzDeserzError::InvalidCoseKey)
+}
+
+fn to_bytes(v: &Value) -> Result<Vec<u8>, SerzDeserzError> {
+    v.as_bytes().cloned().ok_or(SerzDeserzError::InvalidCoseKey)
+}
+
+/// Encode a signature as a CBOR byte string
+pub fn signature_to_cbor<S: SignatureEncoding>(sig: &S) -> Vec<u8> {
+    let mut bytes = vec![];
+    ciborium::ser::into_writer(&Value::Bytes(sig.to_bytes().as_ref().to_vec()), &mut bytes).unwrap();
+    bytes
+}
+
+/// Decode a signature from a CBOR byte string
+pub fn signature_from_cbor<S: SignatureEncoding>(bytes: &[u8]) -> Result<S, SerzDeserzError> {
+    let value: Value =
+        ciborium::de::from_reader(bytes).map_err(|_| SerzDeserzError::InvalidCoseKey)?;
+    let sig = value.as_bytes().ok_or(SerzDeserzError::InvalidCoseKey)?;
+    S::try_from(&sig[..]).map_err(|_| SerzDeserzError::InvalidCoseKey)
+}
+
+#[cfg(any(feature = "secp256k1", feature = "nist256"))]
+impl G1 {
+    pub fn to_cose_key(&self) -> CoseKey {
+        let bytes = self.to_bytes();
+        let (x, y) = bytes.split_at(bytes.len() / 2);
+        CoseKey {
+            kty: KTY_EC2,
+            alg: None,
+            crv: CRV_G1,
+            x: x.to_vec(),
+            y: Some(y.to_vec()),
+        }
+    }
+
+    /// Rejects keys that are not on the curve or are the identity. Compressed `y` is not supported.
+    pub fn from_cose_key(key: &CoseKey) -> Result<Self, SerzDeserzError> {
+        key.check(KTY_EC2, CRV_G1)?;
+        let y = key.y.as_ref().ok_or(SerzDeserzError::InvalidCoseKey)?;
+        let mut bytes = vec![4u8];
+        bytes.extend_from_slice(&key.x);
+        bytes.extend_from_slice(y);
+        let p = G1::from_slice(&bytes).map_err(|_| SerzDeserzError::InvalidCoseKey)?;
+        if p.is_identity() {
+            return Err(SerzDeserzError::InvalidCoseKey);
+        }
+        Ok(p)
+    }
+}
+
+#[cfg(feature = "bls381")]
+impl G1 {
+    pub fn to_cose_key(&self) -> CoseKey {
+        CoseKey {
+            kty: KTY_OKP,
+            alg: None,
+            crv: CRV_G1,
+            x: self.to_compressed_bytes().to_vec(),
+            y: None,
+        }
+    }
+
+    /// Rejects points that are not in the prime order subgroup or are the identity.
+    pub fn from_cose_key(key: &CoseKey) -> Result<Self, SerzDeserzError> {
+        key.check(KTY_OKP, CRV_G1)?;
+        if key.x.len() != FIELD_ORDER_ELEMENT_SIZE {
+            return Err(SerzDeserzError::InvalidCoseKey);
+        }
+        let p = G1::from(array_ref![key.x, 0, FIELD_ORDER_ELEMENT_SIZE]);
+        if p.is_identity() || !p.has_correct_order() {
+            return Err(SerzDeserzError::InvalidCoseKey);
+        }
+        Ok(p)
+    }
+}
+
+#[cfg(feature = "bls381")]
+impl G2 {
+    pub fn to_cose_key(&self) -> CoseKey {
+        CoseKey {
+            kty: KTY_OKP,
+            alg: None,
+            crv: CRV_G2,
+            x: self.to_compressed_bytes().to_vec(),
+            y: None,
+        }
+    }
+
+    /// Rejects points that are not in the prime order subgroup or are the identity.
+    pub fn from_cose_key(key: &CoseKey) -> Result<Self, SerzDeserzError> {
+        key.check(KTY_OKP, CRV_G2)?;
+        if key.x.len() != 2 * FIELD_ORDER_ELEMENT_SIZE {
+            return Err(SerzDeserzError::InvalidCoseKey);
+        }
+        let p = G2::from(array_ref![key.x, 0, 2 * FIELD_ORDER_ELEMENT_SIZE]);
+        if p.is_identity() || !p.has_correct_order() {
+            return Err(SerzDeserzError::InvalidCoseKey);
+        }
+        Ok(p)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::curve_order_elem::CurveOrderElement;
+
+    #[test]
+    fn test_g1_cose_key() {
+        let p = G1::generator() * CurveOrderElement::random();
+        let key = p.to_cose_key();
+        let bytes = key.to_cbor();
+        assert_eq!(CoseKey::from_cbor(&bytes).unwrap(), key);
+        assert_eq!(G1::from_cose_key(&key).unwrap(), p);
+
+        let mut bad = key.clone();
+        bad.crv = 2;
+        assert!(G1::from_cose_key(&bad).is_err());
+        let mut bad = key.clone();
+        bad.x.pop();
+        assert!(G1::from_cose_key(&bad).is_err());
+        assert!(CoseKey::from_cbor(&bytes[1..]).is_err());
+    }
+
+    #[cfg(feature = "bls381")]
+    #[test]
+    fn test_bls_cose_key() {
+        use crate::bls::{SecretKey, Signature};
+        use signature::Signer;
+
+        let sk = SecretKey::random();
+        let pk = crate::bls::PublicKey::from(&sk);
+        let key = pk.to_cose_key();
+        assert_eq!(key.kty, KTY_OKP);
+        assert_eq!(key.crv, CRV_G2);
+        assert_eq!(
+            crate::bls::PublicKey::from_cose_key(&CoseKey::from_cbor(&key.to_cbor()).unwrap())
+                .unwrap(),
+            pk
+        );
+        assert!(G1::from_cose_key(&key).is_err());
+
+        let sig = sk.try_sign(b"test message").unwrap();
+        let bytes = signature_to_cbor(&sig);
+        assert_eq!(signature_from_cbor::<Signature>(&bytes).unwrap(), sig);
+    }
+}
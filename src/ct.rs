@@ -0,0 +1,149 @@
+//! The operations of the crate that are constant time, i.e. their sequence of operations and memory
+//! accesses do not depend on secret inputs, collected in one place for auditing. Many other
+//! functions are constant time only in part or only in their name, eg. the constant time
+//! multi-scalar multiplications skip zero digits, so code handling secrets should only use these.
+//!
+//! Lengths of inputs are considered public. In debug builds, variable time operations like
+//! `CurveOrderElement::inverse` or `to_wnaf` panic when called from within one of these functions,
+//! so refactorings that make them reachable are caught by the tests.
+
+use crate::curve_order_elem::CurveOrderElement;
+use crate::group_elem::GroupElement;
+use crate::group_elem_g1::G1;
+#[cfg(any(feature = "bls381", feature = "bn254"))]
+use crate::group_elem_g2::G2;
+
+use std::cell::Cell;
+
+thread_local! {
+    /// Number of constant time operations being executed by the thread
+    static DEPTH: Cell<usize> = const { Cell::new(0) };
+}
+
+/// Marks the thread as executing a constant time operation until dropped
+struct Section;
+
+impl Section {
+    fn enter() -> Self {
+        DEPTH.with(|d| d.set(d.get() + 1));
+        Section
+    }
+}
+
+impl Drop for Section {
+    fn drop(&mut self) {
+        DEPTH.with(|d| d.set(d.get() - 1));
+    }
+}
+
+/// Called by variable time operations, panics in debug builds if reached from this module
+#[inline]
+pub(crate) fn debug_assert_variable_time(op: &str) {
+    debug_assert!(
+        DEPTH.with(|d| d.get()) == 0,
+        "variable time operation {} reached from a constant time operation",
+        op
+    );
+}
+
+/// `point * scalar` with the fixed window multiplication of AMCL
+pub fn scalar_mul<G: GroupElement>(point: &G, scalar: &CurveOrderElement) -> G {
+    let _s = Section::enter();
+    point.scalar_mul_const_time(scalar)
+}
+
+/// `scalar^-1` as `scalar^(curve_order - 2)` with a fixed addition chain, zero maps to zero
+pub fn inverse(scalar: &CurveOrderElement) -> CurveOrderElement {
+    let _s = Section::enter();
+    scalar.inverse_const_time()
+}
+
+/// `b` if `choice` else `a`
+pub fn select_scalar(
+    a: &CurveOrderElement,
+    b: &CurveOrderElement,
+    choice: bool,
+) -> CurveOrderElement {
+    let _s = Section::enter();
+    a.cmove(b, choice)
+}
+
+/// `b` if `choice` else `a`
+pub fn select_g1(a: &G1, b: &G1, choice: bool) -> G1 {
+    let _s = Section::enter();
+    a.cmove(b, choice)
+}
+
+/// `b` if `choice` else `a`
+#[cfg(any(feature = "bls381", feature = "bn254"))]
+pub fn select_g2(a: &G2, b: &G2, choice: bool) -> G2 {
+    let _s = Section::enter();
+    a.cmove(b, choice)
+}
+
+/// Equality of byte strings of the same length, every byte is compared
+pub fn bytes_eq(a: &[u8], b: &[u8]) -> bool {
+    if a.len() != b.len() {
+        return false;
+    }
+    let diff = a.iter().zip(b.iter()).fold(0u8, |acc, (x, y)| acc | (x ^ y));
+    diff == 0
+}
+
+/// Equality of scalars by their byte encodings. `==` stops at the first differing limb.
+pub fn scalar_eq(a: &CurveOrderElement, b: &CurveOrderElement) -> bool {
+    let _s = Section::enter();
+    bytes_eq(&a.to_bytes(), &b.to_bytes())
+}
+
+/// Equality of group elements by their byte encodings
+pub fn point_eq<G: GroupElement>(a: &G, b: &G) -> bool {
+    let _s = Section::enter();
+    bytes_eq(&a.to_vec(), &b.to_vec())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_ct_ops() {
+        let x = CurveOrderElement::random();
+        let y = CurveOrderElement::random();
+        let g = G1::random();
+
+        assert_eq!(scalar_mul(&g, &x), &g * &x);
+        assert_eq!(inverse(&x), x.inverse());
+        assert!(inverse(&CurveOrderElement::zero()).is_zero());
+        assert_eq!(select_scalar(&x, &y, false), x);
+        assert_eq!(select_scalar(&x, &y, true), y);
+        let h = G1::random();
+        assert_eq!(select_g1(&g, &h, false), g);
+        assert_eq!(select_g1(&g, &h, true), h);
+        #[cfg(any(feature = "bls381", feature = "bn254"))]
+        {
+            let (a, b) = (G2::random(), G2::random());
+            assert_eq!(select_g2(&a, &b, true), b);
+            assert_eq!(scalar_mul(&a, &x), &a * &x);
+        }
+
+        assert!(scalar_eq(&x, &x.clone()));
+        assert!(!scalar_eq(&x, &y));
+        assert!(point_eq(&g, &(&g + &G1::identity())));
+        assert!(!point_eq(&g, &h));
+        assert!(bytes_eq(b"abc", b"abc"));
+        assert!(!bytes_eq(b"abc", b"abd"));
+        assert!(!bytes_eq(b"abc", b"ab"));
+
+        // Variable time operations are allowed again after leaving
+        assert_eq!(&x * &x.inverse(), CurveOrderElement::one());
+    }
+
+    #[cfg(debug_assertions)]
+    #[test]
+    #[should_panic(expected = "variable time operation")]
+    fn test_variable_time_unreachable() {
+        let _s = Section::enter();
+        CurveOrderElement::random().inverse();
+    }
+}
@@ -7,8 +7,11 @@ use crate::constants::{
 
 };
 use crate::errors::{SerzDeserzError, ValueError};
+use crate::secret_mem::{locked_vec, LockedRegion, SecretBuffer};
 use crate::types::{BigNum, DoubleBigNum, Limb};
-use crate::utils::barrett_reduction;
+use crate::utils::{
+    barrett_reduction, expand_seed_to_scalars, fmt_digests, fmt_truncated_hex, SHORT_HEX_CHARS,
+};
 
 use sha3::digest::{ExtendableOutput, Input, XofReader};
 use sha3::Shake256;
@@ -17,15 +20,27 @@ use std::cmp::Ordering;
 use std::fmt;
 use std::hash::{Hash, Hasher};
 use std::convert::TryFrom;
-use std::slice::Iter;
+use std::iter::FromIterator;
+use std::slice::{Chunks, Iter, SliceIndex};
 use std::ops::{Add, AddAssign, Mul, Neg, Sub, SubAssign, Index, IndexMut, RangeBounds};
 
+#[cfg(feature = "serde")]
 use serde::{Serialize, Deserialize, Serializer, Deserializer};
+#[cfg(feature = "serde")]
 use serde::de::{Error as DError, Visitor};
 use rand::prelude::*;
 use rayon::prelude::*;
 use zeroize::Zeroize;
 
+/// Number of products of curve order elements that can be summed in a `DoubleBigNum` before
+/// reducing. Products take at most 2 bits more than twice the curve order's bits so this leaves
+/// room on all supported curves.
+pub const INNER_PRODUCT_CHUNK_SIZE: usize = 1 << 16;
+
+/// Bound on the number of digits of `CurveOrderElement::to_wnaf`, one more than the bits of a
+/// `BigNum`
+pub const MAX_WNAF_DIGITS: usize = NLEN * BIG_NUM_BITS + 1;
+
 #[macro_export]
 macro_rules! add_curve_order_elems {
     ( $( $elem:expr ),* ) => {
@@ -39,14 +54,62 @@ macro_rules! add_curve_order_elems {
     };
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone)]
 pub struct CurveOrderElement {
     value: BigNum,
 }
 
+impl fmt::Debug for CurveOrderElement {
+    /// `CurveOrderElement(<fmt_short>)`, the full value with `{:#?}`
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if f.alternate() {
+            write!(f, "CurveOrderElement({})", self.value)
+        } else {
+            write!(f, "CurveOrderElement({})", self.fmt_short())
+        }
+    }
+}
+
 impl fmt::Display for CurveOrderElement {
+    /// The full value, `fmt_short` with `{:#}`
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        self.value.fmt(f)
+        if f.alternate() {
+            write!(f, "{}", self.fmt_short())
+        } else {
+            self.value.fmt(f)
+        }
+    }
+}
+
+/// Uniformly random curve order element, `rng.gen::<CurveOrderElement>()`. Unlike `random_using_rng`
+/// the generator does not have to be cryptographically secure.
+#[cfg(feature = "rand-distributions")]
+impl Distribution<CurveOrderElement> for rand::distributions::Standard {
+    fn sample<R: Rng + ?Sized>(&self, rng: &mut R) -> CurveOrderElement {
+        CurveOrderElement::random_using_any_rng(rng)
+    }
+}
+
+/// Arbitrary curve order element for fuzzing and property tests. The edge cases zero, one and the
+/// order minus one are each picked with probability 1/8, other elements are arbitrary bytes
+/// reduced modulo the curve order.
+#[cfg(feature = "arbitrary")]
+impl<'a> arbitrary::Arbitrary<'a> for CurveOrderElement {
+    fn arbitrary(u: &mut arbitrary::Unstructured<'a>) -> arbitrary::Result<Self> {
+        Ok(match u.int_in_range(0u8..=7)? {
+            0 => Self::zero(),
+            1 => Self::one(),
+            2 => Self::minus_one(),
+            _ => {
+                let mut bytes = [0u8; FIELD_ORDER_ELEMENT_SIZE];
+                u.fill_buffer(&mut bytes)?;
+                Self::from(bytes)
+            }
+        })
+    }
+
+    fn size_hint(_depth: usize) -> (usize, Option<usize>) {
+        (1, Some(1 + FIELD_ORDER_ELEMENT_SIZE))
     }
 }
 
@@ -78,6 +141,12 @@ impl Drop for CurveOrderElement {
 
 /// Represents an element of the prime order of the curve. All operations are done modulo the curve order
 impl CurveOrderElement {
+    /// Size of the byte representation of `to_bytes`
+    pub const SERIALIZED_SIZE: usize = FIELD_ORDER_ELEMENT_SIZE;
+
+    /// Size of the byte representation of `to_compressed_bytes`
+    pub const COMPRESSED_SIZE: usize = CURVE_ORDER_ELEMENT_SIZE;
+
     /// Creates a new curve order element with value 0
     pub fn new() -> Self {
         Self {
@@ -112,8 +181,19 @@ impl CurveOrderElement {
         Self::random_using_rng(&mut rng)
     }
 
+    /// Return a random curve order element with entropy read directly from the operating system
+    pub fn random_from_os() -> Self {
+        Self::random_using_rng(&mut crate::utils::OsEntropyRng)
+    }
+
     /// Return a random non-zero curve order element using the given random number generator
     pub fn random_using_rng<R: RngCore + CryptoRng>(rng: &mut R) -> Self {
+        Self::random_using_any_rng(rng)
+    }
+
+    /// Twice the needed bytes from the generator reduced modulo the curve order, for a negligible
+    /// bias
+    fn random_using_any_rng<R: RngCore + ?Sized>(rng: &mut R) -> Self {
         let mut data = [0u8; 2 * CURVE_ORDER_ELEMENT_SIZE];
         rng.fill_bytes(&mut data);
         let mut n = DoubleBigNum::new();
@@ -134,14 +214,14 @@ impl CurveOrderElement {
     }
 
     /// Return bytes in MSB form
-    pub fn to_bytes(&self) -> [u8; FIELD_ORDER_ELEMENT_SIZE] {
-        let mut bytes: [u8; FIELD_ORDER_ELEMENT_SIZE] = [0; FIELD_ORDER_ELEMENT_SIZE];
+    pub fn to_bytes(&self) -> [u8; Self::SERIALIZED_SIZE] {
+        let mut bytes = [0u8; Self::SERIALIZED_SIZE];
         self.write_to_slice_unchecked(&mut bytes);
         bytes
     }
 
     /// Returns bytes in big-endian (MSB form) but removes all leading zeros
-    pub fn to_compressed_bytes(&self) -> [u8; CURVE_ORDER_ELEMENT_SIZE] {
+    pub fn to_compressed_bytes(&self) -> [u8; Self::COMPRESSED_SIZE] {
         let mut bytes = [0u8; FIELD_ORDER_ELEMENT_SIZE];
         self.write_to_slice_unchecked(&mut bytes);
 
@@ -151,6 +231,39 @@ impl CurveOrderElement {
         out
     }
 
+    /// The first `SHORT_HEX_CHARS` hex characters of `to_compressed_bytes`, for logs
+    pub fn fmt_short(&self) -> String {
+        self.fmt_truncated(SHORT_HEX_CHARS)
+    }
+
+    /// The first `chars` hex characters of `to_compressed_bytes`
+    pub fn fmt_truncated(&self, chars: usize) -> String {
+        fmt_truncated_hex(&self.to_compressed_bytes(), chars)
+    }
+
+    /// Return bytes in LSB form, `to_bytes` reversed
+    pub fn to_bytes_le(&self) -> [u8; Self::SERIALIZED_SIZE] {
+        let mut bytes = self.to_bytes();
+        bytes.reverse();
+        bytes
+    }
+
+    /// Returns bytes in little-endian (LSB form) but removes all trailing zeros, `to_compressed_bytes`
+    /// reversed
+    pub fn to_compressed_bytes_le(&self) -> [u8; Self::COMPRESSED_SIZE] {
+        let mut bytes = self.to_compressed_bytes();
+        bytes.reverse();
+        bytes
+    }
+
+    /// Create element from bytes in LSB form of the size returned by `to_bytes_le` or
+    /// `to_compressed_bytes_le`, reducing it modulo the curve order
+    pub fn from_bytes_le(bytes: &[u8]) -> Result<Self, SerzDeserzError> {
+        let mut be = bytes.to_vec();
+        be.reverse();
+        Self::try_from(be.as_slice())
+    }
+
     /// Writes bytes in MSB form to given slice. Raises exception when given slice is not of
     /// desired length.
     pub fn write_to_slice(&self, target: &mut [u8]) -> Result<(), SerzDeserzError> {
@@ -249,6 +362,7 @@ impl CurveOrderElement {
 
     /// Exponentiation modulo curve order, i.e. self^exp % CURVE_ORDER
     pub fn pow(&self, exp: &Self) -> Self {
+        crate::ct::debug_assert_variable_time("CurveOrderElement::pow");
         let mut base = self.value.clone();
         let mut res = base.powmod(&exp.value, &CURVE_ORDER);
         res.norm();
@@ -268,6 +382,7 @@ impl CurveOrderElement {
 
     /// Calculate inverse of a curve order element modulo the curve order, i.e `a^-1 % curve_order`
     pub fn inverse(&self) -> Self {
+        crate::ct::debug_assert_variable_time("CurveOrderElement::inverse");
         // Violating constant time guarantee until bug fixed in amcl
         if self.is_zero() {
             return Self::zero();
@@ -278,7 +393,68 @@ impl CurveOrderElement {
         inv.into()
     }
 
+    /// Calculate inverse as `a^(curve_order - 2) % curve_order` by Fermat's little theorem. Unlike
+    /// `inverse`, the sequence of operations is fixed by the curve order and does not depend on
+    /// `self` so use this for secret values like nonces. Slower than `inverse`. Zero maps to zero.
+    pub fn inverse_const_time(&self) -> Self {
+        self.pow_by_order_minus_two()
+    }
+
+    /// `self^(curve_order - 2)` with a precomputed addition chain
+    pub fn pow_by_order_minus_two(&self) -> Self {
+        ORDER_MINUS_TWO_CHAIN.pow(self)
+    }
+
+    /// `self^((curve_order - 1) / 2)` with a precomputed addition chain, the Legendre symbol of
+    /// `self` as 1, -1 or 0
+    pub fn pow_by_order_minus_one_half(&self) -> Self {
+        ORDER_MINUS_ONE_HALF_CHAIN.pow(self)
+    }
+
+    /// True for zero and the non-zero squares
+    pub fn is_quadratic_residue(&self) -> bool {
+        self.pow_by_order_minus_one_half() != Self::minus_one()
+    }
+
+    /// A square root of `self` by Tonelli-Shanks, or None if `self` is not a square. The other root is
+    /// its negation. Variable time.
+    pub fn sqrt(&self) -> Option<Self> {
+        crate::ct::debug_assert_variable_time("CurveOrderElement::sqrt");
+        if self.is_zero() {
+            return Some(Self::zero());
+        }
+        // w = self^((t-1)/2) where curve_order - 1 = 2^s * t
+        let w = SQRT_CHAIN.pow(self);
+        // r = self^((t+1)/2), b = self^t
+        let mut r = self * &w;
+        let mut b = &r * &w;
+        let mut c = TWO_ADIC_ROOT_OF_UNITY.clone();
+        let mut m = *TWO_ADICITY;
+        while !b.is_one() {
+            // Smallest i with b^(2^i) = 1, there is none if self is not a square
+            let mut i = 0;
+            let mut b2 = b.clone();
+            while !b2.is_one() {
+                b2 = b2.square();
+                i += 1;
+                if i == m {
+                    return None;
+                }
+            }
+            let mut d = c.clone();
+            for _ in 0..m - i - 1 {
+                d = d.square();
+            }
+            m = i;
+            c = d.square();
+            r = &r * &d;
+            b = &b * &c;
+        }
+        Some(r)
+    }
+
     pub fn inverse_mut(&mut self) {
+        crate::ct::debug_assert_variable_time("CurveOrderElement::inverse_mut");
         // Violating constant time guarantee until bug fixed in amcl
         if self.is_zero() {
             self.value = BigNum::new();
@@ -351,7 +527,25 @@ impl CurveOrderElement {
     /// Conversion to wNAF, i.e. windowed Non Adjacent form
     /// Taken from Guide to Elliptic Curve Cryptography book, "Algorithm 3.35 Computing the width-w NAF of a positive integer" with modification
     /// at step 2.1, if k_i >= 2^(w-1), k_i = k_i - 2^w
+    /// See `recoding::Wnaf` for the contract of the digits.
     pub fn to_wnaf(&self, w: usize) -> Vec<i8> {
+        let mut naf = vec![0i8; MAX_WNAF_DIGITS];
+        let len = self.write_wnaf(w, &mut naf);
+        naf.truncate(len);
+        naf
+    }
+
+    /// `to_wnaf` in a secret buffer of `MAX_WNAF_DIGITS` digits, returned with the number of
+    /// digits of the wNAF. The digits after those are 0.
+    pub(crate) fn to_wnaf_locked(&self, w: usize) -> (SecretBuffer<i8>, usize) {
+        let mut naf = SecretBuffer::from_fn(MAX_WNAF_DIGITS, |_| 0i8);
+        let len = self.write_wnaf(w, &mut naf);
+        (naf, len)
+    }
+
+    /// Write the digits of `to_wnaf` to the start of `naf` and return their number
+    fn write_wnaf(&self, w: usize, naf: &mut [i8]) -> usize {
+        crate::ct::debug_assert_variable_time("CurveOrderElement::to_wnaf");
         // required by the NAF definition
         debug_assert!(w >= 2);
         // required so that the NAF digits fit in i8
@@ -359,7 +553,7 @@ impl CurveOrderElement {
 
         // Working on the the underlying BIG to save the cost of to and from conversion with CurveOrderElement
         let mut k = self.to_bignum();
-        let mut naf: Vec<i8> = vec![];
+        let mut len = 0;
 
         let two_w_1 = 1 << (w - 1); // 2^(w-1)
         let two_w = 1 << w; // 2^w
@@ -380,18 +574,71 @@ impl CurveOrderElement {
                 }
 
                 k.w[0] = k.w[0] - u;
+                k.norm();
                 u as i8
             } else {
                 0i8
             };
-            naf.push(t);
+            naf[len] = t;
+            len += 1;
             k.fshr(1usize);
         }
 
-        naf
+        len
+    }
+
+    /// Signed fixed window (Booth) recoding with window `w`. Returns digits `d_i` in
+    /// `[-2^(w-1), 2^(w-1)]` with `self = sum d_i * 2^(w*i)`, least significant first. Unlike
+    /// `to_wnaf`, the number of digits only depends on `w` and the curve order, and the digits are
+    /// computed without branching on the bits of `self`, so it can be used for secret scalars.
+    pub fn to_booth(&self, w: usize) -> Vec<i8> {
+        let mut digits = vec![0i8; Self::booth_digits(w)];
+        self.write_booth(w, &mut digits);
+        digits
+    }
+
+    /// `to_booth` in a secret buffer
+    pub(crate) fn to_booth_locked(&self, w: usize) -> SecretBuffer<i8> {
+        let mut digits = SecretBuffer::from_fn(Self::booth_digits(w), |_| 0i8);
+        self.write_booth(w, &mut digits);
+        digits
+    }
+
+    /// Number of digits of `to_booth` with window `w`. The top bit of the last window must be 0.
+    fn booth_digits(w: usize) -> usize {
+        (CURVE_ORDER.nbits() + w) / w
+    }
+
+    fn write_booth(&self, w: usize, digits: &mut [i8]) {
+        debug_assert!(w >= 2);
+        // required so that the digits fit in i8
+        debug_assert!(w <= 7);
+
+        let mut k = self.to_bignum();
+        k.norm();
+        let max_bits = NLEN * BIG_NUM_BITS;
+        let bit = |i: usize| -> i32 {
+            if i < max_bits {
+                k.bit(i) as i32
+            } else {
+                0
+            }
+        };
+
+        let mut carry = 0;
+        for (i, d) in digits.iter_mut().enumerate() {
+            let mut v = 0;
+            for j in 0..w {
+                v |= bit(i * w + j) << j;
+            }
+            let top = bit(i * w + w - 1);
+            *d = (v + carry - (top << w)) as i8;
+            carry = top;
+        }
     }
 
     /// Convert to base that is power of 2. Does not handle negative nos or `base` higher than 2^7
+    /// See `recoding::RadixDigits` for the contract of the digits.
     pub fn to_power_of_2_base(&self, n: usize) -> Vec<u8> {
         debug_assert!(n <= 7);
 
@@ -483,6 +730,56 @@ impl CurveOrderElement {
         Ok(f.into())
     }
 
+    /// Returns the decimal representation of the element reduced modulo the curve order, without
+    /// leading zeros
+    pub fn to_decimal_string(&self) -> String {
+        let mut bytes = [0u8; FIELD_ORDER_ELEMENT_SIZE];
+        self.to_bignum().tobytes(&mut bytes);
+        let mut digits = vec![];
+        // Repeatedly divide the big endian bytes by 10, the remainders are the digits
+        while bytes.iter().any(|b| *b != 0) {
+            let mut rem = 0u16;
+            for b in bytes.iter_mut() {
+                let cur = (rem << 8) | *b as u16;
+                *b = (cur / 10) as u8;
+                rem = cur % 10;
+            }
+            digits.push(b'0' + rem as u8);
+        }
+        if digits.is_empty() {
+            digits.push(b'0');
+        }
+        digits.reverse();
+        String::from_utf8(digits).unwrap()
+    }
+
+    /// Create element from a decimal string, reducing it modulo the curve order like `from_hex`.
+    /// The value must fit in the byte size of a field element.
+    pub fn from_decimal_str(s: &str) -> Result<Self, SerzDeserzError> {
+        if s.is_empty() || !s.bytes().all(|c| c.is_ascii_digit()) {
+            return Err(SerzDeserzError::RequiredDecimalChar);
+        }
+        let mut bytes = [0u8; FIELD_ORDER_ELEMENT_SIZE];
+        for c in s.bytes() {
+            // Multiply the big endian bytes by 10 and add the digit
+            let mut carry = (c - b'0') as u16;
+            for b in bytes.iter_mut().rev() {
+                let cur = *b as u16 * 10 + carry;
+                *b = cur as u8;
+                carry = cur >> 8;
+            }
+            if carry != 0 {
+                return Err(SerzDeserzError::FieldElementBytesIncorrectSize(
+                    s.len(),
+                    FIELD_ORDER_ELEMENT_SIZE,
+                ));
+            }
+        }
+        let mut f = BigNum::frombytes(&bytes);
+        f.rmod(&CURVE_ORDER);
+        Ok(f.into())
+    }
+
     /// Useful for reducing product of BigNums. Uses Barrett reduction
     pub fn reduce_dmod_curve_order(x: &DoubleBigNum) -> BigNum {
         let (k, u, v) = (*BARRETT_REDC_K, *BARRETT_REDC_U, *BARRETT_REDC_V);
@@ -510,6 +807,78 @@ impl CurveOrderElement {
         Ok(BigNum::frombytes(bytes.as_slice()))
     }
 
+    /// Create element from a big endian hex string in constant time, for secrets like secret keys.
+    /// Upper and lower case digits are accepted. Unlike `from_hex`, the time taken depends only on
+    /// the length of the string, not on its digits or whether a digit is invalid.
+    pub fn from_hex_ct(s: &str) -> Result<Self, SerzDeserzError> {
+        let s = s.as_bytes();
+        if s.len() > FIELD_ORDER_ELEMENT_SIZE * 2 {
+            return Err(SerzDeserzError::FieldElementBytesIncorrectSize(
+                s.len(),
+                FIELD_ORDER_ELEMENT_SIZE,
+            ));
+        }
+        let mut bytes = [0u8; FIELD_ORDER_ELEMENT_SIZE];
+        let mut invalid = 0i16;
+        // Right align the digits, the first digit of an odd length string is a low nibble
+        let offset = FIELD_ORDER_ELEMENT_SIZE * 2 - s.len();
+        for (i, c) in s.iter().enumerate() {
+            let v = decode_hex_digit_ct(*c);
+            invalid |= v;
+            let pos = offset + i;
+            bytes[pos / 2] |= ((v & 0xf) as u8) << (4 * (1 - pos % 2));
+        }
+        let f = Self::from_bytes_ct(&bytes);
+        bytes.zeroize();
+        // A negative value sets the sign bit of `invalid`
+        if invalid < 0 {
+            return Err(SerzDeserzError::RequiredHexChar);
+        }
+        Ok(f)
+    }
+
+    /// Create element from big endian bytes in constant time, for secrets like secret keys. Takes
+    /// the same sizes as `try_from` and also reduces modulo the curve order, but unlike it the time
+    /// taken does not depend on the value.
+    pub fn from_slice_ct(bytes: &[u8]) -> Result<Self, SerzDeserzError> {
+        if bytes.len() != FIELD_ORDER_ELEMENT_SIZE && bytes.len() != CURVE_ORDER_ELEMENT_SIZE {
+            return Err(SerzDeserzError::FieldElementBytesIncorrectSize(
+                bytes.len(),
+                FIELD_ORDER_ELEMENT_SIZE,
+            ));
+        }
+        let mut padded = [0u8; FIELD_ORDER_ELEMENT_SIZE];
+        padded[FIELD_ORDER_ELEMENT_SIZE - bytes.len()..].copy_from_slice(bytes);
+        let f = Self::from_bytes_ct(&padded);
+        padded.zeroize();
+        Ok(f)
+    }
+
+    /// Reduce big endian bytes modulo the curve order with a fixed number of conditional
+    /// subtractions. `BigNum::rmod` runs as many subtractions as the value has more bits than the
+    /// order, leaking its size.
+    fn from_bytes_ct(bytes: &[u8; FIELD_ORDER_ELEMENT_SIZE]) -> Self {
+        let mut value = BigNum::frombytes(bytes);
+        // The value has less than 8 * FIELD_ORDER_ELEMENT_SIZE bits and twice the largest
+        // multiple of the order below is bigger than that
+        let k = 8 * FIELD_ORDER_ELEMENT_SIZE - CURVE_ORDER.nbits();
+        let mut m = CURVE_ORDER;
+        m.shl(k);
+        let mut r = BigNum::new();
+        let sign_shift = 8 * std::mem::size_of::<Limb>() - 1;
+        for _ in 0..=k {
+            r.copy(&value);
+            r.sub(&m);
+            r.norm();
+            // Keep the difference when it isn't negative
+            let negative = (r.w[NLEN - 1] >> sign_shift) & 1;
+            value.cmove(&r, (1 - negative) as isize);
+            m.fshr(1);
+        }
+        r.zero();
+        value.into()
+    }
+
     ///If c is False, cmove returns self, otherwise it returns b.
     pub fn cmove(&self, b: &Self, c: bool) -> Self {
        let mut res = self.value.clone();
@@ -517,12 +886,152 @@ impl CurveOrderElement {
         res.into()
     }
 
+    /// Swap `a` and `b` if `c` is true. Does not branch on `c` or the values.
+    pub fn conditional_swap(a: &mut Self, b: &mut Self, c: bool) {
+        a.value.cswap(&mut b.value, c as isize);
+    }
+
+    /// Set self to `b` if `c` is true. Does not branch on `c` or the values.
+    pub fn conditional_assign(&mut self, b: &Self, c: bool) {
+        self.value.cmove(&b.value, c as isize);
+    }
+
     /// Use this instead of static ref and new_int. This allows for initialization at compile time
     pub const fn from_array(w: [Limb; NLEN]) -> CurveOrderElement {
         CurveOrderElement { value: BigNum { w } }
     }
 }
 
+/// Value of the hex digit `c` or -1 if it isn't one, without branching on `c`
+fn decode_hex_digit_ct(c: u8) -> i16 {
+    let c = c as i16;
+    let mut v = -1i16;
+    // Each range check is all ones when `c` is in the range and adds the digit value plus one
+    v += (((0x2f - c) & (c - 0x3a)) >> 8) & (c - 0x2f);
+    v += (((0x40 - c) & (c - 0x47)) >> 8) & (c - 0x36);
+    v += (((0x60 - c) & (c - 0x67)) >> 8) & (c - 0x56);
+    v
+}
+
+/// Sliding window addition chain with window 4 for a fixed exponent. Each step is a number of
+/// squarings followed by an optional multiplication with an odd power of the base, `x^(2*i+1)`.
+/// The steps only depend on the exponent so evaluating it does not branch on the base.
+struct AdditionChain {
+    steps: Vec<(usize, Option<usize>)>,
+}
+
+impl AdditionChain {
+    fn new(exp: &BigNum) -> Self {
+        let mut e = *exp;
+        e.norm();
+        let bits: Vec<u8> = (0..e.nbits()).rev().map(|i| e.bit(i) as u8).collect();
+        let mut steps = vec![];
+        let mut squarings = 0;
+        let mut i = 0;
+        while i < bits.len() {
+            if bits[i] == 0 {
+                squarings += 1;
+                i += 1;
+                continue;
+            }
+            // Longest window of at most 4 bits starting at i and ending with a 1
+            let mut j = std::cmp::min(i + 4, bits.len());
+            while bits[j - 1] == 0 {
+                j -= 1;
+            }
+            let v = bits[i..j].iter().fold(0usize, |v, b| (v << 1) | *b as usize);
+            steps.push((squarings + j - i, Some(v / 2)));
+            squarings = 0;
+            i = j;
+        }
+        if squarings > 0 {
+            steps.push((squarings, None));
+        }
+        Self { steps }
+    }
+
+    fn pow(&self, x: &CurveOrderElement) -> CurveOrderElement {
+        // table[i] = x^(2*i+1)
+        let x2 = x.square();
+        let mut table = vec![x.clone()];
+        for i in 1..8 {
+            let t = &table[i - 1] * &x2;
+            table.push(t);
+        }
+        let mut res: Option<CurveOrderElement> = None;
+        for (squarings, mul) in &self.steps {
+            if let Some(r) = res.as_mut() {
+                for _ in 0..*squarings {
+                    *r = r.square();
+                }
+            }
+            if let Some(m) = mul {
+                res = Some(match res {
+                    Some(r) => &r * &table[*m],
+                    None => table[*m].clone(),
+                });
+            }
+        }
+        res.unwrap_or_else(CurveOrderElement::one)
+    }
+}
+
+lazy_static! {
+    static ref ORDER_MINUS_TWO_CHAIN: AdditionChain = {
+        let mut e = CURVE_ORDER;
+        e.dec(2);
+        AdditionChain::new(&e)
+    };
+
+    static ref ORDER_MINUS_ONE_HALF_CHAIN: AdditionChain = {
+        let mut e = CURVE_ORDER;
+        e.dec(1);
+        e.norm();
+        e.fshr(1);
+        AdditionChain::new(&e)
+    };
+
+    /// Largest `s` such that `2^s` divides `curve_order - 1`
+    pub static ref TWO_ADICITY: usize = {
+        let mut e = CURVE_ORDER;
+        e.dec(1);
+        e.norm();
+        let mut s = 0;
+        while e.parity() == 0 {
+            e.fshr(1);
+            s += 1;
+        }
+        s
+    };
+
+    /// Element of order `2^TWO_ADICITY`, found as `g^t` for the smallest quadratic non-residue `g`
+    /// where `curve_order - 1 = 2^TWO_ADICITY * t`
+    pub static ref TWO_ADIC_ROOT_OF_UNITY: CurveOrderElement = {
+        let mut g = CurveOrderElement::from(2u8);
+        while g.is_quadratic_residue() {
+            g += CurveOrderElement::one();
+        }
+        g.pow(&CurveOrderElement::from(*ODD_PART_OF_ORDER_MINUS_ONE))
+    };
+
+    /// `t` where `curve_order - 1 = 2^TWO_ADICITY * t`
+    static ref ODD_PART_OF_ORDER_MINUS_ONE: BigNum = {
+        let mut e = CURVE_ORDER;
+        e.dec(1);
+        e.norm();
+        e.fshr(*TWO_ADICITY);
+        e
+    };
+
+    /// `(t - 1) / 2` for Tonelli-Shanks
+    static ref SQRT_CHAIN: AdditionChain = {
+        let mut e = *ODD_PART_OF_ORDER_MINUS_ONE;
+        e.fshr(1);
+        AdditionChain::new(&e)
+    };
+}
+
+#[cfg(feature = "serde")]
 impl Serialize for CurveOrderElement {
     fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
     where
@@ -531,6 +1040,8 @@ impl Serialize for CurveOrderElement {
         serializer.serialize_newtype_struct("CurveOrderElement", &self.to_hex())
     }
 }
+
+#[cfg(feature = "serde")]
 impl<'a> Deserialize<'a> for CurveOrderElement {
     fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
     where
@@ -567,16 +1078,18 @@ impl From<u8> for CurveOrderElement {
 
 impl From<u32> for CurveOrderElement {
     fn from(x: u32) -> Self {
-        Self {
-            value: BigNum::new_int(x as isize),
-        }
+        Self::from(x as u64)
     }
 }
 
 impl From<u64> for CurveOrderElement {
     fn from(x: u64) -> Self {
+        // Not using `BigNum::new_int` since `isize` and the limbs are only 32 bits on 32-bit
+        // targets and a single limb holds fewer than 64 bits even on 64-bit targets
+        let mut bytes = [0u8; FIELD_ORDER_ELEMENT_SIZE];
+        bytes[FIELD_ORDER_ELEMENT_SIZE - 8..].copy_from_slice(&x.to_be_bytes());
         Self {
-            value: BigNum::new_int(x as isize),
+            value: BigNum::frombytes(&bytes),
         }
     }
 }
@@ -798,20 +1311,77 @@ impl Neg for &CurveOrderElement {
     }
 }
 
-#[derive(Clone, Debug, Serialize, Deserialize)]
-pub struct CurveOrderElementVector(pub Vec<CurveOrderElement>);
+/// Domain separation tag of `CurveOrderElementVector::from_seed`
+pub const VECTOR_FROM_SEED_DST: &[u8] = b"AMCL_WRAPPER_VECTOR_FROM_SEED";
+
+/// Seed sent in place of a vector of curve order elements, e.g. many challenges, which the sender
+/// and receiver expand to the same vector with `expand`. The tag binds the vector to its use, so
+/// a seed expanded in one protocol or role gives unrelated elements in another.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct VectorSeed([u8; VectorSeed::SIZE]);
+
+impl VectorSeed {
+    pub const SIZE: usize = 32;
+
+    pub fn random() -> Self {
+        Self::random_using_rng(&mut thread_rng())
+    }
+
+    pub fn random_using_rng<R: RngCore + CryptoRng>(rng: &mut R) -> Self {
+        let mut seed = [0u8; Self::SIZE];
+        rng.fill_bytes(&mut seed);
+        Self(seed)
+    }
+
+    /// A random seed and its expansion, for the sender
+    pub fn random_with_vector(dst: &[u8], len: usize) -> (Self, CurveOrderElementVector) {
+        let seed = Self::random();
+        let v = seed.expand(dst, len);
+        (seed, v)
+    }
+
+    /// Fails unless `bytes` has `SIZE` bytes
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, SerzDeserzError> {
+        if bytes.len() != Self::SIZE {
+            return Err(SerzDeserzError::VectorBytesIncorrectSize(
+                bytes.len(),
+                Self::SIZE,
+            ));
+        }
+        let mut seed = [0u8; Self::SIZE];
+        seed.copy_from_slice(bytes);
+        Ok(Self(seed))
+    }
+
+    pub fn as_bytes(&self) -> &[u8; Self::SIZE] {
+        &self.0
+    }
+
+    /// `CurveOrderElementVector::from_seed_with_dst` of the seed
+    pub fn expand(&self, dst: &[u8], len: usize) -> CurveOrderElementVector {
+        CurveOrderElementVector::from_seed_with_dst(&self.0, dst, len)
+    }
+}
+
+/// The allocation of the vector is locked in memory by the hooks of `secret_mem` and zeroized
+/// when dropped or when the vector moves to a bigger allocation. Growing the vector through the
+/// public `Vec` instead of the methods of the vector leaves the new allocation unlocked.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(
+    feature = "serde",
+    serde(from = "Vec<CurveOrderElement>", into = "Vec<CurveOrderElement>")
+)]
+pub struct CurveOrderElementVector(pub Vec<CurveOrderElement>, LockedRegion);
 
 impl CurveOrderElementVector {
     /// Creates a new field element vector with each element being 0
     // FIXME: size should have a type like u64 since usize can be small on older/smaller machines. This code
     // is less likely to be used on older/smaller machines though
     pub fn new(size: usize) -> Self {
-        Self (
-            (0..size)
-                .into_par_iter()
-                .map(|_| CurveOrderElement::new())
-                .collect()
-        )
+        let mut v = Self::with_capacity(size);
+        v.0.par_extend((0..size).into_par_iter().map(|_| CurveOrderElement::new()));
+        v
     }
 
     /// Generate a Vandermonde vector of field elements as:
@@ -825,26 +1395,54 @@ impl CurveOrderElementVector {
         } else if elem.is_one() {
             vec![CurveOrderElement::one(); size].into()
         } else {
-            let mut v = Vec::<CurveOrderElement>::with_capacity(size);
+            let mut v = Self::with_capacity(size);
             v.push(CurveOrderElement::one());
             for i in 1..size {
-                v.push(&v[i - 1] * elem);
+                let next = &v[i - 1] * elem;
+                v.push(next);
             }
-            v.into()
+            v
         }
     }
 
+    /// Vector of `len` elements expanded from `seed` with the tag `VECTOR_FROM_SEED_DST`
+    pub fn from_seed(seed: &[u8], len: usize) -> Self {
+        Self::from_seed_with_dst(seed, VECTOR_FROM_SEED_DST, len)
+    }
+
+    /// Vector of `len` elements expanded from `seed` with the tag `dst`, see
+    /// `utils::expand_seed_to_scalars`
+    pub fn from_seed_with_dst(seed: &[u8], dst: &[u8], len: usize) -> Self {
+        expand_seed_to_scalars(seed, dst, len).into()
+    }
+
     pub fn with_capacity(capacity: usize) -> Self {
-        Self (Vec::<CurveOrderElement>::with_capacity(capacity))
+        let (v, region) = locked_vec(capacity);
+        Self(v, region)
     }
 
     /// Get a vector of random field elements
     pub fn random(size: usize) -> Self {
-        (0..size)
-            .into_par_iter()
-            .map(|_| CurveOrderElement::random())
-            .collect::<Vec<CurveOrderElement>>()
-            .into()
+        let mut v = Self::with_capacity(size);
+        v.0.par_extend((0..size).into_par_iter().map(|_| CurveOrderElement::random()));
+        v
+    }
+
+    /// Make room for `additional` more elements, moving to a new locked allocation if needed
+    fn reserve(&mut self, additional: usize) {
+        if self.0.capacity() - self.0.len() >= additional {
+            return;
+        }
+        let capacity = std::cmp::max(2 * self.0.capacity(), self.0.len() + additional);
+        let mut bigger = Self::with_capacity(capacity);
+        bigger.0.extend_from_slice(&self.0);
+        std::mem::swap(self, &mut bigger);
+    }
+
+    /// Copy of the elements in memory locked by the hooks of `secret_mem`, which is zeroized
+    /// when dropped
+    pub fn to_secret_buffer(&self) -> SecretBuffer<CurveOrderElement> {
+        SecretBuffer::from_slice(&self.0)
     }
 
     pub fn as_slice(&self) -> &[CurveOrderElement] {
@@ -860,10 +1458,12 @@ impl CurveOrderElementVector {
     }
 
     pub fn push(&mut self, value: CurveOrderElement) {
+        self.reserve(1);
         self.0.push(value)
     }
 
     pub fn append(&mut self, other: &mut Self) {
+        self.reserve(other.len());
         self.0.append(&mut other.0)
     }
 
@@ -872,6 +1472,7 @@ impl CurveOrderElementVector {
     }
 
     pub fn insert(&mut self, index: usize, element: CurveOrderElement) {
+        self.reserve(1);
         self.0.insert(index, element)
     }
 
@@ -929,12 +1530,47 @@ impl CurveOrderElementVector {
 
     /// Computes inner product of 2 vectors of field elements
     /// [a1, a2, a3, ...field elements].[b1, b2, b3, ...field elements] = (a1*b1 + a2*b2 + a3*b3) % curve_order
+    /// Computed in parallel with `par_inner_product`.
     pub fn inner_product(&self, b: &CurveOrderElementVector) -> Result<CurveOrderElement, ValueError> {
+        self.par_inner_product(b)
+    }
+
+    /// Sequential version of `inner_product`, for small vectors or callers already running in
+    /// parallel. Products are summed unreduced and reduced once per `INNER_PRODUCT_CHUNK_SIZE` terms.
+    pub fn seq_inner_product(&self, b: &CurveOrderElementVector) -> Result<CurveOrderElement, ValueError> {
         check_vector_size_for_equality!(self, b)?;
-        let r = (0..b.len()).into_par_iter().map(|i| (&self[i] * &b[i])).reduce(|| CurveOrderElement::new(), |a, b| a + b);
+        let r = self
+            .0
+            .chunks(INNER_PRODUCT_CHUNK_SIZE)
+            .zip(b.0.chunks(INNER_PRODUCT_CHUNK_SIZE))
+            .fold(CurveOrderElement::new(), |acc, (x, y)| acc + Self::unreduced_inner_product(x, y));
         Ok(r)
     }
 
+    /// Parallel inner product, each thread reduces once per chunk of terms it sums.
+    pub fn par_inner_product(&self, b: &CurveOrderElementVector) -> Result<CurveOrderElement, ValueError> {
+        check_vector_size_for_equality!(self, b)?;
+        let chunk_size = (self.len() / rayon::current_num_threads()).clamp(1024, INNER_PRODUCT_CHUNK_SIZE);
+        let r = self
+            .0
+            .par_chunks(chunk_size)
+            .zip(b.0.par_chunks(chunk_size))
+            .map(|(x, y)| Self::unreduced_inner_product(x, y))
+            .reduce(CurveOrderElement::new, |a, b| a + b);
+        Ok(r)
+    }
+
+    /// Sum of products of `a` and `b` accumulated in a `DoubleBigNum` and reduced at the end.
+    /// `a` must not be longer than `INNER_PRODUCT_CHUNK_SIZE`.
+    fn unreduced_inner_product(a: &[CurveOrderElement], b: &[CurveOrderElement]) -> CurveOrderElement {
+        let mut acc = DoubleBigNum::new();
+        for (x, y) in a.iter().zip(b.iter()) {
+            acc.add(&BigNum::mul(&x.value, &y.value));
+            acc.norm();
+        }
+        acc.dmod(&CURVE_ORDER).into()
+    }
+
     /// Calculates Hadamard product of 2 field element vectors.
     /// Hadamard product of `a` and `b` = `a` o `b` = (a0 o b0, a1 o b1, ...).
     /// Here `o` denotes multiply operation
@@ -952,11 +1588,77 @@ impl CurveOrderElementVector {
         Ok(hadamard_product)
     }
 
+    /// Inverses of all elements, zeros map to zero. The vector is split into a chunk per thread and
+    /// each chunk is inverted with a single inversion by the Montgomery trick.
+    pub fn batch_invert(&self) -> Self {
+        let mut inverses = self.clone();
+        let chunk_size = (self.len() / rayon::current_num_threads()).max(256);
+        inverses
+            .0
+            .par_chunks_mut(chunk_size)
+            .for_each(Self::batch_invert_in_place);
+        inverses
+    }
+
+    /// Square roots of all elements, None for the non-squares. Square roots are computed in
+    /// parallel, see `CurveOrderElement::sqrt`.
+    pub fn batch_sqrt(&self) -> Vec<Option<CurveOrderElement>> {
+        self.0.par_iter().map(|e| e.sqrt()).collect()
+    }
+
+    /// Montgomery trick skipping zeros, which stay zero
+    fn batch_invert_in_place(elems: &mut [CurveOrderElement]) {
+        // Prefix products of the non-zero elements
+        let mut prefix = Vec::with_capacity(elems.len());
+        let mut acc = CurveOrderElement::one();
+        for e in elems.iter() {
+            prefix.push(acc.clone());
+            if !e.is_zero() {
+                acc = &acc * e;
+            }
+        }
+        let mut u = acc.inverse();
+        for (e, p) in elems.iter_mut().zip(prefix.iter()).rev() {
+            if e.is_zero() {
+                continue;
+            }
+            let inv = &u * p;
+            u = &u * &*e;
+            *e = inv;
+        }
+    }
+
     pub fn split_at(&self, mid: usize) -> (Self, Self) {
         let (l, r) = self.as_slice().split_at(mid);
         (Self::from(l), Self::from(r))
     }
 
+    /// Borrow a range of the vector. Unlike `split_at`, nothing is cloned.
+    pub fn slice<R: SliceIndex<[CurveOrderElement], Output = [CurveOrderElement]>>(
+        &self,
+        range: R,
+    ) -> &[CurveOrderElement] {
+        &self.0[range]
+    }
+
+    /// Split into `[0, mid)`, which is kept in `self`, and `[mid, len)`, which is returned. Elements
+    /// are moved, not cloned.
+    pub fn split_off(&mut self, mid: usize) -> Self {
+        let mut tail = Self::with_capacity(self.len().checked_sub(mid).expect("`mid` > len"));
+        tail.0.extend(self.0.drain(mid..));
+        tail
+    }
+
+    /// Iterate over non-overlapping slices of `chunk_size` elements, the last may be shorter
+    pub fn chunks(&self, chunk_size: usize) -> Chunks<'_, CurveOrderElement> {
+        self.0.chunks(chunk_size)
+    }
+
+    /// Parallel version of `chunks`
+    pub fn par_chunks(&self, chunk_size: usize) -> rayon::slice::Chunks<'_, CurveOrderElement> {
+        self.0.par_chunks(chunk_size)
+    }
+
     /// Replace a range `R` of the vector with `I`. Same as Vector's splice except it does not return
     /// anything. Only available to this crate for now for some manipulations in Polynomial
     pub(crate) fn splice<R, I>(&mut self, range: R, replace_with: I)
@@ -964,29 +1666,92 @@ impl CurveOrderElementVector {
         R: RangeBounds<usize>,
         I: IntoIterator<Item = CurveOrderElement>,
     {
-        self.0.splice(range, replace_with);
+        let replace_with: Self = replace_with.into_iter().collect();
+        self.reserve(replace_with.len());
+        self.0.splice(range, replace_with.0.iter().cloned());
     }
 
     pub fn iter(&self) -> Iter<CurveOrderElement> {
         self.as_slice().iter()
     }
+
+    pub fn par_iter(&self) -> rayon::slice::Iter<'_, CurveOrderElement> {
+        self.0.par_iter()
+    }
+
+    pub fn par_iter_mut(&mut self) -> rayon::slice::IterMut<'_, CurveOrderElement> {
+        self.0.par_iter_mut()
+    }
+
+    /// Big-endian `u32` length followed by the compressed bytes of each element. Much more compact
+    /// and faster to load than the serde encoding of large vectors. Elements are kept in canonical
+    /// form, not Montgomery form, so encoding needs no conversion and is done in parallel.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = vec![0u8; 4 + self.len() * CURVE_ORDER_ELEMENT_SIZE];
+        bytes[..4].copy_from_slice(&(self.len() as u32).to_be_bytes());
+        bytes[4..]
+            .par_chunks_mut(CURVE_ORDER_ELEMENT_SIZE)
+            .zip(self.0.par_iter())
+            .for_each(|(b, e)| b.copy_from_slice(&e.to_compressed_bytes()));
+        bytes
+    }
+
+    /// Inverse of `to_bytes`, elements are decoded in parallel
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, SerzDeserzError> {
+        if bytes.len() < 4 {
+            return Err(SerzDeserzError::VectorBytesIncorrectSize(bytes.len(), 4));
+        }
+        let len = u32::from_be_bytes(*array_ref![bytes, 0, 4]) as usize;
+        let expected = len
+            .checked_mul(CURVE_ORDER_ELEMENT_SIZE)
+            .and_then(|l| l.checked_add(4));
+        if expected != Some(bytes.len()) {
+            return Err(SerzDeserzError::VectorBytesIncorrectSize(
+                bytes.len(),
+                expected.unwrap_or(usize::MAX),
+            ));
+        }
+        let elems = bytes[4..]
+            .par_chunks(CURVE_ORDER_ELEMENT_SIZE)
+            .map(CurveOrderElement::try_from)
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(elems.into())
+    }
 }
 
+/// Locks the allocation of `x` in place
 impl From<Vec<CurveOrderElement>> for CurveOrderElementVector {
     fn from(x: Vec<CurveOrderElement>) -> Self {
-        Self(x)
+        let region = LockedRegion::lock_vec(&x);
+        Self(x, region)
     }
 }
 
 impl From<&[CurveOrderElement]> for CurveOrderElementVector {
     fn from(x: &[CurveOrderElement]) -> Self {
-        Self(x.to_vec())
+        let mut v = Self::with_capacity(x.len());
+        v.0.extend_from_slice(x);
+        v
     }
 }
 
 impl Into<Vec<CurveOrderElement>> for CurveOrderElementVector {
-    fn into(self) -> Vec<CurveOrderElement> {
-        self.0
+    fn into(mut self) -> Vec<CurveOrderElement> {
+        std::mem::take(&mut self.0)
+    }
+}
+
+impl Clone for CurveOrderElementVector {
+    fn clone(&self) -> Self {
+        self.as_slice().into()
+    }
+}
+
+impl Drop for CurveOrderElementVector {
+    fn drop(&mut self) {
+        self.0.zeroize();
+        // Unlock before the memory is freed
+        self.1 = LockedRegion::default();
     }
 }
 
@@ -1010,6 +1775,8 @@ impl IndexMut<usize> for CurveOrderElementVector {
     }
 }
 
+impl_vec_range_index!(CurveOrderElement, CurveOrderElementVector);
+
 impl PartialEq for CurveOrderElementVector {
     fn eq(&self, other: &Self) -> bool {
         if self.len() != other.len() {
@@ -1024,12 +1791,46 @@ impl PartialEq for CurveOrderElementVector {
     }
 }
 
+#[cfg(feature = "arbitrary")]
+impl<'a> arbitrary::Arbitrary<'a> for CurveOrderElementVector {
+    fn arbitrary(u: &mut arbitrary::Unstructured<'a>) -> arbitrary::Result<Self> {
+        Ok(Vec::<CurveOrderElement>::arbitrary(u)?.into())
+    }
+}
+
+impl fmt::Debug for CurveOrderElementVector {
+    /// Length and digests of the first elements
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt_digests(
+            f,
+            "CurveOrderElementVector",
+            self.len(),
+            self.iter().map(|e| e.to_bytes().to_vec()),
+        )
+    }
+}
+
+impl fmt::Display for CurveOrderElementVector {
+    /// Every element on its own line
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "CurveOrderElementVector of length {} [", self.len())?;
+        for (i, e) in self.iter().enumerate() {
+            if f.alternate() {
+                writeln!(f, "  {}: {:#}", i, e)?;
+            } else {
+                writeln!(f, "  {}: {}", i, e)?;
+            }
+        }
+        write!(f, "]")
+    }
+}
+
 impl IntoIterator for CurveOrderElementVector {
     type Item = CurveOrderElement;
     type IntoIter = ::std::vec::IntoIter<CurveOrderElement>;
 
-    fn into_iter(self) -> Self::IntoIter {
-        self.0.into_iter()
+    fn into_iter(mut self) -> Self::IntoIter {
+        std::mem::take(&mut self.0).into_iter()
     }
 }
 
@@ -1039,6 +1840,57 @@ impl AsRef<[CurveOrderElement]> for CurveOrderElementVector {
     }
 }
 
+impl FromIterator<CurveOrderElement> for CurveOrderElementVector {
+    fn from_iter<I: IntoIterator<Item = CurveOrderElement>>(iter: I) -> Self {
+        let mut v = Self::new(0);
+        v.extend(iter);
+        v
+    }
+}
+
+impl Extend<CurveOrderElement> for CurveOrderElementVector {
+    fn extend<I: IntoIterator<Item = CurveOrderElement>>(&mut self, iter: I) {
+        let iter = iter.into_iter();
+        self.reserve(iter.size_hint().0);
+        for e in iter {
+            self.push(e);
+        }
+    }
+}
+
+impl<'a> IntoIterator for &'a CurveOrderElementVector {
+    type Item = &'a CurveOrderElement;
+    type IntoIter = Iter<'a, CurveOrderElement>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.iter()
+    }
+}
+
+impl FromParallelIterator<CurveOrderElement> for CurveOrderElementVector {
+    fn from_par_iter<I: IntoParallelIterator<Item = CurveOrderElement>>(par_iter: I) -> Self {
+        par_iter.into_par_iter().collect::<Vec<_>>().into()
+    }
+}
+
+impl IntoParallelIterator for CurveOrderElementVector {
+    type Item = CurveOrderElement;
+    type Iter = rayon::vec::IntoIter<CurveOrderElement>;
+
+    fn into_par_iter(mut self) -> Self::Iter {
+        std::mem::take(&mut self.0).into_par_iter()
+    }
+}
+
+impl<'a> IntoParallelIterator for &'a CurveOrderElementVector {
+    type Item = &'a CurveOrderElement;
+    type Iter = rayon::slice::Iter<'a, CurveOrderElement>;
+
+    fn into_par_iter(self) -> Self::Iter {
+        self.0.par_iter()
+    }
+}
+
 // TODO: Implement add/sub/mul ops but need some way to handle error when vectors are of different length
 
 pub fn multiply_row_vector_with_matrix(
@@ -1059,8 +1911,9 @@ pub fn multiply_row_vector_with_matrix(
 #[cfg(test)]
 mod test {
     use super::*;
+    use crate::group_elem::GroupElement;
+    use crate::group_elem_g1::G1;
     use rand::Rng;
-    use serde_json;
     use std::collections::{HashMap, HashSet};
     use std::time::Instant;
 
@@ -1084,6 +1937,15 @@ mod test {
         }
     }
 
+    #[test]
+    fn test_from_u64() {
+        // Values that don't fit in a single limb
+        assert_eq!(CurveOrderElement::from(u64::MAX), CurveOrderElement::from_hex("FFFFFFFFFFFFFFFF".to_string()).unwrap());
+        assert_eq!(CurveOrderElement::from(1u64 << 63), CurveOrderElement::from_hex("8000000000000000".to_string()).unwrap());
+        assert_eq!(CurveOrderElement::from(u32::MAX), CurveOrderElement::from_hex("FFFFFFFF".to_string()).unwrap());
+        assert_eq!(CurveOrderElement::from(u32::MAX) + CurveOrderElement::one(), CurveOrderElement::from(1u64 << 32));
+    }
+
     #[test]
     fn test_equality() {
         for _ in 0..10 {
@@ -1113,8 +1975,68 @@ mod test {
         for _ in 0..10 {
             let x = CurveOrderElement::random();
             let x_inv = x.inverse();
+            assert_eq!(x.inverse_const_time(), x_inv);
             assert_eq!(x * x_inv, CurveOrderElement::one())
         }
+        assert!(CurveOrderElement::zero().inverse_const_time().is_zero());
+        assert!(CurveOrderElement::one().inverse_const_time().is_one());
+        assert_eq!(CurveOrderElement::minus_one().inverse_const_time(), CurveOrderElement::minus_one());
+    }
+
+    #[test]
+    fn test_fixed_exponents() {
+        let mut e = CURVE_ORDER;
+        e.dec(2);
+        let order_minus_two = CurveOrderElement::from(e);
+        let half = CurveOrderElement::minus_one().shift_right(1);
+        for _ in 0..10 {
+            let x = CurveOrderElement::random();
+            assert_eq!(x.pow_by_order_minus_two(), x.pow(&order_minus_two));
+            assert_eq!(x.pow_by_order_minus_one_half(), x.pow(&half));
+        }
+        assert!(CurveOrderElement::zero().pow_by_order_minus_two().is_zero());
+    }
+
+    #[test]
+    fn test_vector_batch_invert_and_sqrt() {
+        let mut v = CurveOrderElementVector::random(600);
+        v[3] = CurveOrderElement::zero();
+        v[599] = CurveOrderElement::zero();
+        let inverses = v.batch_invert();
+        assert_eq!(inverses.len(), v.len());
+        for (e, inv) in v.iter().zip(inverses.iter()) {
+            assert_eq!(*inv, e.inverse());
+        }
+        assert_eq!(CurveOrderElementVector::new(0).batch_invert().len(), 0);
+
+        let squares: CurveOrderElementVector = v.iter().map(|e| e.square()).collect();
+        let roots = squares.batch_sqrt();
+        for (s, r) in squares.iter().zip(roots.iter()) {
+            assert_eq!(r.as_ref().unwrap().square(), *s);
+        }
+        // A primitive 2^s-th root of unity is not a square
+        let non_square = &squares[0] * &*TWO_ADIC_ROOT_OF_UNITY;
+        assert_eq!(CurveOrderElementVector::from(vec![non_square]).batch_sqrt(), vec![None]);
+    }
+
+    #[test]
+    fn test_sqrt() {
+        assert_eq!(CurveOrderElement::zero().sqrt(), Some(CurveOrderElement::zero()));
+        let mut non_residues = 0;
+        for _ in 0..20 {
+            let x = CurveOrderElement::random();
+            let x2 = x.square();
+            assert!(x2.is_quadratic_residue());
+            let r = x2.sqrt().unwrap();
+            assert!(r == x || r == x.negation());
+
+            let y = CurveOrderElement::random();
+            if !y.is_quadratic_residue() {
+                non_residues += 1;
+                assert!(y.sqrt().is_none());
+            }
+        }
+        assert!(non_residues > 0);
     }
 
     #[test]
@@ -1211,13 +2133,13 @@ mod test {
 
     #[test]
     fn test_elements_inner_product() {
-        let a = CurveOrderElementVector(vec![
+        let a = CurveOrderElementVector::from(vec![
             CurveOrderElement::from(5),
             CurveOrderElement::one(),
             CurveOrderElement::from(100),
             CurveOrderElement::zero(),
         ]);
-        let b = CurveOrderElementVector(vec![
+        let b = CurveOrderElementVector::from(vec![
             CurveOrderElement::from(18),
             CurveOrderElement::one(),
             CurveOrderElement::from(200),
@@ -1225,23 +2147,38 @@ mod test {
         ]);
         let c = CurveOrderElement::from((90 + 1 + 200 * 100) as u32);
         assert_eq!(a.inner_product(&b).unwrap(), c);
+        assert_eq!(a.seq_inner_product(&b).unwrap(), c);
+
+        // Spans several chunks
+        for n in [0, 1, 1000, INNER_PRODUCT_CHUNK_SIZE + 3].iter() {
+            let a = CurveOrderElementVector::random(*n);
+            let b = CurveOrderElementVector::random(*n);
+            let expected = a
+                .iter()
+                .zip(b.iter())
+                .fold(CurveOrderElement::zero(), |acc, (x, y)| acc + x * y);
+            assert_eq!(a.inner_product(&b).unwrap(), expected);
+            assert_eq!(a.seq_inner_product(&b).unwrap(), expected);
+        }
+        assert!(a.inner_product(&CurveOrderElementVector::random(3)).is_err());
+        assert!(a.seq_inner_product(&CurveOrderElementVector::random(3)).is_err());
     }
 
     #[test]
     fn test_elements_hadamard_product() {
-        let a = CurveOrderElementVector(vec![
+        let a = CurveOrderElementVector::from(vec![
             CurveOrderElement::from(5),
             CurveOrderElement::one(),
             CurveOrderElement::from(100),
             CurveOrderElement::zero(),
         ]);
-        let b = CurveOrderElementVector(vec![
+        let b = CurveOrderElementVector::from(vec![
             CurveOrderElement::from(18),
             CurveOrderElement::one(),
             CurveOrderElement::from(200),
             CurveOrderElement::zero(),
         ]);
-        let h = CurveOrderElementVector(vec![
+        let h = CurveOrderElementVector::from(vec![
             CurveOrderElement::from(90),
             CurveOrderElement::one(),
             CurveOrderElement::from(200 * 100),
@@ -1254,7 +2191,7 @@ mod test {
 
     #[test]
     fn test_scale_element_vector() {
-        let a = CurveOrderElementVector(vec![
+        let a = CurveOrderElementVector::from(vec![
             CurveOrderElement::from(5),
             CurveOrderElement::from(1),
             CurveOrderElement::from(100),
@@ -1270,13 +2207,13 @@ mod test {
 
     #[test]
     fn test_add_element_vectors() {
-        let a = CurveOrderElementVector(vec![
+        let a = CurveOrderElementVector::from(vec![
             CurveOrderElement::from(5),
             CurveOrderElement::one(),
             CurveOrderElement::from(100),
             CurveOrderElement::zero(),
         ]);
-        let b = CurveOrderElementVector(vec![
+        let b = CurveOrderElementVector::from(vec![
             CurveOrderElement::from(18),
             CurveOrderElement::one(),
             CurveOrderElement::from(200),
@@ -1289,6 +2226,79 @@ mod test {
         assert_eq!(c[3], CurveOrderElement::from(0));
     }
 
+    #[test]
+    fn test_vector_to_and_from_bytes() {
+        for size in [0, 1, 10, 100] {
+            let v = CurveOrderElementVector::random(size);
+            let bytes = v.to_bytes();
+            assert_eq!(bytes.len(), 4 + size * CURVE_ORDER_ELEMENT_SIZE);
+            let v1 = CurveOrderElementVector::from_bytes(&bytes).unwrap();
+            assert_eq!(v.as_slice(), v1.as_slice());
+            assert!(CurveOrderElementVector::from_bytes(&bytes[..bytes.len() - 1]).is_err());
+        }
+        assert!(CurveOrderElementVector::from_bytes(&[0u8; 3]).is_err());
+        // The largest length prefix must not overflow the expected size
+        assert!(CurveOrderElementVector::from_bytes(&[0xffu8; 4]).is_err());
+    }
+
+    #[test]
+    fn test_vector_range_index() {
+        let mut v = CurveOrderElementVector::random(10);
+        let elems = v.as_slice().to_vec();
+        assert_eq!(&v[2..5], &elems[2..5]);
+        assert_eq!(&v[7..], &elems[7..]);
+        assert_eq!(&v[..3], &elems[..3]);
+        assert_eq!(&v[..], &elems[..]);
+        assert_eq!(&v[2..=5], &elems[2..=5]);
+        assert_eq!(&v[..=3], &elems[..=3]);
+        assert_eq!(v.slice(4..6), &elems[4..6]);
+
+        v[..2].clone_from_slice(&elems[8..]);
+        assert_eq!(&v[..2], &elems[8..]);
+    }
+
+    #[test]
+    fn test_vector_split_off_and_chunks() {
+        let mut v = CurveOrderElementVector::random(10);
+        let elems = v.as_slice().to_vec();
+        let r = v.split_off(6);
+        assert_eq!(v.as_slice(), &elems[..6]);
+        assert_eq!(r.as_slice(), &elems[6..]);
+
+        let v: CurveOrderElementVector = elems.clone().into();
+        let chunks: Vec<_> = v.chunks(3).collect();
+        assert_eq!(chunks.len(), 4);
+        assert_eq!(chunks[3], &elems[9..]);
+        let sums: Vec<_> = v
+            .par_chunks(3)
+            .map(|c| c.iter().fold(CurveOrderElement::new(), |a, b| a + b))
+            .collect();
+        assert_eq!(sums.len(), 4);
+        assert_eq!(sums[1], &elems[3] + &elems[4] + &elems[5]);
+    }
+
+    #[test]
+    fn test_vector_iterators() {
+        let elems: Vec<_> = (0..10).map(|_| CurveOrderElement::random()).collect();
+        let mut v: CurveOrderElementVector = elems.iter().cloned().collect();
+        assert_eq!(v.as_slice(), elems.as_slice());
+
+        v.extend(elems.iter().cloned());
+        assert_eq!(v.len(), 20);
+        assert_eq!(&v.as_slice()[10..], elems.as_slice());
+
+        let doubled: CurveOrderElementVector = v.par_iter().map(|e| e + e).collect();
+        for (d, e) in doubled.iter().zip(&v) {
+            assert_eq!(*d, e + e);
+        }
+        assert_eq!(
+            (&v).into_par_iter().cloned().reduce(CurveOrderElement::new, |a, b| a + b),
+            v.sum()
+        );
+        let sum = v.sum();
+        assert_eq!(v.into_par_iter().reduce(CurveOrderElement::new, |a, b| a + b), sum);
+    }
+
     #[test]
     fn test_elem_vandermonde_vector() {
         let zero_vec = CurveOrderElementVector::new_vandermonde_vector(&CurveOrderElement::zero(), 5);
@@ -1308,11 +2318,42 @@ mod test {
         }
     }
 
+    #[test]
+    fn test_vector_from_seed() {
+        let v = CurveOrderElementVector::from_seed(b"seed", 10);
+        assert_eq!(v.len(), 10);
+        assert_eq!(v, CurveOrderElementVector::from_seed(b"seed", 10));
+        assert_eq!(CurveOrderElementVector::from_seed(b"seed", 4)[..], v[..4]);
+        assert_ne!(CurveOrderElementVector::from_seed(b"seed2", 10), v);
+        assert_ne!(
+            CurveOrderElementVector::from_seed_with_dst(b"seed", b"other", 10),
+            v
+        );
+        let mut elems = v.as_slice().to_vec();
+        elems.sort();
+        elems.dedup();
+        assert_eq!(elems.len(), 10);
+        assert_eq!(CurveOrderElementVector::from_seed(b"seed", 0).len(), 0);
+
+        // The sender transmits only the seed and the receiver expands it to the same vector
+        let (seed, challenges) = VectorSeed::random_with_vector(b"challenges", 100);
+        let received = VectorSeed::from_bytes(seed.as_bytes()).unwrap();
+        assert_eq!(received.expand(b"challenges", 100), challenges);
+        assert_ne!(received.expand(b"responses", 100), challenges);
+        assert_ne!(VectorSeed::random(), seed);
+        assert!(VectorSeed::from_bytes(&[0u8; 31]).is_err());
+        #[cfg(feature = "serde")]
+        {
+            let s = serde_json::to_string(&seed).unwrap();
+            assert_eq!(serde_json::from_str::<VectorSeed>(&s).unwrap(), seed);
+        }
+    }
+
     #[test]
     fn test_to_bitvectors() {
         let n = CurveOrderElement::from(100u32);
         assert_eq!(n.to_bitvectors(), vec![vec![0, 0, 1, 0, 0, 1, 1]]);
-        let mut c = vec![0i64; NLEN];
+        let mut c = vec![0 as Limb; NLEN];
         c[0] = 2;
         c[1] = 100;
         let m: CurveOrderElement = BigNum::new_ints(&c).into();
@@ -1332,7 +2373,7 @@ mod test {
         bits.append(&mut vec![0; BIG_NUM_BITS - 7]);
         assert_eq!(CurveOrderElement::from(100u32).to_bits(), bits);
 
-        let mut c = vec![0i64; NLEN];
+        let mut c = vec![0 as Limb; NLEN];
         c[0] = 2;
         c[1] = 100;
         let m: CurveOrderElement = BigNum::new_ints(&c).into();
@@ -1391,6 +2432,129 @@ mod test {
         assert_eq!(one + minus_one, zero);
     }
 
+    #[test]
+    fn test_bytes_le() {
+        let x = CurveOrderElement::from(0x0102u64);
+        let bytes = x.to_bytes_le();
+        assert_eq!(bytes[..3], [2, 1, 0]);
+        assert_eq!(x.to_compressed_bytes_le()[..3], [2, 1, 0]);
+        for _ in 0..10 {
+            let r = CurveOrderElement::random();
+            let mut be = r.to_bytes();
+            be.reverse();
+            assert_eq!(r.to_bytes_le(), be);
+            assert_eq!(CurveOrderElement::from_bytes_le(&r.to_bytes_le()).unwrap(), r);
+            assert_eq!(CurveOrderElement::from_bytes_le(&r.to_compressed_bytes_le()).unwrap(), r);
+        }
+        assert!(CurveOrderElement::from_bytes_le(&[1u8; 5]).is_err());
+    }
+
+    #[test]
+    fn test_conditional_swap_assign() {
+        let a = CurveOrderElement::random();
+        let b = CurveOrderElement::random();
+        let (mut x, mut y) = (a.clone(), b.clone());
+        CurveOrderElement::conditional_swap(&mut x, &mut y, false);
+        assert_eq!((&x, &y), (&a, &b));
+        CurveOrderElement::conditional_swap(&mut x, &mut y, true);
+        assert_eq!((&x, &y), (&b, &a));
+
+        let mut x = a.clone();
+        x.conditional_assign(&b, false);
+        assert_eq!(x, a);
+        x.conditional_assign(&b, true);
+        assert_eq!(x, b);
+    }
+
+    #[cfg(feature = "rand-distributions")]
+    #[test]
+    fn test_standard_distribution() {
+        use rand::rngs::{mock::StepRng, StdRng};
+        let mut rng = StdRng::seed_from_u64(7);
+        let a: CurveOrderElement = rng.gen();
+        let b: CurveOrderElement = rng.gen();
+        assert_ne!(a, b);
+        assert_eq!(StdRng::seed_from_u64(7).gen::<CurveOrderElement>(), a);
+
+        // Generators that are not cryptographically secure work too
+        let elems: Vec<CurveOrderElement> = StepRng::new(1, 1).sample_iter(rand::distributions::Standard).take(3).collect();
+        assert_ne!(elems[0], elems[1]);
+    }
+
+    #[cfg(feature = "arbitrary")]
+    #[test]
+    fn test_arbitrary() {
+        use arbitrary::{Arbitrary, Unstructured};
+        let mut data = vec![0u8, 1, 2, 3];
+        data.extend_from_slice(&[0xffu8; FIELD_ORDER_ELEMENT_SIZE]);
+        let mut u = Unstructured::new(&data);
+        assert!(CurveOrderElement::arbitrary(&mut u).unwrap().is_zero());
+        assert!(CurveOrderElement::arbitrary(&mut u).unwrap().is_one());
+        assert_eq!(CurveOrderElement::arbitrary(&mut u).unwrap(), CurveOrderElement::minus_one());
+        let mut bytes = [0xffu8; FIELD_ORDER_ELEMENT_SIZE];
+        assert_eq!(CurveOrderElement::arbitrary(&mut u).unwrap(), CurveOrderElement::from(bytes));
+
+        // Different data gives different elements, and short data still gives elements
+        bytes[0] = 7;
+        let a = CurveOrderElement::arbitrary(&mut Unstructured::new(&bytes)).unwrap();
+        bytes[1] = 8;
+        assert_ne!(CurveOrderElement::arbitrary(&mut Unstructured::new(&bytes)).unwrap(), a);
+        assert!(CurveOrderElement::arbitrary(&mut Unstructured::new(&[])).is_ok());
+        let random: Vec<u8> = (0..1000).map(|_| rand::random()).collect();
+        assert!(CurveOrderElementVector::arbitrary(&mut Unstructured::new(&random)).is_ok());
+    }
+
+    #[test]
+    fn test_to_wnaf() {
+        // Include elements close to the order whose recoding carries into the top limb
+        let mut elems = vec![CurveOrderElement::one(), CurveOrderElement::minus_one()];
+        elems.extend((1..20u64).map(|i| CurveOrderElement::from(i).inverse()));
+        elems.extend((0..10).map(|_| CurveOrderElement::random()));
+        let g = G1::generator();
+        for x in elems {
+            let mut acc = CurveOrderElement::zero();
+            for d in x.to_wnaf(5).iter().rev() {
+                acc = &acc + &acc;
+                if *d >= 0 {
+                    acc += CurveOrderElement::from(*d as u8);
+                } else {
+                    acc -= CurveOrderElement::from(-*d as u8);
+                }
+            }
+            assert_eq!(acc, x);
+            assert_eq!(g.scalar_mul_variable_time(&x), &g * &x);
+        }
+    }
+
+    #[test]
+    fn test_to_booth() {
+        for w in 2..=7 {
+            let two_w = CurveOrderElement::from(1u64 << w);
+            let bound = 1i8 << (w - 1);
+            let mut elems = vec![
+                CurveOrderElement::zero(),
+                CurveOrderElement::one(),
+                CurveOrderElement::minus_one(),
+            ];
+            elems.extend((0..10).map(|_| CurveOrderElement::random()));
+            for x in elems {
+                let digits = x.to_booth(w);
+                assert_eq!(digits.len(), CurveOrderElement::one().to_booth(w).len());
+                let mut acc = CurveOrderElement::zero();
+                for d in digits.iter().rev() {
+                    assert!(*d >= -bound && *d <= bound);
+                    acc = &acc * &two_w;
+                    if *d >= 0 {
+                        acc += CurveOrderElement::from(*d as u8);
+                    } else {
+                        acc -= CurveOrderElement::from(-*d as u8);
+                    }
+                }
+                assert_eq!(acc, x);
+            }
+        }
+    }
+
     #[test]
     fn test_elem_to_from_base() {
         for i in 0..4 {
@@ -1637,6 +2801,70 @@ mod test {
         assert!(CurveOrderElement::parse_hex_as_bignum(h.clone()).is_err());
     }
 
+    #[test]
+    fn test_ct_parsing() {
+        let mut elems = vec![
+            CurveOrderElement::zero(),
+            CurveOrderElement::one(),
+            CurveOrderElement::minus_one(),
+        ];
+        elems.extend((0..20).map(|_| CurveOrderElement::random()));
+        for e in elems {
+            let hex = e.to_hex();
+            assert_eq!(CurveOrderElement::from_hex_ct(&hex).unwrap(), e);
+            assert_eq!(CurveOrderElement::from_hex_ct(&hex.to_lowercase()).unwrap(), e);
+            let bytes = e.to_bytes();
+            assert_eq!(CurveOrderElement::from_slice_ct(&bytes).unwrap(), e);
+            let compressed = e.to_compressed_bytes();
+            assert_eq!(CurveOrderElement::from_slice_ct(&compressed).unwrap(), e);
+        }
+
+        // Values not reduced modulo the order, short and odd length strings
+        for bytes in [[0xffu8; FIELD_ORDER_ELEMENT_SIZE], [0x80u8; FIELD_ORDER_ELEMENT_SIZE]].iter() {
+            let expected = CurveOrderElement::try_from(&bytes[..]).unwrap();
+            assert_eq!(CurveOrderElement::from_slice_ct(bytes).unwrap(), expected);
+            let hex = String::from_utf8(subtle_encoding::hex::encode(&bytes[..])).unwrap();
+            assert_eq!(CurveOrderElement::from_hex_ct(&hex).unwrap(), expected);
+        }
+        for s in ["", "a", "1F", "abc", "0123456789abcdefABCDEF"].iter() {
+            assert_eq!(
+                CurveOrderElement::from_hex_ct(s).unwrap(),
+                CurveOrderElement::from_hex(s.to_string()).unwrap()
+            );
+        }
+
+        for s in ["g", "12 3", "0x12", "-1", "/", ":", "@", "G", "`"].iter() {
+            assert!(CurveOrderElement::from_hex_ct(s).is_err());
+        }
+        assert!(CurveOrderElement::from_hex_ct(&"0".repeat(FIELD_ORDER_ELEMENT_SIZE * 2 + 1)).is_err());
+        assert!(CurveOrderElement::from_slice_ct(&[1u8; 5]).is_err());
+    }
+
+    #[test]
+    fn test_decimal_elem() {
+        assert_eq!(CurveOrderElement::zero().to_decimal_string(), "0");
+        assert_eq!(CurveOrderElement::from(1234567890u64).to_decimal_string(), "1234567890");
+        assert_eq!(CurveOrderElement::from(u64::MAX).to_decimal_string(), "18446744073709551615");
+        assert_eq!(CurveOrderElement::from_decimal_str("18446744073709551615").unwrap(), CurveOrderElement::from(u64::MAX));
+        assert_eq!(CurveOrderElement::from_decimal_str("000042").unwrap(), CurveOrderElement::from(42u64));
+        for _ in 0..30 {
+            let r = CurveOrderElement::random();
+            let s = r.to_decimal_string();
+            assert!(!s.starts_with('0'));
+            assert_eq!(CurveOrderElement::from_decimal_str(&s).unwrap(), r);
+        }
+
+        // Values not less than the curve order are reduced
+        let minus_one = CurveOrderElement::minus_one();
+        let minus_ten = CurveOrderElement::from_decimal_str(&format!("{}0", minus_one.to_decimal_string())).unwrap();
+        assert_eq!(minus_ten, &minus_one * &CurveOrderElement::from(10u64));
+
+        assert!(CurveOrderElement::from_decimal_str("").is_err());
+        assert!(CurveOrderElement::from_decimal_str("12a3").is_err());
+        assert!(CurveOrderElement::from_decimal_str("-5").is_err());
+        assert!(CurveOrderElement::from_decimal_str(&"9".repeat(200)).is_err());
+    }
+
     #[test]
     fn test_hex_elem() {
         for _ in 0..1000 {
@@ -1651,6 +2879,7 @@ mod test {
         }
     }
 
+    #[cfg(feature = "serde")]
     #[test]
     fn test_serialization_deserialization_elem() {
         #[derive(Serialize, Deserialize)]
@@ -1670,4 +2899,17 @@ mod test {
             assert_eq!(f.unwrap().val, r)
         }
     }
+
+    #[test]
+    fn test_vector_fmt() {
+        let v = CurveOrderElementVector::random(3);
+        let debug = format!("{:?}", v);
+        assert!(debug.starts_with("CurveOrderElementVector { len: 3, digests: ["));
+        assert!(!debug.contains(".."));
+        assert!(format!("{:?}", CurveOrderElementVector::random(5)).ends_with(", ..] }"));
+
+        let display = format!("{}", v);
+        assert_eq!(display.lines().count(), 5);
+        assert!(display.contains(&format!("  2: {}", v[2])));
+    }
 }
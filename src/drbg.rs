@@ -0,0 +1,280 @@
+//! HMAC_DRBG with SHA-256 from NIST SP 800-90A, a deterministic random bit generator seeded by the
+//! caller. It implements `RngCore + CryptoRng` so it can be passed to all `*_using_rng`
+//! constructors. The same seed and personalization string give the same output, so the seed must
+//! come from an approved entropy source and never be reused. Prediction resistance is up to the
+//! caller, who can reseed with fresh entropy at any time.
+
+use crate::errors::EntropyError;
+use crate::utils::OsEntropyRng;
+
+use rand::{CryptoRng, RngCore};
+use sha2::{Digest, Sha256};
+use std::fmt;
+use zeroize::Zeroize;
+
+/// Minimum byte size of entropy input for the 256 bit security strength
+pub const MIN_SEED_SIZE: usize = 32;
+/// Minimum byte size of the seed of `HmacDrbg::new`, entropy input and a nonce of half its size
+pub const MIN_INSTANTIATE_SEED_SIZE: usize = MIN_SEED_SIZE + MIN_SEED_SIZE / 2;
+/// Maximum number of bytes per request
+pub const MAX_BYTES_PER_REQUEST: usize = 1 << 16;
+/// Number of requests after which the generator must be reseeded
+pub const RESEED_INTERVAL: u64 = 1 << 48;
+
+const OUT_LEN: usize = 32;
+const BLOCK_LEN: usize = 64;
+
+fn hmac_sha256(key: &[u8; OUT_LEN], data: &[&[u8]]) -> [u8; OUT_LEN] {
+    let mut ipad = [0x36u8; BLOCK_LEN];
+    let mut opad = [0x5cu8; BLOCK_LEN];
+    for (i, k) in key.iter().enumerate() {
+        ipad[i] ^= k;
+        opad[i] ^= k;
+    }
+    let mut inner = Sha256::new();
+    inner.input(&ipad[..]);
+    for d in data {
+        inner.input(d);
+    }
+    let mut outer = Sha256::new();
+    outer.input(&opad[..]);
+    outer.input(inner.result());
+    let mut out = [0u8; OUT_LEN];
+    out.copy_from_slice(outer.result().as_slice());
+    ipad.zeroize();
+    opad.zeroize();
+    out
+}
+
+/// Working state `(K, V)` and the number of requests since the last (re)seeding
+pub struct HmacDrbg {
+    k: [u8; OUT_LEN],
+    v: [u8; OUT_LEN],
+    reseed_counter: u64,
+}
+
+impl fmt::Debug for HmacDrbg {
+    /// Does not show the state
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("HmacDrbg")
+            .field("reseed_counter", &self.reseed_counter)
+            .finish()
+    }
+}
+
+impl HmacDrbg {
+    /// Instantiate from `seed`, the entropy input followed by the nonce, and a personalization
+    /// string which may be empty. `seed` must have at least `MIN_INSTANTIATE_SEED_SIZE` bytes.
+    pub fn new(seed: &[u8], personalization: &[u8]) -> Result<Self, EntropyError> {
+        if seed.len() < MIN_INSTANTIATE_SEED_SIZE {
+            return Err(EntropyError::InsufficientEntropy(
+                seed.len(),
+                MIN_INSTANTIATE_SEED_SIZE,
+            ));
+        }
+        let mut drbg = Self {
+            k: [0u8; OUT_LEN],
+            v: [1u8; OUT_LEN],
+            reseed_counter: 1,
+        };
+        drbg.update(&[seed, personalization]);
+        Ok(drbg)
+    }
+
+    /// Instantiate with entropy input and nonce read from the operating system
+    pub fn from_os(personalization: &[u8]) -> Result<Self, EntropyError> {
+        let mut seed = [0u8; MIN_INSTANTIATE_SEED_SIZE];
+        OsEntropyRng
+            .try_fill_bytes(&mut seed)
+            .map_err(|_| EntropyError::RngFailure)?;
+        let drbg = Self::new(&seed, personalization);
+        seed.zeroize();
+        drbg
+    }
+
+    /// Mix fresh entropy and optional additional input into the state
+    pub fn reseed(&mut self, entropy: &[u8], additional: &[u8]) -> Result<(), EntropyError> {
+        if entropy.len() < MIN_SEED_SIZE {
+            return Err(EntropyError::InsufficientEntropy(
+                entropy.len(),
+                MIN_SEED_SIZE,
+            ));
+        }
+        self.update(&[entropy, additional]);
+        self.reseed_counter = 1;
+        Ok(())
+    }
+
+    /// Fill `out` with at most `MAX_BYTES_PER_REQUEST` bytes, with optional additional input
+    pub fn generate(&mut self, out: &mut [u8], additional: &[u8]) -> Result<(), EntropyError> {
+        if out.len() > MAX_BYTES_PER_REQUEST {
+            return Err(EntropyError::RequestTooLarge(
+                out.len(),
+                MAX_BYTES_PER_REQUEST,
+            ));
+        }
+        if self.reseed_counter > RESEED_INTERVAL {
+            return Err(EntropyError::ReseedRequired);
+        }
+        if !additional.is_empty() {
+            self.update(&[additional]);
+        }
+        for chunk in out.chunks_mut(OUT_LEN) {
+            self.v = hmac_sha256(&self.k, &[&self.v]);
+            chunk.copy_from_slice(&self.v[..chunk.len()]);
+        }
+        self.update(&[additional]);
+        self.reseed_counter += 1;
+        Ok(())
+    }
+
+    /// `HMAC_DRBG_Update` with the concatenation of `data` as provided data
+    fn update(&mut self, data: &[&[u8]]) {
+        let empty = data.iter().all(|d| d.is_empty());
+        for byte in [0u8, 1u8].iter() {
+            let mut input: Vec<&[u8]> = vec![&self.v, std::slice::from_ref(byte)];
+            input.extend_from_slice(data);
+            self.k = hmac_sha256(&self.k, &input);
+            self.v = hmac_sha256(&self.k, &[&self.v]);
+            if empty {
+                break;
+            }
+        }
+    }
+}
+
+impl Drop for HmacDrbg {
+    fn drop(&mut self) {
+        self.k.zeroize();
+        self.v.zeroize();
+    }
+}
+
+impl RngCore for HmacDrbg {
+    fn next_u32(&mut self) -> u32 {
+        let mut bytes = [0u8; 4];
+        self.fill_bytes(&mut bytes);
+        u32::from_le_bytes(bytes)
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        let mut bytes = [0u8; 8];
+        self.fill_bytes(&mut bytes);
+        u64::from_le_bytes(bytes)
+    }
+
+    /// Panics when the generator must be reseeded
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        if let Err(e) = self.try_fill_bytes(dest) {
+            panic!("{}", e);
+        }
+    }
+
+    /// Long outputs are split into requests of `MAX_BYTES_PER_REQUEST` bytes
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand::Error> {
+        for chunk in dest.chunks_mut(MAX_BYTES_PER_REQUEST) {
+            self.generate(chunk, &[]).map_err(rand::Error::new)?;
+        }
+        Ok(())
+    }
+}
+
+impl CryptoRng for HmacDrbg {}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::curve_order_elem::CurveOrderElement;
+    use subtle_encoding::hex;
+
+    #[test]
+    fn test_hmac() {
+        // RFC 4231, test case 2
+        let mut key = [0u8; OUT_LEN];
+        key[..4].copy_from_slice(b"Jefe");
+        let mac = hmac_sha256(&key, &[b"what do ya want ", b"for nothing?"]);
+        let expected = [
+            0x5b, 0xdc, 0xc1, 0x46, 0xbf, 0x60, 0x75, 0x4e, 0x6a, 0x04, 0x24, 0x26, 0x08, 0x95,
+            0x75, 0xc7, 0x5a, 0x00, 0x3f, 0x08, 0x9d, 0x27, 0x39, 0x83, 0x9d, 0xec, 0x58, 0xb9,
+            0x64, 0xec, 0x38, 0x43,
+        ];
+        assert_eq!(mac, expected);
+    }
+
+    #[test]
+    fn test_cavp_vectors() {
+        // NIST CAVP HMAC_DRBG.rsp, [SHA-256], PredictionResistance = False, EntropyInputLen = 256,
+        // NonceLen = 128, PersonalizationStringLen = 0, AdditionalInputLen = 0,
+        // ReturnedBitsLen = 1024. The returned bits are the output of the second generate call.
+        let vectors = [
+            (
+                "ca851911349384bffe89de1cbdc46e6831e44d34a4fb935ee285dd14b71a7488",
+                "659ba96c601dc69fc902940805ec0ca8",
+                "e528e9abf2dece54d47c7e75e5fe302149f817ea9fb4bee6f4199697d04d5b89\
+                 d54fbb978a15b5c443c9ec21036d2460b6f73ebad0dc2aba6e624abf07745bc1\
+                 07694bb7547bb0995f70de25d6b29e2d3011bb19d27676c07162c8b5ccde0668\
+                 961df86803482cb37ed6d5c0bb8d50cf1f50d476aa0458bdaba806f48be9dcb8",
+            ),
+            (
+                "79737479ba4e7642a221fcfd1b820b134e9e3540a35bb48ffae29c20f5418ea3",
+                "3593259c092bef4129bc2c6c9e19f343",
+                "cf5ad5984f9e43917aa9087380dac46e410ddc8a7731859c84e9d0f31bd43655\
+                 b924159413e2293b17610f211e09f770f172b8fb693a35b85d3b9e5e63b1dc25\
+                 2ac0e115002e9bedfb4b5b6fd43f33b8e0eafb2d072e1a6fee1f159df9b51e6c\
+                 8da737e60d5032dd30544ec51558c6f080bdbdab1de8a939e961e06b5f1aca37",
+            ),
+        ];
+        for (entropy, nonce, returned) in vectors.iter() {
+            let mut seed = hex::decode(entropy).unwrap();
+            seed.extend_from_slice(&hex::decode(nonce).unwrap());
+            let mut drbg = HmacDrbg::new(&seed, b"").unwrap();
+            let mut out = [0u8; 128];
+            drbg.generate(&mut out, b"").unwrap();
+            drbg.generate(&mut out, b"").unwrap();
+            assert_eq!(out[..], hex::decode(returned).unwrap()[..]);
+        }
+    }
+
+    #[test]
+    fn test_drbg() {
+        let seed = [7u8; 48];
+        let mut a = HmacDrbg::new(&seed, b"app").unwrap();
+        let mut b = HmacDrbg::new(&seed, b"app").unwrap();
+        let mut c = HmacDrbg::new(&seed, b"other app").unwrap();
+        let (mut x, mut y, mut z) = ([0u8; 100], [0u8; 100], [0u8; 100]);
+        a.fill_bytes(&mut x);
+        b.fill_bytes(&mut y);
+        c.fill_bytes(&mut z);
+        assert_eq!(x[..], y[..]);
+        assert_ne!(x[..], z[..]);
+        assert_ne!(x[..32], x[32..64]);
+
+        // Same state after the same requests, diverging after a reseed
+        assert_eq!(
+            CurveOrderElement::random_using_rng(&mut a),
+            CurveOrderElement::random_using_rng(&mut b)
+        );
+        a.reseed(&[9u8; 32], b"").unwrap();
+        assert_ne!(a.next_u64(), b.next_u64());
+
+        // Additional input changes the output
+        let mut b2 = HmacDrbg::new(&seed, b"app").unwrap();
+        let mut c2 = HmacDrbg::new(&seed, b"app").unwrap();
+        b2.generate(&mut y, b"extra").unwrap();
+        c2.generate(&mut z, b"").unwrap();
+        assert_ne!(y[..], z[..]);
+
+        // Outputs longer than one request
+        let mut long = vec![0u8; MAX_BYTES_PER_REQUEST + 10];
+        a.fill_bytes(&mut long);
+        assert!(a.generate(&mut long, b"").is_err());
+
+        assert!(HmacDrbg::new(&[1u8; 31], b"").is_err());
+        assert!(HmacDrbg::new(&[1u8; 47], b"").is_err());
+        assert!(a.reseed(&[1u8; 16], b"").is_err());
+        assert_ne!(
+            HmacDrbg::from_os(b"").unwrap().next_u64(),
+            HmacDrbg::from_os(b"").unwrap().next_u64()
+        );
+    }
+}
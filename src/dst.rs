@@ -0,0 +1,202 @@
+//! Domain separation tags and messages binding the context of a deployment, e.g. the chain id,
+//! epoch and round, into the inputs of hash to curve, so that hashes, and signatures on them, of
+//! one network, epoch or round are useless in another. Every context part is prefixed with its
+//! length as one byte, so distinct contexts never encode the same, unlike plain concatenation.
+//!
+//! Lengths are checked strictly: parts longer than 255 bytes and tags longer than
+//! `MAX_DST_SIZE` are rejected, rather than hashed like the oversize tags of RFC 9380.
+
+use crate::errors::ValueError;
+use crate::group_elem_g1::G1;
+use crate::utils::hash_msg_to_point;
+
+use hash2curve::DomainSeparationTag;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// Maximum byte size of a domain separation tag
+pub const MAX_DST_SIZE: usize = hash2curve::MAX_DMS_SIZE;
+/// Maximum byte size of a part of a context
+pub const MAX_CONTEXT_PART_SIZE: usize = 255;
+
+/// `parts` each prefixed with its length as one byte
+fn encode_parts(parts: &[&[u8]]) -> Result<Vec<u8>, ValueError> {
+    let mut out = vec![];
+    for part in parts {
+        if part.len() > MAX_CONTEXT_PART_SIZE {
+            return Err(ValueError::OutOfRange(part.len()));
+        }
+        out.push(part.len() as u8);
+        out.extend_from_slice(part);
+    }
+    Ok(out)
+}
+
+/// Tag `protocol_id` followed by the length prefixed `parts`. Fails if `protocol_id` is empty, a
+/// part is longer than `MAX_CONTEXT_PART_SIZE` or the tag longer than `MAX_DST_SIZE`.
+pub fn dst_with_context(protocol_id: &[u8], parts: &[&[u8]]) -> Result<Vec<u8>, ValueError> {
+    if protocol_id.is_empty() {
+        return Err(ValueError::IncorrectSize(0));
+    }
+    let mut dst = protocol_id.to_vec();
+    dst.extend_from_slice(&encode_parts(parts)?);
+    if dst.len() > MAX_DST_SIZE {
+        return Err(ValueError::OutOfRange(dst.len()));
+    }
+    Ok(dst)
+}
+
+/// Construction of `DomainSeparationTag`s binding a context, `DomainSeparationTag::with_context`
+pub trait DomainSeparationTagExt: Sized {
+    /// Tag of `dst_with_context`
+    fn with_context(protocol_id: &[u8], parts: &[&[u8]]) -> Result<Self, ValueError>;
+}
+
+impl DomainSeparationTagExt for DomainSeparationTag {
+    fn with_context(protocol_id: &[u8], parts: &[&[u8]]) -> Result<Self, ValueError> {
+        let dst = dst_with_context(protocol_id, parts)?;
+        // Cannot fail as `dst` is not empty
+        Ok(DomainSeparationTag::new(&dst, None, None, None).unwrap())
+    }
+}
+
+/// Chain id, epoch and round of a beacon or any other protocol run in rounds
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct EpochContext {
+    chain_id: Vec<u8>,
+    epoch: u64,
+    round: u64,
+}
+
+impl EpochContext {
+    /// Fails if `chain_id` is empty or longer than `MAX_CONTEXT_PART_SIZE`
+    pub fn new(chain_id: &[u8], epoch: u64, round: u64) -> Result<Self, ValueError> {
+        if chain_id.is_empty() {
+            return Err(ValueError::IncorrectSize(0));
+        }
+        if chain_id.len() > MAX_CONTEXT_PART_SIZE {
+            return Err(ValueError::OutOfRange(chain_id.len()));
+        }
+        Ok(Self {
+            chain_id: chain_id.to_vec(),
+            epoch,
+            round,
+        })
+    }
+
+    pub fn chain_id(&self) -> &[u8] {
+        &self.chain_id
+    }
+
+    pub fn epoch(&self) -> u64 {
+        self.epoch
+    }
+
+    pub fn round(&self) -> u64 {
+        self.round
+    }
+
+    /// Context of the next round in the same epoch
+    pub fn next_round(&self) -> Self {
+        Self {
+            round: self.round + 1,
+            ..self.clone()
+        }
+    }
+
+    /// Apply `f` to the parts of the context, the chain id and the epoch and round as 8 big
+    /// endian bytes
+    fn with_parts<T>(&self, f: impl FnOnce(&[&[u8]]) -> T) -> T {
+        f(&[
+            &self.chain_id,
+            &self.epoch.to_be_bytes(),
+            &self.round.to_be_bytes(),
+        ])
+    }
+
+    /// Tag `protocol_id` followed by the encoded context, see `dst_with_context`
+    pub fn dst(&self, protocol_id: &[u8]) -> Result<Vec<u8>, ValueError> {
+        self.with_parts(|parts| dst_with_context(protocol_id, parts))
+    }
+
+    /// `dst` as a `DomainSeparationTag`
+    pub fn domain_separation_tag(
+        &self,
+        protocol_id: &[u8],
+    ) -> Result<DomainSeparationTag, ValueError> {
+        self.with_parts(|parts| DomainSeparationTag::with_context(protocol_id, parts))
+    }
+
+    /// The encoded context followed by `msg`, for protocols with a fixed tag like BLS signatures
+    pub fn encode_message(&self, msg: &[u8]) -> Vec<u8> {
+        // The chain id length was checked when created
+        let mut out = self.with_parts(encode_parts).unwrap();
+        out.extend_from_slice(msg);
+        out
+    }
+
+    /// Hash `msg` to group G1 with `hash_msg_to_point` and the tag `dst(protocol_id)`
+    pub fn hash_to_g1(&self, msg: &[u8], protocol_id: &[u8]) -> Result<G1, ValueError> {
+        hash_msg_to_point(msg, &self.dst(protocol_id)?)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_dst_with_context() {
+        let dst = dst_with_context(b"PROTO", &[b"main", &[1, 2]]).unwrap();
+        assert_eq!(dst, b"PROTO\x04main\x02\x01\x02".to_vec());
+        assert_eq!(
+            DomainSeparationTag::with_context(b"PROTO", &[b"main", &[1, 2]])
+                .unwrap()
+                .to_bytes(),
+            dst
+        );
+        // Moving bytes between parts changes the tag
+        assert_ne!(
+            dst_with_context(b"PROTO", &[b"ma", b"in"]).unwrap(),
+            dst_with_context(b"PROTO", &[b"m", b"ain"]).unwrap()
+        );
+
+        assert!(dst_with_context(b"", &[b"main"]).is_err());
+        assert!(dst_with_context(b"PROTO", &[&[0u8; 256]]).is_err());
+        assert!(dst_with_context(&[1u8; 200], &[&[0u8; 54]]).is_ok());
+        assert!(dst_with_context(&[1u8; 200], &[&[0u8; 55]]).is_err());
+    }
+
+    #[test]
+    fn test_epoch_context() {
+        let main = EpochContext::new(b"mainnet", 3, 10).unwrap();
+        let test = EpochContext::new(b"testnet", 3, 10).unwrap();
+        assert_ne!(main.dst(b"BEACON").unwrap(), test.dst(b"BEACON").unwrap());
+        assert_ne!(
+            main.dst(b"BEACON").unwrap(),
+            main.next_round().dst(b"BEACON").unwrap()
+        );
+        assert_eq!(main.next_round().round(), 11);
+        assert_eq!(
+            main.domain_separation_tag(b"BEACON").unwrap().to_bytes(),
+            main.dst(b"BEACON").unwrap()
+        );
+
+        let msg = b"message";
+        assert_ne!(main.encode_message(msg), test.encode_message(msg));
+        assert!(main.encode_message(msg).ends_with(msg));
+        assert_ne!(
+            main.hash_to_g1(msg, b"BEACON").unwrap(),
+            test.hash_to_g1(msg, b"BEACON").unwrap()
+        );
+        assert_eq!(
+            main.hash_to_g1(msg, b"BEACON").unwrap(),
+            main.hash_to_g1(msg, b"BEACON").unwrap()
+        );
+
+        assert!(EpochContext::new(b"", 0, 0).is_err());
+        assert!(EpochContext::new(&[0u8; 256], 0, 0).is_err());
+        assert!(main.dst(&[1u8; 250]).is_err());
+    }
+}
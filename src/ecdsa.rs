@@ -0,0 +1,314 @@
+//! ECDSA over secp256k1 with SHA-256 as the message digest. Signatures are encoded as `r || s` and
+//! are always produced in low-s form, verification accepts both forms.
+
+use crate::constants::{CURVE_ORDER, CURVE_ORDER_ELEMENT_SIZE, GROUP_G1_SIZE};
+use crate::ct;
+use crate::curve_order_elem::CurveOrderElement;
+use crate::group_elem::GroupElement;
+use crate::group_elem_g1::G1;
+
+use rand::{CryptoRng, RngCore};
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use signature::{Error as SignatureError, SignatureEncoding, Signer, Verifier};
+use std::convert::TryFrom;
+use std::fmt;
+
+/// Byte size of a signature, 2 curve order elements
+pub const SIGNATURE_SIZE: usize = 2 * CURVE_ORDER_ELEMENT_SIZE;
+/// Byte size of a public key, an uncompressed element of group G1
+pub const PUBLIC_KEY_SIZE: usize = GROUP_G1_SIZE;
+
+fn digest_to_scalar(msg: &[u8]) -> CurveOrderElement {
+    CurveOrderElement::try_from(Sha256::digest(msg).as_slice()).unwrap()
+}
+
+/// x coordinate of the point reduced modulo the curve order
+fn x_mod_order(p: &G1) -> CurveOrderElement {
+    let mut x = p.to_ecp().getx();
+    x.rmod(&CURVE_ORDER);
+    x.into()
+}
+
+/// Parse a curve order element, rejecting encodings of values not less than the curve order
+fn scalar_from_canonical_bytes(bytes: &[u8]) -> Result<CurveOrderElement, SignatureError> {
+    let s = CurveOrderElement::try_from(bytes).map_err(|_| SignatureError::new())?;
+    if s.to_compressed_bytes()[..] != bytes[..] {
+        return Err(SignatureError::new());
+    }
+    Ok(s)
+}
+
+#[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct SecretKey(CurveOrderElement);
+
+impl fmt::Debug for SecretKey {
+    /// Does not show the key
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("SecretKey(..)")
+    }
+}
+
+impl PartialEq for SecretKey {
+    /// Constant time in the keys
+    fn eq(&self, other: &Self) -> bool {
+        ct::scalar_eq(&self.0, &other.0)
+    }
+}
+
+impl SecretKey {
+    pub fn random() -> Self {
+        Self(CurveOrderElement::random())
+    }
+
+    pub fn random_using_rng<R: RngCore + CryptoRng>(rng: &mut R) -> Self {
+        Self(CurveOrderElement::random_using_rng(rng))
+    }
+
+    pub fn sign(&self, msg: &[u8]) -> Signature {
+        let z = digest_to_scalar(msg);
+        loop {
+            // Nonce depends on the secret key and digest as well as fresh randomness so a weak RNG
+            // does not leak the key.
+            let mut seed = CurveOrderElement::random().to_bytes().to_vec();
+            seed.extend_from_slice(&self.0.to_bytes());
+            seed.extend_from_slice(&z.to_bytes());
+            let k = CurveOrderElement::from_msg_hash(&seed);
+            if k.is_zero() {
+                continue;
+            }
+            let r = x_mod_order(&(G1::generator() * &k));
+            if r.is_zero() {
+                continue;
+            }
+            let mut s = k.inverse_const_time() * (z.clone() + &r * &self.0);
+            if s.is_zero() {
+                continue;
+            }
+            if s > CurveOrderElement::minus_one().shift_right(1) {
+                s.negate();
+            }
+            return Signature { r, s };
+        }
+    }
+
+    pub fn as_scalar(&self) -> &CurveOrderElement {
+        &self.0
+    }
+
+    /// Secret key of `PublicKey::tweak_add` with the same tweak
+    pub fn tweak_add(&self, tweak: &CurveOrderElement) -> Result<Self, SignatureError> {
+        let d = &self.0 + tweak;
+        if d.is_zero() {
+            return Err(SignatureError::new());
+        }
+        Ok(Self(d))
+    }
+}
+
+impl From<CurveOrderElement> for SecretKey {
+    fn from(x: CurveOrderElement) -> Self {
+        Self(x)
+    }
+}
+
+impl Signer<Signature> for SecretKey {
+    fn try_sign(&self, msg: &[u8]) -> Result<Signature, SignatureError> {
+        Ok(self.sign(msg))
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct PublicKey(G1);
+
+impl PublicKey {
+    pub fn new(sk: &SecretKey) -> Self {
+        Self(G1::generator() * &sk.0)
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        self.0.to_vec()
+    }
+
+    pub fn as_point(&self) -> &G1 {
+        &self.0
+    }
+
+    /// `pk + g*tweak`, fails if the result is the identity
+    pub fn tweak_add(&self, tweak: &CurveOrderElement) -> Result<Self, SignatureError> {
+        let q = G1::generator().binary_scalar_mul(&self.0, tweak, &CurveOrderElement::one());
+        if q.is_identity() {
+            return Err(SignatureError::new());
+        }
+        Ok(Self(q))
+    }
+}
+
+#[cfg(feature = "jwk")]
+impl PublicKey {
+    pub fn to_jwk(&self) -> crate::jwk::Jwk {
+        self.0.to_jwk()
+    }
+
+    pub fn from_jwk(jwk: &crate::jwk::Jwk) -> Result<Self, crate::errors::SerzDeserzError> {
+        G1::from_jwk(jwk).map(Self)
+    }
+}
+
+#[cfg(feature = "cose")]
+impl PublicKey {
+    /// The key is marked for use with ES256K
+    pub fn to_cose_key(&self) -> crate::cose::CoseKey {
+        let mut key = self.0.to_cose_key();
+        key.alg = Some(crate::cose::ALG_ES256K);
+        key
+    }
+
+    /// Rejects keys marked for an algorithm other than ES256K
+    pub fn from_cose_key(
+        key: &crate::cose::CoseKey,
+    ) -> Result<Self, crate::errors::SerzDeserzError> {
+        if key.alg.is_some_and(|alg| alg != crate::cose::ALG_ES256K) {
+            return Err(crate::errors::SerzDeserzError::InvalidCoseKey);
+        }
+        G1::from_cose_key(key).map(Self)
+    }
+}
+
+impl From<&SecretKey> for PublicKey {
+    fn from(sk: &SecretKey) -> Self {
+        Self::new(sk)
+    }
+}
+
+impl TryFrom<&[u8]> for PublicKey {
+    type Error = SignatureError;
+
+    fn try_from(bytes: &[u8]) -> Result<Self, SignatureError> {
+        let pk = G1::from_slice(bytes).map_err(|_| SignatureError::new())?;
+        if pk.is_identity() {
+            return Err(SignatureError::new());
+        }
+        Ok(Self(pk))
+    }
+}
+
+impl Verifier<Signature> for PublicKey {
+    fn verify(&self, msg: &[u8], sig: &Signature) -> Result<(), SignatureError> {
+        if self.0.is_identity() || sig.r.is_zero() || sig.s.is_zero() {
+            return Err(SignatureError::new());
+        }
+        let w = sig.s.inverse();
+        let u1 = digest_to_scalar(msg) * &w;
+        let u2 = &sig.r * &w;
+        let p = G1::generator().binary_scalar_mul(&self.0, &u1, &u2);
+        if p.is_identity() || x_mod_order(&p) != sig.r {
+            return Err(SignatureError::new());
+        }
+        Ok(())
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Signature {
+    r: CurveOrderElement,
+    s: CurveOrderElement,
+}
+
+impl TryFrom<&[u8]> for Signature {
+    type Error = SignatureError;
+
+    fn try_from(bytes: &[u8]) -> Result<Self, SignatureError> {
+        if bytes.len() != SIGNATURE_SIZE {
+            return Err(SignatureError::new());
+        }
+        Ok(Self {
+            r: scalar_from_canonical_bytes(&bytes[..CURVE_ORDER_ELEMENT_SIZE])?,
+            s: scalar_from_canonical_bytes(&bytes[CURVE_ORDER_ELEMENT_SIZE..])?,
+        })
+    }
+}
+
+impl TryFrom<[u8; SIGNATURE_SIZE]> for Signature {
+    type Error = SignatureError;
+
+    fn try_from(bytes: [u8; SIGNATURE_SIZE]) -> Result<Self, SignatureError> {
+        Self::try_from(&bytes[..])
+    }
+}
+
+impl From<Signature> for [u8; SIGNATURE_SIZE] {
+    fn from(sig: Signature) -> Self {
+        let mut bytes = [0u8; SIGNATURE_SIZE];
+        bytes[..CURVE_ORDER_ELEMENT_SIZE].copy_from_slice(&sig.r.to_compressed_bytes());
+        bytes[CURVE_ORDER_ELEMENT_SIZE..].copy_from_slice(&sig.s.to_compressed_bytes());
+        bytes
+    }
+}
+
+impl SignatureEncoding for Signature {
+    type Repr = [u8; SIGNATURE_SIZE];
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_secret_key_eq_and_debug() {
+        let sk = SecretKey::random();
+        assert_eq!(sk, sk.clone());
+        assert_ne!(sk, SecretKey::random());
+        assert_eq!(format!("{:?}", sk), "SecretKey(..)");
+    }
+
+    #[test]
+    fn test_sign_verify() {
+        let sk = SecretKey::random();
+        let pk = PublicKey::from(&sk);
+        let msg = b"test message";
+        let sig = sk.try_sign(msg).unwrap();
+        assert!(pk.verify(msg, &sig).is_ok());
+        assert!(pk.verify(b"another message", &sig).is_err());
+        assert!(PublicKey::from(&SecretKey::random()).verify(msg, &sig).is_err());
+
+        // High-s form of the signature also verifies
+        let high_s = Signature {
+            r: sig.r.clone(),
+            s: sig.s.negation(),
+        };
+        assert!(pk.verify(msg, &high_s).is_ok());
+    }
+
+    #[test]
+    fn test_tweak_add() {
+        let sk = SecretKey::random();
+        let pk = PublicKey::from(&sk);
+        let tweak = CurveOrderElement::random();
+        let tweaked_sk = sk.tweak_add(&tweak).unwrap();
+        assert_eq!(PublicKey::from(&tweaked_sk), pk.tweak_add(&tweak).unwrap());
+
+        let neg = sk.as_scalar().negation();
+        assert!(sk.tweak_add(&neg).is_err());
+        assert!(pk.tweak_add(&neg).is_err());
+    }
+
+    #[test]
+    fn test_encoding() {
+        let sk = SecretKey::random();
+        let pk = PublicKey::from(&sk);
+        let sig = sk.sign(b"test message");
+
+        let sig_bytes = sig.to_bytes();
+        assert_eq!(Signature::try_from(&sig_bytes[..]).unwrap(), sig);
+        assert!(Signature::try_from([0xffu8; SIGNATURE_SIZE]).is_err());
+
+        let pk_bytes = pk.to_bytes();
+        assert_eq!(pk_bytes[0], 4);
+        assert_eq!(PublicKey::try_from(&pk_bytes[..]).unwrap(), pk);
+    }
+}
@@ -0,0 +1,304 @@
+//! ElGamal encryption of G1 elements with public key `pk = sk*g`. A message `M` is encrypted with
+//! randomness `r` as `(r*pk, M + r*g)` and decrypted as `c2 - c1/sk`. This layout (Blaze, Bleumer,
+//! Strauss) allows proxy re-encryption: with `rk = sk_to/sk_from` a proxy turns a ciphertext for
+//! `pk_from` into one for `pk_to` by multiplying `c1` by `rk`, without learning the message.
+//! Ciphertexts can be re-randomized by anyone, as mixnets do. Both operations come with proofs of
+//! correctness, proofs of equality of discrete logs built with the OR-proofs of `ring_sig`.
+
+use crate::curve_order_elem::CurveOrderElement;
+use crate::group_elem::GroupElement;
+use crate::group_elem_g1::G1;
+use crate::group_params::GroupParams;
+use crate::ring_sig::OrProof;
+
+use rand::{CryptoRng, RngCore};
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+const RERANDOMIZATION_LABEL: &[u8] = b"ElGamal rerandomization";
+const RE_ENCRYPTION_LABEL: &[u8] = b"ElGamal re-encryption";
+
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct SecretKey(CurveOrderElement);
+
+/// `sk*g` for the G1 generator `g` of the group parameters
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct PublicKey(G1);
+
+/// `(r*pk, M + r*g)`
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Ciphertext {
+    c1: G1,
+    c2: G1,
+}
+
+/// `sk_to/sk_from`, turns ciphertexts for `pk_from` into ciphertexts for `pk_to`. Anyone holding
+/// it together with one of the secret keys learns the other, and it works in both directions.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct ReEncryptionKey(CurveOrderElement);
+
+/// Proof that a ciphertext is a re-randomization of another, `(c1' - c1, c2' - c2) = (s*pk, s*g)`
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct RerandomizationProof(OrProof);
+
+/// Proof that a ciphertext is the re-encryption of another, `c1' = rk*c1` and
+/// `pk_to = rk*pk_from`, with the same `c2`
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct ReEncryptionProof(OrProof);
+
+impl SecretKey {
+    pub fn random() -> Self {
+        Self(CurveOrderElement::random())
+    }
+
+    pub fn random_using_rng<R: RngCore + CryptoRng>(rng: &mut R) -> Self {
+        Self(CurveOrderElement::random_using_rng(rng))
+    }
+
+    pub fn decrypt(&self, ct: &Ciphertext) -> G1 {
+        &ct.c2 - &(&ct.c1 * &self.0.inverse_const_time())
+    }
+}
+
+impl PublicKey {
+    pub fn new(sk: &SecretKey, params: &GroupParams) -> Self {
+        Self(params.g1() * &sk.0)
+    }
+
+    pub fn encrypt<R: RngCore + CryptoRng>(
+        &self,
+        msg: &G1,
+        params: &GroupParams,
+        rng: &mut R,
+    ) -> Ciphertext {
+        self.encrypt_with_randomness(msg, &CurveOrderElement::random_using_rng(rng), params)
+    }
+
+    pub fn encrypt_with_randomness(
+        &self,
+        msg: &G1,
+        r: &CurveOrderElement,
+        params: &GroupParams,
+    ) -> Ciphertext {
+        Ciphertext {
+            c1: &self.0 * r,
+            c2: msg + &(params.g1() * r),
+        }
+    }
+
+    pub fn as_point(&self) -> &G1 {
+        &self.0
+    }
+}
+
+impl Ciphertext {
+    /// Encryption of the same message under the same key with the randomness shifted by a fresh
+    /// `s`, which is returned. The result can't be linked to this ciphertext without the secret
+    /// key.
+    pub fn rerandomize<R: RngCore + CryptoRng>(
+        &self,
+        pk: &PublicKey,
+        params: &GroupParams,
+        rng: &mut R,
+    ) -> (Self, CurveOrderElement) {
+        let s = CurveOrderElement::random_using_rng(rng);
+        (self.rerandomize_with(&s, pk, params), s)
+    }
+
+    /// Re-randomize and prove it was done correctly
+    pub fn rerandomize_with_proof<R: RngCore + CryptoRng>(
+        &self,
+        pk: &PublicKey,
+        params: &GroupParams,
+        rng: &mut R,
+    ) -> (Self, RerandomizationProof) {
+        let (ct, s) = self.rerandomize(pk, params, rng);
+        let statement = rerandomization_statement(self, &ct, pk, params);
+        // A single statement, so the index is always valid
+        let proof = OrProof::new(&[statement], 0, &s, RERANDOMIZATION_LABEL).unwrap();
+        (ct, RerandomizationProof(proof))
+    }
+
+    fn rerandomize_with(
+        &self,
+        s: &CurveOrderElement,
+        pk: &PublicKey,
+        params: &GroupParams,
+    ) -> Self {
+        Self {
+            c1: &self.c1 + &(&pk.0 * s),
+            c2: &self.c2 + &(params.g1() * s),
+        }
+    }
+
+    pub fn c1(&self) -> &G1 {
+        &self.c1
+    }
+
+    pub fn c2(&self) -> &G1 {
+        &self.c2
+    }
+}
+
+fn rerandomization_statement(
+    original: &Ciphertext,
+    rerandomized: &Ciphertext,
+    pk: &PublicKey,
+    params: &GroupParams,
+) -> Vec<(G1, G1)> {
+    vec![
+        (pk.0.clone(), &rerandomized.c1 - &original.c1),
+        (params.g1().clone(), &rerandomized.c2 - &original.c2),
+    ]
+}
+
+impl RerandomizationProof {
+    pub fn verify(
+        &self,
+        original: &Ciphertext,
+        rerandomized: &Ciphertext,
+        pk: &PublicKey,
+        params: &GroupParams,
+    ) -> bool {
+        if pk.0.is_identity() {
+            return false;
+        }
+        let statement = rerandomization_statement(original, rerandomized, pk, params);
+        self.0.verify(&[statement], RERANDOMIZATION_LABEL)
+    }
+}
+
+impl ReEncryptionKey {
+    pub fn new(from: &SecretKey, to: &SecretKey) -> Self {
+        Self(&to.0 * &from.0.inverse_const_time())
+    }
+
+    pub fn re_encrypt(&self, ct: &Ciphertext) -> Ciphertext {
+        Ciphertext {
+            c1: &ct.c1 * &self.0,
+            c2: ct.c2.clone(),
+        }
+    }
+
+    /// Re-encrypt and prove it was done with the key from `pk_from` to `pk_to`
+    pub fn re_encrypt_with_proof(
+        &self,
+        ct: &Ciphertext,
+        pk_from: &PublicKey,
+        pk_to: &PublicKey,
+    ) -> (Ciphertext, ReEncryptionProof) {
+        let re_encrypted = self.re_encrypt(ct);
+        let statement = re_encryption_statement(ct, &re_encrypted, pk_from, pk_to);
+        // A single statement, so the index is always valid
+        let proof = OrProof::new(&[statement], 0, &self.0, RE_ENCRYPTION_LABEL).unwrap();
+        (re_encrypted, ReEncryptionProof(proof))
+    }
+}
+
+fn re_encryption_statement(
+    original: &Ciphertext,
+    re_encrypted: &Ciphertext,
+    pk_from: &PublicKey,
+    pk_to: &PublicKey,
+) -> Vec<(G1, G1)> {
+    vec![
+        (pk_from.0.clone(), pk_to.0.clone()),
+        (original.c1.clone(), re_encrypted.c1.clone()),
+    ]
+}
+
+impl ReEncryptionProof {
+    pub fn verify(
+        &self,
+        original: &Ciphertext,
+        re_encrypted: &Ciphertext,
+        pk_from: &PublicKey,
+        pk_to: &PublicKey,
+    ) -> bool {
+        if original.c2 != re_encrypted.c2 || pk_from.0.is_identity() || pk_to.0.is_identity() {
+            return false;
+        }
+        let statement = re_encryption_statement(original, re_encrypted, pk_from, pk_to);
+        self.0.verify(&[statement], RE_ENCRYPTION_LABEL)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use rand::thread_rng;
+
+    #[test]
+    fn test_encrypt_decrypt() {
+        let params = GroupParams::default();
+        let sk = SecretKey::random();
+        let pk = PublicKey::new(&sk, &params);
+        let msg = G1::random();
+        let ct = pk.encrypt(&msg, &params, &mut thread_rng());
+        assert_eq!(sk.decrypt(&ct), msg);
+        assert_ne!(SecretKey::random().decrypt(&ct), msg);
+    }
+
+    #[test]
+    fn test_rerandomize() {
+        let mut rng = thread_rng();
+        let params = GroupParams::from_label(b"test");
+        let sk = SecretKey::random();
+        let pk = PublicKey::new(&sk, &params);
+        let msg = G1::random();
+        let ct = pk.encrypt(&msg, &params, &mut rng);
+
+        let (rerandomized, s) = ct.rerandomize(&pk, &params, &mut rng);
+        assert_ne!(rerandomized, ct);
+        assert_eq!(sk.decrypt(&rerandomized), msg);
+        assert_eq!(rerandomized.c1().clone() - ct.c1(), pk.as_point() * &s);
+
+        let (rerandomized, proof) = ct.rerandomize_with_proof(&pk, &params, &mut rng);
+        assert_eq!(sk.decrypt(&rerandomized), msg);
+        assert!(proof.verify(&ct, &rerandomized, &pk, &params));
+        let other = pk.encrypt(&msg, &params, &mut rng);
+        assert!(!proof.verify(&other, &rerandomized, &pk, &params));
+
+        // Replacing the message does not verify
+        let mut bad = rerandomized.clone();
+        bad.c2 = &bad.c2 + &G1::random();
+        assert!(!proof.verify(&ct, &bad, &pk, &params));
+    }
+
+    #[test]
+    fn test_re_encrypt() {
+        let mut rng = thread_rng();
+        let params = GroupParams::default();
+        let sk_a = SecretKey::random();
+        let pk_a = PublicKey::new(&sk_a, &params);
+        let sk_b = SecretKey::random();
+        let pk_b = PublicKey::new(&sk_b, &params);
+        let msg = G1::random();
+        let ct = pk_a.encrypt(&msg, &params, &mut rng);
+
+        let rk = ReEncryptionKey::new(&sk_a, &sk_b);
+        let (re_encrypted, proof) = rk.re_encrypt_with_proof(&ct, &pk_a, &pk_b);
+        assert_eq!(re_encrypted, rk.re_encrypt(&ct));
+        assert_eq!(sk_b.decrypt(&re_encrypted), msg);
+        assert_ne!(sk_a.decrypt(&re_encrypted), msg);
+        assert!(proof.verify(&ct, &re_encrypted, &pk_a, &pk_b));
+
+        let pk_c = PublicKey::new(&SecretKey::random(), &params);
+        assert!(!proof.verify(&ct, &re_encrypted, &pk_a, &pk_c));
+
+        // Re-encrypting with another key does not verify
+        let rk_c = ReEncryptionKey::new(&sk_a, &SecretKey::random());
+        let (bad, _) = rk_c.re_encrypt_with_proof(&ct, &pk_a, &pk_b);
+        assert!(!proof.verify(&ct, &bad, &pk_a, &pk_b));
+
+        // Re-encrypted ciphertexts can be re-randomized under the new key
+        let (rerandomized, _) = re_encrypted.rerandomize(&pk_b, &params, &mut rng);
+        assert_eq!(sk_b.decrypt(&rerandomized), msg);
+    }
+}
@@ -0,0 +1,265 @@
+//! Fixed size byte encodings with validation shared by curve order elements and the elements of
+//! groups G1, G2 and GT, so generic code can serialize any of them the same way. Encodings are the
+//! ones of `to_bytes`/`write_to_slice` of each type. Decoding is strict: the bytes must be the
+//! canonical encoding of an element, a reduced scalar or a point in the subgroup of the curve
+//! order, so a value has exactly one encoding.
+
+use crate::constants::{FIELD_ORDER_ELEMENT_SIZE, GROUP_G1_SIZE};
+use crate::curve_order_elem::CurveOrderElement;
+use crate::errors::SerzDeserzError;
+use crate::group_elem::GroupElement;
+use crate::group_elem_g1::G1;
+#[cfg(any(feature = "bls381", feature = "bn254"))]
+use crate::{
+    constants::{GROUP_G2_SIZE, GROUP_GT_SIZE},
+    extension_field_gt::GT,
+    group_elem_g2::G2,
+};
+
+use std::convert::TryFrom;
+
+/// Whether the identity element, eg. a public key or signature at infinity, is accepted when
+/// decoding or verifying. Some protocols must reject it, others produce it legitimately.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum IdentityPolicy {
+    Accept,
+    Reject,
+}
+
+/// Canonical fixed size byte encoding of an element
+pub trait ElementEncoding: Sized {
+    /// Byte size of the encoding
+    const ENCODED_SIZE: usize;
+
+    /// Error for bytes of the wrong size
+    fn size_error(size: usize) -> SerzDeserzError;
+
+    /// Writes the encoding to `target` of exactly `ENCODED_SIZE` bytes
+    fn encode_into(&self, target: &mut [u8]) -> Result<(), SerzDeserzError>;
+
+    /// Decodes without checking that the bytes are the canonical encoding of a valid element
+    fn decode_unchecked(bytes: &[u8]) -> Result<Self, SerzDeserzError>;
+
+    /// Whether the element is valid, e.g. in the subgroup of the curve order
+    fn is_valid(&self) -> bool;
+
+    /// Whether the element is the identity of its group, zero for scalars
+    fn is_identity_element(&self) -> bool {
+        false
+    }
+
+    fn encode(&self) -> Vec<u8> {
+        let mut bytes = vec![0u8; Self::ENCODED_SIZE];
+        self.encode_into(&mut bytes).unwrap();
+        bytes
+    }
+
+    /// Decodes the canonical encoding of a valid element
+    fn decode(bytes: &[u8]) -> Result<Self, SerzDeserzError> {
+        if bytes.len() != Self::ENCODED_SIZE {
+            return Err(Self::size_error(bytes.len()));
+        }
+        let elem = Self::decode_unchecked(bytes)?;
+        if !elem.is_valid() || elem.encode() != bytes {
+            return Err(SerzDeserzError::InvalidEncoding);
+        }
+        Ok(elem)
+    }
+
+    /// `decode` which also fails for the identity element if `policy` rejects it
+    fn decode_with_policy(bytes: &[u8], policy: IdentityPolicy) -> Result<Self, SerzDeserzError> {
+        let elem = Self::decode(bytes)?;
+        if policy == IdentityPolicy::Reject && elem.is_identity_element() {
+            return Err(SerzDeserzError::IdentityElement);
+        }
+        Ok(elem)
+    }
+
+    /// Big-endian `u32` length followed by the encoding of each element, the format of the
+    /// `to_bytes` of the vector types
+    fn encode_vec(elems: &[Self]) -> Vec<u8> {
+        let mut bytes = vec![0u8; 4 + elems.len() * Self::ENCODED_SIZE];
+        bytes[..4].copy_from_slice(&(elems.len() as u32).to_be_bytes());
+        for (b, e) in bytes[4..].chunks_mut(Self::ENCODED_SIZE).zip(elems.iter()) {
+            e.encode_into(b).unwrap();
+        }
+        bytes
+    }
+
+    /// Inverse of `encode_vec`, decoding each element with `decode`
+    fn decode_vec(bytes: &[u8]) -> Result<Vec<Self>, SerzDeserzError> {
+        Self::decode_vec_with_policy(bytes, IdentityPolicy::Accept)
+    }
+
+    /// `decode_vec` decoding each element with `decode_with_policy`
+    fn decode_vec_with_policy(
+        bytes: &[u8],
+        policy: IdentityPolicy,
+    ) -> Result<Vec<Self>, SerzDeserzError> {
+        if bytes.len() < 4 {
+            return Err(SerzDeserzError::VectorBytesIncorrectSize(bytes.len(), 4));
+        }
+        let len = u32::from_be_bytes(*array_ref![bytes, 0, 4]) as usize;
+        let expected = len
+            .checked_mul(Self::ENCODED_SIZE)
+            .and_then(|l| l.checked_add(4));
+        if expected != Some(bytes.len()) {
+            return Err(SerzDeserzError::VectorBytesIncorrectSize(
+                bytes.len(),
+                expected.unwrap_or(usize::MAX),
+            ));
+        }
+        bytes[4..]
+            .chunks(Self::ENCODED_SIZE)
+            .map(|b| Self::decode_with_policy(b, policy))
+            .collect()
+    }
+}
+
+impl ElementEncoding for CurveOrderElement {
+    const ENCODED_SIZE: usize = FIELD_ORDER_ELEMENT_SIZE;
+
+    fn size_error(size: usize) -> SerzDeserzError {
+        SerzDeserzError::FieldElementBytesIncorrectSize(size, Self::ENCODED_SIZE)
+    }
+
+    fn encode_into(&self, target: &mut [u8]) -> Result<(), SerzDeserzError> {
+        self.write_to_slice(target)
+    }
+
+    /// Reduces the bytes modulo the curve order
+    fn decode_unchecked(bytes: &[u8]) -> Result<Self, SerzDeserzError> {
+        Self::try_from(bytes)
+    }
+
+    fn is_valid(&self) -> bool {
+        true
+    }
+
+    fn is_identity_element(&self) -> bool {
+        self.is_zero()
+    }
+}
+
+macro_rules! impl_group_elem_encoding {
+    ( $group_element:ident, $group_size:ident, $size_error:ident, $is_identity:ident ) => {
+        impl ElementEncoding for $group_element {
+            const ENCODED_SIZE: usize = $group_size;
+
+            fn size_error(size: usize) -> SerzDeserzError {
+                SerzDeserzError::$size_error(size, Self::ENCODED_SIZE)
+            }
+
+            fn encode_into(&self, target: &mut [u8]) -> Result<(), SerzDeserzError> {
+                self.write_to_slice(target)
+            }
+
+            fn decode_unchecked(bytes: &[u8]) -> Result<Self, SerzDeserzError> {
+                Self::from_slice(bytes)
+            }
+
+            fn is_valid(&self) -> bool {
+                self.has_correct_order()
+            }
+
+            fn is_identity_element(&self) -> bool {
+                self.$is_identity()
+            }
+        }
+    };
+}
+
+impl_group_elem_encoding!(G1, GROUP_G1_SIZE, G1BytesIncorrectSize, is_identity);
+#[cfg(any(feature = "bls381", feature = "bn254"))]
+impl_group_elem_encoding!(G2, GROUP_G2_SIZE, G2BytesIncorrectSize, is_identity);
+#[cfg(any(feature = "bls381", feature = "bn254"))]
+impl_group_elem_encoding!(GT, GROUP_GT_SIZE, GTBytesIncorrectSize, is_one);
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn check_roundtrip<T: ElementEncoding + PartialEq + std::fmt::Debug>(elems: Vec<T>) {
+        for e in &elems {
+            let bytes = e.encode();
+            assert_eq!(bytes.len(), T::ENCODED_SIZE);
+            assert_eq!(T::decode(&bytes).unwrap(), *e);
+            assert!(T::decode(&bytes[1..]).is_err());
+        }
+        let bytes = T::encode_vec(&elems);
+        assert_eq!(T::decode_vec(&bytes).unwrap(), elems);
+        assert!(T::decode_vec(&bytes[..bytes.len() - 1]).is_err());
+        assert_eq!(T::decode_vec(&T::encode_vec(&[])).unwrap(), vec![]);
+    }
+
+    #[test]
+    fn test_roundtrip() {
+        check_roundtrip((0..5).map(|_| CurveOrderElement::random()).collect());
+        check_roundtrip((0..5).map(|_| G1::random()).collect());
+        #[cfg(any(feature = "bls381", feature = "bn254"))]
+        {
+            check_roundtrip((0..3).map(|_| G2::random()).collect());
+            check_roundtrip((0..3).map(|_| GT::random()).collect());
+        }
+    }
+
+    #[test]
+    fn test_non_canonical() {
+        // Scalars not less than the curve order
+        let bytes = [0xffu8; FIELD_ORDER_ELEMENT_SIZE];
+        assert!(CurveOrderElement::decode_unchecked(&bytes).is_ok());
+        assert!(CurveOrderElement::decode(&bytes).is_err());
+
+        // Points not on the curve
+        let mut bytes = G1::random().encode();
+        let last = bytes.len() - 1;
+        bytes[last] ^= 1;
+        assert!(G1::decode(&bytes).is_err());
+    }
+
+    #[test]
+    fn test_identity_policy() {
+        fn check<T: ElementEncoding + PartialEq + std::fmt::Debug>(identity: T, other: T) {
+            let bytes = identity.encode();
+            assert_eq!(T::decode(&bytes).unwrap(), identity);
+            assert_eq!(
+                T::decode_with_policy(&bytes, IdentityPolicy::Accept).unwrap(),
+                identity
+            );
+            assert!(T::decode_with_policy(&bytes, IdentityPolicy::Reject).is_err());
+            assert_eq!(
+                T::decode_with_policy(&other.encode(), IdentityPolicy::Reject).unwrap(),
+                other
+            );
+
+            let bytes = T::encode_vec(&[other, identity]);
+            assert!(T::decode_vec(&bytes).is_ok());
+            assert!(T::decode_vec_with_policy(&bytes, IdentityPolicy::Reject).is_err());
+        }
+
+        check(CurveOrderElement::zero(), CurveOrderElement::random());
+        check(G1::identity(), G1::random());
+        #[cfg(any(feature = "bls381", feature = "bn254"))]
+        {
+            check(G2::identity(), G2::random());
+            check(GT::one(), GT::random());
+        }
+    }
+
+    #[cfg(any(feature = "bls381", feature = "bn254"))]
+    #[test]
+    fn test_gt_subgroup() {
+        let e = GT::random();
+        assert!(e.has_correct_order());
+        assert!(GT::one().has_correct_order());
+
+        // Changing a coefficient gives an element of the extension field outside the subgroup
+        let mut bytes = e.encode();
+        let last = bytes.len() - 1;
+        bytes[last] ^= 1;
+        let other = GT::decode_unchecked(&bytes).unwrap();
+        assert!(!other.has_correct_order());
+        assert!(GT::decode(&bytes).is_err());
+        assert!(GT::decode(&vec![0u8; GROUP_GT_SIZE]).is_err());
+    }
+}
@@ -8,6 +8,9 @@ pub enum ValueError {
     NonPowerOf2(usize),
     OutOfRange(usize),
     NegativeValue(BigNum),
+    IdentityElement,
+    ZeroElement,
+    Cancelled,
 }
 
 #[derive(Debug, Clone, Copy)]
@@ -16,8 +19,21 @@ pub enum SerzDeserzError {
     G1BytesIncorrectSize(usize, usize),
     G2BytesIncorrectSize(usize, usize),
     GTBytesIncorrectSize(usize, usize),
+    VectorBytesIncorrectSize(usize, usize),
     RequiredHexChar,
+    RequiredDecimalChar,
     CannotParseFP,
+    InvalidEncoding,
+    InvalidJwk,
+    InvalidCoseKey,
+    UnsupportedCodecVersion(u8),
+    CurveMismatch(u8, u8),
+    LabelMismatch,
+    UnexpectedEnd,
+    TrailingBytes(usize),
+    IdentityElement,
+    IoError(std::io::ErrorKind),
+    InvalidElementAt(usize),
 }
 
 impl fmt::Display for SerzDeserzError {
@@ -43,12 +59,126 @@ impl fmt::Display for SerzDeserzError {
                 "Incorrect bytes size for G2 group element. Given {} but expected {}",
                 a, b
             ),
+            SerzDeserzError::VectorBytesIncorrectSize(a, b) => write!(
+                f,
+                "Incorrect bytes size for vector. Given {} but expected {}",
+                a, b
+            ),
             SerzDeserzError::RequiredHexChar => write!(f, "Required hex character"),
+            SerzDeserzError::RequiredDecimalChar => write!(f, "Required decimal digit"),
             SerzDeserzError::CannotParseFP => write!(f, "Error while parsing FP"),
+            SerzDeserzError::InvalidEncoding => write!(f, "Not the canonical encoding of a valid element"),
+            SerzDeserzError::InvalidJwk => write!(f, "Invalid or unsupported JWK"),
+            SerzDeserzError::InvalidCoseKey => write!(f, "Invalid or unsupported COSE key"),
+            SerzDeserzError::UnsupportedCodecVersion(v) => {
+                write!(f, "Unsupported codec version {}", v)
+            }
+            SerzDeserzError::CurveMismatch(a, b) => {
+                write!(f, "Encoded for curve {} but expected curve {}", a, b)
+            }
+            SerzDeserzError::LabelMismatch => write!(f, "Encoded for another struct"),
+            SerzDeserzError::UnexpectedEnd => write!(f, "Unexpected end of bytes"),
+            SerzDeserzError::TrailingBytes(n) => write!(f, "{} trailing bytes", n),
+            SerzDeserzError::IdentityElement => write!(f, "Identity element is not accepted"),
+            SerzDeserzError::IoError(kind) => write!(f, "I/O error: {:?}", kind),
+            SerzDeserzError::InvalidElementAt(i) => {
+                write!(f, "Element at index {} is not a valid encoding", i)
+            }
         }
     }
 }
 
+impl From<std::io::Error> for SerzDeserzError {
+    fn from(e: std::io::Error) -> Self {
+        SerzDeserzError::IoError(e.kind())
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub enum EntropyError {
+    InsufficientEntropy(usize, usize),
+    RngFailure,
+    StuckOutput,
+    RequestTooLarge(usize, usize),
+    ReseedRequired,
+}
+
+impl fmt::Display for EntropyError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            EntropyError::InsufficientEntropy(a, b) => write!(
+                f,
+                "Insufficient entropy. Given {} bytes but need at least {}",
+                a, b
+            ),
+            EntropyError::RngFailure => write!(f, "Random number generator failed to provide entropy"),
+            EntropyError::StuckOutput => write!(f, "Random number generator output is constant"),
+            EntropyError::RequestTooLarge(a, b) => write!(
+                f,
+                "Too many random bytes requested. Given {} but at most {}",
+                a, b
+            ),
+            EntropyError::ReseedRequired => write!(f, "Random number generator must be reseeded"),
+        }
+    }
+}
+
+impl std::error::Error for EntropyError {}
+
+/// Errors of the protocol modules, which wrap the errors of the underlying arithmetic and
+/// serialization
+#[derive(Debug, Clone, Copy)]
+pub enum CryptoError {
+    InvalidPoint,
+    InvalidScalar,
+    VerificationFailed,
+    /// Threshold and the number of valid contributions given
+    ThresholdNotMet(usize, usize),
+    TranscriptMismatch,
+    Value(ValueError),
+    Serialization(SerzDeserzError),
+    Entropy(EntropyError),
+}
+
+impl fmt::Display for CryptoError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            CryptoError::InvalidPoint => write!(f, "Invalid group element"),
+            CryptoError::InvalidScalar => write!(f, "Invalid curve order element"),
+            CryptoError::VerificationFailed => write!(f, "Verification failed"),
+            CryptoError::ThresholdNotMet(a, b) => write!(
+                f,
+                "Threshold not met. Need {} valid contributions but given {}",
+                a, b
+            ),
+            CryptoError::TranscriptMismatch => write!(f, "Transcripts of the parties differ"),
+            CryptoError::Value(e) => write!(f, "{:?}", e),
+            CryptoError::Serialization(e) => write!(f, "{}", e),
+            CryptoError::Entropy(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl std::error::Error for CryptoError {}
+
+impl From<ValueError> for CryptoError {
+    fn from(e: ValueError) -> Self {
+        CryptoError::Value(e)
+    }
+}
+
+impl From<SerzDeserzError> for CryptoError {
+    fn from(e: SerzDeserzError) -> Self {
+        CryptoError::Serialization(e)
+    }
+}
+
+impl From<EntropyError> for CryptoError {
+    fn from(e: EntropyError) -> Self {
+        CryptoError::Entropy(e)
+    }
+}
+
 #[macro_export]
 macro_rules! check_vector_size_for_equality {
     ( $a:expr, $b:expr ) => {{
@@ -73,4 +203,24 @@ mod test {
         let a3 = vec![1, 4, 6];
         assert!(check_vector_size_for_equality!(a3, a2).is_err());
     }
+
+    #[test]
+    fn test_crypto_error_conversions() {
+        fn size(a: &[u8], b: &[u8]) -> Result<(), CryptoError> {
+            check_vector_size_for_equality!(a, b)?;
+            Ok(())
+        }
+        assert!(matches!(
+            size(&[1], &[1, 2]),
+            Err(CryptoError::Value(ValueError::UnequalSizeVectors(1, 2)))
+        ));
+        assert!(matches!(
+            CryptoError::from(SerzDeserzError::InvalidEncoding),
+            CryptoError::Serialization(SerzDeserzError::InvalidEncoding)
+        ));
+        assert_eq!(
+            CryptoError::ThresholdNotMet(3, 2).to_string(),
+            "Threshold not met. Need 3 valid contributions but given 2"
+        );
+    }
 }
@@ -1,19 +1,22 @@
 use crate::types::GroupGT;
 
-use super::ECCurve::fp12::{DENSE, FP12};
-use super::ECCurve::fp4::FP4;
-use super::ECCurve::pair::{another, ate, ate2, fexp, initmp, miller};
-use crate::constants::GROUP_GT_SIZE;
+use crate::backend::{Backend, CurrentBackend, Group, DENSE, FP12, FP4};
+use crate::constants::{CURVE_ORDER, GROUP_GT_COMPRESSED_SIZE, GROUP_GT_SIZE, MODBYTES};
 use crate::errors::{SerzDeserzError, ValueError};
 use crate::curve_order_elem::CurveOrderElement;
 use crate::group_elem::GroupElement;
 use crate::group_elem_g1::G1;
 use crate::group_elem_g2::{parse_hex_as_fp2, G2};
+use crate::utils::{fmt_truncated_hex, hash_msg_to_scalar, SHORT_HEX_CHARS};
+use crate::yielding::{check_chunk_size, ChunkedWork};
+use std::collections::{BTreeMap, HashMap};
 use std::fmt;
 use std::hash::{Hash, Hasher};
 use std::ops::Mul;
 
+#[cfg(feature = "serde")]
 use serde::de::{Deserialize, Deserializer, Error as DError, Visitor};
+#[cfg(feature = "serde")]
 use serde::ser::{Serialize, Serializer};
 use std::str::SplitWhitespace;
 use zeroize::Zeroize;
@@ -23,14 +26,12 @@ pub struct GT {
     value: GroupGT,
 }
 
-impl fmt::Debug for GT {
-    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        let mut c = self.value.clone();
-        write!(f, "{}", c.tostring())
-    }
-}
-
 impl GT {
+    /// Size of the byte representation of `to_vec` and `from_slice`
+    pub const SERIALIZED_SIZE: usize = GROUP_GT_SIZE;
+    /// Size of the byte representation of `to_compressed_bytes`
+    pub const COMPRESSED_SIZE: usize = GROUP_GT_COMPRESSED_SIZE;
+
     pub fn new() -> Self {
         Self {
             value: GroupGT::new(),
@@ -43,8 +44,14 @@ impl GT {
         if g1.is_identity() || g2.is_identity() {
             return Self::one();
         }
-        let e = ate(&g2.to_ecp(), &g1.to_ecp());
-        Self { value: fexp(&e) }
+        Self {
+            value: CurrentBackend::pairing(&g1.to_ecp(), &g2.to_ecp()),
+        }
+    }
+
+    /// `ate_pairing` returning the pairing stored in `cache` when the same inputs were paired before
+    pub fn ate_pairing_cached(g1: &G1, g2: &G2, cache: &mut PairingCache) -> Self {
+        cache.ate_pairing(g1, g2)
     }
 
     /// Reduced ate double pairing. Returns `e(g1, g2) * e(h1, h2)`
@@ -56,38 +63,55 @@ impl GT {
         if h1.is_identity() || h2.is_identity() {
             return Self::ate_pairing(g1, g2);
         }
-        let e = ate2(&g2.to_ecp(), &g1.to_ecp(), &h2.to_ecp(), &h1.to_ecp());
-        Self { value: fexp(&e) }
+        Self {
+            value: CurrentBackend::double_pairing(
+                &g1.to_ecp(),
+                &g2.to_ecp(),
+                &h1.to_ecp(),
+                &h2.to_ecp(),
+            ),
+        }
     }
 
     /// Reduced ate multi pairing. Takes a vector of tuples of group elements G1 and G2 as Vec<(&G1, &G2)>.
     /// Returns the product of their pairings.
     /// More efficient than using ate_pairing or ate_2_pairing and multiplying results
     pub fn ate_multi_pairing(elems: Vec<(&G1, &G2)>) -> Self {
-        let mut accum = initmp();
-        for (g1, g2) in elems {
-            if g1.is_identity() || g2.is_identity() {
-                continue;
-            }
-            another(&mut accum, &g2.to_ecp(), &g1.to_ecp());
+        let pairs = elems
+            .into_iter()
+            .filter(|(g1, g2)| !g1.is_identity() && !g2.is_identity())
+            .map(|(g1, g2)| (g1.to_ecp(), g2.to_ecp()));
+        Self {
+            value: CurrentBackend::multi_pairing(pairs),
         }
-        let e = miller(&accum);
-        Self { value: fexp(&e) }
+    }
+
+    /// `ate_multi_pairing` in steps of `chunk_size` pairs, see `yielding`
+    pub fn ate_multi_pairing_chunked<'a>(
+        elems: Vec<(&'a G1, &'a G2)>,
+        chunk_size: usize,
+    ) -> Result<PairingChunks<'a>, ValueError> {
+        check_chunk_size(chunk_size)?;
+        Ok(PairingChunks {
+            elems,
+            chunk_size,
+            done: 0,
+            accum: FP12::new_int(1),
+        })
     }
 
     /// Inner product of 2 vectors in group G1 and G2.
     /// Equivalent to a multi-pairing
     pub fn inner_product(left: &[G1], right: &[G2]) -> Result<Self, ValueError> {
         check_vector_size_for_equality!(left, right)?;
-        let mut accum = initmp();
-        for (g1, g2) in left.iter().zip(right) {
-            if g1.is_identity() || g2.is_identity() {
-                continue;
-            }
-            another(&mut accum, &g2.to_ecp(), &g1.to_ecp());
-        }
-        let e = miller(&accum);
-        Ok(Self { value: fexp(&e) })
+        let pairs = left
+            .iter()
+            .zip(right)
+            .filter(|(g1, g2)| !g1.is_identity() && !g2.is_identity())
+            .map(|(g1, g2)| (g1.to_ecp(), g2.to_ecp()));
+        Ok(Self {
+            value: CurrentBackend::multi_pairing(pairs),
+        })
     }
 
     pub fn product(a: &Self, b: &Self) -> Self {
@@ -97,8 +121,12 @@ impl GT {
     }
 
     pub fn pow(&self, e: &CurveOrderElement) -> Self {
+        // amcl's pow does not handle a zero exponent
+        if e.is_zero() {
+            return Self::one();
+        }
         Self {
-            value: self.value.pow(&e.to_bignum()),
+            value: self.value.scalar_mul(&e.to_bignum()),
         }
     }
 
@@ -117,6 +145,26 @@ impl GT {
         return self.value.isunity();
     }
 
+    /// Checks if the element is in the subgroup of the curve order, so not any element of the
+    /// extension field decoded from bytes. Uses general squarings since amcl's `pow` assumes an
+    /// element of the cyclotomic subgroup.
+    pub fn has_correct_order(&self) -> bool {
+        if self.value.iszilch() {
+            return false;
+        }
+        let mut order = CURVE_ORDER;
+        order.norm();
+        let mut acc = FP12::new_int(1);
+        for i in (0..order.nbits()).rev() {
+            acc.sqr();
+            if order.bit(i) == 1 {
+                acc.mul(&self.value);
+            }
+        }
+        acc.reduce();
+        acc.isunity()
+    }
+
     pub fn one() -> Self {
         let zero = FP4::new_int(0);
         let one = FP4::new_int(1);
@@ -130,13 +178,21 @@ impl GT {
     }
 
     pub fn to_vec(&self) -> Vec<u8> {
-        let mut temp = FP12::new();
-        temp.copy(&self.value);
         let mut bytes: [u8; GROUP_GT_SIZE] = [0; GROUP_GT_SIZE];
-        temp.tobytes(&mut bytes);
+        self.value.to_bytes(&mut bytes);
         bytes.to_vec()
     }
 
+    /// The first `SHORT_HEX_CHARS` hex characters of `to_vec`, for logs
+    pub fn fmt_short(&self) -> String {
+        self.fmt_truncated(SHORT_HEX_CHARS)
+    }
+
+    /// The first `chars` hex characters of `to_vec`
+    pub fn fmt_truncated(&self, chars: usize) -> String {
+        fmt_truncated_hex(&self.to_vec(), chars)
+    }
+
     pub fn from_slice(bytes: &[u8]) -> Result<Self, SerzDeserzError> {
         if bytes.len() != GROUP_GT_SIZE {
             return Err(SerzDeserzError::GTBytesIncorrectSize(
@@ -145,7 +201,7 @@ impl GT {
             ));
         }
         Ok(Self {
-            value: FP12::frombytes(bytes),
+            value: FP12::from_bytes(bytes),
         })
     }
 
@@ -158,18 +214,90 @@ impl GT {
                 GROUP_GT_SIZE,
             ));
         }
-        let mut temp = FP12::new();
-        temp.copy(&self.value);
-        temp.tobytes(target);
+        self.value.to_bytes(target);
         Ok(())
     }
 
     /// Writes bytes to given slice. Will panic when given slice is not of
     /// desired length.
     pub fn write_to_slice_unchecked(&self, target: &mut [u8]) {
-        let mut temp = FP12::new();
-        temp.copy(&self.value);
-        temp.tobytes(target);
+        self.value.to_bytes(target);
+    }
+
+    /// Encoding of half the size of `to_vec` for elements of the cyclotomic subgroup, like
+    /// pairing outputs, by torus compression. Such an element `f = g0 + g1*w`, with `g0`, `g1` in
+    /// the sextic subfield and `w^2` in it, is encoded as `(1 + g0)/g1` of that subfield. The
+    /// identity, the only element of GT with `g1 = 0`, is encoded as zeros. Other elements of the
+    /// extension field do not decode to themselves.
+    pub fn to_compressed_bytes(&self) -> [u8; Self::COMPRESSED_SIZE] {
+        let mut out = [0u8; Self::COMPRESSED_SIZE];
+        let mut conj = self.value;
+        conj.conj();
+        // 2*g1*w
+        let mut d = fp12_combine(&self.value, &conj, FP4::sub);
+        d.reduce();
+        if d.iszilch() {
+            return out;
+        }
+        // (1 + g0)/g1 = (2 + 2*g0)*w/(2*g1*w)
+        let mut c = fp12_combine(
+            &fp12_combine(&self.value, &conj, FP4::add),
+            &FP12::new_int(2),
+            FP4::add,
+        );
+        c.mul(&fp12_w());
+        d.inverse();
+        c.mul(&d);
+        c.reduce();
+        let mut bytes = [0u8; GROUP_GT_SIZE];
+        c.to_bytes(&mut bytes);
+        for (o, i) in out.chunks_mut(MODBYTES).zip(SUBFIELD_CHUNKS.iter()) {
+            o.copy_from_slice(&bytes[i * MODBYTES..(i + 1) * MODBYTES]);
+        }
+        out
+    }
+
+    /// Inverse of `to_compressed_bytes`. Fails unless `bytes` are the canonical encoding of an
+    /// element of the subgroup of the curve order, so it is safe for untrusted input.
+    pub fn from_compressed_bytes(bytes: &[u8]) -> Result<Self, SerzDeserzError> {
+        let elem = Self::from_compressed_bytes_unchecked(bytes)?;
+        if !elem.has_correct_order() || elem.to_compressed_bytes()[..] != bytes[..] {
+            return Err(SerzDeserzError::InvalidEncoding);
+        }
+        Ok(elem)
+    }
+
+    /// `from_compressed_bytes` without the checks, for trusted bytes. The element is in the
+    /// cyclotomic subgroup but maybe not in the subgroup of the curve order.
+    pub fn from_compressed_bytes_unchecked(bytes: &[u8]) -> Result<Self, SerzDeserzError> {
+        if bytes.len() != Self::COMPRESSED_SIZE {
+            return Err(SerzDeserzError::GTBytesIncorrectSize(
+                bytes.len(),
+                Self::COMPRESSED_SIZE,
+            ));
+        }
+        if bytes.iter().all(|b| *b == 0) {
+            return Ok(Self::one());
+        }
+        let mut full = [0u8; GROUP_GT_SIZE];
+        for (b, i) in bytes.chunks(MODBYTES).zip(SUBFIELD_CHUNKS.iter()) {
+            full[i * MODBYTES..(i + 1) * MODBYTES].copy_from_slice(b);
+        }
+        // f = (c + w)/(c - w), where c - w is never zero as `w` is not in the subfield
+        let c = FP12::from_bytes(&full);
+        let w = fp12_w();
+        let mut value = fp12_combine(&c, &w, FP4::add);
+        let mut d = fp12_combine(&c, &w, FP4::sub);
+        d.inverse();
+        value.mul(&d);
+        value.reduce();
+        Ok(Self { value })
+    }
+
+    /// Hash to a curve order element, for deriving keys or challenges from pairing outputs. The
+    /// fixed size encoding of `to_vec` is hashed with `hash_msg_to_scalar` and tag `dst`.
+    pub fn hash_to_scalar(&self, dst: &[u8]) -> CurveOrderElement {
+        hash_msg_to_scalar(&self.to_vec(), dst)
     }
 
     pub fn to_hex(&self) -> String {
@@ -212,6 +340,39 @@ pub fn parse_hex_as_fp4(iter: &mut SplitWhitespace) -> Result<FP4, SerzDeserzErr
     Ok(fp4)
 }
 
+/// Components of the encoding of `to_vec` of the sextic subfield, the real and imaginary parts of
+/// the coefficients of `1`, `w^2` and `w^4`
+const SUBFIELD_CHUNKS: [usize; 6] = [0, 1, 8, 9, 6, 7];
+
+/// `w`, with `w^2` in the sextic subfield and `w^3` in the quartic one
+fn fp12_w() -> FP12 {
+    FP12::new_fp4s(&FP4::new(), &FP4::new_int(1), &FP4::new())
+}
+
+/// Apply `op` to the coefficients of `x` and `y` over the quartic subfield, e.g. to add them
+fn fp12_combine(x: &FP12, y: &FP12, op: fn(&mut FP4, &FP4)) -> FP12 {
+    let (mut x, mut y) = (*x, *y);
+    let mut parts = [x.geta(), x.getb(), x.getc()];
+    for (p, q) in parts.iter_mut().zip([y.geta(), y.getb(), y.getc()].iter()) {
+        op(p, q);
+        p.norm();
+    }
+    FP12::new_fp4s(&parts[0], &parts[1], &parts[2])
+}
+
+/// Arbitrary element for fuzzing and property tests: one with probability 1/8, otherwise the pairing
+/// of an arbitrary G1 element with the G2 generator
+#[cfg(feature = "arbitrary")]
+impl<'a> arbitrary::Arbitrary<'a> for GT {
+    fn arbitrary(u: &mut arbitrary::Unstructured<'a>) -> arbitrary::Result<Self> {
+        if u.int_in_range(0u8..=7)? == 0 {
+            return Ok(Self::one());
+        }
+        let g1: G1 = u.arbitrary()?;
+        Ok(Self::ate_pairing(&g1, &G2::generator()))
+    }
+}
+
 impl PartialEq for GT {
     fn eq(&self, other: &GT) -> bool {
         self.value.equals(&other.value)
@@ -254,6 +415,176 @@ impl Mul<&GT> for &GT {
     }
 }
 
+/// Serde of `GT` in the binary encoding of `to_compressed_bytes`, for fields annotated with
+/// `#[serde(with = "amcl_wrapper_ml::extension_field_gt::compressed")]`, rather than the hex of
+/// `to_hex`. Deserialization uses `from_compressed_bytes` so only elements of the subgroup of
+/// the curve order are accepted.
+#[cfg(feature = "serde")]
+pub mod compressed {
+    use super::GT;
+    use serde::de::{Deserializer, Error, SeqAccess, Visitor};
+    use serde::ser::Serializer;
+    use std::fmt;
+
+    pub fn serialize<S: Serializer>(elem: &GT, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_bytes(&elem.to_compressed_bytes())
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<GT, D::Error> {
+        struct CompressedVisitor;
+
+        impl<'de> Visitor<'de> for CompressedVisitor {
+            type Value = GT;
+
+            fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+                write!(formatter, "{} bytes of a compressed GT element", GT::COMPRESSED_SIZE)
+            }
+
+            fn visit_bytes<E: Error>(self, value: &[u8]) -> Result<GT, E> {
+                GT::from_compressed_bytes(value).map_err(E::custom)
+            }
+
+            // Formats without bytes, like JSON, give a sequence
+            fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<GT, A::Error> {
+                let mut bytes = Vec::with_capacity(GT::COMPRESSED_SIZE);
+                while let Some(b) = seq.next_element::<u8>()? {
+                    if bytes.len() == GT::COMPRESSED_SIZE {
+                        return Err(A::Error::invalid_length(bytes.len() + 1, &self));
+                    }
+                    bytes.push(b);
+                }
+                self.visit_bytes(&bytes)
+            }
+        }
+
+        deserializer.deserialize_bytes(CompressedVisitor)
+    }
+}
+
+/// Bounded cache of pairings keyed by the compressed bytes of their inputs, evicting the least
+/// recently used pairing when full. Meant for workloads pairing the same few elements, like the
+/// elements of a verification key, over and over. Not synchronized, wrap it in a `Mutex` to share
+/// it between threads.
+#[derive(Clone, Debug)]
+pub struct PairingCache {
+    capacity: usize,
+    /// Pairing and last use of each key
+    entries: HashMap<Vec<u8>, (GT, u64)>,
+    /// Keys by last use
+    recency: BTreeMap<u64, Vec<u8>>,
+    counter: u64,
+    hits: u64,
+    misses: u64,
+}
+
+impl PairingCache {
+    /// Cache holding at most `capacity` pairings. A capacity of 0 disables caching.
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            entries: HashMap::new(),
+            recency: BTreeMap::new(),
+            counter: 0,
+            hits: 0,
+            misses: 0,
+        }
+    }
+
+    /// Returns `e(g1, g2)`, computing and storing it on a miss
+    pub fn ate_pairing(&mut self, g1: &G1, g2: &G2) -> GT {
+        let mut key = g1.to_compressed_bytes().to_vec();
+        key.extend_from_slice(&g2.to_compressed_bytes());
+        self.counter += 1;
+        if let Some((e, last_use)) = self.entries.get_mut(&key) {
+            self.recency.remove(last_use);
+            *last_use = self.counter;
+            self.recency.insert(self.counter, key);
+            self.hits += 1;
+            return e.clone();
+        }
+        self.misses += 1;
+        let e = GT::ate_pairing(g1, g2);
+        if self.capacity == 0 {
+            return e;
+        }
+        if self.entries.len() == self.capacity {
+            let oldest = *self.recency.keys().next().unwrap();
+            let evicted = self.recency.remove(&oldest).unwrap();
+            self.entries.remove(&evicted);
+        }
+        self.recency.insert(self.counter, key.clone());
+        self.entries.insert(key, (e.clone(), self.counter));
+        e
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Number of cached pairings
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Number of pairings returned from the cache
+    pub fn hits(&self) -> u64 {
+        self.hits
+    }
+
+    /// Number of pairings computed
+    pub fn misses(&self) -> u64 {
+        self.misses
+    }
+
+    pub fn clear(&mut self) {
+        self.entries.clear();
+        self.recency.clear();
+    }
+}
+
+/// Multi pairing sharing a Miller loop between `chunk_size` pairs at a time. The outputs of the
+/// Miller loops are multiplied and the final exponentiation is done once by `finish`.
+pub struct PairingChunks<'a> {
+    elems: Vec<(&'a G1, &'a G2)>,
+    chunk_size: usize,
+    done: usize,
+    accum: FP12,
+}
+
+impl<'a> Iterator for PairingChunks<'a> {
+    type Item = (usize, usize);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let total = self.elems.len();
+        if self.done == total {
+            return None;
+        }
+        let end = total.min(self.done + self.chunk_size);
+        let pairs = self.elems[self.done..end]
+            .iter()
+            .filter(|(g1, g2)| !g1.is_identity() && !g2.is_identity())
+            .map(|(g1, g2)| (g1.to_ecp(), g2.to_ecp()));
+        self.accum.mul(&CurrentBackend::miller_loop(pairs));
+        self.done = end;
+        Some((end, total))
+    }
+}
+
+impl<'a> ChunkedWork for PairingChunks<'a> {
+    type Output = GT;
+
+    fn finish(mut self) -> GT {
+        while self.next().is_some() {}
+        GT {
+            value: CurrentBackend::final_exp(&self.accum),
+        }
+    }
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -263,6 +594,67 @@ mod test {
     fn test_unity() {
         let one = GT::one();
         assert!(one.is_one());
+        assert!(GT::random().pow(&CurveOrderElement::zero()).is_one());
+    }
+
+    #[test]
+    fn test_hash_to_scalar() {
+        let g1 = G1::random();
+        let g2 = G2::random();
+        let r = CurveOrderElement::random();
+        // Both sides of the bilinearity equation hash to the same scalar
+        let e = GT::ate_pairing(&(&g1 * &r), &g2);
+        let k = e.hash_to_scalar(b"session key");
+        assert_eq!(GT::ate_pairing(&g1, &(&g2 * &r)).hash_to_scalar(b"session key"), k);
+        assert_ne!(e.hash_to_scalar(b"challenge"), k);
+        assert_ne!(GT::ate_pairing(&g1, &g2).hash_to_scalar(b"session key"), k);
+        assert_eq!(GT::from_slice(&e.to_vec()).unwrap().hash_to_scalar(b"session key"), k);
+    }
+
+    #[test]
+    fn test_compressed() {
+        for e in [GT::random(), GT::random().inverse(), GT::one()].iter() {
+            let bytes = e.to_compressed_bytes();
+            assert_eq!(GT::from_compressed_bytes(&bytes).unwrap(), *e);
+        }
+        assert_eq!(GT::one().to_compressed_bytes(), [0u8; GT::COMPRESSED_SIZE]);
+        assert!(GT::from_compressed_bytes(&[0u8; GT::COMPRESSED_SIZE - 1]).is_err());
+
+        // Decodes to an element of the cyclotomic subgroup outside the subgroup of the curve order
+        let mut outside = [0u8; GT::COMPRESSED_SIZE];
+        outside[GT::COMPRESSED_SIZE - 1] = 1;
+        assert!(!GT::from_compressed_bytes_unchecked(&outside)
+            .unwrap()
+            .has_correct_order());
+        assert!(GT::from_compressed_bytes(&outside).is_err());
+
+        // A component not reduced
+        let e = GT::random();
+        let mut bytes = e.to_compressed_bytes();
+        let mut x = crate::types::BigNum::frombytes(&bytes[..MODBYTES]);
+        x.add(&crate::constants::MODULUS);
+        x.norm();
+        x.tobytes(&mut bytes[..MODBYTES]);
+        assert_eq!(GT::from_compressed_bytes_unchecked(&bytes).unwrap(), e);
+        assert!(GT::from_compressed_bytes(&bytes).is_err());
+
+        #[cfg(feature = "serde")]
+        {
+            #[derive(Debug, PartialEq, serde::Serialize, serde::Deserialize)]
+            struct Credential {
+                #[serde(with = "compressed")]
+                e: GT,
+            }
+            let c = Credential { e };
+            let s = serde_json::to_string(&c).unwrap();
+            assert_eq!(serde_json::from_str::<Credential>(&s).unwrap(), c);
+            let c = Credential {
+                e: GT::from_compressed_bytes_unchecked(&outside).unwrap(),
+            };
+            let s = serde_json::to_string(&c).unwrap();
+            assert!(serde_json::from_str::<Credential>(&s).is_err());
+            assert!(serde_json::from_str::<Credential>(r#"{"e":[1,2,3]}"#).is_err());
+        }
     }
 
     #[test]
@@ -363,6 +755,26 @@ mod test {
         .is_one());
     }
 
+    #[test]
+    fn test_ate_multi_pairing_chunked() {
+        let g1s: Vec<_> = (0..5).map(|_| G1::random()).collect();
+        let mut g2s: Vec<_> = (0..5).map(|_| G2::random()).collect();
+        g2s[2] = G2::identity();
+        let elems: Vec<_> = g1s.iter().zip(g2s.iter()).collect();
+        let expected = GT::ate_multi_pairing(elems.clone());
+
+        let mut chunks = GT::ate_multi_pairing_chunked(elems.clone(), 2).unwrap();
+        assert_eq!(chunks.next(), Some((2, 5)));
+        assert_eq!(chunks.finish(), expected);
+        let chunks = GT::ate_multi_pairing_chunked(elems.clone(), 10).unwrap();
+        assert_eq!(chunks.finish(), expected);
+        assert!(GT::ate_multi_pairing_chunked(vec![], 2)
+            .unwrap()
+            .finish()
+            .is_one());
+        assert!(GT::ate_multi_pairing_chunked(elems, 0).is_err());
+    }
+
     #[test]
     fn test_ate_pairing_negative() {
         let g1 = G1::random();
@@ -537,4 +949,33 @@ mod test {
             assert_eq!(pairing_exp[i], g2_exp[i]);
         }
     }
+
+    #[test]
+    fn test_pairing_cache() {
+        let mut cache = PairingCache::new(2);
+        let g1 = G1::random();
+        let h1 = G1::random();
+        let g2 = G2::random();
+        let e = GT::ate_pairing(&g1, &g2);
+        assert_eq!(GT::ate_pairing_cached(&g1, &g2, &mut cache), e);
+        assert_eq!(GT::ate_pairing_cached(&g1, &g2, &mut cache), e);
+        assert_eq!((cache.hits(), cache.misses()), (1, 1));
+
+        // The least recently used pairing is evicted
+        let f = cache.ate_pairing(&h1, &g2);
+        assert_eq!(f, GT::ate_pairing(&h1, &g2));
+        cache.ate_pairing(&g1, &g2);
+        cache.ate_pairing(&G1::identity(), &g2);
+        assert_eq!(cache.len(), 2);
+        assert_eq!(cache.ate_pairing(&g1, &g2), e);
+        assert_eq!(cache.misses(), 3);
+        assert_eq!(cache.ate_pairing(&h1, &g2), f);
+        assert_eq!(cache.misses(), 4);
+
+        cache.clear();
+        assert!(cache.is_empty());
+        let mut disabled = PairingCache::new(0);
+        assert_eq!(disabled.ate_pairing(&g1, &g2), e);
+        assert!(disabled.is_empty());
+    }
 }
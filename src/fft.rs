@@ -0,0 +1,587 @@
+//! Radix-2 FFT over power of 2 sized multiplicative subgroups of the scalar field. The same domains
+//! are used for vectors of curve order elements, eg. polynomial coefficients, and for vectors of G1
+//! elements treated as coefficients "in the exponent", as needed to compute all KZG opening proofs
+//! at once (Feist–Khovratovich).
+
+use crate::curve_order_elem::{CurveOrderElement, CurveOrderElementVector};
+pub use crate::curve_order_elem::{TWO_ADICITY, TWO_ADIC_ROOT_OF_UNITY};
+use crate::errors::ValueError;
+use crate::group_elem::{GroupElement, GroupElementVector};
+use crate::group_elem_g1::{G1Vector, G1};
+use crate::yielding::{check_chunk_size, ChunkedWork};
+
+use rayon::prelude::*;
+
+/// Elements that an FFT can be computed over
+trait FftElement: Clone + Send + Sync {
+    fn zero() -> Self;
+
+    fn fft_add(&self, b: &Self) -> Self;
+
+    fn fft_sub(&self, b: &Self) -> Self;
+
+    fn fft_mul(&self, s: &CurveOrderElement) -> Self;
+}
+
+impl FftElement for CurveOrderElement {
+    fn zero() -> Self {
+        CurveOrderElement::zero()
+    }
+
+    fn fft_add(&self, b: &Self) -> Self {
+        self + b
+    }
+
+    fn fft_sub(&self, b: &Self) -> Self {
+        self - b
+    }
+
+    fn fft_mul(&self, s: &CurveOrderElement) -> Self {
+        self * s
+    }
+}
+
+impl FftElement for G1 {
+    fn zero() -> Self {
+        G1::identity()
+    }
+
+    fn fft_add(&self, b: &Self) -> Self {
+        self + b
+    }
+
+    fn fft_sub(&self, b: &Self) -> Self {
+        self - b
+    }
+
+    fn fft_mul(&self, s: &CurveOrderElement) -> Self {
+        self * s
+    }
+}
+
+/// The subgroup `{1, w, w^2, ... w^(n-1)}` of order `n`, a power of 2, of the scalar field
+#[derive(Clone, Debug, PartialEq)]
+pub struct EvaluationDomain {
+    size: usize,
+    log_size: usize,
+    omega: CurveOrderElement,
+    omega_inv: CurveOrderElement,
+    size_inv: CurveOrderElement,
+}
+
+impl EvaluationDomain {
+    /// `size` must be a power of 2 not more than `2^TWO_ADICITY`
+    pub fn new(size: usize) -> Result<Self, ValueError> {
+        if !size.is_power_of_two() {
+            return Err(ValueError::NonPowerOf2(size));
+        }
+        let log_size = size.trailing_zeros() as usize;
+        if log_size > *TWO_ADICITY {
+            return Err(ValueError::OutOfRange(size));
+        }
+        let mut omega = TWO_ADIC_ROOT_OF_UNITY.clone();
+        for _ in log_size..*TWO_ADICITY {
+            omega = omega.square();
+        }
+        Ok(Self {
+            size,
+            log_size,
+            omega_inv: omega.inverse(),
+            omega,
+            size_inv: CurveOrderElement::from(size as u64).inverse(),
+        })
+    }
+
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    pub fn log_size(&self) -> usize {
+        self.log_size
+    }
+
+    /// Generator `w` of the domain
+    pub fn generator(&self) -> &CurveOrderElement {
+        &self.omega
+    }
+
+    /// `[1, w, w^2, ... w^(n-1)]`
+    pub fn elements(&self) -> CurveOrderElementVector {
+        CurveOrderElementVector::new_vandermonde_vector(&self.omega, self.size)
+    }
+
+    /// Evaluations at the domain elements of the polynomial with coefficients `coeffs`. Fewer
+    /// coefficients than the domain size are padded with zeros.
+    pub fn fft(&self, coeffs: &CurveOrderElementVector) -> Result<CurveOrderElementVector, ValueError> {
+        self.transform(coeffs.as_slice(), &self.omega, None).map(|v| v.into())
+    }
+
+    /// Coefficients of the polynomial with the given evaluations at the domain elements
+    pub fn ifft(&self, evals: &CurveOrderElementVector) -> Result<CurveOrderElementVector, ValueError> {
+        self.transform(evals.as_slice(), &self.omega_inv, Some(&self.size_inv))
+            .map(|v| v.into())
+    }
+
+    /// `fft` with G1 elements as coefficients, i.e. `out_i = sum_j w^(ij) * points_j`
+    pub fn fft_g1(&self, points: &G1Vector) -> Result<G1Vector, ValueError> {
+        self.transform(points.as_slice(), &self.omega, None).map(|v| v.into())
+    }
+
+    /// Inverse of `fft_g1`
+    pub fn ifft_g1(&self, points: &G1Vector) -> Result<G1Vector, ValueError> {
+        self.transform(points.as_slice(), &self.omega_inv, Some(&self.size_inv))
+            .map(|v| v.into())
+    }
+
+    /// `[g, g*w, g*w^2, ... g*w^(n-1)]`, the coset of the domain shifted by `g`
+    pub fn coset_elements(&self, shift: &CurveOrderElement) -> CurveOrderElementVector {
+        let mut elems = self.elements();
+        elems.scale(shift);
+        elems
+    }
+
+    /// Evaluations at the elements of the coset shifted by `shift` of the polynomial with
+    /// coefficients `coeffs`, i.e. `fft` of `[c_0, g*c_1, g^2*c_2, ...]`
+    pub fn fft_coset(
+        &self,
+        coeffs: &CurveOrderElementVector,
+        shift: &CurveOrderElement,
+    ) -> Result<CurveOrderElementVector, ValueError> {
+        if coeffs.len() > self.size {
+            return Err(ValueError::IncorrectSize(coeffs.len()));
+        }
+        let mut shifted = coeffs.clone();
+        distribute_powers(shifted.as_mut_slice(), shift);
+        self.fft(&shifted)
+    }
+
+    /// Coefficients of the polynomial with the given evaluations at the elements of the coset
+    /// shifted by `shift`, which must not be zero
+    pub fn ifft_coset(
+        &self,
+        evals: &CurveOrderElementVector,
+        shift: &CurveOrderElement,
+    ) -> Result<CurveOrderElementVector, ValueError> {
+        if shift.is_zero() {
+            return Err(ValueError::ZeroElement);
+        }
+        let mut coeffs = self.ifft(evals)?;
+        distribute_powers(coeffs.as_mut_slice(), &shift.inverse());
+        Ok(coeffs)
+    }
+
+    /// Low degree extension: the evaluations on this domain of a polynomial of degree less than
+    /// its size are re-evaluated on the coset shifted by `shift` of the domain `blowup_factor`
+    /// times larger. `blowup_factor` must be a power of 2 and `shift` is usually a non-residue so
+    /// the coset does not overlap the domain.
+    pub fn low_degree_extension(
+        &self,
+        evals: &CurveOrderElementVector,
+        blowup_factor: usize,
+        shift: &CurveOrderElement,
+    ) -> Result<CurveOrderElementVector, ValueError> {
+        if !blowup_factor.is_power_of_two() {
+            return Err(ValueError::NonPowerOf2(blowup_factor));
+        }
+        let size = self
+            .size
+            .checked_mul(blowup_factor)
+            .ok_or(ValueError::OutOfRange(blowup_factor))?;
+        let coeffs = self.ifft(evals)?;
+        Self::new(size)?.fft_coset(&coeffs, shift)
+    }
+
+    /// `fft` in steps of `chunk_size` butterflies, see `yielding`
+    pub fn fft_chunked(
+        &self,
+        coeffs: &CurveOrderElementVector,
+        chunk_size: usize,
+    ) -> Result<FftChunks, ValueError> {
+        FftChunks::new(self, coeffs.as_slice(), &self.omega, None, chunk_size)
+    }
+
+    /// `ifft` in steps of `chunk_size` butterflies, see `yielding`
+    pub fn ifft_chunked(
+        &self,
+        evals: &CurveOrderElementVector,
+        chunk_size: usize,
+    ) -> Result<FftChunks, ValueError> {
+        FftChunks::new(
+            self,
+            evals.as_slice(),
+            &self.omega_inv,
+            Some(&self.size_inv),
+            chunk_size,
+        )
+    }
+
+    /// Iterative Cooley-Tukey with `omega` as the root of unity. Output is scaled by `scale` if given.
+    fn transform<T: FftElement>(
+        &self,
+        input: &[T],
+        omega: &CurveOrderElement,
+        scale: Option<&CurveOrderElement>,
+    ) -> Result<Vec<T>, ValueError> {
+        if input.len() > self.size {
+            return Err(ValueError::IncorrectSize(input.len()));
+        }
+        let n = self.size;
+        let mut a = input.to_vec();
+        a.resize(n, T::zero());
+
+        for i in 0..n {
+            let j = bit_reverse(i, self.log_size);
+            if i < j {
+                a.swap(i, j);
+            }
+        }
+
+        // twiddles[k] = omega^k for k < n/2
+        let twiddles = CurveOrderElementVector::new_vandermonde_vector(omega, n / 2);
+        let mut m = 1;
+        while m < n {
+            let stride = n / (2 * m);
+            a.par_chunks_mut(2 * m).for_each(|chunk| {
+                let (lo, hi) = chunk.split_at_mut(m);
+                for j in 0..m {
+                    let t = hi[j].fft_mul(&twiddles[j * stride]);
+                    hi[j] = lo[j].fft_sub(&t);
+                    lo[j] = lo[j].fft_add(&t);
+                }
+            });
+            m *= 2;
+        }
+
+        if let Some(s) = scale {
+            a.par_iter_mut().for_each(|e| *e = e.fft_mul(s));
+        }
+        Ok(a)
+    }
+}
+
+/// The transform of `EvaluationDomain::transform` over scalars one slice of butterflies at a time
+pub struct FftChunks {
+    values: Vec<CurveOrderElement>,
+    twiddles: CurveOrderElementVector,
+    scale: Option<CurveOrderElement>,
+    chunk_size: usize,
+    /// Half the block size of the current layer
+    m: usize,
+    /// Butterflies done in the current layer
+    pos: usize,
+    done: usize,
+}
+
+impl FftChunks {
+    fn new(
+        domain: &EvaluationDomain,
+        input: &[CurveOrderElement],
+        omega: &CurveOrderElement,
+        scale: Option<&CurveOrderElement>,
+        chunk_size: usize,
+    ) -> Result<Self, ValueError> {
+        if input.len() > domain.size {
+            return Err(ValueError::IncorrectSize(input.len()));
+        }
+        check_chunk_size(chunk_size)?;
+        let n = domain.size;
+        let mut values = input.to_vec();
+        values.resize(n, CurveOrderElement::zero());
+        for i in 0..n {
+            let j = bit_reverse(i, domain.log_size);
+            if i < j {
+                values.swap(i, j);
+            }
+        }
+        Ok(Self {
+            values,
+            twiddles: CurveOrderElementVector::new_vandermonde_vector(omega, n / 2),
+            scale: scale.cloned(),
+            chunk_size,
+            m: 1,
+            pos: 0,
+            done: 0,
+        })
+    }
+
+    fn total(&self) -> usize {
+        (self.values.len() / 2) * self.values.len().trailing_zeros() as usize
+    }
+}
+
+impl Iterator for FftChunks {
+    type Item = (usize, usize);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let n = self.values.len();
+        if self.m >= n {
+            return None;
+        }
+        let (m, stride) = (self.m, n / (2 * self.m));
+        let twiddles = &self.twiddles;
+        let end = (n / 2).min(self.pos + self.chunk_size);
+        let mut b = self.pos;
+        while b < end {
+            let (block, j0) = (b / m, b % m);
+            if j0 == 0 && end - b >= m {
+                // Whole blocks
+                let blocks = (end - b) / m;
+                self.values[block * 2 * m..(block + blocks) * 2 * m]
+                    .par_chunks_mut(2 * m)
+                    .for_each(|chunk| {
+                        let (lo, hi) = chunk.split_at_mut(m);
+                        for j in 0..m {
+                            let t = &hi[j] * &twiddles[j * stride];
+                            hi[j] = &lo[j] - &t;
+                            lo[j] = &lo[j] + &t;
+                        }
+                    });
+                b += blocks * m;
+            } else {
+                // Part of a block
+                let j1 = m.min(j0 + end - b);
+                let (lo, hi) = self.values[block * 2 * m..(block + 1) * 2 * m].split_at_mut(m);
+                lo[j0..j1]
+                    .par_iter_mut()
+                    .zip(hi[j0..j1].par_iter_mut())
+                    .enumerate()
+                    .for_each(|(k, (l, h))| {
+                        let t = &*h * &twiddles[(j0 + k) * stride];
+                        *h = &*l - &t;
+                        *l = &*l + &t;
+                    });
+                b += j1 - j0;
+            }
+        }
+        self.done += end - self.pos;
+        if end == n / 2 {
+            self.m *= 2;
+            self.pos = 0;
+        } else {
+            self.pos = end;
+        }
+        Some((self.done, self.total()))
+    }
+}
+
+impl ChunkedWork for FftChunks {
+    type Output = CurveOrderElementVector;
+
+    fn finish(mut self) -> CurveOrderElementVector {
+        while self.next().is_some() {}
+        if let Some(s) = &self.scale {
+            self.values.par_iter_mut().for_each(|e| *e = &*e * s);
+        }
+        std::mem::take(&mut self.values).into()
+    }
+}
+
+/// Product of the `n x n` Toeplitz matrix `T` with the vector `points` where `n = points.len()`
+/// and `T[i][j] = coeffs[n - 1 + i - j]`, so `coeffs` lists the diagonals from the top right
+/// corner to the bottom left and has `2n - 1` elements. The matrix is embedded in a circulant
+/// matrix of power of 2 size at least `2n` which is then multiplied using the FFT in `O(n log n)`
+/// group operations. This is the step of the Feist–Khovratovich technique that computes all KZG
+/// opening proofs at once.
+pub fn toeplitz_mul(coeffs: &CurveOrderElementVector, points: &G1Vector) -> Result<G1Vector, ValueError> {
+    let n = points.len();
+    if n == 0 {
+        return Err(ValueError::IncorrectSize(0));
+    }
+    if coeffs.len() != 2 * n - 1 {
+        return Err(ValueError::UnequalSizeVectors(coeffs.len(), 2 * n - 1));
+    }
+    let domain = EvaluationDomain::new((2 * n).next_power_of_two())?;
+    let size = domain.size();
+
+    // First column of the circulant matrix, the diagonals below the main one wrap around
+    let mut col = vec![CurveOrderElement::zero(); size];
+    col[..n].clone_from_slice(&coeffs.as_slice()[n - 1..]);
+    for k in 1..n {
+        col[size - k] = coeffs[n - 1 - k].clone();
+    }
+
+    let col_evals = domain.fft(&col.into())?;
+    let point_evals = domain.fft_g1(points)?;
+    let prod: G1Vector = point_evals
+        .as_slice()
+        .par_iter()
+        .zip(col_evals.as_slice().par_iter())
+        .map(|(p, c)| p * c)
+        .collect();
+    let mut res = domain.ifft_g1(&prod)?;
+    res.split_off(n);
+    Ok(res)
+}
+
+/// Multiply the `i`-th element by `g^i`
+fn distribute_powers(elems: &mut [CurveOrderElement], g: &CurveOrderElement) {
+    let mut power = CurveOrderElement::one();
+    for e in elems.iter_mut() {
+        *e = &*e * &power;
+        power = &power * g;
+    }
+}
+
+fn bit_reverse(i: usize, bits: usize) -> usize {
+    if bits == 0 {
+        return i;
+    }
+    i.reverse_bits() >> (usize::BITS as usize - bits)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::univar_poly::UnivarPolynomial;
+
+    #[test]
+    fn test_root_of_unity() {
+        let root = TWO_ADIC_ROOT_OF_UNITY.clone();
+        let mut r = root.clone();
+        for _ in 0..*TWO_ADICITY - 1 {
+            r = r.square();
+        }
+        // Order is exactly 2^TWO_ADICITY
+        assert_eq!(r, CurveOrderElement::minus_one());
+        assert!(r.square().is_one());
+
+        assert!(EvaluationDomain::new(3).is_err());
+        assert!(EvaluationDomain::new(0).is_err());
+        if *TWO_ADICITY < 63 {
+            assert!(EvaluationDomain::new(1 << (*TWO_ADICITY + 1)).is_err());
+        }
+        // BN254 of AMCL only has subgroups of order up to 4
+        let size = 1 << (*TWO_ADICITY).min(3);
+        let domain = EvaluationDomain::new(size).unwrap();
+        assert!(domain.generator().pow(&CurveOrderElement::from(size as u64)).is_one());
+        assert!(!domain.generator().pow(&CurveOrderElement::from(size as u64 / 2)).is_one());
+    }
+
+    #[test]
+    fn test_fft() {
+        let max_log_size = (*TWO_ADICITY).min(4);
+        for log_size in 1..=max_log_size {
+            let domain = EvaluationDomain::new(1 << log_size).unwrap();
+            let poly = UnivarPolynomial::random(domain.size() - 1);
+            let evals = domain.fft(poly.coefficients()).unwrap();
+            for (x, e) in domain.elements().iter().zip(evals.iter()) {
+                assert_eq!(poly.eval(x), *e);
+            }
+            assert_eq!(domain.ifft(&evals).unwrap(), *poly.coefficients());
+        }
+
+        // Shorter inputs are padded, longer are rejected
+        let size = 1 << max_log_size;
+        let domain = EvaluationDomain::new(size).unwrap();
+        let poly = UnivarPolynomial::random(size / 2);
+        let evals = domain.fft(poly.coefficients()).unwrap();
+        assert_eq!(evals.len(), size);
+        assert_eq!(evals[3], poly.eval(&domain.elements()[3]));
+        assert!(domain.fft(&CurveOrderElementVector::random(size + 1)).is_err());
+    }
+
+    #[test]
+    fn test_fft_chunked() {
+        let domain = EvaluationDomain::new(16).unwrap();
+        let coeffs = CurveOrderElementVector::random(13);
+        let evals = domain.fft(&coeffs).unwrap();
+        // Steps spanning several blocks, parts of blocks and layers
+        for chunk_size in [1, 3, 5, 8, 100] {
+            let mut fft = domain.fft_chunked(&coeffs, chunk_size).unwrap();
+            let last = fft.by_ref().last().unwrap();
+            assert_eq!(last, (32, 32));
+            assert_eq!(fft.finish(), evals);
+            let ifft = domain.ifft_chunked(&evals, chunk_size).unwrap();
+            let mut back = ifft.finish();
+            back.split_off(13);
+            assert_eq!(back, coeffs);
+        }
+        assert!(domain.fft_chunked(&coeffs, 0).is_err());
+        assert!(domain
+            .fft_chunked(&CurveOrderElementVector::random(17), 4)
+            .is_err());
+    }
+
+    #[test]
+    fn test_fft_coset() {
+        let size = 1 << (*TWO_ADICITY).min(4);
+        let domain = EvaluationDomain::new(size).unwrap();
+        let shift = TWO_ADIC_ROOT_OF_UNITY.clone();
+        let poly = UnivarPolynomial::random(size - 1);
+        let evals = domain.fft_coset(poly.coefficients(), &shift).unwrap();
+        for (x, e) in domain.coset_elements(&shift).iter().zip(evals.iter()) {
+            assert_eq!(poly.eval(x), *e);
+        }
+        assert_eq!(domain.ifft_coset(&evals, &shift).unwrap(), *poly.coefficients());
+        assert_eq!(
+            domain.fft_coset(poly.coefficients(), &CurveOrderElement::one()).unwrap(),
+            domain.fft(poly.coefficients()).unwrap()
+        );
+        assert!(domain.ifft_coset(&evals, &CurveOrderElement::zero()).is_err());
+        assert!(domain
+            .fft_coset(&CurveOrderElementVector::random(size + 1), &shift)
+            .is_err());
+    }
+
+    #[test]
+    fn test_low_degree_extension() {
+        let log_size = (*TWO_ADICITY).min(4) - 1;
+        let domain = EvaluationDomain::new(1 << log_size).unwrap();
+        let poly = UnivarPolynomial::random(domain.size() - 1);
+        let evals = domain.fft(poly.coefficients()).unwrap();
+        let shift = CurveOrderElement::from(7u64);
+        let extended = domain.low_degree_extension(&evals, 2, &shift).unwrap();
+        assert_eq!(extended.len(), 2 * domain.size());
+        let larger = EvaluationDomain::new(2 * domain.size()).unwrap();
+        for (x, e) in larger.coset_elements(&shift).iter().zip(extended.iter()) {
+            assert_eq!(poly.eval(x), *e);
+        }
+
+        // Without a shift the original evaluations are every other one
+        let extended = domain
+            .low_degree_extension(&evals, 2, &CurveOrderElement::one())
+            .unwrap();
+        for i in 0..domain.size() {
+            assert_eq!(extended[2 * i], evals[i]);
+        }
+        assert!(domain.low_degree_extension(&evals, 3, &shift).is_err());
+    }
+
+    #[test]
+    fn test_fft_g1() {
+        let size = 1 << (*TWO_ADICITY).min(4);
+        let domain = EvaluationDomain::new(size).unwrap();
+        let coeffs = CurveOrderElementVector::random(size);
+        let g = G1::generator();
+        let points: G1Vector = coeffs.iter().map(|c| &g * c).collect();
+
+        let evals = domain.fft(&coeffs).unwrap();
+        let point_evals = domain.fft_g1(&points).unwrap();
+        for (e, p) in evals.iter().zip(point_evals.iter()) {
+            assert_eq!(&g * e, *p);
+        }
+        assert_eq!(domain.ifft_g1(&point_evals).unwrap(), points);
+        assert!(domain.fft_g1(&G1Vector::random(size + 1)).is_err());
+    }
+
+    #[test]
+    fn test_toeplitz_mul() {
+        let max = 1 << ((*TWO_ADICITY).min(4) - 1);
+        for n in 1..=max {
+            let coeffs = CurveOrderElementVector::random(2 * n - 1);
+            let points = G1Vector::random(n);
+            let res = toeplitz_mul(&coeffs, &points).unwrap();
+            assert_eq!(res.len(), n);
+            for i in 0..n {
+                let mut expected = G1::identity();
+                for j in 0..n {
+                    expected += &points[j] * &coeffs[n - 1 + i - j];
+                }
+                assert_eq!(res[i], expected);
+            }
+        }
+
+        assert!(toeplitz_mul(&CurveOrderElementVector::random(4), &G1Vector::random(2)).is_err());
+        assert!(toeplitz_mul(&CurveOrderElementVector::random(1), &G1Vector::new(0)).is_err());
+    }
+}
@@ -0,0 +1,165 @@
+//! Verification of Groth16 proofs. A proof `(A, B, C)` for public inputs `x_1, ..., x_l` is valid
+//! when `e(A, B) = e(alpha, beta) * e(IC, gamma) * e(C, delta)` with
+//! `IC = ic_0 + x_1*ic_1 + ... + x_l*ic_l`. Only verification is supported, proofs and keys come
+//! from whichever framework generated them.
+
+use crate::curve_order_elem::CurveOrderElement;
+use crate::extension_field_gt::GT;
+use crate::group_elem::{GroupElement, GroupElementVector};
+use crate::group_elem_g1::{G1Vector, G1};
+use crate::group_elem_g2::G2;
+use crate::pairing_check::PairingCheck;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct VerifyingKey {
+    pub alpha_g1: G1,
+    pub beta_g2: G2,
+    pub gamma_g2: G2,
+    pub delta_g2: G2,
+    /// `ic_0, ..., ic_l`, one more than the number of public inputs
+    pub ic: G1Vector,
+}
+
+/// Verifying key with `e(alpha, beta)` computed and `gamma` and `delta` negated so a proof is
+/// checked with a single multi-pairing
+#[derive(Clone, Debug, PartialEq)]
+pub struct PreparedVerifyingKey {
+    alpha_beta: GT,
+    neg_gamma_g2: G2,
+    neg_delta_g2: G2,
+    ic: G1Vector,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Proof {
+    pub a: G1,
+    pub b: G2,
+    pub c: G1,
+}
+
+impl VerifyingKey {
+    pub fn prepare(&self) -> PreparedVerifyingKey {
+        PreparedVerifyingKey {
+            alpha_beta: GT::ate_pairing(&self.alpha_g1, &self.beta_g2),
+            neg_gamma_g2: self.gamma_g2.negation(),
+            neg_delta_g2: self.delta_g2.negation(),
+            ic: self.ic.clone(),
+        }
+    }
+}
+
+impl From<&VerifyingKey> for PreparedVerifyingKey {
+    fn from(vk: &VerifyingKey) -> Self {
+        vk.prepare()
+    }
+}
+
+impl PreparedVerifyingKey {
+    /// Number of public inputs a proof is verified with
+    pub fn num_public_inputs(&self) -> usize {
+        self.ic.len().saturating_sub(1)
+    }
+}
+
+/// Check `e(A, B) * e(IC, -gamma) * e(C, -delta) = e(alpha, beta)`. Returns false when the number
+/// of public inputs does not match the key.
+pub fn verify(
+    pvk: &PreparedVerifyingKey,
+    proof: &Proof,
+    public_inputs: &[CurveOrderElement],
+) -> bool {
+    if pvk.ic.len() != public_inputs.len() + 1 {
+        return false;
+    }
+    PairingCheck::new()
+        .add(&proof.a, &proof.b)
+        .add(&pvk.ic[0], &pvk.neg_gamma_g2)
+        .also_msm(&pvk.ic.as_slice()[1..], public_inputs)
+        .add(&proof.c, &pvk.neg_delta_g2)
+        .verify_equals(&pvk.alpha_beta)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::curve_order_elem::CurveOrderElementVector;
+
+    /// Key with known trapdoor, `ic_i = k_i*g1`, and a proof for `inputs` simulated with it
+    fn simulate(inputs: &[CurveOrderElement]) -> (VerifyingKey, Proof) {
+        let g1 = G1::generator();
+        let g2 = G2::generator();
+        let alpha = CurveOrderElement::random();
+        let beta = CurveOrderElement::random();
+        let gamma = CurveOrderElement::random();
+        let delta = CurveOrderElement::random();
+        let k = CurveOrderElementVector::random(inputs.len() + 1);
+        let vk = VerifyingKey {
+            alpha_g1: &g1 * &alpha,
+            beta_g2: &g2 * &beta,
+            gamma_g2: &g2 * &gamma,
+            delta_g2: &g2 * &delta,
+            ic: k.iter().map(|k_i| &g1 * k_i).collect(),
+        };
+
+        // a*b = alpha*beta + ic*gamma + c*delta
+        let ic = inputs
+            .iter()
+            .zip(k.iter().skip(1))
+            .fold(k[0].clone(), |acc, (x, k_i)| acc + x * k_i);
+        let a = CurveOrderElement::random();
+        let b = CurveOrderElement::random();
+        let c = (&a * &b - &alpha * &beta - &ic * &gamma) * delta.inverse();
+        let proof = Proof {
+            a: &g1 * &a,
+            b: &g2 * &b,
+            c: &g1 * &c,
+        };
+        (vk, proof)
+    }
+
+    #[test]
+    fn test_verify() {
+        for num_inputs in 0..4 {
+            let inputs: Vec<_> = (0..num_inputs)
+                .map(|_| CurveOrderElement::random())
+                .collect();
+            let (vk, proof) = simulate(&inputs);
+            let pvk = PreparedVerifyingKey::from(&vk);
+            assert_eq!(pvk.num_public_inputs(), num_inputs);
+            assert!(verify(&pvk, &proof, &inputs));
+
+            let mut bad = proof.clone();
+            bad.c = G1::random();
+            assert!(!verify(&pvk, &bad, &inputs));
+            let mut bad = proof.clone();
+            bad.b = G2::random();
+            assert!(!verify(&pvk, &bad, &inputs));
+
+            // Wrong number of public inputs
+            let mut more = inputs.clone();
+            more.push(CurveOrderElement::random());
+            assert!(!verify(&pvk, &proof, &more));
+            if num_inputs > 0 {
+                let mut other = inputs.clone();
+                other[0] = CurveOrderElement::random();
+                assert!(!verify(&pvk, &proof, &other));
+                assert!(!verify(&pvk, &proof, &inputs[1..]));
+            }
+        }
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serialization() {
+        let (vk, proof) = simulate(&[CurveOrderElement::random()]);
+        let s = serde_json::to_string(&vk).unwrap();
+        assert_eq!(serde_json::from_str::<VerifyingKey>(&s).unwrap(), vk);
+        let s = serde_json::to_string(&proof).unwrap();
+        assert_eq!(serde_json::from_str::<Proof>(&s).unwrap(), proof);
+    }
+}
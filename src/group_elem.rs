@@ -2,8 +2,13 @@ use rand::{CryptoRng, RngCore};
 
 use crate::errors::{SerzDeserzError, ValueError};
 use crate::curve_order_elem::CurveOrderElement;
+use std::any::Any;
+use std::fmt;
 use std::slice::Iter;
 
+/// Number of elements written or read at a time by the streaming serialization of vectors
+pub const STREAM_CHUNK_SIZE: usize = 1 << 12;
+
 #[macro_export]
 macro_rules! add_group_elems {
     ( $( $elem:expr ),* ) => {
@@ -18,6 +23,12 @@ macro_rules! add_group_elems {
 }
 
 pub trait GroupElement: Clone + Sized {
+    /// Size of the byte representation of `to_vec` and `from_slice`
+    const SERIALIZED_SIZE: usize;
+
+    /// Size of the byte representation of `to_compressed_bytes`
+    const COMPRESSED_SIZE: usize;
+
     fn new() -> Self;
 
     /// Return the identity element
@@ -38,6 +49,11 @@ pub trait GroupElement: Clone + Sized {
         Self::generator().scalar_mul_const_time(&n)
     }
 
+    /// Return a random group element with entropy read directly from the operating system
+    fn random_from_os() -> Self {
+        Self::random_using_rng(&mut crate::utils::OsEntropyRng)
+    }
+
     /// Check if the the point is the identity element of the group
     fn is_identity(&self) -> bool;
 
@@ -102,9 +118,94 @@ pub trait GroupElement: Clone + Sized {
     /// Question: But since we always know the multiplicand (group order) is there a faster way?
     fn has_correct_order(&self) -> bool;
 
+    /// Multi-scalar multiplication `points[0]*scalars[0] + points[1]*scalars[1] + ...` with the
+    /// optimized multi-scalar multiplication of the group's vector type. Constant time.
+    fn msm(points: &[Self], scalars: &[CurveOrderElement]) -> Result<Self, ValueError>;
+
+    /// Variable time `msm`, only for public scalars
+    fn msm_var_time(points: &[Self], scalars: &[CurveOrderElement]) -> Result<Self, ValueError>;
+
     // TODO: Implement has_correct_order for variable time as well. Need to implement variable time scalar multiplication for group G2.
 }
 
+/// Object safe subset of `GroupElement` so elements of different groups can be held together as
+/// `Box<dyn DynGroupElement>`, e.g. in registries of protocols over different groups. Combining
+/// elements of different groups fails with `None` or `false`. Implemented for every group element.
+pub trait DynGroupElement: fmt::Debug + Send + Sync {
+    /// Byte representation as returned by `GroupElement::to_vec`
+    fn dyn_to_vec(&self) -> Vec<u8>;
+
+    fn dyn_is_identity(&self) -> bool;
+
+    /// `self + other`, `None` when `other` is an element of another group
+    fn dyn_plus(&self, other: &dyn DynGroupElement) -> Option<Box<dyn DynGroupElement>>;
+
+    fn dyn_negation(&self) -> Box<dyn DynGroupElement>;
+
+    /// Constant time scalar multiplication
+    fn dyn_scalar_mul(&self, a: &CurveOrderElement) -> Box<dyn DynGroupElement>;
+
+    /// Whether `other` is the same element of the same group
+    fn dyn_eq(&self, other: &dyn DynGroupElement) -> bool;
+
+    fn dyn_clone(&self) -> Box<dyn DynGroupElement>;
+
+    /// For downcasting to the concrete group element
+    fn as_any(&self) -> &dyn Any;
+}
+
+impl<T> DynGroupElement for T
+where
+    T: GroupElement + PartialEq + fmt::Debug + Send + Sync + 'static,
+{
+    fn dyn_to_vec(&self) -> Vec<u8> {
+        self.to_vec()
+    }
+
+    fn dyn_is_identity(&self) -> bool {
+        self.is_identity()
+    }
+
+    fn dyn_plus(&self, other: &dyn DynGroupElement) -> Option<Box<dyn DynGroupElement>> {
+        other
+            .as_any()
+            .downcast_ref::<T>()
+            .map(|o| Box::new(self.plus(o)) as Box<dyn DynGroupElement>)
+    }
+
+    fn dyn_negation(&self) -> Box<dyn DynGroupElement> {
+        Box::new(self.negation())
+    }
+
+    fn dyn_scalar_mul(&self, a: &CurveOrderElement) -> Box<dyn DynGroupElement> {
+        Box::new(self.scalar_mul_const_time(a))
+    }
+
+    fn dyn_eq(&self, other: &dyn DynGroupElement) -> bool {
+        other.as_any().downcast_ref::<T>() == Some(self)
+    }
+
+    fn dyn_clone(&self) -> Box<dyn DynGroupElement> {
+        Box::new(self.clone())
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+impl Clone for Box<dyn DynGroupElement> {
+    fn clone(&self) -> Self {
+        self.dyn_clone()
+    }
+}
+
+impl PartialEq for dyn DynGroupElement {
+    fn eq(&self, other: &Self) -> bool {
+        self.dyn_eq(other)
+    }
+}
+
 #[macro_export]
 macro_rules! impl_group_elem_conversions {
     ( $group_element:ident, $group:ident, $group_size:ident ) => {
@@ -123,7 +224,7 @@ macro_rules! impl_group_elem_conversions {
         impl From<&[u8; $group_size]> for $group_element {
             fn from(x: &[u8; $group_size]) -> Self {
                 Self {
-                    value: $group::frombytes(x),
+                    value: <$group as $crate::backend::Group>::from_bytes(x),
                 }
             }
         }
@@ -153,9 +254,26 @@ macro_rules! impl_group_elem_traits {
             }
         }
 
+        #[allow(unused_mut)]
+        impl fmt::Debug for $group_element {
+            /// `Name(<fmt_short>)`, the full value with `{:#?}`
+            fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                if f.alternate() {
+                    let mut c = self.value.clone();
+                    write!(f, "{}({})", stringify!($group_element), c.tostring())
+                } else {
+                    write!(f, "{}({})", stringify!($group_element), self.fmt_short())
+                }
+            }
+        }
+
         #[allow(unused_mut)]
         impl fmt::Display for $group_element {
+            /// The full value, `fmt_short` with `{:#}`
             fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                if f.alternate() {
+                    return write!(f, "{}", self.fmt_short());
+                }
                 let mut c = self.value.clone();
                 write!(f, "{}", c.tostring())
             }
@@ -178,6 +296,7 @@ macro_rules! impl_group_elem_traits {
             }
         }
 
+        #[cfg(feature = "serde")]
         impl Serialize for $group_element {
             fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
             where
@@ -187,6 +306,7 @@ macro_rules! impl_group_elem_traits {
             }
         }
 
+        #[cfg(feature = "serde")]
         impl<'a> Deserialize<'a> for $group_element {
             fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
             where
@@ -228,6 +348,25 @@ macro_rules! impl_group_elem_ops {
 
         impl Eq for $group_element {}
 
+        /// Canonical order, the identity comes first and other elements are ordered by their
+        /// uncompressed byte encoding, which does not depend on the internal representation.
+        impl Ord for $group_element {
+            fn cmp(&self, other: &Self) -> ::std::cmp::Ordering {
+                match (self.is_identity(), other.is_identity()) {
+                    (true, true) => ::std::cmp::Ordering::Equal,
+                    (true, false) => ::std::cmp::Ordering::Less,
+                    (false, true) => ::std::cmp::Ordering::Greater,
+                    (false, false) => self.to_vec().cmp(&other.to_vec()),
+                }
+            }
+        }
+
+        impl PartialOrd for $group_element {
+            fn partial_cmp(&self, other: &Self) -> Option<::std::cmp::Ordering> {
+                Some(self.cmp(other))
+            }
+        }
+
         impl Add for $group_element {
             type Output = Self;
 
@@ -404,36 +543,51 @@ macro_rules! impl_scalar_mul_ops {
 
 macro_rules! impl_group_element_lookup_table {
     ( $group_element:ident, $name:ident  ) => {
-        pub struct $name([$group_element; 8]);
+        /// Odd multiples `A, 3A, 5A, .., (2^(WNAF_WIDTH-1) - 1)A` for multiplication with wNAF
+        /// recoded scalars of width `WNAF_WIDTH`
+        pub struct $name(Vec<$group_element>);
 
         impl $name {
-            /// Given public A and odd x with 0 < x < 2^4, return x.A.
+            /// Given public A and odd x with 0 < x < 2^(WNAF_WIDTH-1), return x.A.
             pub fn select(&self, x: usize) -> &$group_element {
                 debug_assert_eq!(x & 1, 1);
-                debug_assert!(x < 16);
+                debug_assert!(x < 2 * WNAF_LOOKUP_TABLE_SIZE);
 
                 &self.0[x / 2]
             }
         }
 
+        impl fmt::Debug for $name {
+            /// Digests of the first multiples
+            fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                fmt_digests(f, stringify!($name), self.0.len(), self.0.iter().map(|e| e.to_vec()))
+            }
+        }
+
+        impl fmt::Display for $name {
+            /// The multiples `A, 3A, 5A, ..`
+            fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                writeln!(f, "{} [", stringify!($name))?;
+                for (i, e) in self.0.iter().enumerate() {
+                    if f.alternate() {
+                        writeln!(f, "  {}A: {:#}", 2 * i + 1, e)?;
+                    } else {
+                        writeln!(f, "  {}A: {}", 2 * i + 1, e)?;
+                    }
+                }
+                write!(f, "]")
+            }
+        }
+
         impl<'a> From<&'a $group_element> for $name {
             fn from(a: &'a $group_element) -> Self {
-            let mut a_i: [$group_element; 8] = [
-                    $group_element::new(),
-                    $group_element::new(),
-                    $group_element::new(),
-                    $group_element::new(),
-                    $group_element::new(),
-                    $group_element::new(),
-                    $group_element::new(),
-                    $group_element::new(),
-                ];
                 let a_2 = a.double();
-                a_i[0] = a.clone();
-                for i in 0..7 {
-                    a_i[i + 1] = &a_i[i] + &a_2;
+                let mut a_i = Vec::with_capacity(WNAF_LOOKUP_TABLE_SIZE);
+                a_i.push(a.clone());
+                for i in 0..WNAF_LOOKUP_TABLE_SIZE - 1 {
+                    let next = &a_i[i] + &a_2;
+                    a_i.push(next);
                 }
-                // Now Ai = [A, 3A, 5A, 7A, 9A, 11A, 13A, 15A]
                 Self(a_i)
             }
         }
@@ -448,13 +602,47 @@ macro_rules! impl_optmz_scalar_mul_ops {
                 self.value.clone()
             }
 
+            /// If c is false, cmove returns self, otherwise it returns b. Does not branch on `c`.
+            pub fn cmove(&self, b: &Self, c: bool) -> Self {
+                let mut res = self.value.clone();
+                res.cmove(&b.value, c as isize);
+                res.into()
+            }
+
+            /// Blind with `r` as `r*self`, returning the blinded element and `r` to keep for
+            /// `unblind`. Constant time in `r`. Fails if `r` is zero.
+            pub fn blind(
+                &self,
+                r: &CurveOrderElement,
+            ) -> Result<(Self, CurveOrderElement), ValueError> {
+                if r.is_zero() {
+                    return Err(ValueError::ZeroElement);
+                }
+                Ok((self.scalar_mul_const_time(r), r.clone()))
+            }
+
+            /// Remove the blind `r` as `r^-1*self`. Constant time in `r`. Fails if `r` is zero.
+            pub fn unblind(&self, r: &CurveOrderElement) -> Result<Self, ValueError> {
+                if r.is_zero() {
+                    return Err(ValueError::ZeroElement);
+                }
+                Ok(self.scalar_mul_const_time(&r.inverse_const_time()))
+            }
+
             /// Multiply point on the curve (element of group G1) with a scalar. Variable time operation
             /// Uses wNAF.
             pub fn scalar_mul_variable_time(&self, a: &CurveOrderElement) -> Self {
+                let mut result = $group_element::identity();
+                self.scalar_mul_variable_time_into(a, &mut result);
+                result
+            }
+
+            /// `scalar_mul_variable_time` writing the product to `out`
+            pub fn scalar_mul_variable_time_into(&self, a: &CurveOrderElement, out: &mut Self) {
                 // TODO: Optimization: Attach the lookup table to the struct
                 let table = $lookup_table::from(self);
-                let wnaf = a.to_wnaf(5);
-                $group_element::wnaf_mul(&table, &wnaf)
+                let (wnaf, len) = a.to_wnaf_locked(WNAF_WIDTH);
+                $group_element::wnaf_mul_into(&table, &wnaf[..len], out)
             }
 
             /// Return multiples of itself. eg. Given `n`=5, returns self, 2*self, 3*self, 4*self, 5*self
@@ -468,26 +656,92 @@ macro_rules! impl_optmz_scalar_mul_ops {
             }
 
             pub fn to_wnaf_lookup_table(&self, width: usize) -> $lookup_table {
-                // Only supporting table of width 5 for now
-                debug_assert_eq!(width, 5);
+                // Tables only hold the multiples for `WNAF_WIDTH`
+                debug_assert_eq!(width, WNAF_WIDTH);
                 $lookup_table::from(self)
             }
 
+            /// Table `[0, self, 2*self, ... 2^(width-1)*self]` for `booth_mul` with Booth digits of
+            /// window `width`
+            pub fn to_booth_lookup_table(&self, width: usize) -> Vec<$group_element> {
+                let mut table = vec![$group_element::identity(), self.clone()];
+                for i in 2..=(1 << (width - 1)) {
+                    let t = &table[i - 1] + self;
+                    table.push(t);
+                }
+                table
+            }
+
+            /// Constant time multiplication with a scalar recoded by `CurveOrderElement::to_booth`
+            /// using a table from `to_booth_lookup_table` of the same width. Every table entry is
+            /// read for each digit and the selected one conditionally negated with `cmove`, so the
+            /// memory access pattern does not depend on the digits.
+            pub fn booth_mul(table: &[$group_element], booth: &[i8]) -> Self {
+                let mut result = $group_element::identity();
+                $group_element::booth_mul_into(table, booth, &mut result);
+                result
+            }
+
+            /// `booth_mul` writing the product to `out`, so no intermediate elements are created
+            /// and dropped
+            pub fn booth_mul_into(table: &[$group_element], booth: &[i8], out: &mut Self) {
+                debug_assert!((table.len() - 1).is_power_of_two());
+                let width = (table.len() - 1).trailing_zeros() as usize + 1;
+                let result = &mut out.value;
+                result.inf();
+
+                for d in booth.iter().rev() {
+                    for _ in 0..width {
+                        result.dbl();
+                    }
+
+                    let d = *d as i32;
+                    let sign = (d >> 31) & 1;
+                    let abs = (d ^ -sign) + sign;
+                    let mut t = $group_element::identity().value;
+                    for (j, p) in table.iter().enumerate() {
+                        // 1 if j == abs, both are less than 2^31
+                        let eq = (((j as i32 ^ abs) - 1) >> 31) & 1;
+                        t.cmove(&p.value, eq as isize);
+                    }
+                    let mut neg_t = t.clone();
+                    neg_t.neg();
+                    t.cmove(&neg_t, sign as isize);
+                    result.add(&t);
+                }
+            }
+
+            /// Constant time scalar multiplication using Booth recoding with window `width`.
+            /// Prefer building the table once with `to_booth_lookup_table` when multiplying the same
+            /// element many times.
+            pub fn scalar_mul_booth(&self, a: &CurveOrderElement, width: usize) -> Self {
+                let table = self.to_booth_lookup_table(width);
+                $group_element::booth_mul(&table, &a.to_booth_locked(width))
+            }
+
             pub fn wnaf_mul(table: &$lookup_table, wnaf: &[i8]) -> Self {
                 let mut result = $group_element::identity();
+                $group_element::wnaf_mul_into(table, wnaf, &mut result);
+                result
+            }
+
+            /// `wnaf_mul` writing the product to `out`. Doubles and adds in place on the point of
+            /// `out` so no intermediate elements are created and dropped.
+            pub fn wnaf_mul_into(table: &$lookup_table, wnaf: &[i8], out: &mut Self) {
+                crate::ct::debug_assert_variable_time("wnaf_mul");
+                let result = &mut out.value;
+                result.inf();
 
                 for n in wnaf.iter().rev() {
-                    result = result.double();
+                    result.dbl();
 
                     let v = *n;
                     if v > 0 {
-                        result = result + table.select(v as usize);
+                        result.add(&table.select(v as usize).value);
                     } else if v < 0 {
-                        result = result - table.select(-v as usize);
+                        result.sub(&table.select(-v as usize).value);
                     }
                 }
-
-                result
             }
         }
     };
@@ -693,6 +947,32 @@ macro_rules! impl_group_elem_vec_product_ops {
                 (Self::from(l), Self::from(r))
             }
 
+            /// Borrow a range of the vector. Unlike `split_at`, nothing is cloned.
+            pub fn slice<R>(&self, range: R) -> &[$group_element]
+            where
+                R: ::std::slice::SliceIndex<[$group_element], Output = [$group_element]>,
+            {
+                &self.elems[range]
+            }
+
+            /// Split into `[0, mid)`, which is kept in `self`, and `[mid, len)`, which is returned.
+            /// Elements are moved, not cloned.
+            pub fn split_off(&mut self, mid: usize) -> Self {
+                Self {
+                    elems: self.elems.split_off(mid),
+                }
+            }
+
+            /// Iterate over non-overlapping slices of `chunk_size` elements, the last may be shorter
+            pub fn chunks(&self, chunk_size: usize) -> ::std::slice::Chunks<'_, $group_element> {
+                self.elems.chunks(chunk_size)
+            }
+
+            /// Parallel version of `chunks`
+            pub fn par_chunks(&self, chunk_size: usize) -> rayon::slice::Chunks<'_, $group_element> {
+                self.elems.par_chunks(chunk_size)
+            }
+
             /// Constant time multi-scalar multiplication. Naive approach computing `n` scalar
             /// multiplications and n-1 additions for `n` field elements
             pub fn multi_scalar_mul_const_time_naive(
@@ -723,19 +1003,114 @@ macro_rules! impl_group_elem_vec_product_ops {
                 Self::multi_scalar_mul_var_time_without_precomputation(self.as_slice(), field_elems)
             }
 
-            /// Strauss multi-scalar multiplication
+            /// Variable time multi-scalar multiplication with Strauss' method, or Pippenger's method
+            /// with the window of `pippenger_window` from `PIPPENGER_THRESHOLD` terms on. Terms with
+            /// a zero scalar are skipped and terms with a scalar of one are added directly.
             pub fn multi_scalar_mul_var_time_without_precomputation<'g, 'f>(
                 group_elems: impl IntoIterator<Item = &'g $group_element>,
                 field_elems: impl IntoIterator<Item = &'f CurveOrderElement>,
             ) -> Result<$group_element, ValueError> {
-                let lookup_tables: Vec<_> = group_elems
-                    .into_iter()
-                    .map(|e| $lookup_table::from(e))
+                let group_elems: Vec<_> = group_elems.into_iter().collect();
+                let field_elems: Vec<_> = field_elems.into_iter().collect();
+                check_vector_size_for_equality!(group_elems, field_elems)?;
+
+                let mut unit_sum = $group_element::identity();
+                let mut points = vec![];
+                let mut scalars = vec![];
+                for (g, f) in group_elems.into_iter().zip(field_elems.into_iter()) {
+                    if f.is_zero() {
+                        continue;
+                    }
+                    if f.is_one() {
+                        unit_sum += g;
+                    } else {
+                        points.push(g);
+                        scalars.push(f);
+                    }
+                }
+
+                let r = if points.len() >= PIPPENGER_THRESHOLD {
+                    let window = pippenger_window(points.len());
+                    Self::multi_scalar_mul_var_time_pippenger(points, scalars, window)?
+                } else {
+                    let lookup_tables: Vec<_> =
+                        points.into_iter().map($lookup_table::from).collect();
+                    Self::multi_scalar_mul_var_time_with_precomputation_done(
+                        &lookup_tables,
+                        scalars,
+                    )?
+                };
+                Ok(r + unit_sum)
+            }
+
+            /// Variable time multi-scalar multiplication with Pippenger's bucket method: for each
+            /// window of `window` bits of the scalars, the points are added to the bucket of their
+            /// digit and the buckets are summed with their digit as weight. The windows are
+            /// processed in parallel. `window` is between 1 and `MAX_PIPPENGER_WINDOW`.
+            pub fn multi_scalar_mul_var_time_pippenger<'g, 'f>(
+                group_elems: impl IntoIterator<Item = &'g $group_element>,
+                field_elems: impl IntoIterator<Item = &'f CurveOrderElement>,
+                window: usize,
+            ) -> Result<$group_element, ValueError> {
+                if window == 0 || window > MAX_PIPPENGER_WINDOW {
+                    return Err(ValueError::OutOfRange(window));
+                }
+                let group_elems: Vec<_> = group_elems.into_iter().collect();
+                let scalars: Vec<_> = field_elems.into_iter().map(|f| f.to_bignum()).collect();
+                check_vector_size_for_equality!(group_elems, scalars)?;
+
+                let bits = CURVE_ORDER.nbits();
+                let digit = |k: &BigNum, w: usize| {
+                    (w * window..bits.min((w + 1) * window))
+                        .fold(0, |d, i| d | ((k.bit(i) as usize) << (i - w * window)))
+                };
+                let window_sums: Vec<_> = (0..bits.div_ceil(window))
+                    .into_par_iter()
+                    .map(|w| {
+                        let mut buckets = vec![$group_element::identity(); (1 << window) - 1];
+                        for (g, k) in group_elems.iter().zip(scalars.iter()) {
+                            let d = digit(k, w);
+                            if d != 0 {
+                                buckets[d - 1] += *g;
+                            }
+                        }
+                        // sum d * bucket_d as bucket_max + (bucket_max + bucket_max-1) + ...
+                        let mut running = $group_element::identity();
+                        let mut sum = $group_element::identity();
+                        for b in buckets.iter().rev() {
+                            running += b;
+                            sum += &running;
+                        }
+                        sum
+                    })
                     .collect();
 
-                Self::multi_scalar_mul_var_time_with_precomputation_done(
-                    &lookup_tables,
-                    field_elems,
+                let mut r = $group_element::identity();
+                for s in window_sums.iter().rev() {
+                    for _ in 0..window {
+                        r.double_mut();
+                    }
+                    r += s;
+                }
+                Ok(r)
+            }
+
+            /// Variable time multi-scalar multiplication with scalars given in sparse form as
+            /// `(index, scalar)` pairs, elements of this vector at other indices have a zero scalar.
+            pub fn multi_scalar_mul_var_time_sparse(
+                &self,
+                terms: &[(usize, CurveOrderElement)],
+            ) -> Result<$group_element, ValueError> {
+                let mut group_elems = Vec::with_capacity(terms.len());
+                for (i, _) in terms {
+                    if *i >= self.len() {
+                        return Err(ValueError::OutOfRange(*i));
+                    }
+                    group_elems.push(&self[*i]);
+                }
+                Self::multi_scalar_mul_var_time_without_precomputation(
+                    group_elems,
+                    terms.iter().map(|(_, f)| f),
                 )
             }
 
@@ -760,12 +1135,15 @@ macro_rules! impl_group_elem_vec_product_ops {
                 lookup_tables: &[$lookup_table],
                 field_elems: impl IntoIterator<Item = &'f CurveOrderElement>,
             ) -> Result<$group_element, ValueError> {
-                let mut nafs: Vec<_> = field_elems.into_iter().map(|e| e.to_wnaf(5)).collect();
+                let (nafs, lens): (Vec<_>, Vec<_>) = field_elems
+                    .into_iter()
+                    .map(|e| e.to_wnaf_locked(WNAF_WIDTH))
+                    .unzip();
 
                 check_vector_size_for_equality!(nafs, lookup_tables)?;
 
-                // Pad the NAFs with 0 so that all nafs are of same length
-                let new_length = pad_collection!(nafs, 0);
+                // The NAFs are padded with 0 to the same length
+                let new_length = lens.into_iter().max().unwrap_or(0);
 
                 let mut r = $group_element::identity();
 
@@ -793,10 +1171,9 @@ macro_rules! impl_group_elem_vec_product_ops {
                 field_elems: impl IntoIterator<Item = &'f CurveOrderElement>,
             ) -> Result<$group_element, ValueError> {
 
-                // Choosing window of size 3.
                 let group_elem_multiples: Vec<_> = group_elems
                     .into_iter()
-                    .map(|e| e.get_multiples(7)) // 2^3 - 1
+                    .map(|e| e.get_multiples((1 << MSM_CONST_TIME_WINDOW) - 1))
                     .collect();
 
                 Self::multi_scalar_mul_const_time_with_precomputation_done(
@@ -805,6 +1182,8 @@ macro_rules! impl_group_elem_vec_product_ops {
                 )
             }
 
+            /// `group_elem_multiples` are the `2^MSM_CONST_TIME_WINDOW - 1` first multiples of each
+            /// group element from `get_multiples`
             pub fn multi_scalar_mul_const_time_with_precomputation_done<'f>(
                 group_elem_multiples: &[Vec<$group_element>],
                 field_elems: impl IntoIterator<Item = &'f CurveOrderElement>,
@@ -813,7 +1192,7 @@ macro_rules! impl_group_elem_vec_product_ops {
 
                 let mut field_elems_base_repr: Vec<_> = field_elems
                     .into_iter()
-                    .map(|e| e.to_power_of_2_base(3))
+                    .map(|e| e.to_power_of_2_base(MSM_CONST_TIME_WINDOW))
                     .collect();
 
                 check_vector_size_for_equality!(group_elem_multiples, field_elems_base_repr)?;
@@ -823,10 +1202,10 @@ macro_rules! impl_group_elem_vec_product_ops {
 
                 let mut r = $group_element::new();
                 for i in (0..new_length).rev() {
-                    // r = r * 2^3
-                    r.double_mut();
-                    r.double_mut();
-                    r.double_mut();
+                    // r = r * 2^w
+                    for _ in 0..MSM_CONST_TIME_WINDOW {
+                        r.double_mut();
+                    }
                     for (b, m) in field_elems_base_repr
                         .iter()
                         .zip(group_elem_multiples.iter())
@@ -840,13 +1219,90 @@ macro_rules! impl_group_elem_vec_product_ops {
                 Ok(r)
             }
 
+            /// Coefficients `t_i = H(pk_i, {pk_1, .. pk_n})` of `aggregate_keys_with_coefficients`
+            /// for the public keys in this vector. The set of keys is hashed with sorted encodings
+            /// so the coefficients do not depend on the order of the keys.
+            pub fn key_aggregation_coefficients(&self) -> CurveOrderElementVector {
+                let mut encodings: Vec<_> = self.elems.iter().map(|e| e.to_vec()).collect();
+                encodings.sort();
+                let set_digest = <sha2::Sha256 as sha2::Digest>::digest(&encodings.concat());
+                self.elems
+                    .par_iter()
+                    .map(|e| {
+                        CurveOrderElement::from_msg_hash(
+                            &[b"key aggregation".as_ref(), set_digest.as_slice(), &e.to_vec()]
+                                .concat(),
+                        )
+                    })
+                    .collect::<Vec<_>>()
+                    .into()
+            }
+
+            /// Aggregate public keys as `sum t_i * pk_i` with the coefficients of
+            /// `key_aggregation_coefficients`, safe against rogue keys without proofs of
+            /// possession, unlike their plain sum. Signatures on the same message aggregate the
+            /// same way, each multiplied by the signer's coefficient.
+            pub fn aggregate_keys_with_coefficients(&self) -> $group_element {
+                let coeffs = self.key_aggregation_coefficients();
+                // Both vectors have the same length
+                self.multi_scalar_mul_var_time(coeffs.iter()).unwrap()
+            }
+
+            /// Probabilistic equality check of 2 vectors. Checks `sum r_i*(a_i - b_i) == 0` for random
+            /// `r_i` with a single variable time MSM which is much faster than comparing each pair.
+            /// Unequal vectors are wrongly reported equal with probability at most `1/q`.
+            pub fn batch_eq<R: rand::RngCore + rand::CryptoRng>(
+                &self,
+                other: &Self,
+                rng: &mut R,
+            ) -> bool {
+                let diffs = match self.minus(other) {
+                    Ok(d) => d,
+                    Err(_) => return false,
+                };
+                let coeffs: Vec<_> = (0..self.len())
+                    .map(|_| CurveOrderElement::random_using_rng(rng))
+                    .collect();
+                match diffs.multi_scalar_mul_var_time(coeffs.iter()) {
+                    Ok(r) => r.is_identity(),
+                    Err(_) => false,
+                }
+            }
+
+            /// Blind each element with its own blind as in `blind` of the element
+            pub fn blind(
+                &self,
+                blinds: &CurveOrderElementVector,
+            ) -> Result<(Self, CurveOrderElementVector), ValueError> {
+                check_vector_size_for_equality!(self, blinds)?;
+                let blinded: Result<Vec<_>, _> = self
+                    .elems
+                    .par_iter()
+                    .zip(blinds.as_slice().par_iter())
+                    .map(|(e, r)| e.blind(r).map(|(b, _)| b))
+                    .collect();
+                Ok((blinded?.into(), blinds.clone()))
+            }
+
+            /// Remove the blinds of elements blinded with `blind`
+            pub fn unblind(&self, blinds: &CurveOrderElementVector) -> Result<Self, ValueError> {
+                check_vector_size_for_equality!(self, blinds)?;
+                let unblinded: Result<Vec<_>, _> = self
+                    .elems
+                    .par_iter()
+                    .zip(blinds.as_slice().par_iter())
+                    .map(|(e, r)| e.unblind(r))
+                    .collect();
+                Ok(unblinded?.into())
+            }
+
             /// Non-constant time operation. Scale this group element vector by a factor. Each group
             /// element is multiplied by the same factor so wnaf is computed only once.
             pub fn scale_var_time(&mut self, n: &CurveOrderElement) {
-                let wnaf = n.to_wnaf(5);
+                let (wnaf, len) = n.to_wnaf_locked(WNAF_WIDTH);
                 self.elems.as_mut_slice().par_iter_mut().for_each(|e| {
                     let table = $lookup_table::from(&(*e));
-                    *e = $group_element::wnaf_mul(&table, &wnaf);
+                    $group_element::wnaf_mul_into(&table, &wnaf[..len], e);
                 })
             }
 
@@ -861,6 +1317,330 @@ macro_rules! impl_group_elem_vec_product_ops {
     };
 }
 
+macro_rules! impl_precomputed_msm {
+    ( $group_element:ident, $group_element_vec:ident, $name:ident ) => {
+        /// Multi-scalar multiplication with a fixed basis, eg. a commitment key. For each basis
+        /// element `P` and window position `j`, the multiples `d*2^(w*j)*P` for digits `d` in
+        /// `[1, 2^w)` are computed once so multiplications need no doublings, only an addition per
+        /// digit. The tables hold `table_size(len, w)` group elements, a larger window `w` trades
+        /// memory for fewer additions.
+        #[derive(Clone, Debug)]
+        pub struct $name {
+            window: usize,
+            tables: Vec<Vec<Vec<$group_element>>>,
+        }
+
+        impl $name {
+            /// `window` is from 1 to 7
+            pub fn new(basis: &$group_element_vec, window: usize) -> Result<Self, ValueError> {
+                if window == 0 || window > 7 {
+                    return Err(ValueError::OutOfRange(window));
+                }
+                let num_windows = CURVE_ORDER.nbits().div_ceil(window);
+                let tables = basis
+                    .as_slice()
+                    .par_iter()
+                    .map(|p| {
+                        let mut base = p.clone();
+                        (0..num_windows)
+                            .map(|_| {
+                                let multiples = base.get_multiples((1 << window) - 1);
+                                for _ in 0..window {
+                                    base.double_mut();
+                                }
+                                multiples
+                            })
+                            .collect()
+                    })
+                    .collect();
+                Ok(Self { window, tables })
+            }
+
+            /// Largest window whose tables hold at most `max_elements` group elements
+            pub fn with_memory_budget(
+                basis: &$group_element_vec,
+                max_elements: usize,
+            ) -> Result<Self, ValueError> {
+                let window = (1..=7)
+                    .rev()
+                    .find(|w| Self::table_size(basis.len(), *w) <= max_elements)
+                    .ok_or(ValueError::OutOfRange(max_elements))?;
+                Self::new(basis, window)
+            }
+
+            /// Number of group elements in the tables of a basis of `len` elements with window
+            /// `window`
+            pub fn table_size(len: usize, window: usize) -> usize {
+                let num_windows = CURVE_ORDER.nbits().div_ceil(window);
+                len * num_windows * ((1 << window) - 1)
+            }
+
+            pub fn window(&self) -> usize {
+                self.window
+            }
+
+            pub fn len(&self) -> usize {
+                self.tables.len()
+            }
+
+            pub fn is_empty(&self) -> bool {
+                self.tables.is_empty()
+            }
+
+            /// `sum scalars_i * basis_i` for verification, variable time as zero digits are
+            /// skipped
+            pub fn mul(
+                &self,
+                scalars: &CurveOrderElementVector,
+            ) -> Result<$group_element, ValueError> {
+                check_vector_size_for_equality!(scalars, self.tables)?;
+                let r = self
+                    .tables
+                    .par_iter()
+                    .zip(scalars.as_slice().par_iter())
+                    .map(|(table, s)| {
+                        let mut acc = $group_element::identity();
+                        for (multiples, d) in table.iter().zip(s.to_power_of_2_base(self.window)) {
+                            if d != 0 {
+                                acc += &multiples[d as usize - 1];
+                            }
+                        }
+                        acc
+                    })
+                    .reduce($group_element::identity, |a, b| a + b);
+                Ok(r)
+            }
+
+            /// `sum scalars_i * basis_i` for signing with secret scalars. Every window of every
+            /// scalar is processed and all multiples of a window are read with `cmove`, so the
+            /// memory access pattern does not depend on the digits.
+            pub fn mul_const_time(
+                &self,
+                scalars: &CurveOrderElementVector,
+            ) -> Result<$group_element, ValueError> {
+                check_vector_size_for_equality!(scalars, self.tables)?;
+                let mut result = $group_element::identity().value;
+                for (table, s) in self.tables.iter().zip(scalars.iter()) {
+                    let mut digits = s.to_power_of_2_base(self.window);
+                    digits.resize(table.len(), 0);
+                    for (multiples, d) in table.iter().zip(digits) {
+                        let mut t = $group_element::identity().value;
+                        for (j, p) in multiples.iter().enumerate() {
+                            // 1 if j + 1 == d, both are less than 2^31
+                            let eq = ((((j + 1) as i32 ^ d as i32) - 1) >> 31) & 1;
+                            t.cmove(&p.value, eq as isize);
+                        }
+                        result.add(&t);
+                    }
+                }
+                Ok(result.into())
+            }
+        }
+    };
+}
+
+macro_rules! impl_group_elem_vec_bytes {
+    ( $group_element:ident, $group_element_vec:ident, $group_size:ident ) => {
+        impl $group_element_vec {
+            /// Big-endian `u32` length followed by the fixed size encoding of each element. Much
+            /// more compact and faster to load than the serde encoding of large vectors.
+            pub fn to_bytes(&self) -> Vec<u8> {
+                let mut bytes = vec![0u8; 4 + self.len() * $group_size];
+                bytes[..4].copy_from_slice(&(self.len() as u32).to_be_bytes());
+                bytes[4..]
+                    .par_chunks_mut($group_size)
+                    .zip(self.elems.par_iter())
+                    .for_each(|(b, e)| e.write_to_slice_unchecked(b));
+                bytes
+            }
+
+            /// Inverse of `to_bytes`, elements are decoded in parallel
+            pub fn from_bytes(bytes: &[u8]) -> Result<Self, SerzDeserzError> {
+                if bytes.len() < 4 {
+                    return Err(SerzDeserzError::VectorBytesIncorrectSize(bytes.len(), 4));
+                }
+                let len = u32::from_be_bytes(*array_ref![bytes, 0, 4]) as usize;
+                let expected = len
+                    .checked_mul($group_size)
+                    .and_then(|l| l.checked_add(4));
+                if expected != Some(bytes.len()) {
+                    return Err(SerzDeserzError::VectorBytesIncorrectSize(
+                        bytes.len(),
+                        expected.unwrap_or(usize::MAX),
+                    ));
+                }
+                let elems = bytes[4..]
+                    .par_chunks($group_size)
+                    .map($group_element::from_slice)
+                    .collect::<Result<Vec<_>, _>>()?;
+                Ok(Self { elems })
+            }
+
+            /// Vector from the concatenated fixed size encodings of its elements, without a
+            /// length prefix. Elements are decoded and validated in parallel like
+            /// `ElementEncoding::decode`, an invalid one is reported with the index of the first.
+            pub fn from_serialized_chunks(bytes: &[u8]) -> Result<Self, SerzDeserzError> {
+                if bytes.len() % $group_size != 0 {
+                    return Err(SerzDeserzError::VectorBytesIncorrectSize(
+                        bytes.len(),
+                        (bytes.len() / $group_size + 1) * $group_size,
+                    ));
+                }
+                let decoded: Vec<_> = bytes
+                    .par_chunks($group_size)
+                    .map(<$group_element as crate::encoding::ElementEncoding>::decode)
+                    .collect();
+                let elems = decoded
+                    .into_iter()
+                    .enumerate()
+                    .map(|(i, e)| e.map_err(|_| SerzDeserzError::InvalidElementAt(i)))
+                    .collect::<Result<Vec<_>, _>>()?;
+                Ok(Self { elems })
+            }
+
+            /// Write the encoding of `to_bytes` to `writer` without buffering all of it
+            pub fn write_to<W: std::io::Write>(&self, writer: W) -> Result<(), SerzDeserzError> {
+                self.write_to_with_progress(writer, |_, _| {})
+            }
+
+            /// `write_to` calling `progress(written, len)` after each chunk of
+            /// `STREAM_CHUNK_SIZE` elements
+            pub fn write_to_with_progress<W: std::io::Write>(
+                &self,
+                mut writer: W,
+                mut progress: impl FnMut(usize, usize),
+            ) -> Result<(), SerzDeserzError> {
+                writer.write_all(&(self.len() as u32).to_be_bytes())?;
+                let mut written = 0;
+                let mut buf = vec![0u8; STREAM_CHUNK_SIZE * $group_size];
+                for chunk in self.elems.chunks(STREAM_CHUNK_SIZE) {
+                    let bytes = &mut buf[..chunk.len() * $group_size];
+                    bytes
+                        .par_chunks_mut($group_size)
+                        .zip(chunk.par_iter())
+                        .for_each(|(b, e)| e.write_to_slice_unchecked(b));
+                    writer.write_all(bytes)?;
+                    written += chunk.len();
+                    progress(written, self.len());
+                }
+                writer.flush()?;
+                Ok(())
+            }
+
+            /// Read a vector of `expected_len` elements written by `write_to` or `to_bytes`, one
+            /// chunk at a time. Only the elements are buffered, not the whole encoding.
+            pub fn read_from<R: std::io::Read>(
+                reader: R,
+                expected_len: usize,
+            ) -> Result<Self, SerzDeserzError> {
+                Self::read_from_with_progress(reader, expected_len, |_, _| {})
+            }
+
+            /// `read_from` calling `progress(read, expected_len)` after each chunk of
+            /// `STREAM_CHUNK_SIZE` elements
+            pub fn read_from_with_progress<R: std::io::Read>(
+                mut reader: R,
+                expected_len: usize,
+                mut progress: impl FnMut(usize, usize),
+            ) -> Result<Self, SerzDeserzError> {
+                let mut len_bytes = [0u8; 4];
+                reader.read_exact(&mut len_bytes)?;
+                let len = u32::from_be_bytes(len_bytes) as usize;
+                if len != expected_len {
+                    return Err(SerzDeserzError::VectorBytesIncorrectSize(
+                        4 + len * $group_size,
+                        4 + expected_len * $group_size,
+                    ));
+                }
+                let mut elems = Vec::with_capacity(len);
+                let mut buf = vec![0u8; STREAM_CHUNK_SIZE.min(len) * $group_size];
+                while elems.len() < len {
+                    let n = STREAM_CHUNK_SIZE.min(len - elems.len());
+                    let bytes = &mut buf[..n * $group_size];
+                    reader.read_exact(bytes)?;
+                    let chunk = bytes
+                        .par_chunks($group_size)
+                        .map($group_element::from_slice)
+                        .collect::<Result<Vec<_>, _>>()?;
+                    elems.extend(chunk);
+                    progress(elems.len(), len);
+                }
+                Ok(Self { elems })
+            }
+        }
+    };
+}
+
+macro_rules! impl_group_elem_vec_fmt {
+    ( $group_element_vec:ident ) => {
+        impl fmt::Debug for $group_element_vec {
+            /// Length and digests of the first elements
+            fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                fmt_digests(
+                    f,
+                    stringify!($group_element_vec),
+                    self.len(),
+                    self.elems.iter().map(|e| e.to_vec()),
+                )
+            }
+        }
+
+        impl fmt::Display for $group_element_vec {
+            /// Every element on its own line
+            fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                writeln!(
+                    f,
+                    "{} of length {} [",
+                    stringify!($group_element_vec),
+                    self.len()
+                )?;
+                for (i, e) in self.elems.iter().enumerate() {
+                    if f.alternate() {
+                        writeln!(f, "  {}: {:#}", i, e)?;
+                    } else {
+                        writeln!(f, "  {}: {}", i, e)?;
+                    }
+                }
+                write!(f, "]")
+            }
+        }
+    };
+}
+
+/// Arbitrary elements for fuzzing and property tests: the identity and the generator are each
+/// picked with probability 1/8, other elements are the generator multiplied by an arbitrary scalar.
+macro_rules! impl_group_elem_arbitrary {
+    ( $group_element:ident, $group_element_vec:ident ) => {
+        #[cfg(feature = "arbitrary")]
+        impl<'a> arbitrary::Arbitrary<'a> for $group_element {
+            fn arbitrary(u: &mut arbitrary::Unstructured<'a>) -> arbitrary::Result<Self> {
+                Ok(match u.int_in_range(0u8..=7)? {
+                    0 => Self::identity(),
+                    1 => Self::generator(),
+                    _ => {
+                        let s: CurveOrderElement = u.arbitrary()?;
+                        Self::generator() * &s
+                    }
+                })
+            }
+
+            fn size_hint(depth: usize) -> (usize, Option<usize>) {
+                let scalar_hint = <CurveOrderElement as arbitrary::Arbitrary>::size_hint(depth);
+                arbitrary::size_hint::and((1, Some(1)), scalar_hint)
+            }
+        }
+
+        #[cfg(feature = "arbitrary")]
+        impl<'a> arbitrary::Arbitrary<'a> for $group_element_vec {
+            fn arbitrary(u: &mut arbitrary::Unstructured<'a>) -> arbitrary::Result<Self> {
+                Ok(Self {
+                    elems: u.arbitrary()?,
+                })
+            }
+        }
+    };
+}
+
 #[macro_export]
 macro_rules! impl_group_elem_vec_conversions {
     ( $group_element:ident, $group_element_vec:ident ) => {
@@ -902,6 +1682,8 @@ macro_rules! impl_group_elem_vec_conversions {
             }
         }
 
+        $crate::impl_vec_range_index!($group_element, $group_element_vec);
+
         impl PartialEq for $group_element_vec {
             fn eq(&self, other: &Self) -> bool {
                 if self.len() != other.len() {
@@ -930,22 +1712,105 @@ macro_rules! impl_group_elem_vec_conversions {
                 self.elems.as_slice()
             }
         }
+
+        impl ::std::iter::FromIterator<$group_element> for $group_element_vec {
+            fn from_iter<I: IntoIterator<Item = $group_element>>(iter: I) -> Self {
+                Self {
+                    elems: iter.into_iter().collect(),
+                }
+            }
+        }
+
+        impl Extend<$group_element> for $group_element_vec {
+            fn extend<I: IntoIterator<Item = $group_element>>(&mut self, iter: I) {
+                self.elems.extend(iter)
+            }
+        }
+
+        impl<'a> IntoIterator for &'a $group_element_vec {
+            type Item = &'a $group_element;
+            type IntoIter = ::std::slice::Iter<'a, $group_element>;
+
+            fn into_iter(self) -> Self::IntoIter {
+                self.elems.iter()
+            }
+        }
+
+        impl rayon::iter::FromParallelIterator<$group_element> for $group_element_vec {
+            fn from_par_iter<I>(par_iter: I) -> Self
+            where
+                I: rayon::iter::IntoParallelIterator<Item = $group_element>,
+            {
+                Self {
+                    elems: par_iter.into_par_iter().collect(),
+                }
+            }
+        }
+
+        impl rayon::iter::IntoParallelIterator for $group_element_vec {
+            type Item = $group_element;
+            type Iter = rayon::vec::IntoIter<$group_element>;
+
+            fn into_par_iter(self) -> Self::Iter {
+                self.elems.into_par_iter()
+            }
+        }
+
+        impl<'a> rayon::iter::IntoParallelIterator for &'a $group_element_vec {
+            type Item = &'a $group_element;
+            type Iter = rayon::slice::Iter<'a, $group_element>;
+
+            fn into_par_iter(self) -> Self::Iter {
+                self.elems.par_iter()
+            }
+        }
+
+        impl $group_element_vec {
+            pub fn par_iter(&self) -> rayon::slice::Iter<'_, $group_element> {
+                self.elems.par_iter()
+            }
+
+            pub fn par_iter_mut(&mut self) -> rayon::slice::IterMut<'_, $group_element> {
+                self.elems.par_iter_mut()
+            }
+
+            /// Sort in the canonical order of the elements and remove duplicates, so vectors with
+            /// the same set of elements end up identical. Each element is encoded only once.
+            pub fn sort_dedup(&mut self) {
+                let mut keyed: Vec<_> = self
+                    .elems
+                    .par_drain(..)
+                    .map(|e| {
+                        let key = if e.is_identity() { None } else { Some(e.to_vec()) };
+                        (key, e)
+                    })
+                    .collect();
+                keyed.par_sort_unstable_by(|a, b| a.0.cmp(&b.0));
+                keyed.dedup_by(|a, b| a.0 == b.0);
+                self.elems = keyed.into_iter().map(|(_, e)| e).collect();
+            }
+        }
     };
 }
 
 #[cfg(test)]
 mod test {
     use super::*;
+    #[cfg(feature = "serde")]
     use serde::{Serialize, Deserialize};
-    use crate::constants::GROUP_G1_SIZE;
+    use crate::constants::{
+        GROUP_G1_SIZE, MAX_PIPPENGER_WINDOW, MSM_CONST_TIME_WINDOW, PIPPENGER_THRESHOLD, WNAF_WIDTH,
+    };
     use crate::curve_order_elem::CurveOrderElementVector;
     #[cfg(any(feature = "bls381", feature = "bn254"))]
     use crate::constants::{GROUP_G2_SIZE, GROUP_GT_SIZE};
     #[cfg(any(feature = "bls381", feature = "bn254"))]
     use crate::extension_field_gt::GT;
-    use crate::group_elem_g1::{G1LookupTable, G1Vector, G1};
+    use crate::group_elem_g1::{G1LookupTable, G1PrecomputedMsm, G1Vector, G1};
     #[cfg(any(feature = "bls381", feature = "bn254"))]
-    use crate::group_elem_g2::{G2LookupTable, G2Vector, G2};
+    use crate::group_elem_g2::{G2LookupTable, G2PrecomputedMsm, G2Vector, G2};
+    use rayon::prelude::*;
+    use std::cmp::Ordering;
     use std::collections::{HashMap, HashSet};
     use std::time::Instant;
 
@@ -984,6 +1849,200 @@ mod test {
         to_and_fro_bytes!(GT, GROUP_GT_SIZE);
     }
 
+    #[test]
+    fn test_serialized_sizes() {
+        fn check<G: GroupElement>() {
+            let g = G::random();
+            assert_eq!(g.to_vec().len(), G::SERIALIZED_SIZE);
+            assert!(G::from_slice(&vec![0u8; G::SERIALIZED_SIZE + 1]).is_err());
+        }
+
+        check::<G1>();
+        let mut buf = [0u8; G1::SERIALIZED_SIZE];
+        let g = G1::random();
+        g.write_to_slice(&mut buf).unwrap();
+        assert_eq!(G1::from_slice(&buf).unwrap(), g);
+        let _: [u8; G1::COMPRESSED_SIZE] = g.to_compressed_bytes();
+        let _: [u8; CurveOrderElement::SERIALIZED_SIZE] = CurveOrderElement::random().to_bytes();
+        let _: [u8; CurveOrderElement::COMPRESSED_SIZE] =
+            CurveOrderElement::random().to_compressed_bytes();
+        #[cfg(any(feature = "bls381", feature = "bn254"))]
+        {
+            check::<G2>();
+            let _: [u8; G2::COMPRESSED_SIZE] = G2::random().to_compressed_bytes();
+            assert_eq!(GT::random().to_vec().len(), GT::SERIALIZED_SIZE);
+        }
+    }
+
+    #[test]
+    fn test_vector_to_and_from_bytes() {
+        macro_rules! to_and_fro_bytes {
+            ( $group_vec:ident, $group_size:ident ) => {
+                for size in vec![0, 1, 10, 50] {
+                    let v = $group_vec::random(size);
+                    let bytes = v.to_bytes();
+                    assert_eq!(bytes.len(), 4 + size * $group_size);
+                    assert_eq!($group_vec::from_bytes(&bytes).unwrap(), v);
+                    assert!($group_vec::from_bytes(&bytes[..bytes.len() - 1]).is_err());
+
+                    let mut bytes1 = bytes.clone();
+                    bytes1.push(0);
+                    assert!($group_vec::from_bytes(&bytes1).is_err());
+                }
+                assert!($group_vec::from_bytes(&[0u8; 3]).is_err());
+                assert!($group_vec::from_bytes(&[0xffu8; 4]).is_err());
+            };
+        }
+
+        to_and_fro_bytes!(G1Vector, GROUP_G1_SIZE);
+        #[cfg(any(feature = "bls381", feature = "bn254"))]
+        to_and_fro_bytes!(G2Vector, GROUP_G2_SIZE);
+    }
+
+    #[test]
+    fn test_vector_streaming() {
+        macro_rules! stream {
+            ( $group_vec:ident, $sizes:expr ) => {
+                for size in $sizes {
+                    let v = $group_vec::random(size);
+                    let mut bytes = vec![];
+                    let mut calls = vec![];
+                    v.write_to_with_progress(&mut bytes, |done, total| calls.push((done, total)))
+                        .unwrap();
+                    assert_eq!(bytes, v.to_bytes());
+                    if size > STREAM_CHUNK_SIZE {
+                        assert_eq!(calls, vec![(STREAM_CHUNK_SIZE, size), (size, size)]);
+                    }
+
+                    let mut read = 0;
+                    let w = $group_vec::read_from_with_progress(&bytes[..], size, |done, _| {
+                        read = done
+                    })
+                    .unwrap();
+                    assert_eq!(w, v);
+                    assert_eq!(read, size);
+                    assert!($group_vec::read_from(&bytes[..], size + 1).is_err());
+                    assert!($group_vec::read_from(&bytes[..bytes.len() - 1], size).is_err());
+                }
+            };
+        }
+
+        stream!(G1Vector, [0, 1, STREAM_CHUNK_SIZE + 3]);
+        #[cfg(any(feature = "bls381", feature = "bn254"))]
+        {
+            stream!(G2Vector, [0, 1, 10]);
+        }
+    }
+
+    #[test]
+    fn test_vector_from_serialized_chunks() {
+        macro_rules! chunks {
+            ( $group_vec:ident, $group_size:ident ) => {
+                let v = $group_vec::random(10);
+                let mut bytes = v.to_bytes()[4..].to_vec();
+                assert_eq!($group_vec::from_serialized_chunks(&bytes).unwrap(), v);
+                assert!($group_vec::from_serialized_chunks(&[]).unwrap().len() == 0);
+                assert!(matches!(
+                    $group_vec::from_serialized_chunks(&bytes[1..]),
+                    Err(SerzDeserzError::VectorBytesIncorrectSize(_, _))
+                ));
+
+                // The first invalid element is reported
+                bytes[8 * $group_size - 1] ^= 1;
+                bytes[4 * $group_size - 1] ^= 1;
+                assert!(matches!(
+                    $group_vec::from_serialized_chunks(&bytes),
+                    Err(SerzDeserzError::InvalidElementAt(3))
+                ));
+            };
+        }
+
+        chunks!(G1Vector, GROUP_G1_SIZE);
+        #[cfg(any(feature = "bls381", feature = "bn254"))]
+        {
+            chunks!(G2Vector, GROUP_G2_SIZE);
+        }
+    }
+
+    #[test]
+    fn test_vector_range_index() {
+        macro_rules! range_index {
+            ( $group_vec:ident ) => {{
+                let mut v = $group_vec::random(10);
+                let elems = v.as_slice().to_vec();
+                assert_eq!(&v[2..5], &elems[2..5]);
+                assert_eq!(&v[7..], &elems[7..]);
+                assert_eq!(&v[..3], &elems[..3]);
+                assert_eq!(&v[..], &elems[..]);
+                assert_eq!(&v[2..=5], &elems[2..=5]);
+                assert_eq!(&v[..=3], &elems[..=3]);
+                assert_eq!(v.slice(4..6), &elems[4..6]);
+
+                v[..2].clone_from_slice(&elems[8..]);
+                assert_eq!(&v[..2], &elems[8..]);
+            }};
+        }
+
+        range_index!(G1Vector);
+        #[cfg(any(feature = "bls381", feature = "bn254"))]
+        range_index!(G2Vector);
+    }
+
+    #[test]
+    fn test_vector_split_off_and_chunks() {
+        macro_rules! split_off_and_chunks {
+            ( $group:ident, $group_vec:ident ) => {{
+                let mut v = $group_vec::random(10);
+                let elems = v.as_slice().to_vec();
+                let r = v.split_off(6);
+                assert_eq!(v.as_slice(), &elems[..6]);
+                assert_eq!(r.as_slice(), &elems[6..]);
+
+                let v: $group_vec = elems.clone().into();
+                let chunks: Vec<_> = v.chunks(3).collect();
+                assert_eq!(chunks.len(), 4);
+                assert_eq!(chunks[3], &elems[9..]);
+                let sums: Vec<_> = v
+                    .par_chunks(3)
+                    .map(|c| c.iter().fold($group::identity(), |a, b| a + b))
+                    .collect();
+                assert_eq!(sums.len(), 4);
+                assert_eq!(sums[1], &elems[3] + &elems[4] + &elems[5]);
+            }};
+        }
+
+        split_off_and_chunks!(G1, G1Vector);
+        #[cfg(any(feature = "bls381", feature = "bn254"))]
+        split_off_and_chunks!(G2, G2Vector);
+    }
+
+    #[test]
+    fn test_vector_iterators() {
+        macro_rules! iterators {
+            ( $group:ident, $group_vec:ident ) => {{
+                let elems: Vec<_> = (0..10).map(|_| $group::random()).collect();
+                let mut v: $group_vec = elems.iter().cloned().collect();
+                assert_eq!(v.as_slice(), elems.as_slice());
+
+                v.extend(elems.iter().cloned());
+                assert_eq!(v.len(), 20);
+                assert_eq!(&v.as_slice()[10..], elems.as_slice());
+
+                let doubled: $group_vec = v.par_iter().map(|e| e.double()).collect();
+                for (d, e) in doubled.iter().zip(&v) {
+                    assert_eq!(*d, e.double());
+                }
+                let sum = v.sum();
+                assert_eq!((&v).into_par_iter().cloned().reduce($group::identity, |a, b| a + b), sum);
+                assert_eq!(v.into_par_iter().reduce($group::identity, |a, b| a + b), sum);
+            }};
+        }
+
+        iterators!(G1, G1Vector);
+        #[cfg(any(feature = "bls381", feature = "bn254"))]
+        iterators!(G2, G2Vector);
+    }
+
     #[test]
     fn test_hashing() {
         // If the element can be added to HashSet or HashMap, it must be hashable.
@@ -1173,6 +2232,7 @@ mod test {
         hex!(GT);
     }
 
+    #[cfg(feature = "serde")]
     #[test]
     fn test_serialization_deserialization_group_elem() {
         macro_rules! serz {
@@ -1231,10 +2291,17 @@ mod test {
                     let expected = &a * &r;
 
                     let table = $lookup_table::from(&a);
-                    let wnaf = r.to_wnaf(5);
+                    let wnaf = r.to_wnaf(WNAF_WIDTH);
                     let p = $group::wnaf_mul(&table, &wnaf);
 
                     assert_eq!(expected, p);
+
+                    // Whatever `out` held is overwritten
+                    let mut out = $group::random();
+                    $group::wnaf_mul_into(&table, &wnaf, &mut out);
+                    assert_eq!(expected, out);
+                    a.scalar_mul_variable_time_into(&r, &mut out);
+                    assert_eq!(expected, out);
                 }
             };
         }
@@ -1243,6 +2310,36 @@ mod test {
         wnaf_mul!(G2, G2LookupTable);
     }
 
+    #[test]
+    fn test_booth_mul() {
+        macro_rules! booth_mul {
+            ( $group:ident ) => {
+                for width in 2..=6 {
+                    let a = $group::random();
+                    let table = a.to_booth_lookup_table(width);
+                    assert_eq!(table.len(), (1 << (width - 1)) + 1);
+                    for r in vec![
+                        CurveOrderElement::zero(),
+                        CurveOrderElement::one(),
+                        CurveOrderElement::minus_one(),
+                        CurveOrderElement::random(),
+                        CurveOrderElement::random(),
+                    ] {
+                        let expected = &a * &r;
+                        assert_eq!($group::booth_mul(&table, &r.to_booth(width)), expected);
+                        assert_eq!(a.scalar_mul_booth(&r, width), expected);
+                        let mut out = $group::random();
+                        $group::booth_mul_into(&table, &r.to_booth(width), &mut out);
+                        assert_eq!(out, expected);
+                    }
+                }
+            };
+        }
+        booth_mul!(G1);
+        #[cfg(any(feature = "bls381", feature = "bn254"))]
+        booth_mul!(G2);
+    }
+
     #[test]
     fn test_multi_scalar_multiplication() {
         macro_rules! mul_scal_mul {
@@ -1295,6 +2392,428 @@ mod test {
         mul_scal_mul!(G2, G2Vector);
     }
 
+    #[test]
+    fn test_multi_scalar_multiplication_pippenger() {
+        macro_rules! pippenger {
+            ( $group:ident, $vector:ident ) => {
+                let mut fs: Vec<_> = (0..40).map(|_| CurveOrderElement::random()).collect();
+                fs[3] = CurveOrderElement::zero();
+                fs[7] = CurveOrderElement::one();
+                fs[9] = CurveOrderElement::minus_one();
+                let gs: Vec<_> = (0..40).map(|_| $group::random()).collect();
+                let expected = $vector::from(gs.as_slice())
+                    .multi_scalar_mul_const_time_naive(&CurveOrderElementVector::from(
+                        fs.as_slice(),
+                    ))
+                    .unwrap();
+
+                for window in vec![1, 2, 5, 8, 13] {
+                    let r = $vector::multi_scalar_mul_var_time_pippenger(&gs, &fs, window)
+                        .unwrap();
+                    assert_eq!(r, expected);
+                }
+                assert!($vector::multi_scalar_mul_var_time_pippenger(&gs, &fs, 0).is_err());
+                assert!($vector::multi_scalar_mul_var_time_pippenger(
+                    &gs,
+                    &fs,
+                    MAX_PIPPENGER_WINDOW + 1
+                )
+                .is_err());
+                assert!($vector::multi_scalar_mul_var_time_pippenger(&gs, &fs[1..], 4).is_err());
+
+                // Enough terms to go through Pippenger's method
+                let n = PIPPENGER_THRESHOLD + 3;
+                let fs: Vec<_> = (0..n).map(|_| CurveOrderElement::random()).collect();
+                let gs: Vec<_> = (0..n).map(|_| $group::random()).collect();
+                let expected = $vector::from(gs.as_slice())
+                    .multi_scalar_mul_const_time(&CurveOrderElementVector::from(fs.as_slice()))
+                    .unwrap();
+                assert_eq!(
+                    $vector::multi_scalar_mul_var_time_without_precomputation(&gs, &fs).unwrap(),
+                    expected
+                );
+            };
+        }
+        pippenger!(G1, G1Vector);
+        #[cfg(any(feature = "bls381", feature = "bn254"))]
+        pippenger!(G2, G2Vector);
+    }
+
+    #[test]
+    #[ignore]
+    fn timing_msm_parameters() {
+        // Benchmark behind the MSM parameters of `constants`, prints the best of 5 runs. Run with
+        // `cargo test --release --lib --no-default-features --features <curve> timing_msm_parameters -- --ignored --nocapture`
+        fn best_of_5<F: FnMut()>(mut f: F) -> std::time::Duration {
+            (0..5)
+                .map(|_| {
+                    let start = Instant::now();
+                    f();
+                    start.elapsed()
+                })
+                .min()
+                .unwrap()
+        }
+
+        macro_rules! bench {
+            ( $group:ident, $vector:ident, $lookup_table:ident ) => {
+                let fs: Vec<_> = (0..64).map(|_| CurveOrderElement::random()).collect();
+                let gs: Vec<_> = (0..64).map(|_| $group::random()).collect();
+                let t = best_of_5(|| {
+                    for (g, f) in gs.iter().zip(fs.iter()) {
+                        let _ = g.scalar_mul_variable_time(f);
+                    }
+                });
+                println!(
+                    "{}: 64 wNAF scalar multiplications (width {}) = {:?}",
+                    stringify!($group),
+                    WNAF_WIDTH,
+                    t
+                );
+                let t = best_of_5(|| {
+                    $vector::multi_scalar_mul_const_time_without_precomputation(&gs, &fs)
+                        .unwrap();
+                });
+                println!(
+                    "{}: constant time MSM of 64 terms (window {}) = {:?}",
+                    stringify!($group),
+                    MSM_CONST_TIME_WINDOW,
+                    t
+                );
+
+                for n in vec![64, 128, 256, 512, 1024, 2048, 4096] {
+                    let fs: Vec<_> = (0..n).map(|_| CurveOrderElement::random()).collect();
+                    let gs: Vec<_> = (0..n).map(|_| $group::random()).collect();
+                    let t = best_of_5(|| {
+                        let tables: Vec<_> = gs.iter().map($lookup_table::from).collect();
+                        $vector::multi_scalar_mul_var_time_with_precomputation_done(
+                            &tables,
+                            fs.iter(),
+                        )
+                        .unwrap();
+                    });
+                    println!("{}: n = {}, Strauss = {:?}", stringify!($group), n, t);
+                    for window in 4..=10 {
+                        let t = best_of_5(|| {
+                            $vector::multi_scalar_mul_var_time_pippenger(&gs, &fs, window)
+                                .unwrap();
+                        });
+                        println!(
+                            "{}: n = {}, Pippenger window {} = {:?}",
+                            stringify!($group),
+                            n,
+                            window,
+                            t
+                        );
+                    }
+                }
+            };
+        }
+        bench!(G1, G1Vector, G1LookupTable);
+        #[cfg(any(feature = "bls381", feature = "bn254"))]
+        bench!(G2, G2Vector, G2LookupTable);
+    }
+
+    #[test]
+    fn test_ordering_and_sort_dedup() {
+        macro_rules! ordering {
+            ( $group:ident, $vector:ident ) => {{
+                let a = $group::random();
+                let b = $group::random();
+                assert_eq!(a.cmp(&a), Ordering::Equal);
+                assert_eq!(a.cmp(&b), b.cmp(&a).reverse());
+                assert_eq!(a.cmp(&b), a.to_vec().cmp(&b.to_vec()));
+                // Same element with a different internal representation
+                let a_1 = (&a + &b) - &b;
+                assert_eq!(a.cmp(&a_1), Ordering::Equal);
+                let id = &a - &a;
+                assert_eq!(id.cmp(&$group::identity()), Ordering::Equal);
+                assert!(id < a);
+
+                let elems: Vec<_> = (0..10).map(|_| $group::random()).collect();
+                let mut v = $vector::from(elems.clone());
+                v.extend(elems.iter().rev().cloned());
+                v.push(id.clone());
+                v.push($group::identity());
+                v.sort_dedup();
+                assert_eq!(v.len(), 11);
+                assert!(v[0].is_identity());
+                for i in 1..v.len() {
+                    assert!(v[i - 1] < v[i]);
+                }
+
+                let mut sorted = elems.clone();
+                sorted.push($group::identity());
+                sorted.sort();
+                assert_eq!(v.as_slice(), sorted.as_slice());
+            }};
+        }
+        ordering!(G1, G1Vector);
+        #[cfg(any(feature = "bls381", feature = "bn254"))]
+        ordering!(G2, G2Vector);
+    }
+
+    #[test]
+    fn test_batch_eq() {
+        let mut rng = rand::thread_rng();
+        macro_rules! batch_eq {
+            ( $group:ident, $vector:ident ) => {{
+                let a = $vector::random(20);
+                let mut b = a.clone();
+                assert!(a.batch_eq(&b, &mut rng));
+
+                b[7] = $group::random();
+                assert!(!a.batch_eq(&b, &mut rng));
+
+                // Differences that cancel in a plain sum are still detected
+                let mut c = a.clone();
+                let d = $group::random();
+                c[3] = &c[3] + &d;
+                c[4] = &c[4] - &d;
+                assert!(!a.batch_eq(&c, &mut rng));
+
+                b.pop();
+                assert!(!a.batch_eq(&b, &mut rng));
+                assert!($vector::new(0).batch_eq(&$vector::new(0), &mut rng));
+            }};
+        }
+        batch_eq!(G1, G1Vector);
+        #[cfg(any(feature = "bls381", feature = "bn254"))]
+        batch_eq!(G2, G2Vector);
+    }
+
+    #[test]
+    fn test_sparse_multi_scalar_multiplication() {
+        macro_rules! sparse_mul_scal_mul {
+            ( $group:ident, $vector:ident ) => {{
+                let n = 50;
+                let gv = $vector::random(n);
+                let mut fs = vec![CurveOrderElement::zero(); n];
+                let mut terms = vec![];
+                for i in (0..n).step_by(5) {
+                    let f = if i % 2 == 0 {
+                        CurveOrderElement::random()
+                    } else {
+                        CurveOrderElement::one()
+                    };
+                    fs[i] = f.clone();
+                    terms.push((i, f));
+                }
+                let fv = CurveOrderElementVector::from(fs);
+                let expected = gv.multi_scalar_mul_const_time_naive(&fv).unwrap();
+
+                assert_eq!(gv.multi_scalar_mul_var_time(fv.as_ref()).unwrap(), expected);
+                assert_eq!(gv.multi_scalar_mul_var_time_sparse(&terms).unwrap(), expected);
+                assert_eq!(
+                    gv.multi_scalar_mul_var_time_sparse(&[]).unwrap(),
+                    $group::identity()
+                );
+                assert!(gv
+                    .multi_scalar_mul_var_time_sparse(&[(n, CurveOrderElement::one())])
+                    .is_err());
+
+                // All zero and all one scalars
+                assert!(gv
+                    .multi_scalar_mul_var_time(CurveOrderElementVector::new(n).as_ref())
+                    .unwrap()
+                    .is_identity());
+                let ones = CurveOrderElementVector::from(vec![CurveOrderElement::one(); n]);
+                assert_eq!(gv.multi_scalar_mul_var_time(ones.as_ref()).unwrap(), gv.sum());
+                assert!(gv.multi_scalar_mul_var_time(ones.as_slice()[1..].iter()).is_err());
+            }};
+        }
+        sparse_mul_scal_mul!(G1, G1Vector);
+        #[cfg(any(feature = "bls381", feature = "bn254"))]
+        sparse_mul_scal_mul!(G2, G2Vector);
+    }
+
+    #[test]
+    fn test_dyn_group_element() {
+        let a = G1::random();
+        let b = G1::random();
+        let mut elems: Vec<Box<dyn DynGroupElement>> =
+            vec![Box::new(a.clone()), Box::new(b.clone())];
+        #[cfg(any(feature = "bls381", feature = "bn254"))]
+        elems.push(Box::new(G2::random()));
+
+        let sum = elems[0].dyn_plus(elems[1].as_ref()).unwrap();
+        assert_eq!(sum.as_any().downcast_ref::<G1>(), Some(&(&a + &b)));
+        assert_eq!(sum.dyn_to_vec(), (&a + &b).to_vec());
+        assert!(elems[0]
+            .dyn_plus(elems[0].dyn_negation().as_ref())
+            .unwrap()
+            .dyn_is_identity());
+        let r = CurveOrderElement::random();
+        assert!(elems[0].dyn_scalar_mul(&r).dyn_eq(&(&a * &r)));
+        assert!(*elems[0] == *elems[0].clone());
+        assert!(*elems[0] != *elems[1]);
+
+        #[cfg(any(feature = "bls381", feature = "bn254"))]
+        {
+            // Elements of different groups
+            assert!(elems[0].dyn_plus(elems[2].as_ref()).is_none());
+            assert!(!elems[0].dyn_eq(elems[2].as_ref()));
+        }
+    }
+
+    #[cfg(feature = "arbitrary")]
+    #[test]
+    fn test_arbitrary() {
+        use arbitrary::{Arbitrary, Unstructured};
+        fn check<'a, G: GroupElement + Arbitrary<'a> + PartialEq + std::fmt::Debug>(
+            data: &'a [u8],
+        ) {
+            let mut u = Unstructured::new(data);
+            assert!(G::arbitrary(&mut u).unwrap().is_identity());
+            assert_eq!(G::arbitrary(&mut u).unwrap(), G::generator());
+            let p = G::arbitrary(&mut u).unwrap();
+            assert!(p.has_correct_order());
+            assert_ne!(p, G::generator());
+        }
+        let random: Vec<u8> = (0..100).map(|_| rand::random()).collect();
+        let data = [&[0u8, 1, 2, 4][..], &random].concat();
+        check::<G1>(&data);
+        assert!(G1Vector::arbitrary(&mut Unstructured::new(&random)).is_ok());
+        #[cfg(any(feature = "bls381", feature = "bn254"))]
+        {
+            check::<G2>(&data);
+            let mut u = Unstructured::new(&data);
+            assert!(GT::arbitrary(&mut u).unwrap().is_one());
+            assert!(GT::arbitrary(&mut u).unwrap().has_correct_order());
+        }
+    }
+
+    #[test]
+    fn test_msm_through_trait() {
+        // Generic code reaches the optimized multi-scalar multiplication through the trait
+        fn naive<G: GroupElement>(points: &[G], scalars: &[CurveOrderElement]) -> G {
+            let mut sum = G::identity();
+            for (p, s) in points.iter().zip(scalars) {
+                sum.add_assign_(&p.scalar_mul_const_time(s));
+            }
+            sum
+        }
+
+        fn check<G: GroupElement + PartialEq + std::fmt::Debug>() {
+            let points: Vec<G> = (0..10).map(|_| G::random()).collect();
+            let scalars: Vec<_> = (0..10).map(|_| CurveOrderElement::random()).collect();
+            let expected = naive(&points, &scalars);
+            assert_eq!(G::msm(&points, &scalars).unwrap(), expected);
+            assert_eq!(G::msm_var_time(&points, &scalars).unwrap(), expected);
+            assert!(G::msm(&points, &scalars[1..]).is_err());
+            assert!(G::msm_var_time(&[], &[]).unwrap().is_identity());
+        }
+
+        check::<G1>();
+        #[cfg(any(feature = "bls381", feature = "bn254"))]
+        check::<G2>();
+    }
+
+    #[test]
+    fn test_precomputed_msm() {
+        macro_rules! check {
+            ( $group_element_vec:ident, $name:ident ) => {
+                let basis = $group_element_vec::random(6);
+                let mut scalars = CurveOrderElementVector::random(6);
+                scalars[1] = CurveOrderElement::zero();
+                scalars[2] = CurveOrderElement::minus_one();
+                let expected = basis.multi_scalar_mul_var_time(scalars.iter()).unwrap();
+                for window in 1..=7 {
+                    let msm = $name::new(&basis, window).unwrap();
+                    assert_eq!(msm.len(), 6);
+                    assert_eq!(msm.mul(&scalars).unwrap(), expected);
+                    assert_eq!(msm.mul_const_time(&scalars).unwrap(), expected);
+                }
+                assert!($name::new(&basis, 0).is_err());
+                assert!($name::new(&basis, 8).is_err());
+                assert!($name::new(&basis, 4).unwrap().mul(&scalars.split_at(5).0).is_err());
+
+                let budget = $name::table_size(6, 4);
+                let msm = $name::with_memory_budget(&basis, budget + 1).unwrap();
+                assert_eq!(msm.window(), 4);
+                assert_eq!(msm.mul(&scalars).unwrap(), expected);
+                assert!($name::with_memory_budget(&basis, 10).is_err());
+            };
+        }
+
+        check!(G1Vector, G1PrecomputedMsm);
+        #[cfg(any(feature = "bls381", feature = "bn254"))]
+        {
+            check!(G2Vector, G2PrecomputedMsm);
+        }
+    }
+
+    #[test]
+    fn test_aggregate_keys_with_coefficients() {
+        let sks = CurveOrderElementVector::random(4);
+        macro_rules! check {
+            ( $group_element:ident, $group_element_vec:ident ) => {
+                let g = $group_element::generator();
+                let pks: $group_element_vec = sks.iter().map(|sk| &g * sk).collect();
+                let coeffs = pks.key_aggregation_coefficients();
+                let agg = pks.aggregate_keys_with_coefficients();
+                let agg_sk = sks.inner_product(&coeffs).unwrap();
+                assert_eq!(agg, &g * &agg_sk);
+                assert_ne!(agg, pks.sum());
+
+                // Same set of keys in another order
+                let mut reordered = pks.clone();
+                reordered.as_mut_slice().swap(0, 3);
+                assert_eq!(reordered.aggregate_keys_with_coefficients(), agg);
+
+                // A rogue key `r*g - pk_0` no longer cancels the honest key
+                let rogue = &(&g * &CurveOrderElement::random()) - &pks[0];
+                let attacked: $group_element_vec = vec![pks[0].clone(), rogue.clone()].into();
+                assert_ne!(attacked.aggregate_keys_with_coefficients(), attacked.sum());
+                assert!($group_element_vec::new(0)
+                    .aggregate_keys_with_coefficients()
+                    .is_identity());
+            };
+        }
+
+        check!(G1, G1Vector);
+        #[cfg(any(feature = "bls381", feature = "bn254"))]
+        {
+            check!(G2, G2Vector);
+        }
+    }
+
+    #[test]
+    fn test_blinding() {
+        macro_rules! check {
+            ( $group_element:ident, $group_element_vec:ident ) => {
+                let p = $group_element::random();
+                let r = CurveOrderElement::random();
+                let (blinded, r1) = p.blind(&r).unwrap();
+                assert_eq!(r1, r);
+                assert_eq!(blinded, &p * &r);
+                assert_eq!(blinded.unblind(&r).unwrap(), p);
+                // Blinding commutes with multiplication by a key, as in OPRFs
+                let k = CurveOrderElement::random();
+                assert_eq!((&blinded * &k).unblind(&r).unwrap(), &p * &k);
+                assert!(p.blind(&CurveOrderElement::zero()).is_err());
+                assert!(blinded.unblind(&CurveOrderElement::zero()).is_err());
+
+                let v = $group_element_vec::random(5);
+                let blinds = CurveOrderElementVector::random(5);
+                let (blinded, _) = v.blind(&blinds).unwrap();
+                assert_eq!(blinded[2], &v[2] * &blinds[2]);
+                assert_eq!(blinded.unblind(&blinds).unwrap(), v);
+                assert!(v.blind(&CurveOrderElementVector::random(4)).is_err());
+                assert!(blinded.unblind(&CurveOrderElementVector::random(6)).is_err());
+                let mut with_zero = blinds.clone();
+                with_zero[1] = CurveOrderElement::zero();
+                assert!(v.blind(&with_zero).is_err());
+            };
+        }
+
+        check!(G1, G1Vector);
+        #[cfg(any(feature = "bls381", feature = "bn254"))]
+        {
+            check!(G2, G2Vector);
+        }
+    }
+
     #[test]
     fn timing_vector_scaling() {
         let size = 30;
@@ -1,9 +1,14 @@
-use crate::constants::{CURVE_ORDER, GROUP_G1_SIZE, FIELD_ORDER_ELEMENT_SIZE};
+use crate::constants::{
+    CURVE_ORDER, FIELD_ORDER_ELEMENT_SIZE, GROUP_G1_SIZE, MODULUS, MSM_CONST_TIME_WINDOW,
+    WNAF_LOOKUP_TABLE_SIZE, WNAF_WIDTH, MAX_PIPPENGER_WINDOW, PIPPENGER_THRESHOLD,
+    pippenger_window,
+};
 use crate::errors::{SerzDeserzError, ValueError};
 use crate::curve_order_elem::{CurveOrderElement, CurveOrderElementVector};
-use crate::group_elem::{GroupElement, GroupElementVector};
+use crate::group_elem::{GroupElement, GroupElementVector, STREAM_CHUNK_SIZE};
+use crate::backend::{Backend, CurrentBackend, Group, MapToPoint};
 use crate::types::{GroupG1, FP, BigNum};
-use crate::utils::hash_msg;
+use crate::utils::{fmt_digests, fmt_truncated_hex, SHORT_HEX_CHARS};
 use std::ops::{Add, AddAssign, Index, IndexMut, Mul, Neg, Sub, SubAssign};
 
 use std::fmt;
@@ -11,18 +16,50 @@ use std::hash::{Hash, Hasher};
 use std::slice::Iter;
 
 use rayon::prelude::*;
+#[cfg(feature = "serde")]
 use serde::de::{Error as DError, Visitor};
+#[cfg(feature = "serde")]
 use serde::{Deserialize, Deserializer, Serialize, Serializer};
 use std::str::{FromStr, SplitWhitespace};
 use zeroize::Zeroize;
 use hash2curve::HashToCurveXmd;
 
-#[derive(Clone, Debug)]
+/// How the sign of `y` of a compressed point is chosen among `y` and `-y`
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum SignConvention {
+    /// `y` is odd, as in SEC1
+    Parity,
+    /// `y > (p-1)/2`, as in the ZCash encoding of BLS12-381
+    LexicographicallyLargest,
+}
+
+/// Flag of the ZCash encoding of BLS12-381 points set on compressed points
+#[cfg(feature = "bls381")]
+pub(crate) const ZCASH_COMPRESSED: u8 = 0x80;
+/// Flag of the ZCash encoding set on the identity
+#[cfg(feature = "bls381")]
+pub(crate) const ZCASH_IDENTITY: u8 = 0x40;
+/// Flag of the ZCash encoding set when `y` is lexicographically largest
+#[cfg(feature = "bls381")]
+pub(crate) const ZCASH_SIGN: u8 = 0x20;
+
+/// Whether the field element `y` is greater than `(p-1)/2`, i.e. greater than `-y`
+pub(crate) fn is_lexicographically_largest(y: &BigNum) -> bool {
+    let mut neg_y = MODULUS;
+    neg_y.sub(y);
+    neg_y.norm();
+    BigNum::comp(y, &neg_y) > 0
+}
+
+#[derive(Clone)]
 pub struct G1 {
     value: GroupG1,
 }
 
 impl GroupElement for G1 {
+    const SERIALIZED_SIZE: usize = GROUP_G1_SIZE;
+    const COMPRESSED_SIZE: usize = FIELD_ORDER_ELEMENT_SIZE;
+
     fn new() -> Self {
         Self {
             value: GroupG1::new(),
@@ -48,8 +85,9 @@ impl GroupElement for G1 {
         self.value.inf()
     }
 
+    #[allow(deprecated)]
     fn from_msg_hash(msg: &[u8]) -> Self {
-        GroupG1::mapit(&hash_msg(msg)).into()
+        GroupG1::map_to_point(&crate::utils::hash_msg(msg)).into()
     }
 
     #[cfg(feature = "bls381")]
@@ -81,7 +119,7 @@ impl GroupElement for G1 {
                 GROUP_G1_SIZE,
             ));
         }
-        Ok(GroupG1::frombytes(bytes).into())
+        Ok(GroupG1::from_bytes(bytes).into())
     }
 
     fn write_to_slice(&self, target: &mut [u8]) -> Result<(), SerzDeserzError> {
@@ -96,9 +134,7 @@ impl GroupElement for G1 {
     }
 
     fn write_to_slice_unchecked(&self, target: &mut [u8]) {
-        let mut temp = GroupG1::new();
-        temp.copy(&self.value);
-        temp.tobytes(target, false);
+        self.value.to_bytes(target);
     }
 
     fn add_assign_(&mut self, b: &Self) {
@@ -122,7 +158,7 @@ impl GroupElement for G1 {
     }
 
     fn scalar_mul_const_time(&self, a: &CurveOrderElement) -> Self {
-        self.value.mul(&a.to_bignum()).into()
+        self.value.scalar_mul(&a.to_bignum()).into()
     }
 
     fn double(&self) -> Self {
@@ -162,7 +198,15 @@ impl GroupElement for G1 {
     }
 
     fn has_correct_order(&self) -> bool {
-        return self.value.mul(&CURVE_ORDER).is_infinity();
+        return self.value.scalar_mul(&CURVE_ORDER).is_infinity();
+    }
+
+    fn msm(points: &[Self], scalars: &[CurveOrderElement]) -> Result<Self, ValueError> {
+        G1Vector::multi_scalar_mul_const_time_without_precomputation(points, scalars)
+    }
+
+    fn msm_var_time(points: &[Self], scalars: &[CurveOrderElement]) -> Result<Self, ValueError> {
+        G1Vector::multi_scalar_mul_var_time_without_precomputation(points, scalars)
     }
 }
 
@@ -171,27 +215,184 @@ impl G1 {
     /// Faster than doing the scalar multiplications individually and then adding them. Uses lookup table
     /// returns self*a + h*b
     pub fn binary_scalar_mul(&self, h: &Self, a: &CurveOrderElement, b: &CurveOrderElement) -> Self {
-        self.value
-            .mul2(&a.to_bignum(), &h.to_ecp(), &b.to_bignum())
-            .into()
+        CurrentBackend::g1_mul2(&self.value, &a.to_bignum(), &h.value, &b.to_bignum()).into()
     }
 
     pub fn to_bytes(&self) -> [u8; 2 * FIELD_ORDER_ELEMENT_SIZE] {
         let mut bytes = [0u8; 2 * FIELD_ORDER_ELEMENT_SIZE + 1];
-        let mut temp = GroupG1::new();
-        temp.copy(&self.value);
-        temp.tobytes(bytes.as_mut(), false);
+        self.value.to_bytes(bytes.as_mut());
         *array_ref![bytes, 1, 2 * FIELD_ORDER_ELEMENT_SIZE]
     }
 
-    pub fn to_compressed_bytes(&self) -> [u8; FIELD_ORDER_ELEMENT_SIZE] {
+    /// The first `SHORT_HEX_CHARS` hex characters of `to_compressed_bytes`, for logs
+    pub fn fmt_short(&self) -> String {
+        self.fmt_truncated(SHORT_HEX_CHARS)
+    }
+
+    /// The first `chars` hex characters of `to_compressed_bytes`
+    pub fn fmt_truncated(&self, chars: usize) -> String {
+        fmt_truncated_hex(&self.to_compressed_bytes(), chars)
+    }
+
+    pub fn to_compressed_bytes(&self) -> [u8; Self::COMPRESSED_SIZE] {
         let mut bytes = [0u8; FIELD_ORDER_ELEMENT_SIZE + 1];
-        let mut temp = GroupG1::new();
-        temp.copy(&self.value);
-        temp.tobytes(bytes.as_mut(), true);
+        CurrentBackend::g1_to_compressed_bytes(&self.value, bytes.as_mut());
         bytes[1] |= (bytes[0] & 1) << 7;
         *array_ref![bytes, 1, FIELD_ORDER_ELEMENT_SIZE]
     }
+
+    /// Sign of `y` under `convention`, false for the identity
+    pub fn y_sign(&self, convention: SignConvention) -> bool {
+        if self.is_identity() {
+            return false;
+        }
+        let y = self.value.gety();
+        match convention {
+            SignConvention::Parity => y.parity() == 1,
+            SignConvention::LexicographicallyLargest => is_lexicographically_largest(&y),
+        }
+    }
+
+    /// `x` big endian after a byte `0x02 | sign` with the sign of `y` under `convention`, the
+    /// layout of SEC1 for `SignConvention::Parity`. The identity is all zeros.
+    pub fn to_compressed_bytes_with_sign(
+        &self,
+        convention: SignConvention,
+    ) -> [u8; FIELD_ORDER_ELEMENT_SIZE + 1] {
+        let mut bytes = [0u8; FIELD_ORDER_ELEMENT_SIZE + 1];
+        if self.is_identity() {
+            return bytes;
+        }
+        bytes[0] = 0x02 | self.y_sign(convention) as u8;
+        self.value.getx().tobytes(&mut bytes[1..]);
+        bytes
+    }
+
+    /// Inverse of `to_compressed_bytes_with_sign`. Fails if `x` is not reduced or not the
+    /// coordinate of a point on the curve.
+    pub fn from_compressed_bytes_with_sign(
+        bytes: &[u8],
+        convention: SignConvention,
+    ) -> Result<Self, SerzDeserzError> {
+        if bytes.len() != FIELD_ORDER_ELEMENT_SIZE + 1 {
+            return Err(SerzDeserzError::G1BytesIncorrectSize(
+                bytes.len(),
+                FIELD_ORDER_ELEMENT_SIZE + 1,
+            ));
+        }
+        if bytes.iter().all(|b| *b == 0) {
+            return Ok(Self::identity());
+        }
+        if bytes[0] & 0xFE != 0x02 {
+            return Err(SerzDeserzError::InvalidEncoding);
+        }
+        let x = BigNum::frombytes(&bytes[1..]);
+        if BigNum::comp(&x, &MODULUS) >= 0 {
+            return Err(SerzDeserzError::InvalidEncoding);
+        }
+        let p = Self {
+            value: GroupG1::new_bigint(&x, 0),
+        };
+        if p.is_identity() {
+            return Err(SerzDeserzError::InvalidEncoding);
+        }
+        if p.y_sign(convention) == (bytes[0] & 1 == 1) {
+            Ok(p)
+        } else {
+            Ok(p.negation())
+        }
+    }
+
+    /// Compressed ZCash encoding of BLS12-381 (blst, py_ecc, IETF drafts): `x` big endian with
+    /// the flags `ZCASH_COMPRESSED`, `ZCASH_IDENTITY` and `ZCASH_SIGN` in the top 3 bits. The sign
+    /// is `SignConvention::LexicographicallyLargest`.
+    #[cfg(feature = "bls381")]
+    pub fn to_zcash_bytes(&self) -> [u8; FIELD_ORDER_ELEMENT_SIZE] {
+        let mut bytes = [0u8; FIELD_ORDER_ELEMENT_SIZE];
+        if self.is_identity() {
+            bytes[0] = ZCASH_COMPRESSED | ZCASH_IDENTITY;
+            return bytes;
+        }
+        self.value.getx().tobytes(&mut bytes);
+        bytes[0] |= ZCASH_COMPRESSED;
+        if self.y_sign(SignConvention::LexicographicallyLargest) {
+            bytes[0] |= ZCASH_SIGN;
+        }
+        bytes
+    }
+
+    /// Inverse of `to_zcash_bytes`. Fails on non-canonical encodings and on points not on the
+    /// curve or not in the subgroup.
+    #[cfg(feature = "bls381")]
+    pub fn from_zcash_bytes(bytes: &[u8]) -> Result<Self, SerzDeserzError> {
+        if bytes.len() != FIELD_ORDER_ELEMENT_SIZE {
+            return Err(SerzDeserzError::G1BytesIncorrectSize(
+                bytes.len(),
+                FIELD_ORDER_ELEMENT_SIZE,
+            ));
+        }
+        let flags = bytes[0];
+        let mut x_bytes = *array_ref![bytes, 0, FIELD_ORDER_ELEMENT_SIZE];
+        x_bytes[0] &= !(ZCASH_COMPRESSED | ZCASH_IDENTITY | ZCASH_SIGN);
+        if flags & ZCASH_COMPRESSED == 0 {
+            return Err(SerzDeserzError::InvalidEncoding);
+        }
+        if flags & ZCASH_IDENTITY != 0 {
+            if flags & ZCASH_SIGN != 0 || x_bytes.iter().any(|b| *b != 0) {
+                return Err(SerzDeserzError::InvalidEncoding);
+            }
+            return Ok(Self::identity());
+        }
+        let x = BigNum::frombytes(&x_bytes);
+        if BigNum::comp(&x, &MODULUS) >= 0 {
+            return Err(SerzDeserzError::InvalidEncoding);
+        }
+        let mut p = Self {
+            value: GroupG1::new_bigint(&x, 0),
+        };
+        if p.is_identity() {
+            return Err(SerzDeserzError::InvalidEncoding);
+        }
+        if p.y_sign(SignConvention::LexicographicallyLargest) != (flags & ZCASH_SIGN != 0) {
+            p = p.negation();
+        }
+        if !p.has_correct_order() {
+            return Err(SerzDeserzError::InvalidEncoding);
+        }
+        Ok(p)
+    }
+
+    /// Uncompressed `x || y` like `to_bytes` but with both coordinates little endian
+    pub fn to_bytes_le(&self) -> [u8; 2 * FIELD_ORDER_ELEMENT_SIZE] {
+        let mut bytes = self.to_bytes();
+        for c in bytes.chunks_mut(FIELD_ORDER_ELEMENT_SIZE) {
+            c.reverse();
+        }
+        bytes
+    }
+
+    /// Point from uncompressed `x || y` with both coordinates little endian
+    pub fn from_bytes_le(bytes: &[u8]) -> Result<Self, SerzDeserzError> {
+        if bytes.len() != 2 * FIELD_ORDER_ELEMENT_SIZE {
+            return Err(SerzDeserzError::G1BytesIncorrectSize(
+                bytes.len(),
+                2 * FIELD_ORDER_ELEMENT_SIZE,
+            ));
+        }
+        let mut be = *array_ref![bytes, 0, 2 * FIELD_ORDER_ELEMENT_SIZE];
+        for c in be.chunks_mut(FIELD_ORDER_ELEMENT_SIZE) {
+            c.reverse();
+        }
+        Ok(Self::from(&be))
+    }
+}
+
+/// Multiple of the generator by a uniformly random curve order element, `rng.gen::<G1>()`
+#[cfg(feature = "rand-distributions")]
+impl rand::distributions::Distribution<G1> for rand::distributions::Standard {
+    fn sample<R: rand::Rng + ?Sized>(&self, rng: &mut R) -> G1 {
+        G1::generator().scalar_mul_const_time(&self.sample(rng))
+    }
 }
 
 impl From<[u8; 2 * FIELD_ORDER_ELEMENT_SIZE]> for G1 {
@@ -236,7 +437,8 @@ impl_group_element_lookup_table!(G1, G1LookupTable);
 // Represents an element of the sub-group of the elliptic curve over the prime field
 impl_optmz_scalar_mul_ops!(G1, GroupG1, G1LookupTable);
 
-#[derive(Clone, Debug, Serialize, Deserialize)]
+#[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct G1Vector {
     elems: Vec<G1>,
 }
@@ -245,8 +447,14 @@ impl_group_elem_vec_ops!(G1, G1Vector);
 
 impl_group_elem_vec_product_ops!(G1, G1Vector, G1LookupTable);
 
+impl_precomputed_msm!(G1, G1Vector, G1PrecomputedMsm);
+
 impl_group_elem_vec_conversions!(G1, G1Vector);
 
+impl_group_elem_vec_bytes!(G1, G1Vector, GROUP_G1_SIZE);
+impl_group_elem_vec_fmt!(G1Vector);
+impl_group_elem_arbitrary!(G1, G1Vector);
+
 /// Parse given hex string as FP
 pub fn parse_hex_as_fp(iter: &mut SplitWhitespace) -> Result<FP, SerzDeserzError> {
     // Logic almost copied from AMCL but with error handling and constant time execution.
@@ -278,6 +486,31 @@ mod test {
     use std::time::Instant;
     use hash2curve::DomainSeparationTag;
 
+    #[cfg(feature = "rand-distributions")]
+    #[test]
+    fn test_standard_distribution() {
+        use rand::{rngs::StdRng, Rng, SeedableRng};
+        let mut rng = StdRng::seed_from_u64(7);
+        let p: G1 = rng.gen();
+        assert!(!p.is_identity());
+        assert_ne!(rng.gen::<G1>(), p);
+        let s: CurveOrderElement = StdRng::seed_from_u64(7).gen();
+        assert_eq!(G1::generator() * &s, p);
+    }
+
+    #[test]
+    fn test_bytes_le() {
+        for _ in 0..10 {
+            let p = G1::random();
+            let bytes = p.to_bytes_le();
+            let be = p.to_bytes();
+            assert_eq!(bytes[0], be[FIELD_ORDER_ELEMENT_SIZE - 1]);
+            assert_eq!(bytes[FIELD_ORDER_ELEMENT_SIZE], be[2 * FIELD_ORDER_ELEMENT_SIZE - 1]);
+            assert_eq!(G1::from_bytes_le(&bytes).unwrap(), p);
+        }
+        assert!(G1::from_bytes_le(&[0u8; 10]).is_err());
+    }
+
     #[test]
     fn compressed_tests() {
         let g1 = G1::generator();
@@ -290,6 +523,104 @@ mod test {
         assert_eq!(e, e1);
     }
 
+    #[test]
+    fn test_compression_sign_conventions() {
+        for convention in [SignConvention::Parity, SignConvention::LexicographicallyLargest] {
+            for _ in 0..10 {
+                let p = G1::random();
+                let bytes = p.to_compressed_bytes_with_sign(convention);
+                assert_eq!(G1::from_compressed_bytes_with_sign(&bytes, convention).unwrap(), p);
+                // The negation has the other sign and the same x
+                assert_ne!(p.y_sign(convention), (-&p).y_sign(convention));
+                let neg = (-&p).to_compressed_bytes_with_sign(convention);
+                assert_eq!(neg[1..], bytes[1..]);
+                assert_ne!(neg[0], bytes[0]);
+            }
+            let bytes = G1::identity().to_compressed_bytes_with_sign(convention);
+            assert!(G1::from_compressed_bytes_with_sign(&bytes, convention)
+                .unwrap()
+                .is_identity());
+            assert!(G1::from_compressed_bytes_with_sign(&bytes[1..], convention).is_err());
+            let mut bytes = G1::random().to_compressed_bytes_with_sign(convention);
+            bytes[0] = 0x04;
+            assert!(G1::from_compressed_bytes_with_sign(&bytes, convention).is_err());
+        }
+
+        // Parity is the sign of AMCL's compressed encoding
+        let p = G1::random();
+        let mut amcl = [0u8; FIELD_ORDER_ELEMENT_SIZE + 1];
+        p.to_ecp().tobytes(&mut amcl, true);
+        assert_eq!(p.to_compressed_bytes_with_sign(SignConvention::Parity), amcl);
+
+        // In the ZCash encoding the sign flag 0x20 of the generator is not set
+        #[cfg(feature = "bls381")]
+        assert!(!G1::generator().y_sign(SignConvention::LexicographicallyLargest));
+    }
+
+    #[cfg(feature = "bls381")]
+    #[test]
+    fn test_zcash_encoding() {
+        use crate::types::GroupG1;
+        use subtle_encoding::hex;
+
+        let g = G1::generator().to_zcash_bytes();
+        assert_eq!(
+            String::from_utf8(hex::encode(&g[..])).unwrap(),
+            "97f1d3a73197d7942695638c4fa9ac0fc3688c4f9774b905a14e3a3f171bac586c55e83ff97a1aeffb3af00adb22c6bb"
+        );
+        let mut identity = [0u8; FIELD_ORDER_ELEMENT_SIZE];
+        identity[0] = 0xc0;
+        assert_eq!(G1::identity().to_zcash_bytes(), identity);
+        assert!(G1::from_zcash_bytes(&identity).unwrap().is_identity());
+
+        for _ in 0..10 {
+            let p = G1::random();
+            for q in [p.clone(), -&p].iter() {
+                let bytes = q.to_zcash_bytes();
+                assert_eq!(&G1::from_zcash_bytes(&bytes).unwrap(), q);
+            }
+        }
+
+        let mut bad = g;
+        // Not compressed
+        bad[0] &= !ZCASH_COMPRESSED;
+        assert!(G1::from_zcash_bytes(&bad).is_err());
+        // Identity with a sign or a coordinate
+        let mut bad = identity;
+        bad[0] |= ZCASH_SIGN;
+        assert!(G1::from_zcash_bytes(&bad).is_err());
+        let mut bad = identity;
+        bad[FIELD_ORDER_ELEMENT_SIZE - 1] = 1;
+        assert!(G1::from_zcash_bytes(&bad).is_err());
+        assert!(G1::from_zcash_bytes(&g[1..]).is_err());
+        // `x + p` instead of `x`
+        let mut x = G1::generator().to_ecp().getx();
+        x.add(&MODULUS);
+        x.norm();
+        let mut bad = [0u8; FIELD_ORDER_ELEMENT_SIZE];
+        x.tobytes(&mut bad);
+        bad[0] |= ZCASH_COMPRESSED;
+        assert!(G1::from_zcash_bytes(&bad).is_err());
+
+        // A point on the curve outside the subgroup, found by trying `x = 0, 1, ...`
+        let mut x = BigNum::new();
+        loop {
+            x.inc(1);
+            let p = G1 {
+                value: GroupG1::new_bigint(&x, 0),
+            };
+            if !p.is_identity() && !p.has_correct_order() {
+                break;
+            }
+        }
+        let mut bytes = [0u8; FIELD_ORDER_ELEMENT_SIZE];
+        x.tobytes(&mut bytes);
+        bytes[0] |= ZCASH_COMPRESSED;
+        assert!(G1::from_zcash_bytes(&bytes).is_err());
+        bytes[0] |= ZCASH_SIGN;
+        assert!(G1::from_zcash_bytes(&bytes).is_err());
+    }
+
     #[test]
     fn test_hash_to_curve() {
         let e = G1::from_hex("1 060595F69756F0528EAACAC84E6844CEBCB844042DC456EC738F7332221BF27CA38E6C3DC2FD8710FFE37ECB92779E46 1 153980BAF12F8EB31BBF9D8CACE7FB750AB76CEE4DD118CCB1A27637BE878544128ABDDA8F7E22A393B7920547BF6DEC 2 13317C30F3A0D636D56A23C34FDD80B891ECBDE7C2B7D6E16B0F4B0B7E6D26CB6147ACDE629C4A23C57400D203A9FB84".to_string()).unwrap();
@@ -359,7 +690,7 @@ mod test {
         let lookup_tables: Vec<_> = gv
             .as_slice()
             .into_iter()
-            .map(|e| e.to_wnaf_lookup_table(5))
+            .map(|e| e.to_wnaf_lookup_table(WNAF_WIDTH))
             .collect();
 
         let f_refs: Vec<&CurveOrderElement> = fs.iter().map(|f| f).collect();
@@ -380,7 +711,7 @@ mod test {
         let group_elem_multiples: Vec<_> = gv
             .as_slice()
             .into_iter()
-            .map(|e| e.get_multiples(7))
+            .map(|e| e.get_multiples((1 << MSM_CONST_TIME_WINDOW) - 1))
             .collect();
 
         start = Instant::now();
@@ -421,7 +752,7 @@ mod test {
         let mut gs = vec![];
 
         let n = 64;
-        let w = 5;
+        let w = WNAF_WIDTH;
 
         for _ in 0..n {
             fs.push(CurveOrderElement::random());
@@ -455,4 +786,50 @@ mod test {
             start.elapsed()
         );
     }
+
+    #[test]
+    fn test_vector_and_table_fmt() {
+        let v = G1Vector::random(6);
+        let debug = format!("{:?}", v);
+        assert!(debug.starts_with("G1Vector { len: 6, digests: ["));
+        assert!(debug.ends_with(", ..] }"));
+        assert_ne!(debug, format!("{:?}", G1Vector::random(6)));
+        assert_eq!(format!("{:?}", G1Vector::new(0)), "G1Vector { len: 0, digests: [] }");
+
+        let display = format!("{}", v);
+        assert_eq!(display.lines().count(), 8);
+        assert!(display.contains(&format!("  5: {}", v[5])));
+
+        let table = G1LookupTable::from(&G1::random());
+        assert!(format!("{:?}", table).starts_with(&format!(
+            "G1LookupTable {{ len: {}, digests: [",
+            WNAF_LOOKUP_TABLE_SIZE
+        )));
+        let last = 2 * WNAF_LOOKUP_TABLE_SIZE - 1;
+        assert!(format!("{}", table).contains(&format!("  {}A: {}", last, table.select(last))));
+    }
+
+    #[test]
+    fn test_short_fmt() {
+        let g = G1::random();
+        let short = g.fmt_short();
+        assert_eq!(short.len(), SHORT_HEX_CHARS + 2);
+        assert!(short.ends_with(".."));
+        let prefix: String = g.to_compressed_bytes()[..SHORT_HEX_CHARS / 2]
+            .iter()
+            .map(|b| format!("{:02x}", b))
+            .collect();
+        assert_eq!(short, prefix + "..");
+        assert_eq!(format!("{:#}", g), short);
+        assert_eq!(format!("{:?}", g), format!("G1({})", short));
+        assert_eq!(format!("{:#?}", g), format!("G1({})", g));
+        assert_eq!(g.fmt_truncated(2 * G1::COMPRESSED_SIZE).len(), 2 * G1::COMPRESSED_SIZE);
+
+        let s = CurveOrderElement::random();
+        assert_eq!(format!("{:#}", s), s.fmt_short());
+        assert_eq!(format!("{:?}", s), format!("CurveOrderElement({})", s.fmt_short()));
+
+        let v = G1Vector::random(3);
+        assert!(format!("{:#}", v).contains(&format!("  2: {}\n", v[2].fmt_short())));
+    }
 }
@@ -1,9 +1,14 @@
-use crate::constants::{CURVE_ORDER, GROUP_G2_SIZE, FIELD_ORDER_ELEMENT_SIZE};
+use crate::constants::{
+    CURVE_ORDER, FIELD_ORDER_ELEMENT_SIZE, GROUP_G2_SIZE, MSM_CONST_TIME_WINDOW,
+    WNAF_LOOKUP_TABLE_SIZE, WNAF_WIDTH, MAX_PIPPENGER_WINDOW, PIPPENGER_THRESHOLD,
+    pippenger_window,
+};
 use crate::errors::{SerzDeserzError, ValueError};
 use crate::curve_order_elem::{CurveOrderElement, CurveOrderElementVector};
-use crate::group_elem::{GroupElement, GroupElementVector};
+use crate::group_elem::{GroupElement, GroupElementVector, STREAM_CHUNK_SIZE};
+use crate::backend::{Group, MapToPoint};
 use crate::types::{GroupG2, FP2, BigNum};
-use crate::utils::hash_msg;
+use crate::utils::{fmt_digests, fmt_truncated_hex, SHORT_HEX_CHARS};
 use std::iter;
 use std::ops::{Add, AddAssign, Index, IndexMut, Mul, Neg, Sub, SubAssign};
 
@@ -12,18 +17,51 @@ use std::hash::{Hash, Hasher};
 use std::slice::Iter;
 
 use crate::group_elem_g1::parse_hex_as_fp;
+#[cfg(feature = "bls381")]
+use crate::constants::MODULUS;
+#[cfg(feature = "bls381")]
+use crate::group_elem_g1::{
+    is_lexicographically_largest, ZCASH_COMPRESSED, ZCASH_IDENTITY, ZCASH_SIGN,
+};
 use rayon::prelude::*;
+#[cfg(feature = "serde")]
 use serde::{Serialize, Serializer, Deserialize, Deserializer};
+#[cfg(feature = "serde")]
 use serde::de::{Error as DError, Visitor};
 use std::str::SplitWhitespace;
 use zeroize::Zeroize;
 
-#[derive(Clone, Debug)]
+/// Order of the two components of an element `c0 + c1*u` of the quadratic extension field in
+/// uncompressed encodings of G2 points. Libraries differ: this crate and EIP-2537 put `c0` first
+/// while Zcash style encodings (blst, py_ecc) and the EIP-197 precompiles put `c1` first.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum Fp2Ordering {
+    /// `c0 || c1`
+    RealFirst,
+    /// `c1 || c0`
+    ImaginaryFirst,
+}
+
+/// Swap the components of both coordinates if the ordering is not the one of `to_bytes`
+fn reorder_fp2s(bytes: &mut [u8; GROUP_G2_SIZE], ordering: Fp2Ordering) {
+    if ordering == Fp2Ordering::ImaginaryFirst {
+        for coordinate in bytes.chunks_mut(2 * FIELD_ORDER_ELEMENT_SIZE) {
+            let (c0, c1) = coordinate.split_at_mut(FIELD_ORDER_ELEMENT_SIZE);
+            c0.swap_with_slice(c1);
+        }
+    }
+}
+
+#[derive(Clone)]
 pub struct G2 {
     value: GroupG2,
 }
 
 impl GroupElement for G2 {
+    const SERIALIZED_SIZE: usize = GROUP_G2_SIZE;
+    const COMPRESSED_SIZE: usize = 2 * FIELD_ORDER_ELEMENT_SIZE;
+
     fn new() -> Self {
         Self {
             value: GroupG2::new(),
@@ -49,8 +87,9 @@ impl GroupElement for G2 {
         self.value.inf()
     }
 
+    #[allow(deprecated)]
     fn from_msg_hash(msg: &[u8]) -> Self {
-        GroupG2::mapit(&hash_msg(msg)).into()
+        GroupG2::map_to_point(&crate::utils::hash_msg(msg)).into()
     }
 
     /// TODO: call the appropriate function once implemented in `hash2curve` crate
@@ -71,7 +110,7 @@ impl GroupElement for G2 {
                 GROUP_G2_SIZE,
             ));
         }
-        Ok(GroupG2::frombytes(bytes).into())
+        Ok(GroupG2::from_bytes(bytes).into())
     }
 
     fn write_to_slice(&self, target: &mut [u8]) -> Result<(), SerzDeserzError> {
@@ -86,9 +125,7 @@ impl GroupElement for G2 {
     }
 
     fn write_to_slice_unchecked(&self, target: &mut [u8]) {
-        let mut temp = GroupG2::new();
-        temp.copy(&self.value);
-        temp.tobytes(target);
+        self.value.to_bytes(target);
     }
 
     fn add_assign_(&mut self, b: &Self) {
@@ -112,7 +149,7 @@ impl GroupElement for G2 {
     }
 
     fn scalar_mul_const_time(&self, a: &CurveOrderElement) -> Self {
-        self.value.mul(&a.to_bignum()).into()
+        self.value.scalar_mul(&a.to_bignum()).into()
     }
 
     fn double(&self) -> Self {
@@ -152,18 +189,36 @@ impl GroupElement for G2 {
     }
 
     fn has_correct_order(&self) -> bool {
-        return self.value.mul(&CURVE_ORDER).is_infinity();
+        return self.value.scalar_mul(&CURVE_ORDER).is_infinity();
+    }
+
+    fn msm(points: &[Self], scalars: &[CurveOrderElement]) -> Result<Self, ValueError> {
+        G2Vector::multi_scalar_mul_const_time_without_precomputation(points, scalars)
+    }
+
+    fn msm_var_time(points: &[Self], scalars: &[CurveOrderElement]) -> Result<Self, ValueError> {
+        G2Vector::multi_scalar_mul_var_time_without_precomputation(points, scalars)
     }
 }
 
 impl G2 {
     pub fn to_bytes(&self) -> [u8; 4 * FIELD_ORDER_ELEMENT_SIZE] {
         let mut bytes = [0u8; 4 * FIELD_ORDER_ELEMENT_SIZE];
-        self.value.tobytes(&mut bytes[..]);
+        self.value.to_bytes(&mut bytes[..]);
         bytes
     }
 
-    pub fn to_compressed_bytes(&self) -> [u8; 2 * FIELD_ORDER_ELEMENT_SIZE] {
+    /// The first `SHORT_HEX_CHARS` hex characters of `to_compressed_bytes`, for logs
+    pub fn fmt_short(&self) -> String {
+        self.fmt_truncated(SHORT_HEX_CHARS)
+    }
+
+    /// The first `chars` hex characters of `to_compressed_bytes`
+    pub fn fmt_truncated(&self, chars: usize) -> String {
+        fmt_truncated_hex(&self.to_compressed_bytes(), chars)
+    }
+
+    pub fn to_compressed_bytes(&self) -> [u8; Self::COMPRESSED_SIZE] {
         let mut bytes = [0u8; 2 * FIELD_ORDER_ELEMENT_SIZE];
         let mut temp = GroupG2::new();
         temp.copy(&self.value);
@@ -179,6 +234,136 @@ impl G2 {
         bytes[0] |= parity << 6;
         bytes
     }
+
+    /// Uncompressed `x || y` with the components of both coordinates in the given order, each big
+    /// endian. `RealFirst` gives the same bytes as `to_bytes`.
+    pub fn to_bytes_with_ordering(&self, ordering: Fp2Ordering) -> [u8; GROUP_G2_SIZE] {
+        let mut bytes = self.to_bytes();
+        reorder_fp2s(&mut bytes, ordering);
+        bytes
+    }
+
+    /// Point from uncompressed `x || y` with the components of both coordinates in the given
+    /// order. Fails if the bytes are not a point on the curve.
+    pub fn from_bytes_with_ordering(
+        bytes: &[u8],
+        ordering: Fp2Ordering,
+    ) -> Result<Self, SerzDeserzError> {
+        if bytes.len() != GROUP_G2_SIZE {
+            return Err(SerzDeserzError::G2BytesIncorrectSize(
+                bytes.len(),
+                GROUP_G2_SIZE,
+            ));
+        }
+        let mut ordered = *array_ref![bytes, 0, GROUP_G2_SIZE];
+        reorder_fp2s(&mut ordered, ordering);
+        let p = Self::from(&ordered);
+        // Bytes not on the curve decode to the identity
+        if p.is_identity() && ordered.iter().any(|b| *b != 0) {
+            return Err(SerzDeserzError::CannotParseFP);
+        }
+        Ok(p)
+    }
+
+    /// Compressed ZCash encoding of BLS12-381 (blst, py_ecc, IETF drafts): `x` as `c1 || c0`, each
+    /// big endian, with the flags of `G1::to_zcash_bytes` in the top 3 bits. `y` is compared by
+    /// `c1`, or by `c0` if `c1` is 0.
+    #[cfg(feature = "bls381")]
+    pub fn to_zcash_bytes(&self) -> [u8; 2 * FIELD_ORDER_ELEMENT_SIZE] {
+        let mut bytes = [0u8; 2 * FIELD_ORDER_ELEMENT_SIZE];
+        if self.is_identity() {
+            bytes[0] = ZCASH_COMPRESSED | ZCASH_IDENTITY;
+            return bytes;
+        }
+        let mut temp = GroupG2::new();
+        temp.copy(&self.value);
+        temp.affine();
+        temp.x.getb().tobytes(&mut bytes[..FIELD_ORDER_ELEMENT_SIZE]);
+        temp.x.geta().tobytes(&mut bytes[FIELD_ORDER_ELEMENT_SIZE..]);
+        bytes[0] |= ZCASH_COMPRESSED;
+        if Self::zcash_sign(&mut temp.y) {
+            bytes[0] |= ZCASH_SIGN;
+        }
+        bytes
+    }
+
+    /// Inverse of `to_zcash_bytes`. Fails on non-canonical encodings and on points not on the
+    /// curve or not in the subgroup.
+    #[cfg(feature = "bls381")]
+    pub fn from_zcash_bytes(bytes: &[u8]) -> Result<Self, SerzDeserzError> {
+        if bytes.len() != 2 * FIELD_ORDER_ELEMENT_SIZE {
+            return Err(SerzDeserzError::G2BytesIncorrectSize(
+                bytes.len(),
+                2 * FIELD_ORDER_ELEMENT_SIZE,
+            ));
+        }
+        let flags = bytes[0];
+        let mut x_bytes = *array_ref![bytes, 0, 2 * FIELD_ORDER_ELEMENT_SIZE];
+        x_bytes[0] &= !(ZCASH_COMPRESSED | ZCASH_IDENTITY | ZCASH_SIGN);
+        if flags & ZCASH_COMPRESSED == 0 {
+            return Err(SerzDeserzError::InvalidEncoding);
+        }
+        if flags & ZCASH_IDENTITY != 0 {
+            if flags & ZCASH_SIGN != 0 || x_bytes.iter().any(|b| *b != 0) {
+                return Err(SerzDeserzError::InvalidEncoding);
+            }
+            return Ok(Self::identity());
+        }
+        let c1 = BigNum::frombytes(&x_bytes[..FIELD_ORDER_ELEMENT_SIZE]);
+        let c0 = BigNum::frombytes(&x_bytes[FIELD_ORDER_ELEMENT_SIZE..]);
+        if BigNum::comp(&c0, &MODULUS) >= 0 || BigNum::comp(&c1, &MODULUS) >= 0 {
+            return Err(SerzDeserzError::InvalidEncoding);
+        }
+        let mut p = Self {
+            value: GroupG2::new_fp2(&FP2::new_bigs(&c0, &c1)),
+        };
+        if p.is_identity() {
+            return Err(SerzDeserzError::InvalidEncoding);
+        }
+        if Self::zcash_sign(&mut p.value.gety()) != (flags & ZCASH_SIGN != 0) {
+            p = p.negation();
+        }
+        if !p.has_correct_order() {
+            return Err(SerzDeserzError::InvalidEncoding);
+        }
+        Ok(p)
+    }
+
+    /// Whether the affine `y` is lexicographically largest in the ZCash encoding
+    #[cfg(feature = "bls381")]
+    fn zcash_sign(y: &mut FP2) -> bool {
+        let c1 = y.getb();
+        if c1.iszilch() {
+            is_lexicographically_largest(&y.geta())
+        } else {
+            is_lexicographically_largest(&c1)
+        }
+    }
+
+    /// Uncompressed coordinates like `to_bytes` but with each of the 4 base field elements little
+    /// endian
+    pub fn to_bytes_le(&self) -> [u8; 4 * FIELD_ORDER_ELEMENT_SIZE] {
+        let mut bytes = self.to_bytes();
+        for c in bytes.chunks_mut(FIELD_ORDER_ELEMENT_SIZE) {
+            c.reverse();
+        }
+        bytes
+    }
+
+    /// Point from uncompressed coordinates with each of the 4 base field elements little endian
+    pub fn from_bytes_le(bytes: &[u8]) -> Result<Self, SerzDeserzError> {
+        if bytes.len() != GROUP_G2_SIZE {
+            return Err(SerzDeserzError::G2BytesIncorrectSize(
+                bytes.len(),
+                GROUP_G2_SIZE,
+            ));
+        }
+        let mut be = *array_ref![bytes, 0, GROUP_G2_SIZE];
+        for c in be.chunks_mut(FIELD_ORDER_ELEMENT_SIZE) {
+            c.reverse();
+        }
+        Ok(Self::from(&be))
+    }
 }
 
 impl From<[u8; 2*FIELD_ORDER_ELEMENT_SIZE]> for G2 {
@@ -244,7 +429,8 @@ impl_group_element_lookup_table!(G2, G2LookupTable);
 // Represents an element of the sub-group of the elliptic curve over prime the extension field
 impl_optmz_scalar_mul_ops!(G2, GroupG2, G2LookupTable);
 
-#[derive(Clone, Debug, Serialize, Deserialize)]
+#[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct G2Vector {
     elems: Vec<G2>,
 }
@@ -253,8 +439,14 @@ impl_group_elem_vec_ops!(G2, G2Vector);
 
 impl_group_elem_vec_product_ops!(G2, G2Vector, G2LookupTable);
 
+impl_precomputed_msm!(G2, G2Vector, G2PrecomputedMsm);
+
 impl_group_elem_vec_conversions!(G2, G2Vector);
 
+impl_group_elem_vec_bytes!(G2, G2Vector, GROUP_G2_SIZE);
+impl_group_elem_vec_fmt!(G2Vector);
+impl_group_elem_arbitrary!(G2, G2Vector);
+
 impl G2 {
     /// Computes sum of 2 scalar multiplications.
     /// Faster than doing the scalar multiplications individually and then adding them. Uses lookup table
@@ -266,6 +458,12 @@ impl G2 {
         G2Vector::multi_scalar_mul_const_time_without_precomputation(group_elems, field_elems)
             .unwrap()
     }
+
+    /// `self + g*tweak` for the standard generator `g`, the public counterpart of adding `tweak`
+    /// to a secret key
+    pub fn tweak_add(&self, tweak: &CurveOrderElement) -> Self {
+        Self::generator().binary_scalar_mul(self, tweak, &CurveOrderElement::one())
+    }
 }
 
 #[cfg(test)]
@@ -291,6 +489,104 @@ mod test {
         }
     }
 
+    #[test]
+    fn test_fp2_ordering() {
+        use super::Fp2Ordering;
+        use crate::constants::FIELD_ORDER_ELEMENT_SIZE;
+        let f = FIELD_ORDER_ELEMENT_SIZE;
+        let p = G2::random();
+        let real_first = p.to_bytes_with_ordering(Fp2Ordering::RealFirst);
+        assert_eq!(real_first[..], p.to_bytes()[..]);
+        let imaginary_first = p.to_bytes_with_ordering(Fp2Ordering::ImaginaryFirst);
+        assert_eq!(imaginary_first[..f], real_first[f..2 * f]);
+        assert_eq!(imaginary_first[f..2 * f], real_first[..f]);
+        assert_eq!(imaginary_first[2 * f..3 * f], real_first[3 * f..]);
+        assert_eq!(imaginary_first[3 * f..], real_first[2 * f..3 * f]);
+
+        assert_eq!(G2::from_bytes_with_ordering(&real_first, Fp2Ordering::RealFirst).unwrap(), p);
+        assert_eq!(G2::from_bytes_with_ordering(&imaginary_first, Fp2Ordering::ImaginaryFirst).unwrap(), p);
+        // Decoding with the wrong ordering does not give a point on the curve
+        assert!(G2::from_bytes_with_ordering(&imaginary_first, Fp2Ordering::RealFirst).is_err());
+        assert!(G2::from_bytes_with_ordering(&real_first[1..], Fp2Ordering::RealFirst).is_err());
+    }
+
+    #[test]
+    fn test_bytes_le() {
+        use crate::constants::FIELD_ORDER_ELEMENT_SIZE;
+        for _ in 0..10 {
+            let p = G2::random();
+            let bytes = p.to_bytes_le();
+            let be = p.to_bytes();
+            for i in 0..4 {
+                assert_eq!(bytes[i * FIELD_ORDER_ELEMENT_SIZE], be[(i + 1) * FIELD_ORDER_ELEMENT_SIZE - 1]);
+            }
+            assert_eq!(G2::from_bytes_le(&bytes).unwrap(), p);
+        }
+        assert!(G2::from_bytes_le(&[0u8; 10]).is_err());
+    }
+
+    #[cfg(feature = "bls381")]
+    #[test]
+    fn test_zcash_encoding() {
+        use super::{GroupG2, FP2};
+        use crate::constants::{FIELD_ORDER_ELEMENT_SIZE, MODULUS};
+        use crate::group_elem_g1::{ZCASH_COMPRESSED, ZCASH_SIGN};
+        use crate::types::BigNum;
+        use subtle_encoding::hex;
+
+        let f = FIELD_ORDER_ELEMENT_SIZE;
+        let g = G2::generator().to_zcash_bytes();
+        assert_eq!(
+            String::from_utf8(hex::encode(&g[..])).unwrap(),
+            "93e02b6052719f607dacd3a088274f65596bd0d09920b61ab5da61bbdc7f5049334cf11213945d57e5ac7d055d042b7e024aa2b2f08f0a91260805272dc51051c6e47ad4fa403b02b4510b647ae3d1770bac0326a805bbefd48056c8c121bdb8"
+        );
+        let mut identity = [0u8; 2 * FIELD_ORDER_ELEMENT_SIZE];
+        identity[0] = 0xc0;
+        assert_eq!(G2::identity().to_zcash_bytes()[..], identity[..]);
+        assert!(G2::from_zcash_bytes(&identity).unwrap().is_identity());
+
+        for _ in 0..10 {
+            let p = G2::random();
+            for q in [p.clone(), -&p].iter() {
+                let bytes = q.to_zcash_bytes();
+                assert_eq!(&G2::from_zcash_bytes(&bytes).unwrap(), q);
+            }
+        }
+
+        let mut bad = g;
+        bad[0] &= !ZCASH_COMPRESSED;
+        assert!(G2::from_zcash_bytes(&bad).is_err());
+        let mut bad = identity;
+        bad[0] |= ZCASH_SIGN;
+        assert!(G2::from_zcash_bytes(&bad).is_err());
+        assert!(G2::from_zcash_bytes(&g[1..]).is_err());
+        // `c0 + p` instead of `c0`
+        let mut c0 = BigNum::frombytes(&g[f..]);
+        c0.add(&MODULUS);
+        c0.norm();
+        let mut bad = g;
+        c0.tobytes(&mut bad[f..]);
+        assert!(G2::from_zcash_bytes(&bad).is_err());
+
+        // A point on the curve outside the subgroup, found by trying `x = 1, 2, ...`
+        let mut c0 = BigNum::new();
+        loop {
+            c0.inc(1);
+            let p = G2 {
+                value: GroupG2::new_fp2(&FP2::new_big(&c0)),
+            };
+            if !p.is_identity() && !p.has_correct_order() {
+                break;
+            }
+        }
+        let mut bytes = [0u8; 2 * FIELD_ORDER_ELEMENT_SIZE];
+        c0.tobytes(&mut bytes[f..]);
+        bytes[0] |= ZCASH_COMPRESSED;
+        assert!(G2::from_zcash_bytes(&bytes).is_err());
+        bytes[0] |= ZCASH_SIGN;
+        assert!(G2::from_zcash_bytes(&bytes).is_err());
+    }
+
     #[test]
     fn test_parse_hex_for_fp2() {
         // TODO:
@@ -0,0 +1,365 @@
+//! Generators used by protocols built over the groups. Most protocols use the standard generators
+//! returned by `GroupElement::generator` but some specs (e.g. certain credential systems) mandate
+//! different, often hash-derived, base points.
+//!
+//! `GeneratorSuite` derives generators exactly like the spec it names, so they match other
+//! implementations of the spec byte for byte. The Bulletproofs generators of dalek's
+//! `PedersenGens` and `BulletproofGens` are points of ristretto255, which is none of the curves
+//! supported here, so they have no suite.
+
+use crate::errors::ValueError;
+use crate::group_elem::{GroupElement, GroupElementVector};
+use crate::group_elem_g1::{G1Vector, G1};
+#[cfg(any(feature = "bls381", feature = "bn254"))]
+use crate::group_elem_g2::G2;
+#[cfg(feature = "bls381")]
+use crate::hash_to_curve::{
+    expand_message_xmd, expand_message_xof, hash_to_g1, hash_to_g1_xof, Expander,
+};
+
+use crate::utils::hash_msg_to_point;
+#[cfg(any(feature = "bls381", feature = "bn254"))]
+use crate::utils::domain_separated;
+
+use rayon::prelude::*;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+const GROUP_PARAMS_G1_DST: &[u8] = b"AMCL_WRAPPER_GROUP_PARAMS_G1";
+#[cfg(any(feature = "bls381", feature = "bn254"))]
+const GROUP_PARAMS_G2_DST: &[u8] = b"AMCL_WRAPPER_GROUP_PARAMS_G2";
+const GENERATORS_U_DST: &[u8] = b"AMCL_WRAPPER_GENERATORS_U";
+const GENERATORS_G_VEC_DST: &[u8] = b"AMCL_WRAPPER_GENERATORS_G_VEC";
+const GENERATORS_H_VEC_DST: &[u8] = b"AMCL_WRAPPER_GENERATORS_H_VEC";
+
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct GroupParams {
+    g1: G1,
+    #[cfg(any(feature = "bls381", feature = "bn254"))]
+    g2: G2,
+}
+
+impl Default for GroupParams {
+    /// Parameters with the standard generators of the groups
+    fn default() -> Self {
+        Self {
+            g1: G1::generator(),
+            #[cfg(any(feature = "bls381", feature = "bn254"))]
+            g2: G2::generator(),
+        }
+    }
+}
+
+impl GroupParams {
+    /// Create parameters from the given generators. The identity element is not a generator and is rejected.
+    #[cfg(any(feature = "bls381", feature = "bn254"))]
+    pub fn new(g1: G1, g2: G2) -> Result<Self, ValueError> {
+        if g1.is_identity() || g2.is_identity() {
+            return Err(ValueError::IdentityElement);
+        }
+        Ok(Self { g1, g2 })
+    }
+
+    /// Create parameters from the given generator. The identity element is not a generator and is rejected.
+    #[cfg(not(any(feature = "bls381", feature = "bn254")))]
+    pub fn new(g1: G1) -> Result<Self, ValueError> {
+        if g1.is_identity() {
+            return Err(ValueError::IdentityElement);
+        }
+        Ok(Self { g1 })
+    }
+
+    /// Derive generators by hashing `label`. Different labels give independent generators whose
+    /// discrete log relative to the standard generators is unknown. The G1 generator is
+    /// `hash_msg_to_point`, G2 has no `hash_to_curve` so the G2 generator is `from_msg_hash` of
+    /// the domain separated label.
+    pub fn from_label(label: &[u8]) -> Self {
+        Self {
+            // The tags are not empty
+            g1: hash_msg_to_point(label, GROUP_PARAMS_G1_DST).unwrap(),
+            #[cfg(any(feature = "bls381", feature = "bn254"))]
+            #[allow(deprecated)]
+            g2: G2::from_msg_hash(&domain_separated(label, GROUP_PARAMS_G2_DST)),
+        }
+    }
+
+    /// Generator of group G1
+    pub fn g1(&self) -> &G1 {
+        &self.g1
+    }
+
+    /// Generator of group G2
+    #[cfg(any(feature = "bls381", feature = "bn254"))]
+    pub fn g2(&self) -> &G2 {
+        &self.g2
+    }
+}
+
+/// Vectors of G1 generators `g_vec` and `h_vec` and a generator `u` as used by Bulletproofs and
+/// vector Pedersen commitments, all hash-derived from a label so provers and verifiers agree on
+/// them without exchanging them. The `i`th generators only depend on the label and `i`, so the
+/// vectors can grow without changing existing elements.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Generators {
+    label: Vec<u8>,
+    g_vec: G1Vector,
+    h_vec: G1Vector,
+    u: G1,
+}
+
+impl Generators {
+    /// Derive `capacity` generators in each of `g_vec` and `h_vec`
+    pub fn new(label: &[u8], capacity: usize) -> Self {
+        let mut gens = Self {
+            label: label.to_vec(),
+            g_vec: G1Vector::with_capacity(capacity),
+            h_vec: G1Vector::with_capacity(capacity),
+            // The tags are not empty
+            u: hash_msg_to_point(label, GENERATORS_U_DST).unwrap(),
+        };
+        gens.grow(capacity);
+        gens
+    }
+
+    /// Derive more generators so both vectors have at least `capacity` elements. Does nothing if
+    /// they already do.
+    pub fn grow(&mut self, capacity: usize) {
+        let len = self.g_vec.len();
+        if capacity <= len {
+            return;
+        }
+        let g: Vec<G1> = (len..capacity)
+            .into_par_iter()
+            .map(|i| self.derive(GENERATORS_G_VEC_DST, i))
+            .collect();
+        let h: Vec<G1> = (len..capacity)
+            .into_par_iter()
+            .map(|i| self.derive(GENERATORS_H_VEC_DST, i))
+            .collect();
+        self.g_vec.extend(g);
+        self.h_vec.extend(h);
+    }
+
+    fn derive(&self, dst: &[u8], i: usize) -> G1 {
+        hash_msg_to_point(&[&self.label, &(i as u64).to_be_bytes()[..]].concat(), dst).unwrap()
+    }
+
+    pub fn label(&self) -> &[u8] {
+        &self.label
+    }
+
+    /// Number of elements in each of `g_vec` and `h_vec`
+    pub fn capacity(&self) -> usize {
+        self.g_vec.len()
+    }
+
+    pub fn g_vec(&self) -> &G1Vector {
+        &self.g_vec
+    }
+
+    pub fn h_vec(&self) -> &G1Vector {
+        &self.h_vec
+    }
+
+    pub fn u(&self) -> &G1 {
+        &self.u
+    }
+
+    /// Check that the generators are the ones derived from the label, eg. after deserializing them
+    pub fn is_valid(&self) -> bool {
+        self.g_vec.len() == self.h_vec.len() && *self == Self::new(&self.label, self.capacity())
+    }
+}
+
+/// Generator derivation procedures of specs, selectable by name
+#[cfg(feature = "bls381")]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum GeneratorSuite {
+    /// `create_generators` of the BBS signature draft, ciphersuite `BLS12-381-SHA-256`
+    BbsBls12381Sha256,
+    /// `create_generators` of the BBS signature draft, ciphersuite `BLS12-381-SHAKE-256`
+    BbsBls12381Shake256,
+}
+
+#[cfg(feature = "bls381")]
+impl GeneratorSuite {
+    pub const ALL: [GeneratorSuite; 2] = [
+        GeneratorSuite::BbsBls12381Sha256,
+        GeneratorSuite::BbsBls12381Shake256,
+    ];
+
+    /// Suite with the given `name`, None if there is none
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.iter().find(|s| s.name() == name).copied()
+    }
+
+    /// Name of the suite, the `api_id` of the ciphersuite for BBS
+    pub fn name(&self) -> &'static str {
+        match self {
+            GeneratorSuite::BbsBls12381Sha256 => "BBS_BLS12381G1_XMD:SHA-256_SSWU_RO_H2G_HM2S_",
+            GeneratorSuite::BbsBls12381Shake256 => "BBS_BLS12381G1_XOF:SHAKE-256_SSWU_RO_H2G_HM2S_",
+        }
+    }
+
+    /// `count` generators from `generator_seed`, `create_generators(count, generator_seed)`
+    pub fn create_generators(&self, count: usize, generator_seed: &[u8]) -> Vec<G1> {
+        let api_id = self.name().as_bytes();
+        let seed_dst = [api_id, b"SIG_GENERATOR_SEED_"].concat();
+        let generator_dst = [api_id, b"SIG_GENERATOR_DST_"].concat();
+        let expand: Expander = match self {
+            GeneratorSuite::BbsBls12381Sha256 => expand_message_xmd,
+            GeneratorSuite::BbsBls12381Shake256 => expand_message_xof,
+        };
+        let hash = match self {
+            GeneratorSuite::BbsBls12381Sha256 => hash_to_g1,
+            GeneratorSuite::BbsBls12381Shake256 => hash_to_g1_xof,
+        };
+        // Neither tag is empty and 48 bytes are in range, so nothing below fails
+        let mut v = expand(generator_seed, &seed_dst, 48).unwrap();
+        (1..=count as u64)
+            .map(|i| {
+                v = expand(&[&v[..], &i.to_be_bytes()].concat(), &seed_dst, 48).unwrap();
+                hash(&v, &generator_dst).unwrap()
+            })
+            .collect()
+    }
+
+    /// The first `count` message generators, `Q_1` followed by `H_1, ..., H_(count-1)`
+    pub fn message_generators(&self, count: usize) -> Vec<G1> {
+        let seed = [self.name().as_bytes(), b"MESSAGE_GENERATOR_SEED"].concat();
+        self.create_generators(count, &seed)
+    }
+
+    /// The fixed point `P1` of the ciphersuite
+    pub fn p1(&self) -> G1 {
+        let seed = [self.name().as_bytes(), b"BP_MESSAGE_GENERATOR_SEED"].concat();
+        self.create_generators(1, &seed).remove(0)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::curve_order_elem::CurveOrderElement;
+
+    #[test]
+    fn test_default_params() {
+        let params = GroupParams::default();
+        assert_eq!(*params.g1(), G1::generator());
+        #[cfg(any(feature = "bls381", feature = "bn254"))]
+        assert_eq!(*params.g2(), G2::generator());
+    }
+
+    #[test]
+    fn test_params_from_label() {
+        let p1 = GroupParams::from_label(b"test protocol");
+        let p2 = GroupParams::from_label(b"test protocol");
+        let p3 = GroupParams::from_label(b"another protocol");
+        assert_eq!(p1, p2);
+        assert_ne!(p1.g1(), p3.g1());
+        assert_ne!(*p1.g1(), G1::generator());
+        assert!(p1.g1().has_correct_order());
+        #[cfg(any(feature = "bls381", feature = "bn254"))]
+        {
+            assert_ne!(p1.g2(), p3.g2());
+            assert!(p1.g2().has_correct_order());
+        }
+    }
+
+    #[test]
+    fn test_params_reject_identity() {
+        let g1 = G1::generator() * CurveOrderElement::random();
+        #[cfg(any(feature = "bls381", feature = "bn254"))]
+        {
+            let g2 = G2::generator() * CurveOrderElement::random();
+            let params = GroupParams::new(g1.clone(), g2.clone()).unwrap();
+            assert_eq!(*params.g1(), g1);
+            assert_eq!(*params.g2(), g2);
+            assert!(GroupParams::new(G1::identity(), g2.clone()).is_err());
+            assert!(GroupParams::new(g1, G2::identity()).is_err());
+        }
+        #[cfg(not(any(feature = "bls381", feature = "bn254")))]
+        {
+            let params = GroupParams::new(g1.clone()).unwrap();
+            assert_eq!(*params.g1(), g1);
+            assert!(GroupParams::new(G1::identity()).is_err());
+        }
+    }
+
+    #[test]
+    fn test_generators() {
+        let gens = Generators::new(b"test bulletproofs", 4);
+        assert_eq!(gens.capacity(), 4);
+        assert_eq!(gens.h_vec().len(), 4);
+        assert_eq!(gens, Generators::new(b"test bulletproofs", 4));
+        assert_ne!(gens.u(), Generators::new(b"another label", 4).u());
+        let mut all: Vec<G1> = gens
+            .g_vec()
+            .iter()
+            .chain(gens.h_vec().iter())
+            .cloned()
+            .collect();
+        all.push(gens.u().clone());
+        let mut all = G1Vector::from(all);
+        all.sort_dedup();
+        assert_eq!(all.len(), 9);
+
+        // Growing keeps existing generators and matches deriving with the larger capacity
+        let mut grown = gens.clone();
+        grown.grow(10);
+        assert_eq!(grown, Generators::new(b"test bulletproofs", 10));
+        assert_eq!(grown.g_vec()[..4], gens.g_vec()[..]);
+        grown.grow(2);
+        assert_eq!(grown.capacity(), 10);
+
+        #[cfg(feature = "serde")]
+        {
+            let s = serde_json::to_string(&grown).unwrap();
+            let d: Generators = serde_json::from_str(&s).unwrap();
+            assert_eq!(d, grown);
+        }
+        assert!(grown.is_valid());
+        let mut bad = grown.clone();
+        bad.u = G1::random();
+        assert!(!bad.is_valid());
+    }
+
+    #[cfg(feature = "bls381")]
+    #[test]
+    fn test_generator_suite() {
+        use subtle_encoding::hex;
+
+        // The BBS draft uses the compressed ZCash encoding
+        let zcash = |p: &G1| String::from_utf8(hex::encode(&p.to_zcash_bytes()[..])).unwrap();
+        // `P1` of the test vectors of the BBS draft
+        assert_eq!(
+            zcash(&GeneratorSuite::BbsBls12381Sha256.p1()),
+            "a8ce256102840821a3e94ea9025e4662b205762f9776b3a766c872b948f1fd225e7c59698588e70d11406d161b4e28c9"
+        );
+        assert_eq!(
+            zcash(&GeneratorSuite::BbsBls12381Shake256.p1()),
+            "8929dfbc7e6642c4ed9cba0856e493f8b9d7d5fcb0c31ef8fdcd34d50648a56c795e106e9eada6e0bda386b414150755"
+        );
+
+        for suite in GeneratorSuite::ALL.iter() {
+            assert_eq!(GeneratorSuite::from_name(suite.name()), Some(*suite));
+            let gens = suite.message_generators(4);
+            assert_eq!(gens.len(), 4);
+            assert_eq!(suite.message_generators(2)[..], gens[..2]);
+            let mut all = G1Vector::from(gens.clone());
+            all.sort_dedup();
+            assert_eq!(all.len(), 4);
+            assert!(gens
+                .iter()
+                .all(|g| g.has_correct_order() && !g.is_identity()));
+        }
+        assert_ne!(
+            GeneratorSuite::BbsBls12381Sha256.message_generators(1),
+            GeneratorSuite::BbsBls12381Shake256.message_generators(1)
+        );
+        assert_eq!(GeneratorSuite::from_name("bulletproofs"), None);
+    }
+}
@@ -0,0 +1,430 @@
+//! Hashing to group G1 exactly as specified by RFC 9380, for interoperability with other
+//! implementations of protocols hashing to the curve, e.g. the generators of BBS signatures. The
+//! expanders `expand_message_xmd` with SHA-256 and `expand_message_xof` with SHAKE256 are
+//! available on every curve, the suites `BLS12381G1_XMD:SHA-256_SSWU_RO_` and
+//! `BLS12381G1_XOF:SHAKE256_SSWU_RO_` on BLS12-381.
+//!
+//! `GroupElement::hash_to_curve` and `utils::hash_msg_to_point` follow an earlier draft of the
+//! RFC, whose tags are encoded differently, so their outputs differ from these. They are kept so
+//! existing outputs do not change. None of this is constant time.
+
+use crate::errors::ValueError;
+#[cfg(feature = "bls381")]
+use crate::{
+    constants::MODULUS,
+    group_elem::GroupElement,
+    group_elem_g1::G1,
+    types::{BigNum, DoubleBigNum, GroupG1, Limb, FP},
+};
+
+use sha2::Sha256;
+use sha3::digest::{ExtendableOutput, FixedOutput, Input, XofReader};
+use sha3::Shake256;
+
+/// Maximum byte size of the output of `expand_message_xmd` and `expand_message_xof`
+pub const MAX_EXPAND_SIZE: usize = 65535;
+
+/// `expand_message_xmd` or `expand_message_xof`, taking the message, tag and output length
+pub type Expander = fn(&[u8], &[u8], usize) -> Result<Vec<u8>, ValueError>;
+
+const SHA256_OUTPUT_SIZE: usize = 32;
+const SHA256_BLOCK_SIZE: usize = 64;
+const OVERSIZE_DST_PREFIX: &[u8] = b"H2C-OVERSIZE-DST-";
+
+/// `DST || I2OSP(len(DST), 1)`, where tags longer than 255 bytes are first hashed with
+/// `oversize`. Fails for an empty tag.
+fn dst_prime(dst: &[u8], oversize: fn(&[u8]) -> Vec<u8>) -> Result<Vec<u8>, ValueError> {
+    if dst.is_empty() {
+        return Err(ValueError::IncorrectSize(0));
+    }
+    let mut dst = if dst.len() > 255 {
+        oversize(&[OVERSIZE_DST_PREFIX, dst].concat())
+    } else {
+        dst.to_vec()
+    };
+    dst.push(dst.len() as u8);
+    Ok(dst)
+}
+
+/// `expand_message_xmd` of RFC 9380 section 5.3.1 with SHA-256. Fails for an empty `dst` or if
+/// `len` is more than 255 hash outputs.
+pub fn expand_message_xmd(msg: &[u8], dst: &[u8], len: usize) -> Result<Vec<u8>, ValueError> {
+    let ell = len.div_ceil(SHA256_OUTPUT_SIZE);
+    if ell > 255 || len > MAX_EXPAND_SIZE {
+        return Err(ValueError::OutOfRange(len));
+    }
+    let dst_prime = dst_prime(dst, |d| {
+        let mut hasher = Sha256::default();
+        hasher.input(d);
+        hasher.fixed_result().to_vec()
+    })?;
+    let mut hasher = Sha256::default();
+    hasher.input(&[0u8; SHA256_BLOCK_SIZE][..]);
+    hasher.input(msg);
+    hasher.input((len as u16).to_be_bytes());
+    hasher.input([0u8]);
+    hasher.input(&dst_prime);
+    let b_0 = hasher.fixed_result();
+
+    let mut out = Vec::with_capacity(ell * SHA256_OUTPUT_SIZE);
+    let mut b_i = [0u8; SHA256_OUTPUT_SIZE];
+    for i in 1..=ell {
+        // b_i = H(strxor(b_0, b_(i-1)) || I2OSP(i, 1) || DST_prime), with b_0 alone for b_1
+        let mut hasher = Sha256::default();
+        let x: Vec<u8> = b_0.iter().zip(b_i.iter()).map(|(a, b)| a ^ b).collect();
+        hasher.input(&x);
+        hasher.input([i as u8]);
+        hasher.input(&dst_prime);
+        b_i.copy_from_slice(&hasher.fixed_result());
+        out.extend_from_slice(&b_i);
+    }
+    out.truncate(len);
+    Ok(out)
+}
+
+/// `expand_message_xof` of RFC 9380 section 5.3.2 with SHAKE256. Fails for an empty `dst` or if
+/// `len` is more than `MAX_EXPAND_SIZE`.
+pub fn expand_message_xof(msg: &[u8], dst: &[u8], len: usize) -> Result<Vec<u8>, ValueError> {
+    if len > MAX_EXPAND_SIZE {
+        return Err(ValueError::OutOfRange(len));
+    }
+    let dst_prime = dst_prime(dst, |d| {
+        let mut hasher = Shake256::default();
+        hasher.input(d);
+        let mut h = vec![0u8; 64];
+        hasher.xof_result().read(&mut h);
+        h
+    })?;
+    let mut hasher = Shake256::default();
+    hasher.input(msg);
+    hasher.input((len as u16).to_be_bytes());
+    hasher.input(&dst_prime);
+    let mut out = vec![0u8; len];
+    hasher.xof_result().read(&mut out);
+    Ok(out)
+}
+
+/// Byte size `L` of the expanded message of a field element of BLS12-381
+#[cfg(feature = "bls381")]
+const FIELD_EXPAND_SIZE: usize = 64;
+
+/// `A'` of the curve isogenous to G1
+#[cfg(feature = "bls381")]
+const ISO_A: &str = "144698a3b8e9433d693a02c96d4982b0ea985383ee66a8d8e8981aefd881ac98936f8da0e0f97f5cf428082d584c1d";
+/// `B'` of the curve isogenous to G1
+#[cfg(feature = "bls381")]
+const ISO_B: &str = "12e2908d11688030018b12e8753eee3b2016c1f0f24f4070a0b9c14fcef35ef55a23215a316ceaa5d1cc48e98e172be0";
+/// Coefficients `k_(1, i)` of the numerator of `x` of the isogeny map
+#[cfg(feature = "bls381")]
+const X_NUM: [&str; 12] = [
+    "11a05f2b1e833340b809101dd99815856b303e88a2d7005ff2627b56cdb4e2c85610c2d5f2e62d6eaeac1662734649b7",
+    "17294ed3e943ab2f0588bab22147a81c7c17e75b2f6a8417f565e33c70d1e86b4838f2a6f318c356e834eef1b3cb83bb",
+    "d54005db97678ec1d1048c5d10a9a1bce032473295983e56878e501ec68e25c958c3e3d2a09729fe0179f9dac9edcb0",
+    "1778e7166fcc6db74e0609d307e55412d7f5e4656a8dbf25f1b33289f1b330835336e25ce3107193c5b388641d9b6861",
+    "e99726a3199f4436642b4b3e4118e5499db995a1257fb3f086eeb65982fac18985a286f301e77c451154ce9ac8895d9",
+    "1630c3250d7313ff01d1201bf7a74ab5db3cb17dd952799b9ed3ab9097e68f90a0870d2dcae73d19cd13c1c66f652983",
+    "d6ed6553fe44d296a3726c38ae652bfb11586264f0f8ce19008e218f9c86b2a8da25128c1052ecaddd7f225a139ed84",
+    "17b81e7701abdbe2e8743884d1117e53356de5ab275b4db1a682c62ef0f2753339b7c8f8c8f475af9ccb5618e3f0c88e",
+    "80d3cf1f9a78fc47b90b33563be990dc43b756ce79f5574a2c596c928c5d1de4fa295f296b74e956d71986a8497e317",
+    "169b1f8e1bcfa7c42e0c37515d138f22dd2ecb803a0c5c99676314baf4bb1b7fa3190b2edc0327797f241067be390c9e",
+    "10321da079ce07e272d8ec09d2565b0dfa7dccdde6787f96d50af36003b14866f69b771f8c285decca67df3f1605fb7b",
+    "6e08c248e260e70bd1e962381edee3d31d79d7e22c837bc23c0bf1bc24c6b68c24b1b80b64d391fa9c8ba2e8ba2d229",
+];
+/// Coefficients `k_(2, i)` of the denominator of `x` of the isogeny map
+#[cfg(feature = "bls381")]
+const X_DEN: [&str; 11] = [
+    "8ca8d548cff19ae18b2e62f4bd3fa6f01d5ef4ba35b48ba9c9588617fc8ac62b558d681be343df8993cf9fa40d21b1c",
+    "12561a5deb559c4348b4711298e536367041e8ca0cf0800c0126c2588c48bf5713daa8846cb026e9e5c8276ec82b3bff",
+    "b2962fe57a3225e8137e629bff2991f6f89416f5a718cd1fca64e00b11aceacd6a3d0967c94fedcfcc239ba5cb83e19",
+    "3425581a58ae2fec83aafef7c40eb545b08243f16b1655154cca8abc28d6fd04976d5243eecf5c4130de8938dc62cd8",
+    "13a8e162022914a80a6f1d5f43e7a07dffdfc759a12062bb8d6b44e833b306da9bd29ba81f35781d539d395b3532a21e",
+    "e7355f8e4e667b955390f7f0506c6e9395735e9ce9cad4d0a43bcef24b8982f7400d24bc4228f11c02df9a29f6304a5",
+    "772caacf16936190f3e0c63e0596721570f5799af53a1894e2e073062aede9cea73b3538f0de06cec2574496ee84a3a",
+    "14a7ac2a9d64a8b230b3f5b074cf01996e7f63c21bca68a81996e1cdf9822c580fa5b9489d11e2d311f7d99bbdcc5a5e",
+    "a10ecf6ada54f825e920b3dafc7a3cce07f8d1d7161366b74100da67f39883503826692abba43704776ec3a79a1d641",
+    "95fc13ab9e92ad4476d6e3eb3a56680f682b4ee96f7d03776df533978f31c1593174e4b4b7865002d6384d168ecdd0a",
+    "1",
+];
+/// Coefficients `k_(3, i)` of the numerator of `y` of the isogeny map
+#[cfg(feature = "bls381")]
+const Y_NUM: [&str; 16] = [
+    "90d97c81ba24ee0259d1f094980dcfa11ad138e48a869522b52af6c956543d3cd0c7aee9b3ba3c2be9845719707bb33",
+    "134996a104ee5811d51036d776fb46831223e96c254f383d0f906343eb67ad34d6c56711962fa8bfe097e75a2e41c696",
+    "cc786baa966e66f4a384c86a3b49942552e2d658a31ce2c344be4b91400da7d26d521628b00523b8dfe240c72de1f6",
+    "1f86376e8981c217898751ad8746757d42aa7b90eeb791c09e4a3ec03251cf9de405aba9ec61deca6355c77b0e5f4cb",
+    "8cc03fdefe0ff135caf4fe2a21529c4195536fbe3ce50b879833fd221351adc2ee7f8dc099040a841b6daecf2e8fedb",
+    "16603fca40634b6a2211e11db8f0a6a074a7d0d4afadb7bd76505c3d3ad5544e203f6326c95a807299b23ab13633a5f0",
+    "4ab0b9bcfac1bbcb2c977d027796b3ce75bb8ca2be184cb5231413c4d634f3747a87ac2460f415ec961f8855fe9d6f2",
+    "987c8d5333ab86fde9926bd2ca6c674170a05bfe3bdd81ffd038da6c26c842642f64550fedfe935a15e4ca31870fb29",
+    "9fc4018bd96684be88c9e221e4da1bb8f3abd16679dc26c1e8b6e6a1f20cabe69d65201c78607a360370e577bdba587",
+    "e1bba7a1186bdb5223abde7ada14a23c42a0ca7915af6fe06985e7ed1e4d43b9b3f7055dd4eba6f2bafaaebca731c30",
+    "19713e47937cd1be0dfd0b8f1d43fb93cd2fcbcb6caf493fd1183e416389e61031bf3a5cce3fbafce813711ad011c132",
+    "18b46a908f36f6deb918c143fed2edcc523559b8aaf0c2462e6bfe7f911f643249d9cdf41b44d606ce07c8a4d0074d8e",
+    "b182cac101b9399d155096004f53f447aa7b12a3426b08ec02710e807b4633f06c851c1919211f20d4c04f00b971ef8",
+    "245a394ad1eca9b72fc00ae7be315dc757b3b080d4c158013e6632d3c40659cc6cf90ad1c232a6442d9d3f5db980133",
+    "5c129645e44cf1102a159f748c4a3fc5e673d81d7e86568d9ab0f5d396a7ce46ba1049b6579afb7866b1e715475224b",
+    "15e6be4e990f03ce4ea50b3b42df2eb5cb181d8f84965a3957add4fa95af01b2b665027efec01c7704b456be69c8b604",
+];
+/// Coefficients `k_(4, i)` of the denominator of `y` of the isogeny map
+#[cfg(feature = "bls381")]
+const Y_DEN: [&str; 16] = [
+    "16112c4c3a9c98b252181140fad0eae9601a6de578980be6eec3232b5be72e7a07f3688ef60c206d01479253b03663c1",
+    "1962d75c2381201e1a0cbd6c43c348b885c84ff731c4d59ca4a10356f453e01f78a4260763529e3532f6102c2e49a03d",
+    "58df3306640da276faaae7d6e8eb15778c4855551ae7f310c35a5dd279cd2eca6757cd636f96f891e2538b53dbf67f2",
+    "16b7d288798e5395f20d23bf89edb4d1d115c5dbddbcd30e123da489e726af41727364f2c28297ada8d26d98445f5416",
+    "be0e079545f43e4b00cc912f8228ddcc6d19c9f0f69bbb0542eda0fc9dec916a20b15dc0fd2ededda39142311a5001d",
+    "8d9e5297186db2d9fb266eaac783182b70152c65550d881c5ecd87b6f0f5a6449f38db9dfa9cce202c6477faaf9b7ac",
+    "166007c08a99db2fc3ba8734ace9824b5eecfdfa8d0cf8ef5dd365bc400a0051d5fa9c01a58b1fb93d1a1399126a775c",
+    "16a3ef08be3ea7ea03bcddfabba6ff6ee5a4375efa1f4fd7feb34fd206357132b920f5b00801dee460ee415a15812ed9",
+    "1866c8ed336c61231a1be54fd1d74cc4f9fb0ce4c6af5920abc5750c4bf39b4852cfe2f7bb9248836b233d9d55535d4a",
+    "167a55cda70a6e1cea820597d94a84903216f763e13d87bb5308592e7ea7d4fbc7385ea3d529b35e346ef48bb8913f55",
+    "4d2f259eea405bd48f010a01ad2911d9c6dd039bb61a6290e591b36e636a5c871a5c29f4f83060400f8b49cba8f6aa8",
+    "accbb67481d033ff5852c1e48c50c477f94ff8aefce42d28c0f9a88cea7913516f968986f7ebbea9684b529e2561092",
+    "ad6b9514c767fe3c3613144b45f1496543346d98adf02267d5ceef9a00d9b8693000763e3b90ac11e99b138573345cc",
+    "2660400eb2e4f3b628bdd0d53cd76f2bf565b94e72927c1cb748df27942480e420517bd8714cc80d1fadc1326ed06f7",
+    "e0fa1d816ddc03e6b24255e0d7819c171c40f65e273b853324efcd6356caa205ca2f570f13497804415473a1d634b8f",
+    "1",
+];
+#[cfg(feature = "bls381")]
+lazy_static! {
+    static ref ISO_CONSTANTS: IsoConstants = IsoConstants::new();
+}
+
+/// Constants of the simplified SWU map to the curve 11-isogenous to G1 and of the isogeny, RFC
+/// 9380 section 8.8.1 and appendix E.2
+#[cfg(feature = "bls381")]
+struct IsoConstants {
+    a: FP,
+    b: FP,
+    z: FP,
+    x_num: Vec<FP>,
+    x_den: Vec<FP>,
+    y_num: Vec<FP>,
+    y_den: Vec<FP>,
+}
+
+#[cfg(feature = "bls381")]
+impl IsoConstants {
+    fn new() -> Self {
+        let fp = |h: &str| FP::new_big(&BigNum::fromstring(h.to_string()));
+        let fps = |hs: &[&str]| hs.iter().map(|h| fp(h)).collect();
+        Self {
+            a: fp(ISO_A),
+            b: fp(ISO_B),
+            z: FP::new_int(11),
+            x_num: fps(&X_NUM),
+            x_den: fps(&X_DEN),
+            y_num: fps(&Y_NUM),
+            y_den: fps(&Y_DEN),
+        }
+    }
+}
+
+/// `sgn0` of RFC 9380 section 4.1, the parity of the canonical representative
+#[cfg(feature = "bls381")]
+fn sgn0(x: &FP) -> isize {
+    let mut x = *x;
+    x.redc().parity()
+}
+
+/// `x^3 + a*x + b`
+#[cfg(feature = "bls381")]
+fn curve_rhs(x: &FP, a: &FP, b: &FP) -> FP {
+    let mut gx = *x;
+    gx.sqr();
+    gx.add(a);
+    gx.mul(x);
+    gx.add(b);
+    gx.reduce();
+    gx
+}
+
+/// Polynomial with coefficients `k`, lowest degree first, at `x`
+#[cfg(feature = "bls381")]
+fn eval_poly(k: &[FP], x: &FP) -> FP {
+    let mut acc = FP::new();
+    for c in k.iter().rev() {
+        acc.mul(x);
+        acc.add(c);
+        acc.norm();
+    }
+    acc.reduce();
+    acc
+}
+
+/// Simplified SWU map of RFC 9380 section 6.6.2 to the isogenous curve followed by the isogeny
+/// map to G1, so the point is not yet in the subgroup of the curve order
+#[cfg(feature = "bls381")]
+fn map_to_curve(u: &FP) -> GroupG1 {
+    let c = &*ISO_CONSTANTS;
+    // tv1 = Z*u^2, tv2 = Z^2*u^4 + Z*u^2
+    let mut tv1 = *u;
+    tv1.sqr();
+    tv1.mul(&c.z);
+    let mut tv2 = tv1;
+    tv2.sqr();
+    tv2.add(&tv1);
+    tv2.reduce();
+
+    // x1 = (-B/A) * (1 + 1/tv2), or B/(Z*A) if tv2 is zero
+    let mut x1 = if tv2.iszilch() {
+        let mut d = c.z;
+        d.mul(&c.a);
+        d.inverse();
+        d.mul(&c.b);
+        d
+    } else {
+        let mut t = tv2;
+        t.inverse();
+        t.add(&FP::new_int(1));
+        let mut d = c.a;
+        d.inverse();
+        d.mul(&c.b);
+        d.neg();
+        d.mul(&t);
+        d
+    };
+    x1.reduce();
+    let mut gx1 = curve_rhs(&x1, &c.a, &c.b);
+    let (mut x, mut y) = if gx1.jacobi() >= 0 {
+        (x1, gx1.sqrt())
+    } else {
+        let mut x2 = tv1;
+        x2.mul(&x1);
+        x2.reduce();
+        let mut gx2 = curve_rhs(&x2, &c.a, &c.b);
+        (x2, gx2.sqrt())
+    };
+    y.reduce();
+    if sgn0(u) != sgn0(&y) {
+        y.neg();
+        y.reduce();
+    }
+
+    // The 11-isogeny, mapping the exceptional points of zero denominators to the identity
+    let mut x_den = eval_poly(&c.x_den, &x);
+    let mut y_den = eval_poly(&c.y_den, &x);
+    if x_den.iszilch() || y_den.iszilch() {
+        return G1::identity().to_ecp();
+    }
+    x_den.inverse();
+    y_den.inverse();
+    let mut x_num = eval_poly(&c.x_num, &x);
+    x_num.mul(&x_den);
+    let mut y_num = eval_poly(&c.y_num, &x);
+    y_num.mul(&y_den);
+    y_num.mul(&y);
+    x = x_num;
+    GroupG1::new_bigs(&x.redc(), &y_num.redc())
+}
+
+/// `count` field elements of the expanded message, each from `FIELD_EXPAND_SIZE` bytes
+#[cfg(feature = "bls381")]
+fn hash_to_field(
+    msg: &[u8],
+    dst: &[u8],
+    count: usize,
+    expand: Expander,
+) -> Result<Vec<FP>, ValueError> {
+    let bytes = expand(msg, dst, count * FIELD_EXPAND_SIZE)?;
+    Ok(bytes
+        .chunks(FIELD_EXPAND_SIZE)
+        .map(|chunk| {
+            let mut d = DoubleBigNum::new();
+            for b in chunk {
+                d.shl(8);
+                d.w[0] += *b as Limb;
+            }
+            FP::new_big(&d.dmod(&MODULUS))
+        })
+        .collect())
+}
+
+#[cfg(feature = "bls381")]
+fn hash_to_g1_with(msg: &[u8], dst: &[u8], expand: Expander) -> Result<G1, ValueError> {
+    let u = hash_to_field(msg, dst, 2, expand)?;
+    let mut p = map_to_curve(&u[0]);
+    p.add(&map_to_curve(&u[1]));
+    // Clear the cofactor by multiplying with h_eff
+    Ok(p.mul(&BigNum::fromstring("d201000000010001".to_string()))
+        .into())
+}
+
+/// `hash_to_curve` of the suite `BLS12381G1_XMD:SHA-256_SSWU_RO_` of RFC 9380. Fails for an empty
+/// `dst`.
+#[cfg(feature = "bls381")]
+pub fn hash_to_g1(msg: &[u8], dst: &[u8]) -> Result<G1, ValueError> {
+    hash_to_g1_with(msg, dst, expand_message_xmd)
+}
+
+/// `hash_to_curve` of the suite `BLS12381G1_XOF:SHAKE256_SSWU_RO_` of RFC 9380. Fails for an empty
+/// `dst`.
+#[cfg(feature = "bls381")]
+pub fn hash_to_g1_xof(msg: &[u8], dst: &[u8]) -> Result<G1, ValueError> {
+    hash_to_g1_with(msg, dst, expand_message_xof)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use subtle_encoding::hex;
+
+    // Test vectors of RFC 9380 appendix K.1
+    #[test]
+    fn test_expand_message_xmd() {
+        let dst = b"QUUX-V01-CS02-with-expander-SHA256-128";
+        for (msg, expected) in [
+            (
+                &b""[..],
+                "68a985b87eb6b46952128911f2a4412bbc302a9d759667f87f7a21d803f07235",
+            ),
+            (
+                &b"abc"[..],
+                "d8ccab23b5985ccea865c6c97b6e5b8350e794e603b4b97902f53a8a0d605615",
+            ),
+        ]
+        .iter()
+        {
+            let out = expand_message_xmd(msg, dst, 0x20).unwrap();
+            assert_eq!(hex::encode(&out), expected.as_bytes());
+        }
+        assert!(expand_message_xmd(b"", b"", 32).is_err());
+        assert!(expand_message_xmd(b"", dst, 255 * 32 + 1).is_err());
+        assert_eq!(
+            expand_message_xmd(b"", dst, 255 * 32).unwrap().len(),
+            255 * 32
+        );
+    }
+
+    // Test vectors of RFC 9380 appendix J.9.1
+    #[cfg(feature = "bls381")]
+    #[test]
+    fn test_hash_to_g1() {
+        let dst = b"QUUX-V01-CS02-with-BLS12381G1_XMD:SHA-256_SSWU_RO_";
+        for (msg, x, y) in [
+            (
+                &b""[..],
+                "052926add2207b76ca4fa57a8734416c8dc95e24501772c814278700eed6d1e4e8cf62d9c09db0fac349612b759e79a1",
+                "08ba738453bfed09cb546dbb0783dbb3a5f1f566ed67bb6be0e8c67e2e81a4cc68ee29813bb7994998f3eae0c9c6a265",
+            ),
+            (
+                &b"abc"[..],
+                "03567bc5ef9c690c2ab2ecdf6a96ef1c139cc0b2f284dca0a9a7943388a49a3aee664ba5379a7655d3c68900be2f6903",
+                "0b9c15f3fe6e5cf4211f346271d7b01c8f3b28be689c8429c85b67af215533311f0b8dfaaa154fa6b88176c229f2885d",
+            ),
+        ]
+        .iter()
+        {
+            let p = hash_to_g1(msg, dst).unwrap().to_ecp();
+            assert_eq!(p.getx().tostring().to_lowercase(), *x);
+            assert_eq!(p.gety().tostring().to_lowercase(), *y);
+        }
+        assert!(hash_to_g1(b"abc", b"").is_err());
+        let p = hash_to_g1_xof(
+            b"abc",
+            b"QUUX-V01-CS02-with-BLS12381G1_XOF:SHAKE256_SSWU_RO_",
+        )
+        .unwrap();
+        assert!(p.has_correct_order() && !p.is_identity());
+    }
+}
@@ -0,0 +1,179 @@
+//! JSON Web Key (RFC 7517) encoding of group elements and public keys. On secp256k1 and P-256, G1
+//! elements are `EC` keys with `x` and `y` coordinates (RFC 7518, RFC 8812). On the pairing curves,
+//! elements of G1 and G2 are `OKP` keys whose `x` is the compressed point, following the BLS key
+//! representation draft. Binary values are base64url encoded without padding.
+
+#[cfg(any(feature = "bls381", feature = "bn254"))]
+use crate::constants::FIELD_ORDER_ELEMENT_SIZE;
+use crate::errors::SerzDeserzError;
+use crate::group_elem::GroupElement;
+use crate::group_elem_g1::G1;
+#[cfg(any(feature = "bls381", feature = "bn254"))]
+use crate::group_elem_g2::G2;
+
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use serde::{Deserialize, Serialize};
+
+#[cfg(feature = "secp256k1")]
+pub const CRV_G1: &str = "secp256k1";
+#[cfg(feature = "nist256")]
+pub const CRV_G1: &str = "P-256";
+#[cfg(feature = "bls381")]
+pub const CRV_G1: &str = "Bls12381G1";
+#[cfg(feature = "bls381")]
+pub const CRV_G2: &str = "Bls12381G2";
+#[cfg(feature = "bn254")]
+pub const CRV_G1: &str = "Bn254G1";
+#[cfg(feature = "bn254")]
+pub const CRV_G2: &str = "Bn254G2";
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Jwk {
+    pub kty: String,
+    pub crv: String,
+    pub x: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub y: Option<String>,
+}
+
+impl Jwk {
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).unwrap()
+    }
+
+    pub fn from_json(s: &str) -> Result<Self, SerzDeserzError> {
+        serde_json::from_str(s).map_err(|_| SerzDeserzError::InvalidJwk)
+    }
+
+    fn check(&self, kty: &str, crv: &str) -> Result<(), SerzDeserzError> {
+        if self.kty != kty || self.crv != crv {
+            return Err(SerzDeserzError::InvalidJwk);
+        }
+        Ok(())
+    }
+}
+
+fn encode(bytes: &[u8]) -> String {
+    URL_SAFE_NO_PAD.encode(bytes)
+}
+
+fn decode(s: &str, expected_len: usize) -> Result<Vec<u8>, SerzDeserzError> {
+    let bytes = URL_SAFE_NO_PAD
+        .decode(s)
+        .map_err(|_| SerzDeserzError::InvalidJwk)?;
+    if bytes.len() != expected_len {
+        return Err(SerzDeserzError::InvalidJwk);
+    }
+    Ok(bytes)
+}
+
+#[cfg(any(feature = "secp256k1", feature = "nist256"))]
+impl G1 {
+    pub fn to_jwk(&self) -> Jwk {
+        let bytes = self.to_bytes();
+        let (x, y) = bytes.split_at(bytes.len() / 2);
+        Jwk {
+            kty: "EC".to_string(),
+            crv: CRV_G1.to_string(),
+            x: encode(x),
+            y: Some(encode(y)),
+        }
+    }
+
+    /// Rejects keys that are not on the curve or are the identity.
+    pub fn from_jwk(jwk: &Jwk) -> Result<Self, SerzDeserzError> {
+        jwk.check("EC", CRV_G1)?;
+        let size = crate::constants::FIELD_ORDER_ELEMENT_SIZE;
+        let y = jwk.y.as_ref().ok_or(SerzDeserzError::InvalidJwk)?;
+        let mut bytes = vec![4u8];
+        bytes.extend_from_slice(&decode(&jwk.x, size)?);
+        bytes.extend_from_slice(&decode(y, size)?);
+        let p = G1::from_slice(&bytes)?;
+        if p.is_identity() {
+            return Err(SerzDeserzError::InvalidJwk);
+        }
+        Ok(p)
+    }
+}
+
+#[cfg(any(feature = "bls381", feature = "bn254"))]
+impl G1 {
+    pub fn to_jwk(&self) -> Jwk {
+        Jwk {
+            kty: "OKP".to_string(),
+            crv: CRV_G1.to_string(),
+            x: encode(&self.to_compressed_bytes()),
+            y: None,
+        }
+    }
+
+    /// Rejects points that are not in the prime order subgroup or are the identity.
+    pub fn from_jwk(jwk: &Jwk) -> Result<Self, SerzDeserzError> {
+        jwk.check("OKP", CRV_G1)?;
+        let bytes = decode(&jwk.x, FIELD_ORDER_ELEMENT_SIZE)?;
+        let p = G1::from(array_ref![bytes, 0, FIELD_ORDER_ELEMENT_SIZE]);
+        if p.is_identity() || !p.has_correct_order() {
+            return Err(SerzDeserzError::InvalidJwk);
+        }
+        Ok(p)
+    }
+}
+
+#[cfg(any(feature = "bls381", feature = "bn254"))]
+impl G2 {
+    pub fn to_jwk(&self) -> Jwk {
+        Jwk {
+            kty: "OKP".to_string(),
+            crv: CRV_G2.to_string(),
+            x: encode(&self.to_compressed_bytes()),
+            y: None,
+        }
+    }
+
+    /// Rejects points that are not in the prime order subgroup or are the identity.
+    pub fn from_jwk(jwk: &Jwk) -> Result<Self, SerzDeserzError> {
+        jwk.check("OKP", CRV_G2)?;
+        let bytes = decode(&jwk.x, 2 * FIELD_ORDER_ELEMENT_SIZE)?;
+        let p = G2::from(array_ref![bytes, 0, 2 * FIELD_ORDER_ELEMENT_SIZE]);
+        if p.is_identity() || !p.has_correct_order() {
+            return Err(SerzDeserzError::InvalidJwk);
+        }
+        Ok(p)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::curve_order_elem::CurveOrderElement;
+
+    #[test]
+    fn test_g1_jwk() {
+        let p = G1::generator() * CurveOrderElement::random();
+        let jwk = p.to_jwk();
+        assert_eq!(jwk.crv, CRV_G1);
+        let json = jwk.to_json();
+        assert_eq!(G1::from_jwk(&Jwk::from_json(&json).unwrap()).unwrap(), p);
+
+        let mut bad = jwk.clone();
+        bad.crv = "P-384".to_string();
+        assert!(G1::from_jwk(&bad).is_err());
+        let mut bad = jwk.clone();
+        bad.x = encode(&[1u8; 3]);
+        assert!(G1::from_jwk(&bad).is_err());
+        assert!(G1::from_jwk(&G1::identity().to_jwk()).is_err());
+    }
+
+    #[cfg(any(feature = "bls381", feature = "bn254"))]
+    #[test]
+    fn test_g2_jwk() {
+        let p = G2::generator() * CurveOrderElement::random();
+        let jwk = p.to_jwk();
+        assert_eq!(jwk.kty, "OKP");
+        assert!(jwk.y.is_none());
+        assert!(!jwk.to_json().contains("\"y\""));
+        assert_eq!(G2::from_jwk(&jwk).unwrap(), p);
+        assert!(G1::from_jwk(&jwk).is_err());
+    }
+}
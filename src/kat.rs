@@ -0,0 +1,238 @@
+//! Known answer tests of the curve the crate is built for, to be run at startup as a health check
+//! of the linked backend, e.g. as the power-on self-tests of FIPS style deployments. Each test
+//! recomputes a fixed output, a scalar inverse, multiples of the generators, a hash to the curve
+//! or a pairing, and compares it to the value recorded for the curve. Outputs are the byte
+//! encodings of `to_bytes`/`to_vec`, pairings are compared by the SHA-256 digest of theirs.
+
+use crate::curve_order_elem::CurveOrderElement;
+use crate::group_elem::GroupElement;
+use crate::group_elem_g1::G1;
+use crate::utils::hash_msg_to_point;
+#[cfg(any(feature = "bls381", feature = "bn254"))]
+use crate::{extension_field_gt::GT, group_elem_g2::G2};
+
+#[cfg(any(feature = "bls381", feature = "bn254"))]
+use sha2::{Digest, Sha256};
+use std::convert::TryFrom;
+use subtle_encoding::hex;
+
+const KAT_DST: &[u8] = b"AMCL_WRAPPER_KNOWN_ANSWER_TEST";
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum KnownAnswerKind {
+    ScalarArithmetic,
+    GeneratorMultiple,
+    HashToCurve,
+    Pairing,
+}
+
+#[derive(Clone, Debug)]
+pub struct KnownAnswerTest {
+    pub name: &'static str,
+    pub kind: KnownAnswerKind,
+    /// Hex of the expected output
+    pub expected: &'static str,
+    compute: fn() -> Vec<u8>,
+}
+
+impl KnownAnswerTest {
+    /// Output of the test on the linked backend
+    pub fn compute(&self) -> Vec<u8> {
+        (self.compute)()
+    }
+
+    /// Whether the output matches the expected one
+    pub fn run(&self) -> bool {
+        hex::encode(self.compute()) == self.expected.as_bytes()
+    }
+}
+
+/// The fixed scalar of the tests
+fn scalar() -> CurveOrderElement {
+    CurveOrderElement::try_from(&[0x2au8; 32][..]).unwrap()
+}
+
+fn scalar_inverse() -> Vec<u8> {
+    scalar().inverse().to_bytes().to_vec()
+}
+
+fn g1_double() -> Vec<u8> {
+    G1::generator().double().to_vec()
+}
+
+fn g1_mul() -> Vec<u8> {
+    (&G1::generator() * &scalar()).to_vec()
+}
+
+fn g1_mul_const_time() -> Vec<u8> {
+    G1::generator().scalar_mul_const_time(&scalar()).to_vec()
+}
+
+fn hash_to_g1() -> Vec<u8> {
+    hash_msg_to_point(b"known answer test", KAT_DST)
+        .unwrap()
+        .to_vec()
+}
+
+#[cfg(any(feature = "bls381", feature = "bn254"))]
+fn g2_mul() -> Vec<u8> {
+    G2::generator().scalar_mul_const_time(&scalar()).to_vec()
+}
+
+#[cfg(any(feature = "bls381", feature = "bn254"))]
+fn pairing() -> Vec<u8> {
+    let e = GT::ate_pairing(&(&G1::generator() * &scalar()), &G2::generator());
+    Sha256::digest(&e.to_vec()).to_vec()
+}
+
+/// The known answer tests of the curve the crate is built for
+pub fn known_answer_tests() -> Vec<KnownAnswerTest> {
+    // Only extended on pairing curves
+    #[allow(unused_mut)]
+    let mut tests = vec![
+        KnownAnswerTest {
+            name: "scalar inverse",
+            kind: KnownAnswerKind::ScalarArithmetic,
+            expected: expected::SCALAR_INVERSE,
+            compute: scalar_inverse,
+        },
+        KnownAnswerTest {
+            name: "G1 generator doubled",
+            kind: KnownAnswerKind::GeneratorMultiple,
+            expected: expected::G1_DOUBLE,
+            compute: g1_double,
+        },
+        KnownAnswerTest {
+            name: "G1 generator multiple",
+            kind: KnownAnswerKind::GeneratorMultiple,
+            expected: expected::G1_MUL,
+            compute: g1_mul,
+        },
+        KnownAnswerTest {
+            name: "G1 generator multiple, constant time",
+            kind: KnownAnswerKind::GeneratorMultiple,
+            expected: expected::G1_MUL,
+            compute: g1_mul_const_time,
+        },
+        KnownAnswerTest {
+            name: "hash to G1",
+            kind: KnownAnswerKind::HashToCurve,
+            expected: expected::HASH_TO_G1,
+            compute: hash_to_g1,
+        },
+    ];
+    #[cfg(any(feature = "bls381", feature = "bn254"))]
+    tests.extend(vec![
+        KnownAnswerTest {
+            name: "G2 generator multiple",
+            kind: KnownAnswerKind::GeneratorMultiple,
+            expected: expected::G2_MUL,
+            compute: g2_mul,
+        },
+        KnownAnswerTest {
+            name: "pairing",
+            kind: KnownAnswerKind::Pairing,
+            expected: expected::PAIRING,
+            compute: pairing,
+        },
+    ]);
+    tests
+}
+
+/// Run all known answer tests, returning the names of the failed ones
+pub fn run_known_answer_tests() -> Result<(), Vec<&'static str>> {
+    let failed: Vec<_> = known_answer_tests()
+        .iter()
+        .filter(|t| !t.run())
+        .map(|t| t.name)
+        .collect();
+    if failed.is_empty() {
+        Ok(())
+    } else {
+        Err(failed)
+    }
+}
+
+#[cfg(feature = "bls381")]
+mod expected {
+    pub const SCALAR_INVERSE: &str =
+        "0000000000000000000000000000000039f9f70cae3ecad364ba2815a0b4f25593f74edbd4913ab937be4ebd8e852bec";
+    pub const G1_DOUBLE: &str =
+        "040572cbea904d67468808c8eb50a9450c9721db309128012543902d0ac358a62ae28f75bb8f1c7c42c39a8c5529bf0f4e166a9d8cabc673a322fda673779d8e3822ba3ecb8670e461f73bb9021d5fd76a4c56d9d4cd16bd1bba86881979749d28";
+    pub const G1_MUL: &str =
+        "040193db75369ee1f1b3d8828adbc05c0dc6bd38ef5dab2528174eace2140da80cd8f253ce43748a53b316fdc263234af400b84dad7bb86267970fd67112f88a3326f0bcb73c469f9dfd27ee16449190c54d315f0bbe0f9404e52f29468d0d7304";
+    pub const HASH_TO_G1: &str =
+        "04026a06bf6fa469c2b714b78869885217591aab71b2d7c5b84a5453c3be802f41a611cd1e105b077485704919d605362507e8cddbba53213782681acd6585cc7b274b6c9296b9a14b68c23ab77dca1fd2032f1f4ced4264a6696586189019e0af";
+    pub const G2_MUL: &str =
+        "058b3e8b9fc9552e30787cb4a541a1c3bf67a02e91fc648b2c19f4bb333e14c5c73b9bfbc5ec56dadabb07ff15d451241772c16106e9c70b2073dfe17989225dd10f3adb675365fc6d833587ad4cbd3ae692ad1e20679003f676b0b089e83feb007716a86bd9db89662f87a026604bb85fd531599681071feddab5f40869ea036145f6bcf138e67b986361ce25d9c63c06a63710dada90a4ab7b4c89b64cc2f94dc2e77dd6dc77b0b0653620bee399d05a27aea1c12e96540a80aad355af3d40";
+    pub const PAIRING: &str = "6e8ce91dc61fe34536a0994348e8c40c7c217f9736418d5df052cb0b80e9f042";
+}
+
+#[cfg(feature = "bn254")]
+mod expected {
+    pub const SCALAR_INVERSE: &str =
+        "091226a47f8becc86c114b4ec90dccb9665e7f93c24c34ca32abaeca718095b9";
+    pub const G1_DOUBLE: &str =
+        "040948d920900000006e8d1360000000021848400000000004e9c000000000000917361ed1680000011460b070000000053cb4a0000000000c4860000000000003";
+    pub const G1_MUL: &str =
+        "040f9cb5f2a9e80054fe90368de78b4507698b2d8653322837d73e93ffc4ad323d1c5a9e6fbe7d15ea263cc03ca7cbb2c5c1be560b425ad3c4c6bd84e19a972073";
+    pub const HASH_TO_G1: &str =
+        "041ce5dc37e87d8ee600c1b9b77b58475d4b7a7b2f7fb241f435f1f124b9f881321a84bd3d4049e3658e2095f1a2d8c0269494e662c5c186038322a48983f9abd8";
+    pub const G2_MUL: &str =
+        "0900d57752e418940ed51065f61bf52d4d416530f927d0707d9ae27db0e3eacf195fe569f2d503cc66cfbfd5ae8c1124ae7c259b845a544b9000808bbc57595c23a40ce7a4381185f1c1fb40ad648755cb3a0bc73579ad54fb7c1b6bc319368120460c4e8cd8205e781886c01691cedd74ba22f67ac2a841344794a9a5914d5d";
+    pub const PAIRING: &str = "f79b74d97870f79fdaeb680f3c8805f8f8d91be5cbd64822d8839151939b0807";
+}
+
+#[cfg(feature = "secp256k1")]
+mod expected {
+    pub const SCALAR_INVERSE: &str =
+        "9efb3685208b166156bb06a5fe9e0db4a78f83cc291dddf95ea5d1dd59a5b9ac";
+    pub const G1_DOUBLE: &str =
+        "04c6047f9441ed7d6d3045406e95c07cd85c778e4b8cef3ca7abac09b95c709ee51ae168fea63dc339a3c58419466ceaeef7f632653266d0e1236431a950cfe52a";
+    pub const G1_MUL: &str =
+        "045be5e9478209674a96e60f1f037f6176540fd001fa1d64694770c56a7709c42c035a88c3843dbf8b10d52cc71e26cce171e1aa1e32f8e193c9f6a6bd8f07b8bf";
+    pub const HASH_TO_G1: &str =
+        "04d696d2c3287d8eeea3c73d377b584787311f7b2f7fb2425678f1f124b9f881928ac37a3247f277d88050abf3e00b6269f0ad457a8e15f5ecf9374a11b3342a56";
+}
+
+#[cfg(feature = "ed25519")]
+mod expected {
+    pub const SCALAR_INVERSE: &str =
+        "06333a6e035995e785efdd602a88276fe84ecba0a52ddb99daa77e46b68f1ae2";
+    pub const G1_DOUBLE: &str =
+        "0436ab384c9f5a046c3d043b7d1833e7ac080d8e4515d7a45f83c5a14e2843ce0e2260cdf3092329c21da25ee8c9a21f5697390f51643851560e5f46ae6af8a3c9";
+    pub const G1_MUL: &str =
+        "041ff5e5f8ce1b08a51ab7fd40d6400c3cda9b57e1afa40c12396f8e2366fee0610fb95cc59d6113675aa6ab86ad7b26a82f48351d2fc0c594e85bd0afa9c87357";
+    pub const HASH_TO_G1: &str =
+        "0432d82162df19f3cd5f8d4390a8b6f084de7ea6b2adb095a893d462fabced684578cc58cd3bdafa228247c0f520084530a0f234d2d16a11689a694ae13c4bff2b";
+}
+
+#[cfg(feature = "nist256")]
+mod expected {
+    pub const SCALAR_INVERSE: &str =
+        "86435e773143d5883c0d8894c50634a13720698af6469b300bc0eaf52ece8c77";
+    pub const G1_DOUBLE: &str =
+        "047cf27b188d034f7e8a52380304b51ac3c08969e277f21b35a60b48fc4766997807775510db8ed040293d9ac69f7430dbba7dade63ce982299e04b79d227873d1";
+    pub const G1_MUL: &str =
+        "040c901d423c831ca85e27c73c263ba132721bb9d7a84c4f0380b2a6756fd601331c8870234dec878504c174144fa4b14b66a651691606d8173e55bd37e381569e";
+    pub const HASH_TO_G1: &str =
+        "04d696d2c3287d8eeea3c73d377b584787311f7b2f7fb2425678f1f124b9f881910d65cf1c31bdb1ff7d9a0b42412cbb207f47e13276bf7870a22b189fb2be82f4";
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_known_answer_tests() {
+        assert!(run_known_answer_tests().is_ok());
+        let tests = known_answer_tests();
+        assert!(tests.iter().all(|t| t.run()));
+        // Both scalar multiplications give the same multiple
+        assert_eq!(tests[2].expected, tests[3].expected);
+        assert_eq!(
+            tests[1].compute(),
+            (&G1::generator() + &G1::generator()).to_vec()
+        );
+    }
+}
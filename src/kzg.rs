@@ -0,0 +1,219 @@
+//! KZG polynomial commitments. A polynomial `p` is committed as `p(tau)*g1` using the powers of a
+//! secret `tau` in the setup and an opening at `z` is the commitment to `(p(x) - p(z)) / (x - z)`.
+
+use crate::curve_order_elem::{CurveOrderElement, CurveOrderElementVector};
+use crate::errors::ValueError;
+use crate::extension_field_gt::GT;
+use crate::group_elem::{GroupElement, GroupElementVector};
+use crate::group_elem_g1::{G1Vector, G1};
+use crate::group_elem_g2::G2;
+use crate::group_params::GroupParams;
+use crate::univar_poly::UnivarPolynomial;
+
+use rayon::prelude::*;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// Powers of the setup secret `tau`, `[g1, tau*g1, tau^2*g1, ... tau^d*g1]` and `g2, tau*g2`
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct KzgParams {
+    powers_g1: G1Vector,
+    g2: G2,
+    tau_g2: G2,
+}
+
+/// Claim that the polynomial committed in `commitment` evaluates to `value` at `point` with the
+/// opening `proof`
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct OpeningClaim {
+    pub commitment: G1,
+    pub point: CurveOrderElement,
+    pub value: CurveOrderElement,
+    pub proof: G1,
+}
+
+impl KzgParams {
+    /// Setup for polynomials of degree up to `max_degree` with a random `tau` which is discarded.
+    /// Whoever runs this could have kept `tau` and forge openings so it is only suitable for tests
+    /// or when the same party commits and verifies.
+    pub fn new(max_degree: usize, params: &GroupParams) -> Self {
+        Self::new_with_trapdoor(max_degree, &CurveOrderElement::random(), params)
+    }
+
+    /// Setup for polynomials of degree up to `max_degree` with the given `tau`
+    pub fn new_with_trapdoor(max_degree: usize, tau: &CurveOrderElement, params: &GroupParams) -> Self {
+        let powers = CurveOrderElementVector::new_vandermonde_vector(tau, max_degree + 1);
+        let g1 = params.g1();
+        let powers_g1 = powers.par_iter().map(|p| g1 * p).collect();
+        Self {
+            powers_g1,
+            g2: params.g2().clone(),
+            tau_g2: params.g2() * tau,
+        }
+    }
+
+    pub fn max_degree(&self) -> usize {
+        self.powers_g1.len() - 1
+    }
+
+    pub fn powers_g1(&self) -> &G1Vector {
+        &self.powers_g1
+    }
+
+    pub fn g2(&self) -> &G2 {
+        &self.g2
+    }
+
+    pub fn tau_g2(&self) -> &G2 {
+        &self.tau_g2
+    }
+
+    /// Commit to a polynomial of degree not more than `max_degree`
+    pub fn commit(&self, poly: &UnivarPolynomial) -> Result<G1, ValueError> {
+        let coeffs = poly.coefficients();
+        if coeffs.len() > self.powers_g1.len() {
+            return Err(ValueError::IncorrectSize(coeffs.len()));
+        }
+        G1Vector::multi_scalar_mul_var_time_without_precomputation(
+            &self.powers_g1.as_slice()[..coeffs.len()],
+            coeffs.iter(),
+        )
+    }
+
+    /// Evaluation of `poly` at `point` and the proof for it
+    pub fn open(
+        &self,
+        poly: &UnivarPolynomial,
+        point: &CurveOrderElement,
+    ) -> Result<(CurveOrderElement, G1), ValueError> {
+        let coeffs = poly.coefficients();
+        if coeffs.len() > self.powers_g1.len() {
+            return Err(ValueError::IncorrectSize(coeffs.len()));
+        }
+        let (quotient, value) = poly.divide_by_linear(point);
+        let proof = self.commit(&quotient)?;
+        Ok((value, proof))
+    }
+
+    /// Check `e(proof, tau*g2 - point*g2) == e(commitment - value*g1, g2)`. Costs 2 pairings.
+    pub fn verify(
+        &self,
+        commitment: &G1,
+        point: &CurveOrderElement,
+        value: &CurveOrderElement,
+        proof: &G1,
+    ) -> bool {
+        let lhs = commitment - &(&self.powers_g1[0] * value) + proof * point;
+        GT::ate_2_pairing(&lhs, &self.g2, &proof.negation(), &self.tau_g2).is_one()
+    }
+
+    /// Verify all claims with 2 pairings in total. Claims are combined with powers of a challenge
+    /// derived by hashing them so a wrong claim can't be cancelled by another.
+    pub fn batch_verify(&self, claims: &[OpeningClaim]) -> bool {
+        let mut bytes = vec![];
+        for c in claims {
+            bytes.extend_from_slice(&c.commitment.to_vec());
+            bytes.extend_from_slice(&c.point.to_compressed_bytes());
+            bytes.extend_from_slice(&c.value.to_compressed_bytes());
+            bytes.extend_from_slice(&c.proof.to_vec());
+        }
+        self.batch_verify_with_challenge(claims, &CurveOrderElement::from_msg_hash(&bytes))
+    }
+
+    /// Verify all claims with 2 pairings in total using powers of `challenge` to combine them,
+    /// `challenge` must be chosen after the claims are fixed. Since
+    /// `e(proof_i, tau*g2) == e(commitment_i - value_i*g1 + point_i*proof_i, g2)` for each claim,
+    /// checks `e(sum r^i*proof_i, tau*g2) == e(sum r^i*(commitment_i - value_i*g1 + point_i*proof_i), g2)`.
+    pub fn batch_verify_with_challenge(
+        &self,
+        claims: &[OpeningClaim],
+        challenge: &CurveOrderElement,
+    ) -> bool {
+        if claims.is_empty() {
+            return true;
+        }
+        let r = CurveOrderElementVector::new_vandermonde_vector(challenge, claims.len());
+
+        // lhs is an MSM over all commitments and proofs and the generator with the sum of values
+        let mut points = Vec::with_capacity(2 * claims.len() + 1);
+        let mut scalars = Vec::with_capacity(2 * claims.len() + 1);
+        let mut value_sum = CurveOrderElement::zero();
+        for (c, r_i) in claims.iter().zip(r.iter()) {
+            points.push(c.commitment.clone());
+            scalars.push(r_i.clone());
+            points.push(c.proof.clone());
+            scalars.push(r_i * &c.point);
+            value_sum += r_i * &c.value;
+        }
+        points.push(self.powers_g1[0].clone());
+        scalars.push(value_sum.negation());
+        let lhs = match G1Vector::from(points).multi_scalar_mul_var_time(scalars.iter()) {
+            Ok(p) => p,
+            Err(_) => return false,
+        };
+
+        let proofs: G1Vector = claims.iter().map(|c| c.proof.clone()).collect();
+        let proof_sum = match proofs.multi_scalar_mul_var_time(r.iter()) {
+            Ok(p) => p,
+            Err(_) => return false,
+        };
+        GT::ate_2_pairing(&lhs, &self.g2, &proof_sum.negation(), &self.tau_g2).is_one()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_commit_open() {
+        let params = KzgParams::new(10, &GroupParams::default());
+        assert_eq!(params.max_degree(), 10);
+        let poly = UnivarPolynomial::random(10);
+        let comm = params.commit(&poly).unwrap();
+
+        let point = CurveOrderElement::random();
+        let (value, proof) = params.open(&poly, &point).unwrap();
+        assert_eq!(value, poly.eval(&point));
+        assert!(params.verify(&comm, &point, &value, &proof));
+        assert!(!params.verify(&comm, &point, &(&value + &CurveOrderElement::one()), &proof));
+        assert!(!params.verify(&comm, &CurveOrderElement::random(), &value, &proof));
+
+        assert!(params.commit(&UnivarPolynomial::random(11)).is_err());
+        assert!(params.open(&UnivarPolynomial::random(11), &point).is_err());
+    }
+
+    #[test]
+    fn test_batch_verify() {
+        let params = KzgParams::new(8, &GroupParams::default());
+        let mut claims = vec![];
+        for i in 0..5 {
+            let poly = UnivarPolynomial::random(i + 2);
+            let commitment = params.commit(&poly).unwrap();
+            let point = CurveOrderElement::random();
+            let (value, proof) = params.open(&poly, &point).unwrap();
+            claims.push(OpeningClaim {
+                commitment,
+                point,
+                value,
+                proof,
+            });
+        }
+        assert!(params.batch_verify(&claims));
+        assert!(params.batch_verify(&[]));
+        assert!(params.batch_verify_with_challenge(&claims, &CurveOrderElement::random()));
+
+        let mut bad = claims.clone();
+        bad[3].value += CurveOrderElement::one();
+        assert!(!params.batch_verify(&bad));
+
+        // Swapped proofs fail even though each is a valid proof for some claim
+        let mut bad = claims.clone();
+        let p = bad[0].proof.clone();
+        bad[0].proof = bad[1].proof.clone();
+        bad[1].proof = p;
+        assert!(!params.batch_verify(&bad));
+    }
+}
@@ -20,6 +20,10 @@ pub use amcl::secp256k1 as ECCurve;
 #[cfg(feature = "ed25519")]
 pub use amcl::ed25519 as ECCurve;
 
+#[cfg(feature = "nist256")]
+pub use amcl::nist256 as ECCurve;
+
+mod backend;
 pub mod constants;
 pub mod types;
 
@@ -30,16 +34,64 @@ pub mod errors;
 pub mod macros;
 
 pub mod utils;
+pub mod dst;
+pub mod hash_to_curve;
+pub mod ct;
+pub mod secret_mem;
+pub mod yielding;
+pub mod drbg;
 
 #[macro_use]
 pub mod curve_order_elem;
+pub mod mod_int;
+pub mod recoding;
+pub mod typed_scalar;
 #[macro_use]
 pub mod group_elem;
 #[macro_use]
 pub mod group_elem_g1;
+pub mod group_params;
+pub mod encoding;
+pub mod checked;
+pub mod kat;
+pub mod reencode;
+pub mod codec;
 pub mod commitment;
 #[macro_use]
 pub mod univar_poly;
+pub mod bivar_poly;
+pub mod fft;
+pub mod secret_sharing;
+pub mod schnorr;
+pub mod blind_schnorr;
+pub mod sigma;
+pub mod oprf;
+pub mod psi;
+pub mod ring_sig;
+pub mod shuffle;
+pub mod twisted_elgamal;
+pub mod elgamal;
+
+#[cfg(feature = "secp256k1")]
+pub mod ecdsa;
+
+#[cfg(feature = "secp256k1")]
+pub mod bip340;
+
+#[cfg(all(feature = "elliptic-curve", any(feature = "secp256k1", feature = "nist256")))]
+pub mod rustcrypto;
+
+#[cfg(all(feature = "jwk", not(feature = "ed25519")))]
+pub mod jwk;
+
+#[cfg(all(feature = "pkcs8", any(feature = "secp256k1", feature = "nist256")))]
+pub mod pkcs8;
+
+#[cfg(all(
+    feature = "cose",
+    any(feature = "secp256k1", feature = "nist256", feature = "bls381")
+))]
+pub mod cose;
 
 #[cfg(any(feature = "bls381", feature = "bn254"))]
 pub mod types_g2;
@@ -52,4 +104,37 @@ pub mod group_elem_g2;
 #[macro_use]
 pub mod extension_field_gt;
 
+#[cfg(any(feature = "bls381", feature = "bn254"))]
+pub mod pairing_check;
+
+#[cfg(any(feature = "bls381", feature = "bn254"))]
+pub mod bls;
+
+#[cfg(any(feature = "bls381", feature = "bn254"))]
+pub mod bls_min_pk;
+
+#[cfg(any(feature = "bls381", feature = "bn254"))]
+pub mod beacon;
+
+#[cfg(any(feature = "bls381", feature = "bn254"))]
+pub mod tripartite;
+
+#[cfg(any(feature = "bls381", feature = "bn254"))]
+pub mod kzg;
+
+#[cfg(any(feature = "bls381", feature = "bn254"))]
+pub mod vector_commitment;
+
+#[cfg(any(feature = "bls381", feature = "bn254"))]
+pub mod accumulator;
+
+#[cfg(any(feature = "bls381", feature = "bn254"))]
+pub mod groth16;
+
+#[cfg(any(feature = "bls381", feature = "bn254"))]
+pub mod ps_sig;
+
+#[cfg(feature = "bls381")]
+pub mod multiset_hash;
+
 // TODO: Move the timing tests to benchmark
@@ -28,3 +28,32 @@ macro_rules! pad_collection {
         }
     }};
 }
+
+/// Implements `Index` for all range types on a vector type with an `as_slice` method so that
+/// `v[a..b]` gives a slice without cloning the elements.
+#[macro_export]
+macro_rules! impl_vec_range_index {
+    ( $elem:ty, $vec:ty ) => {
+        $crate::impl_vec_range_index!($elem, $vec, ::std::ops::Range<usize>);
+        $crate::impl_vec_range_index!($elem, $vec, ::std::ops::RangeFrom<usize>);
+        $crate::impl_vec_range_index!($elem, $vec, ::std::ops::RangeTo<usize>);
+        $crate::impl_vec_range_index!($elem, $vec, ::std::ops::RangeFull);
+        $crate::impl_vec_range_index!($elem, $vec, ::std::ops::RangeInclusive<usize>);
+        $crate::impl_vec_range_index!($elem, $vec, ::std::ops::RangeToInclusive<usize>);
+    };
+    ( $elem:ty, $vec:ty, $range:ty ) => {
+        impl ::std::ops::Index<$range> for $vec {
+            type Output = [$elem];
+
+            fn index(&self, range: $range) -> &[$elem] {
+                &self.as_slice()[range]
+            }
+        }
+
+        impl ::std::ops::IndexMut<$range> for $vec {
+            fn index_mut(&mut self, range: $range) -> &mut [$elem] {
+                &mut self.as_mut_slice()[range]
+            }
+        }
+    };
+}
@@ -0,0 +1,376 @@
+//! Integers modulo a modulus chosen at runtime, for protocol glue like mapping values to the order
+//! of another curve or experiments with RSA style groups, using the `BigNum` arithmetic and
+//! Barrett reduction of curve order elements. Moduli are odd and have at most `MAX_MODULUS_BITS`
+//! bits so products fit a `DoubleBigNum`. Elements share their `Modulus` through an `Arc` and
+//! the operators panic when given elements of different moduli.
+//!
+//! Unlike the arithmetic of curve order elements none of this is constant time.
+
+use crate::constants::MODBYTES;
+use crate::curve_order_elem::CurveOrderElement;
+use crate::errors::ValueError;
+use crate::types::{BigNum, DoubleBigNum};
+use crate::utils::{barrett_reduction, barrett_reduction_params};
+
+use rand::{CryptoRng, RngCore};
+use std::convert::TryFrom;
+use std::fmt;
+use std::ops::{Add, Mul, Neg, Sub};
+use std::sync::Arc;
+
+/// Maximum number of bits of a modulus
+pub const MAX_MODULUS_BITS: usize = 8 * MODBYTES;
+
+/// Modulus with its Barrett reduction parameters
+#[derive(Clone)]
+pub struct Modulus {
+    m: BigNum,
+    k: usize,
+    u: BigNum,
+    v: BigNum,
+}
+
+impl Modulus {
+    /// Big-endian bytes of an odd modulus greater than 1 of at most `MAX_MODULUS_BITS` bits
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, ValueError> {
+        if bytes.len() > MODBYTES {
+            return Err(ValueError::IncorrectSize(bytes.len()));
+        }
+        let mut padded = [0u8; MODBYTES];
+        padded[MODBYTES - bytes.len()..].copy_from_slice(bytes);
+        Self::from_bignum(BigNum::frombytes(&padded))
+    }
+
+    pub fn from_u64(m: u64) -> Result<Self, ValueError> {
+        Self::from_bytes(&m.to_be_bytes())
+    }
+
+    /// The curve order, for comparing with curve order elements
+    pub fn curve_order() -> Self {
+        Self::from_bignum(crate::constants::CURVE_ORDER).unwrap()
+    }
+
+    /// The order of the field the curve is defined over
+    pub fn field_order() -> Self {
+        Self::from_bignum(crate::constants::MODULUS).unwrap()
+    }
+
+    fn from_bignum(mut m: BigNum) -> Result<Self, ValueError> {
+        m.norm();
+        if m.parity() == 0 || BigNum::comp(&m, &BigNum::new_int(1)) <= 0 {
+            return Err(ValueError::OutOfRange(m.nbits()));
+        }
+        let (k, u, v) = barrett_reduction_params(&m);
+        Ok(Self { m, k, u, v })
+    }
+
+    pub fn bits(&self) -> usize {
+        self.k
+    }
+
+    /// `MODBYTES` big-endian bytes
+    pub fn to_bytes(&self) -> Vec<u8> {
+        to_bytes(&self.m)
+    }
+
+    fn reduce(&self, x: &DoubleBigNum) -> BigNum {
+        barrett_reduction(x, &self.m, self.k, &self.u, &self.v)
+    }
+}
+
+impl PartialEq for Modulus {
+    fn eq(&self, other: &Self) -> bool {
+        BigNum::comp(&self.m, &other.m) == 0
+    }
+}
+
+impl Eq for Modulus {}
+
+impl fmt::Debug for Modulus {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Modulus({})", self.m)
+    }
+}
+
+fn to_bytes(x: &BigNum) -> Vec<u8> {
+    let mut bytes = vec![0u8; MODBYTES];
+    x.clone().tobytes(&mut bytes);
+    bytes
+}
+
+/// Integer modulo a `Modulus`
+#[derive(Clone)]
+pub struct ModInt {
+    value: BigNum,
+    modulus: Arc<Modulus>,
+}
+
+impl ModInt {
+    pub fn zero(modulus: &Arc<Modulus>) -> Self {
+        Self::from_reduced(BigNum::new(), modulus)
+    }
+
+    pub fn one(modulus: &Arc<Modulus>) -> Self {
+        Self::from_reduced(BigNum::new_int(1), modulus)
+    }
+
+    pub fn from_u64(x: u64, modulus: &Arc<Modulus>) -> Self {
+        Self::from_bytes(&x.to_be_bytes(), modulus).unwrap()
+    }
+
+    /// Big-endian bytes, at most `2 * MODBYTES` of them, reduced modulo the modulus
+    pub fn from_bytes(bytes: &[u8], modulus: &Arc<Modulus>) -> Result<Self, ValueError> {
+        if bytes.len() > 2 * MODBYTES {
+            return Err(ValueError::IncorrectSize(bytes.len()));
+        }
+        let mut x = DoubleBigNum::new();
+        for b in bytes {
+            x.shl(8);
+            x.w[0] += *b as crate::types::Limb;
+        }
+        Ok(Self::from_reduced(x.dmod(&modulus.m), modulus))
+    }
+
+    /// Curve order element reduced modulo the modulus
+    pub fn from_scalar(s: &CurveOrderElement, modulus: &Arc<Modulus>) -> Self {
+        Self::from_bytes(&s.to_bytes(), modulus).unwrap()
+    }
+
+    pub fn random(modulus: &Arc<Modulus>) -> Self {
+        Self::random_using_rng(modulus, &mut rand::thread_rng())
+    }
+
+    /// Uniform up to a statistical distance of `2^-(8*MODBYTES)`, as twice as many bytes as the
+    /// modulus can have are reduced
+    pub fn random_using_rng<R: RngCore + CryptoRng>(modulus: &Arc<Modulus>, rng: &mut R) -> Self {
+        let mut bytes = [0u8; 2 * MODBYTES];
+        rng.fill_bytes(&mut bytes);
+        Self::from_bytes(&bytes, modulus).unwrap()
+    }
+
+    fn from_reduced(mut value: BigNum, modulus: &Arc<Modulus>) -> Self {
+        value.norm();
+        Self {
+            value,
+            modulus: modulus.clone(),
+        }
+    }
+
+    fn check_modulus(&self, other: &Self) {
+        assert!(
+            Arc::ptr_eq(&self.modulus, &other.modulus) || self.modulus == other.modulus,
+            "ModInts of different moduli"
+        );
+    }
+
+    pub fn modulus(&self) -> &Arc<Modulus> {
+        &self.modulus
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.value.iszilch()
+    }
+
+    pub fn is_one(&self) -> bool {
+        self.value.isunity()
+    }
+
+    /// `MODBYTES` big-endian bytes
+    pub fn to_bytes(&self) -> Vec<u8> {
+        to_bytes(&self.value)
+    }
+
+    /// The value reduced modulo the curve order
+    pub fn to_scalar(&self) -> CurveOrderElement {
+        CurveOrderElement::try_from(&self.to_bytes()[..]).unwrap()
+    }
+
+    pub fn square(&self) -> Self {
+        let value = self.modulus.reduce(&BigNum::sqr(&self.value));
+        Self::from_reduced(value, &self.modulus)
+    }
+
+    /// `self^exp` for the big-endian bytes `exp`, by square and multiply
+    pub fn pow(&self, exp: &[u8]) -> Self {
+        let mut res = Self::one(&self.modulus);
+        for b in exp {
+            for i in (0..8).rev() {
+                res = res.square();
+                if (b >> i) & 1 == 1 {
+                    res = &res * self;
+                }
+            }
+        }
+        res
+    }
+
+    /// Fails with `ValueError::ZeroElement` if `self` is not invertible, i.e. zero or sharing a
+    /// factor with the modulus
+    pub fn inverse(&self) -> Result<Self, ValueError> {
+        // `invmodp` does not terminate for non-invertible values, so check the gcd first
+        let (mut a, mut b) = (self.modulus.m, self.value);
+        while !b.iszilch() {
+            a.rmod(&b);
+            std::mem::swap(&mut a, &mut b);
+        }
+        if !a.isunity() {
+            return Err(ValueError::ZeroElement);
+        }
+        let mut inv = self.value;
+        inv.invmodp(&self.modulus.m);
+        Ok(Self::from_reduced(inv, &self.modulus))
+    }
+}
+
+impl PartialEq for ModInt {
+    fn eq(&self, other: &Self) -> bool {
+        self.modulus == other.modulus && BigNum::comp(&self.value, &other.value) == 0
+    }
+}
+
+impl Eq for ModInt {}
+
+impl fmt::Debug for ModInt {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "ModInt({})", self.value)
+    }
+}
+
+impl fmt::Display for ModInt {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.value.fmt(f)
+    }
+}
+
+impl<'a> Add<&'a ModInt> for &'a ModInt {
+    type Output = ModInt;
+
+    fn add(self, other: &ModInt) -> ModInt {
+        self.check_modulus(other);
+        let mut sum = self.value;
+        sum.add(&other.value);
+        sum.norm();
+        if BigNum::comp(&sum, &self.modulus.m) >= 0 {
+            sum.sub(&self.modulus.m);
+        }
+        ModInt::from_reduced(sum, &self.modulus)
+    }
+}
+
+impl<'a> Sub<&'a ModInt> for &'a ModInt {
+    type Output = ModInt;
+
+    fn sub(self, other: &ModInt) -> ModInt {
+        self + &(-other)
+    }
+}
+
+impl Neg for &ModInt {
+    type Output = ModInt;
+
+    fn neg(self) -> ModInt {
+        if self.is_zero() {
+            return self.clone();
+        }
+        let mut neg = self.modulus.m;
+        neg.sub(&self.value);
+        ModInt::from_reduced(neg, &self.modulus)
+    }
+}
+
+impl<'a> Mul<&'a ModInt> for &'a ModInt {
+    type Output = ModInt;
+
+    fn mul(self, other: &ModInt) -> ModInt {
+        self.check_modulus(other);
+        let value = self.modulus.reduce(&BigNum::mul(&self.value, &other.value));
+        ModInt::from_reduced(value, &self.modulus)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_small_modulus() {
+        let m = Arc::new(Modulus::from_u64(1_000_003).unwrap());
+        assert_eq!(m.bits(), 20);
+        let a = ModInt::from_u64(999_999, &m);
+        let b = ModInt::from_u64(12_345, &m);
+        assert_eq!(
+            &a + &b,
+            ModInt::from_u64((999_999 + 12_345) % 1_000_003, &m)
+        );
+        assert_eq!(&b - &a, ModInt::from_u64(12_345 + 1_000_003 - 999_999, &m));
+        assert_eq!(&a * &b, ModInt::from_u64(999_999 * 12_345 % 1_000_003, &m));
+        assert_eq!(a.square(), &a * &a);
+        assert!((&a + &(-&a)).is_zero());
+        assert_eq!(ModInt::from_u64(1_000_004, &m), ModInt::one(&m));
+
+        // Fermat
+        assert!(a.pow(&(1_000_003u64 - 1).to_be_bytes()).is_one());
+        assert!((&a.inverse().unwrap() * &a).is_one());
+        assert!(ModInt::zero(&m).inverse().is_err());
+
+        // Composite modulus, 15 = 3 * 5
+        let m15 = Arc::new(Modulus::from_u64(15).unwrap());
+        assert!(ModInt::from_u64(6, &m15).inverse().is_err());
+        assert_eq!(
+            ModInt::from_u64(7, &m15).inverse().unwrap(),
+            ModInt::from_u64(13, &m15)
+        );
+
+        assert!(Modulus::from_u64(16).is_err());
+        assert!(Modulus::from_u64(1).is_err());
+        assert!(Modulus::from_bytes(&[1u8; MODBYTES + 1]).is_err());
+    }
+
+    #[test]
+    fn test_against_curve_order_elements() {
+        let m = Arc::new(Modulus::curve_order());
+        for _ in 0..20 {
+            let (x, y) = (CurveOrderElement::random(), CurveOrderElement::random());
+            let (a, b) = (ModInt::from_scalar(&x, &m), ModInt::from_scalar(&y, &m));
+            assert_eq!((&a * &b).to_scalar(), &x * &y);
+            assert_eq!((&a + &b).to_scalar(), &x + &y);
+            assert_eq!((&a - &b).to_scalar(), &x - &y);
+            assert_eq!(a.inverse().unwrap().to_scalar(), x.inverse());
+            assert_eq!(a.pow(&y.to_bytes()).to_scalar(), x.pow(&y));
+        }
+    }
+
+    #[test]
+    fn test_large_moduli() {
+        // Largest odd modulus
+        let m = Arc::new(Modulus::from_bytes(&[0xffu8; MODBYTES]).unwrap());
+        assert_eq!(m.bits(), MAX_MODULUS_BITS);
+        let minus_one = -&ModInt::one(&m);
+        assert!(minus_one.square().is_one());
+        assert_eq!(&minus_one + &ModInt::one(&m), ModInt::zero(&m));
+        assert_eq!(
+            ModInt::from_bytes(&[0xffu8; MODBYTES], &m).unwrap(),
+            ModInt::zero(&m)
+        );
+        // 3 divides 2^(8*MODBYTES) - 1
+        assert!(ModInt::from_u64(3, &m).inverse().is_err());
+
+        // The prime order of the base field
+        let p = Arc::new(Modulus::field_order());
+        for _ in 0..10 {
+            let a = ModInt::random(&p);
+            let b = ModInt::random(&p);
+            assert_eq!(&(&a * &b) * &b.inverse().unwrap(), a);
+            assert_eq!(&(&a + &b) - &b, a);
+            assert_eq!(a.square(), &a * &a);
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "ModInts of different moduli")]
+    fn test_different_moduli() {
+        let a = ModInt::one(&Arc::new(Modulus::from_u64(7).unwrap()));
+        let b = ModInt::one(&Arc::new(Modulus::from_u64(11).unwrap()));
+        let _ = &a + &b;
+    }
+}
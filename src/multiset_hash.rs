@@ -0,0 +1,123 @@
+//! Incremental multiset hash into group G1. The hash of a multiset is the sum of `hash_to_curve` of
+//! its items so it does not depend on the order of insertion and can be updated with an addition or
+//! subtraction per item, eg. to maintain a commitment to a changing set.
+
+use crate::constants::GROUP_G1_SIZE;
+use crate::group_elem::GroupElement;
+use crate::group_elem_g1::G1;
+
+use hash2curve::DomainSeparationTag;
+use sha2::{Digest, Sha256};
+
+#[derive(Clone, Debug)]
+pub struct MultisetHash {
+    dst: DomainSeparationTag,
+    value: G1,
+}
+
+impl MultisetHash {
+    /// Hash of the empty multiset. Hashes with different `dst` are unrelated.
+    pub fn new(dst: DomainSeparationTag) -> Self {
+        Self {
+            dst,
+            value: G1::identity(),
+        }
+    }
+
+    pub fn insert(&mut self, item: &[u8]) {
+        self.value += G1::hash_to_curve(item, &self.dst);
+    }
+
+    /// Does not check that `item` was inserted, removing an absent item gives a hash no multiset has
+    /// unless the item is inserted again.
+    pub fn remove(&mut self, item: &[u8]) {
+        self.value -= G1::hash_to_curve(item, &self.dst);
+    }
+
+    /// Hash of the union of both multisets. Both must use the same `dst` for the result to be
+    /// meaningful.
+    pub fn union(&mut self, other: &Self) {
+        self.value += &other.value;
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.value.is_identity()
+    }
+
+    pub fn as_point(&self) -> &G1 {
+        &self.value
+    }
+
+    /// SHA-256 of the uncompressed point, or of zeros for the empty multiset since the identity has
+    /// no unique encoding.
+    pub fn digest(&self) -> [u8; 32] {
+        let bytes = if self.value.is_identity() {
+            vec![0u8; GROUP_G1_SIZE]
+        } else {
+            self.value.to_vec()
+        };
+        let mut out = [0u8; 32];
+        out.copy_from_slice(&Sha256::digest(&bytes));
+        out
+    }
+}
+
+impl PartialEq for MultisetHash {
+    fn eq(&self, other: &Self) -> bool {
+        self.dst == other.dst && self.value == other.value
+    }
+}
+
+impl Eq for MultisetHash {}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn dst() -> DomainSeparationTag {
+        DomainSeparationTag::new(b"multiset_hash_", Some(b"test"), None, None).unwrap()
+    }
+
+    #[test]
+    fn test_multiset_hash() {
+        let items: Vec<Vec<u8>> = (0..10u8).map(|i| vec![i; 10]).collect();
+
+        let mut h1 = MultisetHash::new(dst());
+        let mut h2 = MultisetHash::new(dst());
+        assert!(h1.is_empty());
+        assert_eq!(h1.digest(), h2.digest());
+        for i in 0..items.len() {
+            h1.insert(&items[i]);
+            h2.insert(&items[items.len() - 1 - i]);
+        }
+        assert_eq!(h1, h2);
+        assert_eq!(h1.digest(), h2.digest());
+
+        // Multiplicity matters
+        h2.insert(&items[0]);
+        assert_ne!(h1.digest(), h2.digest());
+        h2.remove(&items[0]);
+        assert_eq!(h1.digest(), h2.digest());
+
+        for item in &items {
+            h1.remove(item);
+        }
+        assert!(h1.is_empty());
+        assert_eq!(h1.digest(), MultisetHash::new(dst()).digest());
+
+        let mut a = MultisetHash::new(dst());
+        let mut b = MultisetHash::new(dst());
+        items[..4].iter().for_each(|i| a.insert(i));
+        items[4..].iter().for_each(|i| b.insert(i));
+        a.union(&b);
+        assert_eq!(a, h2);
+
+        let other_dst =
+            DomainSeparationTag::new(b"multiset_hash_", Some(b"other"), None, None).unwrap();
+        let mut c = MultisetHash::new(other_dst);
+        c.insert(&items[0]);
+        let mut d = MultisetHash::new(dst());
+        d.insert(&items[0]);
+        assert_ne!(c.digest(), d.digest());
+    }
+}
@@ -0,0 +1,311 @@
+//! Oblivious pseudorandom function over group G1 following the structure of the CFRG OPRF draft
+//! (RFC 9497), in the base and the verifiable mode. The function is
+//! `F(k, input) = H(input || k*H_G(input))`. The client sends `r*H_G(input)` for a random blind
+//! `r`, the server multiplies it by its key and the client removes the blind, so the server
+//! learns nothing about the input. In the verifiable mode the server also proves with a DLEQ proof
+//! that it used the key of its public key `k*g`, batched over all elements of a request, and the
+//! client checks the proof before unblinding.
+//!
+//! Hashing to the group and to scalars uses the hash functions of this crate with the draft's
+//! domain separation, so outputs are not interoperable with other implementations of the draft.
+
+use crate::curve_order_elem::CurveOrderElement;
+use crate::errors::ValueError;
+use crate::group_elem::GroupElement;
+use crate::group_elem_g1::G1;
+
+use rand::{CryptoRng, RngCore};
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+/// Identifier of the group and hash function in the context string
+pub const SUITE_ID: &[u8] = b"amcl-G1-SHA256";
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum Mode {
+    Oprf,
+    Voprf,
+}
+
+impl Mode {
+    /// `"OPRFV1-" || mode || "-" || SUITE_ID`
+    pub fn context_string(&self) -> Vec<u8> {
+        let id = match self {
+            Mode::Oprf => 0u8,
+            Mode::Voprf => 1u8,
+        };
+        [b"OPRFV1-", &[id][..], b"-", SUITE_ID].concat()
+    }
+
+    #[cfg(feature = "bls381")]
+    fn hash_to_group(&self, input: &[u8]) -> G1 {
+        let dst = [b"HashToGroup-", self.context_string().as_slice()].concat();
+        let dst = hash2curve::DomainSeparationTag::new(&dst, None, None, None).unwrap();
+        G1::hash_to_curve(input, &dst)
+    }
+
+    // `hash_to_curve` is only implemented for BLS12-381
+    #[cfg(not(feature = "bls381"))]
+    #[allow(deprecated)]
+    fn hash_to_group(&self, input: &[u8]) -> G1 {
+        G1::from_msg_hash(&[b"HashToGroup-", self.context_string().as_slice(), input].concat())
+    }
+
+    fn hash_to_scalar(&self, input: &[u8]) -> CurveOrderElement {
+        CurveOrderElement::from_msg_hash(
+            &[b"HashToScalar-", self.context_string().as_slice(), input].concat(),
+        )
+    }
+}
+
+/// Append `I2OSP(len(bytes), 2) || bytes`
+fn append_with_len(transcript: &mut Vec<u8>, bytes: &[u8]) {
+    transcript.extend_from_slice(&(bytes.len() as u16).to_be_bytes());
+    transcript.extend_from_slice(bytes);
+}
+
+fn serialize_element(e: &G1) -> Vec<u8> {
+    e.to_compressed_bytes().to_vec()
+}
+
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct ServerSecretKey(CurveOrderElement);
+
+/// `k*g` for the standard generator `g` of group G1
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct ServerPublicKey(G1);
+
+/// DLEQ proof that the evaluated elements are the blinded elements multiplied by the key of the
+/// server's public key
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Proof {
+    c: CurveOrderElement,
+    s: CurveOrderElement,
+}
+
+impl ServerSecretKey {
+    pub fn random() -> Self {
+        Self(CurveOrderElement::random())
+    }
+
+    pub fn random_using_rng<R: RngCore + CryptoRng>(rng: &mut R) -> Self {
+        Self(CurveOrderElement::random_using_rng(rng))
+    }
+
+    /// Evaluate a blinded element in the base mode
+    pub fn blind_evaluate(&self, blinded: &G1) -> G1 {
+        blinded * &self.0
+    }
+
+    /// Evaluate blinded elements in the verifiable mode, with one proof for all of them
+    pub fn blind_evaluate_verifiable(&self, blinded: &[G1]) -> (Vec<G1>, Proof) {
+        let evaluated: Vec<G1> = blinded.iter().map(|b| b * &self.0).collect();
+        let pk = ServerPublicKey::new(self);
+        let proof = Proof::new(&self.0, &pk.0, blinded, &evaluated);
+        (evaluated, proof)
+    }
+
+    /// The function on `input` computed by the server directly, e.g. to check a client's output
+    pub fn evaluate(&self, input: &[u8], mode: Mode) -> Result<Vec<u8>, ValueError> {
+        let p = mode.hash_to_group(input);
+        if p.is_identity() {
+            return Err(ValueError::IdentityElement);
+        }
+        Ok(finalize_unblinded(input, &(p * &self.0)))
+    }
+}
+
+impl ServerPublicKey {
+    pub fn new(sk: &ServerSecretKey) -> Self {
+        Self(G1::generator() * &sk.0)
+    }
+
+    pub fn as_point(&self) -> &G1 {
+        &self.0
+    }
+}
+
+/// Composite elements `M = sum d_i*C_i` and `Z = sum d_i*D_i` with `d_i` derived from the
+/// public key and all elements
+fn compute_composites(b: &G1, c: &[G1], d: &[G1]) -> (G1, G1) {
+    let mode = Mode::Voprf;
+    let mut seed_transcript = vec![];
+    append_with_len(&mut seed_transcript, &serialize_element(b));
+    append_with_len(
+        &mut seed_transcript,
+        &[b"Seed-", mode.context_string().as_slice()].concat(),
+    );
+    let seed = Sha256::digest(&seed_transcript);
+
+    let mut m = G1::identity();
+    let mut z = G1::identity();
+    for (i, (c_i, d_i)) in c.iter().zip(d).enumerate() {
+        let mut transcript = vec![];
+        append_with_len(&mut transcript, seed.as_slice());
+        transcript.extend_from_slice(&(i as u16).to_be_bytes());
+        append_with_len(&mut transcript, &serialize_element(c_i));
+        append_with_len(&mut transcript, &serialize_element(d_i));
+        transcript.extend_from_slice(b"Composite");
+        let di = mode.hash_to_scalar(&transcript);
+        m += c_i * &di;
+        z += d_i * &di;
+    }
+    (m, z)
+}
+
+fn proof_challenge(b: &G1, m: &G1, z: &G1, t2: &G1, t3: &G1) -> CurveOrderElement {
+    let mut transcript = vec![];
+    for e in [b, m, z, t2, t3].iter() {
+        append_with_len(&mut transcript, &serialize_element(e));
+    }
+    transcript.extend_from_slice(b"Challenge");
+    Mode::Voprf.hash_to_scalar(&transcript)
+}
+
+impl Proof {
+    /// Prove `pk = k*g` and `D_i = k*C_i` for all `i`
+    fn new(k: &CurveOrderElement, pk: &G1, c: &[G1], d: &[G1]) -> Self {
+        let (m, z) = compute_composites(pk, c, d);
+        let r = CurveOrderElement::random();
+        let t2 = G1::generator() * &r;
+        let t3 = &m * &r;
+        let c = proof_challenge(pk, &m, &z, &t2, &t3);
+        let s = r - &c * k;
+        Self { c, s }
+    }
+
+    fn verify(&self, pk: &G1, c: &[G1], d: &[G1]) -> bool {
+        if c.len() != d.len() {
+            return false;
+        }
+        let (m, z) = compute_composites(pk, c, d);
+        let t2 = G1::generator().binary_scalar_mul(pk, &self.s, &self.c);
+        let t3 = m.binary_scalar_mul(&z, &self.s, &self.c);
+        proof_challenge(pk, &m, &z, &t2, &t3) == self.c
+    }
+}
+
+/// Blind `input`, returning the blind to keep and the blinded element to send to the server
+pub fn blind(input: &[u8], mode: Mode) -> Result<(CurveOrderElement, G1), ValueError> {
+    let p = mode.hash_to_group(input);
+    if p.is_identity() {
+        return Err(ValueError::IdentityElement);
+    }
+    let mut r = CurveOrderElement::random();
+    while r.is_zero() {
+        r = CurveOrderElement::random();
+    }
+    let (blinded, r) = p.blind(&r)?;
+    Ok((r, blinded))
+}
+
+fn finalize_unblinded(input: &[u8], unblinded: &G1) -> Vec<u8> {
+    let mut transcript = vec![];
+    append_with_len(&mut transcript, input);
+    append_with_len(&mut transcript, &serialize_element(unblinded));
+    transcript.extend_from_slice(b"Finalize");
+    Sha256::digest(&transcript).to_vec()
+}
+
+/// Output of the function on `input` from the server's evaluation in the base mode
+pub fn finalize(input: &[u8], blind: &CurveOrderElement, evaluated: &G1) -> Vec<u8> {
+    // `blind` never returns a zero blind
+    let unblinded = evaluated.unblind(blind).unwrap_or_else(|_| G1::identity());
+    finalize_unblinded(input, &unblinded)
+}
+
+/// Outputs of the function on `inputs` from the server's evaluations in the verifiable mode.
+/// Returns `None` when the proof does not verify for the blinded and evaluated elements.
+pub fn finalize_verifiable(
+    inputs: &[&[u8]],
+    blinds: &[CurveOrderElement],
+    blinded: &[G1],
+    evaluated: &[G1],
+    proof: &Proof,
+    pk: &ServerPublicKey,
+) -> Option<Vec<Vec<u8>>> {
+    if inputs.len() != blinds.len() || inputs.len() != evaluated.len() {
+        return None;
+    }
+    if !proof.verify(&pk.0, blinded, evaluated) {
+        return None;
+    }
+    Some(
+        inputs
+            .iter()
+            .zip(blinds.iter().zip(evaluated))
+            .map(|(input, (r, e))| finalize(input, r, e))
+            .collect(),
+    )
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_oprf() {
+        let sk = ServerSecretKey::random();
+        let input = b"test input";
+        let (r, blinded) = blind(input, Mode::Oprf).unwrap();
+        let evaluated = sk.blind_evaluate(&blinded);
+        let output = finalize(input, &r, &evaluated);
+        assert_eq!(output, sk.evaluate(input, Mode::Oprf).unwrap());
+        assert_eq!(output.len(), 32);
+
+        // Blinding is randomized but the output is not
+        let (r2, blinded2) = blind(input, Mode::Oprf).unwrap();
+        assert_ne!(blinded, blinded2);
+        assert_eq!(finalize(input, &r2, &sk.blind_evaluate(&blinded2)), output);
+
+        assert_ne!(sk.evaluate(b"another input", Mode::Oprf).unwrap(), output);
+        assert_ne!(
+            ServerSecretKey::random()
+                .evaluate(input, Mode::Oprf)
+                .unwrap(),
+            output
+        );
+        assert_ne!(sk.evaluate(input, Mode::Voprf).unwrap(), output);
+    }
+
+    #[test]
+    fn test_voprf() {
+        let sk = ServerSecretKey::random();
+        let pk = ServerPublicKey::new(&sk);
+        let inputs: Vec<&[u8]> = vec![b"input 1", b"input 2", b"input 3"];
+        let (blinds, blinded): (Vec<_>, Vec<_>) = inputs
+            .iter()
+            .map(|input| blind(input, Mode::Voprf).unwrap())
+            .unzip();
+        let (evaluated, proof) = sk.blind_evaluate_verifiable(&blinded);
+        let outputs =
+            finalize_verifiable(&inputs, &blinds, &blinded, &evaluated, &proof, &pk).unwrap();
+        for (input, output) in inputs.iter().zip(outputs.iter()) {
+            assert_eq!(*output, sk.evaluate(input, Mode::Voprf).unwrap());
+        }
+
+        // Proof for another key
+        let other_pk = ServerPublicKey::new(&ServerSecretKey::random());
+        assert!(
+            finalize_verifiable(&inputs, &blinds, &blinded, &evaluated, &proof, &other_pk)
+                .is_none()
+        );
+
+        // Server evaluating one element with another key
+        let mut bad = evaluated.clone();
+        bad[1] = ServerSecretKey::random().blind_evaluate(&blinded[1]);
+        assert!(finalize_verifiable(&inputs, &blinds, &blinded, &bad, &proof, &pk).is_none());
+        let other_sk = ServerSecretKey::random();
+        let (bad, bad_proof) = other_sk.blind_evaluate_verifiable(&blinded);
+        assert!(finalize_verifiable(&inputs, &blinds, &blinded, &bad, &bad_proof, &pk).is_none());
+
+        assert!(
+            finalize_verifiable(&inputs[1..], &blinds, &blinded, &evaluated, &proof, &pk).is_none()
+        );
+    }
+}
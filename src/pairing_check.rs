@@ -0,0 +1,186 @@
+//! Builder for verification equations of the form `prod e(P_i, Q_i)^(±1) = 1` or `= T`, where the
+//! G1 arguments may be linear combinations of points, as in Groth16, PS or BBS+ verification.
+//! Terms with the same G2 element are merged, their G1 sides are computed with one multi-scalar
+//! multiplication each and the product with one multi-pairing. Terms with the identity on either
+//! side contribute 1, like in `GT::ate_multi_pairing`.
+//!
+//! ```ignore
+//! let ok = PairingCheck::new()
+//!     .add(&proof.a, &proof.b)
+//!     .add(&pvk.ic[0], &pvk.neg_gamma_g2)
+//!     .also_msm(&pvk.ic.as_slice()[1..], public_inputs)
+//!     .sub(&proof.c, &pvk.delta_g2)
+//!     .verify_equals(&pvk.alpha_beta);
+//! ```
+//!
+//! Scalars are treated as public, the multi-scalar multiplications are variable time.
+
+use crate::curve_order_elem::CurveOrderElement;
+use crate::errors::ValueError;
+use crate::extension_field_gt::GT;
+use crate::group_elem::GroupElement;
+use crate::group_elem_g1::G1;
+use crate::group_elem_g2::G2;
+
+use rayon::prelude::*;
+
+#[derive(Clone, Debug)]
+struct Term {
+    g2: G2,
+    points: Vec<G1>,
+    scalars: Vec<CurveOrderElement>,
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct PairingCheck {
+    terms: Vec<Term>,
+    /// Index in `terms` of the term `also_msm` extends and whether it was subtracted
+    last: Option<(usize, bool)>,
+    /// First misuse of the builder, reported when evaluating
+    error: Option<ValueError>,
+}
+
+impl PairingCheck {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Multiply by `e(g1, g2)`
+    pub fn add(self, g1: &G1, g2: &G2) -> Self {
+        self.term(g1, CurveOrderElement::one(), g2, false)
+    }
+
+    /// Divide by `e(g1, g2)`
+    pub fn sub(self, g1: &G1, g2: &G2) -> Self {
+        self.term(g1, CurveOrderElement::minus_one(), g2, true)
+    }
+
+    /// Multiply by `e(g1, g2)^scalar`
+    pub fn add_scaled(self, g1: &G1, scalar: &CurveOrderElement, g2: &G2) -> Self {
+        self.term(g1, scalar.clone(), g2, false)
+    }
+
+    /// Add `points[0]*scalars[0] + points[1]*scalars[1] + ...` to the G1 side of the last added
+    /// term, with the sign of that term. Fails on evaluation if there is no such term or the
+    /// sizes differ.
+    pub fn also_msm(mut self, points: &[G1], scalars: &[CurveOrderElement]) -> Self {
+        if self.error.is_some() {
+            return self;
+        }
+        if let Err(e) = check_vector_size_for_equality!(points, scalars) {
+            self.error = Some(e);
+            return self;
+        }
+        let (last, negate) = match self.last {
+            Some(l) => l,
+            None => {
+                self.error = Some(ValueError::IncorrectSize(0));
+                return self;
+            }
+        };
+        let term = &mut self.terms[last];
+        term.points.extend_from_slice(points);
+        term.scalars
+            .extend(scalars.iter().map(|s| if negate { -s } else { s.clone() }));
+        self
+    }
+
+    fn term(mut self, g1: &G1, scalar: CurveOrderElement, g2: &G2, negated: bool) -> Self {
+        let idx = match self.terms.iter().position(|t| &t.g2 == g2) {
+            Some(i) => i,
+            None => {
+                self.terms.push(Term {
+                    g2: g2.clone(),
+                    points: vec![],
+                    scalars: vec![],
+                });
+                self.terms.len() - 1
+            }
+        };
+        self.terms[idx].points.push(g1.clone());
+        self.terms[idx].scalars.push(scalar);
+        self.last = Some((idx, negated));
+        self
+    }
+
+    /// Product of the pairing terms
+    pub fn evaluate(&self) -> Result<GT, ValueError> {
+        if let Some(e) = self.error {
+            return Err(e);
+        }
+        let g1s = self
+            .terms
+            .par_iter()
+            .map(|t| G1::msm_var_time(&t.points, &t.scalars))
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(GT::ate_multi_pairing(
+            g1s.iter().zip(self.terms.iter().map(|t| &t.g2)).collect(),
+        ))
+    }
+
+    /// Whether the product of the pairing terms is 1, false if the builder was misused
+    pub fn verify(&self) -> bool {
+        self.evaluate().map(|e| e.is_one()).unwrap_or(false)
+    }
+
+    /// Whether the product of the pairing terms is `target`, false if the builder was misused
+    pub fn verify_equals(&self, target: &GT) -> bool {
+        self.evaluate().map(|e| &e == target).unwrap_or(false)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_pairing_check() {
+        let g1 = G1::generator();
+        let g2 = G2::generator();
+        let (a, b) = (CurveOrderElement::random(), CurveOrderElement::random());
+
+        // e(a*g1, b*g2) = e(ab*g1, g2)
+        let ab = &a * &b;
+        assert!(PairingCheck::new()
+            .add(&(&g1 * &a), &(&g2 * &b))
+            .sub(&(&g1 * &ab), &g2)
+            .verify());
+        assert!(!PairingCheck::new()
+            .add(&(&g1 * &a), &(&g2 * &b))
+            .sub(&(&g1 * &a), &g2)
+            .verify());
+        assert!(PairingCheck::new()
+            .add_scaled(&(&g1 * &a), &b, &g2)
+            .sub(&(&g1 * &ab), &g2)
+            .verify());
+
+        // e(a*P, g2) * e(b*Q, g2) * e(g1, g2) / e(g1 + a*P + b*Q, g2) = 1, terms on g2 are merged
+        let p = G1::random();
+        let q = G1::random();
+        let check = PairingCheck::new()
+            .sub(&g1, &g2)
+            .also_msm(&[p.clone(), q.clone()], &[a.clone(), b.clone()])
+            .add(&g1, &g2)
+            .add(&(&p * &a), &g2)
+            .add(&(&q * &b), &g2);
+        assert_eq!(check.terms.len(), 1);
+        assert!(check.verify());
+
+        // Against a target and with identities
+        let target = GT::ate_pairing(&g1, &g2);
+        assert!(PairingCheck::new()
+            .add(&g1, &g2)
+            .add(&G1::identity(), &g2)
+            .add(&p, &G2::identity())
+            .verify_equals(&target));
+        assert!(PairingCheck::new().verify());
+
+        // Misuse
+        let (p, a) = (vec![p], vec![a]);
+        assert!(PairingCheck::new().also_msm(&p, &a).evaluate().is_err());
+        assert!(!PairingCheck::new()
+            .add(&g1, &g2)
+            .also_msm(&p, &[])
+            .verify_equals(&target));
+    }
+}
@@ -0,0 +1,252 @@
+//! Pointcheval-Sanders signatures on vectors of messages with public keys in group G2. The secret
+//! key is `(x, y_1, ..., y_r)` and a signature on `m_1, ..., m_r` is `(h, (x + sum y_i*m_i) * h)`
+//! for a random `h` in G1, verified with `e(sigma_1, X~ + sum m_i*Y~_i) == e(sigma_2, g2)`.
+//! Signatures can be randomized into unlinkable signatures on the same messages by multiplying
+//! both elements by a random scalar. Messages can also be signed blindly, committed as
+//! `t*g1 + sum m_i*Y_i` using the G1 part of the public key.
+
+use crate::curve_order_elem::{CurveOrderElement, CurveOrderElementVector};
+use crate::errors::ValueError;
+use crate::extension_field_gt::GT;
+use crate::group_elem::{GroupElement, GroupElementVector};
+use crate::group_elem_g1::{G1Vector, G1};
+use crate::group_elem_g2::{G2Vector, G2};
+use crate::group_params::GroupParams;
+
+use rand::{CryptoRng, RngCore};
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct SecretKey {
+    x: CurveOrderElement,
+    y: CurveOrderElementVector,
+}
+
+/// `X~ = x*g2` and `Y~_i = y_i*g2` for verifying, and `Y_i = y_i*g1` for committing to messages to
+/// be signed blindly
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct PublicKey {
+    x_g2: G2,
+    y_g2: G2Vector,
+    y_g1: G1Vector,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Signature {
+    sigma_1: G1,
+    sigma_2: G1,
+}
+
+/// Hash byte messages to the scalars that are signed
+pub fn hash_messages(msgs: &[&[u8]]) -> Vec<CurveOrderElement> {
+    msgs.iter()
+        .map(|m| CurveOrderElement::from_msg_hash(m))
+        .collect()
+}
+
+impl SecretKey {
+    /// Key for signing `message_count` messages at a time
+    pub fn random(message_count: usize) -> Self {
+        Self {
+            x: CurveOrderElement::random(),
+            y: CurveOrderElementVector::random(message_count),
+        }
+    }
+
+    pub fn random_using_rng<R: RngCore + CryptoRng>(message_count: usize, rng: &mut R) -> Self {
+        Self {
+            x: CurveOrderElement::random_using_rng(rng),
+            y: (0..message_count)
+                .map(|_| CurveOrderElement::random_using_rng(rng))
+                .collect(),
+        }
+    }
+
+    pub fn message_count(&self) -> usize {
+        self.y.len()
+    }
+
+    pub fn sign(&self, msgs: &[CurveOrderElement]) -> Result<Signature, ValueError> {
+        check_vector_size_for_equality!(self.y, msgs)?;
+        let h = random_non_identity();
+        let exp = self
+            .y
+            .iter()
+            .zip(msgs)
+            .fold(self.x.clone(), |acc, (y, m)| acc + y * m);
+        Ok(Signature {
+            sigma_2: &h * &exp,
+            sigma_1: h,
+        })
+    }
+
+    /// Sign the messages committed in `commitment = t*g1 + sum m_i*Y_i`. The signer does not learn
+    /// the messages, so it should first check a proof of knowledge of the opening. The requester
+    /// gets the signature with `Signature::unblind`.
+    pub fn blind_sign(&self, commitment: &G1, params: &GroupParams) -> Signature {
+        let u = random_non_zero();
+        let sigma_1 = params.g1() * &u;
+        let sigma_2 = (params.g1() * &self.x + commitment) * &u;
+        Signature { sigma_1, sigma_2 }
+    }
+}
+
+fn random_non_zero() -> CurveOrderElement {
+    loop {
+        let r = CurveOrderElement::random();
+        if !r.is_zero() {
+            return r;
+        }
+    }
+}
+
+fn random_non_identity() -> G1 {
+    G1::generator() * random_non_zero()
+}
+
+impl PublicKey {
+    pub fn new(sk: &SecretKey, params: &GroupParams) -> Self {
+        Self {
+            x_g2: params.g2() * &sk.x,
+            y_g2: sk.y.iter().map(|y| params.g2() * y).collect(),
+            y_g1: sk.y.iter().map(|y| params.g1() * y).collect(),
+        }
+    }
+
+    pub fn message_count(&self) -> usize {
+        self.y_g2.len()
+    }
+
+    /// Commitment `t*g1 + sum m_i*Y_i` to messages to be signed blindly, with blinding `t`
+    pub fn commit_messages(
+        &self,
+        msgs: &[CurveOrderElement],
+        t: &CurveOrderElement,
+        params: &GroupParams,
+    ) -> Result<G1, ValueError> {
+        check_vector_size_for_equality!(self.y_g1, msgs)?;
+        let mut bases = self.y_g1.clone();
+        bases.push(params.g1().clone());
+        bases.multi_scalar_mul_const_time(msgs.iter().chain(std::iter::once(t)))
+    }
+
+    /// Check `e(sigma_1, X~ + sum m_i*Y~_i) == e(sigma_2, g2)` and `sigma_1` is not the identity
+    pub fn verify(
+        &self,
+        msgs: &[CurveOrderElement],
+        sig: &Signature,
+        params: &GroupParams,
+    ) -> bool {
+        if msgs.len() != self.y_g2.len() || sig.sigma_1.is_identity() {
+            return false;
+        }
+        let y_m = match self.y_g2.multi_scalar_mul_var_time(msgs.iter()) {
+            Ok(y_m) => y_m,
+            Err(_) => return false,
+        };
+        GT::ate_2_pairing(
+            &sig.sigma_1,
+            &(y_m + &self.x_g2),
+            &sig.sigma_2.negation(),
+            params.g2(),
+        )
+        .is_one()
+    }
+}
+
+impl Signature {
+    /// Signature on the same messages that can't be linked to this one
+    pub fn randomize(&self) -> Self {
+        let t = random_non_zero();
+        Self {
+            sigma_1: &self.sigma_1 * &t,
+            sigma_2: &self.sigma_2 * &t,
+        }
+    }
+
+    /// Signature from a blind signature on a commitment with blinding `t`
+    pub fn unblind(&self, t: &CurveOrderElement) -> Self {
+        Self {
+            sigma_1: self.sigma_1.clone(),
+            sigma_2: &self.sigma_2 - &(&self.sigma_1 * t),
+        }
+    }
+
+    pub fn sigma_1(&self) -> &G1 {
+        &self.sigma_1
+    }
+
+    pub fn sigma_2(&self) -> &G1 {
+        &self.sigma_2
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_sign_verify() {
+        let params = GroupParams::default();
+        let sk = SecretKey::random(4);
+        let pk = PublicKey::new(&sk, &params);
+        assert_eq!(pk.message_count(), 4);
+        let msgs = hash_messages(&[b"name", b"age", b"country", b"expiry"]);
+        let sig = sk.sign(&msgs).unwrap();
+        assert!(pk.verify(&msgs, &sig, &params));
+
+        let mut other = msgs.clone();
+        other[2] = CurveOrderElement::random();
+        assert!(!pk.verify(&other, &sig, &params));
+        assert!(!pk.verify(&msgs[1..], &sig, &params));
+        assert!(sk.sign(&msgs[1..]).is_err());
+        let other_pk = PublicKey::new(&SecretKey::random(4), &params);
+        assert!(!other_pk.verify(&msgs, &sig, &params));
+
+        // The identity signature verifies for any messages and must be rejected
+        let identity = Signature {
+            sigma_1: G1::identity(),
+            sigma_2: G1::identity(),
+        };
+        assert!(!pk.verify(&msgs, &identity, &params));
+    }
+
+    #[test]
+    fn test_randomize() {
+        let params = GroupParams::from_label(b"test");
+        let sk = SecretKey::random(3);
+        let pk = PublicKey::new(&sk, &params);
+        let msgs: Vec<_> = (0..3).map(|_| CurveOrderElement::random()).collect();
+        let sig = sk.sign(&msgs).unwrap();
+        let randomized = sig.randomize();
+        assert_ne!(randomized, sig);
+        assert_ne!(randomized.sigma_1(), sig.sigma_1());
+        assert!(pk.verify(&msgs, &randomized, &params));
+        assert!(pk.verify(&msgs, &randomized.randomize(), &params));
+        assert!(!pk.verify(&msgs, &sig, &GroupParams::default()));
+    }
+
+    #[test]
+    fn test_blind_sign() {
+        let params = GroupParams::default();
+        let sk = SecretKey::random(3);
+        let pk = PublicKey::new(&sk, &params);
+        let msgs: Vec<_> = (0..3).map(|_| CurveOrderElement::random()).collect();
+        let t = CurveOrderElement::random();
+        let commitment = pk.commit_messages(&msgs, &t, &params).unwrap();
+        let blind_sig = sk.blind_sign(&commitment, &params);
+        assert!(!pk.verify(&msgs, &blind_sig, &params));
+        let sig = blind_sig.unblind(&t);
+        assert!(pk.verify(&msgs, &sig, &params));
+        assert!(!pk.verify(
+            &msgs,
+            &blind_sig.unblind(&CurveOrderElement::random()),
+            &params
+        ));
+        assert!(pk.commit_messages(&msgs[..2], &t, &params).is_err());
+    }
+}
@@ -0,0 +1,144 @@
+//! Private set intersection from the commutativity of blinding and keyed hashing to G1, in the
+//! style of the PSI protocols derived from Meadows and Huberman-Franklin-Hogg. The client hashes
+//! each element of its set to G1 and blinds it, the server multiplies the blinded elements by its
+//! key and returns them with the tags of its own set, `SHA256(k*H(y))`. The client removes the
+//! blinds, computes the tags `SHA256(k*H(x))` of its elements and learns which of them are in the
+//! intersection. The client learns nothing else about the server's set but its size, the server
+//! learns only the size of the client's set.
+//!
+//! Everything happens in G1 so no pairing is needed. Security is against semi-honest parties;
+//! the server should shuffle its tags and should not answer requests of unbounded size.
+
+use crate::curve_order_elem::{CurveOrderElement, CurveOrderElementVector};
+use crate::errors::ValueError;
+use crate::group_elem::{GroupElement, GroupElementVector};
+use crate::group_elem_g1::{G1Vector, G1};
+use crate::utils::hash_msg_to_point;
+
+use rayon::prelude::*;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::HashSet;
+
+const HASH_TO_GROUP_DST: &[u8] = b"AMCL_WRAPPER_PSI_HASH_TO_GROUP";
+const TAG_LABEL: &[u8] = b"PSI tag";
+
+/// Tag of a set element under the server's key
+pub type Tag = [u8; 32];
+
+/// Hash a set element to G1
+pub fn hash_to_group(element: &[u8]) -> G1 {
+    // The tag is not empty
+    hash_msg_to_point(element, HASH_TO_GROUP_DST).unwrap()
+}
+
+fn tag(evaluated: &G1) -> Tag {
+    let mut hasher = Sha256::new();
+    hasher.input(TAG_LABEL);
+    hasher.input(&evaluated.to_compressed_bytes()[..]);
+    let mut out = [0u8; 32];
+    out.copy_from_slice(hasher.result().as_slice());
+    out
+}
+
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct ServerKey(CurveOrderElement);
+
+impl ServerKey {
+    pub fn random() -> Self {
+        let mut k = CurveOrderElement::random();
+        while k.is_zero() {
+            k = CurveOrderElement::random();
+        }
+        Self(k)
+    }
+
+    /// Multiply the client's blinded elements by the key
+    pub fn evaluate(&self, blinded: &G1Vector) -> G1Vector {
+        blinded.scaled_by(&self.0)
+    }
+
+    /// Tags of the server's set, in the order of the set
+    pub fn tags(&self, set: &[&[u8]]) -> Vec<Tag> {
+        set.par_iter()
+            .map(|y| tag(&hash_to_group(y).scalar_mul_const_time(&self.0)))
+            .collect()
+    }
+}
+
+/// Hash the client's set to G1 and blind it, returning the blinded elements to send to the
+/// server and the blinds to keep
+pub fn blind_set(set: &[&[u8]]) -> Result<(G1Vector, CurveOrderElementVector), ValueError> {
+    let hashed: G1Vector = set
+        .par_iter()
+        .map(|x| hash_to_group(x))
+        .collect::<Vec<_>>()
+        .into();
+    if hashed.iter().any(|h| h.is_identity()) {
+        return Err(ValueError::IdentityElement);
+    }
+    let blinds: CurveOrderElementVector = (0..set.len())
+        .map(|_| {
+            let mut r = CurveOrderElement::random();
+            while r.is_zero() {
+                r = CurveOrderElement::random();
+            }
+            r
+        })
+        .collect::<Vec<_>>()
+        .into();
+    hashed.blind(&blinds)
+}
+
+/// Tags of the client's set from the server's evaluation of the blinded elements
+pub fn unblind_tags(
+    evaluated: &G1Vector,
+    blinds: &CurveOrderElementVector,
+) -> Result<Vec<Tag>, ValueError> {
+    let unblinded = evaluated.unblind(blinds)?;
+    Ok(unblinded.as_slice().par_iter().map(tag).collect())
+}
+
+/// Indices of the client's elements whose tags are among the server's tags
+pub fn intersection(client_tags: &[Tag], server_tags: &[Tag]) -> Vec<usize> {
+    let server: HashSet<&Tag> = server_tags.iter().collect();
+    client_tags
+        .iter()
+        .enumerate()
+        .filter(|(_, t)| server.contains(t))
+        .map(|(i, _)| i)
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_psi() {
+        let client_set: Vec<&[u8]> = vec![b"alice", b"bob", b"carol", b"dave"];
+        let server_set: Vec<&[u8]> = vec![b"erin", b"dave", b"frank", b"alice", b"grace"];
+        let key = ServerKey::random();
+
+        let (blinded, blinds) = blind_set(&client_set).unwrap();
+        // The blinded elements do not reveal the hashes
+        assert_ne!(blinded[0], hash_to_group(client_set[0]));
+
+        let evaluated = key.evaluate(&blinded);
+        let server_tags = key.tags(&server_set);
+        let client_tags = unblind_tags(&evaluated, &blinds).unwrap();
+        assert_eq!(intersection(&client_tags, &server_tags), vec![0, 3]);
+
+        // Tags under another key do not match
+        let other = ServerKey::random();
+        assert!(intersection(&client_tags, &other.tags(&server_set)).is_empty());
+
+        assert!(unblind_tags(&evaluated, &CurveOrderElementVector::random(3)).is_err());
+        let (blinded, blinds) = blind_set(&[]).unwrap();
+        assert!(unblind_tags(&key.evaluate(&blinded), &blinds)
+            .unwrap()
+            .is_empty());
+    }
+}
@@ -0,0 +1,216 @@
+//! Digit recodings of curve order elements for custom scalar multiplication, with the contracts of
+//! `CurveOrderElement::to_wnaf` and `CurveOrderElement::to_power_of_2_base` made explicit. Digits
+//! are least significant first in both.
+//!
+//! - `Wnaf` of width `w` in `2..=8`: every digit is 0 or odd with absolute value less than
+//!   `2^(w-1)`, of any `w` consecutive digits at most one is non zero, and the last digit is non
+//!   zero, so there are at most `bits + 1` digits for a scalar of `bits` bits and none for zero.
+//!   The scalar is `sum d_i * 2^i`. Computing it branches on the bits of the scalar.
+//! - `RadixDigits` of width `w` in `1..=7`: every digit is in `[0, 2^w)` and the scalar is
+//!   `sum d_i * 2^(w*i)`. There are `ceil(bits / w)` digits for a scalar of `bits` bits and a
+//!   single 0 digit for zero.
+
+use crate::curve_order_elem::CurveOrderElement;
+use crate::errors::ValueError;
+
+pub const MIN_WNAF_WIDTH: usize = 2;
+pub const MAX_WNAF_WIDTH: usize = 8;
+pub const MIN_RADIX_WIDTH: usize = 1;
+pub const MAX_RADIX_WIDTH: usize = 7;
+
+/// Width-w non adjacent form
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Wnaf {
+    width: usize,
+    digits: Vec<i8>,
+}
+
+/// Digits in base `2^w`
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RadixDigits {
+    width: usize,
+    digits: Vec<u8>,
+}
+
+impl Wnaf {
+    /// Fails if `width` is not in `MIN_WNAF_WIDTH..=MAX_WNAF_WIDTH`
+    pub fn new(scalar: &CurveOrderElement, width: usize) -> Result<Self, ValueError> {
+        check_width(width, MIN_WNAF_WIDTH, MAX_WNAF_WIDTH)?;
+        Ok(Self {
+            width,
+            digits: scalar.to_wnaf(width),
+        })
+    }
+
+    /// Check that `digits` are a wNAF of width `width`
+    pub fn from_digits(digits: Vec<i8>, width: usize) -> Result<Self, ValueError> {
+        check_width(width, MIN_WNAF_WIDTH, MAX_WNAF_WIDTH)?;
+        let bound = 1i16 << (width - 1);
+        let mut last_non_zero: Option<usize> = None;
+        for (i, d) in digits.iter().enumerate() {
+            if *d == 0 {
+                continue;
+            }
+            let d = *d as i16;
+            if d % 2 == 0 || d.abs() >= bound {
+                return Err(ValueError::OutOfRange(i));
+            }
+            if let Some(j) = last_non_zero {
+                if i - j < width {
+                    return Err(ValueError::OutOfRange(i));
+                }
+            }
+            last_non_zero = Some(i);
+        }
+        if digits.last() == Some(&0) {
+            return Err(ValueError::OutOfRange(digits.len() - 1));
+        }
+        Ok(Self { width, digits })
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn digits(&self) -> &[i8] {
+        &self.digits
+    }
+
+    pub fn into_digits(self) -> Vec<i8> {
+        self.digits
+    }
+
+    /// `sum d_i * 2^i` modulo the curve order
+    pub fn to_scalar(&self) -> CurveOrderElement {
+        let mut acc = CurveOrderElement::zero();
+        for d in self.digits.iter().rev() {
+            acc = &acc + &acc;
+            if *d >= 0 {
+                acc += CurveOrderElement::from(*d as u8);
+            } else {
+                acc -= CurveOrderElement::from(d.unsigned_abs());
+            }
+        }
+        acc
+    }
+}
+
+impl RadixDigits {
+    /// Fails if `width` is not in `MIN_RADIX_WIDTH..=MAX_RADIX_WIDTH`
+    pub fn new(scalar: &CurveOrderElement, width: usize) -> Result<Self, ValueError> {
+        check_width(width, MIN_RADIX_WIDTH, MAX_RADIX_WIDTH)?;
+        Ok(Self {
+            width,
+            digits: scalar.to_power_of_2_base(width),
+        })
+    }
+
+    /// Check that `digits` are less than `2^width`
+    pub fn from_digits(digits: Vec<u8>, width: usize) -> Result<Self, ValueError> {
+        check_width(width, MIN_RADIX_WIDTH, MAX_RADIX_WIDTH)?;
+        if let Some(i) = digits.iter().position(|d| (*d as usize) >> width != 0) {
+            return Err(ValueError::OutOfRange(i));
+        }
+        Ok(Self { width, digits })
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn digits(&self) -> &[u8] {
+        &self.digits
+    }
+
+    pub fn into_digits(self) -> Vec<u8> {
+        self.digits
+    }
+
+    /// `sum d_i * 2^(w*i)` modulo the curve order
+    pub fn to_scalar(&self) -> CurveOrderElement {
+        CurveOrderElement::from_power_of_2_base(&self.digits, self.width)
+    }
+}
+
+fn check_width(width: usize, min: usize, max: usize) -> Result<(), ValueError> {
+    if width < min || width > max {
+        return Err(ValueError::OutOfRange(width));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::constants::CURVE_ORDER;
+
+    fn scalars() -> Vec<CurveOrderElement> {
+        let mut elems = vec![
+            CurveOrderElement::zero(),
+            CurveOrderElement::one(),
+            CurveOrderElement::minus_one(),
+            CurveOrderElement::from(255u64),
+        ];
+        elems.extend((0..10).map(|_| CurveOrderElement::random()));
+        elems
+    }
+
+    #[test]
+    fn test_wnaf() {
+        let bits = CURVE_ORDER.nbits();
+        for width in MIN_WNAF_WIDTH..=MAX_WNAF_WIDTH {
+            for x in scalars() {
+                let wnaf = Wnaf::new(&x, width).unwrap();
+                assert_eq!(wnaf.to_scalar(), x);
+                assert!(wnaf.digits().len() <= bits + 1);
+                // The contract is what `from_digits` checks
+                let checked = Wnaf::from_digits(wnaf.digits().to_vec(), width).unwrap();
+                assert_eq!(checked, wnaf);
+            }
+        }
+        assert!(Wnaf::new(&CurveOrderElement::zero(), 5)
+            .unwrap()
+            .digits()
+            .is_empty());
+        assert!(Wnaf::new(&CurveOrderElement::one(), 1).is_err());
+        assert!(Wnaf::new(&CurveOrderElement::one(), 9).is_err());
+
+        // Even digit, digit out of range, adjacent non zero digits and a trailing zero
+        assert!(Wnaf::from_digits(vec![2], 3).is_err());
+        assert!(Wnaf::from_digits(vec![5], 3).is_err());
+        assert!(Wnaf::from_digits(vec![1, 0, 3], 3).is_err());
+        assert!(Wnaf::from_digits(vec![1, 0, 0, 3], 3).is_ok());
+        assert!(Wnaf::from_digits(vec![1, 0], 3).is_err());
+        assert_eq!(
+            Wnaf::from_digits(vec![-1, 0, 0, 1], 3).unwrap().to_scalar(),
+            CurveOrderElement::from(7u64)
+        );
+    }
+
+    #[test]
+    fn test_radix_digits() {
+        let bits = CURVE_ORDER.nbits();
+        for width in MIN_RADIX_WIDTH..=MAX_RADIX_WIDTH {
+            for x in scalars() {
+                let digits = RadixDigits::new(&x, width).unwrap();
+                assert_eq!(digits.to_scalar(), x);
+                assert!(digits.digits().len() <= bits.div_ceil(width));
+                let checked = RadixDigits::from_digits(digits.digits().to_vec(), width).unwrap();
+                assert_eq!(checked, digits);
+            }
+        }
+        assert_eq!(
+            RadixDigits::new(&CurveOrderElement::zero(), 4)
+                .unwrap()
+                .digits(),
+            &[0]
+        );
+        assert!(RadixDigits::new(&CurveOrderElement::one(), 0).is_err());
+        assert!(RadixDigits::new(&CurveOrderElement::one(), 8).is_err());
+        assert!(RadixDigits::from_digits(vec![3, 4], 2).is_err());
+        assert_eq!(
+            RadixDigits::from_digits(vec![3, 1], 2).unwrap().to_scalar(),
+            CurveOrderElement::from(7u64)
+        );
+    }
+}
@@ -0,0 +1,203 @@
+//! Bulk conversion of stored elements between the hex strings of the serde implementations and
+//! the binary encodings of `ElementEncoding`, for migrating stored data in either direction. The
+//! hex strings are the ones of `to_hex`, hex coordinates separated by whitespace for group
+//! elements. Elements are validated in both directions like `ElementEncoding::decode` does, so
+//! invalid stored elements are reported instead of being carried over.
+//!
+//! Vectors use the layout of `ElementEncoding::encode_vec` on the binary side and a list of hex
+//! strings on the other, the serde layout of the vector types. The `reencode_*` functions convert
+//! many independent records in parallel with a result per record, so one bad record does not stop
+//! a migration.
+
+use crate::curve_order_elem::CurveOrderElement;
+use crate::encoding::ElementEncoding;
+use crate::errors::SerzDeserzError;
+use crate::group_elem::GroupElement;
+use crate::group_elem_g1::G1;
+#[cfg(any(feature = "bls381", feature = "bn254"))]
+use crate::{extension_field_gt::GT, group_elem_g2::G2};
+
+use rayon::prelude::*;
+
+/// Hex string format of the serde implementations
+pub trait HexEncoding: Sized {
+    fn to_hex_string(&self) -> String;
+
+    /// Parses without validating the element
+    fn from_hex_string(s: &str) -> Result<Self, SerzDeserzError>;
+}
+
+impl HexEncoding for CurveOrderElement {
+    fn to_hex_string(&self) -> String {
+        self.to_hex()
+    }
+
+    fn from_hex_string(s: &str) -> Result<Self, SerzDeserzError> {
+        Self::from_hex(s.to_string())
+    }
+}
+
+macro_rules! impl_hex_encoding {
+    ( $element:ident ) => {
+        impl HexEncoding for $element {
+            fn to_hex_string(&self) -> String {
+                self.to_hex()
+            }
+
+            fn from_hex_string(s: &str) -> Result<Self, SerzDeserzError> {
+                Self::from_hex(s.to_string())
+            }
+        }
+    };
+}
+
+impl_hex_encoding!(G1);
+#[cfg(any(feature = "bls381", feature = "bn254"))]
+impl_hex_encoding!(G2);
+#[cfg(any(feature = "bls381", feature = "bn254"))]
+impl_hex_encoding!(GT);
+
+/// Parse a hex string and validate the element
+fn parse_hex<E: ElementEncoding + HexEncoding>(hex: &str) -> Result<E, SerzDeserzError> {
+    let elem = E::from_hex_string(hex)?;
+    // Round trip through the strict decoding, which also rejects points not on the curve
+    E::decode(&elem.encode())
+}
+
+/// Binary encoding of the element of a hex string
+pub fn hex_to_binary<E: ElementEncoding + HexEncoding>(
+    hex: &str,
+) -> Result<Vec<u8>, SerzDeserzError> {
+    parse_hex::<E>(hex).map(|e| e.encode())
+}
+
+/// Hex string of the element of a binary encoding
+pub fn binary_to_hex<E: ElementEncoding + HexEncoding>(
+    bytes: &[u8],
+) -> Result<String, SerzDeserzError> {
+    E::decode(bytes).map(|e| e.to_hex_string())
+}
+
+/// Binary encoding of a vector from the hex strings of its elements
+pub fn hex_vec_to_binary<E, S>(hexes: &[S]) -> Result<Vec<u8>, SerzDeserzError>
+where
+    E: ElementEncoding + HexEncoding + Send,
+    S: AsRef<str> + Sync,
+{
+    let elems: Vec<E> = hexes
+        .par_iter()
+        .map(|h| parse_hex(h.as_ref()))
+        .collect::<Result<_, _>>()?;
+    Ok(E::encode_vec(&elems))
+}
+
+/// Hex strings of the elements of a binary encoded vector
+pub fn binary_vec_to_hex<E>(bytes: &[u8]) -> Result<Vec<String>, SerzDeserzError>
+where
+    E: ElementEncoding + HexEncoding + Sync,
+{
+    let elems = E::decode_vec(bytes)?;
+    Ok(elems.par_iter().map(|e| e.to_hex_string()).collect())
+}
+
+/// `hex_to_binary` of many records
+pub fn reencode_hex_to_binary<E, S>(records: &[S]) -> Vec<Result<Vec<u8>, SerzDeserzError>>
+where
+    E: ElementEncoding + HexEncoding,
+    S: AsRef<str> + Sync,
+{
+    records
+        .par_iter()
+        .map(|r| hex_to_binary::<E>(r.as_ref()))
+        .collect()
+}
+
+/// `binary_to_hex` of many records
+pub fn reencode_binary_to_hex<E, B>(records: &[B]) -> Vec<Result<String, SerzDeserzError>>
+where
+    E: ElementEncoding + HexEncoding,
+    B: AsRef<[u8]> + Sync,
+{
+    records
+        .par_iter()
+        .map(|r| binary_to_hex::<E>(r.as_ref()))
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::group_elem::GroupElementVector;
+    use crate::group_elem_g1::G1Vector;
+
+    fn check<E>(elems: Vec<E>)
+    where
+        E: ElementEncoding + HexEncoding + PartialEq + std::fmt::Debug + Send + Sync,
+    {
+        let hexes: Vec<String> = elems.iter().map(|e| e.to_hex_string()).collect();
+        for (e, h) in elems.iter().zip(hexes.iter()) {
+            let bytes = hex_to_binary::<E>(h).unwrap();
+            assert_eq!(bytes, e.encode());
+            assert_eq!(
+                E::from_hex_string(&binary_to_hex::<E>(&bytes).unwrap()).unwrap(),
+                *e
+            );
+        }
+
+        let bytes = hex_vec_to_binary::<E, _>(&hexes).unwrap();
+        assert_eq!(bytes, E::encode_vec(&elems));
+        let back = binary_vec_to_hex::<E>(&bytes).unwrap();
+        assert_eq!(hex_vec_to_binary::<E, _>(&back).unwrap(), bytes);
+
+        let records: Vec<Vec<u8>> = reencode_hex_to_binary::<E, _>(&hexes)
+            .into_iter()
+            .collect::<Result<_, _>>()
+            .unwrap();
+        assert_eq!(records[0], elems[0].encode());
+        assert!(reencode_binary_to_hex::<E, _>(&records)
+            .iter()
+            .all(|r| r.is_ok()));
+    }
+
+    #[test]
+    fn test_reencode() {
+        check((0..4).map(|_| CurveOrderElement::random()).collect());
+        check((0..4).map(|_| G1::random()).collect());
+        #[cfg(any(feature = "bls381", feature = "bn254"))]
+        {
+            check((0..3).map(|_| G2::random()).collect());
+            check((0..3).map(|_| GT::random()).collect());
+        }
+
+        // The serde format of vectors is a list of the hex strings of the elements
+        let v = G1Vector::random(3);
+        let hexes: Vec<String> = v.iter().map(|e| e.to_hex()).collect();
+        assert_eq!(hex_vec_to_binary::<G1, _>(&hexes).unwrap(), v.to_bytes());
+    }
+
+    #[test]
+    fn test_reencode_rejects_invalid() {
+        // A point not on the curve
+        let mut coords: Vec<String> = G1::random()
+            .to_hex()
+            .split_whitespace()
+            .map(String::from)
+            .collect();
+        coords[1] = coords[0].clone();
+        let bad = coords.join(" ");
+        assert!(hex_to_binary::<G1>(&bad).is_err());
+        assert!(hex_to_binary::<G1>("not hex").is_err());
+
+        let good = G1::random().to_hex();
+        let results = reencode_hex_to_binary::<G1, _>(&[good.clone(), bad.clone()]);
+        assert!(results[0].is_ok());
+        assert!(results[1].is_err());
+        assert!(hex_vec_to_binary::<G1, _>(&[good, bad]).is_err());
+
+        let mut bytes = G1::random().encode();
+        let last = bytes.len() - 1;
+        bytes[last] ^= 1;
+        assert!(binary_to_hex::<G1>(&bytes).is_err());
+        assert!(binary_vec_to_hex::<G1>(&[0u8; 3]).is_err());
+    }
+}
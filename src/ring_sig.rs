@@ -0,0 +1,368 @@
+//! AOS (Abe, Ohkubo, Suzuki) ring signatures over group G1. A signature proves knowledge of the
+//! secret key of one of the public keys in a ring without revealing which one. It is an
+//! OR-composition of Schnorr proofs where each member's challenge is the hash of the previous
+//! member's commitment, `c_(i+1) = H(prefix || R_i)` with `R_i = g*s_i + pk_i*c_i`, and the signer
+//! closes the chain with its secret key. Keys are the Schnorr keys of this crate so rings can be
+//! formed on any of the curves, including the ones without a pairing.
+//!
+//! Linkable ring signatures (Liu, Wei, Wong) additionally carry the key image `I = x * H(pk)` of the
+//! signer and prove it uses the same secret key as the signer's public key. The key image does not
+//! reveal the signer but is the same for all signatures by a key, so two signatures by the same
+//! member are detected by comparing key images.
+
+use crate::curve_order_elem::CurveOrderElement;
+use crate::errors::ValueError;
+use crate::group_elem::GroupElement;
+use crate::group_elem_g1::G1;
+use crate::group_params::GroupParams;
+use crate::schnorr::{PublicKey, SecretKey};
+use crate::utils::hash_msg_to_point;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// Relation of one member of an OR-proof, knowledge of `x` with `base * x = image` for every
+/// `(base, image)` pair. A single pair is knowledge of a discrete log, several pairs prove the
+/// discrete logs are equal.
+pub type Statement = Vec<(G1, G1)>;
+
+/// Non-interactive proof of knowledge of the witness of one of several statements, without
+/// revealing which. Holds the challenge of the first statement and a response per statement.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct OrProof {
+    c: CurveOrderElement,
+    s: Vec<CurveOrderElement>,
+}
+
+/// Bytes hashed into every challenge, the caller's prefix followed by all statements
+fn transcript(statements: &[Statement], prefix: &[u8]) -> Vec<u8> {
+    let mut bytes = prefix.to_vec();
+    for (base, image) in statements.iter().flatten() {
+        bytes.extend_from_slice(&base.to_vec());
+        bytes.extend_from_slice(&image.to_vec());
+    }
+    bytes
+}
+
+fn chain_challenge(transcript: &[u8], commitments: &[G1]) -> CurveOrderElement {
+    let mut bytes = transcript.to_vec();
+    for r in commitments {
+        bytes.extend_from_slice(&r.to_vec());
+    }
+    CurveOrderElement::from_msg_hash(&bytes)
+}
+
+/// Commitments `base*s + image*c` of a statement with response `s` to challenge `c`
+fn commitments(statement: &[(G1, G1)], s: &CurveOrderElement, c: &CurveOrderElement) -> Vec<G1> {
+    statement
+        .iter()
+        .map(|(base, image)| base.binary_scalar_mul(image, s, c))
+        .collect()
+}
+
+impl OrProof {
+    /// Prove knowledge of `witness` for the statement at `index`. `prefix` is hashed into the
+    /// challenges, e.g. a message or a protocol label.
+    pub fn new(
+        statements: &[Statement],
+        index: usize,
+        witness: &CurveOrderElement,
+        prefix: &[u8],
+    ) -> Result<Self, ValueError> {
+        let n = statements.len();
+        if index >= n {
+            return Err(ValueError::OutOfRange(index));
+        }
+        if statements.iter().any(|st| st.is_empty()) {
+            return Err(ValueError::IncorrectSize(0));
+        }
+        let transcript = transcript(statements, prefix);
+        let mut c = vec![CurveOrderElement::zero(); n];
+        let mut s = vec![CurveOrderElement::zero(); n];
+
+        let k = CurveOrderElement::random();
+        let r: Vec<_> = statements[index]
+            .iter()
+            .map(|(base, _)| base * &k)
+            .collect();
+        let mut i = (index + 1) % n;
+        c[i] = chain_challenge(&transcript, &r);
+        // Simulate the other members going around the ring back to the prover
+        while i != index {
+            s[i] = CurveOrderElement::random();
+            let r = commitments(&statements[i], &s[i], &c[i]);
+            let next = (i + 1) % n;
+            c[next] = chain_challenge(&transcript, &r);
+            i = next;
+        }
+        s[index] = k - &c[index] * witness;
+        Ok(Self {
+            c: c.swap_remove(0),
+            s,
+        })
+    }
+
+    pub fn verify(&self, statements: &[Statement], prefix: &[u8]) -> bool {
+        if statements.is_empty() || statements.len() != self.s.len() {
+            return false;
+        }
+        if statements.iter().any(|st| st.is_empty()) {
+            return false;
+        }
+        let transcript = transcript(statements, prefix);
+        let c = statements
+            .iter()
+            .zip(self.s.iter())
+            .fold(self.c.clone(), |c, (statement, s)| {
+                chain_challenge(&transcript, &commitments(statement, s, &c))
+            });
+        c == self.c
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct RingSignature(OrProof);
+
+fn ring_statements(ring: &[PublicKey], params: &GroupParams) -> Vec<Statement> {
+    ring.iter()
+        .map(|pk| vec![(params.g1().clone(), pk.as_point().clone())])
+        .collect()
+}
+
+impl RingSignature {
+    /// Sign with respect to the standard generator of group G1. The public key of `sk` must be
+    /// in `ring`.
+    pub fn new(msg: &[u8], ring: &[PublicKey], sk: &SecretKey) -> Result<Self, ValueError> {
+        Self::new_with_params(msg, ring, sk, &GroupParams::default())
+    }
+
+    pub fn new_with_params(
+        msg: &[u8],
+        ring: &[PublicKey],
+        sk: &SecretKey,
+        params: &GroupParams,
+    ) -> Result<Self, ValueError> {
+        let pk = PublicKey::new_with_params(sk, params);
+        let index = ring
+            .iter()
+            .position(|p| *p == pk)
+            .ok_or(ValueError::OutOfRange(ring.len()))?;
+        OrProof::new(&ring_statements(ring, params), index, sk.as_scalar(), msg).map(Self)
+    }
+
+    /// Verify against the ring of public keys, in the order used for signing
+    pub fn verify(&self, msg: &[u8], ring: &[PublicKey]) -> bool {
+        self.verify_with_params(msg, ring, &GroupParams::default())
+    }
+
+    pub fn verify_with_params(&self, msg: &[u8], ring: &[PublicKey], params: &GroupParams) -> bool {
+        if ring.iter().any(|pk| pk.as_point().is_identity()) {
+            return false;
+        }
+        self.0.verify(&ring_statements(ring, params), msg)
+    }
+
+    pub fn as_or_proof(&self) -> &OrProof {
+        &self.0
+    }
+}
+
+/// `x * H(pk)` for the secret key `x` of public key `pk`
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct KeyImage(G1);
+
+const KEY_IMAGE_DST: &[u8] = b"AMCL_WRAPPER_RING_SIG_KEY_IMAGE";
+
+fn key_image_base(pk: &PublicKey) -> G1 {
+    // The tag is not empty
+    hash_msg_to_point(&pk.to_bytes(), KEY_IMAGE_DST).unwrap()
+}
+
+impl KeyImage {
+    /// Key image of a secret key whose public key is `pk`
+    pub fn new(sk: &SecretKey, pk: &PublicKey) -> Self {
+        Self(key_image_base(pk) * sk.as_scalar())
+    }
+
+    pub fn as_point(&self) -> &G1 {
+        &self.0
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct LinkableRingSignature {
+    proof: OrProof,
+    key_image: KeyImage,
+}
+
+fn linkable_ring_statements(
+    ring: &[PublicKey],
+    key_image: &KeyImage,
+    params: &GroupParams,
+) -> Vec<Statement> {
+    ring.iter()
+        .map(|pk| {
+            vec![
+                (params.g1().clone(), pk.as_point().clone()),
+                (key_image_base(pk), key_image.0.clone()),
+            ]
+        })
+        .collect()
+}
+
+impl LinkableRingSignature {
+    /// Sign with respect to the standard generator of group G1. The public key of `sk` must be
+    /// in `ring`.
+    pub fn new(msg: &[u8], ring: &[PublicKey], sk: &SecretKey) -> Result<Self, ValueError> {
+        Self::new_with_params(msg, ring, sk, &GroupParams::default())
+    }
+
+    pub fn new_with_params(
+        msg: &[u8],
+        ring: &[PublicKey],
+        sk: &SecretKey,
+        params: &GroupParams,
+    ) -> Result<Self, ValueError> {
+        let pk = PublicKey::new_with_params(sk, params);
+        let index = ring
+            .iter()
+            .position(|p| *p == pk)
+            .ok_or(ValueError::OutOfRange(ring.len()))?;
+        let key_image = KeyImage::new(sk, &pk);
+        let statements = linkable_ring_statements(ring, &key_image, params);
+        let proof = OrProof::new(&statements, index, sk.as_scalar(), msg)?;
+        Ok(Self { proof, key_image })
+    }
+
+    /// Verify against the ring of public keys, in the order used for signing
+    pub fn verify(&self, msg: &[u8], ring: &[PublicKey]) -> bool {
+        self.verify_with_params(msg, ring, &GroupParams::default())
+    }
+
+    pub fn verify_with_params(&self, msg: &[u8], ring: &[PublicKey], params: &GroupParams) -> bool {
+        if self.key_image.0.is_identity() || ring.iter().any(|pk| pk.as_point().is_identity()) {
+            return false;
+        }
+        self.proof.verify(
+            &linkable_ring_statements(ring, &self.key_image, params),
+            msg,
+        )
+    }
+
+    pub fn key_image(&self) -> &KeyImage {
+        &self.key_image
+    }
+
+    /// Whether both signatures were created with the same secret key. Only meaningful for
+    /// signatures that verify.
+    pub fn is_linked(&self, other: &Self) -> bool {
+        self.key_image == other.key_image
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_ring_sign_verify() {
+        let sks: Vec<_> = (0..5).map(|_| SecretKey::random()).collect();
+        let ring: Vec<_> = sks.iter().map(PublicKey::from).collect();
+        let msg = b"test message";
+        for sk in &sks {
+            let sig = RingSignature::new(msg, &ring, sk).unwrap();
+            assert!(sig.verify(msg, &ring));
+            assert!(!sig.verify(b"another message", &ring));
+            assert!(!sig.verify(msg, &ring[1..]));
+            let mut reordered = ring.clone();
+            reordered.swap(0, 3);
+            assert!(!sig.verify(msg, &reordered));
+        }
+
+        // Signer must be in the ring
+        assert!(RingSignature::new(msg, &ring, &SecretKey::random()).is_err());
+        assert!(RingSignature::new(msg, &[], &sks[0]).is_err());
+
+        // Ring of a single key
+        let sig = RingSignature::new(msg, &ring[2..3], &sks[2]).unwrap();
+        assert!(sig.verify(msg, &ring[2..3]));
+        assert!(!sig.verify(msg, &ring[1..2]));
+
+        let params = GroupParams::from_label(b"test");
+        let ring: Vec<_> = sks
+            .iter()
+            .map(|sk| PublicKey::new_with_params(sk, &params))
+            .collect();
+        let sig = RingSignature::new_with_params(msg, &ring, &sks[1], &params).unwrap();
+        assert!(sig.verify_with_params(msg, &ring, &params));
+        assert!(!sig.verify(msg, &ring));
+    }
+
+    #[test]
+    fn test_or_proof_of_equal_discrete_logs() {
+        let g = G1::generator();
+        let h = G1::random();
+        let x = CurveOrderElement::random();
+        let statements: Vec<Statement> = vec![
+            vec![(g.clone(), G1::random()), (h.clone(), G1::random())],
+            vec![(g.clone(), &g * &x), (h.clone(), &h * &x)],
+            vec![(g.clone(), G1::random()), (h.clone(), G1::random())],
+        ];
+        let proof = OrProof::new(&statements, 1, &x, b"label").unwrap();
+        assert!(proof.verify(&statements, b"label"));
+        assert!(!proof.verify(&statements, b"other label"));
+
+        // Discrete logs that differ across the pair do not verify
+        let y = CurveOrderElement::random();
+        let mut bad = statements.clone();
+        bad[1][1].1 = &h * &y;
+        let proof = OrProof::new(&bad, 1, &x, b"label").unwrap();
+        assert!(!proof.verify(&bad, b"label"));
+
+        assert!(OrProof::new(&statements, 3, &x, b"label").is_err());
+        assert!(OrProof::new(&[vec![]], 0, &x, b"label").is_err());
+    }
+
+    #[test]
+    fn test_linkable_ring_sign_verify() {
+        let sks: Vec<_> = (0..4).map(|_| SecretKey::random()).collect();
+        let ring: Vec<_> = sks.iter().map(PublicKey::from).collect();
+        let msg = b"test message";
+
+        let sigs: Vec<_> = sks
+            .iter()
+            .map(|sk| LinkableRingSignature::new(msg, &ring, sk).unwrap())
+            .collect();
+        for (i, sig) in sigs.iter().enumerate() {
+            assert!(sig.verify(msg, &ring));
+            assert!(!sig.verify(b"another message", &ring));
+            assert!(!sig.verify(msg, &ring[..3]));
+            assert_eq!(sig.key_image(), &KeyImage::new(&sks[i], &ring[i]));
+            for (j, other) in sigs.iter().enumerate() {
+                assert_eq!(sig.is_linked(other), i == j);
+            }
+        }
+
+        // The same key signing again, on another message and in another ring, is linked
+        let other_ring = vec![
+            PublicKey::from(&SecretKey::random()),
+            ring[2].clone(),
+            PublicKey::from(&SecretKey::random()),
+        ];
+        let sig = LinkableRingSignature::new(b"another message", &other_ring, &sks[2]).unwrap();
+        assert!(sig.verify(b"another message", &other_ring));
+        assert!(sig.is_linked(&sigs[2]));
+        assert!(!sig.is_linked(&sigs[1]));
+
+        // Swapping in the key image of another member breaks the signature
+        let mut forged = sigs[0].clone();
+        forged.key_image = sigs[1].key_image.clone();
+        assert!(!forged.verify(msg, &ring));
+
+        assert!(LinkableRingSignature::new(msg, &ring, &SecretKey::random()).is_err());
+    }
+}
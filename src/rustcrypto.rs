@@ -0,0 +1,846 @@
+//! Implementations of the RustCrypto `elliptic-curve` traits (and the `ff` and `group` traits they
+//! build on) for secp256k1 and NIST P-256 so that the crate can be used with `ecdsa`, `ecdh` and other
+//! crates generic over `elliptic_curve::CurveArithmetic`.
+//! `Scalar`, `ProjectivePoint` and `AffinePoint` are `Copy` wrappers (the traits demand `Copy`) that
+//! convert from and into `CurveOrderElement` and `G1`.
+
+use crate::curve_order_elem::CurveOrderElement;
+use crate::group_elem::GroupElement;
+use crate::group_elem_g1::G1;
+use crate::types::{BigNum, GroupG1};
+
+use elliptic_curve::bigint::{ArrayEncoding, Encoding, Integer, Limb, U256};
+use elliptic_curve::ff::{helpers, Field, PrimeField};
+use elliptic_curve::generic_array::typenum::U32;
+use elliptic_curve::group::prime::{PrimeCurve as PrimeCurveGroup, PrimeCurveAffine, PrimeGroup};
+use elliptic_curve::group::{Curve as CurveGroup, Group, GroupEncoding};
+use elliptic_curve::ops::{Invert, LinearCombination, MulByGenerator, Reduce};
+use elliptic_curve::point::AffineCoordinates;
+use elliptic_curve::rand_core::RngCore;
+use elliptic_curve::scalar::{FromUintUnchecked, IsHigh};
+use elliptic_curve::sec1::{CompressedPoint, FromEncodedPoint, ToEncodedPoint};
+use elliptic_curve::subtle::{
+    Choice, ConditionallySelectable, ConstantTimeEq, ConstantTimeGreater, CtOption,
+};
+use elliptic_curve::zeroize::DefaultIsZeroes;
+use elliptic_curve::{
+    Curve, CurveArithmetic, FieldBytes, FieldBytesEncoding, PrimeCurve, PrimeCurveArithmetic,
+    ScalarPrimitive,
+};
+
+use std::iter::{Product, Sum};
+use std::ops::{Add, AddAssign, Mul, MulAssign, Neg, Sub, SubAssign};
+
+#[cfg(feature = "secp256k1")]
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq, PartialOrd, Ord)]
+pub struct Secp256k1;
+
+#[cfg(feature = "secp256k1")]
+type ThisCurve = Secp256k1;
+
+#[cfg(feature = "nist256")]
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq, PartialOrd, Ord)]
+pub struct NistP256;
+
+#[cfg(feature = "nist256")]
+type ThisCurve = NistP256;
+
+pub type EncodedPoint = elliptic_curve::sec1::EncodedPoint<ThisCurve>;
+
+#[cfg(feature = "secp256k1")]
+mod params {
+    use super::U256;
+
+    pub const ORDER_HEX: &str = "fffffffffffffffffffffffffffffffebaaedce6af48a03bbfd25e8cd0364141";
+    pub const S: u32 = 6;
+    pub const TWO_INV: U256 =
+        U256::from_be_hex("7fffffffffffffffffffffffffffffff5d576e7357a4501ddfe92f46681b20a1");
+    pub const ROOT_OF_UNITY: U256 =
+        U256::from_be_hex("0c1dc060e7a91986df9879a3fbc483a898bdeab680756045992f4b5402b052f2");
+    pub const ROOT_OF_UNITY_INV: U256 =
+        U256::from_be_hex("fd3ae181f12d7096efc7b0c75b8cbb7277a275910aa413c3b6fb30a0884f0d1c");
+    pub const DELTA: U256 =
+        U256::from_be_hex("0000000000000000000cbc21fe4561c8d63b78e780e1341e199417c8c0bb7601");
+    /// `(t - 1) / 2` where `order - 1 = 2^S * t`, little endian 64-bit words
+    pub const T_MINUS_1_OVER_2: [u64; 4] = [
+        0x777fa4bd19a06c82,
+        0xfd755db9cd5e9140,
+        0xffffffffffffffff,
+        0x01ffffffffffffff,
+    ];
+}
+
+#[cfg(feature = "nist256")]
+mod params {
+    use super::U256;
+
+    pub const ORDER_HEX: &str = "ffffffff00000000ffffffffffffffffbce6faada7179e84f3b9cac2fc632551";
+    pub const S: u32 = 4;
+    pub const TWO_INV: U256 =
+        U256::from_be_hex("7fffffff800000007fffffffffffffffde737d56d38bcf4279dce5617e3192a9");
+    pub const ROOT_OF_UNITY: U256 =
+        U256::from_be_hex("ffc97f062a770992ba807ace842a3dfc1546cad004378daf0592d7fbb41e6602");
+    pub const ROOT_OF_UNITY_INV: U256 =
+        U256::from_be_hex("a0a66a5562d46f2ac645fa0458131caee3ac117c794c4137379c7f0657c73764");
+    pub const DELTA: U256 =
+        U256::from_be_hex("00000000000000000000000000000000000000000000000000001e39a5057d81");
+    /// `(t - 1) / 2` where `order - 1 = 2^S * t`, little endian 64-bit words
+    pub const T_MINUS_1_OVER_2: [u64; 4] = [
+        0x279dce5617e3192a,
+        0xfde737d56d38bcf4,
+        0x07ffffffffffffff,
+        0x07fffffff8000000,
+    ];
+}
+
+const ORDER: U256 = U256::from_be_hex(params::ORDER_HEX);
+const HALF_ORDER: U256 = ORDER.shr_vartime(1);
+
+impl Curve for ThisCurve {
+    type FieldBytesSize = U32;
+    type Uint = U256;
+
+    const ORDER: U256 = ORDER;
+}
+
+impl PrimeCurve for ThisCurve {}
+
+impl CurveArithmetic for ThisCurve {
+    type AffinePoint = AffinePoint;
+    type ProjectivePoint = ProjectivePoint;
+    type Scalar = Scalar;
+}
+
+impl PrimeCurveArithmetic for ThisCurve {
+    type CurveGroup = ProjectivePoint;
+}
+
+impl FieldBytesEncoding<ThisCurve> for U256 {}
+
+/// Element of the scalar field, always less than the curve order
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq, PartialOrd, Ord)]
+pub struct Scalar(U256);
+
+impl Scalar {
+    fn to_curve_order_elem(self) -> CurveOrderElement {
+        BigNum::frombytes(&self.0.to_be_bytes()).into()
+    }
+
+    fn from_curve_order_elem(e: &CurveOrderElement) -> Self {
+        let mut bytes = [0u8; 32];
+        e.to_bignum().tobytes(&mut bytes);
+        Self(U256::from_be_slice(&bytes))
+    }
+}
+
+impl From<&CurveOrderElement> for Scalar {
+    fn from(e: &CurveOrderElement) -> Self {
+        Self::from_curve_order_elem(e)
+    }
+}
+
+impl From<CurveOrderElement> for Scalar {
+    fn from(e: CurveOrderElement) -> Self {
+        Self::from_curve_order_elem(&e)
+    }
+}
+
+impl From<Scalar> for CurveOrderElement {
+    fn from(s: Scalar) -> Self {
+        s.to_curve_order_elem()
+    }
+}
+
+impl Field for Scalar {
+    const ZERO: Self = Self(U256::ZERO);
+    const ONE: Self = Self(U256::ONE);
+
+    fn random(mut rng: impl RngCore) -> Self {
+        let mut bytes = [0u8; 32];
+        loop {
+            rng.fill_bytes(&mut bytes);
+            let n = U256::from_be_slice(&bytes);
+            if n < ORDER {
+                return Self(n);
+            }
+        }
+    }
+
+    fn square(&self) -> Self {
+        Self::from_curve_order_elem(&self.to_curve_order_elem().square())
+    }
+
+    fn double(&self) -> Self {
+        *self + self
+    }
+
+    fn invert(&self) -> CtOption<Self> {
+        let inv = Self::from_curve_order_elem(&self.to_curve_order_elem().inverse_const_time());
+        CtOption::new(inv, !self.is_zero())
+    }
+
+    fn sqrt_ratio(num: &Self, div: &Self) -> (Choice, Self) {
+        helpers::sqrt_ratio_generic(num, div)
+    }
+
+    fn sqrt(&self) -> CtOption<Self> {
+        helpers::sqrt_tonelli_shanks(self, params::T_MINUS_1_OVER_2)
+    }
+}
+
+impl PrimeField for Scalar {
+    type Repr = FieldBytes<ThisCurve>;
+
+    const MODULUS: &'static str = params::ORDER_HEX;
+    const NUM_BITS: u32 = 256;
+    const CAPACITY: u32 = 255;
+    const TWO_INV: Self = Self(params::TWO_INV);
+    const MULTIPLICATIVE_GENERATOR: Self = Self(U256::from_u8(7));
+    const S: u32 = params::S;
+    const ROOT_OF_UNITY: Self = Self(params::ROOT_OF_UNITY);
+    const ROOT_OF_UNITY_INV: Self = Self(params::ROOT_OF_UNITY_INV);
+    const DELTA: Self = Self(params::DELTA);
+
+    fn from_repr(bytes: Self::Repr) -> CtOption<Self> {
+        let n = U256::from_be_byte_array(bytes);
+        CtOption::new(Self(n), ORDER.ct_gt(&n))
+    }
+
+    fn to_repr(&self) -> Self::Repr {
+        self.0.to_be_byte_array()
+    }
+
+    fn is_odd(&self) -> Choice {
+        self.0.is_odd()
+    }
+}
+
+impl From<u64> for Scalar {
+    fn from(n: u64) -> Self {
+        Self(U256::from_u64(n))
+    }
+}
+
+impl AsRef<Scalar> for Scalar {
+    fn as_ref(&self) -> &Scalar {
+        self
+    }
+}
+
+impl ConditionallySelectable for Scalar {
+    fn conditional_select(a: &Self, b: &Self, choice: Choice) -> Self {
+        Self(U256::conditional_select(&a.0, &b.0, choice))
+    }
+}
+
+impl ConstantTimeEq for Scalar {
+    fn ct_eq(&self, other: &Self) -> Choice {
+        self.0.ct_eq(&other.0)
+    }
+}
+
+impl DefaultIsZeroes for Scalar {}
+
+impl From<ScalarPrimitive<ThisCurve>> for Scalar {
+    fn from(s: ScalarPrimitive<ThisCurve>) -> Self {
+        Self(*s.as_uint())
+    }
+}
+
+impl From<Scalar> for ScalarPrimitive<ThisCurve> {
+    fn from(s: Scalar) -> Self {
+        ScalarPrimitive::from_uint_unchecked(s.0)
+    }
+}
+
+impl From<Scalar> for U256 {
+    fn from(s: Scalar) -> Self {
+        s.0
+    }
+}
+
+impl From<Scalar> for FieldBytes<ThisCurve> {
+    fn from(s: Scalar) -> Self {
+        s.to_repr()
+    }
+}
+
+impl FromUintUnchecked for Scalar {
+    type Uint = U256;
+
+    fn from_uint_unchecked(uint: U256) -> Self {
+        Self(uint)
+    }
+}
+
+impl Invert for Scalar {
+    type Output = CtOption<Scalar>;
+
+    fn invert(&self) -> CtOption<Scalar> {
+        Field::invert(self)
+    }
+}
+
+impl IsHigh for Scalar {
+    fn is_high(&self) -> Choice {
+        self.0.ct_gt(&HALF_ORDER)
+    }
+}
+
+impl Reduce<U256> for Scalar {
+    type Bytes = FieldBytes<ThisCurve>;
+
+    fn reduce(n: U256) -> Self {
+        // Curve order is more than 2^255 so a single conditional subtraction suffices
+        let (r, borrow) = n.sbb(&ORDER, Limb::ZERO);
+        let underflow = Choice::from((borrow.0 >> (Limb::BITS - 1)) as u8);
+        Self(U256::conditional_select(&r, &n, underflow))
+    }
+
+    fn reduce_bytes(bytes: &Self::Bytes) -> Self {
+        Self::reduce(U256::from_be_byte_array(*bytes))
+    }
+}
+
+impl std::ops::ShrAssign<usize> for Scalar {
+    fn shr_assign(&mut self, rhs: usize) {
+        self.0 = self.0.shr_vartime(rhs);
+    }
+}
+
+impl Neg for Scalar {
+    type Output = Scalar;
+
+    fn neg(self) -> Scalar {
+        Self(self.0.neg_mod(&ORDER))
+    }
+}
+
+/// Implements the binary operator traits (and the assigning variants) for owned and borrowed
+/// right hand sides given the operation on `(&Self, &Rhs)`
+macro_rules! impl_binops {
+    ($lhs:ty, $rhs:ty, $output:ty, $trait:ident, $fn:ident, $assign_trait:ident, $assign_fn:ident, $op:expr) => {
+        impl $trait<$rhs> for $lhs {
+            type Output = $output;
+
+            fn $fn(self, other: $rhs) -> $output {
+                $op(&self, &other)
+            }
+        }
+
+        impl<'a> $trait<&'a $rhs> for $lhs {
+            type Output = $output;
+
+            fn $fn(self, other: &'a $rhs) -> $output {
+                $op(&self, other)
+            }
+        }
+
+        impl $assign_trait<$rhs> for $lhs {
+            fn $assign_fn(&mut self, other: $rhs) {
+                *self = $op(self, &other);
+            }
+        }
+
+        impl<'a> $assign_trait<&'a $rhs> for $lhs {
+            fn $assign_fn(&mut self, other: &'a $rhs) {
+                *self = $op(self, other);
+            }
+        }
+    };
+}
+
+impl_binops!(Scalar, Scalar, Scalar, Add, add, AddAssign, add_assign, |a: &Scalar, b: &Scalar| {
+    Scalar(a.0.add_mod(&b.0, &ORDER))
+});
+impl_binops!(Scalar, Scalar, Scalar, Sub, sub, SubAssign, sub_assign, |a: &Scalar, b: &Scalar| {
+    Scalar(a.0.sub_mod(&b.0, &ORDER))
+});
+impl_binops!(Scalar, Scalar, Scalar, Mul, mul, MulAssign, mul_assign, |a: &Scalar, b: &Scalar| {
+    Scalar::from_curve_order_elem(&(a.to_curve_order_elem() * b.to_curve_order_elem()))
+});
+
+impl Sum for Scalar {
+    fn sum<I: Iterator<Item = Self>>(iter: I) -> Self {
+        iter.fold(Self::ZERO, |acc, s| acc + s)
+    }
+}
+
+impl<'a> Sum<&'a Scalar> for Scalar {
+    fn sum<I: Iterator<Item = &'a Scalar>>(iter: I) -> Self {
+        iter.fold(Self::ZERO, |acc, s| acc + s)
+    }
+}
+
+impl Product for Scalar {
+    fn product<I: Iterator<Item = Self>>(iter: I) -> Self {
+        iter.fold(Self::ONE, |acc, s| acc * s)
+    }
+}
+
+impl<'a> Product<&'a Scalar> for Scalar {
+    fn product<I: Iterator<Item = &'a Scalar>>(iter: I) -> Self {
+        iter.fold(Self::ONE, |acc, s| acc * s)
+    }
+}
+
+/// Point in projective coordinates
+#[derive(Copy, Clone, Debug)]
+pub struct ProjectivePoint(GroupG1);
+
+/// Point in affine coordinates
+#[derive(Copy, Clone, Debug)]
+pub struct AffinePoint(GroupG1);
+
+impl From<&G1> for ProjectivePoint {
+    fn from(p: &G1) -> Self {
+        Self(p.to_ecp())
+    }
+}
+
+impl From<ProjectivePoint> for G1 {
+    fn from(p: ProjectivePoint) -> Self {
+        p.0.into()
+    }
+}
+
+impl From<&G1> for AffinePoint {
+    fn from(p: &G1) -> Self {
+        ProjectivePoint::from(p).to_affine()
+    }
+}
+
+impl From<AffinePoint> for G1 {
+    fn from(p: AffinePoint) -> Self {
+        p.0.into()
+    }
+}
+
+impl From<AffinePoint> for ProjectivePoint {
+    fn from(p: AffinePoint) -> Self {
+        Self(p.0)
+    }
+}
+
+impl From<ProjectivePoint> for AffinePoint {
+    fn from(p: ProjectivePoint) -> Self {
+        p.to_affine()
+    }
+}
+
+impl PartialEq for ProjectivePoint {
+    fn eq(&self, other: &Self) -> bool {
+        self.0.equals(&other.0)
+    }
+}
+
+impl Eq for ProjectivePoint {}
+
+impl PartialEq for AffinePoint {
+    fn eq(&self, other: &Self) -> bool {
+        self.0.equals(&other.0)
+    }
+}
+
+impl Eq for AffinePoint {}
+
+/// Uncompressed encodings compared in constant time, the identity equal to itself whatever its
+/// projective coordinates
+fn ct_eq_points(a: &GroupG1, b: &GroupG1) -> Choice {
+    let encode = |p: &GroupG1| {
+        let mut bytes = [0u8; 65];
+        p.tobytes(&mut bytes, false);
+        bytes
+    };
+    let a_inf = Choice::from(a.is_infinity() as u8);
+    let b_inf = Choice::from(b.is_infinity() as u8);
+    (a_inf & b_inf) | (!a_inf & !b_inf & encode(a)[..].ct_eq(&encode(b)[..]))
+}
+
+impl ConstantTimeEq for ProjectivePoint {
+    fn ct_eq(&self, other: &Self) -> Choice {
+        ct_eq_points(&self.0, &other.0)
+    }
+}
+
+impl ConstantTimeEq for AffinePoint {
+    fn ct_eq(&self, other: &Self) -> Choice {
+        ct_eq_points(&self.0, &other.0)
+    }
+}
+
+impl ConditionallySelectable for ProjectivePoint {
+    fn conditional_select(a: &Self, b: &Self, choice: Choice) -> Self {
+        let mut p = a.0;
+        p.cmove(&b.0, choice.unwrap_u8() as isize);
+        Self(p)
+    }
+}
+
+impl ConditionallySelectable for AffinePoint {
+    fn conditional_select(a: &Self, b: &Self, choice: Choice) -> Self {
+        let mut p = a.0;
+        p.cmove(&b.0, choice.unwrap_u8() as isize);
+        Self(p)
+    }
+}
+
+impl Default for ProjectivePoint {
+    fn default() -> Self {
+        Self::identity()
+    }
+}
+
+impl Default for AffinePoint {
+    fn default() -> Self {
+        Self(GroupG1::new())
+    }
+}
+
+impl DefaultIsZeroes for ProjectivePoint {}
+
+impl DefaultIsZeroes for AffinePoint {}
+
+impl AffineCoordinates for AffinePoint {
+    type FieldRepr = FieldBytes<ThisCurve>;
+
+    fn x(&self) -> Self::FieldRepr {
+        let mut bytes = FieldBytes::<ThisCurve>::default();
+        self.0.getx().tobytes(&mut bytes);
+        bytes
+    }
+
+    fn y_is_odd(&self) -> Choice {
+        Choice::from(self.0.gety().parity() as u8)
+    }
+}
+
+impl FromEncodedPoint<ThisCurve> for AffinePoint {
+    fn from_encoded_point(point: &EncodedPoint) -> CtOption<Self> {
+        if point.is_identity() {
+            return CtOption::new(Self::default(), Choice::from(1));
+        }
+        let p = GroupG1::frombytes(point.as_bytes());
+        CtOption::new(Self(p), Choice::from(!p.is_infinity() as u8))
+    }
+}
+
+impl ToEncodedPoint<ThisCurve> for AffinePoint {
+    fn to_encoded_point(&self, compress: bool) -> EncodedPoint {
+        if self.0.is_infinity() {
+            return EncodedPoint::identity();
+        }
+        let mut bytes = [0u8; 65];
+        self.0.tobytes(&mut bytes, compress);
+        let len = if compress { 33 } else { 65 };
+        EncodedPoint::from_bytes(&bytes[..len]).unwrap()
+    }
+}
+
+impl FromEncodedPoint<ThisCurve> for ProjectivePoint {
+    fn from_encoded_point(point: &EncodedPoint) -> CtOption<Self> {
+        AffinePoint::from_encoded_point(point).map(Self::from)
+    }
+}
+
+impl ToEncodedPoint<ThisCurve> for ProjectivePoint {
+    fn to_encoded_point(&self, compress: bool) -> EncodedPoint {
+        self.to_affine().to_encoded_point(compress)
+    }
+}
+
+impl GroupEncoding for AffinePoint {
+    type Repr = CompressedPoint<ThisCurve>;
+
+    /// The identity is encoded as all zeros
+    fn from_bytes(bytes: &Self::Repr) -> CtOption<Self> {
+        if bool::from(bytes.ct_eq(&Self::Repr::default())) {
+            return CtOption::new(Self::default(), Choice::from(1));
+        }
+        match EncodedPoint::from_bytes(bytes) {
+            Ok(p) => Self::from_encoded_point(&p),
+            Err(_) => CtOption::new(Self::default(), Choice::from(0)),
+        }
+    }
+
+    fn from_bytes_unchecked(bytes: &Self::Repr) -> CtOption<Self> {
+        Self::from_bytes(bytes)
+    }
+
+    fn to_bytes(&self) -> Self::Repr {
+        let mut bytes = Self::Repr::default();
+        if !self.0.is_infinity() {
+            self.0.tobytes(&mut bytes, true);
+        }
+        bytes
+    }
+}
+
+impl GroupEncoding for ProjectivePoint {
+    type Repr = CompressedPoint<ThisCurve>;
+
+    fn from_bytes(bytes: &Self::Repr) -> CtOption<Self> {
+        AffinePoint::from_bytes(bytes).map(Self::from)
+    }
+
+    fn from_bytes_unchecked(bytes: &Self::Repr) -> CtOption<Self> {
+        Self::from_bytes(bytes)
+    }
+
+    fn to_bytes(&self) -> Self::Repr {
+        self.to_affine().to_bytes()
+    }
+}
+
+impl Group for ProjectivePoint {
+    type Scalar = Scalar;
+
+    fn random(rng: impl RngCore) -> Self {
+        Self::generator() * Scalar::random(rng)
+    }
+
+    fn identity() -> Self {
+        Self(GroupG1::new())
+    }
+
+    fn generator() -> Self {
+        Self(GroupG1::generator())
+    }
+
+    fn is_identity(&self) -> Choice {
+        Choice::from(self.0.is_infinity() as u8)
+    }
+
+    fn double(&self) -> Self {
+        let mut p = self.0;
+        p.dbl();
+        Self(p)
+    }
+}
+
+impl CurveGroup for ProjectivePoint {
+    type AffineRepr = AffinePoint;
+
+    fn to_affine(&self) -> AffinePoint {
+        let mut p = self.0;
+        p.affine();
+        AffinePoint(p)
+    }
+}
+
+impl PrimeGroup for ProjectivePoint {}
+
+impl PrimeCurveGroup for ProjectivePoint {
+    type Affine = AffinePoint;
+}
+
+impl PrimeCurveAffine for AffinePoint {
+    type Scalar = Scalar;
+    type Curve = ProjectivePoint;
+
+    fn identity() -> Self {
+        Self::default()
+    }
+
+    fn generator() -> Self {
+        Self(GroupG1::generator())
+    }
+
+    fn is_identity(&self) -> Choice {
+        Choice::from(self.0.is_infinity() as u8)
+    }
+
+    fn to_curve(&self) -> ProjectivePoint {
+        ProjectivePoint(self.0)
+    }
+}
+
+impl LinearCombination for ProjectivePoint {
+    fn lincomb(x: &Self, k: &Scalar, y: &Self, l: &Scalar) -> Self {
+        G1::from(x.0)
+            .binary_scalar_mul(&G1::from(y.0), &k.to_curve_order_elem(), &l.to_curve_order_elem())
+            .to_ecp()
+            .into()
+    }
+}
+
+impl From<GroupG1> for ProjectivePoint {
+    fn from(p: GroupG1) -> Self {
+        Self(p)
+    }
+}
+
+impl MulByGenerator for ProjectivePoint {}
+
+fn add_points(a: &ProjectivePoint, b: &GroupG1) -> ProjectivePoint {
+    let mut p = a.0;
+    p.add(b);
+    ProjectivePoint(p)
+}
+
+fn sub_points(a: &ProjectivePoint, b: &GroupG1) -> ProjectivePoint {
+    let mut p = a.0;
+    p.sub(b);
+    ProjectivePoint(p)
+}
+
+fn mul_point(a: &GroupG1, s: &Scalar) -> ProjectivePoint {
+    G1::from(*a)
+        .scalar_mul_const_time(&s.to_curve_order_elem())
+        .to_ecp()
+        .into()
+}
+
+impl_binops!(
+    ProjectivePoint, ProjectivePoint, ProjectivePoint, Add, add, AddAssign, add_assign,
+    |a: &ProjectivePoint, b: &ProjectivePoint| add_points(a, &b.0)
+);
+impl_binops!(
+    ProjectivePoint, ProjectivePoint, ProjectivePoint, Sub, sub, SubAssign, sub_assign,
+    |a: &ProjectivePoint, b: &ProjectivePoint| sub_points(a, &b.0)
+);
+impl_binops!(
+    ProjectivePoint, AffinePoint, ProjectivePoint, Add, add, AddAssign, add_assign,
+    |a: &ProjectivePoint, b: &AffinePoint| add_points(a, &b.0)
+);
+impl_binops!(
+    ProjectivePoint, AffinePoint, ProjectivePoint, Sub, sub, SubAssign, sub_assign,
+    |a: &ProjectivePoint, b: &AffinePoint| sub_points(a, &b.0)
+);
+impl_binops!(
+    ProjectivePoint, Scalar, ProjectivePoint, Mul, mul, MulAssign, mul_assign,
+    |a: &ProjectivePoint, s: &Scalar| mul_point(&a.0, s)
+);
+
+impl Mul<Scalar> for AffinePoint {
+    type Output = ProjectivePoint;
+
+    fn mul(self, s: Scalar) -> ProjectivePoint {
+        mul_point(&self.0, &s)
+    }
+}
+
+impl<'a> Mul<&'a Scalar> for AffinePoint {
+    type Output = ProjectivePoint;
+
+    fn mul(self, s: &'a Scalar) -> ProjectivePoint {
+        mul_point(&self.0, s)
+    }
+}
+
+impl Neg for ProjectivePoint {
+    type Output = ProjectivePoint;
+
+    fn neg(self) -> ProjectivePoint {
+        let mut p = self.0;
+        p.neg();
+        Self(p)
+    }
+}
+
+impl Neg for AffinePoint {
+    type Output = AffinePoint;
+
+    fn neg(self) -> AffinePoint {
+        let mut p = self.0;
+        p.neg();
+        Self(p)
+    }
+}
+
+impl Sum for ProjectivePoint {
+    fn sum<I: Iterator<Item = Self>>(iter: I) -> Self {
+        iter.fold(Self::identity(), |acc, p| acc + p)
+    }
+}
+
+impl<'a> Sum<&'a ProjectivePoint> for ProjectivePoint {
+    fn sum<I: Iterator<Item = &'a ProjectivePoint>>(iter: I) -> Self {
+        iter.fold(Self::identity(), |acc, p| acc + p)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use elliptic_curve::ecdh::diffie_hellman;
+    use elliptic_curve::{NonZeroScalar, PublicKey};
+
+    #[test]
+    fn test_scalar_field() {
+        let mut rng = rand_core::OsRng;
+        let a = Scalar::random(&mut rng);
+        let b = Scalar::random(&mut rng);
+        let a_elem = CurveOrderElement::from(a);
+        let b_elem = CurveOrderElement::from(b);
+        assert_eq!(Scalar::from(&a_elem + &b_elem), a + b);
+        assert_eq!(Scalar::from(&a_elem - &b_elem), a - b);
+        assert_eq!(Scalar::from(&a_elem * &b_elem), a * b);
+        assert_eq!(Scalar::from(-&a_elem), -a);
+        assert_eq!(a * Field::invert(&a).unwrap(), Scalar::ONE);
+        assert!(bool::from(Field::invert(&Scalar::ZERO).is_none()));
+
+        let sq = a.square();
+        let root = sq.sqrt().unwrap();
+        assert!(root == a || root == -a);
+
+        assert_eq!(Scalar::from_repr(a.to_repr()).unwrap(), a);
+        assert!(bool::from(Scalar::from_repr(ORDER.to_be_byte_array()).is_none()));
+        assert_eq!(Scalar::reduce(ORDER), Scalar::ZERO);
+        assert_eq!(Scalar::TWO_INV.double(), Scalar::ONE);
+        assert_eq!(
+            Scalar::ROOT_OF_UNITY.pow_vartime([1u64 << Scalar::S]),
+            Scalar::ONE
+        );
+        assert_eq!(Scalar::ROOT_OF_UNITY * Scalar::ROOT_OF_UNITY_INV, Scalar::ONE);
+    }
+
+    #[test]
+    fn test_group() {
+        let mut rng = rand_core::OsRng;
+        let a = Scalar::random(&mut rng);
+        let b = Scalar::random(&mut rng);
+        let g = ProjectivePoint::generator();
+        assert_eq!(g * a + g * b, g * (a + b));
+        assert_eq!(g * a - g * a, ProjectivePoint::identity());
+        assert_eq!(g.double(), g + g);
+        assert_eq!(
+            ProjectivePoint::lincomb(&g, &a, &(g * b), &b),
+            g * (a + b * b)
+        );
+
+        let g1 = G1::generator() * CurveOrderElement::from(a);
+        assert_eq!(G1::from(g * a), g1);
+        assert_eq!(ProjectivePoint::from(&g1), g * a);
+        // Projective points with different coordinates compare equal in constant time
+        assert!(bool::from((g * a + g).ct_eq(&(g + g * a))));
+        assert!(bool::from((g * a - g * a).ct_eq(&ProjectivePoint::identity())));
+        assert!(!bool::from((g * a).ct_eq(&(g * b))));
+        assert!(!bool::from((g * a).ct_eq(&ProjectivePoint::identity())));
+        assert_eq!(a * Field::invert(&a).unwrap(), Scalar::ONE);
+
+        let p = (g * a).to_affine();
+        assert_eq!(AffinePoint::from_bytes(&p.to_bytes()).unwrap(), p);
+        let identity = AffinePoint::identity();
+        assert_eq!(AffinePoint::from_bytes(&identity.to_bytes()).unwrap(), identity);
+        for compress in [true, false].iter() {
+            let enc = p.to_encoded_point(*compress);
+            assert_eq!(AffinePoint::from_encoded_point(&enc).unwrap(), p);
+        }
+    }
+
+    #[test]
+    fn test_ecdh() {
+        let mut rng = rand_core::OsRng;
+        let sk1 = NonZeroScalar::<ThisCurve>::random(&mut rng);
+        let sk2 = NonZeroScalar::<ThisCurve>::random(&mut rng);
+        let pk1 = PublicKey::<ThisCurve>::from_secret_scalar(&sk1);
+        let pk2 = PublicKey::<ThisCurve>::from_secret_scalar(&sk2);
+        let s1 = diffie_hellman(sk1, pk2.as_affine());
+        let s2 = diffie_hellman(sk2, pk1.as_affine());
+        assert_eq!(s1.raw_secret_bytes(), s2.raw_secret_bytes());
+
+        let enc = pk1.to_encoded_point(true);
+        assert_eq!(PublicKey::<ThisCurve>::from_sec1_bytes(enc.as_bytes()).unwrap(), pk1);
+    }
+}
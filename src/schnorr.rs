@@ -0,0 +1,277 @@
+//! Schnorr signatures over group G1. A signature is the pair `(e, s)` with `R = g*s + pk*e` and
+//! `e = H(R || pk || msg)`.
+
+use crate::constants::{CURVE_ORDER_ELEMENT_SIZE, GROUP_G1_SIZE};
+use crate::ct;
+use crate::curve_order_elem::CurveOrderElement;
+use crate::group_elem::GroupElement;
+use crate::group_elem_g1::G1;
+use crate::group_params::GroupParams;
+
+use rand::{CryptoRng, RngCore};
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+use signature::{Error as SignatureError, SignatureEncoding, Signer, Verifier};
+use std::convert::TryFrom;
+use std::fmt;
+
+/// Byte size of a signature, 2 curve order elements
+pub const SIGNATURE_SIZE: usize = 2 * CURVE_ORDER_ELEMENT_SIZE;
+/// Byte size of a public key, an uncompressed element of group G1
+pub const PUBLIC_KEY_SIZE: usize = GROUP_G1_SIZE;
+
+pub(crate) fn challenge(r: &G1, pk: &G1, msg: &[u8]) -> CurveOrderElement {
+    CurveOrderElement::from_msg_hash(&[&r.to_vec(), &pk.to_vec(), msg].concat())
+}
+
+/// Parse a curve order element, rejecting encodings of values not less than the curve order
+fn scalar_from_canonical_bytes(bytes: &[u8]) -> Result<CurveOrderElement, SignatureError> {
+    let s = CurveOrderElement::try_from(bytes).map_err(|_| SignatureError::new())?;
+    if s.to_compressed_bytes()[..] != bytes[..] {
+        return Err(SignatureError::new());
+    }
+    Ok(s)
+}
+
+#[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct SecretKey(CurveOrderElement);
+
+impl fmt::Debug for SecretKey {
+    /// Does not show the key
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("SecretKey(..)")
+    }
+}
+
+impl PartialEq for SecretKey {
+    /// Constant time in the keys
+    fn eq(&self, other: &Self) -> bool {
+        ct::scalar_eq(&self.0, &other.0)
+    }
+}
+
+impl SecretKey {
+    pub fn random() -> Self {
+        Self(CurveOrderElement::random())
+    }
+
+    pub fn random_using_rng<R: RngCore + CryptoRng>(rng: &mut R) -> Self {
+        Self(CurveOrderElement::random_using_rng(rng))
+    }
+
+    /// Sign with respect to the standard generator of group G1
+    pub fn sign(&self, msg: &[u8]) -> Signature {
+        self.sign_with_params(msg, &GroupParams::default())
+    }
+
+    pub fn sign_with_params(&self, msg: &[u8], params: &GroupParams) -> Signature {
+        let pk = params.g1() * &self.0;
+        // Nonce depends on the secret key and message as well as fresh randomness so a weak RNG
+        // does not leak the key.
+        let mut seed = CurveOrderElement::random().to_bytes().to_vec();
+        seed.extend_from_slice(&self.0.to_bytes());
+        seed.extend_from_slice(msg);
+        let k = CurveOrderElement::from_msg_hash(&seed);
+        let r = params.g1() * &k;
+        let e = challenge(&r, &pk, msg);
+        let s = k - &e * &self.0;
+        Signature { e, s }
+    }
+
+    pub fn as_scalar(&self) -> &CurveOrderElement {
+        &self.0
+    }
+}
+
+impl From<CurveOrderElement> for SecretKey {
+    fn from(x: CurveOrderElement) -> Self {
+        Self(x)
+    }
+}
+
+impl Signer<Signature> for SecretKey {
+    fn try_sign(&self, msg: &[u8]) -> Result<Signature, SignatureError> {
+        Ok(self.sign(msg))
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct PublicKey(G1);
+
+impl PublicKey {
+    /// Public key for the standard generator of group G1
+    pub fn new(sk: &SecretKey) -> Self {
+        Self::new_with_params(sk, &GroupParams::default())
+    }
+
+    pub fn new_with_params(sk: &SecretKey, params: &GroupParams) -> Self {
+        Self(params.g1() * &sk.0)
+    }
+
+    pub fn verify_with_params(&self, msg: &[u8], sig: &Signature, params: &GroupParams) -> bool {
+        if self.0.is_identity() {
+            return false;
+        }
+        let r = params.g1().binary_scalar_mul(&self.0, &sig.s, &sig.e);
+        challenge(&r, &self.0, msg) == sig.e
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        self.0.to_vec()
+    }
+
+    pub fn as_point(&self) -> &G1 {
+        &self.0
+    }
+}
+
+#[cfg(all(feature = "jwk", not(feature = "ed25519")))]
+impl PublicKey {
+    pub fn to_jwk(&self) -> crate::jwk::Jwk {
+        self.0.to_jwk()
+    }
+
+    pub fn from_jwk(jwk: &crate::jwk::Jwk) -> Result<Self, crate::errors::SerzDeserzError> {
+        G1::from_jwk(jwk).map(Self)
+    }
+}
+
+#[cfg(all(
+    feature = "cose",
+    any(feature = "secp256k1", feature = "nist256", feature = "bls381")
+))]
+impl PublicKey {
+    pub fn to_cose_key(&self) -> crate::cose::CoseKey {
+        self.0.to_cose_key()
+    }
+
+    pub fn from_cose_key(
+        key: &crate::cose::CoseKey,
+    ) -> Result<Self, crate::errors::SerzDeserzError> {
+        G1::from_cose_key(key).map(Self)
+    }
+}
+
+impl From<&SecretKey> for PublicKey {
+    fn from(sk: &SecretKey) -> Self {
+        Self::new(sk)
+    }
+}
+
+impl TryFrom<&[u8]> for PublicKey {
+    type Error = SignatureError;
+
+    fn try_from(bytes: &[u8]) -> Result<Self, SignatureError> {
+        let pk = G1::from_slice(bytes).map_err(|_| SignatureError::new())?;
+        if pk.is_identity() {
+            return Err(SignatureError::new());
+        }
+        Ok(Self(pk))
+    }
+}
+
+impl Verifier<Signature> for PublicKey {
+    fn verify(&self, msg: &[u8], sig: &Signature) -> Result<(), SignatureError> {
+        if self.verify_with_params(msg, sig, &GroupParams::default()) {
+            Ok(())
+        } else {
+            Err(SignatureError::new())
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Signature {
+    e: CurveOrderElement,
+    s: CurveOrderElement,
+}
+
+impl Signature {
+    pub(crate) fn from_parts(e: CurveOrderElement, s: CurveOrderElement) -> Self {
+        Self { e, s }
+    }
+}
+
+impl TryFrom<&[u8]> for Signature {
+    type Error = SignatureError;
+
+    fn try_from(bytes: &[u8]) -> Result<Self, SignatureError> {
+        if bytes.len() != SIGNATURE_SIZE {
+            return Err(SignatureError::new());
+        }
+        Ok(Self {
+            e: scalar_from_canonical_bytes(&bytes[..CURVE_ORDER_ELEMENT_SIZE])?,
+            s: scalar_from_canonical_bytes(&bytes[CURVE_ORDER_ELEMENT_SIZE..])?,
+        })
+    }
+}
+
+impl TryFrom<[u8; SIGNATURE_SIZE]> for Signature {
+    type Error = SignatureError;
+
+    fn try_from(bytes: [u8; SIGNATURE_SIZE]) -> Result<Self, SignatureError> {
+        Self::try_from(&bytes[..])
+    }
+}
+
+impl From<Signature> for [u8; SIGNATURE_SIZE] {
+    fn from(sig: Signature) -> Self {
+        let mut bytes = [0u8; SIGNATURE_SIZE];
+        bytes[..CURVE_ORDER_ELEMENT_SIZE].copy_from_slice(&sig.e.to_compressed_bytes());
+        bytes[CURVE_ORDER_ELEMENT_SIZE..].copy_from_slice(&sig.s.to_compressed_bytes());
+        bytes
+    }
+}
+
+impl SignatureEncoding for Signature {
+    type Repr = [u8; SIGNATURE_SIZE];
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_secret_key_eq_and_debug() {
+        let sk = SecretKey::random();
+        assert_eq!(sk, sk.clone());
+        assert_ne!(sk, SecretKey::random());
+        assert_eq!(format!("{:?}", sk), "SecretKey(..)");
+    }
+
+    #[test]
+    fn test_sign_verify() {
+        let sk = SecretKey::random();
+        let pk = PublicKey::from(&sk);
+        let msg = b"test message";
+        let sig = sk.try_sign(msg).unwrap();
+        assert!(pk.verify(msg, &sig).is_ok());
+        assert!(pk.verify(b"another message", &sig).is_err());
+        assert!(PublicKey::from(&SecretKey::random()).verify(msg, &sig).is_err());
+
+        let params = GroupParams::from_label(b"test");
+        let pk = PublicKey::new_with_params(&sk, &params);
+        let sig = sk.sign_with_params(msg, &params);
+        assert!(pk.verify_with_params(msg, &sig, &params));
+        assert!(pk.verify(msg, &sig).is_err());
+    }
+
+    #[test]
+    fn test_encoding() {
+        let sk = SecretKey::random();
+        let pk = PublicKey::from(&sk);
+        let sig = sk.sign(b"test message");
+
+        let sig_bytes = sig.to_bytes();
+        assert_eq!(Signature::try_from(&sig_bytes[..]).unwrap(), sig);
+        assert!(Signature::try_from(&sig_bytes[1..]).is_err());
+        assert!(Signature::try_from([0xffu8; SIGNATURE_SIZE]).is_err());
+
+        let pk_bytes = pk.to_bytes();
+        assert_eq!(pk_bytes.len(), PUBLIC_KEY_SIZE);
+        assert_eq!(PublicKey::try_from(&pk_bytes[..]).unwrap(), pk);
+    }
+}
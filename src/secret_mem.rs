@@ -0,0 +1,379 @@
+//! Memory for secrets that should not be swapped to disk. A `SecretBuffer` is a fixed size heap
+//! buffer which is passed to the installed `SecretMemoryHooks` to be locked before secrets are
+//! written to it, and is zeroized and unlocked when dropped. With the `mlock` feature the default
+//! hooks lock pages with `mlock` on unix, otherwise they do nothing. Locking can fail, e.g. over
+//! the `RLIMIT_MEMLOCK` limit, in which case the buffer still works unlocked and `is_locked`
+//! returns false.
+//!
+//! `mlock` works on whole pages and does not nest, so `MlockHooks` counts the locked ranges on
+//! each page and only unlocks a page when the last of them is unlocked. Buffers sharing a page
+//! with a secret buffer are locked with it.
+//!
+//! `CurveOrderElementVector` locks its allocation the same way and moves to a new locked
+//! allocation, zeroizing the old one, when it grows through its own methods. Growing the public
+//! `Vec` directly bypasses that. The wNAF and Booth digits of scalar multiplications are also
+//! kept in secret buffers.
+
+#[cfg(all(feature = "mlock", unix))]
+use std::collections::HashMap;
+use std::fmt;
+use std::ops::{Deref, DerefMut};
+#[cfg(all(feature = "mlock", unix))]
+use std::sync::Mutex;
+use std::sync::{Arc, RwLock};
+use zeroize::Zeroize;
+
+/// Locks and unlocks the memory of secret buffers
+pub trait SecretMemoryHooks: Send + Sync {
+    /// Lock `len` bytes at `ptr` in memory, returns false if that is not possible
+    fn lock(&self, ptr: *const u8, len: usize) -> bool;
+
+    /// Undo a successful `lock` of the same bytes
+    fn unlock(&self, ptr: *const u8, len: usize);
+}
+
+/// Hooks that don't lock anything
+#[derive(Clone, Copy, Debug, Default)]
+pub struct NoopHooks;
+
+impl SecretMemoryHooks for NoopHooks {
+    fn lock(&self, _ptr: *const u8, _len: usize) -> bool {
+        false
+    }
+
+    fn unlock(&self, _ptr: *const u8, _len: usize) {}
+}
+
+/// Hooks locking pages with `mlock`. A page stays locked until every range locked on it is
+/// unlocked.
+#[cfg(all(feature = "mlock", unix))]
+#[derive(Clone, Copy, Debug, Default)]
+pub struct MlockHooks;
+
+#[cfg(all(feature = "mlock", unix))]
+lazy_static! {
+    static ref PAGE_SIZE: usize = unsafe { libc::sysconf(libc::_SC_PAGESIZE) as usize };
+    /// Number of ranges locked on each page locked by `MlockHooks`
+    static ref LOCKED_PAGES: Mutex<HashMap<usize, usize>> = Mutex::new(HashMap::new());
+}
+
+/// Start addresses of the pages containing the `len > 0` bytes at `ptr`
+#[cfg(all(feature = "mlock", unix))]
+fn pages(ptr: *const u8, len: usize) -> impl Iterator<Item = usize> {
+    let page_size = *PAGE_SIZE;
+    let start = ptr as usize & !(page_size - 1);
+    let end = ptr as usize + len;
+    (start..end).step_by(page_size)
+}
+
+#[cfg(all(feature = "mlock", unix))]
+impl SecretMemoryHooks for MlockHooks {
+    fn lock(&self, ptr: *const u8, len: usize) -> bool {
+        let mut locked_pages = LOCKED_PAGES.lock().unwrap();
+        let mut newly_locked = vec![];
+        for page in pages(ptr, len).filter(|p| !locked_pages.contains_key(p)) {
+            if unsafe { libc::mlock(page as *const libc::c_void, *PAGE_SIZE) } != 0 {
+                for page in newly_locked {
+                    unsafe {
+                        libc::munlock(page as *const libc::c_void, *PAGE_SIZE);
+                    }
+                }
+                return false;
+            }
+            newly_locked.push(page);
+        }
+        for page in pages(ptr, len) {
+            *locked_pages.entry(page).or_insert(0) += 1;
+        }
+        true
+    }
+
+    fn unlock(&self, ptr: *const u8, len: usize) {
+        let mut locked_pages = LOCKED_PAGES.lock().unwrap();
+        for page in pages(ptr, len) {
+            let count = locked_pages.get_mut(&page).expect("page was not locked");
+            *count -= 1;
+            if *count == 0 {
+                locked_pages.remove(&page);
+                unsafe {
+                    libc::munlock(page as *const libc::c_void, *PAGE_SIZE);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(all(feature = "mlock", unix))]
+fn default_hooks() -> Arc<dyn SecretMemoryHooks> {
+    Arc::new(MlockHooks)
+}
+
+#[cfg(not(all(feature = "mlock", unix)))]
+fn default_hooks() -> Arc<dyn SecretMemoryHooks> {
+    Arc::new(NoopHooks)
+}
+
+lazy_static! {
+    static ref HOOKS: RwLock<Arc<dyn SecretMemoryHooks>> = RwLock::new(default_hooks());
+}
+
+/// Install the hooks used by secret buffers created from now on. Existing buffers are unlocked
+/// with the hooks that locked them.
+pub fn set_hooks(hooks: Arc<dyn SecretMemoryHooks>) {
+    *HOOKS.write().unwrap() = hooks;
+}
+
+/// Go back to the default hooks
+pub fn reset_hooks() {
+    set_hooks(default_hooks());
+}
+
+/// Bytes locked by the installed hooks, unlocked by the same hooks when dropped
+#[derive(Default)]
+pub(crate) struct LockedRegion {
+    ptr: usize,
+    len: usize,
+    /// Hooks which locked the bytes, if they were locked
+    locked_by: Option<Arc<dyn SecretMemoryHooks>>,
+}
+
+impl LockedRegion {
+    /// Lock the allocation of `v`, including its spare capacity
+    pub(crate) fn lock_vec<T>(v: &Vec<T>) -> Self {
+        let len = v.capacity() * std::mem::size_of::<T>();
+        let ptr = v.as_ptr() as *const u8;
+        let locked_by = if len == 0 {
+            None
+        } else {
+            let hooks = HOOKS.read().unwrap().clone();
+            if hooks.lock(ptr, len) {
+                Some(hooks)
+            } else {
+                None
+            }
+        };
+        Self {
+            ptr: ptr as usize,
+            len,
+            locked_by,
+        }
+    }
+
+    pub(crate) fn is_locked(&self) -> bool {
+        self.locked_by.is_some()
+    }
+}
+
+impl Drop for LockedRegion {
+    fn drop(&mut self) {
+        if let Some(hooks) = &self.locked_by {
+            hooks.unlock(self.ptr as *const u8, self.len);
+        }
+    }
+}
+
+/// Empty `Vec` with capacity for `len` elements, locked before anything is written to it
+pub(crate) fn locked_vec<T>(len: usize) -> (Vec<T>, LockedRegion) {
+    let v = Vec::with_capacity(len);
+    let region = LockedRegion::lock_vec(&v);
+    (v, region)
+}
+
+/// Fixed size buffer of secrets, locked in memory if the hooks allow it
+pub struct SecretBuffer<T: Zeroize> {
+    elems: Box<[T]>,
+    region: LockedRegion,
+}
+
+impl<T: Zeroize> SecretBuffer<T> {
+    /// Buffer of `len` elements `f(0), f(1), ...`, written after the memory is locked
+    pub fn from_fn(len: usize, f: impl FnMut(usize) -> T) -> Self {
+        let (mut elems, region) = locked_vec(len);
+        // The capacity is exact so neither extending nor converting to a boxed slice reallocates
+        elems.extend((0..len).map(f));
+        Self {
+            elems: elems.into_boxed_slice(),
+            region,
+        }
+    }
+
+    /// Copy of `elems` in a new buffer
+    pub fn from_slice(elems: &[T]) -> Self
+    where
+        T: Clone,
+    {
+        Self::from_fn(elems.len(), |i| elems[i].clone())
+    }
+
+    /// Whether the hooks locked the memory of the buffer
+    pub fn is_locked(&self) -> bool {
+        self.region.is_locked()
+    }
+}
+
+impl<T: Zeroize> Deref for SecretBuffer<T> {
+    type Target = [T];
+
+    fn deref(&self) -> &[T] {
+        &self.elems
+    }
+}
+
+impl<T: Zeroize> DerefMut for SecretBuffer<T> {
+    fn deref_mut(&mut self) -> &mut [T] {
+        &mut self.elems
+    }
+}
+
+impl<T: Zeroize> Drop for SecretBuffer<T> {
+    fn drop(&mut self) {
+        for e in self.elems.iter_mut() {
+            e.zeroize();
+        }
+        // Unlock before the memory is freed
+        self.region = LockedRegion::default();
+    }
+}
+
+/// Shows the length only
+impl<T: Zeroize> fmt::Debug for SecretBuffer<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("SecretBuffer")
+            .field("len", &self.elems.len())
+            .field("locked", &self.is_locked())
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::curve_order_elem::{CurveOrderElement, CurveOrderElementVector};
+    use std::collections::HashMap;
+    use std::sync::Mutex;
+
+    /// Records the locked ranges. Hooks are global so other tests may lock with them too, tests
+    /// only look at their own ranges.
+    #[derive(Default)]
+    struct RecordingHooks {
+        locked: Mutex<HashMap<usize, usize>>,
+    }
+
+    impl RecordingHooks {
+        fn locked_len(&self, ptr: *const CurveOrderElement) -> Option<usize> {
+            self.locked.lock().unwrap().get(&(ptr as usize)).cloned()
+        }
+    }
+
+    impl SecretMemoryHooks for RecordingHooks {
+        fn lock(&self, ptr: *const u8, len: usize) -> bool {
+            self.locked.lock().unwrap().insert(ptr as usize, len);
+            true
+        }
+
+        fn unlock(&self, ptr: *const u8, len: usize) {
+            assert_eq!(self.locked.lock().unwrap().remove(&(ptr as usize)), Some(len));
+        }
+    }
+
+    #[test]
+    fn test_secret_buffer() {
+        let hooks = Arc::new(RecordingHooks::default());
+        set_hooks(hooks.clone());
+        let v = CurveOrderElementVector::random(5);
+        let buf = v.to_secret_buffer();
+        reset_hooks();
+
+        assert!(buf.is_locked());
+        assert_eq!(&buf[..], v.as_slice());
+        let bytes = 5 * std::mem::size_of::<CurveOrderElement>();
+        assert_eq!(hooks.locked_len(buf.as_ptr()), Some(bytes));
+        // Unlocked by the hooks that locked it even though they were replaced
+        let ptr = buf.as_ptr();
+        drop(buf);
+        assert_eq!(hooks.locked_len(ptr), None);
+
+        // Locking nothing, and failing to lock
+        let empty = SecretBuffer::<u8>::from_fn(0, |_| 0);
+        assert!(!empty.is_locked());
+        set_hooks(Arc::new(NoopHooks));
+        let mut buf = SecretBuffer::from_slice(&[1u8, 2, 3]);
+        reset_hooks();
+        assert!(!buf.is_locked());
+        buf[0] = 7;
+        assert_eq!(&buf[..], &[7, 2, 3]);
+        assert_eq!(
+            format!("{:?}", buf),
+            "SecretBuffer { len: 3, locked: false }"
+        );
+    }
+
+    #[test]
+    fn test_locked_vector() {
+        let size = std::mem::size_of::<CurveOrderElement>();
+        let hooks = Arc::new(RecordingHooks::default());
+        set_hooks(hooks.clone());
+        let mut v = CurveOrderElementVector::random(2);
+        assert_eq!(hooks.locked_len(v.as_slice().as_ptr()), Some(2 * size));
+        let elems = v.as_slice().to_vec();
+
+        // Growing moves to a new locked allocation and unlocks the old one
+        let old = v.as_slice().as_ptr();
+        let e = CurveOrderElement::random();
+        v.push(e.clone());
+        assert_ne!(v.as_slice().as_ptr(), old);
+        assert_eq!(hooks.locked_len(old), None);
+        assert_eq!(hooks.locked_len(v.as_slice().as_ptr()), Some(4 * size));
+        assert_eq!(&v[..2], &elems[..]);
+        assert_eq!(v[2], e);
+
+        let w = v.clone();
+        let tail = v.split_off(1);
+        let collected: CurveOrderElementVector = w.iter().cloned().collect();
+        reset_hooks();
+        assert_eq!(hooks.locked_len(w.as_slice().as_ptr()), Some(3 * size));
+        assert!(hooks.locked_len(tail.as_slice().as_ptr()).is_some());
+        assert!(hooks.locked_len(collected.as_slice().as_ptr()).is_some());
+        assert_eq!(collected, w);
+        assert_eq!(&tail[..], &w[1..]);
+
+        let ptrs = [
+            v.as_slice().as_ptr(),
+            w.as_slice().as_ptr(),
+            tail.as_slice().as_ptr(),
+            collected.as_slice().as_ptr(),
+        ];
+        drop((v, w, tail, collected));
+        for ptr in ptrs.iter() {
+            assert_eq!(hooks.locked_len(*ptr), None);
+        }
+    }
+
+    #[cfg(all(feature = "mlock", unix))]
+    #[test]
+    fn test_mlock_shared_pages() {
+        let page_size = *PAGE_SIZE;
+        let buf = vec![0u8; 3 * page_size];
+        // Two ranges sharing the middle page
+        let (a, b) = (buf.as_ptr(), unsafe { buf.as_ptr().add(page_size + 1) });
+        let page_count = |p: *const u8| {
+            LOCKED_PAGES
+                .lock()
+                .unwrap()
+                .get(&(p as usize & !(page_size - 1)))
+                .cloned()
+        };
+        let hooks = MlockHooks;
+        if !hooks.lock(a, page_size + 2) {
+            // Over the limit of locked memory
+            return;
+        }
+        assert!(hooks.lock(b, 10));
+        assert_eq!(page_count(b), Some(2));
+        hooks.unlock(a, page_size + 2);
+        // Still locked for the second range
+        assert_eq!(page_count(b), Some(1));
+        hooks.unlock(b, 10);
+        assert_eq!(page_count(b), None);
+    }
+}
@@ -0,0 +1,561 @@
+//! Shamir secret sharing of curve order elements and of group elements. A secret group element `P`
+//! is shared with a polynomial whose coefficients are group elements, `f(x) = P + x*A_1 + ... +
+//! x^(t-1)*A_(t-1)`, so shares are group elements and reconstruction combines them with Lagrange
+//! coefficients "in the exponent". Share ids are the non-zero x coordinates the polynomial is
+//! evaluated at.
+//!
+//! In weighted sharing a party of weight `w` holds `w` shares, with consecutive ids allocated by
+//! `WeightedAllocation`, and any set of parties of total weight at least the threshold can
+//! reconstruct. Group elements are shared with `share_g1`/`share_g2` for the total weight and the
+//! shares distributed with `WeightedAllocation::assign`.
+
+use crate::curve_order_elem::{CurveOrderElement, CurveOrderElementVector};
+use crate::errors::ValueError;
+use crate::group_elem::{GroupElement, GroupElementVector};
+use crate::group_elem_g1::{G1Vector, G1};
+#[cfg(any(feature = "bls381", feature = "bn254"))]
+use crate::group_elem_g2::{G2Vector, G2};
+use crate::univar_poly::UnivarPolynomial;
+
+use rayon::prelude::*;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::ops::RangeInclusive;
+
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Share<T> {
+    pub id: usize,
+    pub value: T,
+}
+
+fn check_threshold(threshold: usize, total: usize) -> Result<(), ValueError> {
+    if threshold == 0 {
+        return Err(ValueError::OutOfRange(threshold));
+    }
+    if threshold > total {
+        return Err(ValueError::UnequalSizeVectors(threshold, total));
+    }
+    Ok(())
+}
+
+/// Lagrange coefficients for interpolating the value at 0 from the values at `ids`,
+/// `l_i = prod_(j != i) j / (j - i)`. Ids must be distinct and non-zero.
+pub fn lagrange_coefficients_at_zero(ids: &[usize]) -> Result<Vec<CurveOrderElement>, ValueError> {
+    if ids.is_empty() {
+        return Err(ValueError::IncorrectSize(0));
+    }
+    if ids.contains(&0) {
+        return Err(ValueError::ZeroElement);
+    }
+    let mut seen = HashSet::new();
+    for i in ids {
+        if !seen.insert(*i) {
+            return Err(ValueError::OutOfRange(*i));
+        }
+    }
+    let xs: Vec<_> = ids
+        .iter()
+        .map(|i| CurveOrderElement::from(*i as u64))
+        .collect();
+    let all = xs.iter().fold(CurveOrderElement::one(), |acc, x| acc * x);
+    // l_i = (prod_j j) / (i * prod_(j != i) (j - i))
+    let denominators: Vec<_> = xs
+        .par_iter()
+        .map(|x_i| {
+            xs.iter()
+                .filter(|x_j| *x_j != x_i)
+                .fold(x_i.clone(), |acc, x_j| acc * (x_j - x_i))
+        })
+        .collect();
+    let (inverses, _) = CurveOrderElement::batch_invert(&denominators);
+    Ok(inverses.into_iter().map(|inv| &all * &inv).collect())
+}
+
+/// Lagrange coefficients at zero of a fixed committee, computed once and reused for every combination
+/// of shares of its members, e.g. when the same signers produce many threshold signatures.
+#[derive(Clone, Debug, PartialEq)]
+pub struct LagrangeCoefficients {
+    ids: Vec<usize>,
+    coeffs: Vec<CurveOrderElement>,
+    positions: HashMap<usize, usize>,
+}
+
+impl LagrangeCoefficients {
+    /// Ids must be distinct and non-zero
+    pub fn new(ids: &[usize]) -> Result<Self, ValueError> {
+        let coeffs = lagrange_coefficients_at_zero(ids)?;
+        let positions = ids.iter().enumerate().map(|(p, i)| (*i, p)).collect();
+        Ok(Self {
+            ids: ids.to_vec(),
+            coeffs,
+            positions,
+        })
+    }
+
+    pub fn ids(&self) -> &[usize] {
+        &self.ids
+    }
+
+    /// Coefficients in the order of the ids
+    pub fn coefficients(&self) -> &[CurveOrderElement] {
+        &self.coeffs
+    }
+
+    pub fn coefficient(&self, id: usize) -> Option<&CurveOrderElement> {
+        self.positions.get(&id).map(|p| &self.coeffs[*p])
+    }
+
+    /// Coefficients for shares of all members of the committee, in any order
+    fn coefficients_for<T>(
+        &self,
+        shares: &[Share<T>],
+    ) -> Result<Vec<CurveOrderElement>, ValueError> {
+        if shares.len() != self.ids.len() {
+            return Err(ValueError::UnequalSizeVectors(self.ids.len(), shares.len()));
+        }
+        let mut seen = HashSet::new();
+        shares
+            .iter()
+            .map(|s| match self.coefficient(s.id) {
+                Some(c) if seen.insert(s.id) => Ok(c.clone()),
+                _ => Err(ValueError::OutOfRange(s.id)),
+            })
+            .collect()
+    }
+
+    /// `reconstruct_secret` from shares of the committee's members
+    pub fn combine_secret(
+        &self,
+        shares: &[Share<CurveOrderElement>],
+    ) -> Result<CurveOrderElement, ValueError> {
+        let coeffs = self.coefficients_for(shares)?;
+        let values: CurveOrderElementVector = shares.iter().map(|s| s.value.clone()).collect();
+        values.inner_product(&coeffs.into())
+    }
+}
+
+/// Split `secret` into `total` shares with ids `1..=total`, any `threshold` of which reconstruct
+/// it. Also returns the polynomial, whose constant term is the secret.
+pub fn share_secret(
+    secret: &CurveOrderElement,
+    threshold: usize,
+    total: usize,
+) -> Result<(Vec<Share<CurveOrderElement>>, UnivarPolynomial), ValueError> {
+    check_threshold(threshold, total)?;
+    let mut poly = UnivarPolynomial::random(threshold - 1);
+    poly[0] = secret.clone();
+    let shares = (1..=total)
+        .into_par_iter()
+        .map(|id| Share {
+            id,
+            value: poly.eval(&CurveOrderElement::from(id as u64)),
+        })
+        .collect();
+    Ok((shares, poly))
+}
+
+/// Reconstruct the secret from at least threshold many shares. Fewer shares give a wrong secret
+/// without an error.
+pub fn reconstruct_secret(
+    shares: &[Share<CurveOrderElement>],
+) -> Result<CurveOrderElement, ValueError> {
+    let ids: Vec<_> = shares.iter().map(|s| s.id).collect();
+    let coeffs = lagrange_coefficients_at_zero(&ids)?;
+    let values: CurveOrderElementVector = shares.iter().map(|s| s.value.clone()).collect();
+    values.inner_product(&coeffs.into())
+}
+
+/// Shares of a party in weighted sharing, one per unit of its weight
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct WeightedShare<T> {
+    /// Index of the party in the weights of the allocation
+    pub party: usize,
+    pub shares: Vec<Share<T>>,
+}
+
+impl<T: Clone> WeightedShare<T> {
+    /// All shares of the parties, to reconstruct with `reconstruct_secret`, `reconstruct_g1`, ..
+    pub fn flatten(shares: &[Self]) -> Vec<Share<T>> {
+        shares
+            .iter()
+            .flat_map(|w| w.shares.iter().cloned())
+            .collect()
+    }
+}
+
+/// Share ids of the parties of weighted sharing, party `i` of weight `w_i` gets the `w_i` ids
+/// following those of party `i - 1`, starting from 1
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct WeightedAllocation {
+    weights: Vec<usize>,
+    offsets: Vec<usize>,
+}
+
+impl WeightedAllocation {
+    /// Weights must be non-zero
+    pub fn new(weights: &[usize]) -> Result<Self, ValueError> {
+        if weights.is_empty() {
+            return Err(ValueError::IncorrectSize(0));
+        }
+        let mut offsets = Vec::with_capacity(weights.len());
+        let mut total = 0usize;
+        for w in weights {
+            if *w == 0 {
+                return Err(ValueError::ZeroElement);
+            }
+            offsets.push(total);
+            total = total.checked_add(*w).ok_or(ValueError::OutOfRange(*w))?;
+        }
+        Ok(Self {
+            weights: weights.to_vec(),
+            offsets,
+        })
+    }
+
+    pub fn num_parties(&self) -> usize {
+        self.weights.len()
+    }
+
+    /// Number of shares of all parties
+    pub fn total_weight(&self) -> usize {
+        self.offsets[self.offsets.len() - 1] + self.weights[self.weights.len() - 1]
+    }
+
+    pub fn weight(&self, party: usize) -> Option<usize> {
+        self.weights.get(party).cloned()
+    }
+
+    /// Ids of the shares of `party`
+    pub fn ids(&self, party: usize) -> Option<RangeInclusive<usize>> {
+        let w = self.weight(party)?;
+        let start = self.offsets[party] + 1;
+        Some(start..=start + w - 1)
+    }
+
+    /// Party holding the share with id `id`
+    pub fn party_of(&self, id: usize) -> Option<usize> {
+        if id == 0 || id > self.total_weight() {
+            return None;
+        }
+        // Index of the last party whose ids start at or before `id`
+        Some(self.offsets.partition_point(|o| *o < id) - 1)
+    }
+
+    /// Distribute the `total_weight` shares with ids `1..=total_weight` of a sharing to the parties
+    pub fn assign<T>(&self, shares: Vec<Share<T>>) -> Result<Vec<WeightedShare<T>>, ValueError> {
+        if shares.len() != self.total_weight() {
+            return Err(ValueError::UnequalSizeVectors(
+                self.total_weight(),
+                shares.len(),
+            ));
+        }
+        let mut parties: Vec<_> = (0..self.num_parties())
+            .map(|party| WeightedShare {
+                party,
+                shares: Vec::with_capacity(self.weights[party]),
+            })
+            .collect();
+        for share in shares {
+            let party = self
+                .party_of(share.id)
+                .ok_or(ValueError::OutOfRange(share.id))?;
+            parties[party].shares.push(share);
+        }
+        Ok(parties)
+    }
+
+    /// Whether the parties have total weight at least `threshold`, counting each party once
+    pub fn reaches_threshold(&self, parties: &[usize], threshold: usize) -> bool {
+        let parties: HashSet<_> = parties.iter().collect();
+        parties
+            .into_iter()
+            .map(|p| self.weight(*p).unwrap_or(0))
+            .sum::<usize>()
+            >= threshold
+    }
+}
+
+/// Split `secret` among parties by weight, any parties of total weight at least `threshold`
+/// reconstruct it. Also returns the polynomial, whose constant term is the secret.
+pub fn share_secret_weighted(
+    secret: &CurveOrderElement,
+    threshold: usize,
+    allocation: &WeightedAllocation,
+) -> Result<(Vec<WeightedShare<CurveOrderElement>>, UnivarPolynomial), ValueError> {
+    let (shares, poly) = share_secret(secret, threshold, allocation.total_weight())?;
+    Ok((allocation.assign(shares)?, poly))
+}
+
+/// Reconstruct the secret from the shares of parties of total weight at least the threshold.
+/// Less weight gives a wrong secret without an error.
+pub fn reconstruct_secret_weighted(
+    shares: &[WeightedShare<CurveOrderElement>],
+) -> Result<CurveOrderElement, ValueError> {
+    reconstruct_secret(&WeightedShare::flatten(shares))
+}
+
+macro_rules! impl_group_elem_sharing {
+    ( $group_element:ident, $group_element_vec:ident, $share_fn:ident, $reconstruct_fn:ident, $combine_fn:ident ) => {
+        /// Split the group element `secret` into `total` shares with ids `1..=total`, any
+        /// `threshold` of which reconstruct it. Also returns the coefficients of the polynomial,
+        /// the first of which is the secret.
+        pub fn $share_fn(
+            secret: &$group_element,
+            threshold: usize,
+            total: usize,
+        ) -> Result<(Vec<Share<$group_element>>, $group_element_vec), ValueError> {
+            check_threshold(threshold, total)?;
+            let mut coeffs = $group_element_vec::with_capacity(threshold);
+            coeffs.push(secret.clone());
+            for _ in 1..threshold {
+                coeffs.push($group_element::random());
+            }
+            let shares = (1..=total)
+                .into_par_iter()
+                .map(|id| {
+                    let powers = CurveOrderElementVector::new_vandermonde_vector(
+                        &CurveOrderElement::from(id as u64),
+                        threshold,
+                    );
+                    Share {
+                        id,
+                        value: coeffs.multi_scalar_mul_var_time(powers.iter()).unwrap(),
+                    }
+                })
+                .collect();
+            Ok((shares, coeffs))
+        }
+
+        /// Reconstruct the group element from at least threshold many shares. Fewer shares give a
+        /// wrong element without an error.
+        pub fn $reconstruct_fn(
+            shares: &[Share<$group_element>],
+        ) -> Result<$group_element, ValueError> {
+            let ids: Vec<_> = shares.iter().map(|s| s.id).collect();
+            let values: $group_element_vec = shares.iter().map(|s| s.value.clone()).collect();
+            $group_element_vec::interpolate_in_exponent(&ids, &values)
+        }
+
+        impl $group_element_vec {
+            /// Value at 0 of the polynomial "in the exponent" with value `shares[i]` at
+            /// `indices[i]`, `sum l_i * shares[i]` with the Lagrange coefficients of
+            /// `lagrange_coefficients_at_zero` in a single multi-scalar multiplication. This is
+            /// combining threshold signatures or reconstructing a distributed key.
+            pub fn interpolate_in_exponent(
+                indices: &[usize],
+                shares: &Self,
+            ) -> Result<$group_element, ValueError> {
+                check_vector_size_for_equality!(indices, shares)?;
+                let coeffs = lagrange_coefficients_at_zero(indices)?;
+                shares.multi_scalar_mul_var_time(coeffs.iter())
+            }
+        }
+
+        impl LagrangeCoefficients {
+            /// Reconstruct the group element from shares of the committee's members
+            pub fn $combine_fn(
+                &self,
+                shares: &[Share<$group_element>],
+            ) -> Result<$group_element, ValueError> {
+                let coeffs = self.coefficients_for(shares)?;
+                let values: $group_element_vec = shares.iter().map(|s| s.value.clone()).collect();
+                values.multi_scalar_mul_var_time(coeffs.iter())
+            }
+        }
+    };
+}
+
+impl_group_elem_sharing!(G1, G1Vector, share_g1, reconstruct_g1, combine_g1);
+#[cfg(any(feature = "bls381", feature = "bn254"))]
+impl_group_elem_sharing!(G2, G2Vector, share_g2, reconstruct_g2, combine_g2);
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_lagrange_coefficients() {
+        let poly = UnivarPolynomial::random(3);
+        let ids = vec![2, 5, 7, 11];
+        let coeffs = lagrange_coefficients_at_zero(&ids).unwrap();
+        let value = ids
+            .iter()
+            .zip(coeffs.iter())
+            .fold(CurveOrderElement::zero(), |acc, (i, l)| {
+                acc + poly.eval(&CurveOrderElement::from(*i as u64)) * l
+            });
+        assert_eq!(value, poly[0]);
+
+        assert!(lagrange_coefficients_at_zero(&[]).is_err());
+        assert!(lagrange_coefficients_at_zero(&[1, 0, 2]).is_err());
+        assert!(lagrange_coefficients_at_zero(&[1, 2, 1]).is_err());
+    }
+
+    #[test]
+    fn test_fixed_committee() {
+        let secret = CurveOrderElement::random();
+        let (shares, _) = share_secret(&secret, 3, 5).unwrap();
+        let committee = LagrangeCoefficients::new(&[4, 1, 2]).unwrap();
+        assert_eq!(committee.ids(), &[4, 1, 2]);
+        assert_eq!(
+            committee.coefficients(),
+            &lagrange_coefficients_at_zero(&[4, 1, 2]).unwrap()[..]
+        );
+        assert_eq!(committee.coefficient(1), Some(&committee.coefficients()[1]));
+        assert!(committee.coefficient(3).is_none());
+
+        // Shares in any order, only of the committee's members
+        let members = vec![shares[0].clone(), shares[1].clone(), shares[3].clone()];
+        assert_eq!(committee.combine_secret(&members).unwrap(), secret);
+        let reordered = vec![shares[3].clone(), shares[0].clone(), shares[1].clone()];
+        assert_eq!(committee.combine_secret(&reordered).unwrap(), secret);
+        assert!(committee.combine_secret(&shares[..3]).is_err());
+        assert!(committee.combine_secret(&members[..2]).is_err());
+        let repeated = vec![shares[0].clone(), shares[0].clone(), shares[3].clone()];
+        assert!(committee.combine_secret(&repeated).is_err());
+
+        let g = G1::generator();
+        let point_shares: Vec<_> = members
+            .iter()
+            .map(|sh| Share {
+                id: sh.id,
+                value: &g * &sh.value,
+            })
+            .collect();
+        assert_eq!(committee.combine_g1(&point_shares).unwrap(), &g * &secret);
+        assert!(LagrangeCoefficients::new(&[1, 1]).is_err());
+    }
+
+    #[test]
+    fn test_share_scalar() {
+        let secret = CurveOrderElement::random();
+        let (shares, poly) = share_secret(&secret, 3, 5).unwrap();
+        assert_eq!(poly.degree(), 2);
+        assert_eq!(shares.len(), 5);
+        assert_eq!(reconstruct_secret(&shares[..3]).unwrap(), secret);
+        assert_eq!(reconstruct_secret(&shares[2..]).unwrap(), secret);
+        assert_eq!(reconstruct_secret(&shares).unwrap(), secret);
+        assert_ne!(reconstruct_secret(&shares[..2]).unwrap(), secret);
+
+        assert!(share_secret(&secret, 0, 5).is_err());
+        assert!(share_secret(&secret, 6, 5).is_err());
+        let (shares, _) = share_secret(&secret, 1, 1).unwrap();
+        assert_eq!(shares[0].value, secret);
+    }
+
+    #[test]
+    fn test_weighted_sharing() {
+        let allocation = WeightedAllocation::new(&[3, 1, 2, 1]).unwrap();
+        assert_eq!(allocation.num_parties(), 4);
+        assert_eq!(allocation.total_weight(), 7);
+        assert_eq!(allocation.ids(0), Some(1..=3));
+        assert_eq!(allocation.ids(2), Some(5..=6));
+        assert_eq!(allocation.ids(4), None);
+        let owners: Vec<_> = (0..=8).map(|id| allocation.party_of(id)).collect();
+        assert_eq!(
+            owners,
+            vec![
+                None,
+                Some(0),
+                Some(0),
+                Some(0),
+                Some(1),
+                Some(2),
+                Some(2),
+                Some(3),
+                None
+            ]
+        );
+
+        let secret = CurveOrderElement::random();
+        let (shares, poly) = share_secret_weighted(&secret, 4, &allocation).unwrap();
+        assert_eq!(poly.degree(), 3);
+        for (p, w) in shares.iter().enumerate() {
+            assert_eq!(w.party, p);
+            assert_eq!(w.shares.len(), allocation.weight(p).unwrap());
+            assert!(w
+                .shares
+                .iter()
+                .all(|s| allocation.ids(p).unwrap().contains(&s.id)));
+        }
+
+        // The heavy party with any other reaches the threshold, as do all light parties together
+        assert!(allocation.reaches_threshold(&[0, 3], 4));
+        assert!(allocation.reaches_threshold(&[1, 2, 3], 4));
+        assert!(!allocation.reaches_threshold(&[1, 2], 4));
+        assert!(!allocation.reaches_threshold(&[0, 0], 4));
+        let heavy = vec![shares[0].clone(), shares[3].clone()];
+        assert_eq!(reconstruct_secret_weighted(&heavy).unwrap(), secret);
+        let light = vec![shares[1].clone(), shares[2].clone(), shares[3].clone()];
+        assert_eq!(reconstruct_secret_weighted(&light).unwrap(), secret);
+        assert_ne!(reconstruct_secret_weighted(&shares[1..3]).unwrap(), secret);
+
+        // Group elements shared for the total weight
+        let point = G1::random();
+        let (point_shares, _) = share_g1(&point, 4, allocation.total_weight()).unwrap();
+        let point_shares = allocation.assign(point_shares).unwrap();
+        let flat = WeightedShare::flatten(&[point_shares[2].clone(), point_shares[0].clone()]);
+        assert_eq!(reconstruct_g1(&flat).unwrap(), point);
+
+        assert!(WeightedAllocation::new(&[]).is_err());
+        assert!(WeightedAllocation::new(&[2, 0]).is_err());
+        assert!(share_secret_weighted(&secret, 8, &allocation).is_err());
+        assert!(allocation.assign(shares[0].shares.clone()).is_err());
+    }
+
+    #[test]
+    fn test_share_group_elem() {
+        macro_rules! check_sharing {
+            ( $group:ident, $share_fn:ident, $reconstruct_fn:ident ) => {
+                let secret = $group::random();
+                let (shares, coeffs) = $share_fn(&secret, 3, 6).unwrap();
+                assert_eq!(coeffs.len(), 3);
+                assert_eq!(coeffs[0], secret);
+                assert_eq!($reconstruct_fn(&shares[..3]).unwrap(), secret);
+                assert_eq!(
+                    $reconstruct_fn(&[shares[5].clone(), shares[1].clone(), shares[3].clone()])
+                        .unwrap(),
+                    secret
+                );
+                assert_ne!($reconstruct_fn(&shares[..2]).unwrap(), secret);
+                assert!($share_fn(&secret, 7, 6).is_err());
+
+                // Shares of s*g from the scalar sharing of s reconstruct to s*g
+                let s = CurveOrderElement::random();
+                let g = $group::generator();
+                let (scalar_shares, _) = share_secret(&s, 2, 3).unwrap();
+                let point_shares: Vec<_> = scalar_shares
+                    .iter()
+                    .map(|sh| Share {
+                        id: sh.id,
+                        value: &g * &sh.value,
+                    })
+                    .collect();
+                assert_eq!($reconstruct_fn(&point_shares[1..]).unwrap(), &g * &s);
+            };
+        }
+        check_sharing!(G1, share_g1, reconstruct_g1);
+        #[cfg(any(feature = "bls381", feature = "bn254"))]
+        {
+            check_sharing!(G2, share_g2, reconstruct_g2);
+        }
+    }
+
+    #[test]
+    fn test_interpolate_in_exponent() {
+        let secret = G1::random();
+        let (shares, _) = share_g1(&secret, 3, 5).unwrap();
+        let ids = vec![5, 2, 3];
+        let values: G1Vector = ids.iter().map(|i| shares[i - 1].value.clone()).collect();
+        assert_eq!(
+            G1Vector::interpolate_in_exponent(&ids, &values).unwrap(),
+            secret
+        );
+        assert!(G1Vector::interpolate_in_exponent(&ids[..2], &values).is_err());
+        assert!(G1Vector::interpolate_in_exponent(&[1, 1, 2], &values).is_err());
+    }
+}
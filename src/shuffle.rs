@@ -0,0 +1,426 @@
+//! Building blocks of the Bayer-Groth shuffle argument over group G1, made non-interactive with
+//! Fiat-Shamir. Vectors are committed with generalized Pedersen commitments
+//! `com(a; r) = a_1*g_1 + ... + a_n*g_n + r*u` using the generators `g_vec` and `u` of a
+//! `Generators`.
+//! - `ProductArgument` proves a committed vector has a given product (the single value product
+//!   argument).
+//! - `MultiExpArgument` proves a group element is the multi-exponentiation of public group
+//!   elements with a committed vector of exponents.
+//! - `PermutationArgument` proves a committed vector is a permutation of a public vector, by
+//!   proving `prod (b_i - z) = prod (a_i - z)` for a random `z`.
+//!
+//! A shuffle commits to the permutation, derives the committed exponents `x^pi(i)` and combines
+//! the permutation and multi-exponentiation arguments.
+
+use crate::curve_order_elem::{CurveOrderElement, CurveOrderElementVector};
+use crate::errors::ValueError;
+use crate::group_elem::{GroupElement, GroupElementVector};
+use crate::group_elem_g1::{G1Vector, G1};
+use crate::group_params::Generators;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// Commit to the vector `a` with randomness `r` using the first `a.len()` elements of `g_vec`
+/// and `u` of `gens`
+pub fn commit_vector(
+    gens: &Generators,
+    a: &CurveOrderElementVector,
+    r: &CurveOrderElement,
+) -> Result<G1, ValueError> {
+    if a.len() > gens.capacity() {
+        return Err(ValueError::OutOfRange(a.len()));
+    }
+    let mut bases = G1Vector::from(gens.g_vec().slice(..a.len()));
+    bases.push(gens.u().clone());
+    let scalars: Vec<_> = a.iter().chain(std::iter::once(r)).collect();
+    bases.inner_product_const_time(scalars)
+}
+
+fn challenge(transcript: &mut Vec<u8>, elems: &[&G1]) -> CurveOrderElement {
+    for e in elems {
+        transcript.extend_from_slice(&e.to_vec());
+    }
+    let c = CurveOrderElement::from_msg_hash(transcript);
+    transcript.extend_from_slice(&c.to_bytes());
+    c
+}
+
+fn append_scalars(transcript: &mut Vec<u8>, elems: &CurveOrderElementVector) {
+    for e in elems.iter() {
+        transcript.extend_from_slice(&e.to_bytes());
+    }
+}
+
+/// Proof that the vector `a` committed in `c_a` has product `prod a_i = b`. Needs vectors of at
+/// least 2 elements.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct ProductArgument {
+    c_d: G1,
+    c_small_delta: G1,
+    c_big_delta: G1,
+    a_tilde: CurveOrderElementVector,
+    b_tilde: CurveOrderElementVector,
+    r_tilde: CurveOrderElement,
+    s_tilde: CurveOrderElement,
+}
+
+impl ProductArgument {
+    /// `a` and `r` open the commitment `c_a`. `prefix` is hashed into the challenge.
+    pub fn new(
+        gens: &Generators,
+        c_a: &G1,
+        a: &CurveOrderElementVector,
+        r: &CurveOrderElement,
+        prefix: &[u8],
+    ) -> Result<Self, ValueError> {
+        let n = a.len();
+        if n < 2 {
+            return Err(ValueError::IncorrectSize(n));
+        }
+        // Partial products b_k = a_1 * ... * a_k
+        let mut b = CurveOrderElementVector::with_capacity(n);
+        b.push(a[0].clone());
+        for i in 1..n {
+            let p = &b[i - 1] * &a[i];
+            b.push(p);
+        }
+
+        let d = CurveOrderElementVector::random(n);
+        let r_d = CurveOrderElement::random();
+        let mut delta = CurveOrderElementVector::random(n);
+        delta[0] = d[0].clone();
+        delta[n - 1] = CurveOrderElement::zero();
+        let s_1 = CurveOrderElement::random();
+        let s_x = CurveOrderElement::random();
+
+        let small_delta: CurveOrderElementVector =
+            (0..n - 1).map(|k| -(&delta[k] * &d[k + 1])).collect();
+        let big_delta: CurveOrderElementVector = (0..n - 1)
+            .map(|k| &delta[k + 1] - &a[k + 1] * &delta[k] - &b[k] * &d[k + 1])
+            .collect();
+        let c_d = commit_vector(gens, &d, &r_d)?;
+        let c_small_delta = commit_vector(gens, &small_delta, &s_1)?;
+        let c_big_delta = commit_vector(gens, &big_delta, &s_x)?;
+
+        let mut transcript = Self::transcript(c_a, &b[n - 1], prefix);
+        let x = challenge(&mut transcript, &[&c_d, &c_small_delta, &c_big_delta]);
+
+        Ok(Self {
+            a_tilde: a.scaled_by(&x).plus(&d)?,
+            b_tilde: b.scaled_by(&x).plus(&delta)?,
+            r_tilde: &x * r + r_d,
+            s_tilde: &x * &s_x + s_1,
+            c_d,
+            c_small_delta,
+            c_big_delta,
+        })
+    }
+
+    pub fn verify(
+        &self,
+        gens: &Generators,
+        c_a: &G1,
+        b: &CurveOrderElement,
+        prefix: &[u8],
+    ) -> bool {
+        let n = self.a_tilde.len();
+        if n < 2 || self.b_tilde.len() != n {
+            return false;
+        }
+        let mut transcript = Self::transcript(c_a, b, prefix);
+        let x = challenge(
+            &mut transcript,
+            &[&self.c_d, &self.c_small_delta, &self.c_big_delta],
+        );
+
+        if self.b_tilde[0] != self.a_tilde[0] || self.b_tilde[n - 1] != &x * b {
+            return false;
+        }
+        match commit_vector(gens, &self.a_tilde, &self.r_tilde) {
+            Ok(c) if c == c_a * &x + &self.c_d => (),
+            _ => return false,
+        }
+        let e: CurveOrderElementVector = (0..n - 1)
+            .map(|k| &x * &self.b_tilde[k + 1] - &self.b_tilde[k] * &self.a_tilde[k + 1])
+            .collect();
+        match commit_vector(gens, &e, &self.s_tilde) {
+            Ok(c) => c == &self.c_big_delta * &x + &self.c_small_delta,
+            Err(_) => false,
+        }
+    }
+
+    fn transcript(c_a: &G1, b: &CurveOrderElement, prefix: &[u8]) -> Vec<u8> {
+        let mut transcript = prefix.to_vec();
+        transcript.extend_from_slice(&c_a.to_vec());
+        transcript.extend_from_slice(&b.to_bytes());
+        transcript
+    }
+}
+
+/// Proof that `e = b_1*C_1 + ... + b_n*C_n` for public group elements `C_i` and the vector `b`
+/// committed in `c_b`
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct MultiExpArgument {
+    c_0: G1,
+    e_0: G1,
+    a: CurveOrderElementVector,
+    r: CurveOrderElement,
+}
+
+impl MultiExpArgument {
+    /// `b` and `r` open the commitment `c_b`. `prefix` is hashed into the challenge.
+    pub fn new(
+        gens: &Generators,
+        bases: &G1Vector,
+        e: &G1,
+        c_b: &G1,
+        b: &CurveOrderElementVector,
+        r: &CurveOrderElement,
+        prefix: &[u8],
+    ) -> Result<Self, ValueError> {
+        check_vector_size_for_equality!(bases, b)?;
+        let a_0 = CurveOrderElementVector::random(b.len());
+        let r_0 = CurveOrderElement::random();
+        let c_0 = commit_vector(gens, &a_0, &r_0)?;
+        let e_0 = bases.inner_product_const_time(a_0.iter())?;
+
+        let mut transcript = Self::transcript(bases, e, c_b, prefix);
+        let x = challenge(&mut transcript, &[&c_0, &e_0]);
+        Ok(Self {
+            a: b.scaled_by(&x).plus(&a_0)?,
+            r: &x * r + r_0,
+            c_0,
+            e_0,
+        })
+    }
+
+    pub fn verify(
+        &self,
+        gens: &Generators,
+        bases: &G1Vector,
+        e: &G1,
+        c_b: &G1,
+        prefix: &[u8],
+    ) -> bool {
+        if bases.len() != self.a.len() {
+            return false;
+        }
+        let mut transcript = Self::transcript(bases, e, c_b, prefix);
+        let x = challenge(&mut transcript, &[&self.c_0, &self.e_0]);
+        match commit_vector(gens, &self.a, &self.r) {
+            Ok(c) if c == c_b * &x + &self.c_0 => (),
+            _ => return false,
+        }
+        match bases.multi_scalar_mul_var_time(self.a.iter()) {
+            Ok(m) => m == e * &x + &self.e_0,
+            Err(_) => false,
+        }
+    }
+
+    fn transcript(bases: &G1Vector, e: &G1, c_b: &G1, prefix: &[u8]) -> Vec<u8> {
+        let mut transcript = prefix.to_vec();
+        for g in bases.iter().chain([e, c_b].iter().cloned()) {
+            transcript.extend_from_slice(&g.to_vec());
+        }
+        transcript
+    }
+}
+
+/// Proof that the vector committed in `c_b` is a permutation of the public vector `a`
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct PermutationArgument(ProductArgument);
+
+impl PermutationArgument {
+    /// `b` and `r` open the commitment `c_b`. `prefix` is hashed into the challenges.
+    pub fn new(
+        gens: &Generators,
+        c_b: &G1,
+        a: &CurveOrderElementVector,
+        b: &CurveOrderElementVector,
+        r: &CurveOrderElement,
+        prefix: &[u8],
+    ) -> Result<Self, ValueError> {
+        check_vector_size_for_equality!(a, b)?;
+        let (transcript, z) = Self::transcript(c_b, a, prefix);
+        let shifted: CurveOrderElementVector = b.iter().map(|b_i| b_i - &z).collect();
+        let c_shifted = Self::shifted_commitment(gens, c_b, a.len(), &z)?;
+        ProductArgument::new(gens, &c_shifted, &shifted, r, &transcript).map(Self)
+    }
+
+    pub fn verify(
+        &self,
+        gens: &Generators,
+        c_b: &G1,
+        a: &CurveOrderElementVector,
+        prefix: &[u8],
+    ) -> bool {
+        let (transcript, z) = Self::transcript(c_b, a, prefix);
+        let product = a
+            .iter()
+            .fold(CurveOrderElement::one(), |acc, a_i| acc * (a_i - &z));
+        match Self::shifted_commitment(gens, c_b, a.len(), &z) {
+            Ok(c) => self.0.verify(gens, &c, &product, &transcript),
+            Err(_) => false,
+        }
+    }
+
+    /// Commitment to `b_i - z` from the commitment to `b` by subtracting `z * sum g_i`
+    fn shifted_commitment(
+        gens: &Generators,
+        c_b: &G1,
+        n: usize,
+        z: &CurveOrderElement,
+    ) -> Result<G1, ValueError> {
+        if n > gens.capacity() {
+            return Err(ValueError::OutOfRange(n));
+        }
+        let sum = G1Vector::from(gens.g_vec().slice(..n)).sum();
+        Ok(c_b - &(sum * z))
+    }
+
+    fn transcript(
+        c_b: &G1,
+        a: &CurveOrderElementVector,
+        prefix: &[u8],
+    ) -> (Vec<u8>, CurveOrderElement) {
+        let mut transcript = prefix.to_vec();
+        append_scalars(&mut transcript, a);
+        let z = challenge(&mut transcript, &[c_b]);
+        (transcript, z)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use rand::seq::SliceRandom;
+    use rand::thread_rng;
+
+    #[test]
+    fn test_product_argument() {
+        let gens = Generators::new(b"test shuffle", 8);
+        for n in 2..=8 {
+            let a = CurveOrderElementVector::random(n);
+            let r = CurveOrderElement::random();
+            let c_a = commit_vector(&gens, &a, &r).unwrap();
+            let b = a
+                .iter()
+                .fold(CurveOrderElement::one(), |acc, a_i| acc * a_i);
+            let proof = ProductArgument::new(&gens, &c_a, &a, &r, b"label").unwrap();
+            assert!(proof.verify(&gens, &c_a, &b, b"label"));
+            assert!(!proof.verify(&gens, &c_a, &(&b + &CurveOrderElement::one()), b"label"));
+            assert!(!proof.verify(&gens, &c_a, &b, b"other label"));
+            assert!(!proof.verify(&gens, &G1::random(), &b, b"label"));
+        }
+
+        let a = CurveOrderElementVector::random(1);
+        let r = CurveOrderElement::random();
+        let c_a = commit_vector(&gens, &a, &r).unwrap();
+        assert!(ProductArgument::new(&gens, &c_a, &a, &r, b"label").is_err());
+        assert!(commit_vector(&gens, &CurveOrderElementVector::random(9), &r).is_err());
+    }
+
+    #[test]
+    fn test_multi_exp_argument() {
+        let gens = Generators::new(b"test shuffle", 8);
+        let bases = G1Vector::random(6);
+        let b = CurveOrderElementVector::random(6);
+        let r = CurveOrderElement::random();
+        let c_b = commit_vector(&gens, &b, &r).unwrap();
+        let e = bases.multi_scalar_mul_var_time(b.iter()).unwrap();
+        let proof = MultiExpArgument::new(&gens, &bases, &e, &c_b, &b, &r, b"label").unwrap();
+        assert!(proof.verify(&gens, &bases, &e, &c_b, b"label"));
+        assert!(!proof.verify(&gens, &bases, &G1::random(), &c_b, b"label"));
+        assert!(!proof.verify(&gens, &G1Vector::random(6), &e, &c_b, b"label"));
+        assert!(!proof.verify(&gens, &bases, &e, &c_b, b"other label"));
+
+        // Exponents other than the committed ones do not verify
+        let other = CurveOrderElementVector::random(6);
+        let e = bases.multi_scalar_mul_var_time(other.iter()).unwrap();
+        let proof = MultiExpArgument::new(&gens, &bases, &e, &c_b, &b, &r, b"label").unwrap();
+        assert!(!proof.verify(&gens, &bases, &e, &c_b, b"label"));
+    }
+
+    #[test]
+    fn test_permutation_argument() {
+        let gens = Generators::new(b"test shuffle", 8);
+        let a = CurveOrderElementVector::random(7);
+        let mut b = a.clone();
+        b.as_mut_slice().shuffle(&mut thread_rng());
+        let r = CurveOrderElement::random();
+        let c_b = commit_vector(&gens, &b, &r).unwrap();
+        let proof = PermutationArgument::new(&gens, &c_b, &a, &b, &r, b"label").unwrap();
+        assert!(proof.verify(&gens, &c_b, &a, b"label"));
+        assert!(!proof.verify(&gens, &c_b, &CurveOrderElementVector::random(7), b"label"));
+
+        // Not a permutation
+        b[3] = CurveOrderElement::random();
+        let c_b = commit_vector(&gens, &b, &r).unwrap();
+        let proof = PermutationArgument::new(&gens, &c_b, &a, &b, &r, b"label").unwrap();
+        assert!(!proof.verify(&gens, &c_b, &a, b"label"));
+    }
+
+    #[test]
+    fn test_shuffle_of_group_elements() {
+        // Prove `shuffled` is a permutation of `elems` by committing to the permutation `pi`,
+        // then to `x^pi(i)`, proving `y*pi(i) + x^pi(i)` is a permutation of `y*i + x^i` and that
+        // `sum x^pi(i) * shuffled_i = sum x^i * elems_i`
+        let n = 6;
+        let gens = Generators::new(b"test shuffle", n);
+        let elems = G1Vector::random(n);
+        let mut pi: Vec<usize> = (0..n).collect();
+        pi.shuffle(&mut thread_rng());
+        let shuffled: G1Vector = pi.iter().map(|i| elems[*i].clone()).collect();
+
+        let mut transcript = vec![];
+        for g in elems.iter().chain(shuffled.iter()) {
+            transcript.extend_from_slice(&g.to_vec());
+        }
+        let pi_elems: CurveOrderElementVector = pi
+            .iter()
+            .map(|i| CurveOrderElement::from(*i as u64 + 1))
+            .collect();
+        let r_a = CurveOrderElement::random();
+        let c_a = commit_vector(&gens, &pi_elems, &r_a).unwrap();
+        let x = challenge(&mut transcript, &[&c_a]);
+
+        let x_powers = CurveOrderElementVector::new_vandermonde_vector(&x, n + 1).split_off(1);
+        let b: CurveOrderElementVector = pi.iter().map(|i| x_powers[*i].clone()).collect();
+        let r_b = CurveOrderElement::random();
+        let c_b = commit_vector(&gens, &b, &r_b).unwrap();
+        let y = challenge(&mut transcript, &[&c_b]);
+
+        let public: CurveOrderElementVector = (0..n)
+            .map(|i| &y * &CurveOrderElement::from(i as u64 + 1) + &x_powers[i])
+            .collect();
+        let combined = pi_elems.scaled_by(&y).plus(&b).unwrap();
+        let c_combined = &c_a * &y + &c_b;
+        let r_combined = &y * &r_a + &r_b;
+        let perm_proof = PermutationArgument::new(
+            &gens,
+            &c_combined,
+            &public,
+            &combined,
+            &r_combined,
+            &transcript,
+        )
+        .unwrap();
+        let e = elems.multi_scalar_mul_var_time(x_powers.iter()).unwrap();
+        let mexp_proof =
+            MultiExpArgument::new(&gens, &shuffled, &e, &c_b, &b, &r_b, &transcript).unwrap();
+
+        assert!(perm_proof.verify(&gens, &c_combined, &public, &transcript));
+        assert!(mexp_proof.verify(&gens, &shuffled, &e, &c_b, &transcript));
+
+        // Replacing an element of the shuffled vector breaks the multi-exponentiation argument
+        let mut bad = shuffled.clone();
+        bad[2] = G1::random();
+        let mexp_proof =
+            MultiExpArgument::new(&gens, &bad, &e, &c_b, &b, &r_b, &transcript).unwrap();
+        assert!(!mexp_proof.verify(&gens, &bad, &e, &c_b, &transcript));
+    }
+}
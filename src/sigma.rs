@@ -0,0 +1,170 @@
+//! Non-interactive Schnorr proofs of knowledge of a discrete logarithm `x` with `y = g*x` in
+//! group G1, in commitment form `(t, s)` with `g*s = t + y*c` and `c = H(g || y || t || context)`.
+//! Unlike the `(e, s)` form of `schnorr::Signature`, the commitment form lets many proofs be
+//! verified at once: `batch_verify` checks `sum r_i*(g_i*s_i - t_i - y_i*c_i) = 0` for random
+//! weights `r_i` with a single multi-scalar multiplication, where terms on the same base `g_i` are
+//! merged. A batch with an invalid proof passes with probability about `1/q`.
+
+use crate::curve_order_elem::CurveOrderElement;
+use crate::group_elem::GroupElement;
+use crate::group_elem_g1::G1;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+const CHALLENGE_DST: &[u8] = b"AMCL_WRAPPER_SCHNORR_POK";
+
+/// Statement proven by a `SchnorrProof`, knowledge of the discrete logarithm of `y` to base `g`
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct DlogStatement {
+    pub g: G1,
+    pub y: G1,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct SchnorrProof {
+    t: G1,
+    s: CurveOrderElement,
+}
+
+fn challenge(statement: &DlogStatement, t: &G1, context: &[u8]) -> CurveOrderElement {
+    CurveOrderElement::from_msg_hash(
+        &[
+            CHALLENGE_DST,
+            &statement.g.to_vec(),
+            &statement.y.to_vec(),
+            &t.to_vec(),
+            context,
+        ]
+        .concat(),
+    )
+}
+
+impl DlogStatement {
+    pub fn new(g: G1, y: G1) -> Self {
+        Self { g, y }
+    }
+
+    /// Statement `y = g*x`
+    pub fn from_witness(g: G1, x: &CurveOrderElement) -> Self {
+        let y = g.scalar_mul_const_time(x);
+        Self { g, y }
+    }
+}
+
+impl SchnorrProof {
+    /// Prove knowledge of `x` with `statement.y = statement.g*x`. `context` is bound into the
+    /// challenge, e.g. a session id or the presentation the proof is part of.
+    pub fn prove(statement: &DlogStatement, x: &CurveOrderElement, context: &[u8]) -> Self {
+        let k = CurveOrderElement::random();
+        let t = statement.g.scalar_mul_const_time(&k);
+        let c = challenge(statement, &t, context);
+        let s = k + &c * x;
+        Self { t, s }
+    }
+
+    /// Check `g*s == t + y*c`
+    pub fn verify(&self, statement: &DlogStatement, context: &[u8]) -> bool {
+        let c = challenge(statement, &self.t, context);
+        statement.g.binary_scalar_mul(&statement.y, &self.s, &-&c) == self.t
+    }
+
+    /// Verify all proofs with one multi-scalar multiplication, each with its own context. Fails
+    /// if any proof is invalid, without telling which; verify individually to find it. An empty
+    /// batch is valid.
+    pub fn batch_verify(batch: &[(&DlogStatement, &SchnorrProof, &[u8])]) -> bool {
+        let mut bases: Vec<G1> = vec![];
+        let mut base_scalars: Vec<CurveOrderElement> = vec![];
+        let mut points = Vec::with_capacity(2 * batch.len());
+        let mut scalars = Vec::with_capacity(2 * batch.len());
+        for (statement, proof, context) in batch {
+            let c = challenge(statement, &proof.t, context);
+            let r = CurveOrderElement::random();
+            let rs = &r * &proof.s;
+            match bases.iter().position(|g| g == &statement.g) {
+                Some(i) => base_scalars[i] += rs,
+                None => {
+                    bases.push(statement.g.clone());
+                    base_scalars.push(rs);
+                }
+            }
+            points.push(proof.t.clone());
+            scalars.push(-&r);
+            points.push(statement.y.clone());
+            scalars.push(-(&r * &c));
+        }
+        points.extend(bases);
+        scalars.extend(base_scalars);
+        match G1::msm_var_time(&points, &scalars) {
+            Ok(p) => p.is_identity(),
+            Err(_) => false,
+        }
+    }
+
+    pub fn commitment(&self) -> &G1 {
+        &self.t
+    }
+
+    pub fn response(&self) -> &CurveOrderElement {
+        &self.s
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::group_params::GroupParams;
+
+    #[test]
+    fn test_prove_verify() {
+        let x = CurveOrderElement::random();
+        let statement = DlogStatement::from_witness(G1::generator(), &x);
+        let proof = SchnorrProof::prove(&statement, &x, b"session 1");
+        assert!(proof.verify(&statement, b"session 1"));
+        assert!(!proof.verify(&statement, b"session 2"));
+        let other = DlogStatement::new(G1::generator(), G1::random());
+        assert!(!proof.verify(&other, b"session 1"));
+        let wrong = SchnorrProof::prove(&statement, &CurveOrderElement::random(), b"session 1");
+        assert!(!wrong.verify(&statement, b"session 1"));
+    }
+
+    #[test]
+    fn test_batch_verify() {
+        let h = GroupParams::from_label(b"test").g1().clone();
+        let mut statements = vec![];
+        let mut proofs = vec![];
+        for i in 0..10 {
+            let x = CurveOrderElement::random();
+            // Two distinct bases, terms on each are merged
+            let g = if i % 2 == 0 {
+                G1::generator()
+            } else {
+                h.clone()
+            };
+            let statement = DlogStatement::from_witness(g, &x);
+            proofs.push(SchnorrProof::prove(&statement, &x, &[i as u8]));
+            statements.push(statement);
+        }
+        let contexts: Vec<[u8; 1]> = (0..10u8).map(|i| [i]).collect();
+        let batch: Vec<_> = statements
+            .iter()
+            .zip(proofs.iter())
+            .zip(contexts.iter())
+            .map(|((st, p), c)| (st, p, &c[..]))
+            .collect();
+        assert!(SchnorrProof::batch_verify(&batch));
+        assert!(SchnorrProof::batch_verify(&[]));
+
+        // One proof against the wrong context
+        let mut bad = batch.clone();
+        bad[3].2 = b"other";
+        assert!(!SchnorrProof::batch_verify(&bad));
+
+        // One proof for another statement
+        let mut bad = batch.clone();
+        bad[4].1 = &proofs[5];
+        assert!(!SchnorrProof::batch_verify(&bad));
+    }
+}
@@ -0,0 +1,83 @@
+//! Joux's one-round tripartite Diffie-Hellman. Each of three parties picks a secret `a` and
+//! publishes `(a*g1, a*g2)` for the generators of G1 and G2. With the public values of the two
+//! others, one contributing its G1 element and the other its G2 element, every party computes the
+//! same `e(g1, g2)^(abc)`. The shared GT element is turned into symmetric key bytes with
+//! `derive_key`. Like plain Diffie-Hellman this is unauthenticated.
+
+use crate::curve_order_elem::CurveOrderElement;
+use crate::errors::ValueError;
+use crate::extension_field_gt::GT;
+use crate::group_elem::GroupElement;
+use crate::group_elem_g1::G1;
+use crate::group_elem_g2::G2;
+
+use sha2::{Digest, Sha256};
+
+const KDF_LABEL: &[u8] = b"Joux tripartite key";
+
+/// `(a*g1, a*g2)` to send to the other parties
+pub fn public_values(my_scalar: &CurveOrderElement) -> (G1, G2) {
+    (G1::generator() * my_scalar, G2::generator() * my_scalar)
+}
+
+/// `e(their_g1, their_g2)^a` where `their_g1` and `their_g2` come from different parties. Fails
+/// if either is the identity.
+pub fn joux_shared_key(
+    my_scalar: &CurveOrderElement,
+    their_g1: &G1,
+    their_g2: &G2,
+) -> Result<GT, ValueError> {
+    if their_g1.is_identity() || their_g2.is_identity() {
+        return Err(ValueError::IdentityElement);
+    }
+    // Exponentiating in G1 is cheaper than in GT
+    Ok(GT::ate_pairing(&(their_g1 * my_scalar), their_g2))
+}
+
+/// 32 byte key `SHA256(label || shared || info)` where `info` binds the key to its context, eg.
+/// the public values of the three parties
+pub fn derive_key(shared: &GT, info: &[u8]) -> [u8; 32] {
+    let mut hasher = Sha256::new();
+    hasher.input(KDF_LABEL);
+    hasher.input(shared.to_vec());
+    hasher.input(info);
+    let mut key = [0u8; 32];
+    key.copy_from_slice(hasher.result().as_slice());
+    key
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_three_parties() {
+        let (a, b, c) = (
+            CurveOrderElement::random(),
+            CurveOrderElement::random(),
+            CurveOrderElement::random(),
+        );
+        let (a1, a2) = public_values(&a);
+        let (b1, b2) = public_values(&b);
+        let (c1, c2) = public_values(&c);
+
+        let k_a = joux_shared_key(&a, &b1, &c2).unwrap();
+        let k_b = joux_shared_key(&b, &c1, &a2).unwrap();
+        let k_c = joux_shared_key(&c, &a1, &b2).unwrap();
+        assert_eq!(k_a, k_b);
+        assert_eq!(k_b, k_c);
+        assert_eq!(
+            k_a,
+            GT::ate_pairing(&G1::generator(), &G2::generator()).pow(&(&(&a * &b) * &c))
+        );
+        assert_eq!(derive_key(&k_a, b"session"), derive_key(&k_c, b"session"));
+        assert_ne!(derive_key(&k_a, b"session"), derive_key(&k_a, b"other"));
+
+        // An outsider gets another key
+        let d = CurveOrderElement::random();
+        assert_ne!(joux_shared_key(&d, &b1, &c2).unwrap(), k_a);
+
+        assert!(joux_shared_key(&a, &G1::identity(), &c2).is_err());
+        assert!(joux_shared_key(&a, &b1, &G2::identity()).is_err());
+    }
+}
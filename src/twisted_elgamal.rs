@@ -0,0 +1,419 @@
+//! Twisted ElGamal encryption over group G1 (Chen, Ma, Tang, Au). With independent generators
+//! `g` and `h` and public key `pk = sk*h`, a value `m` is encrypted with randomness `r` as
+//! `(r*pk, m*g + r*h)`. The second element is a Pedersen commitment to `m` so proofs about
+//! committed values, e.g. range proofs, apply to ciphertexts directly. Ciphertexts under the same
+//! key are additively homomorphic. Decryption recovers `m*g` and then `m` by a discrete log
+//! search, so only small values can be decrypted.
+//!
+//! A value committed to in a separate Pedersen commitment `m*g + s*h` can be encrypted together
+//! with a `ConsistencyProof` that the ciphertext holds the committed value, linking commitments
+//! used in other proofs to ciphertexts an auditor can decrypt.
+
+use crate::commitment::commit_to_field_element;
+use crate::curve_order_elem::CurveOrderElement;
+use crate::errors::ValueError;
+use crate::group_elem::GroupElement;
+use crate::group_elem_g1::G1;
+use crate::utils::hash_msg_to_point;
+
+use rand::{CryptoRng, RngCore};
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::ops::{Add, Sub};
+
+const G_DST: &[u8] = b"AMCL_WRAPPER_TWISTED_ELGAMAL_G";
+const H_DST: &[u8] = b"AMCL_WRAPPER_TWISTED_ELGAMAL_H";
+
+/// Generators `g` and `h` whose discrete log relative to each other must be unknown
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Parameters {
+    g: G1,
+    h: G1,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct SecretKey(CurveOrderElement);
+
+/// `sk*h`
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct PublicKey(G1);
+
+/// `(r*pk, m*g + r*h)`
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Ciphertext {
+    x: G1,
+    y: G1,
+}
+
+/// Proof of knowledge of `m`, `r` and `s` with ciphertext `(r*pk, m*g + r*h)` and commitment
+/// `m*g + s*h`, i.e. that the ciphertext encrypts the committed value
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct ConsistencyProof {
+    c: CurveOrderElement,
+    z_m: CurveOrderElement,
+    z_r: CurveOrderElement,
+    z_s: CurveOrderElement,
+}
+
+/// Baby steps `j*g` for `0 < j < step` for decrypting values up to `max`
+#[derive(Clone, Debug)]
+pub struct DecryptionTable {
+    max: u64,
+    step: u64,
+    baby_steps: HashMap<G1, u64>,
+    giant_step: G1,
+}
+
+impl Parameters {
+    /// Fails for identity or equal generators
+    pub fn new(g: G1, h: G1) -> Result<Self, ValueError> {
+        if g.is_identity() || h.is_identity() || g == h {
+            return Err(ValueError::IdentityElement);
+        }
+        Ok(Self { g, h })
+    }
+
+    /// Derive both generators by hashing `label` with `hash_msg_to_point` under different tags
+    pub fn from_label(label: &[u8]) -> Self {
+        // The tags are not empty
+        Self {
+            g: hash_msg_to_point(label, G_DST).unwrap(),
+            h: hash_msg_to_point(label, H_DST).unwrap(),
+        }
+    }
+
+    pub fn g(&self) -> &G1 {
+        &self.g
+    }
+
+    pub fn h(&self) -> &G1 {
+        &self.h
+    }
+}
+
+impl SecretKey {
+    pub fn random() -> Self {
+        Self(CurveOrderElement::random())
+    }
+
+    pub fn random_using_rng<R: RngCore + CryptoRng>(rng: &mut R) -> Self {
+        Self(CurveOrderElement::random_using_rng(rng))
+    }
+
+    /// `m*g` for the encrypted `m`, `y - x/sk`
+    pub fn decrypt_to_point(&self, ct: &Ciphertext) -> G1 {
+        &ct.y - &(&ct.x * &self.0.inverse_const_time())
+    }
+
+    /// Decrypt a value not more than the table's maximum, `None` otherwise
+    pub fn decrypt(&self, ct: &Ciphertext, table: &DecryptionTable) -> Option<u64> {
+        table.discrete_log(&self.decrypt_to_point(ct))
+    }
+}
+
+impl PublicKey {
+    pub fn new(sk: &SecretKey, params: &Parameters) -> Self {
+        Self(&params.h * &sk.0)
+    }
+
+    /// Encrypt with fresh randomness and return the ciphertext and the randomness, which opens
+    /// the commitment in the ciphertext
+    pub fn encrypt(
+        &self,
+        m: &CurveOrderElement,
+        params: &Parameters,
+    ) -> (Ciphertext, CurveOrderElement) {
+        let r = CurveOrderElement::random();
+        (self.encrypt_with_randomness(m, &r, params), r)
+    }
+
+    pub fn encrypt_with_randomness(
+        &self,
+        m: &CurveOrderElement,
+        r: &CurveOrderElement,
+        params: &Parameters,
+    ) -> Ciphertext {
+        Ciphertext {
+            x: &self.0 * r,
+            y: commit_to_field_element(&params.g, &params.h, m, r),
+        }
+    }
+
+    /// Encrypt the value `m` of the commitment `m*g + s*h` with fresh randomness and prove that
+    /// the ciphertext holds the committed value. Returns the ciphertext, its randomness and the
+    /// proof.
+    pub fn encrypt_committed(
+        &self,
+        m: &CurveOrderElement,
+        s: &CurveOrderElement,
+        params: &Parameters,
+    ) -> (Ciphertext, CurveOrderElement, ConsistencyProof) {
+        let (ct, r) = self.encrypt(m, params);
+        let proof = ConsistencyProof::new(m, &r, s, &ct, self, params);
+        (ct, r, proof)
+    }
+
+    pub fn as_point(&self) -> &G1 {
+        &self.0
+    }
+}
+
+impl ConsistencyProof {
+    /// Prove that `ct`, encrypted with randomness `r`, holds the value `m` of the commitment
+    /// `m*g + s*h`
+    pub fn new(
+        m: &CurveOrderElement,
+        r: &CurveOrderElement,
+        s: &CurveOrderElement,
+        ct: &Ciphertext,
+        pk: &PublicKey,
+        params: &Parameters,
+    ) -> Self {
+        let commitment = commit_to_field_element(&params.g, &params.h, m, s);
+        let (k_m, k_r, k_s) = (
+            CurveOrderElement::random(),
+            CurveOrderElement::random(),
+            CurveOrderElement::random(),
+        );
+        let t_x = &pk.0 * &k_r;
+        let t_y = commit_to_field_element(&params.g, &params.h, &k_m, &k_r);
+        let t_c = commit_to_field_element(&params.g, &params.h, &k_m, &k_s);
+        let c = Self::challenge(ct, &commitment, pk, params, &[&t_x, &t_y, &t_c]);
+        Self {
+            z_m: k_m - &c * m,
+            z_r: k_r - &c * r,
+            z_s: k_s - &c * s,
+            c,
+        }
+    }
+
+    pub fn verify(
+        &self,
+        ct: &Ciphertext,
+        commitment: &G1,
+        pk: &PublicKey,
+        params: &Parameters,
+    ) -> bool {
+        if pk.0.is_identity() {
+            return false;
+        }
+        let t_x = pk.0.binary_scalar_mul(&ct.x, &self.z_r, &self.c);
+        let t_y =
+            commit_to_field_element(&params.g, &params.h, &self.z_m, &self.z_r) + &ct.y * &self.c;
+        let t_c = commit_to_field_element(&params.g, &params.h, &self.z_m, &self.z_s)
+            + commitment * &self.c;
+        Self::challenge(ct, commitment, pk, params, &[&t_x, &t_y, &t_c]) == self.c
+    }
+
+    fn challenge(
+        ct: &Ciphertext,
+        commitment: &G1,
+        pk: &PublicKey,
+        params: &Parameters,
+        t: &[&G1],
+    ) -> CurveOrderElement {
+        let mut bytes = b"twisted ElGamal consistency".to_vec();
+        for p in [&params.g, &params.h, &pk.0, &ct.x, &ct.y, commitment]
+            .iter()
+            .chain(t.iter())
+        {
+            bytes.extend_from_slice(&p.to_vec());
+        }
+        CurveOrderElement::from_msg_hash(&bytes)
+    }
+}
+
+impl Ciphertext {
+    /// Pedersen commitment `m*g + r*h` to the encrypted value
+    pub fn commitment(&self) -> &G1 {
+        &self.y
+    }
+
+    /// `r*pk`
+    pub fn handle(&self) -> &G1 {
+        &self.x
+    }
+
+    /// Encryption of `s*m` with randomness `s*r`
+    pub fn scaled_by(&self, s: &CurveOrderElement) -> Self {
+        Self {
+            x: &self.x * s,
+            y: &self.y * s,
+        }
+    }
+
+    /// Encryption of `m + a` with the same randomness
+    pub fn add_plaintext(&self, a: &CurveOrderElement, params: &Parameters) -> Self {
+        Self {
+            x: self.x.clone(),
+            y: &self.y + &(&params.g * a),
+        }
+    }
+}
+
+impl Add<&Ciphertext> for &Ciphertext {
+    type Output = Ciphertext;
+
+    /// Encryption of the sum of the values, both must be under the same key
+    fn add(self, other: &Ciphertext) -> Ciphertext {
+        Ciphertext {
+            x: &self.x + &other.x,
+            y: &self.y + &other.y,
+        }
+    }
+}
+
+impl Sub<&Ciphertext> for &Ciphertext {
+    type Output = Ciphertext;
+
+    /// Encryption of the difference of the values, both must be under the same key
+    fn sub(self, other: &Ciphertext) -> Ciphertext {
+        Ciphertext {
+            x: &self.x - &other.x,
+            y: &self.y - &other.y,
+        }
+    }
+}
+
+impl DecryptionTable {
+    /// Baby-step giant-step table for values in `[0, max]`, `sqrt(max)` elements in size and
+    /// `sqrt(max)` additions per decryption
+    pub fn new(params: &Parameters, max: u64) -> Self {
+        let mut step = (max as f64).sqrt() as u64 + 1;
+        while step.saturating_mul(step) <= max {
+            step += 1;
+        }
+        // The identity is handled separately as its encodings are not unique
+        let mut baby_steps = HashMap::with_capacity(step as usize);
+        let mut p = params.g.clone();
+        for j in 1..step {
+            baby_steps.insert(p.clone(), j);
+            p += &params.g;
+        }
+        Self {
+            max,
+            step,
+            baby_steps,
+            giant_step: p.negation(),
+        }
+    }
+
+    pub fn max(&self) -> u64 {
+        self.max
+    }
+
+    /// `m` with `p = m*g` and `m <= max`
+    fn discrete_log(&self, p: &G1) -> Option<u64> {
+        let mut q = p.clone();
+        for i in 0..self.step {
+            let j = if q.is_identity() {
+                Some(&0)
+            } else {
+                self.baby_steps.get(&q)
+            };
+            if let Some(j) = j {
+                let m = i * self.step + j;
+                return if m <= self.max { Some(m) } else { None };
+            }
+            q += &self.giant_step;
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_encrypt_decrypt() {
+        let params = Parameters::from_label(b"test");
+        let sk = SecretKey::random();
+        let pk = PublicKey::new(&sk, &params);
+        let table = DecryptionTable::new(&params, 1000);
+        for m in [0u64, 1, 2, 31, 32, 999, 1000].iter() {
+            let (ct, r) = pk.encrypt(&CurveOrderElement::from(*m), &params);
+            assert_eq!(sk.decrypt(&ct, &table), Some(*m));
+            assert_eq!(
+                *ct.commitment(),
+                params.g() * &CurveOrderElement::from(*m) + params.h() * &r
+            );
+        }
+        let (ct, _) = pk.encrypt(&CurveOrderElement::from(1001u64), &params);
+        assert_eq!(sk.decrypt(&ct, &table), None);
+        assert_eq!(
+            sk.decrypt_to_point(&ct),
+            params.g() * &CurveOrderElement::from(1001u64)
+        );
+        let (ct, _) = pk.encrypt(&CurveOrderElement::from(10u64), &params);
+        assert_eq!(SecretKey::random().decrypt(&ct, &table), None);
+
+        assert!(Parameters::new(G1::generator(), G1::generator()).is_err());
+        assert!(Parameters::new(G1::generator(), G1::identity()).is_err());
+    }
+
+    #[test]
+    fn test_homomorphism() {
+        let params = Parameters::from_label(b"test");
+        let sk = SecretKey::random();
+        let pk = PublicKey::new(&sk, &params);
+        let table = DecryptionTable::new(&params, 500);
+        let (a, r_a) = pk.encrypt(&CurveOrderElement::from(120u64), &params);
+        let (b, r_b) = pk.encrypt(&CurveOrderElement::from(45u64), &params);
+
+        let sum = &a + &b;
+        assert_eq!(sk.decrypt(&sum, &table), Some(165));
+        assert_eq!(
+            sum,
+            pk.encrypt_with_randomness(&CurveOrderElement::from(165u64), &(&r_a + &r_b), &params)
+        );
+        assert_eq!(sk.decrypt(&(&a - &b), &table), Some(75));
+        assert_eq!(
+            sk.decrypt(&a.scaled_by(&CurveOrderElement::from(3u64)), &table),
+            Some(360)
+        );
+        assert_eq!(
+            sk.decrypt(
+                &b.add_plaintext(&CurveOrderElement::from(5u64), &params),
+                &table
+            ),
+            Some(50)
+        );
+    }
+
+    #[test]
+    fn test_commitment_consistency() {
+        let params = Parameters::from_label(b"test");
+        let sk = SecretKey::random();
+        let pk = PublicKey::new(&sk, &params);
+        let table = DecryptionTable::new(&params, 100);
+        let m = CurveOrderElement::from(42u64);
+        let s = CurveOrderElement::random();
+        let commitment = commit_to_field_element(params.g(), params.h(), &m, &s);
+
+        let (ct, r, proof) = pk.encrypt_committed(&m, &s, &params);
+        assert_eq!(ct, pk.encrypt_with_randomness(&m, &r, &params));
+        assert_eq!(sk.decrypt(&ct, &table), Some(42));
+        assert!(proof.verify(&ct, &commitment, &pk, &params));
+
+        // Another commitment, ciphertext or key does not verify
+        let other =
+            commit_to_field_element(params.g(), params.h(), &m, &CurveOrderElement::random());
+        assert!(!proof.verify(&ct, &other, &pk, &params));
+        let (ct_other, _) = pk.encrypt(&m, &params);
+        assert!(!proof.verify(&ct_other, &commitment, &pk, &params));
+        let pk_other = PublicKey::new(&SecretKey::random(), &params);
+        assert!(!proof.verify(&ct, &commitment, &pk_other, &params));
+
+        // Encrypting another value than the committed one
+        let (ct_bad, r_bad) = pk.encrypt(&CurveOrderElement::from(43u64), &params);
+        let bad = ConsistencyProof::new(&m, &r_bad, &s, &ct_bad, &pk, &params);
+        assert!(!bad.verify(&ct_bad, &commitment, &pk, &params));
+    }
+}
@@ -0,0 +1,156 @@
+//! Curve order elements tagged with the role they play in a protocol, so APIs can demand an
+//! `Exponent`, e.g. a secret key or a blinding factor, or a `MessageScalar`, a message mapped to
+//! the scalar field, and mixing them up is a type error. Arithmetic is only defined between
+//! scalars of the same role and the tag costs nothing at runtime. Protocols can define their own
+//! roles by implementing `ScalarRole` for a marker type.
+
+use crate::curve_order_elem::CurveOrderElement;
+use crate::group_elem::GroupElement;
+use crate::utils::hash_msg_to_scalar;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::marker::PhantomData;
+use std::ops::{Add, Neg, Sub};
+
+/// Marker of the role of a `TypedScalar`
+pub trait ScalarRole: Clone + Copy + fmt::Debug + PartialEq + Eq + Send + Sync + 'static {}
+
+/// Secret keys, blinding factors and other exponents
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ExponentRole;
+
+/// Messages mapped to the scalar field
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct MessageRole;
+
+impl ScalarRole for ExponentRole {}
+impl ScalarRole for MessageRole {}
+
+/// Curve order element in the role `R`, serialized like the element
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(transparent, bound = ""))]
+pub struct TypedScalar<R: ScalarRole> {
+    value: CurveOrderElement,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    role: PhantomData<R>,
+}
+
+pub type Exponent = TypedScalar<ExponentRole>;
+pub type MessageScalar = TypedScalar<MessageRole>;
+
+impl<R: ScalarRole> TypedScalar<R> {
+    /// Tag `value` with the role `R`
+    pub fn new(value: CurveOrderElement) -> Self {
+        Self {
+            value,
+            role: PhantomData,
+        }
+    }
+
+    pub fn as_scalar(&self) -> &CurveOrderElement {
+        &self.value
+    }
+
+    pub fn into_scalar(self) -> CurveOrderElement {
+        self.value
+    }
+
+    /// `g * self`, constant time
+    pub fn scale<G: GroupElement>(&self, g: &G) -> G {
+        g.scalar_mul_const_time(&self.value)
+    }
+}
+
+impl Exponent {
+    pub fn random() -> Self {
+        Self::new(CurveOrderElement::random())
+    }
+
+    /// Inverse in constant time, zero maps to zero
+    pub fn inverse(&self) -> Self {
+        Self::new(self.value.inverse_const_time())
+    }
+}
+
+impl MessageScalar {
+    /// `msg` hashed to a scalar with `hash_msg_to_scalar`
+    pub fn from_msg(msg: &[u8], dst: &[u8]) -> Self {
+        Self::new(hash_msg_to_scalar(msg, dst))
+    }
+}
+
+impl<R: ScalarRole> From<TypedScalar<R>> for CurveOrderElement {
+    fn from(s: TypedScalar<R>) -> Self {
+        s.value
+    }
+}
+
+impl<R: ScalarRole> AsRef<CurveOrderElement> for TypedScalar<R> {
+    fn as_ref(&self) -> &CurveOrderElement {
+        &self.value
+    }
+}
+
+impl<'a, R: ScalarRole> Add<&'a TypedScalar<R>> for &'a TypedScalar<R> {
+    type Output = TypedScalar<R>;
+
+    fn add(self, other: &TypedScalar<R>) -> TypedScalar<R> {
+        TypedScalar::new(&self.value + &other.value)
+    }
+}
+
+impl<'a, R: ScalarRole> Sub<&'a TypedScalar<R>> for &'a TypedScalar<R> {
+    type Output = TypedScalar<R>;
+
+    fn sub(self, other: &TypedScalar<R>) -> TypedScalar<R> {
+        TypedScalar::new(&self.value - &other.value)
+    }
+}
+
+impl<R: ScalarRole> Neg for &TypedScalar<R> {
+    type Output = TypedScalar<R>;
+
+    fn neg(self) -> TypedScalar<R> {
+        TypedScalar::new(-&self.value)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::group_elem_g1::G1;
+
+    #[test]
+    fn test_typed_scalars() {
+        let x = Exponent::random();
+        let y = Exponent::random();
+        assert_eq!((&x + &y).as_scalar(), &(x.as_scalar() + y.as_scalar()));
+        assert_eq!((&(&x - &y) + &y), x);
+        assert!((&(-&x) + &x).as_scalar().is_zero());
+        assert_eq!(
+            x.inverse().as_scalar() * x.as_scalar(),
+            CurveOrderElement::one()
+        );
+
+        let g = G1::random();
+        assert_eq!(x.scale(&g), &g * x.as_scalar());
+        let m = MessageScalar::from_msg(b"message", b"test dst");
+        assert_eq!(m, MessageScalar::from_msg(b"message", b"test dst"));
+        assert_eq!(
+            CurveOrderElement::from(m.clone()),
+            hash_msg_to_scalar(b"message", b"test dst")
+        );
+        // `&x + &m` does not compile
+
+        #[cfg(feature = "serde")]
+        {
+            let s = serde_json::to_string(&m).unwrap();
+            assert_eq!(s, serde_json::to_string(m.as_scalar()).unwrap());
+            let back: MessageScalar = serde_json::from_str(&s).unwrap();
+            assert_eq!(back, m);
+        }
+    }
+}
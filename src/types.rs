@@ -1,14 +1,9 @@
-use super::amcl::arch::Chunk;
-use super::ECCurve::big::BIG;
-use super::ECCurve::dbig::DBIG;
-use super::ECCurve::ecp::ECP;
+use crate::backend::{Chunk, BIG, DBIG, ECP};
 #[cfg(any(feature = "bls381", feature = "bn254"))]
-use super::ECCurve::ecp2::ECP2;
-pub use super::ECCurve::fp::FP;
+use crate::backend::{ECP2, FP12};
+pub use crate::backend::FP;
 #[cfg(any(feature = "bls381", feature = "bn254"))]
-use super::ECCurve::fp12::FP12;
-#[cfg(any(feature = "bls381", feature = "bn254"))]
-pub use super::ECCurve::fp2::FP2;
+pub use crate::backend::FP2;
 
 pub type Limb = Chunk;
 pub type BigNum = BIG;
@@ -7,6 +7,9 @@ pub const GROUP_G2_SIZE: usize = (4 * MODBYTES) as usize;
 // Byte size of element in group GT
 pub const GROUP_GT_SIZE: usize = (12 * MODBYTES) as usize;
 
+// Byte size of compressed element in group GT
+pub const GROUP_GT_COMPRESSED_SIZE: usize = 6 * MODBYTES;
+
 lazy_static! {
     pub static ref GENERATOR_G2: GroupG2 = GroupG2::generator();
 }
@@ -1,12 +1,21 @@
 use std::ops::{Add, Index, IndexMut, Mul, Sub};
 
 use crate::curve_order_elem::{CurveOrderElement, CurveOrderElementVector};
+use crate::fft::EvaluationDomain;
+#[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
 use rayon::prelude::*;
-use std::cmp::max;
+use std::cmp::{max, min};
+
+/// Below this many points `evaluate_batch` evaluates each point with Horner's method
+const BATCH_EVAL_THRESHOLD: usize = 64;
+
+/// Below this length of the shorter factor, polynomials are multiplied without FFT
+const FFT_MUL_THRESHOLD: usize = 32;
 
 /// Univariate polynomial represented with coefficients in a vector. The ith element of the vector is the coefficient of the ith degree term.
-#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct UnivarPolynomial(pub CurveOrderElementVector);
 
 impl UnivarPolynomial {
@@ -79,7 +88,7 @@ impl UnivarPolynomial {
             // p(x) = a_0 + x*(a_1 + x*(a_2 + x*(a_3 + x*(a_4 + ... x*(a_{n-1} + x*a_n))))..
             // Reading coefficients from higher to lower degrees.
             let mut res = self.0[self.0.len() - 1].clone(); // a_n
-            for i in (0..=self.0.len() - 2).rev() {
+            for i in (0..self.0.len() - 1).rev() {
                 // in each iteration, multiply `res` with `x` and add the coefficient for ith degree, a_i
                 res = &self.0[i] + &(&res * x);
             }
@@ -87,6 +96,31 @@ impl UnivarPolynomial {
         }
     }
 
+    /// Evaluate polynomial at each of `points`. Many points are evaluated with a sub-product tree:
+    /// the polynomial is reduced modulo the products of `x - p_i` of halves of the points,
+    /// recursively, with FFT multiplication and Newton division. Few points are evaluated in
+    /// parallel with Horner's method.
+    pub fn evaluate_batch(&self, points: &CurveOrderElementVector) -> CurveOrderElementVector {
+        let points = points.as_slice();
+        if self.0.len() == 0 {
+            return CurveOrderElementVector::new(points.len());
+        }
+        if points.len() < BATCH_EVAL_THRESHOLD || self.0.len() < BATCH_EVAL_THRESHOLD {
+            return points.par_iter().map(|x| self.eval(x)).collect();
+        }
+        let tree = subproduct_tree(points);
+        let mut evals = vec![CurveOrderElement::zero(); points.len()];
+        reduce_down_tree(
+            self.0.as_slice().to_vec(),
+            &tree,
+            tree.len() - 1,
+            0,
+            points,
+            &mut evals,
+        );
+        evals.into()
+    }
+
     /// Divides 2 polynomials i.e. `dividend` / `divisor` using long division.
     /// Returns (quotient, remainder)
     pub fn long_division(dividend: &Self, divisor: &Self) -> (Self, Self) {
@@ -124,6 +158,22 @@ impl UnivarPolynomial {
         )
     }
 
+    /// Divides by `x - point` using synthetic division. Returns (quotient, remainder) where the
+    /// remainder is the evaluation at `point`. Cheaper than `long_division` with a degree 1 divisor.
+    pub fn divide_by_linear(&self, point: &CurveOrderElement) -> (Self, CurveOrderElement) {
+        let n = self.0.len();
+        if n == 0 {
+            return (Self(CurveOrderElementVector::new(0)), CurveOrderElement::zero());
+        }
+        let mut quotient = CurveOrderElementVector::new(n - 1);
+        let mut rem = self.0[n - 1].clone();
+        for i in (0..n - 1).rev() {
+            quotient[i] = rem.clone();
+            rem = &self.0[i] + &(&rem * point);
+        }
+        (Self(quotient), rem)
+    }
+
     /// Return product of 2 polynomials. `left` * `right`
     pub fn multiply(left: &Self, right: &Self) -> Self {
         let mut product = Self::new(left.degree() + right.degree());
@@ -239,6 +289,139 @@ impl<'a> Mul<&'a UnivarPolynomial> for &UnivarPolynomial {
     }
 }
 
+/// Levels of products of `x - p_i`, the first level the linear factors and the last the product
+/// of all. Node `j` of a level is the product of nodes `2j` and `2j+1` of the level below, an odd
+/// node out is carried up unchanged.
+fn subproduct_tree(points: &[CurveOrderElement]) -> Vec<Vec<Vec<CurveOrderElement>>> {
+    let mut levels = vec![points
+        .iter()
+        .map(|p| vec![-p, CurveOrderElement::one()])
+        .collect::<Vec<_>>()];
+    while levels[levels.len() - 1].len() > 1 {
+        let next = levels[levels.len() - 1]
+            .par_chunks(2)
+            .map(|pair| match pair {
+                [a, b] => mul_coeffs(a, b),
+                _ => pair[0].clone(),
+            })
+            .collect();
+        levels.push(next);
+    }
+    levels
+}
+
+/// Evaluate `f` at the points below node `index` of `level` by reducing it modulo the node's
+/// product. `evals` starts at the node's first point.
+fn reduce_down_tree(
+    f: Vec<CurveOrderElement>,
+    tree: &[Vec<Vec<CurveOrderElement>>],
+    level: usize,
+    index: usize,
+    points: &[CurveOrderElement],
+    evals: &mut [CurveOrderElement],
+) {
+    let f = rem_by_monic(f, &tree[level][index]);
+    let n = 1 << level;
+    let start = index * n;
+    let count = min(n, points.len() - start);
+    if count <= BATCH_EVAL_THRESHOLD {
+        // The remainder has degree less than `count`, so Horner's method is cheap
+        let f = UnivarPolynomial(f.into());
+        for i in 0..count {
+            evals[i] = f.eval(&points[start + i]);
+        }
+        return;
+    }
+    let half = n / 2;
+    let (left, right) = evals.split_at_mut(half);
+    if 2 * index + 1 < tree[level - 1].len() {
+        rayon::join(
+            || reduce_down_tree(f.clone(), tree, level - 1, 2 * index, points, left),
+            || reduce_down_tree(f.clone(), tree, level - 1, 2 * index + 1, points, right),
+        );
+    } else {
+        // Carried node
+        reduce_down_tree(f, tree, level - 1, 2 * index, points, evals);
+    }
+}
+
+fn mul_coeffs(a: &[CurveOrderElement], b: &[CurveOrderElement]) -> Vec<CurveOrderElement> {
+    if a.is_empty() || b.is_empty() {
+        return vec![];
+    }
+    let len = a.len() + b.len() - 1;
+    if min(a.len(), b.len()) >= FFT_MUL_THRESHOLD {
+        if let Ok(domain) = EvaluationDomain::new(len.next_power_of_two()) {
+            let a_evals = domain.fft(&a.into()).unwrap();
+            let b_evals = domain.fft(&b.into()).unwrap();
+            let product = a_evals.hadamard_product(&b_evals).unwrap();
+            let mut coeffs = domain.ifft(&product).unwrap().as_slice().to_vec();
+            coeffs.truncate(len);
+            return coeffs;
+        }
+    }
+    let mut product = vec![CurveOrderElement::zero(); len];
+    for (i, x) in a.iter().enumerate() {
+        for (j, y) in b.iter().enumerate() {
+            product[i + j] += x * y;
+        }
+    }
+    product
+}
+
+/// Inverse of the power series `g`, which has constant term 1, modulo `x^k` by Newton iteration
+fn inverse_series(g: &[CurveOrderElement], k: usize) -> Vec<CurveOrderElement> {
+    let mut h = vec![CurveOrderElement::one()];
+    let mut precision = 1;
+    while precision < k {
+        precision = min(2 * precision, k);
+        // h = h * (2 - g*h) mod x^precision
+        let mut e = mul_coeffs(&g[..min(g.len(), precision)], &h);
+        e.truncate(precision);
+        for c in e.iter_mut() {
+            *c = -(&*c);
+        }
+        e[0] += CurveOrderElement::from(2u64);
+        h = mul_coeffs(&h, &e);
+        h.truncate(precision);
+    }
+    h
+}
+
+/// Remainder of `f` divided by the monic `g`
+fn rem_by_monic(mut f: Vec<CurveOrderElement>, g: &[CurveOrderElement]) -> Vec<CurveOrderElement> {
+    let deg_g = g.len() - 1;
+    if f.len() <= deg_g {
+        return f;
+    }
+    let m = f.len() - 1 - deg_g;
+    if m < FFT_MUL_THRESHOLD || deg_g < FFT_MUL_THRESHOLD {
+        for i in (deg_g..f.len()).rev() {
+            let q = f[i].clone();
+            if q.is_zero() {
+                continue;
+            }
+            for j in 0..deg_g {
+                f[i - deg_g + j] -= &g[j] * &q;
+            }
+        }
+        f.truncate(deg_g);
+        return f;
+    }
+    // With reversed coefficients, quotient = rev(f) / rev(g) mod x^(m+1)
+    let f_rev: Vec<_> = f.iter().rev().take(m + 1).cloned().collect();
+    let g_rev: Vec<_> = g.iter().rev().cloned().collect();
+    let mut q = mul_coeffs(&f_rev, &inverse_series(&g_rev, m + 1));
+    q.resize(m + 1, CurveOrderElement::zero());
+    q.reverse();
+    let qg = mul_coeffs(&q, g);
+    f.truncate(deg_g);
+    for (r, x) in f.iter_mut().zip(qg) {
+        *r -= x;
+    }
+    f
+}
+
 /// Creates a new univariate polynomial from given coefficients from lower to higher degree terms
 #[macro_export]
 macro_rules! univar_polynomial {
@@ -272,6 +455,7 @@ mod tests {
         assert!(poly3.is_zero());
 
         let poly4 = UnivarPolynomial::new_constant(CurveOrderElement::from(100u64));
+        assert_eq!(poly4.eval(&CurveOrderElement::random()), CurveOrderElement::from(100u64));
         assert!(!poly4.is_zero());
         assert_eq!(poly4.degree(), 0);
         assert_eq!(poly4[0], CurveOrderElement::from(100u64));
@@ -619,6 +803,22 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_divide_by_linear() {
+        let mut rng = rand::thread_rng();
+        for _ in 0..10 {
+            let poly = UnivarPolynomial::random(rng.gen_range(1, 100));
+            let point = CurveOrderElement::random();
+            let (quotient, rem) = poly.divide_by_linear(&point);
+            assert_eq!(rem, poly.eval(&point));
+
+            let divisor = UnivarPolynomial::new_with_roots(std::slice::from_ref(&point));
+            let (q, r) = UnivarPolynomial::long_division(&poly, &divisor);
+            assert_eq!(quotient, q);
+            assert_eq!(rem, r[0]);
+        }
+    }
+
     #[test]
     fn test_random_poly_long_div_remainder() {
         // Divide 2 random polynomials and check that the quotient and remainder are correct using
@@ -788,4 +988,58 @@ mod tests {
         assert_eq!(new[7], CurveOrderElement::zero());
         assert_eq!(new[8], CurveOrderElement::from(18));
     }
+
+    #[test]
+    fn test_evaluate_batch() {
+        // Below and above the threshold, with a number of points that is not a power of 2 and
+        // polynomials of lower and higher degree than the number of points
+        for (degree, num_points) in [(5, 10), (100, 20), (300, 200), (150, 1000)] {
+            let poly = UnivarPolynomial::random(degree);
+            let points = CurveOrderElementVector::random(num_points);
+            let evals = poly.evaluate_batch(&points);
+            assert_eq!(evals.len(), num_points);
+            for (x, y) in points.iter().zip(evals.iter()) {
+                assert_eq!(poly.eval(x), *y);
+            }
+        }
+
+        // Repeated points and zero
+        let poly = UnivarPolynomial::random(80);
+        let mut points = CurveOrderElementVector::random(70);
+        points[3] = points[50].clone();
+        points[7] = CurveOrderElement::zero();
+        let evals = poly.evaluate_batch(&points);
+        assert_eq!(evals[3], evals[50]);
+        assert_eq!(evals[7], poly[0]);
+
+        assert_eq!(
+            UnivarPolynomial(CurveOrderElementVector::new(0)).evaluate_batch(&points),
+            CurveOrderElementVector::new(70)
+        );
+        assert_eq!(poly.evaluate_batch(&CurveOrderElementVector::new(0)).len(), 0);
+    }
+
+    #[test]
+    fn timing_evaluate_batch() {
+        let poly = UnivarPolynomial::random(1023);
+        let points = CurveOrderElementVector::random(1024);
+
+        let start = Instant::now();
+        let evals = poly.evaluate_batch(&points);
+        println!(
+            "Sub-product tree evaluation at {} points takes {:?}",
+            points.len(),
+            start.elapsed()
+        );
+
+        let start = Instant::now();
+        let horner: CurveOrderElementVector =
+            points.as_slice().par_iter().map(|x| poly.eval(x)).collect();
+        println!(
+            "Horner evaluation at {} points takes {:?}",
+            points.len(),
+            start.elapsed()
+        );
+        assert_eq!(evals, horner);
+    }
 }
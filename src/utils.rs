@@ -3,14 +3,24 @@ extern crate sha3;
 
 use rand::{CryptoRng, RngCore};
 
-use crate::constants::{CURVE_ORDER, FIELD_ORDER_ELEMENT_SIZE};
-use crate::types::{BigNum, DoubleBigNum};
-use amcl::rand::RAND;
+use crate::constants::{CURVE_ORDER, CURVE_ORDER_ELEMENT_SIZE, FIELD_ORDER_ELEMENT_SIZE};
+use crate::curve_order_elem::CurveOrderElement;
+use crate::errors::{EntropyError, ValueError};
+use crate::group_elem::GroupElement;
+use crate::group_elem_g1::G1;
+use crate::types::{BigNum, DoubleBigNum, Limb};
+use crate::backend::{Backend, CurrentBackend, RAND};
 
 use sha3::digest::{ExtendableOutput, Input, XofReader};
 use sha3::Shake256;
+use std::fmt;
+use zeroize::Zeroize;
 
 /// Hash message and return output of size equal to curve modulus. Uses SHAKE to hash the message.
+#[deprecated(
+    since = "0.5.0",
+    note = "Please use `hash_msg_to_scalar` or `hash_msg_to_point` instead"
+)]
 pub fn hash_msg(msg: &[u8]) -> [u8; FIELD_ORDER_ELEMENT_SIZE] {
     let mut hasher = Shake256::default();
     hasher.input(&msg);
@@ -19,25 +29,174 @@ pub fn hash_msg(msg: &[u8]) -> [u8; FIELD_ORDER_ELEMENT_SIZE] {
     h
 }
 
-pub fn get_seeded_rng_with_rng<R: RngCore + CryptoRng>(entropy_size: usize, rng: &mut R) -> RAND {
-    // initialise from at least 128 byte string of raw random entropy
-    let mut entropy = vec![0; entropy_size];
-    rng.fill_bytes(&mut entropy.as_mut_slice());
-    get_rand(entropy_size, entropy.as_slice())
+/// `msg || dst || I2OSP(len(dst), 1)`, with tags longer than 255 bytes replaced by
+/// `SHAKE256("H2C-OVERSIZE-DST-" || dst)` as in RFC 9380
+pub(crate) fn domain_separated(msg: &[u8], dst: &[u8]) -> Vec<u8> {
+    let oversize;
+    let dst = if dst.len() > 255 {
+        let mut hasher = Shake256::default();
+        hasher.input(b"H2C-OVERSIZE-DST-");
+        hasher.input(dst);
+        let mut h = [0u8; 64];
+        hasher.xof_result().read(&mut h);
+        oversize = h;
+        &oversize[..]
+    } else {
+        dst
+    };
+    [msg, dst, &[dst.len() as u8][..]].concat()
+}
+
+/// Reduce modulo the curve order with the same sequence of operations for every `d` less than
+/// `2^bits`. `DoubleBigNum::dmod` first shifts the modulus up to the size of `d`, which leaks it.
+fn reduce_ct(mut d: DoubleBigNum, bits: usize) -> BigNum {
+    d.norm();
+    let shift = bits + 1 - CURVE_ORDER.nbits();
+    let mut m = DoubleBigNum::new_scopy(&CURVE_ORDER);
+    m.shl(shift);
+    let mut dr = DoubleBigNum::new();
+    for _ in 0..shift {
+        m.shr(1);
+        dr.copy(&d);
+        dr.sub(&m);
+        dr.norm();
+        let negative = (dr.w[dr.w.len() - 1] >> (Limb::BITS - 1)) & 1;
+        d.cmove(&dr, (1 - negative) as isize);
+    }
+    BigNum::new_dcopy(&d)
+}
+
+/// Hash `msg` with domain separation tag `dst` to a curve order element. The message is expanded
+/// with SHAKE256 to twice the size of curve order elements, so the result is statistically
+/// uniform, and reduced in constant time.
+pub fn hash_msg_to_scalar(msg: &[u8], dst: &[u8]) -> CurveOrderElement {
+    let mut hasher = Shake256::default();
+    hasher.input(domain_separated(msg, dst));
+    let mut h = [0u8; 2 * CURVE_ORDER_ELEMENT_SIZE];
+    hasher.xof_result().read(&mut h);
+    let mut d = DoubleBigNum::new();
+    for b in h.iter() {
+        d.shl(8);
+        d.w[0] += *b as Limb;
+    }
+    reduce_ct(d, 8 * h.len()).into()
+}
+
+/// `len` curve order elements expanded from `seed` with domain separation tag `dst`. The domain
+/// separated seed is hashed with SHAKE256 and the output read in chunks of twice the size of
+/// curve order elements, each reduced like `hash_msg_to_scalar`. The elements do not depend on
+/// `len`, so a shorter expansion is a prefix of a longer one.
+pub fn expand_seed_to_scalars(seed: &[u8], dst: &[u8], len: usize) -> Vec<CurveOrderElement> {
+    let mut hasher = Shake256::default();
+    hasher.input(domain_separated(seed, dst));
+    let mut reader = hasher.xof_result();
+    let mut h = [0u8; 2 * CURVE_ORDER_ELEMENT_SIZE];
+    (0..len)
+        .map(|_| {
+            reader.read(&mut h);
+            let mut d = DoubleBigNum::new();
+            for b in h.iter() {
+                d.shl(8);
+                d.w[0] += *b as Limb;
+            }
+            reduce_ct(d, 8 * h.len()).into()
+        })
+        .collect()
+}
+
+/// Hash `msg` with domain separation tag `dst` to group G1. On BLS12-381 this is the
+/// `hash_to_curve` of RFC 9380 with `dst` as tag, on other curves `from_msg_hash` of the
+/// domain separated message. Fails if `dst` is empty.
+#[cfg(feature = "bls381")]
+pub fn hash_msg_to_point(msg: &[u8], dst: &[u8]) -> Result<G1, ValueError> {
+    let dst = hash2curve::DomainSeparationTag::new(dst, None, None, None)
+        .map_err(|_| ValueError::IncorrectSize(dst.len()))?;
+    Ok(G1::hash_to_curve(msg, &dst))
 }
 
-pub fn get_seeded_rng(entropy_size: usize) -> RAND {
+/// Hash `msg` with domain separation tag `dst` to group G1. On BLS12-381 this is the
+/// `hash_to_curve` of RFC 9380 with `dst` as tag, on other curves `from_msg_hash` of the
+/// domain separated message. Fails if `dst` is empty.
+// `hash_to_curve` is only implemented for BLS12-381
+#[cfg(not(feature = "bls381"))]
+#[allow(deprecated)]
+pub fn hash_msg_to_point(msg: &[u8], dst: &[u8]) -> Result<G1, ValueError> {
+    if dst.is_empty() {
+        return Err(ValueError::IncorrectSize(0));
+    }
+    Ok(G1::from_msg_hash(&domain_separated(msg, dst)))
+}
+
+/// Random number generator reading every byte directly from the operating system with
+/// `getrandom`, keeping no state in user space. Slower than `thread_rng` but there is a single
+/// place where entropy enters.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct OsEntropyRng;
+
+impl RngCore for OsEntropyRng {
+    fn next_u32(&mut self) -> u32 {
+        let mut bytes = [0u8; 4];
+        self.fill_bytes(&mut bytes);
+        u32::from_le_bytes(bytes)
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        let mut bytes = [0u8; 8];
+        self.fill_bytes(&mut bytes);
+        u64::from_le_bytes(bytes)
+    }
+
+    /// Panics if the operating system fails to provide entropy
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        if let Err(e) = self.try_fill_bytes(dest) {
+            panic!("Error getting entropy from the operating system: {}", e);
+        }
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand::Error> {
+        getrandom::getrandom(dest).map_err(rand::Error::from)
+    }
+}
+
+impl CryptoRng for OsEntropyRng {}
+
+/// Minimum number of bytes of entropy for seeding AMCL's RAND
+pub const MIN_ENTROPY_SIZE: usize = 128;
+
+/// Seed AMCL's RAND with `entropy_size` bytes taken from `rng`. Fails when fewer than
+/// `MIN_ENTROPY_SIZE` bytes are requested, when the generator reports an error or when all bytes
+/// are equal, which indicates a broken source rather than bad luck.
+pub fn get_seeded_rng_with_rng<R: RngCore + CryptoRng>(
+    entropy_size: usize,
+    rng: &mut R,
+) -> Result<RAND, EntropyError> {
+    if entropy_size < MIN_ENTROPY_SIZE {
+        return Err(EntropyError::InsufficientEntropy(
+            entropy_size,
+            MIN_ENTROPY_SIZE,
+        ));
+    }
     let mut entropy = vec![0; entropy_size];
-    let mut rng = rand::thread_rng();
-    rng.fill_bytes(&mut entropy.as_mut_slice());
-    get_rand(entropy_size, entropy.as_slice())
+    rng.try_fill_bytes(entropy.as_mut_slice())
+        .map_err(|_| EntropyError::RngFailure)?;
+    if entropy.iter().all(|b| *b == entropy[0]) {
+        return Err(EntropyError::StuckOutput);
+    }
+    let r = CurrentBackend::seeded_rng(entropy.as_slice());
+    entropy.zeroize();
+    Ok(r)
 }
 
-fn get_rand(entropy_size: usize, entropy: &[u8]) -> RAND {
-    let mut r = RAND::new();
-    r.clean();
-    r.seed(entropy_size, &entropy);
-    r
+/// Seed AMCL's RAND with `entropy_size` bytes from `thread_rng`, a CSPRNG which is itself
+/// seeded, and periodically reseeded, from the operating system. Use `get_seeded_rng_from_os` to
+/// skip the intermediate generator.
+pub fn get_seeded_rng(entropy_size: usize) -> Result<RAND, EntropyError> {
+    get_seeded_rng_with_rng(entropy_size, &mut rand::thread_rng())
+}
+
+/// Seed AMCL's RAND with `entropy_size` bytes read directly from the operating system
+pub fn get_seeded_rng_from_os(entropy_size: usize) -> Result<RAND, EntropyError> {
+    get_seeded_rng_with_rng(entropy_size, &mut OsEntropyRng)
 }
 
 /// Perform Barrett reduction given the params computed from `barrett_reduction_params`. Algorithm 14.42 from Handbook of Applied Cryptography
@@ -61,14 +220,14 @@ pub fn barrett_reduction(
     q3.shr(k + 1);
     let q3 = BigNum::new_dcopy(&q3);
 
-    // r1 = x % 2^{k+1}
+    // r1 = x % 2^{k+2}, as r = x - q3 * modulus < 3 * modulus might not fit k+1 bits
     let mut r1 = x.clone();
-    r1.mod2m(k + 1);
+    r1.mod2m(k + 2);
     let r1 = BigNum::new_dcopy(&r1);
 
-    // r2 = (q3 * modulus) % 2^{k+1}
+    // r2 = (q3 * modulus) % 2^{k+2}
     let mut r2 = BigNum::mul(&q3, modulus);
-    r2.mod2m(k + 1);
+    r2.mod2m(k + 2);
     let r2 = BigNum::new_dcopy(&r2);
 
     // if r1 > r2, r = r1 - r2 else r = r1 - r2 + v
@@ -104,13 +263,13 @@ fn __barrett_reduction__(x: &BigNum, modulus: &BigNum, k: usize, u: &BigNum, v:
     q3.shr(k + 1);
     let q3 = BigNum::new_dcopy(&q3);
 
-    // r1 = x % 2^{k+1}
+    // r1 = x % 2^{k+2}, as r = x - q3 * modulus < 3 * modulus might not fit k+1 bits
     let mut r1 = x.clone();
-    r1.mod2m(k + 1);
+    r1.mod2m(k + 2);
 
-    // r2 = (q3 * modulus) % 2^{k+1}
+    // r2 = (q3 * modulus) % 2^{k+2}
     let mut r2 = BigNum::mul(&q3, modulus);
-    r2.mod2m(k + 1);
+    r2.mod2m(k + 2);
     let r2 = BigNum::new_dcopy(&r2);
 
     // if r1 > r2, r = r1 - r2 else r = r1 - r2 + v
@@ -136,26 +295,67 @@ fn __barrett_reduction__(x: &BigNum, modulus: &BigNum, k: usize, u: &BigNum, v:
 /// For a modulus returns
 /// k = number of bits in modulus
 /// u = floor(2^2k / modulus)
-/// v = 2^(k+1)
+/// v = 2^(k+2)
 pub fn barrett_reduction_params(modulus: &BigNum) -> (usize, BigNum, BigNum) {
     let k = modulus.nbits();
 
-    // u = floor(2^2k/CURVE_ORDER)
+    // u = floor(2^2k/modulus)
     let mut u = DoubleBigNum::new();
     u.w[0] = 1;
     // `u.shl(2*k)` crashes, so perform shl(k) twice
     u.shl(k);
     u.shl(k);
     // div returns floored value
-    let u = u.div(&CURVE_ORDER);
+    let u = u.div(modulus);
 
-    // v = 2^(k+1)
+    // v = 2^(k+2)
     let mut v = BigNum::new_int(1isize);
-    v.shl(k + 1);
+    v.shl(k + 2);
 
     (k, u, v)
 }
 
+/// Number of hex characters of the encoding shown by `fmt_short` of scalars, points and GT elements
+pub const SHORT_HEX_CHARS: usize = 8;
+
+/// The first `chars` hex characters of `bytes`, followed by `..` if any were left out
+pub fn fmt_truncated_hex(bytes: &[u8], chars: usize) -> String {
+    let mut s: String = bytes.iter().map(|b| format!("{:02x}", b)).collect();
+    if chars < s.len() {
+        s.truncate(chars);
+        s.push_str("..");
+    }
+    s
+}
+
+/// Number of elements whose digests are shown by the `Debug` of vectors and lookup tables
+pub(crate) const DEBUG_DIGESTS: usize = 4;
+
+/// Write `name { len: .., digests: [..] }` with the first 4 bytes of the SHAKE256 of the encodings
+/// of the first `DEBUG_DIGESTS` elements in hex, enough to tell values apart in logs and test
+/// failures without printing every element.
+pub(crate) fn fmt_digests<I: Iterator<Item = Vec<u8>>>(
+    f: &mut fmt::Formatter,
+    name: &str,
+    len: usize,
+    encodings: I,
+) -> fmt::Result {
+    let mut digests: Vec<String> = encodings
+        .take(DEBUG_DIGESTS)
+        .map(|e| {
+            let mut hasher = Shake256::default();
+            hasher.input(&e);
+            let mut d = [0u8; 4];
+            hasher.xof_result().read(&mut d);
+            d.iter().map(|b| format!("{:02x}", b)).collect()
+        })
+        .collect();
+    if len > DEBUG_DIGESTS {
+        digests.push("..".to_string());
+    }
+    write!(f, "{} {{ len: {}, digests: [{}] }}", name, len, digests.join(", "))
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -164,11 +364,151 @@ mod test {
     use crate::group_elem::GroupElement;
     use crate::group_elem_g1::G1;
     use crate::utils::rand::Rng;
-    use crate::ECCurve::big::BIG;
-    use crate::ECCurve::ecp::ECP;
-    use crate::ECCurve::fp::FP;
+    use crate::backend::{BIG, ECP, FP};
     use std::time::Instant;
 
+    #[test]
+    fn test_barrett_reduction_moduli() {
+        // Moduli other than the curve order, some close to a power of 2 where the remainder
+        // before the final subtractions needs k+2 bits
+        let moduli = [
+            "7fffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffed",
+            "fffffffffffffffffffffffffffffffebaaedce6af48a03bbfd25e8cd0364141",
+            "7fffffffffffffffffffffffffffffff",
+            "f4243",
+        ];
+        let mut rng = get_seeded_rng(128).unwrap();
+        for m in moduli.iter() {
+            let m = BigNum::fromstring(m.to_string());
+            let (k, u, v) = barrett_reduction_params(&m);
+            let mut max = m;
+            max.dec(1);
+            let mut values = vec![(max, max)];
+            for _ in 0..200 {
+                values.push((BigNum::randomnum(&m, &mut rng), BigNum::randomnum(&m, &mut rng)));
+            }
+            for (a, b) in values {
+                let x = BigNum::mul(&a, &b);
+                let mut expected = x;
+                let expected = expected.dmod(&m);
+                assert_eq!(
+                    BigNum::comp(&barrett_reduction(&x, &m, k, &u, &v), &expected),
+                    0
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_hash_msg_to_scalar() {
+        let a = hash_msg_to_scalar(b"msg", b"dst");
+        assert_eq!(a, hash_msg_to_scalar(b"msg", b"dst"));
+        assert_ne!(a, hash_msg_to_scalar(b"msg", b"dst2"));
+        assert_ne!(a, hash_msg_to_scalar(b"msgd", b"st"));
+        assert_ne!(a, hash_msg_to_scalar(b"msg", b""));
+        let long = [7u8; 300];
+        assert_ne!(
+            hash_msg_to_scalar(b"msg", &long),
+            hash_msg_to_scalar(b"msg", &long[..299])
+        );
+
+        // Same result as the variable time reduction
+        let bits = 16 * CURVE_ORDER_ELEMENT_SIZE;
+        let from_bytes = |bytes: &[u8]| {
+            let mut d = DoubleBigNum::new();
+            for b in bytes {
+                d.shl(8);
+                d.w[0] += *b as Limb;
+            }
+            d
+        };
+        let mut inputs = vec![
+            DoubleBigNum::new(),
+            from_bytes(&[0xff; 2 * CURVE_ORDER_ELEMENT_SIZE]),
+            DoubleBigNum::new_scopy(&CURVE_ORDER),
+        ];
+        for _ in 0..20 {
+            let bytes: Vec<u8> = (0..2 * CURVE_ORDER_ELEMENT_SIZE)
+                .map(|_| rand::thread_rng().gen())
+                .collect();
+            inputs.push(from_bytes(&bytes));
+        }
+        for d in inputs {
+            let mut expected = DoubleBigNum::new_copy(&d);
+            assert_eq!(
+                BigNum::comp(&reduce_ct(d, bits), &expected.dmod(&CURVE_ORDER)),
+                0
+            );
+        }
+    }
+
+    #[test]
+    fn test_hash_msg_to_point() {
+        let p = hash_msg_to_point(b"msg", b"dst").unwrap();
+        assert!(!p.is_identity());
+        assert!(p.has_correct_order());
+        assert_eq!(p, hash_msg_to_point(b"msg", b"dst").unwrap());
+        assert_ne!(p, hash_msg_to_point(b"msg", b"dst2").unwrap());
+        assert!(hash_msg_to_point(b"msg", b"").is_err());
+    }
+
+    #[test]
+    fn test_os_entropy_rng() {
+        let mut rng = OsEntropyRng;
+        let mut a = [0u8; 64];
+        let mut b = [0u8; 64];
+        rng.fill_bytes(&mut a);
+        rng.fill_bytes(&mut b);
+        assert_ne!(a, b);
+        assert_ne!(rng.next_u64(), rng.next_u64());
+        assert!(rng.try_fill_bytes(&mut []).is_ok());
+
+        let x = CurveOrderElement::random_from_os();
+        assert_ne!(x, CurveOrderElement::random_from_os());
+        assert!(!G1::random_from_os().is_identity());
+
+        let mut r1 = get_seeded_rng_from_os(128).unwrap();
+        let mut r2 = get_seeded_rng_from_os(128).unwrap();
+        let a: Vec<_> = (0..32).map(|_| r1.getbyte()).collect();
+        let b: Vec<_> = (0..32).map(|_| r2.getbyte()).collect();
+        assert_ne!(a, b);
+    }
+
+    #[test]
+    fn test_seeded_rng_health_checks() {
+        use rand::rngs::mock::StepRng;
+        use rand::CryptoRng;
+
+        assert!(get_seeded_rng(MIN_ENTROPY_SIZE).is_ok());
+        assert!(matches!(
+            get_seeded_rng(MIN_ENTROPY_SIZE - 1),
+            Err(EntropyError::InsufficientEntropy(127, MIN_ENTROPY_SIZE))
+        ));
+
+        struct Failing;
+        impl RngCore for Failing {
+            fn next_u32(&mut self) -> u32 { 0 }
+            fn next_u64(&mut self) -> u64 { 0 }
+            fn fill_bytes(&mut self, _: &mut [u8]) {}
+            fn try_fill_bytes(&mut self, _: &mut [u8]) -> Result<(), rand::Error> {
+                Err(rand::Error::from(std::num::NonZeroU32::new(rand::Error::CUSTOM_START).unwrap()))
+            }
+        }
+        impl CryptoRng for Failing {}
+        assert!(matches!(get_seeded_rng_with_rng(MIN_ENTROPY_SIZE, &mut Failing), Err(EntropyError::RngFailure)));
+
+        // Constant output
+        struct Stuck(StepRng);
+        impl RngCore for Stuck {
+            fn next_u32(&mut self) -> u32 { self.0.next_u32() }
+            fn next_u64(&mut self) -> u64 { self.0.next_u64() }
+            fn fill_bytes(&mut self, dest: &mut [u8]) { self.0.fill_bytes(dest) }
+            fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand::Error> { self.0.try_fill_bytes(dest) }
+        }
+        impl CryptoRng for Stuck {}
+        assert!(matches!(get_seeded_rng_with_rng(MIN_ENTROPY_SIZE, &mut Stuck(StepRng::new(0, 0))), Err(EntropyError::StuckOutput)));
+    }
+
     #[test]
     fn timing_fp_big() {
         // TODO: Compare adding raw BIGs and FieldElement to check the overhead of the abstraction
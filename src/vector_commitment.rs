@@ -0,0 +1,244 @@
+//! Pointproofs vector commitments (Gorbunov, Reyzin, Wee, Zhang). A vector of `n` scalars is
+//! committed in a single G1 element, the opening of any position is a single G1 element and the
+//! openings of several positions of the same commitment aggregate into one G1 element that is
+//! verified with 2 pairings. Positions are 0-based in the API.
+
+use crate::curve_order_elem::{CurveOrderElement, CurveOrderElementVector};
+use crate::errors::ValueError;
+use crate::extension_field_gt::GT;
+use crate::group_elem::{GroupElement, GroupElementVector};
+use crate::group_elem_g1::{G1Vector, G1};
+use crate::group_elem_g2::{G2Vector, G2};
+use crate::group_params::GroupParams;
+
+use rayon::prelude::*;
+
+/// Public parameters for vectors of length up to `n` from a secret `alpha`. Holds
+/// `alpha^i*g1` for `i` in `[1, 2n]` except `n+1`, `alpha^i*g2` for `i` in `[1, n]` and
+/// `e(g1, g2)^(alpha^(n+1))`.
+#[derive(Clone, Debug)]
+pub struct VectorCommitmentParams {
+    n: usize,
+    /// `alpha^(i+1)*g1` at index `i`, the identity at index `n`
+    g1_powers: G1Vector,
+    /// `alpha^(i+1)*g2` at index `i`
+    g2_powers: G2Vector,
+    g2: G2,
+    gt_alpha: GT,
+}
+
+impl VectorCommitmentParams {
+    /// Parameters with a random `alpha` which is discarded. Whoever runs this could have kept
+    /// `alpha` and open commitments to any value.
+    pub fn new(n: usize, params: &GroupParams) -> Self {
+        Self::new_with_trapdoor(n, &CurveOrderElement::random(), params)
+    }
+
+    pub fn new_with_trapdoor(n: usize, alpha: &CurveOrderElement, params: &GroupParams) -> Self {
+        let mut powers = CurveOrderElementVector::new_vandermonde_vector(alpha, 2 * n + 1);
+        // Drop alpha^0
+        powers.remove(0);
+        let g1 = params.g1();
+        let g2 = params.g2();
+        let g1_powers = (0..2 * n)
+            .into_par_iter()
+            .map(|i| if i == n { G1::identity() } else { g1 * &powers[i] })
+            .collect();
+        let g2_powers = powers.as_slice()[..n].par_iter().map(|p| g2 * p).collect();
+        Self {
+            n,
+            g1_powers,
+            g2_powers,
+            g2: g2.clone(),
+            gt_alpha: GT::ate_pairing(g1, g2).pow(&powers[n]),
+        }
+    }
+
+    /// Maximum length of a committed vector
+    pub fn size(&self) -> usize {
+        self.n
+    }
+
+    /// `sum values[i]*alpha^(i+1)*g1`. Vectors shorter than `n` are padded with zeros.
+    pub fn commit(&self, values: &CurveOrderElementVector) -> Result<G1, ValueError> {
+        if values.len() > self.n {
+            return Err(ValueError::IncorrectSize(values.len()));
+        }
+        G1Vector::multi_scalar_mul_var_time_without_precomputation(
+            &self.g1_powers.as_slice()[..values.len()],
+            values.iter(),
+        )
+    }
+
+    /// Commitment to `values` after `values[index]` changes from `old` to `new`, without the
+    /// rest of the vector
+    pub fn update_commitment(
+        &self,
+        commitment: &G1,
+        index: usize,
+        old: &CurveOrderElement,
+        new: &CurveOrderElement,
+    ) -> Result<G1, ValueError> {
+        if index >= self.n {
+            return Err(ValueError::OutOfRange(index));
+        }
+        Ok(commitment + &self.g1_powers[index] * &(new - old))
+    }
+
+    /// Opening of position `index`, `sum_(j != index) values[j]*alpha^(n+1-index+j)*g1`
+    pub fn open(&self, values: &CurveOrderElementVector, index: usize) -> Result<G1, ValueError> {
+        if values.len() > self.n {
+            return Err(ValueError::IncorrectSize(values.len()));
+        }
+        if index >= self.n {
+            return Err(ValueError::OutOfRange(index));
+        }
+        // alpha^(n+1-(index+1)+(j+1)) is at index n-index+j of g1_powers, which is the identity
+        // for j == index so that term does not need to be skipped
+        let start = self.n - index;
+        G1Vector::multi_scalar_mul_var_time_without_precomputation(
+            &self.g1_powers.as_slice()[start..start + values.len()],
+            values.iter(),
+        )
+    }
+
+    /// Check `e(commitment, alpha^(n-index)*g2) == e(proof, g2) * e(g1, g2)^(alpha^(n+1)*value)`
+    pub fn verify(&self, commitment: &G1, index: usize, value: &CurveOrderElement, proof: &G1) -> bool {
+        if index >= self.n {
+            return false;
+        }
+        let lhs = GT::ate_2_pairing(
+            commitment,
+            &self.g2_powers[self.n - 1 - index],
+            &proof.negation(),
+            &self.g2,
+        );
+        lhs == self.gt_alpha.pow(value)
+    }
+
+    /// Combine the openings `proofs` of positions `indices` with values `values` of the same
+    /// commitment into a single proof
+    pub fn aggregate(
+        &self,
+        commitment: &G1,
+        indices: &[usize],
+        values: &[CurveOrderElement],
+        proofs: &[G1],
+    ) -> Result<G1, ValueError> {
+        check_vector_size_for_equality!(indices, values)?;
+        check_vector_size_for_equality!(indices, proofs)?;
+        let t = self.aggregation_coefficients(commitment, indices, values);
+        G1Vector::multi_scalar_mul_var_time_without_precomputation(proofs, t.iter())
+    }
+
+    /// Verify a proof created by `aggregate`. Costs 2 pairings regardless of the number of
+    /// positions.
+    pub fn verify_aggregated(
+        &self,
+        commitment: &G1,
+        indices: &[usize],
+        values: &[CurveOrderElement],
+        proof: &G1,
+    ) -> bool {
+        if indices.len() != values.len() || indices.iter().any(|i| *i >= self.n) {
+            return false;
+        }
+        let t = self.aggregation_coefficients(commitment, indices, values);
+        let g2s: Vec<&G2> = indices
+            .iter()
+            .map(|i| &self.g2_powers[self.n - 1 - i])
+            .collect();
+        let g2_sum = match G2Vector::multi_scalar_mul_var_time_without_precomputation(g2s, t.iter()) {
+            Ok(g) => g,
+            Err(_) => return false,
+        };
+        let exp = values
+            .iter()
+            .zip(t.iter())
+            .fold(CurveOrderElement::zero(), |acc, (v, t_i)| acc + v * t_i);
+        let lhs = GT::ate_2_pairing(commitment, &g2_sum, &proof.negation(), &self.g2);
+        lhs == self.gt_alpha.pow(&exp)
+    }
+
+    /// `t_i = H(commitment, indices, values, i)`
+    fn aggregation_coefficients(
+        &self,
+        commitment: &G1,
+        indices: &[usize],
+        values: &[CurveOrderElement],
+    ) -> Vec<CurveOrderElement> {
+        let mut bytes = commitment.to_vec();
+        for (i, v) in indices.iter().zip(values.iter()) {
+            bytes.extend_from_slice(&(*i as u64).to_be_bytes());
+            bytes.extend_from_slice(&v.to_compressed_bytes());
+        }
+        indices
+            .par_iter()
+            .map(|i| {
+                let mut b = bytes.clone();
+                b.extend_from_slice(&(*i as u64).to_be_bytes());
+                CurveOrderElement::from_msg_hash(&b)
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_open_verify() {
+        let n = 8;
+        let params = VectorCommitmentParams::new(n, &GroupParams::default());
+        let values = CurveOrderElementVector::random(n);
+        let comm = params.commit(&values).unwrap();
+        for i in 0..n {
+            let proof = params.open(&values, i).unwrap();
+            assert!(params.verify(&comm, i, &values[i], &proof));
+            assert!(!params.verify(&comm, i, &CurveOrderElement::random(), &proof));
+            assert!(!params.verify(&comm, (i + 1) % n, &values[i], &proof));
+        }
+        assert!(params.open(&values, n).is_err());
+        assert!(params.commit(&CurveOrderElementVector::random(n + 1)).is_err());
+
+        // Shorter vectors are padded with zeros
+        let short = CurveOrderElementVector::random(3);
+        let comm = params.commit(&short).unwrap();
+        let proof = params.open(&short, 5).unwrap();
+        assert!(params.verify(&comm, 5, &CurveOrderElement::zero(), &proof));
+    }
+
+    #[test]
+    fn test_update_commitment() {
+        let params = VectorCommitmentParams::new(4, &GroupParams::default());
+        let mut values = CurveOrderElementVector::random(4);
+        let comm = params.commit(&values).unwrap();
+        let new = CurveOrderElement::random();
+        let updated = params.update_commitment(&comm, 2, &values[2], &new).unwrap();
+        values[2] = new;
+        assert_eq!(updated, params.commit(&values).unwrap());
+        assert!(params.update_commitment(&comm, 4, &values[0], &values[1]).is_err());
+    }
+
+    #[test]
+    fn test_aggregate() {
+        let n = 10;
+        let params = VectorCommitmentParams::new(n, &GroupParams::default());
+        let values = CurveOrderElementVector::random(n);
+        let comm = params.commit(&values).unwrap();
+
+        let indices = vec![1, 4, 5, 9];
+        let vals: Vec<_> = indices.iter().map(|i| values[*i].clone()).collect();
+        let proofs: Vec<_> = indices.iter().map(|i| params.open(&values, *i).unwrap()).collect();
+        let proof = params.aggregate(&comm, &indices, &vals, &proofs).unwrap();
+        assert!(params.verify_aggregated(&comm, &indices, &vals, &proof));
+
+        let mut bad_vals = vals.clone();
+        bad_vals[2] = CurveOrderElement::random();
+        assert!(!params.verify_aggregated(&comm, &indices, &bad_vals, &proof));
+        assert!(!params.verify_aggregated(&comm, &indices[..3], &vals[..3], &proof));
+        assert!(!params.verify_aggregated(&comm, &[1, 4, 5, n], &vals, &proof));
+        assert!(params.aggregate(&comm, &indices, &vals[..3], &proofs).is_err());
+    }
+}
@@ -0,0 +1,194 @@
+//! Long running operations done in bounded steps so they can be interleaved with other work,
+//! e.g. awaiting between steps on an async executor, and cancelled. Each operation is a
+//! `ChunkedWork`, an iterator doing one step per `next` and yielding its progress as
+//! `(done, total)`, where `finish` does the remaining steps and returns the output.
+//!
+//! ```ignore
+//! let mut msm = MsmChunks::var_time(points.as_slice(), scalars.as_slice(), 1024)?;
+//! while msm.next().is_some() {
+//!     tokio::task::yield_now().await;
+//! }
+//! let result = msm.finish();
+//! ```
+//!
+//! Steps are parallelized like the operations they split, and splitting costs little: a step of
+//! `MsmChunks` is an MSM of `chunk_size` terms, of `GT::ate_multi_pairing_chunked` a shared Miller
+//! loop of `chunk_size` pairs with a single final exponentiation at the end and of
+//! `EvaluationDomain::fft_chunked` `chunk_size` butterflies.
+
+use crate::curve_order_elem::CurveOrderElement;
+use crate::errors::ValueError;
+use crate::group_elem::GroupElement;
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+/// Shared flag to cancel `ChunkedWork` from another thread or task
+#[derive(Clone, Debug, Default)]
+pub struct CancellationToken(Arc<AtomicBool>);
+
+impl CancellationToken {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn cancel(&self) {
+        self.0.store(true, Ordering::SeqCst)
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::SeqCst)
+    }
+}
+
+/// Operation done one step per call of `next`, yielding `(done, total)` after each step
+pub trait ChunkedWork: Iterator<Item = (usize, usize)> + Sized {
+    type Output;
+
+    /// Do the remaining steps and return the output
+    fn finish(self) -> Self::Output;
+
+    /// Do all steps, checking `token` before each. Fails with `ValueError::Cancelled` if it is
+    /// cancelled before the last step.
+    fn run_cancellable(mut self, token: &CancellationToken) -> Result<Self::Output, ValueError> {
+        loop {
+            if token.is_cancelled() {
+                return Err(ValueError::Cancelled);
+            }
+            if self.next().is_none() {
+                return Ok(self.finish());
+            }
+        }
+    }
+}
+
+pub(crate) fn check_chunk_size(chunk_size: usize) -> Result<(), ValueError> {
+    if chunk_size == 0 {
+        return Err(ValueError::IncorrectSize(0));
+    }
+    Ok(())
+}
+
+/// Multi-scalar multiplication `chunk_size` terms at a time
+pub struct MsmChunks<'a, G: GroupElement> {
+    points: &'a [G],
+    scalars: &'a [CurveOrderElement],
+    chunk_size: usize,
+    done: usize,
+    accum: G,
+    msm: fn(&[G], &[CurveOrderElement]) -> Result<G, ValueError>,
+}
+
+impl<'a, G: GroupElement> MsmChunks<'a, G> {
+    /// Steps of `GroupElement::msm`, constant time
+    pub fn const_time(
+        points: &'a [G],
+        scalars: &'a [CurveOrderElement],
+        chunk_size: usize,
+    ) -> Result<Self, ValueError> {
+        Self::with_msm(points, scalars, chunk_size, G::msm)
+    }
+
+    /// Steps of `GroupElement::msm_var_time`, only for public scalars
+    pub fn var_time(
+        points: &'a [G],
+        scalars: &'a [CurveOrderElement],
+        chunk_size: usize,
+    ) -> Result<Self, ValueError> {
+        Self::with_msm(points, scalars, chunk_size, G::msm_var_time)
+    }
+
+    fn with_msm(
+        points: &'a [G],
+        scalars: &'a [CurveOrderElement],
+        chunk_size: usize,
+        msm: fn(&[G], &[CurveOrderElement]) -> Result<G, ValueError>,
+    ) -> Result<Self, ValueError> {
+        check_vector_size_for_equality!(points, scalars)?;
+        check_chunk_size(chunk_size)?;
+        Ok(Self {
+            points,
+            scalars,
+            chunk_size,
+            done: 0,
+            accum: G::identity(),
+            msm,
+        })
+    }
+}
+
+impl<'a, G: GroupElement> Iterator for MsmChunks<'a, G> {
+    type Item = (usize, usize);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let total = self.points.len();
+        if self.done == total {
+            return None;
+        }
+        let end = total.min(self.done + self.chunk_size);
+        // The sizes were checked when created
+        let r = (self.msm)(&self.points[self.done..end], &self.scalars[self.done..end]).unwrap();
+        self.accum.add_assign_(&r);
+        self.done = end;
+        Some((end, total))
+    }
+}
+
+impl<'a, G: GroupElement> ChunkedWork for MsmChunks<'a, G> {
+    type Output = G;
+
+    fn finish(mut self) -> G {
+        while self.next().is_some() {}
+        self.accum
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::curve_order_elem::CurveOrderElementVector;
+    use crate::group_elem::GroupElementVector;
+    use crate::group_elem_g1::{G1Vector, G1};
+
+    #[test]
+    fn test_msm_chunks() {
+        let points = G1Vector::random(50);
+        let scalars = CurveOrderElementVector::random(50);
+        let expected = points.multi_scalar_mul_var_time(scalars.iter()).unwrap();
+
+        let mut msm = MsmChunks::var_time(points.as_slice(), scalars.as_slice(), 16).unwrap();
+        let progress: Vec<_> = msm.by_ref().collect();
+        assert_eq!(progress, vec![(16, 50), (32, 50), (48, 50), (50, 50)]);
+        assert_eq!(msm.finish(), expected);
+
+        let msm = MsmChunks::const_time(points.as_slice(), scalars.as_slice(), 7).unwrap();
+        assert_eq!(msm.finish(), expected);
+        let msm = MsmChunks::<G1>::var_time(&[], &[], 7).unwrap();
+        assert!(msm.finish().is_identity());
+
+        assert!(MsmChunks::var_time(points.as_slice(), &scalars.as_slice()[1..], 16).is_err());
+        assert!(MsmChunks::var_time(points.as_slice(), scalars.as_slice(), 0).is_err());
+    }
+
+    #[test]
+    fn test_cancellation() {
+        let points = G1Vector::random(20);
+        let scalars = CurveOrderElementVector::random(20);
+        let token = CancellationToken::new();
+        let msm = MsmChunks::var_time(points.as_slice(), scalars.as_slice(), 4).unwrap();
+        assert_eq!(
+            msm.run_cancellable(&token).unwrap(),
+            points.multi_scalar_mul_var_time(scalars.iter()).unwrap()
+        );
+
+        // Cancelled from a clone, e.g. held by another task
+        let mut msm = MsmChunks::var_time(points.as_slice(), scalars.as_slice(), 4).unwrap();
+        msm.next();
+        token.clone().cancel();
+        assert!(token.is_cancelled());
+        assert!(matches!(
+            msm.run_cancellable(&token),
+            Err(ValueError::Cancelled)
+        ));
+    }
+}